@@ -0,0 +1,18 @@
+-- Per-family supermarket aisle order for shopping lists
+CREATE TABLE aisle_orders (
+    family_id TEXT PRIMARY KEY,
+    aisles TEXT NOT NULL,                        -- JSON array of aisle names in walking order
+    ingredient_aisles TEXT NOT NULL DEFAULT '{}', -- JSON object: ingredient name -> aisle
+    updated_by TEXT,
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+-- Public links to a shopping list built from a set of recipes
+CREATE TABLE shopping_list_links (
+    token TEXT PRIMARY KEY,
+    recipe_ids TEXT NOT NULL,                    -- JSON array of recipe IDs
+    family_id TEXT,
+    created_by TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    expires_at TEXT NOT NULL
+);
@@ -0,0 +1,2 @@
+-- Where an imported recipe came from (web page URL or share link)
+ALTER TABLE recipes ADD COLUMN source_url TEXT;
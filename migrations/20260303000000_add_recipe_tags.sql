@@ -0,0 +1,7 @@
+CREATE TABLE recipe_tags (
+    recipe_id TEXT NOT NULL REFERENCES recipes(id) ON DELETE CASCADE,
+    tag TEXT NOT NULL,
+    PRIMARY KEY (recipe_id, tag)
+);
+
+CREATE INDEX idx_recipe_tags_tag ON recipe_tags(LOWER(tag));
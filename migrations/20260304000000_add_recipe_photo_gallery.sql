@@ -0,0 +1,20 @@
+-- Multiple photos per recipe. recipes.photo_filename now names the cover,
+-- which is always one of the recipe's photos.
+-- Photos link to a step by position because steps are recreated on edit.
+CREATE TABLE recipe_photos (
+    id TEXT PRIMARY KEY,
+    recipe_id TEXT NOT NULL REFERENCES recipes(id) ON DELETE CASCADE,
+    filename TEXT NOT NULL UNIQUE,
+    position INTEGER NOT NULL,
+    caption TEXT,
+    step_position INTEGER,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX idx_recipe_photos_recipe_id ON recipe_photos(recipe_id, position);
+
+-- Existing single photos become each recipe's first (cover) photo
+INSERT INTO recipe_photos (id, recipe_id, filename, position)
+SELECT lower(hex(randomblob(16))), id, photo_filename, 0
+FROM recipes
+WHERE photo_filename IS NOT NULL;
@@ -0,0 +1,4 @@
+-- Source scans: the handwritten card or cookbook page a recipe was
+-- transcribed from. They are stored like gallery photos but are never the
+-- cover and are listed separately.
+ALTER TABLE recipe_photos ADD COLUMN source_scan INTEGER NOT NULL DEFAULT 0;
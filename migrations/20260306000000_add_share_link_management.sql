@@ -0,0 +1,22 @@
+-- Share link management: links may never expire (NULL expires_at), may be
+-- limited to a number of page views, and record how often they were opened.
+-- SQLite can't drop NOT NULL from a column, so the table is rebuilt.
+CREATE TABLE share_links_new (
+    token TEXT PRIMARY KEY,
+    recipe_id TEXT NOT NULL REFERENCES recipes(id) ON DELETE CASCADE,
+    created_by TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    expires_at TEXT,
+    max_views INTEGER,
+    view_count INTEGER NOT NULL DEFAULT 0,
+    last_accessed_at TEXT
+);
+
+INSERT INTO share_links_new (token, recipe_id, created_by, created_at, expires_at)
+SELECT token, recipe_id, created_by, created_at, expires_at
+FROM share_links;
+
+DROP TABLE share_links;
+ALTER TABLE share_links_new RENAME TO share_links;
+
+CREATE INDEX idx_share_links_recipe_id ON share_links(recipe_id);
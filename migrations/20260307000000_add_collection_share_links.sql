@@ -0,0 +1,36 @@
+-- Share links can point at a set of recipes: everything with a tag, a
+-- hand-picked list or a meal plan. Such links have no recipe_id; their
+-- recipes are listed in share_link_recipes, in page order.
+CREATE TABLE share_links_new (
+    token TEXT PRIMARY KEY,
+    recipe_id TEXT REFERENCES recipes(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL DEFAULT 'recipe',
+    title TEXT,
+    guest_count INTEGER,
+    created_by TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    expires_at TEXT,
+    max_views INTEGER,
+    view_count INTEGER NOT NULL DEFAULT 0,
+    last_accessed_at TEXT
+);
+
+INSERT INTO share_links_new (token, recipe_id, created_by, created_at, expires_at, max_views, view_count, last_accessed_at)
+SELECT token, recipe_id, created_by, created_at, expires_at, max_views, view_count, last_accessed_at
+FROM share_links;
+
+DROP TABLE share_links;
+ALTER TABLE share_links_new RENAME TO share_links;
+
+CREATE INDEX idx_share_links_recipe_id ON share_links(recipe_id);
+
+CREATE TABLE share_link_recipes (
+    token TEXT NOT NULL REFERENCES share_links(token) ON DELETE CASCADE,
+    recipe_id TEXT NOT NULL REFERENCES recipes(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    -- Meal plan role, e.g. "centrepiece" or "side"
+    role TEXT,
+    PRIMARY KEY (token, recipe_id)
+);
+
+CREATE INDEX idx_share_link_recipes_recipe_id ON share_link_recipes(recipe_id);
@@ -0,0 +1,3 @@
+-- Who a recipe saved from a share link came from: the original's author,
+-- or the host of the other Recipe Vault it was fetched from
+ALTER TABLE recipes ADD COLUMN shared_by TEXT;
@@ -0,0 +1,13 @@
+-- Share links can require a passphrase (argon2 hash) and be single use:
+-- the page is shown once, then the link expires after a short grace period
+-- for its photos to load.
+ALTER TABLE share_links ADD COLUMN passphrase_hash TEXT;
+ALTER TABLE share_links ADD COLUMN single_use INTEGER NOT NULL DEFAULT 0;
+
+-- Wrong passphrases, for rate limiting guesses per link
+CREATE TABLE share_link_attempts (
+    token TEXT NOT NULL REFERENCES share_links(token) ON DELETE CASCADE,
+    attempted_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX idx_share_link_attempts_token ON share_link_attempts(token, attempted_at);
@@ -0,0 +1,33 @@
+-- Families move from families.yaml into the database so they can be managed
+-- without a restart. `id` is the family key (as in families.yaml), which
+-- aisle orders and shopping list links already refer to.
+CREATE TABLE families (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+-- An email belongs to at most one family
+CREATE TABLE family_members (
+    email TEXT PRIMARY KEY,
+    family_id TEXT NOT NULL REFERENCES families(id) ON DELETE CASCADE,
+    added_by TEXT,
+    added_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX idx_family_members_family_id ON family_members(family_id);
+
+-- Single-use links that add whoever opens them to a family. `email`, when
+-- set, restricts the invitation to that address.
+CREATE TABLE family_invitations (
+    token TEXT PRIMARY KEY,
+    family_id TEXT NOT NULL REFERENCES families(id) ON DELETE CASCADE,
+    email TEXT,
+    created_by TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    expires_at TEXT NOT NULL,
+    accepted_by TEXT,
+    accepted_at TEXT
+);
+
+CREATE INDEX idx_family_invitations_family_id ON family_invitations(family_id);
@@ -0,0 +1,12 @@
+-- Recipes and share links belong to a family rather than to whoever created
+-- them, so they stay put when a member leaves, changes email or moves to
+-- another family. `created_by` stays as authorship.
+--
+-- Existing rows are filled in at startup by `queries::assign_family_ids`,
+-- since families.yaml is only imported into `family_members` after the
+-- migrations have run.
+ALTER TABLE recipes ADD COLUMN family_id TEXT;
+ALTER TABLE share_links ADD COLUMN family_id TEXT;
+
+CREATE INDEX idx_recipes_family_id ON recipes(family_id);
+CREATE INDEX idx_share_links_family_id ON share_links(family_id);
@@ -0,0 +1,18 @@
+-- Per-user API keys. The key itself is only shown once; `key_hash` is its
+-- SHA-256 and `prefix` its first characters, so people can tell keys apart.
+-- The key's owner (`email`) is the identity every request made with it gets.
+CREATE TABLE api_keys (
+    id TEXT PRIMARY KEY,
+    key_hash TEXT NOT NULL UNIQUE,
+    prefix TEXT NOT NULL,
+    label TEXT NOT NULL,
+    email TEXT NOT NULL,
+    scope TEXT NOT NULL DEFAULT 'read_write',
+    created_by TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    expires_at TEXT,
+    last_used_at TEXT,
+    revoked_at TEXT
+);
+
+CREATE INDEX idx_api_keys_email ON api_keys(email);
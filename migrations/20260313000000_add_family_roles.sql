@@ -0,0 +1,7 @@
+-- Roles within a family: owners manage members and can delete anything,
+-- editors delete only what they created, viewers only read. Everyone who
+-- is already a member keeps full control as an owner; people added from
+-- now on join as editors unless given another role.
+ALTER TABLE family_members ADD COLUMN role TEXT NOT NULL DEFAULT 'editor';
+
+UPDATE family_members SET role = 'owner';
@@ -0,0 +1,8 @@
+-- LLM calls made on behalf of each family, one row per UTC day, for the
+-- daily quota (LLM_DAILY_CALLS_PER_FAMILY).
+CREATE TABLE llm_usage (
+    family_id TEXT NOT NULL,
+    day TEXT NOT NULL,
+    calls INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (family_id, day)
+);
@@ -0,0 +1,4 @@
+-- Keys minted for a user's chat agent while it runs. They act as that user
+-- like any personal key, but are managed by the server: hidden from key
+-- listings and revoked when the agent stops (or at the next startup).
+ALTER TABLE api_keys ADD COLUMN chat_agent INTEGER NOT NULL DEFAULT 0;
@@ -1,4 +1,4 @@
-use crate::ai::llm::{ContentBlock, LlmError, LlmProvider, LlmResponse, Message, ToolCall, ToolDefinition, ToolResult, tools};
+use crate::ai::llm::{ContentBlock, ImageSource, LlmError, LlmProvider, LlmResponse, Message, ToolCall, ToolDefinition, ToolResult, tools};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::process::Stdio;
@@ -54,6 +54,18 @@ fn filter_meal_plan_recipes(
     (resolved, dropped_count, false)
 }
 
+/// The most recent image the user shared in a conversation, which
+/// attach_source_scan keeps with the recipe transcribed from it
+fn latest_user_image(conversation: &[Message]) -> Option<&ImageSource> {
+    conversation.iter().rev().find_map(|message| match message {
+        Message::User { content } => content.iter().rev().find_map(|block| match block {
+            ContentBlock::Image { source } => Some(source),
+            _ => None,
+        }),
+        _ => None,
+    })
+}
+
 #[derive(Debug, Error)]
 pub enum AiError {
     #[error("LLM error: {0}")]
@@ -153,6 +165,7 @@ impl AiAgent {
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::inherit())
+            .kill_on_drop(true)
             .spawn()?;
 
         Ok(McpProcess {
@@ -487,7 +500,12 @@ impl AiAgent {
     /// If the tool is display_recipe, returns the recipe_id for the chat handler to emit SSE.
     /// If the tool is start_timer, returns (duration_minutes, label) for the chat handler to emit SSE.
     /// If the tool is display_meal_plan, returns MealArtifactData for the chat handler to emit SSE.
-    async fn execute_tool(&self, tool_call: &ToolCall) -> Result<(String, Option<String>, Option<(f64, String)>, Option<MealArtifactData>), AiError> {
+    /// If the tool is attach_source_scan, the conversation's latest image is passed to the MCP server.
+    async fn execute_tool(
+        &self,
+        tool_call: &ToolCall,
+        source_image: Option<&ImageSource>,
+    ) -> Result<(String, Option<String>, Option<(f64, String)>, Option<MealArtifactData>), AiError> {
         // Handle native tools (not MCP)
         if tool_call.name == "display_recipe" {
             tracing::info!("Tool call detected: display_recipe with args: {:?}", tool_call.arguments);
@@ -555,13 +573,22 @@ impl AiAgent {
         let server_name = server_name.clone();
         drop(registry);
 
+        // The image is too large for the model to repeat, so it is added here
+        let mut arguments = tool_call.arguments.clone();
+        if tool_call.name == "attach_source_scan"
+            && let (Some(image), Some(args)) = (source_image, arguments.as_object_mut())
+        {
+            args.insert("image_data".to_string(), image.data.clone().into());
+            args.insert("media_type".to_string(), image.media_type.clone().into());
+        }
+
         let result = self
             .call_mcp_server(
                 &server_name,
                 "tools/call",
                 serde_json::json!({
                     "name": tool_call.name,
-                    "arguments": tool_call.arguments
+                    "arguments": arguments
                 }),
             )
             .await?;
@@ -693,7 +720,7 @@ impl AiAgent {
                     )
                 } else {
                     tool_uses.push(call.name.clone());
-                    let result = self.execute_tool(call).await;
+                    let result = self.execute_tool(call, latest_user_image(conversation)).await;
                     match result {
                         Ok((text, maybe_recipe_id, maybe_timer_data, maybe_meal_plan)) => {
                             if let Some(rid) = maybe_recipe_id {
@@ -859,4 +886,26 @@ mod tests {
         let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed["guest_count"], serde_json::Value::Null);
     }
+
+    #[test]
+    fn test_latest_user_image_finds_most_recent_image() {
+        let image = |data: &str| ContentBlock::Image {
+            source: ImageSource {
+                source_type: "base64".to_string(),
+                media_type: "image/jpeg".to_string(),
+                data: data.to_string(),
+            },
+        };
+        let text = |text: &str| ContentBlock::Text { text: text.to_string() };
+
+        assert!(latest_user_image(&[Message::User { content: vec![text("hi")] }]).is_none());
+
+        let conversation = vec![
+            Message::User { content: vec![text("old card"), image("first")] },
+            Message::User { content: vec![text("grandma's card"), image("second")] },
+            Message::Assistant { content: Some("Here is the recipe".to_string()), tool_calls: None },
+            Message::User { content: vec![text("save it")] },
+        ];
+        assert_eq!(latest_user_image(&conversation).unwrap().data, "second");
+    }
 }
@@ -190,10 +190,13 @@ mod tests {
             servings: Some(4),
             difficulty: None,
             photo_filename: None,
+            source_url: None,
+            shared_by: None,
             created_at: "2024-01-01T00:00:00Z".to_string(),
             updated_at: "2024-01-01T00:00:00Z".to_string(),
             created_by: Some("test@example.com".to_string()),
             updated_by: Some("test@example.com".to_string()),
+            family_id: None,
         };
 
         let ingredients = vec![
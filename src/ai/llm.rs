@@ -159,7 +159,7 @@ impl LlmProvider {
             provider_type,
             api_key,
             model,
-            client: http_client.unwrap_or_else(reqwest::Client::new),
+            client: http_client.unwrap_or_default(),
             mock_recipe_id: None,
         }
     }
@@ -221,41 +221,37 @@ impl LlmProvider {
             // Step 1: list_recipes result (JSON with "recipes" key) → dispatch display_meal_plan
             // Step 2: display_meal_plan result → return final text
             if lower.contains("meal") && lower.contains("plan") {
-                if let Some(first_result) = tool_results.first() {
-                    if !first_result.is_error {
-                        if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&first_result.content) {
-                            if let Some(recipes) = parsed.get("recipes").and_then(|v| v.as_array()) {
-                                if !recipes.is_empty() {
-                                    let centrepiece_id = recipes[0]
-                                        .get("recipe_id")
-                                        .and_then(|v| v.as_str())
-                                        .unwrap_or("")
-                                        .to_string();
-                                    let mut recipe_entries = vec![
-                                        serde_json::json!({"recipe_id": centrepiece_id, "role": "centrepiece"})
-                                    ];
-                                    if recipes.len() >= 2 {
-                                        let side_id = recipes[1]
-                                            .get("recipe_id")
-                                            .and_then(|v| v.as_str())
-                                            .unwrap_or("")
-                                            .to_string();
-                                        recipe_entries.push(serde_json::json!({"recipe_id": side_id, "role": "side"}));
-                                    }
-                                    return LlmResponse::ToolUse(vec![ToolCall {
-                                        id: "toolu_mock_meal_plan".to_string(),
-                                        name: "display_meal_plan".to_string(),
-                                        arguments: serde_json::json!({
-                                            "title": "Mock Dinner Party",
-                                            "guest_count": 4,
-                                            "recipes": recipe_entries
-                                        }),
-                                        thought_signature: None,
-                                    }]);
-                                }
-                            }
-                        }
+                if let Some(first_result) = tool_results.first()
+                    && !first_result.is_error
+                    && let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&first_result.content)
+                    && let Some(recipes) = parsed.get("recipes").and_then(|v| v.as_array())
+                    && !recipes.is_empty() {
+                    let centrepiece_id = recipes[0]
+                        .get("recipe_id")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("")
+                        .to_string();
+                    let mut recipe_entries = vec![
+                        serde_json::json!({"recipe_id": centrepiece_id, "role": "centrepiece"})
+                    ];
+                    if recipes.len() >= 2 {
+                        let side_id = recipes[1]
+                            .get("recipe_id")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("")
+                            .to_string();
+                        recipe_entries.push(serde_json::json!({"recipe_id": side_id, "role": "side"}));
                     }
+                    return LlmResponse::ToolUse(vec![ToolCall {
+                        id: "toolu_mock_meal_plan".to_string(),
+                        name: "display_meal_plan".to_string(),
+                        arguments: serde_json::json!({
+                            "title": "Mock Dinner Party",
+                            "guest_count": 4,
+                            "recipes": recipe_entries
+                        }),
+                        thought_signature: None,
+                    }]);
                 }
                 // display_meal_plan result is back, or recipe list was empty
                 return LlmResponse::Text("I've assembled your meal plan for you!".to_string());
@@ -303,6 +299,17 @@ impl LlmProvider {
         if lower_message.contains("difficulty") && lower_message.contains("rating") {
             // Difficulty assessment query - return a valid rating (3 = medium)
             LlmResponse::Text("3".to_string())
+        } else if lower_message.contains("extract the recipe") && lower_message.contains("web page") {
+            // URL import fallback for pages without schema.org data
+            LlmResponse::Text(
+                serde_json::json!({
+                    "title": "Mock Extracted Recipe",
+                    "servings": 2,
+                    "ingredients": [{"name": "flour", "quantity": 100.0, "unit": "g"}],
+                    "steps": [{"instruction": "Mix everything together."}]
+                })
+                .to_string(),
+            )
         } else if lower_message.contains("meal") && lower_message.contains("plan") {
             // Meal plan flow: first list recipes to get their real IDs from the DB,
             // then on the next call (after Tool result) dispatch display_meal_plan.
@@ -2,7 +2,9 @@ mod client;
 pub mod difficulty_assessment;
 pub mod llm;
 pub mod prompts;
+pub mod recipe_extraction;
 
 pub use client::{AiAgent, AiAgentConfig, McpServerConfig, MealArtifactData, MealPlanEntry};
 pub use difficulty_assessment::{assess_recipe_difficulty, DifficultyAssessmentError};
+pub use recipe_extraction::{extract_recipe_from_text, RecipeExtractionError};
 pub use llm::{LlmProvider, LlmProviderType, Message, ContentBlock, ImageSource};
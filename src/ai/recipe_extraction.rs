@@ -0,0 +1,140 @@
+use crate::ai::llm::{ContentBlock, LlmError, LlmProvider, LlmResponse, Message};
+use crate::models::CreateRecipeInput;
+use thiserror::Error;
+
+/// Page text beyond this many characters is not sent to the LLM
+const MAX_PAGE_CHARS: usize = 30_000;
+
+#[derive(Debug, Error)]
+pub enum RecipeExtractionError {
+    #[error("LLM error: {0}")]
+    Llm(#[from] LlmError),
+    #[error("Invalid response: {0}")]
+    InvalidResponse(String),
+    #[error("No recipe found on the page")]
+    NoRecipe,
+}
+
+/// Extract a recipe from the visible text of a web page using AI.
+///
+/// Used for pages without schema.org markup. The LLM is asked for JSON in the
+/// shape of `CreateRecipeInput`, or `null` when the page holds no recipe.
+pub async fn extract_recipe_from_text(
+    llm: &LlmProvider,
+    page_text: &str,
+) -> Result<CreateRecipeInput, RecipeExtractionError> {
+    let prompt = format_extraction_prompt(page_text);
+
+    let messages = vec![Message::User {
+        content: vec![ContentBlock::Text { text: prompt }],
+    }];
+
+    let response = llm
+        .complete(&messages, &[], Some("You are a precise recipe data extractor."))
+        .await?;
+
+    parse_extraction_response(&response)
+}
+
+fn format_extraction_prompt(page_text: &str) -> String {
+    let mut end = page_text.len().min(MAX_PAGE_CHARS);
+    while !page_text.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    format!(
+        r#"Extract the recipe from this web page text and respond with ONLY a JSON object of this shape:
+
+{{"title": "...", "description": "...", "prep_time_minutes": 10, "cook_time_minutes": 20, "servings": 4,
+  "ingredients": [{{"name": "flour", "quantity": 200, "unit": "g", "notes": "sifted"}}],
+  "steps": [{{"instruction": "...", "duration_minutes": 5, "temperature_value": 180, "temperature_unit": "Celsius"}}]}}
+
+Omit fields that are not stated. temperature_unit is "Celsius" or "Fahrenheit".
+If the page does not contain a recipe, respond with null.
+
+Page text:
+{}"#,
+        &page_text[..end]
+    )
+}
+
+fn parse_extraction_response(response: &LlmResponse) -> Result<CreateRecipeInput, RecipeExtractionError> {
+    let text = match response {
+        LlmResponse::Text(t) => t.as_str(),
+        LlmResponse::TextWithToolUse { text, .. } => text.as_str(),
+        LlmResponse::ToolUse(_) => {
+            return Err(RecipeExtractionError::InvalidResponse(
+                "Received tool use response instead of text".to_string(),
+            ));
+        }
+    };
+
+    // Tolerate a Markdown code fence around the JSON
+    let trimmed = text.trim();
+    let json = trimmed
+        .strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .and_then(|s| s.strip_suffix("```"))
+        .unwrap_or(trimmed)
+        .trim();
+
+    if json == "null" {
+        return Err(RecipeExtractionError::NoRecipe);
+    }
+
+    let recipe: CreateRecipeInput = serde_json::from_str(json)
+        .map_err(|e| RecipeExtractionError::InvalidResponse(format!("{}: {}", e, json)))?;
+
+    if recipe.title.trim().is_empty() {
+        return Err(RecipeExtractionError::NoRecipe);
+    }
+    Ok(recipe)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_extraction_response() {
+        let response = LlmResponse::Text(
+            r#"{"title": "Pancakes", "servings": 2, "ingredients": [{"name": "egg", "quantity": 1}], "steps": [{"instruction": "Whisk."}]}"#
+                .to_string(),
+        );
+        let recipe = parse_extraction_response(&response).unwrap();
+        assert_eq!(recipe.title, "Pancakes");
+        assert_eq!(recipe.ingredients[0].quantity, Some(1.0));
+        assert_eq!(recipe.steps.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_extraction_response_with_code_fence() {
+        let response = LlmResponse::Text("```json\n{\"title\": \"Toast\"}\n```".to_string());
+        assert_eq!(parse_extraction_response(&response).unwrap().title, "Toast");
+    }
+
+    #[test]
+    fn test_parse_extraction_response_null() {
+        let response = LlmResponse::Text("null".to_string());
+        assert!(matches!(
+            parse_extraction_response(&response),
+            Err(RecipeExtractionError::NoRecipe)
+        ));
+    }
+
+    #[test]
+    fn test_parse_extraction_response_invalid() {
+        let response = LlmResponse::Text("Sorry, I can't help".to_string());
+        assert!(matches!(
+            parse_extraction_response(&response),
+            Err(RecipeExtractionError::InvalidResponse(_))
+        ));
+    }
+
+    #[test]
+    fn test_prompt_truncates_long_pages() {
+        let page = "é".repeat(MAX_PAGE_CHARS);
+        let prompt = format_extraction_prompt(&page);
+        assert!(prompt.len() < MAX_PAGE_CHARS + 1000);
+    }
+}
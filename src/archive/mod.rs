@@ -0,0 +1,494 @@
+//! Portable vault archives: a zip of a family's recipes, photos, share links
+//! and shopping list settings, used to move data between instances.
+//!
+//! Layout (format version 1):
+//!
+//! ```text
+//! manifest.json               format, version, counts
+//! recipes/<id>.json           one recipe with ingredients, steps and photo list
+//! photos/<filename>           original bytes of each recipe photo
+//! share_links.json            with each collection link's recipes
+//! shopping_list_links.json
+//! aisle_order.json            only when the family has configured one
+//! ```
+
+pub mod restore;
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    io::{Read, Seek, Write},
+};
+use thiserror::Error;
+use zip::{result::ZipError, write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::{
+    error::ApiError,
+    models::{RecipePhoto, RecipeWithDetails, ShareLink, SharedRecipe, ShoppingListLink},
+    shopping::AisleConfig,
+};
+
+pub use restore::{restore_archive, ConflictStrategy, ImportReport, RecipeAction, RestoreTarget};
+
+/// Identifies a vault archive in its manifest
+pub const ARCHIVE_FORMAT: &str = "recipe-vault-archive";
+
+/// Bumped whenever the layout changes incompatibly. Archives with a newer
+/// version than this are rejected.
+pub const ARCHIVE_VERSION: u32 = 1;
+
+/// Largest single entry read from an archive (guards against zip bombs)
+const MAX_ENTRY_BYTES: u64 = 20 * 1024 * 1024;
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const SHARE_LINKS_ENTRY: &str = "share_links.json";
+const SHOPPING_LIST_LINKS_ENTRY: &str = "shopping_list_links.json";
+const AISLE_ORDER_ENTRY: &str = "aisle_order.json";
+const RECIPES_DIR: &str = "recipes/";
+const PHOTOS_DIR: &str = "photos/";
+
+#[derive(Debug, Error)]
+pub enum ArchiveError {
+    #[error("Invalid zip file: {0}")]
+    Zip(#[from] ZipError),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid {entry}: {source}")]
+    Json {
+        entry: String,
+        source: serde_json::Error,
+    },
+    #[error("Archive has no manifest.json")]
+    MissingManifest,
+    #[error("Not a Recipe Vault archive (format '{0}')")]
+    UnsupportedFormat(String),
+    #[error("Archive version {0} is newer than this server supports ({ARCHIVE_VERSION})")]
+    UnsupportedVersion(u32),
+    #[error("Archive entry {0} exceeds the size limit")]
+    EntryTooLarge(String),
+}
+
+impl From<ArchiveError> for ApiError {
+    fn from(e: ArchiveError) -> Self {
+        match e {
+            ArchiveError::Io(e) => ApiError::FileSystemError(e.to_string()),
+            other => ApiError::Validation(other.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exported_by: Option<String>,
+    /// Family the data was exported from. None for god mode exports.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub family_id: Option<String>,
+    pub app_version: String,
+    #[serde(default)]
+    pub counts: ManifestCounts,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ManifestCounts {
+    pub recipes: usize,
+    pub photos: usize,
+    pub share_links: usize,
+    pub shopping_list_links: usize,
+}
+
+impl Manifest {
+    /// Manifest for a new export. Counts are filled in when it is written.
+    pub fn new(exported_by: Option<String>, family_id: Option<String>) -> Self {
+        Self {
+            format: ARCHIVE_FORMAT.to_string(),
+            version: ARCHIVE_VERSION,
+            exported_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            exported_by,
+            family_id,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            counts: ManifestCounts::default(),
+        }
+    }
+}
+
+/// Shopping list link as archived: recipe IDs as a list rather than the
+/// JSON-encoded column
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ArchivedShoppingListLink {
+    pub token: String,
+    pub recipe_ids: Vec<String>,
+    pub created_by: String,
+    pub created_at: String,
+    pub expires_at: String,
+}
+
+impl From<&ShoppingListLink> for ArchivedShoppingListLink {
+    fn from(link: &ShoppingListLink) -> Self {
+        Self {
+            token: link.token.clone(),
+            recipe_ids: link.recipe_ids(),
+            created_by: link.created_by.clone(),
+            created_at: link.created_at.clone(),
+            expires_at: link.expires_at.clone(),
+        }
+    }
+}
+
+/// Share link as archived, with a collection's recipes in page order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedShareLink {
+    #[serde(flatten)]
+    pub link: ShareLink,
+    /// Empty for links to a single recipe
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recipes: Vec<ArchivedSharedRecipe>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ArchivedSharedRecipe {
+    pub recipe_id: String,
+    /// Meal plan role
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+}
+
+impl From<&SharedRecipe> for ArchivedSharedRecipe {
+    fn from(recipe: &SharedRecipe) -> Self {
+        Self {
+            recipe_id: recipe.recipe_id.clone(),
+            role: recipe.role.clone(),
+        }
+    }
+}
+
+/// The contents of a vault archive
+#[derive(Debug, Clone)]
+pub struct VaultArchive {
+    pub manifest: Manifest,
+    pub recipes: Vec<RecipeWithDetails>,
+    pub share_links: Vec<ArchivedShareLink>,
+    pub shopping_list_links: Vec<ArchivedShoppingListLink>,
+    pub aisle_order: Option<AisleConfig>,
+    /// Photo bytes by filename. Only populated when reading: exports stream
+    /// photos straight from photo storage.
+    pub photos: HashMap<String, Vec<u8>>,
+}
+
+/// Write an archive, copying each recipe's photos as `read_photo` returns
+/// them by filename. Missing photo files are skipped. Returns the manifest as
+/// written.
+pub fn write_archive<W: Write + Seek>(
+    writer: W,
+    archive: VaultArchive,
+    mut read_photo: impl FnMut(&str) -> Result<Option<Vec<u8>>, String>,
+) -> Result<Manifest, ArchiveError> {
+    let mut zip = ZipWriter::new(writer);
+    // Images are already compressed
+    let photo_options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+
+    let mut manifest = archive.manifest;
+    manifest.counts = ManifestCounts {
+        recipes: archive.recipes.len(),
+        photos: 0,
+        share_links: archive.share_links.len(),
+        shopping_list_links: archive.shopping_list_links.len(),
+    };
+
+    for recipe in &archive.recipes {
+        write_json(&mut zip, &format!("{}{}.json", RECIPES_DIR, recipe.recipe.id), recipe)?;
+
+        for photo in archived_photos(recipe) {
+            match read_photo(&photo.filename) {
+                Ok(Some(bytes)) => {
+                    zip.start_file(format!("{}{}", PHOTOS_DIR, photo.filename), photo_options)?;
+                    zip.write_all(&bytes)?;
+                    manifest.counts.photos += 1;
+                }
+                Ok(None) => tracing::warn!("Skipping photo {} in export: file not found", photo.filename),
+                Err(e) => tracing::warn!("Skipping photo {} in export: {}", photo.filename, e),
+            }
+        }
+    }
+
+    write_json(&mut zip, SHARE_LINKS_ENTRY, &archive.share_links)?;
+
+    write_json(&mut zip, SHOPPING_LIST_LINKS_ENTRY, &archive.shopping_list_links)?;
+
+    if let Some(aisle_order) = &archive.aisle_order {
+        write_json(&mut zip, AISLE_ORDER_ENTRY, aisle_order)?;
+    }
+
+    // Written last so the counts reflect what was actually included
+    write_json(&mut zip, MANIFEST_ENTRY, &manifest)?;
+
+    zip.finish()?;
+    Ok(manifest)
+}
+
+fn write_json<W: Write + Seek, T: Serialize>(
+    zip: &mut ZipWriter<W>,
+    name: &str,
+    value: &T,
+) -> Result<(), ArchiveError> {
+    zip.start_file(name, SimpleFileOptions::default().compression_method(CompressionMethod::Deflated))?;
+    serde_json::to_writer_pretty(zip, value).map_err(|source| ArchiveError::Json {
+        entry: name.to_string(),
+        source,
+    })
+}
+
+/// Read and validate an archive
+pub fn read_archive<R: Read + Seek>(reader: R) -> Result<VaultArchive, ArchiveError> {
+    let mut zip = ZipArchive::new(reader)?;
+
+    let manifest: Manifest = match zip.by_name(MANIFEST_ENTRY) {
+        Ok(mut entry) => parse_json(MANIFEST_ENTRY, &read_entry(MANIFEST_ENTRY, &mut entry)?)?,
+        Err(ZipError::FileNotFound) => return Err(ArchiveError::MissingManifest),
+        Err(e) => return Err(e.into()),
+    };
+    if manifest.format != ARCHIVE_FORMAT {
+        return Err(ArchiveError::UnsupportedFormat(manifest.format));
+    }
+    if manifest.version > ARCHIVE_VERSION {
+        return Err(ArchiveError::UnsupportedVersion(manifest.version));
+    }
+
+    let mut archive = VaultArchive {
+        manifest,
+        recipes: Vec::new(),
+        share_links: Vec::new(),
+        shopping_list_links: Vec::new(),
+        aisle_order: None,
+        photos: HashMap::new(),
+    };
+
+    for index in 0..zip.len() {
+        let mut entry = zip.by_index(index)?;
+        if entry.is_dir() {
+            continue;
+        }
+        let name = entry.name().to_string();
+
+        if let Some(filename) = name.strip_prefix(PHOTOS_DIR) {
+            // Photos are renamed on restore, so only a plain filename is accepted
+            if filename.is_empty() || filename.contains(['/', '\\']) || filename.starts_with('.') {
+                tracing::warn!("Ignoring archive entry {}", name);
+                continue;
+            }
+            let bytes = read_entry(&name, &mut entry)?;
+            archive.photos.insert(filename.to_string(), bytes);
+        } else if name.starts_with(RECIPES_DIR) && name.ends_with(".json") {
+            archive.recipes.push(parse_json(&name, &read_entry(&name, &mut entry)?)?);
+        } else if name == SHARE_LINKS_ENTRY {
+            archive.share_links = parse_json(&name, &read_entry(&name, &mut entry)?)?;
+        } else if name == SHOPPING_LIST_LINKS_ENTRY {
+            archive.shopping_list_links = parse_json(&name, &read_entry(&name, &mut entry)?)?;
+        } else if name == AISLE_ORDER_ENTRY {
+            archive.aisle_order = Some(parse_json(&name, &read_entry(&name, &mut entry)?)?);
+        } else if name != MANIFEST_ENTRY {
+            tracing::warn!("Ignoring unknown archive entry {}", name);
+        }
+    }
+
+    Ok(archive)
+}
+
+fn read_entry(name: &str, entry: &mut impl Read) -> Result<Vec<u8>, ArchiveError> {
+    let mut bytes = Vec::new();
+    entry.take(MAX_ENTRY_BYTES + 1).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 > MAX_ENTRY_BYTES {
+        return Err(ArchiveError::EntryTooLarge(name.to_string()));
+    }
+    Ok(bytes)
+}
+
+/// A recipe's photos followed by its source scans. Archives written before
+/// recipes had several photos only name the cover, in `photo_filename`.
+pub fn archived_photos(recipe: &RecipeWithDetails) -> Vec<RecipePhoto> {
+    if !recipe.photos.is_empty() || !recipe.source_scans.is_empty() {
+        return recipe.photos.iter().chain(&recipe.source_scans).cloned().collect();
+    }
+    recipe
+        .recipe
+        .photo_filename
+        .iter()
+        .map(|filename| RecipePhoto {
+            id: recipe.recipe.id.clone(),
+            recipe_id: recipe.recipe.id.clone(),
+            filename: filename.clone(),
+            position: 0,
+            caption: None,
+            step_position: None,
+            is_cover: true,
+            source_scan: false,
+            created_at: recipe.recipe.created_at.clone(),
+        })
+        .collect()
+}
+
+fn parse_json<T: serde::de::DeserializeOwned>(name: &str, bytes: &[u8]) -> Result<T, ArchiveError> {
+    serde_json::from_slice(bytes).map_err(|source| ArchiveError::Json {
+        entry: name.to_string(),
+        source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Recipe, RecipeIngredient};
+    use std::io::Cursor;
+
+    fn sample_recipe(id: &str, photo: Option<&str>) -> RecipeWithDetails {
+        RecipeWithDetails {
+            recipe: Recipe {
+                id: id.to_string(),
+                title: format!("Recipe {}", id),
+                description: None,
+                prep_time_minutes: Some(5),
+                cook_time_minutes: None,
+                servings: Some(2),
+                difficulty: None,
+                photo_filename: photo.map(String::from),
+                source_url: None,
+                shared_by: None,
+                created_at: "2026-01-01 00:00:00".to_string(),
+                updated_at: "2026-01-01 00:00:00".to_string(),
+                created_by: Some("cook@example.com".to_string()),
+                updated_by: None,
+                family_id: None,
+            },
+            ingredients: vec![RecipeIngredient {
+                id: "i1".to_string(),
+                recipe_id: id.to_string(),
+                position: 0,
+                name: "salt".to_string(),
+                quantity: Some(1.0),
+                unit: Some("tsp".to_string()),
+                notes: None,
+            }],
+            steps: vec![],
+            tags: vec![],
+            photos: vec![],
+            source_scans: vec![],
+        }
+    }
+
+    fn empty_archive() -> VaultArchive {
+        VaultArchive {
+            manifest: Manifest::new(Some("cook@example.com".to_string()), Some("family-a".to_string())),
+            recipes: vec![],
+            share_links: vec![],
+            shopping_list_links: vec![],
+            aisle_order: None,
+            photos: HashMap::new(),
+        }
+    }
+
+    fn zip_with(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, bytes) in entries {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(bytes).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut archive = empty_archive();
+        archive.recipes = vec![sample_recipe("r1", Some("r1.png")), sample_recipe("r2", Some("gone.jpg"))];
+        archive.aisle_order = Some(AisleConfig::default());
+        archive.shopping_list_links = vec![ArchivedShoppingListLink {
+            token: "abc".to_string(),
+            recipe_ids: vec!["r1".to_string(), "r2".to_string()],
+            created_by: "cook@example.com".to_string(),
+            created_at: "2026-01-01 00:00:00".to_string(),
+            expires_at: "2026-02-01 00:00:00".to_string(),
+        }];
+
+        let share_links = vec![ArchivedShareLink {
+            link: serde_json::from_value(serde_json::json!({
+                "token": "plan",
+                "recipe_id": null,
+                "kind": "meal_plan",
+                "title": "Lunch",
+                "created_by": "cook@example.com",
+                "created_at": "2026-01-01 00:00:00",
+                "expires_at": null
+            }))
+            .unwrap(),
+            recipes: vec![ArchivedSharedRecipe {
+                recipe_id: "r2".to_string(),
+                role: Some("main".to_string()),
+            }],
+        }];
+        archive.share_links = share_links.clone();
+
+        let mut buffer = Cursor::new(Vec::new());
+        let photos = HashMap::from([("r1.png".to_string(), b"png bytes".to_vec())]);
+        let manifest = write_archive(&mut buffer, archive, |filename| Ok(photos.get(filename).cloned())).unwrap();
+        assert_eq!(manifest.counts.recipes, 2);
+        // The missing photo file is skipped
+        assert_eq!(manifest.counts.photos, 1);
+
+        buffer.set_position(0);
+        let read = read_archive(buffer).unwrap();
+        assert_eq!(read.manifest.version, ARCHIVE_VERSION);
+        assert_eq!(read.manifest.family_id.as_deref(), Some("family-a"));
+        assert_eq!(read.manifest.counts, manifest.counts);
+        assert_eq!(read.recipes.len(), 2);
+        assert_eq!(read.recipes[0].ingredients[0].name, "salt");
+        assert_eq!(read.photos.get("r1.png").map(Vec::as_slice), Some(&b"png bytes"[..]));
+        assert_eq!(read.shopping_list_links[0].recipe_ids, vec!["r1", "r2"]);
+        assert_eq!(read.share_links[0].link.token, "plan");
+        assert_eq!(read.share_links[0].recipes, share_links[0].recipes);
+        assert_eq!(read.aisle_order, Some(AisleConfig::default()));
+    }
+
+    #[test]
+    fn test_rejects_missing_manifest() {
+        let bytes = zip_with(&[("recipes/x.json", b"{}")]);
+        assert!(matches!(read_archive(Cursor::new(bytes)), Err(ArchiveError::MissingManifest)));
+    }
+
+    #[test]
+    fn test_rejects_newer_version() {
+        let manifest = format!(
+            r#"{{"format": "{}", "version": {}, "exported_at": "", "app_version": "9.9"}}"#,
+            ARCHIVE_FORMAT,
+            ARCHIVE_VERSION + 1
+        );
+        let bytes = zip_with(&[("manifest.json", manifest.as_bytes())]);
+        assert!(matches!(
+            read_archive(Cursor::new(bytes)),
+            Err(ArchiveError::UnsupportedVersion(_))
+        ));
+    }
+
+    #[test]
+    fn test_rejects_other_formats() {
+        let bytes = zip_with(&[("manifest.json", br#"{"format": "other", "version": 1, "exported_at": "", "app_version": ""}"#)]);
+        assert!(matches!(
+            read_archive(Cursor::new(bytes)),
+            Err(ArchiveError::UnsupportedFormat(_))
+        ));
+        assert!(matches!(read_archive(Cursor::new(b"not a zip".to_vec())), Err(ArchiveError::Zip(_))));
+    }
+
+    #[test]
+    fn test_ignores_nested_photo_paths() {
+        let manifest = serde_json::to_vec(&Manifest::new(None, None)).unwrap();
+        let bytes = zip_with(&[
+            ("manifest.json", &manifest),
+            ("photos/../../etc/passwd", b"x"),
+            ("photos/ok.jpg", b"y"),
+        ]);
+        let archive = read_archive(Cursor::new(bytes)).unwrap();
+        assert_eq!(archive.photos.keys().collect::<Vec<_>>(), vec!["ok.jpg"]);
+    }
+}
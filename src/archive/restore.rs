@@ -0,0 +1,473 @@
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet};
+
+use super::{archived_photos, VaultArchive};
+use crate::{
+    db::queries::{self, NewRecipePhoto, NewShareLink},
+    error::{ApiError, ApiResult},
+    handlers::share::{is_expired, link_expired},
+    models::{CreateRecipeInput, Recipe},
+    photo_store::PhotoStore,
+    photos,
+};
+
+/// What to do when an archived recipe's title is already taken
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictStrategy {
+    /// Keep the existing recipe and leave the archived one out
+    #[default]
+    Skip,
+    /// Import under a new title: "Title (2)", "Title (3)", ...
+    Rename,
+    /// Replace the existing recipe in place, keeping its ID and share links
+    Overwrite,
+}
+
+/// Who the archive is restored for
+#[derive(Debug, Clone, Copy)]
+pub struct RestoreTarget<'a> {
+    pub user_email: Option<&'a str>,
+    /// None in god mode
+    pub family_members: Option<&'a [String]>,
+    pub family_id: Option<&'a str>,
+}
+
+impl RestoreTarget<'_> {
+    /// Whether this user is in the target's family, so can stay a recipe's author
+    fn has_member(&self, email: Option<&str>) -> bool {
+        match self.family_members {
+            Some(members) => email.is_some_and(|e| members.iter().any(|m| m.eq_ignore_ascii_case(e))),
+            None => true,
+        }
+    }
+
+    /// Whether a recipe in this family is visible to the target
+    fn owns(&self, family_id: Option<&str>) -> bool {
+        match self.family_id {
+            Some(own) => family_id == Some(own),
+            None => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecipeAction {
+    Created,
+    Renamed,
+    Overwritten,
+    Skipped,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecipeOutcome {
+    /// Title in the archive
+    pub title: String,
+    pub action: RecipeAction,
+    /// New title when renamed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub imported_title: Option<String>,
+    /// ID of the created or overwritten recipe. Not set for dry runs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipe_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Result of an import. For dry runs, what would have happened.
+#[derive(Debug, Serialize)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub conflict: ConflictStrategy,
+    pub format_version: u32,
+    pub recipes: Vec<RecipeOutcome>,
+    pub created: usize,
+    pub renamed: usize,
+    pub overwritten: usize,
+    pub skipped: usize,
+    pub photos: usize,
+    pub share_links: usize,
+    pub shopping_list_links: usize,
+    pub aisle_order: bool,
+    pub warnings: Vec<String>,
+}
+
+/// Planned action for one archived recipe
+enum Plan {
+    Create { title: String },
+    Overwrite { existing_id: String },
+    Skip { reason: String },
+}
+
+/// Restore an archive into the target's family.
+///
+/// Recipes get new IDs (except when overwriting), and share links and
+/// shopping list links are re-pointed at them. Expired links and links whose
+/// token is already in use are left out. Each recipe is saved in its own
+/// transaction, so a failure part way leaves the earlier recipes in place.
+pub async fn restore_archive(
+    pool: &SqlitePool,
+    photo_store: &dyn PhotoStore,
+    mut archive: VaultArchive,
+    target: RestoreTarget<'_>,
+    conflict: ConflictStrategy,
+    dry_run: bool,
+) -> ApiResult<ImportReport> {
+    let mut report = ImportReport {
+        dry_run,
+        conflict,
+        format_version: archive.manifest.version,
+        recipes: Vec::new(),
+        created: 0,
+        renamed: 0,
+        overwritten: 0,
+        skipped: 0,
+        photos: 0,
+        share_links: 0,
+        shopping_list_links: 0,
+        aisle_order: false,
+        warnings: Vec::new(),
+    };
+
+    archive.recipes.sort_by_key(|r| r.recipe.title.to_lowercase());
+
+    // Archived recipe ID -> restored recipe ID (the archived ID in dry runs)
+    let mut id_map: HashMap<String, String> = HashMap::new();
+    // Lowercased titles claimed by this import so far
+    let mut claimed: HashSet<String> = HashSet::new();
+
+    for archived in &archive.recipes {
+        let title = archived.recipe.title.clone();
+        let mut input = CreateRecipeInput::from(archived);
+
+        let plan = match input.validate() {
+            Err(e) => Plan::Skip {
+                reason: e.to_string(),
+            },
+            Ok(()) => plan_recipe(pool, &input.title, &mut claimed, target, conflict).await?,
+        };
+
+        let (action, imported_title, recipe_id) = match plan {
+            Plan::Skip { reason } => {
+                report.skipped += 1;
+                report.recipes.push(RecipeOutcome {
+                    title,
+                    action: RecipeAction::Skipped,
+                    imported_title: None,
+                    recipe_id: None,
+                    reason: Some(reason),
+                });
+                continue;
+            }
+            Plan::Create { title: new_title } => {
+                let renamed = new_title != input.title;
+                input.title = new_title.clone();
+                let recipe_id = if dry_run {
+                    None
+                } else {
+                    let author = if target.has_member(archived.recipe.created_by.as_deref()) {
+                        archived.recipe.created_by.clone()
+                    } else {
+                        target.user_email.map(String::from)
+                    };
+                    Some(queries::create_recipe(pool, input, author, target.family_id).await?.recipe.id)
+                };
+                if renamed {
+                    report.renamed += 1;
+                    (RecipeAction::Renamed, Some(new_title), recipe_id)
+                } else {
+                    report.created += 1;
+                    (RecipeAction::Created, None, recipe_id)
+                }
+            }
+            Plan::Overwrite { existing_id } => {
+                if !dry_run {
+                    queries::replace_recipe(pool, &existing_id, input, target.user_email.map(String::from))
+                        .await?;
+                    for old_photo in queries::delete_recipe_photos(pool, &existing_id).await? {
+                        photos::remove_photo(photo_store, &old_photo.filename).await;
+                    }
+                }
+                report.overwritten += 1;
+                (RecipeAction::Overwritten, None, Some(existing_id))
+            }
+        };
+
+        for photo in archived_photos(archived) {
+            match archive.photos.get(&photo.filename) {
+                // Checked and stripped of metadata like any upload, since
+                // the archive may not be one we wrote
+                Some(bytes) => match photos::clean_upload(bytes.clone()).await {
+                    Ok(clean) => {
+                        if let Some(id) = &recipe_id
+                            && !dry_run
+                        {
+                            let photo_id = Recipe::new_id();
+                            let new_filename = format!("{}.{}", photo_id, clean.extension);
+                            photos::write_photo(photo_store, &new_filename, &clean.bytes).await?;
+                            let new_photo = NewRecipePhoto {
+                                id: &photo_id,
+                                filename: &new_filename,
+                                caption: photo.caption.as_deref(),
+                                step_position: photo.step_position,
+                                cover: photo.is_cover,
+                                source_scan: photo.source_scan,
+                            };
+                            queries::add_recipe_photo(pool, id, new_photo).await?;
+                        }
+                        report.photos += 1;
+                    }
+                    Err(ApiError::UnsupportedFileType(reason)) => report
+                        .warnings
+                        .push(format!("Photo {} for '{}' was skipped: {}", photo.filename, title, reason)),
+                    Err(e) => return Err(e),
+                },
+                None => report
+                    .warnings
+                    .push(format!("Photo {} for '{}' is missing from the archive", photo.filename, title)),
+            }
+        }
+
+        id_map.insert(
+            archived.recipe.id.clone(),
+            recipe_id.clone().unwrap_or_else(|| archived.recipe.id.clone()),
+        );
+        report.recipes.push(RecipeOutcome {
+            title,
+            action,
+            imported_title,
+            recipe_id: if dry_run { None } else { recipe_id },
+            reason: None,
+        });
+    }
+
+    restore_share_links(pool, &archive, &id_map, target, dry_run, &mut report).await?;
+    restore_shopping_list_links(pool, &archive, &id_map, target, dry_run, &mut report).await?;
+    restore_aisle_order(pool, &archive, target, conflict, dry_run, &mut report).await?;
+
+    Ok(report)
+}
+
+/// Decide what happens to one recipe, claiming its (possibly new) title
+async fn plan_recipe(
+    pool: &SqlitePool,
+    title: &str,
+    claimed: &mut HashSet<String>,
+    target: RestoreTarget<'_>,
+    conflict: ConflictStrategy,
+) -> ApiResult<Plan> {
+    let existing = queries::find_recipe_by_title(pool, title).await?;
+    let claimed_by_import = claimed.contains(&title.to_lowercase());
+
+    if existing.is_none() && !claimed_by_import {
+        claimed.insert(title.to_lowercase());
+        return Ok(Plan::Create {
+            title: title.to_string(),
+        });
+    }
+
+    match conflict {
+        ConflictStrategy::Skip => Ok(Plan::Skip {
+            reason: "A recipe with this title already exists".to_string(),
+        }),
+        ConflictStrategy::Rename => {
+            let new_title = free_title(pool, title, claimed).await?;
+            claimed.insert(new_title.to_lowercase());
+            Ok(Plan::Create { title: new_title })
+        }
+        ConflictStrategy::Overwrite => match existing {
+            _ if claimed_by_import => Ok(Plan::Skip {
+                reason: "Another recipe in the archive has the same title".to_string(),
+            }),
+            Some(existing) if target.owns(existing.family_id.as_deref()) => {
+                claimed.insert(title.to_lowercase());
+                Ok(Plan::Overwrite {
+                    existing_id: existing.id,
+                })
+            }
+            _ => Ok(Plan::Skip {
+                reason: "The title is used by a recipe outside your family".to_string(),
+            }),
+        },
+    }
+}
+
+/// First "Title (n)" not used in the database or by this import
+pub(crate) async fn free_title(pool: &SqlitePool, title: &str, claimed: &HashSet<String>) -> ApiResult<String> {
+    for n in 2.. {
+        let suffix = format!(" ({})", n);
+        let mut base = title.to_string();
+        // Stay within the 200 character title limit
+        while base.len() + suffix.len() > 200 {
+            base.pop();
+        }
+        let candidate = format!("{}{}", base.trim_end(), suffix);
+        if !claimed.contains(&candidate.to_lowercase())
+            && queries::find_recipe_by_title(pool, &candidate).await?.is_none()
+        {
+            return Ok(candidate);
+        }
+    }
+    unreachable!("unbounded range")
+}
+
+async fn restore_share_links(
+    pool: &SqlitePool,
+    archive: &VaultArchive,
+    id_map: &HashMap<String, String>,
+    target: RestoreTarget<'_>,
+    dry_run: bool,
+    report: &mut ImportReport,
+) -> ApiResult<()> {
+    for archived in &archive.share_links {
+        let link = &archived.link;
+        // A collection keeps whichever of its recipes were restored, in order
+        let recipes: Vec<(String, Option<String>)> = match &link.recipe_id {
+            Some(original_id) => id_map.get(original_id).map(|id| (id.clone(), None)).into_iter().collect(),
+            None => archived
+                .recipes
+                .iter()
+                .filter_map(|recipe| id_map.get(&recipe.recipe_id).map(|id| (id.clone(), recipe.role.clone())))
+                .collect(),
+        };
+        if recipes.is_empty() || link_expired(link) || link.views_used_up() {
+            continue;
+        }
+        if queries::get_share_link(pool, &link.token).await?.is_some() {
+            report
+                .warnings
+                .push(format!("Share link {} already exists and was not restored", link.token));
+            continue;
+        }
+        // The archive could carry any hash, so a passphrase can't be taken from it
+        if link.passphrase_hash.is_some() {
+            report.warnings.push(format!(
+                "Share link {} needs a passphrase and was not restored; share the recipe again to protect it",
+                link.token
+            ));
+            continue;
+        }
+        if !dry_run {
+            let new_link = NewShareLink {
+                token: &link.token,
+                // Whoever restores the links is answerable for them
+                created_by: target.user_email.unwrap_or(&link.created_by),
+                family_id: target.family_id,
+                expires_at: link.expires_at.as_deref(),
+                max_views: link.max_views,
+                passphrase_hash: None,
+                single_use: link.single_use,
+            };
+            if link.is_collection() {
+                let title = link.title.as_deref().unwrap_or_default();
+                queries::create_collection_share_link(pool, link.kind, title, link.guest_count, &recipes, new_link)
+                    .await?;
+            } else {
+                queries::create_share_link(pool, &recipes[0].0, new_link).await?;
+            }
+        }
+        report.share_links += 1;
+    }
+    Ok(())
+}
+
+async fn restore_shopping_list_links(
+    pool: &SqlitePool,
+    archive: &VaultArchive,
+    id_map: &HashMap<String, String>,
+    target: RestoreTarget<'_>,
+    dry_run: bool,
+    report: &mut ImportReport,
+) -> ApiResult<()> {
+    for link in &archive.shopping_list_links {
+        let recipe_ids: Vec<String> = link.recipe_ids.iter().filter_map(|id| id_map.get(id).cloned()).collect();
+        if recipe_ids.is_empty() || is_expired(&link.expires_at) {
+            continue;
+        }
+        if queries::get_shopping_list_link(pool, &link.token).await?.is_some() {
+            report
+                .warnings
+                .push(format!("Shopping list link {} already exists and was not restored", link.token));
+            continue;
+        }
+        if !dry_run {
+            queries::create_shopping_list_link(
+                pool,
+                &link.token,
+                &recipe_ids,
+                target.family_id,
+                target.user_email.unwrap_or(&link.created_by),
+                &link.expires_at,
+            )
+            .await?;
+        }
+        report.shopping_list_links += 1;
+    }
+    Ok(())
+}
+
+/// The archived aisle order replaces the family's only when it has none or
+/// the conflict strategy is overwrite
+async fn restore_aisle_order(
+    pool: &SqlitePool,
+    archive: &VaultArchive,
+    target: RestoreTarget<'_>,
+    conflict: ConflictStrategy,
+    dry_run: bool,
+    report: &mut ImportReport,
+) -> ApiResult<()> {
+    let Some(aisle_order) = &archive.aisle_order else {
+        return Ok(());
+    };
+    let Some(family_id) = target.family_id else {
+        report
+            .warnings
+            .push("Aisle order was not restored: it belongs to a family and you are not in one".to_string());
+        return Ok(());
+    };
+
+    let existing = queries::get_aisle_config(pool, family_id).await?;
+    if existing.is_some() && conflict != ConflictStrategy::Overwrite {
+        report
+            .warnings
+            .push("Kept your family's existing aisle order".to_string());
+        return Ok(());
+    }
+
+    if !dry_run {
+        queries::save_aisle_config(pool, family_id, aisle_order, target.user_email).await?;
+    }
+    report.aisle_order = true;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_owns() {
+        let members = vec!["alice@example.com".to_string()];
+        let family = RestoreTarget {
+            user_email: Some("alice@example.com"),
+            family_members: Some(&members),
+            family_id: Some("family-a"),
+        };
+        assert!(family.has_member(Some("Alice@Example.com")));
+        assert!(!family.has_member(Some("bob@example.com")));
+        assert!(!family.has_member(None));
+        assert!(family.owns(Some("family-a")));
+        assert!(!family.owns(Some("family-b")));
+        assert!(!family.owns(None));
+
+        let god_mode = RestoreTarget {
+            user_email: None,
+            family_members: None,
+            family_id: None,
+        };
+        assert!(god_mode.has_member(None));
+        assert!(god_mode.owns(None));
+    }
+}
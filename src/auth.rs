@@ -1,6 +1,6 @@
 use axum::{
     body::Body,
-    http::{Request, StatusCode},
+    http::{Method, Request, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
     Json,
@@ -8,12 +8,23 @@ use axum::{
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use sqlx::SqlitePool;
 use std::fs;
-use std::path::Path;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 use tracing::{info, warn};
 
-use crate::config::FamiliesConfig;
+use crate::{
+    cloudflare_access::AccessVerifier,
+    db::queries,
+    error::{ApiError, ApiResult},
+    families::{FamilyDirectory, Membership},
+    models::{
+        api_key::{hash_api_key, ApiKeyScope},
+        ApiKey, FamilyRole,
+    },
+};
 
 const API_KEY_FILE: &str = "/app/data/.api_key";
 const API_KEY_LENGTH: usize = 32;
@@ -31,31 +42,149 @@ pub struct UserIdentity {
     /// Some(vec) = scoped to family, None = god mode (no filtering).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub family_members: Option<Vec<String>>,
+    /// Key of the user's family. None in god mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub family_id: Option<String>,
+    /// The user's role in their family. None in god mode, which may do anything.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<FamilyRole>,
+}
+
+impl UserIdentity {
+    /// Identity limited to the user's family; without a family the user can't
+    /// get past `api_key_auth`
+    fn scoped(email: Option<String>, membership: Option<Membership>) -> Self {
+        let (family_id, family_members, role) = match membership {
+            Some(membership) => (Some(membership.family_id), Some(membership.members), Some(membership.role)),
+            None => (None, None, None),
+        };
+        Self {
+            email,
+            family_members,
+            family_id,
+            role,
+        }
+    }
+
+    /// Fail unless the user may add or change recipes: anyone but a viewer
+    pub fn require_editor(&self) -> ApiResult<()> {
+        if self.role.is_some_and(|role| !role.can_edit()) {
+            return Err(ApiError::Forbidden(
+                "Viewers can only read the family's recipes; ask a family owner for editor access".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Fail unless the user may delete something created by `created_by`:
+    /// owners anything, editors only what they created themselves
+    pub fn require_can_delete(&self, what: &str, created_by: Option<&str>) -> ApiResult<()> {
+        self.require_editor()?;
+        if self
+            .role
+            .is_some_and(|role| !role.can_delete(self.email.as_deref(), created_by))
+        {
+            return Err(ApiError::Forbidden(format!(
+                "Editors can only delete {} they created; ask a family owner",
+                what
+            )));
+        }
+        Ok(())
+    }
+
+    /// Fail unless the user is an owner of their family (or in god mode)
+    pub fn require_owner(&self) -> ApiResult<()> {
+        if self.role.is_some_and(|role| role != FamilyRole::Owner) {
+            return Err(ApiError::Forbidden(
+                "Only family owners can manage members and invitations".to_string(),
+            ));
+        }
+        Ok(())
+    }
 }
 
 /// Shared state for the API key auth middleware
 #[derive(Clone)]
 pub struct ApiKeyState {
-    pub key: Arc<String>,
-    pub families_config: Arc<FamiliesConfig>,
+    pub pool: SqlitePool,
+    pub legacy_key: LegacyApiKey,
+    pub families: FamilyDirectory,
     pub dev_user_email: Option<String>,
 }
 
+/// The instance-wide API key, used by chat agents and for administration.
+/// After a rotation the previous key keeps working for a grace period, so
+/// clients can be moved over; restarting the server ends it early.
+#[derive(Clone)]
+pub struct LegacyApiKey {
+    keys: Arc<RwLock<LegacyKeys>>,
+    /// Where a rotated key is saved; None when it comes from `API_KEY`
+    path: Option<PathBuf>,
+}
+
+struct LegacyKeys {
+    current: String,
+    /// The key before the last rotation, and when it stops working
+    previous: Option<(String, Instant)>,
+}
+
+impl LegacyApiKey {
+    pub fn new(key: String, path: Option<PathBuf>) -> Self {
+        Self {
+            keys: Arc::new(RwLock::new(LegacyKeys {
+                current: key,
+                previous: None,
+            })),
+            path,
+        }
+    }
+
+    pub fn current(&self) -> String {
+        self.keys.read().unwrap().current.clone()
+    }
+
+    /// The current key, or the previous one during its grace period
+    pub fn matches(&self, key: &str) -> bool {
+        let keys = self.keys.read().unwrap();
+        constant_time_compare(key, &keys.current)
+            || keys
+                .previous
+                .as_ref()
+                .is_some_and(|(previous, until)| Instant::now() < *until && constant_time_compare(key, previous))
+    }
+
+    /// Replace the key, saving the new one. The old key keeps working for `grace`.
+    pub fn rotate(&self, grace: Duration) -> Result<String, String> {
+        let Some(path) = &self.path else {
+            return Err("The API key is set by the API_KEY environment variable; change it there".to_string());
+        };
+        let key = generate_api_key();
+        fs::write(path, &key).map_err(|e| format!("Failed to save the new API key: {}", e))?;
+
+        let mut keys = self.keys.write().unwrap();
+        let previous = std::mem::replace(&mut keys.current, key.clone());
+        keys.previous = Some((previous, Instant::now() + grace));
+        Ok(key)
+    }
+}
+
 /// Shared state for the cloudflare auth middleware
 #[derive(Clone)]
 pub struct CloudflareAuthState {
     pub dev_user_email: Option<String>,
-    pub families_config: Arc<FamiliesConfig>,
+    pub families: FamilyDirectory,
+    /// Verifies `Cf-Access-Jwt-Assertion`; None trusts the email header
+    pub access: Option<AccessVerifier>,
 }
 
-/// Load API key from environment, file, or generate a new one
-pub fn load_or_generate_api_key() -> String {
+/// Load the API key from environment, file, or generate a new one
+pub fn load_or_generate_api_key() -> LegacyApiKey {
     // Check for API_KEY environment variable first (for testing)
     if let Ok(key) = std::env::var("API_KEY") {
         let key = key.trim().to_string();
         if !key.is_empty() {
             info!("Using API key from API_KEY environment variable");
-            return key;
+            return LegacyApiKey::new(key, None);
         }
     }
 
@@ -68,7 +197,7 @@ pub fn load_or_generate_api_key() -> String {
                 let key = key.trim().to_string();
                 if !key.is_empty() {
                     info!("Loaded API key from {}", API_KEY_FILE);
-                    return key;
+                    return LegacyApiKey::new(key, Some(key_path.to_path_buf()));
                 }
             }
             Err(e) => {
@@ -110,7 +239,7 @@ pub fn load_or_generate_api_key() -> String {
         }
     }
 
-    key
+    LegacyApiKey::new(key, Some(key_path.to_path_buf()))
 }
 
 /// Generate a random 32-character hex API key
@@ -128,36 +257,59 @@ mod hex {
 }
 
 /// Middleware to extract user identity from Cloudflare headers or dev environment.
-/// Looks up family members from the families config and sets them in UserIdentity.
+/// Looks up the user's family in the database and sets it in UserIdentity.
 pub async fn cloudflare_auth(
     axum::extract::State(state): axum::extract::State<CloudflareAuthState>,
     mut request: Request<Body>,
     next: Next,
 ) -> Response {
-    // Priority: Cloudflare header > dev email
+    // Priority: Cloudflare identity > dev email. With token verification the
+    // token is the only identity; a dev email there would let anyone who
+    // reaches the origin in.
     // Note: X-User-Email is now handled in api_key_auth for scoped API key access
-    let email = request
-        .headers()
-        .get("Cf-Access-Authenticated-User-Email")
-        .and_then(|v| v.to_str().ok())
-        .map(normalize_email)
-        .or_else(|| state.dev_user_email.as_ref().map(|e| normalize_email(e)));
-
-    let family_members = email
-        .as_ref()
-        .and_then(|e| state.families_config.get_family_members(e))
-        .cloned();
-
-    request.extensions_mut().insert(UserIdentity {
-        email,
-        family_members,
-    });
+    let email = match &state.access {
+        Some(verifier) => {
+            let token = request
+                .headers()
+                .get("Cf-Access-Jwt-Assertion")
+                .and_then(|v| v.to_str().ok())
+                .map(|s| s.to_string());
+            match token {
+                Some(token) => match verifier.verify(&token).await {
+                    Ok(claims) => claims.email.as_deref().map(normalize_email),
+                    Err(e) => {
+                        warn!("Rejected Cloudflare Access token: {}", e);
+                        None
+                    }
+                },
+                None => None,
+            }
+        }
+        None => request
+            .headers()
+            .get("Cf-Access-Authenticated-User-Email")
+            .and_then(|v| v.to_str().ok())
+            .map(normalize_email)
+            .or_else(|| state.dev_user_email.as_ref().map(|e| normalize_email(e))),
+    };
+
+    let membership = match &email {
+        Some(email) => match state.families.membership(email).await {
+            Ok(membership) => membership,
+            Err(e) => return e.into_response(),
+        },
+        None => None,
+    };
+
+    request.extensions_mut().insert(UserIdentity::scoped(email, membership));
     next.run(request).await
 }
 
 /// Middleware to validate API key or Cloudflare identity.
-/// For API key auth with X-User-Email header, scopes to that user's family.
-/// For API key auth without X-User-Email, grants god mode (family_members = None).
+/// A per-user API key acts as its owner, limited by its scope, and is added
+/// to the request extensions as an `ApiKey`.
+/// The instance API key grants god mode (family_members = None) and can't act
+/// as a user: X-User-Email with it is refused.
 pub async fn api_key_auth(
     axum::extract::State(state): axum::extract::State<ApiKeyState>,
     mut request: Request<Body>,
@@ -171,49 +323,38 @@ pub async fn api_key_auth(
         .map(|s| s.to_string());
 
     if let Some(key) = api_key {
-        if constant_time_compare(&key, &state.key) {
-            // Valid API key — check for X-User-Email header for scoped access
-            let x_user_email = request
-                .headers()
-                .get("X-User-Email")
-                .and_then(|v| v.to_str().ok())
-                .map(normalize_email);
-
-            if let Some(email) = x_user_email {
-                // Scoped mode: look up family for this email
-                let family_members = state.families_config.get_family_members(&email).cloned();
-                if family_members.is_none() {
-                    // Email not in config → 403
-                    return (
-                        StatusCode::FORBIDDEN,
-                        Json(json!({
-                            "error": "Your email is not configured for access. Please contact the administrator."
-                        })),
-                    )
-                        .into_response();
-                }
-                request.extensions_mut().insert(UserIdentity {
-                    email: Some(email),
-                    family_members,
-                });
-            } else {
-                // God mode: no X-User-Email header, use DEV_USER_EMAIL for authorship
-                let email = state.dev_user_email.as_ref().map(|e| normalize_email(e));
-                request.extensions_mut().insert(UserIdentity {
-                    email,
-                    family_members: None, // None = god mode
-                });
+        if state.legacy_key.matches(&key) {
+            // Anyone holding the instance key could name any user here, so
+            // acting as a user takes that user's own key
+            if request.headers().contains_key("X-User-Email") {
+                return forbidden(
+                    "The instance API key can't act as a user; use a personal API key for that user instead",
+                );
             }
+            // God mode, using DEV_USER_EMAIL for authorship
+            let email = state.dev_user_email.as_ref().map(|e| normalize_email(e));
+            request.extensions_mut().insert(UserIdentity {
+                email,
+                family_members: None, // None = god mode
+                family_id: None,
+                role: None,
+            });
             return next.run(request).await;
         }
-        // Invalid API key
-        return (
-            StatusCode::UNAUTHORIZED,
-            Json(json!({
-                "error": "Invalid API key"
-            })),
-        )
-            .into_response();
+
+        let claimed_email = request
+            .headers()
+            .get("X-User-Email")
+            .and_then(|v| v.to_str().ok())
+            .map(normalize_email);
+        return match user_key_identity(&state, &key, request.method().clone(), claimed_email).await {
+            Ok((identity, api_key)) => {
+                request.extensions_mut().insert(identity);
+                request.extensions_mut().insert(api_key);
+                next.run(request).await
+            }
+            Err(response) => response,
+        };
     }
 
     // Check if Cloudflare identity is present in extensions
@@ -221,13 +362,7 @@ pub async fn api_key_auth(
         && identity.email.is_some() {
             if identity.family_members.is_none() {
                 // User authenticated via Cloudflare but not in any family → 403
-                return (
-                    StatusCode::FORBIDDEN,
-                    Json(json!({
-                        "error": "Your email is not configured for access. Please contact the administrator."
-                    })),
-                )
-                    .into_response();
+                return not_configured();
             }
             return next.run(request).await;
         }
@@ -242,6 +377,58 @@ pub async fn api_key_auth(
         .into_response()
 }
 
+/// Identity for a per-user API key: always the key's owner, never whoever
+/// X-User-Email names
+async fn user_key_identity(
+    state: &ApiKeyState,
+    key: &str,
+    method: Method,
+    claimed_email: Option<String>,
+) -> Result<(UserIdentity, ApiKey), Response> {
+    let api_key = match queries::find_active_api_key(&state.pool, &hash_api_key(key)).await {
+        Ok(Some(api_key)) => api_key,
+        Ok(None) => {
+            return Err((StatusCode::UNAUTHORIZED, Json(json!({"error": "Invalid API key"}))).into_response());
+        }
+        Err(e) => return Err(e.into_response()),
+    };
+
+    if claimed_email.is_some_and(|email| email != api_key.email) {
+        return Err(forbidden("X-User-Email does not match the owner of this API key"));
+    }
+    if api_key.scope == ApiKeyScope::ReadOnly && !matches!(method, Method::GET | Method::HEAD) {
+        return Err(forbidden("This API key is read-only"));
+    }
+
+    if let Err(e) = queries::touch_api_key(&state.pool, &api_key.id).await {
+        warn!("Failed to record use of API key {}: {}", api_key.id, e);
+    }
+
+    if api_key.scope == ApiKeyScope::Admin {
+        let identity = UserIdentity {
+            email: Some(api_key.email.clone()),
+            family_members: None,
+            family_id: None,
+            role: None,
+        };
+        return Ok((identity, api_key));
+    }
+    let membership = match state.families.membership(&api_key.email).await {
+        Ok(Some(membership)) => membership,
+        Ok(None) => return Err(not_configured()),
+        Err(e) => return Err(e.into_response()),
+    };
+    Ok((UserIdentity::scoped(Some(api_key.email.clone()), Some(membership)), api_key))
+}
+
+fn forbidden(message: &str) -> Response {
+    (StatusCode::FORBIDDEN, Json(json!({"error": message}))).into_response()
+}
+
+fn not_configured() -> Response {
+    forbidden("Your email is not configured for access. Please contact the administrator.")
+}
+
 /// Constant-time string comparison to prevent timing attacks
 fn constant_time_compare(a: &str, b: &str) -> bool {
     if a.len() != b.len() {
@@ -313,4 +500,30 @@ mod tests {
     fn test_normalize_email_mixed_case() {
         assert_eq!(normalize_email("Bob@GMAIL.com"), "bob@gmail.com");
     }
+
+    #[test]
+    fn test_legacy_key_rotation_grace() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".api_key");
+        let legacy = LegacyApiKey::new("old-key".to_string(), Some(path.clone()));
+
+        let new_key = legacy.rotate(Duration::from_secs(60)).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), new_key);
+        assert_eq!(legacy.current(), new_key);
+        assert!(legacy.matches(&new_key));
+        assert!(legacy.matches("old-key"));
+
+        // Rotating again with no grace retires the previous key at once
+        let newest = legacy.rotate(Duration::ZERO).unwrap();
+        assert!(legacy.matches(&newest));
+        assert!(!legacy.matches(&new_key));
+        assert!(!legacy.matches("old-key"));
+    }
+
+    #[test]
+    fn test_legacy_key_from_env_cannot_rotate() {
+        let legacy = LegacyApiKey::new("env-key".to_string(), None);
+        assert!(legacy.rotate(Duration::from_secs(60)).is_err());
+        assert!(legacy.matches("env-key"));
+    }
 }
@@ -56,18 +56,19 @@ fn main() {
         }
     };
 
-    // Get user email from environment (optional, for family scoping).
+    // Get user email from environment (optional).
     // USER_EMAIL takes priority; falls back to DEFAULT_AUTHOR_EMAIL for backward compatibility.
-    // When set, requests are scoped to the user's family.
-    // When not set, MCP operates in god mode (access to all recipes).
+    // Family scope comes from API_KEY: a personal key acts as its owner, and
+    // the server checks it belongs to USER_EMAIL. The instance key gives god
+    // mode and is refused together with USER_EMAIL.
     let user_email = match env::var("USER_EMAIL").or_else(|_| env::var("DEFAULT_AUTHOR_EMAIL")) {
         Ok(email) => {
             let normalized = email.trim().to_lowercase();
-            tracing::info!("User email configured: {} (scoped mode)", normalized);
+            tracing::info!("User email configured: {}", normalized);
             Some(normalized)
         }
         Err(_) => {
-            tracing::info!("USER_EMAIL not set - MCP operating in god mode (all recipes accessible)");
+            tracing::info!("USER_EMAIL not set - access is whatever API_KEY allows");
             None
         }
     };
@@ -0,0 +1,58 @@
+// Mirrors Recipe Vault to a folder of Markdown files so recipes can be kept
+// in git and edited as text.
+//
+// Usage:
+//   recipe-vault-sync pull <dir>            write every recipe to <dir>
+//   recipe-vault-sync push [--prune] <dir>  send edited and new files back
+//
+// Uses the same environment as the MCP server: API_BASE_URL and API_KEY. A
+// personal API key scopes the sync to its owner's family.
+
+use recipe_vault::{mcp::http_client::ApiClient, sync};
+use std::env;
+use std::path::PathBuf;
+
+const USAGE: &str = "Usage: recipe-vault-sync pull <dir>\n       recipe-vault-sync push [--prune] <dir>";
+
+fn main() {
+    dotenvy::dotenv().ok();
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (command, prune, dir) = match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["pull", dir] => ("pull", false, PathBuf::from(dir)),
+        ["push", dir] => ("push", false, PathBuf::from(dir)),
+        ["push", "--prune", dir] | ["push", dir, "--prune"] => ("push", true, PathBuf::from(dir)),
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let Ok(api_base_url) = env::var("API_BASE_URL") else {
+        eprintln!("Error: API_BASE_URL environment variable is required");
+        eprintln!("Example: API_BASE_URL=http://192.168.1.100:3000");
+        std::process::exit(1);
+    };
+    let api_key = env::var("API_KEY").ok();
+    let user_email = env::var("USER_EMAIL").ok().map(|email| email.trim().to_lowercase());
+
+    let client = match ApiClient::new(api_base_url, api_key, user_email) {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let result = match command {
+        "pull" => sync::pull(&client, &dir),
+        _ => sync::push(&client, &dir, prune),
+    };
+    match result {
+        Ok(report) => println!("{}: {}", command, report),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
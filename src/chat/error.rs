@@ -6,15 +6,26 @@ pub enum ChatError {
     Agent(String),
     #[error("Session error: {0}")]
     Session(String),
+    #[error("{0}")]
+    Busy(String),
+    #[error(transparent)]
+    Api(#[from] crate::error::ApiError),
 }
 
 impl IntoResponse for ChatError {
     fn into_response(self) -> axum::response::Response {
+        if let ChatError::Api(error) = self {
+            return error.into_response();
+        }
+        let status = match self {
+            ChatError::Busy(_) => axum::http::StatusCode::SERVICE_UNAVAILABLE,
+            _ => axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+        };
         let body = serde_json::json!({
             "error": self.to_string()
         });
         (
-            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+            status,
             axum::Json(body),
         )
             .into_response()
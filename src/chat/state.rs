@@ -1,148 +1,332 @@
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
 
-use tokio::sync::RwLock;
+use sqlx::SqlitePool;
+use tokio::sync::{Mutex, OnceCell};
 
 use crate::ai::{AiAgent, AiAgentConfig, McpServerConfig, LlmProvider, LlmProviderType};
 use crate::ai::prompts::CHAT_SYSTEM_PROMPT;
 use crate::config::{Config, LlmProviderKind};
 use crate::chat::{ChatError, SessionStore};
+use crate::db::queries::{self, NewApiKey};
+use crate::llm_quota::LlmQuota;
+use crate::models::{
+    api_key::{api_key_prefix, generate_api_key, hash_api_key},
+    ApiKeyScope,
+};
+
+/// How often idle agents are looked for by `spawn_idle_reaper`
+const REAP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// One user's agent. Each agent owns its own MCP children, which call the
+/// API with a key minted for that user, so every tool call has the user's
+/// family scope, role and authorship.
+struct AgentSlot {
+    agent: OnceCell<RunningAgent>,
+    last_used: std::sync::Mutex<Instant>,
+}
+
+impl AgentSlot {
+    fn new() -> Self {
+        Self {
+            agent: OnceCell::new(),
+            last_used: std::sync::Mutex::new(Instant::now()),
+        }
+    }
+
+    fn touch(&self) {
+        *self.last_used.lock().unwrap() = Instant::now();
+    }
+
+    fn last_used(&self) -> Instant {
+        *self.last_used.lock().unwrap()
+    }
+
+    /// Still starting, or a chat request holds a handle to the agent
+    fn is_busy(&self) -> bool {
+        self.agent.get().is_none_or(|running| Arc::strong_count(&running.agent) > 1)
+    }
+}
+
+struct RunningAgent {
+    agent: Arc<AiAgent>,
+    /// The agent's API key, revoked when it stops
+    key_id: String,
+}
 
 #[derive(Clone)]
 pub struct ChatState {
-    agent: Arc<RwLock<Option<AiAgent>>>,
+    agents: Arc<Mutex<HashMap<String, Arc<AgentSlot>>>>,
     sessions: SessionStore,
     config: Arc<Config>,
-    api_key: Arc<String>,
+    pool: SqlitePool,
     http_client: reqwest::Client,
+    mcp_binary_path: Arc<String>,
+    llm_quota: Option<LlmQuota>,
 }
 
 impl ChatState {
-    pub fn new(config: Config, api_key: String, http_client: reqwest::Client) -> Self {
+    pub fn new(config: Config, pool: SqlitePool, http_client: reqwest::Client) -> Self {
+        let mcp_binary_path = std::env::var("MCP_BINARY_PATH")
+            .unwrap_or_else(|_| "./target/release/recipe-vault-mcp".to_string());
         Self {
-            agent: Arc::new(RwLock::new(None)),
+            agents: Arc::new(Mutex::new(HashMap::new())),
             sessions: SessionStore::new(),
             config: Arc::new(config),
-            api_key: Arc::new(api_key),
+            pool,
             http_client,
+            mcp_binary_path: Arc::new(mcp_binary_path),
+            llm_quota: None,
         }
     }
 
+    /// Use a different recipe-vault-mcp binary than `MCP_BINARY_PATH`
+    pub fn with_mcp_binary(mut self, path: impl Into<String>) -> Self {
+        self.mcp_binary_path = Arc::new(path.into());
+        self
+    }
+
+    /// Count chat LLM calls against each family's daily quota
+    pub fn with_llm_quota(mut self, quota: LlmQuota) -> Self {
+        self.llm_quota = Some(quota);
+        self
+    }
+
+    pub fn llm_quota(&self) -> Option<&LlmQuota> {
+        self.llm_quota.as_ref()
+    }
+
     pub fn sessions(&self) -> &SessionStore {
         &self.sessions
     }
 
-    pub async fn get_or_create_agent(&self, user_email: &str) -> Result<(), ChatError> {
-        let mut agent_guard = self.agent.write().await;
-        if agent_guard.is_none() {
-            let llm = if self.config.mock_llm {
-                LlmProvider::mock(self.config.mock_recipe_id.clone())
-            } else {
-                let (provider_type, api_key) = match self.config.ai_provider {
-                    LlmProviderKind::Anthropic => (
-                        LlmProviderType::Anthropic,
-                        self.config.anthropic_api_key.clone(),
-                    ),
-                    LlmProviderKind::Gemini => (
-                        LlmProviderType::Gemini,
-                        self.config.gemini_api_key.clone(),
-                    ),
-                };
-                let api_key = api_key
-                    .ok_or_else(|| ChatError::Agent("Configured LLM provider API key missing".to_string()))?;
-                LlmProvider::new(
-                    provider_type,
-                    api_key,
-                    self.config.ai_model.clone(),
-                    Some(self.http_client.clone()),
-                )
-            };
+    /// Number of agents currently running (or starting)
+    pub async fn agent_count(&self) -> usize {
+        self.agents.lock().await.len()
+    }
 
-            // Configure MCP servers
-            let mcp_binary_path = std::env::var("MCP_BINARY_PATH")
-                .unwrap_or_else(|_| "./target/release/recipe-vault-mcp".to_string());
-            let api_base_url = format!(
-                "http://127.0.0.1:{}",
-                self.config.bind_address.split(':').next_back().unwrap_or("3000")
-            );
+    /// The agent for this user, starting one if needed. At most
+    /// `chat_max_agents` run at once: the least recently used idle agent
+    /// makes room for a new one, and if every agent is busy the request
+    /// is turned away rather than spawning more processes.
+    pub async fn get_or_create_agent(&self, user_email: &str) -> Result<Arc<AiAgent>, ChatError> {
+        let key = user_email.to_lowercase();
+        let (slot, evicted) = {
+            let mut agents = self.agents.lock().await;
+            let mut evicted = None;
+            if !agents.contains_key(&key) && agents.len() >= self.config.chat_max_agents {
+                let oldest_idle = agents
+                    .iter()
+                    .filter(|(_, slot)| !slot.is_busy())
+                    .min_by_key(|(_, slot)| slot.last_used())
+                    .map(|(key, _)| key.clone())
+                    .ok_or_else(|| {
+                        ChatError::Busy("Too many chats in progress, please try again shortly".to_string())
+                    })?;
+                evicted = agents.remove(&oldest_idle);
+            }
+            let slot = agents.entry(key.clone()).or_insert_with(|| Arc::new(AgentSlot::new())).clone();
+            slot.touch();
+            (slot, evicted)
+        };
 
-            // Recipes server config
-            let recipes_server = McpServerConfig {
-                name: "recipes".to_string(),
-                command: mcp_binary_path,
-                args: vec![],
-                env: vec![
-                    ("API_BASE_URL".to_string(), api_base_url),
-                    ("API_KEY".to_string(), (*self.api_key).clone()),
-                    ("USER_EMAIL".to_string(), user_email.to_string()),
-                ]
-                .into_iter()
-                .collect(),
-            };
+        if let Some(slot) = evicted {
+            self.stop_agent(&slot).await;
+        }
 
-            // Fetch server config (only if uvx is available)
-            let mut mcp_servers = vec![recipes_server];
-
-            // Check if uvx is available before adding fetch server
-            if std::process::Command::new("uvx")
-                .arg("--version")
-                .output()
-                .is_ok()
-            {
-                let fetch_server = McpServerConfig {
-                    name: "fetch".to_string(),
-                    command: "uvx".to_string(),
-                    args: vec!["mcp-server-fetch".to_string()],
-                    env: std::collections::HashMap::new(),
-                };
-                mcp_servers.push(fetch_server);
-                tracing::info!("uvx available - fetch server enabled");
-            } else {
-                tracing::warn!(
-                    "uvx not available - fetch server disabled. Install uv to enable URL recipe fetching."
-                );
+        match slot.agent.get_or_try_init(|| self.start_agent(&key)).await {
+            Ok(running) => Ok(running.agent.clone()),
+            Err(e) => {
+                // Let the next request try again from scratch
+                let mut agents = self.agents.lock().await;
+                if agents.get(&key).is_some_and(|current| Arc::ptr_eq(current, &slot)) {
+                    agents.remove(&key);
+                }
+                Err(e)
             }
+        }
+    }
+
+    /// Stop agents nobody has chatted with for `chat_agent_idle_minutes`
+    pub async fn evict_idle(&self) -> usize {
+        let idle_for = Duration::from_secs(self.config.chat_agent_idle_minutes * 60);
+        let now = Instant::now();
+        let evicted: Vec<Arc<AgentSlot>> = {
+            let mut agents = self.agents.lock().await;
+            let idle: Vec<String> = agents
+                .iter()
+                .filter(|(_, slot)| !slot.is_busy() && now.duration_since(slot.last_used()) >= idle_for)
+                .map(|(key, _)| key.clone())
+                .collect();
+            idle.iter().filter_map(|key| agents.remove(key)).collect()
+        };
+
+        for slot in &evicted {
+            self.stop_agent(slot).await;
+        }
+        evicted.len()
+    }
 
-            // Add DuckDuckGo search server if uvx is available (no API key required)
-            if std::process::Command::new("uvx")
-                .arg("--version")
-                .output()
-                .is_ok()
-            {
-                let search_server = McpServerConfig {
-                    name: "search".to_string(),
-                    command: "uvx".to_string(),
-                    args: vec![
-                        "--from".to_string(),
-                        "duckduckgo-mcp==2.2.0".to_string(),
-                        "duckduckgo-mcp".to_string(),
-                        "serve".to_string(),
-                    ],
-                    env: std::collections::HashMap::new(),
-                };
-                mcp_servers.push(search_server);
-                tracing::info!("uvx available - DuckDuckGo search server enabled");
+    /// Periodically stop idle agents and their MCP processes
+    pub fn spawn_idle_reaper(&self) {
+        let state = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(REAP_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                let evicted = state.evict_idle().await;
+                if evicted > 0 {
+                    tracing::info!("Stopped {} idle chat agents", evicted);
+                }
             }
+        });
+    }
+
+    /// Revoke keys of agents that were running when the server last stopped
+    pub async fn revoke_stale_agent_keys(&self) -> Result<u64, ChatError> {
+        queries::revoke_chat_agent_keys(&self.pool)
+            .await
+            .map_err(|e| ChatError::Agent(format!("Failed to revoke old chat agent keys: {}", e)))
+    }
 
-            let agent_config = AiAgentConfig {
-                mcp_servers,
-                system_prompt: Some(CHAT_SYSTEM_PROMPT.to_string()),
+    async fn start_agent(&self, user_email: &str) -> Result<RunningAgent, ChatError> {
+        let llm = if self.config.mock_llm {
+            LlmProvider::mock(self.config.mock_recipe_id.clone())
+        } else {
+            let (provider_type, api_key) = match self.config.ai_provider {
+                LlmProviderKind::Anthropic => (
+                    LlmProviderType::Anthropic,
+                    self.config.anthropic_api_key.clone(),
+                ),
+                LlmProviderKind::Gemini => (
+                    LlmProviderType::Gemini,
+                    self.config.gemini_api_key.clone(),
+                ),
             };
+            let api_key = api_key
+                .ok_or_else(|| ChatError::Agent("Configured LLM provider API key missing".to_string()))?;
+            LlmProvider::new(
+                provider_type,
+                api_key,
+                self.config.ai_model.clone(),
+                Some(self.http_client.clone()),
+            )
+        };
+
+        // Configure MCP servers
+        let api_base_url = format!(
+            "http://127.0.0.1:{}",
+            self.config.bind_address.split(':').next_back().unwrap_or("3000")
+        );
+
+        // The agent's own key: it acts as this user and no one else, and
+        // isn't affected by rotating the instance key
+        let key = generate_api_key();
+        let key_id = queries::create_api_key(
+            &self.pool,
+            NewApiKey {
+                key_hash: &hash_api_key(&key),
+                prefix: &api_key_prefix(&key),
+                label: "Chat agent",
+                email: user_email,
+                scope: ApiKeyScope::ReadWrite,
+                created_by: None,
+                expires_at: None,
+                chat_agent: true,
+            },
+        )
+        .await
+        .map_err(|e| ChatError::Agent(format!("Failed to create an API key for the agent: {}", e)))?
+        .id;
+
+        // Recipes server config
+        let recipes_server = McpServerConfig {
+            name: "recipes".to_string(),
+            command: (*self.mcp_binary_path).clone(),
+            args: vec![],
+            env: vec![
+                ("API_BASE_URL".to_string(), api_base_url),
+                ("API_KEY".to_string(), key),
+                ("USER_EMAIL".to_string(), user_email.to_string()),
+            ]
+            .into_iter()
+            .collect(),
+        };
 
-            let agent = AiAgent::new(llm, agent_config);
-            agent.start().await.map_err(|e| ChatError::Agent(e.to_string()))?;
-            *agent_guard = Some(agent);
+        let mut mcp_servers = vec![recipes_server];
+
+        // Fetch and DuckDuckGo search servers (only if uvx is available, no API key required)
+        if uvx_available() {
+            mcp_servers.push(McpServerConfig {
+                name: "fetch".to_string(),
+                command: "uvx".to_string(),
+                args: vec!["mcp-server-fetch".to_string()],
+                env: std::collections::HashMap::new(),
+            });
+            mcp_servers.push(McpServerConfig {
+                name: "search".to_string(),
+                command: "uvx".to_string(),
+                args: vec![
+                    "--from".to_string(),
+                    "duckduckgo-mcp==2.2.0".to_string(),
+                    "duckduckgo-mcp".to_string(),
+                    "serve".to_string(),
+                ],
+                env: std::collections::HashMap::new(),
+            });
+        }
+
+        let agent_config = AiAgentConfig {
+            mcp_servers,
+            system_prompt: Some(CHAT_SYSTEM_PROMPT.to_string()),
+        };
+
+        tracing::info!("Starting chat agent for {}", user_email);
+        let agent = AiAgent::new(llm, agent_config);
+        if let Err(e) = agent.start().await {
+            self.revoke_agent_key(&key_id).await;
+            return Err(ChatError::Agent(e.to_string()));
         }
-        Ok(())
+        Ok(RunningAgent {
+            agent: Arc::new(agent),
+            key_id,
+        })
     }
 
-    pub async fn chat(&self, conversation: &[crate::ai::Message]) -> Result<(String, Vec<String>, Vec<String>, Vec<(f64, String)>, Vec<crate::ai::MealArtifactData>, Vec<crate::ai::Message>), ChatError> {
-        let agent_guard = self.agent.read().await;
-        let agent = agent_guard
-            .as_ref()
-            .ok_or_else(|| ChatError::Agent("Agent not initialized".to_string()))?;
-        agent
-            .chat(conversation)
-            .await
-            .map_err(|e| ChatError::Agent(e.to_string()))
+    async fn stop_agent(&self, slot: &AgentSlot) {
+        let Some(running) = slot.agent.get() else {
+            return;
+        };
+        if let Err(e) = running.agent.stop().await {
+            tracing::warn!("Failed to stop chat agent: {}", e);
+        }
+        self.revoke_agent_key(&running.key_id).await;
     }
+
+    async fn revoke_agent_key(&self, key_id: &str) {
+        if let Err(e) = queries::revoke_api_key(&self.pool, key_id, None).await {
+            tracing::warn!("Failed to revoke chat agent key {}: {}", key_id, e);
+        }
+    }
+}
+
+/// Whether uvx can be run, checked once per process
+fn uvx_available() -> bool {
+    static AVAILABLE: OnceLock<bool> = OnceLock::new();
+    *AVAILABLE.get_or_init(|| {
+        let available = std::process::Command::new("uvx")
+            .arg("--version")
+            .output()
+            .is_ok();
+        if available {
+            tracing::info!("uvx available - fetch and DuckDuckGo search servers enabled");
+        } else {
+            tracing::warn!(
+                "uvx not available - fetch server disabled. Install uv to enable URL recipe fetching."
+            );
+        }
+        available
+    })
 }
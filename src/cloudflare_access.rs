@@ -0,0 +1,154 @@
+//! Verification of Cloudflare Access tokens.
+//!
+//! Cloudflare Access signs a JWT for every request it lets through and sends
+//! it in `Cf-Access-Jwt-Assertion`. Anyone who reaches the origin directly can
+//! set the email header, but can't sign a token, so when verification is
+//! configured the email comes from the token alone.
+//!
+//! Signing keys are read from the configured JWKS file or URL and cached.
+//! Cloudflare rotates them every few weeks, so a token signed with a key we
+//! don't know triggers a refresh.
+
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use jsonwebtoken::{
+    decode, decode_header,
+    jwk::{Jwk, JwkSet},
+    Algorithm, DecodingKey, Validation,
+};
+use serde::Deserialize;
+
+use crate::config::{CloudflareAccessConfig, JwksSource};
+
+/// How long fetched keys are used before fetching them again
+const JWKS_TTL: Duration = Duration::from_secs(60 * 60);
+/// Least time between attempts to load keys, so tokens with made-up key ids
+/// or a failing endpoint can't make us hammer it
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The claims we use from an Access token
+#[derive(Debug, Clone, Deserialize)]
+pub struct AccessClaims {
+    /// Missing for service tokens
+    #[serde(default)]
+    pub email: Option<String>,
+}
+
+#[derive(Default)]
+struct KeyCache {
+    keys: Option<(Instant, JwkSet)>,
+    /// When loading keys was last tried, successfully or not
+    last_attempt: Option<Instant>,
+}
+
+#[derive(Clone)]
+pub struct AccessVerifier {
+    config: CloudflareAccessConfig,
+    http_client: reqwest::Client,
+    cache: Arc<RwLock<KeyCache>>,
+    /// Held while keys load, so requests arriving meanwhile wait for them
+    loading: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl AccessVerifier {
+    pub fn new(config: CloudflareAccessConfig, http_client: reqwest::Client) -> Self {
+        Self {
+            config,
+            http_client,
+            cache: Arc::new(RwLock::new(KeyCache::default())),
+            loading: Arc::new(tokio::sync::Mutex::new(())),
+        }
+    }
+
+    /// Check a token's signature, audience, issuer and expiry, returning its
+    /// claims
+    pub async fn verify(&self, token: &str) -> Result<AccessClaims, String> {
+        let header = decode_header(token).map_err(|e| format!("Malformed token: {}", e))?;
+        let kid = header.kid.ok_or("Token has no key id")?;
+
+        let jwk = match self.cached_key(&kid, JWKS_TTL) {
+            Some(jwk) => jwk,
+            None => {
+                // Stale keys are better than none if the refresh fails
+                let refreshed = self.refresh().await;
+                match self.cached_key(&kid, Duration::MAX) {
+                    Some(jwk) => jwk,
+                    None => {
+                        refreshed?;
+                        return Err(format!("Token signed with unknown key {}", kid));
+                    }
+                }
+            }
+        };
+
+        let key = DecodingKey::from_jwk(&jwk).map_err(|e| format!("Unusable key {}: {}", kid, e))?;
+        let mut validation = Validation::new(Algorithm::RS256);
+        validation.set_audience(&self.config.audiences);
+        validation.set_issuer(&[&self.config.issuer]);
+        validation.set_required_spec_claims(&["exp", "aud", "iss"]);
+
+        decode::<AccessClaims>(token, &key, &validation)
+            .map(|data| data.claims)
+            .map_err(|e| format!("Invalid token: {}", e))
+    }
+
+    /// The key with this id, if the cached set is younger than `max_age`
+    fn cached_key(&self, kid: &str, max_age: Duration) -> Option<Jwk> {
+        let cache = self.cache.read().unwrap();
+        let (fetched_at, keys) = cache.keys.as_ref()?;
+        if fetched_at.elapsed() > max_age {
+            return None;
+        }
+        keys.find(kid).cloned()
+    }
+
+    /// Load the key set again, unless that was tried a moment ago. Keeps the
+    /// old keys if loading fails. Callers arriving while keys load wait for
+    /// that load rather than starting another.
+    pub async fn refresh(&self) -> Result<(), String> {
+        let _loading = self.loading.lock().await;
+        {
+            let mut cache = self.cache.write().unwrap();
+            if cache
+                .last_attempt
+                .is_some_and(|attempt| attempt.elapsed() < MIN_REFRESH_INTERVAL)
+            {
+                return Ok(());
+            }
+            cache.last_attempt = Some(Instant::now());
+        }
+
+        match self.load().await {
+            Ok(keys) => {
+                tracing::debug!("Loaded {} Cloudflare Access signing keys", keys.keys.len());
+                self.cache.write().unwrap().keys = Some((Instant::now(), keys));
+                Ok(())
+            }
+            Err(e) => {
+                tracing::warn!("Failed to load Cloudflare Access signing keys: {}", e);
+                Err(e)
+            }
+        }
+    }
+
+    async fn load(&self) -> Result<JwkSet, String> {
+        let json = match &self.config.jwks {
+            JwksSource::File(path) => tokio::fs::read_to_string(path)
+                .await
+                .map_err(|e| format!("{}: {}", path.display(), e))?,
+            JwksSource::Url(url) => self
+                .http_client
+                .get(url)
+                .timeout(Duration::from_secs(10))
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(|e| format!("{}: {}", url, e))?
+                .text()
+                .await
+                .map_err(|e| format!("{}: {}", url, e))?,
+        };
+        serde_json::from_str(&json).map_err(|e| format!("Not a JSON Web Key Set: {}", e))
+    }
+}
@@ -2,7 +2,7 @@ use serde::Deserialize;
 use std::collections::HashMap;
 use std::env;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -17,8 +17,49 @@ pub struct Config {
     pub dev_user_email: Option<String>,
     pub mock_llm: bool,
     pub mock_recipe_id: Option<String>,
-    pub families_config: FamiliesConfig,
+    /// Families from families.yaml, copied into an empty database at startup.
+    /// None if the file doesn't exist.
+    pub families_config: Option<FamiliesConfig>,
     pub photos_dir: String,
+    /// Where photo files are kept; `photos_dir` is used by local storage
+    pub photo_storage: PhotoStorage,
+    /// Hours between automatic photo storage repairs; None disables them
+    pub photo_check_interval_hours: Option<u64>,
+    /// Most chat agents (each with its own MCP processes) running at once
+    pub chat_max_agents: usize,
+    /// Minutes without a chat before a user's agent is stopped
+    pub chat_agent_idle_minutes: u64,
+    /// How to verify Cloudflare Access tokens; None trusts the
+    /// `Cf-Access-Authenticated-User-Email` header as-is
+    pub cloudflare_access: Option<CloudflareAccessConfig>,
+    /// Requests per minute each user (or API key) may make to costly endpoints
+    pub rate_limits: RateLimits,
+    /// LLM calls each family may make per UTC day; None for no limit
+    pub llm_daily_calls_per_family: Option<u32>,
+    /// Hosts imports may fetch from even though they are on a private network
+    pub import_private_hosts: Vec<String>,
+}
+
+/// Per-minute request limits, each None when disabled
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RateLimits {
+    /// POST /api/chat
+    pub chat_per_minute: Option<u32>,
+    /// Creating, changing and deleting recipes, photos, imports, vault
+    /// restores and families
+    pub mutations_per_minute: Option<u32>,
+    /// Creating and revoking share links, shopping list links included
+    pub shares_per_minute: Option<u32>,
+}
+
+impl RateLimits {
+    fn from_env() -> Result<Self, String> {
+        Ok(Self {
+            chat_per_minute: parse_limit("RATE_LIMIT_CHAT_PER_MINUTE", 10)?,
+            mutations_per_minute: parse_limit("RATE_LIMIT_MUTATIONS_PER_MINUTE", 60)?,
+            shares_per_minute: parse_limit("RATE_LIMIT_SHARES_PER_MINUTE", 20)?,
+        })
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -40,11 +81,210 @@ impl LlmProviderKind {
     }
 }
 
+/// Photo storage backend, selected by PHOTO_STORAGE
+#[derive(Debug, Clone)]
+pub enum PhotoStorage {
+    /// Files in `photos_dir`
+    Local,
+    /// Objects in an S3-compatible bucket
+    S3(S3Config),
+}
+
+/// Connection settings for an S3-compatible bucket (AWS S3, MinIO, R2...)
+#[derive(Clone)]
+pub struct S3Config {
+    /// e.g. `https://s3.eu-west-2.amazonaws.com` or `http://minio:9000`
+    pub endpoint: String,
+    pub bucket: String,
+    pub region: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// Prepended to every key; empty or ending in `/`
+    pub prefix: String,
+    /// Address the bucket as `{endpoint}/{bucket}` rather than `{bucket}.{endpoint host}`
+    pub path_style: bool,
+}
+
+impl std::fmt::Debug for S3Config {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("S3Config")
+            .field("endpoint", &self.endpoint)
+            .field("bucket", &self.bucket)
+            .field("region", &self.region)
+            .field("access_key_id", &self.access_key_id)
+            .field("secret_access_key", &"<redacted>")
+            .field("prefix", &self.prefix)
+            .field("path_style", &self.path_style)
+            .finish()
+    }
+}
+
+impl PhotoStorage {
+    fn from_env() -> Result<Self, String> {
+        let kind = env::var("PHOTO_STORAGE").unwrap_or_else(|_| "local".to_string());
+        match kind.trim().to_lowercase().as_str() {
+            "local" => Ok(Self::Local),
+            "s3" => Ok(Self::S3(S3Config::from_env()?)),
+            other => Err(format!(
+                "Unsupported PHOTO_STORAGE '{}'. Expected 'local' or 's3'",
+                other
+            )),
+        }
+    }
+}
+
+impl S3Config {
+    fn from_env() -> Result<Self, String> {
+        let required = |name: &str| {
+            env::var(name)
+                .ok()
+                .filter(|value| !value.is_empty())
+                .ok_or_else(|| format!("{} must be set when PHOTO_STORAGE is s3", name))
+        };
+
+        let region = env::var("S3_REGION").unwrap_or_else(|_| "us-east-1".to_string());
+        let custom_endpoint = env::var("S3_ENDPOINT").ok().filter(|value| !value.is_empty());
+        // Self-hosted stores (MinIO) generally need path-style addressing; AWS prefers virtual-hosted
+        let path_style = match env::var("S3_PATH_STYLE") {
+            Ok(value) => parse_bool("S3_PATH_STYLE", &value)?,
+            Err(_) => custom_endpoint.is_some(),
+        };
+
+        Ok(S3Config {
+            endpoint: custom_endpoint
+                .unwrap_or_else(|| format!("https://s3.{}.amazonaws.com", region))
+                .trim_end_matches('/')
+                .to_string(),
+            bucket: required("S3_BUCKET")?,
+            access_key_id: required("S3_ACCESS_KEY_ID")?,
+            secret_access_key: required("S3_SECRET_ACCESS_KEY")?,
+            prefix: normalize_prefix(&env::var("S3_PREFIX").unwrap_or_default()),
+            path_style,
+            region,
+        })
+    }
+}
+
+/// Settings for verifying the `Cf-Access-Jwt-Assertion` token Cloudflare
+/// Access adds to every request it lets through
+#[derive(Debug, Clone)]
+pub struct CloudflareAccessConfig {
+    /// Application Audience (AUD) tags; a token must be for one of them
+    pub audiences: Vec<String>,
+    /// `https://<team>.cloudflareaccess.com`; a token must be issued by it
+    pub issuer: String,
+    /// Where the signing keys come from
+    pub jwks: JwksSource,
+}
+
+/// A JSON Web Key Set with the keys Cloudflare signs tokens with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JwksSource {
+    /// e.g. `https://<team>.cloudflareaccess.com/cdn-cgi/access/certs`
+    Url(String),
+    /// A copy of the key set on disk
+    File(PathBuf),
+}
+
+impl CloudflareAccessConfig {
+    /// From CF_ACCESS_AUD and CF_ACCESS_TEAM_DOMAIN. The keys are fetched from
+    /// the team domain unless CF_ACCESS_JWKS_URL or CF_ACCESS_JWKS_FILE says
+    /// otherwise. None if none of them are set.
+    fn from_env() -> Result<Option<Self>, String> {
+        let var = |name: &str| {
+            env::var(name)
+                .ok()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let audiences: Vec<String> = var("CF_ACCESS_AUD")
+            .map(|value| {
+                value
+                    .split(',')
+                    .map(|aud| aud.trim().to_string())
+                    .filter(|aud| !aud.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let issuer = var("CF_ACCESS_TEAM_DOMAIN").map(|domain| {
+            let domain = domain.trim_start_matches("https://").trim_end_matches('/');
+            format!("https://{}", domain)
+        });
+        let sources: Vec<JwksSource> = [
+            var("CF_ACCESS_JWKS_URL").map(JwksSource::Url),
+            var("CF_ACCESS_JWKS_FILE").map(|path| JwksSource::File(PathBuf::from(path))),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        if audiences.is_empty() && issuer.is_none() && sources.is_empty() {
+            return Ok(None);
+        }
+        if audiences.is_empty() {
+            return Err("CF_ACCESS_AUD must be set to verify Cloudflare Access tokens".to_string());
+        }
+        let Some(issuer) = issuer else {
+            return Err("CF_ACCESS_AUD needs CF_ACCESS_TEAM_DOMAIN".to_string());
+        };
+        let jwks = match sources.len() {
+            0 => JwksSource::Url(format!("{}/cdn-cgi/access/certs", issuer)),
+            1 => sources.into_iter().next().unwrap(),
+            _ => return Err("Set either CF_ACCESS_JWKS_URL or CF_ACCESS_JWKS_FILE, not both".to_string()),
+        };
+        Ok(Some(Self { audiences, issuer, jwks }))
+    }
+}
+
+/// Trim slashes and end a non-empty prefix with exactly one
+fn normalize_prefix(prefix: &str) -> String {
+    let prefix = prefix.trim().trim_matches('/');
+    if prefix.is_empty() {
+        String::new()
+    } else {
+        format!("{}/", prefix)
+    }
+}
+
+/// A whole number greater than zero from the environment, or `default` if unset
+fn parse_positive(name: &str, default: u64) -> Result<u64, String> {
+    match env::var(name) {
+        Ok(value) => match value.trim().parse::<u64>() {
+            Ok(number) if number > 0 => Ok(number),
+            _ => Err(format!("{} must be a whole number greater than zero, got '{}'", name, value)),
+        },
+        Err(_) => Ok(default),
+    }
+}
+
+/// A limit from the environment, or `default` if unset; 0 turns it off
+fn parse_limit(name: &str, default: u32) -> Result<Option<u32>, String> {
+    let limit = match env::var(name) {
+        Ok(value) => value
+            .trim()
+            .parse::<u32>()
+            .map_err(|_| format!("{} must be a whole number (0 for no limit), got '{}'", name, value))?,
+        Err(_) => default,
+    };
+    Ok((limit > 0).then_some(limit))
+}
+
+fn parse_bool(name: &str, value: &str) -> Result<bool, String> {
+    match value.trim().to_lowercase().as_str() {
+        "true" | "1" | "yes" => Ok(true),
+        "false" | "0" | "no" => Ok(false),
+        _ => Err(format!("{} must be true or false, got '{}'", name, value)),
+    }
+}
+
 /// Configuration for family-based multi-tenancy, loaded from families.yaml
 #[derive(Debug, Clone)]
 pub struct FamiliesConfig {
     /// Reverse lookup: normalized email -> list of all family member emails
     email_to_family: HashMap<String, Vec<String>>,
+    /// Reverse lookup: normalized email -> family key from the YAML file
+    email_to_family_id: HashMap<String, String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -68,17 +308,20 @@ impl FamiliesConfig {
             .map_err(|e| format!("Failed to parse families config: {}", e))?;
 
         let mut email_to_family = HashMap::new();
+        let mut email_to_family_id = HashMap::new();
 
-        for (_family_name, info) in yaml.families {
+        for (family_name, info) in yaml.families {
             let members: Vec<String> = info.members.iter().map(|e| e.to_lowercase()).collect();
 
             for email in &members {
                 email_to_family.insert(email.clone(), members.clone());
+                email_to_family_id.insert(email.clone(), family_name.clone());
             }
         }
 
         Ok(FamiliesConfig {
             email_to_family,
+            email_to_family_id,
         })
     }
 
@@ -88,6 +331,24 @@ impl FamiliesConfig {
     pub fn get_family_members(&self, email: &str) -> Option<&Vec<String>> {
         self.email_to_family.get(&email.to_lowercase())
     }
+
+    /// Get the family key (as named in families.yaml) for a given email address.
+    /// Returns None if the email is not in any family.
+    pub fn get_family_id(&self, email: &str) -> Option<&str> {
+        self.email_to_family_id.get(&email.to_lowercase()).map(|s| s.as_str())
+    }
+
+    /// Every family key with its members, sorted by key
+    pub fn families(&self) -> Vec<(String, Vec<String>)> {
+        let mut families: Vec<(String, Vec<String>)> = self
+            .email_to_family_id
+            .iter()
+            .map(|(email, family_id)| (family_id.clone(), self.email_to_family[email].clone()))
+            .collect();
+        families.sort();
+        families.dedup();
+        families
+    }
 }
 
 impl Config {
@@ -136,11 +397,45 @@ impl Config {
 
         let families_config_path = env::var("FAMILIES_CONFIG_PATH")
             .unwrap_or_else(|_| "/app/data/families.yaml".to_string());
-        let families_config = FamiliesConfig::load(Path::new(&families_config_path))?;
+        let families_config = if Path::new(&families_config_path).exists() {
+            Some(FamiliesConfig::load(Path::new(&families_config_path))?)
+        } else {
+            None
+        };
 
         let photos_dir = env::var("PHOTOS_DIR")
             .unwrap_or_else(|_| "./data/photos".to_string());
 
+        let photo_storage = PhotoStorage::from_env()?;
+
+        let photo_check_interval_hours = match env::var("PHOTO_CHECK_INTERVAL_HOURS") {
+            Ok(value) => {
+                let hours: u64 = value.trim().parse().map_err(|_| {
+                    format!("PHOTO_CHECK_INTERVAL_HOURS must be a whole number of hours, got '{}'", value)
+                })?;
+                (hours > 0).then_some(hours)
+            }
+            Err(_) => None,
+        };
+
+        let chat_max_agents = parse_positive("CHAT_MAX_AGENTS", 10)? as usize;
+        let chat_agent_idle_minutes = parse_positive("CHAT_AGENT_IDLE_MINUTES", 30)?;
+
+        let cloudflare_access = CloudflareAccessConfig::from_env()?;
+
+        let rate_limits = RateLimits::from_env()?;
+        let llm_daily_calls_per_family = parse_limit("LLM_DAILY_CALLS_PER_FAMILY", 500)?;
+
+        let import_private_hosts = env::var("IMPORT_ALLOW_PRIVATE_HOSTS")
+            .map(|value| {
+                value
+                    .split(',')
+                    .map(|host| host.trim().to_lowercase())
+                    .filter(|host| !host.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
         Ok(Config {
             database_url,
             bind_address,
@@ -155,6 +450,14 @@ impl Config {
             mock_recipe_id,
             families_config,
             photos_dir,
+            photo_storage,
+            photo_check_interval_hours,
+            chat_max_agents,
+            chat_agent_idle_minutes,
+            cloudflare_access,
+            rate_limits,
+            llm_daily_calls_per_family,
+            import_private_hosts,
         })
     }
 }
@@ -289,6 +592,30 @@ families:
         assert!(config.get_family_members("unknown@example.com").is_none());
     }
 
+    #[test]
+    fn test_get_family_id() {
+        let yaml = r#"
+families:
+  hewitt-family:
+    members:
+      - Alice@Example.com
+  friend-family:
+    members:
+      - charlie@example.com
+"#;
+        let file = write_temp_yaml(yaml);
+        let config = FamiliesConfig::load(file.path()).unwrap();
+
+        assert_eq!(config.get_family_id("alice@example.com"), Some("hewitt-family"));
+        assert_eq!(config.get_family_id("CHARLIE@example.com"), Some("friend-family"));
+        assert_eq!(config.get_family_id("unknown@example.com"), None);
+
+        let families = config.families();
+        assert_eq!(families.len(), 2);
+        assert_eq!(families[0], ("friend-family".to_string(), vec!["charlie@example.com".to_string()]));
+        assert_eq!(families[1], ("hewitt-family".to_string(), vec!["alice@example.com".to_string()]));
+    }
+
     #[test]
     fn test_config_file_missing() {
         let result = FamiliesConfig::load(Path::new("/nonexistent/families.yaml"));
@@ -296,6 +623,14 @@ families:
         assert!(result.unwrap_err().contains("Failed to read"));
     }
 
+    #[test]
+    fn test_normalize_s3_prefix() {
+        assert_eq!(normalize_prefix(""), "");
+        assert_eq!(normalize_prefix("/"), "");
+        assert_eq!(normalize_prefix("photos"), "photos/");
+        assert_eq!(normalize_prefix("/vault/photos/"), "vault/photos/");
+    }
+
     #[test]
     fn test_parse_provider_defaults_supported_values() {
         assert_eq!(LlmProviderKind::parse("anthropic").unwrap(), LlmProviderKind::Anthropic);
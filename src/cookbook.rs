@@ -0,0 +1,410 @@
+//! Printable family cookbook: one self-contained HTML document with a title
+//! page, table of contents, one recipe per page and an ingredient index.
+//!
+//! Recipes are numbered in book order and the contents and index refer to
+//! those numbers, since browsers can't print page numbers into links.
+
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::handlers::share::html_escape;
+use crate::models::RecipeWithDetails;
+use crate::shopping::{format_quantity, normalize_ingredient_name};
+
+/// Heading for recipes without tags when the contents are grouped by tag
+const UNTAGGED: &str = "Other recipes";
+
+/// How the table of contents is organised
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TocGrouping {
+    /// By first letter of the title
+    #[default]
+    Alphabetical,
+    /// Under each tag; recipes with several tags appear under each
+    Tag,
+}
+
+#[derive(Debug, Clone)]
+pub struct CookbookOptions {
+    pub title: String,
+    pub subtitle: Option<String>,
+    pub toc: TocGrouping,
+}
+
+/// Render the cookbook. `photos` maps recipe IDs to image data URIs.
+pub fn render_cookbook(
+    recipes: &[RecipeWithDetails],
+    photos: &HashMap<String, String>,
+    options: &CookbookOptions,
+) -> String {
+    let subtitle = options
+        .subtitle
+        .as_deref()
+        .map(|s| format!("<p class=\"subtitle\">{}</p>", html_escape(s)))
+        .unwrap_or_default();
+    let count = match recipes.len() {
+        1 => "1 recipe".to_string(),
+        n => format!("{} recipes", n),
+    };
+
+    let pages: String = recipes
+        .iter()
+        .enumerate()
+        .map(|(index, recipe)| recipe_page(index + 1, recipe, photos.get(&recipe.recipe.id)))
+        .collect();
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>
+*{{margin:0;padding:0;box-sizing:border-box}}
+body{{font-family:Georgia,'Times New Roman',serif;max-width:760px;margin:0 auto;padding:24px 16px;color:#333;background:#faf9f6;line-height:1.5}}
+a{{color:inherit;text-decoration:none}}
+section{{padding:32px 0;border-bottom:1px dashed #e0d6c8}}
+.title-page{{text-align:center;padding:120px 0}}
+.title-page h1{{font-size:2.6em;color:#2c1810;margin-bottom:12px}}
+.subtitle{{font-size:1.2em;color:#666;font-style:italic}}
+.count{{margin-top:48px;color:#999}}
+h2{{font-size:1.6em;color:#2c1810;margin-bottom:16px}}
+h3{{font-size:1.1em;color:#2c1810;margin:20px 0 8px;border-bottom:1px solid #e0d6c8;padding-bottom:2px}}
+.toc ul,.index ul{{list-style:none}}
+.toc li,.index li{{display:flex;gap:8px;margin:2px 0}}
+.toc .leader{{flex:1;border-bottom:1px dotted #bbb;margin-bottom:6px}}
+.number{{color:#999;font-variant-numeric:tabular-nums}}
+.recipe .number{{display:block;font-size:0.9em;margin-bottom:4px}}
+.meta{{color:#666;margin:8px 0;font-size:0.95em}}
+.description{{color:#555;margin:8px 0;font-style:italic}}
+.recipe-photo{{display:block;width:100%;max-height:9cm;object-fit:cover;border-radius:6px;margin:12px 0}}
+.recipe ul,.recipe ol{{padding-left:22px}}
+.recipe li{{margin:4px 0}}
+.ingredients{{columns:2;column-gap:32px}}
+.notes{{color:#888;font-style:italic}}
+.tags{{margin-top:16px;color:#999;font-size:0.85em}}
+.index ul{{columns:2;column-gap:32px}}
+.index li{{break-inside:avoid}}
+.index .refs{{color:#999}}
+@page{{margin:18mm 16mm}}
+@media print{{
+body{{background:#fff;max-width:none;padding:0;font-size:11pt}}
+section{{border:none;padding:0}}
+.title-page{{padding-top:35vh;break-after:page}}
+.toc,.recipe,.index{{break-before:page}}
+h2,h3{{break-after:avoid}}
+li,.recipe-photo,.meta{{break-inside:avoid}}
+.recipe-photo{{max-height:8cm}}
+}}
+</style>
+</head>
+<body>
+<section class="title-page">
+<h1>{title}</h1>
+{subtitle}
+<p class="count">{count}</p>
+</section>
+{toc}
+{pages}
+{index}
+</body>
+</html>"#,
+        title = html_escape(&options.title),
+        subtitle = subtitle,
+        count = count,
+        toc = table_of_contents(recipes, options.toc),
+        pages = pages,
+        index = ingredient_index(recipes),
+    )
+}
+
+fn anchor(number: usize) -> String {
+    format!("recipe-{}", number)
+}
+
+fn table_of_contents(recipes: &[RecipeWithDetails], grouping: TocGrouping) -> String {
+    // heading -> (title, number), each group sorted by title
+    let mut groups: BTreeMap<String, Vec<(&str, usize)>> = BTreeMap::new();
+    let mut untagged = Vec::new();
+    for (index, recipe) in recipes.iter().enumerate() {
+        let entry = (recipe.recipe.title.as_str(), index + 1);
+        match grouping {
+            TocGrouping::Alphabetical => {
+                let letter = recipe
+                    .recipe
+                    .title
+                    .chars()
+                    .next()
+                    .filter(|c| c.is_alphabetic())
+                    .map(|c| c.to_uppercase().to_string())
+                    .unwrap_or_else(|| "#".to_string());
+                groups.entry(letter).or_default().push(entry);
+            }
+            TocGrouping::Tag if recipe.tags.is_empty() => untagged.push(entry),
+            TocGrouping::Tag => {
+                for tag in &recipe.tags {
+                    groups.entry(tag.clone()).or_default().push(entry);
+                }
+            }
+        }
+    }
+
+    let mut groups: Vec<(String, Vec<(&str, usize)>)> = groups.into_iter().collect();
+    // Tags sort case-insensitively, with untagged recipes last
+    groups.sort_by_key(|(heading, _)| heading.to_lowercase());
+    if !untagged.is_empty() {
+        groups.push((UNTAGGED.to_string(), untagged));
+    }
+
+    let body: String = groups
+        .into_iter()
+        .map(|(heading, mut entries)| {
+            entries.sort_by_key(|(title, number)| (title.to_lowercase(), *number));
+            let items: String = entries
+                .iter()
+                .map(|(title, number)| {
+                    format!(
+                        "<li><a href=\"#{}\">{}</a><span class=\"leader\"></span><span class=\"number\">{}</span></li>",
+                        anchor(*number),
+                        html_escape(title),
+                        number
+                    )
+                })
+                .collect();
+            format!("<h3>{}</h3>\n<ul>{}</ul>\n", html_escape(&heading), items)
+        })
+        .collect();
+
+    format!("<section class=\"toc\">\n<h2>Contents</h2>\n{}</section>", body)
+}
+
+fn recipe_page(number: usize, recipe: &RecipeWithDetails, photo: Option<&String>) -> String {
+    let r = &recipe.recipe;
+
+    let mut meta_items = Vec::new();
+    if let Some(servings) = r.servings {
+        meta_items.push(format!("Serves {}", servings));
+    }
+    if let Some(prep) = r.prep_time_minutes {
+        meta_items.push(format!("Prep {} min", prep));
+    }
+    if let Some(cook) = r.cook_time_minutes {
+        meta_items.push(format!("Cook {} min", cook));
+    }
+    if let Some(difficulty) = r.difficulty {
+        let dots: String = (1..=5).map(|i| if i <= difficulty { '●' } else { '○' }).collect();
+        meta_items.push(format!("Difficulty {}", dots));
+    }
+    let meta = if meta_items.is_empty() {
+        String::new()
+    } else {
+        format!("<p class=\"meta\">{}</p>\n", meta_items.join(" &middot; "))
+    };
+
+    let description = r
+        .description
+        .as_deref()
+        .map(|d| format!("<p class=\"description\">{}</p>\n", html_escape(d)))
+        .unwrap_or_default();
+    let photo = photo
+        .map(|src| {
+            format!(
+                "<img class=\"recipe-photo\" src=\"{}\" alt=\"{}\">\n",
+                html_escape(src),
+                html_escape(&r.title)
+            )
+        })
+        .unwrap_or_default();
+
+    let ingredients: String = recipe
+        .ingredients
+        .iter()
+        .map(|ing| {
+            let quantity = ing.quantity.map(|q| format!("{} ", format_quantity(q))).unwrap_or_default();
+            let unit = ing.unit.as_deref().map(|u| format!("{} ", u)).unwrap_or_default();
+            let notes = ing
+                .notes
+                .as_deref()
+                .map(|n| format!(" <span class=\"notes\">({})</span>", html_escape(n)))
+                .unwrap_or_default();
+            format!("<li>{}{}{}</li>", html_escape(&format!("{}{}", quantity, unit)), html_escape(&ing.name), notes)
+        })
+        .collect();
+    let steps: String = recipe
+        .steps
+        .iter()
+        .map(|step| {
+            let mut details = Vec::new();
+            if let Some(minutes) = step.duration_minutes {
+                details.push(format!("{} min", minutes));
+            }
+            if let (Some(value), Some(unit)) = (step.temperature_value, step.temperature_unit.as_deref()) {
+                details.push(format!("{}°{}", value, unit.chars().next().unwrap_or('C')));
+            }
+            let details = if details.is_empty() {
+                String::new()
+            } else {
+                format!(" <span class=\"notes\">({})</span>", html_escape(&details.join(", ")))
+            };
+            format!("<li>{}{}</li>", html_escape(&step.instruction), details)
+        })
+        .collect();
+
+    let tags = if recipe.tags.is_empty() {
+        String::new()
+    } else {
+        format!("<p class=\"tags\">{}</p>\n", html_escape(&recipe.tags.join(" · ")))
+    };
+
+    format!(
+        "<section class=\"recipe\" id=\"{anchor}\">\n<span class=\"number\">No. {number}</span>\n<h2>{title}</h2>\n{meta}{description}{photo}<h3>Ingredients</h3>\n<ul class=\"ingredients\">{ingredients}</ul>\n<h3>Method</h3>\n<ol>{steps}</ol>\n{tags}</section>\n",
+        anchor = anchor(number),
+        number = number,
+        title = html_escape(&r.title),
+    )
+}
+
+fn ingredient_index(recipes: &[RecipeWithDetails]) -> String {
+    // ingredient -> recipe numbers using it
+    let mut index: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (i, recipe) in recipes.iter().enumerate() {
+        for ingredient in &recipe.ingredients {
+            let name = normalize_ingredient_name(&ingredient.name);
+            if name.is_empty() {
+                continue;
+            }
+            let numbers = index.entry(name).or_default();
+            if !numbers.contains(&(i + 1)) {
+                numbers.push(i + 1);
+            }
+        }
+    }
+    if index.is_empty() {
+        return String::new();
+    }
+
+    let items: String = index
+        .iter()
+        .map(|(name, numbers)| {
+            let refs: Vec<String> = numbers
+                .iter()
+                .map(|n| format!("<a href=\"#{}\">{}</a>", anchor(*n), n))
+                .collect();
+            format!(
+                "<li><span>{}</span><span class=\"refs\">{}</span></li>",
+                html_escape(name),
+                refs.join(", ")
+            )
+        })
+        .collect();
+    format!("<section class=\"index\">\n<h2>Ingredient index</h2>\n<ul>{}</ul>\n</section>", items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Recipe, RecipeIngredient};
+
+    fn recipe(title: &str, tags: &[&str], ingredients: &[&str]) -> RecipeWithDetails {
+        let id = Recipe::new_id();
+        RecipeWithDetails {
+            recipe: Recipe {
+                id: id.clone(),
+                title: title.to_string(),
+                description: None,
+                prep_time_minutes: None,
+                cook_time_minutes: None,
+                servings: Some(4),
+                difficulty: None,
+                photo_filename: None,
+                source_url: None,
+                shared_by: None,
+                created_at: String::new(),
+                updated_at: String::new(),
+                created_by: None,
+                updated_by: None,
+                family_id: None,
+            },
+            ingredients: ingredients
+                .iter()
+                .enumerate()
+                .map(|(position, name)| RecipeIngredient {
+                    id: Recipe::new_id(),
+                    recipe_id: id.clone(),
+                    position: position as i32,
+                    name: name.to_string(),
+                    quantity: None,
+                    unit: None,
+                    notes: None,
+                })
+                .collect(),
+            steps: vec![],
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            photos: vec![],
+            source_scans: vec![],
+        }
+    }
+
+    fn options(toc: TocGrouping) -> CookbookOptions {
+        CookbookOptions {
+            title: "The Smith Family Cookbook".to_string(),
+            subtitle: None,
+            toc,
+        }
+    }
+
+    #[test]
+    fn test_alphabetical_contents() {
+        let recipes = vec![recipe("Scones", &[], &[]), recipe("apple pie", &[], &[]), recipe("Stew", &[], &[])];
+        let toc = table_of_contents(&recipes, TocGrouping::Alphabetical);
+        let a = toc.find("<h3>A</h3>").unwrap();
+        let s = toc.find("<h3>S</h3>").unwrap();
+        assert!(a < s);
+        // Entries keep their book numbers and sort by title within a letter
+        assert!(toc.find("Scones").unwrap() < toc.find("Stew").unwrap());
+        assert!(toc.contains("<a href=\"#recipe-2\">apple pie</a>"));
+    }
+
+    #[test]
+    fn test_contents_by_tag() {
+        let recipes = vec![
+            recipe("Scones", &["Baking", "Tea"], &[]),
+            recipe("Stew", &[], &[]),
+            recipe("Bread", &["baking"], &[]),
+        ];
+        let toc = table_of_contents(&recipes, TocGrouping::Tag);
+        assert_eq!(toc.matches("#recipe-1\"").count(), 2);
+        assert!(toc.find("<h3>Tea</h3>").unwrap() < toc.find("<h3>Other recipes</h3>").unwrap());
+        assert!(toc.find("<h3>Baking</h3>").unwrap() < toc.find("<h3>baking</h3>").unwrap_or(usize::MAX));
+    }
+
+    #[test]
+    fn test_ingredient_index() {
+        let recipes = vec![
+            recipe("Scones", &[], &["Plain flour", "Butter", "butter"]),
+            recipe("Shortbread", &[], &["butter", "Sugar"]),
+        ];
+        let index = ingredient_index(&recipes);
+        assert!(index.contains(
+            "<li><span>butter</span><span class=\"refs\"><a href=\"#recipe-1\">1</a>, <a href=\"#recipe-2\">2</a></span></li>"
+        ));
+        assert!(index.find("plain flour").unwrap() < index.find("sugar").unwrap());
+    }
+
+    #[test]
+    fn test_render_escapes_and_embeds_photos() {
+        let mut pie = recipe("Pie <3", &[], &["apples"]);
+        pie.recipe.description = Some("Grandma's".to_string());
+        let photos = HashMap::from([(pie.recipe.id.clone(), "data:image/png;base64,AAAA".to_string())]);
+
+        let html = render_cookbook(&[pie], &photos, &options(TocGrouping::Alphabetical));
+        assert!(html.contains("<h1>The Smith Family Cookbook</h1>"));
+        assert!(html.contains("<p class=\"count\">1 recipe</p>"));
+        assert!(html.contains("<h2>Pie &lt;3</h2>"));
+        assert!(html.contains("Grandma&#x27;s"));
+        assert!(html.contains("src=\"data:image/png;base64,AAAA\""));
+        assert!(html.contains(".toc,.recipe,.index{break-before:page}"));
+    }
+}
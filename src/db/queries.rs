@@ -1,26 +1,92 @@
-use sqlx::SqlitePool;
+use std::collections::BTreeMap;
+
+use sqlx::{SqliteConnection, SqlitePool};
 use uuid::Uuid;
 
 use crate::{
     error::{ApiError, ApiResult},
     models::{
-        recipe::{CreateRecipeInput, UpdateRecipeInput},
-        RecipeIngredient, Recipe, RecipeWithDetails, ShareLink, Step,
+        recipe::{
+            normalize_tags, validate_tags, CreateIngredientInput, CreateRecipeInput, CreateStepInput,
+            UpdateRecipeInput,
+        },
+        photo::MAX_PHOTOS_PER_RECIPE,
+        ApiKey, ApiKeyScope, Family, FamilyInvitation, FamilyRole, FamilyWithMembers, RecipeIngredient, Recipe, RecipePhoto,
+        RecipeWithDetails, ShareKind, ShareLink, SharedRecipe, ShoppingListLink, Step,
     },
+    shopping::AisleConfig,
 };
 
-/// Build a SQL IN clause with placeholders for the given number of items.
-/// Returns (clause_string, bindings) where clause_string is like "LOWER(created_by) IN (?, ?, ?)"
-fn family_filter_clause(family_members: &[String]) -> String {
-    let placeholders: Vec<&str> = family_members.iter().map(|_| "?").collect();
-    format!("LOWER(created_by) IN ({})", placeholders.join(", "))
+/// Insert a recipe's ingredients in list order
+async fn insert_ingredients(
+    conn: &mut SqliteConnection,
+    recipe_id: &str,
+    ingredients: &[CreateIngredientInput],
+) -> ApiResult<()> {
+    for (position, ingredient) in ingredients.iter().enumerate() {
+        let ingredient_id = Uuid::new_v4().to_string();
+        sqlx::query(
+            "INSERT INTO ingredients (id, recipe_id, position, name, quantity, unit, notes)
+             VALUES (?, ?, ?, ?, ?, ?, ?)"
+        )
+        .bind(&ingredient_id)
+        .bind(recipe_id)
+        .bind(position as i32)
+        .bind(&ingredient.name)
+        .bind(ingredient.quantity)
+        .bind(&ingredient.unit)
+        .bind(&ingredient.notes)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Insert a recipe's steps in list order
+async fn insert_steps(
+    conn: &mut SqliteConnection,
+    recipe_id: &str,
+    steps: &[CreateStepInput],
+) -> ApiResult<()> {
+    for (position, step) in steps.iter().enumerate() {
+        let step_id = Uuid::new_v4().to_string();
+        sqlx::query(
+            "INSERT INTO steps (id, recipe_id, position, instruction, duration_minutes, temperature_value, temperature_unit)
+             VALUES (?, ?, ?, ?, ?, ?, ?)"
+        )
+        .bind(&step_id)
+        .bind(recipe_id)
+        .bind(position as i32)
+        .bind(&step.instruction)
+        .bind(step.duration_minutes)
+        .bind(step.temperature_value)
+        .bind(&step.temperature_unit)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Insert a recipe's tags, normalized
+async fn insert_tags(conn: &mut SqliteConnection, recipe_id: &str, tags: &[String]) -> ApiResult<()> {
+    for tag in normalize_tags(tags) {
+        sqlx::query("INSERT INTO recipe_tags (recipe_id, tag) VALUES (?, ?)")
+            .bind(recipe_id)
+            .bind(&tag)
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
 }
 
-/// Create a new recipe with ingredients and steps
+/// Create a new recipe with ingredients and steps.
+/// The recipe belongs to `family_id`; in god mode (None) it goes to the
+/// creator's family, if they have one.
 pub async fn create_recipe(
     pool: &SqlitePool,
     input: CreateRecipeInput,
     user_email: Option<String>,
+    family_id: Option<&str>,
 ) -> ApiResult<RecipeWithDetails> {
     // Validate input
     input.validate()?;
@@ -44,8 +110,8 @@ pub async fn create_recipe(
 
     // Insert recipe
     sqlx::query(
-        "INSERT INTO recipes (id, title, description, prep_time_minutes, cook_time_minutes, servings, difficulty, created_by, updated_by)
-         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"
+        "INSERT INTO recipes (id, title, description, prep_time_minutes, cook_time_minutes, servings, difficulty, source_url, shared_by, created_by, updated_by, family_id)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, COALESCE(?, (SELECT family_id FROM family_members WHERE email = LOWER(?))))"
     )
     .bind(&recipe_id)
     .bind(&input.title)
@@ -54,46 +120,18 @@ pub async fn create_recipe(
     .bind(input.cook_time_minutes)
     .bind(input.servings)
     .bind(input.difficulty)
+    .bind(&input.source_url)
+    .bind(&input.shared_by)
+    .bind(&user_email)
     .bind(&user_email)
+    .bind(family_id)
     .bind(&user_email)
     .execute(&mut *tx)
     .await?;
 
-    // Insert ingredients
-    for (position, ingredient) in input.ingredients.iter().enumerate() {
-        let ingredient_id = Uuid::new_v4().to_string();
-        sqlx::query(
-            "INSERT INTO ingredients (id, recipe_id, position, name, quantity, unit, notes)
-             VALUES (?, ?, ?, ?, ?, ?, ?)"
-        )
-        .bind(&ingredient_id)
-        .bind(&recipe_id)
-        .bind(position as i32)
-        .bind(&ingredient.name)
-        .bind(ingredient.quantity)
-        .bind(&ingredient.unit)
-        .bind(&ingredient.notes)
-        .execute(&mut *tx)
-        .await?;
-    }
-
-    // Insert steps
-    for (position, step) in input.steps.iter().enumerate() {
-        let step_id = Uuid::new_v4().to_string();
-        sqlx::query(
-            "INSERT INTO steps (id, recipe_id, position, instruction, duration_minutes, temperature_value, temperature_unit)
-             VALUES (?, ?, ?, ?, ?, ?, ?)"
-        )
-        .bind(&step_id)
-        .bind(&recipe_id)
-        .bind(position as i32)
-        .bind(&step.instruction)
-        .bind(step.duration_minutes)
-        .bind(step.temperature_value)
-        .bind(&step.temperature_unit)
-        .execute(&mut *tx)
-        .await?;
-    }
+    insert_ingredients(&mut tx, &recipe_id, &input.ingredients).await?;
+    insert_steps(&mut tx, &recipe_id, &input.steps).await?;
+    insert_tags(&mut tx, &recipe_id, &input.tags).await?;
 
     tx.commit().await?;
 
@@ -102,26 +140,24 @@ pub async fn create_recipe(
 }
 
 /// Get a recipe by ID with all ingredients and steps.
-/// When family_members is Some, only returns the recipe if created_by is in the list.
-/// When family_members is None (god mode), returns any recipe.
+/// When family_id is Some, only returns the recipe if it belongs to that family.
+/// When family_id is None (god mode), returns any recipe.
 pub async fn get_recipe(
     pool: &SqlitePool,
     recipe_id: &str,
-    family_members: Option<&[String]>,
+    family_id: Option<&str>,
 ) -> ApiResult<RecipeWithDetails> {
     // Fetch recipe with optional family filtering
-    let recipe: Option<Recipe> = match family_members {
-        Some(members) if !members.is_empty() => {
-            let filter = family_filter_clause(members);
-            let sql = format!("SELECT * FROM recipes WHERE id = ? AND {}", filter);
-            let mut query = sqlx::query_as(&sql).bind(recipe_id);
-            for member in members {
-                query = query.bind(member);
-            }
-            query.fetch_optional(pool).await?
+    let recipe: Option<Recipe> = match family_id {
+        Some(family_id) => {
+            sqlx::query_as("SELECT * FROM recipes WHERE id = ? AND family_id = ?")
+                .bind(recipe_id)
+                .bind(family_id)
+                .fetch_optional(pool)
+                .await?
         }
-        _ => {
-            // God mode or empty members — no filtering
+        None => {
+            // God mode — no filtering
             sqlx::query_as("SELECT * FROM recipes WHERE id = ?")
                 .bind(recipe_id)
                 .fetch_optional(pool)
@@ -147,32 +183,42 @@ pub async fn get_recipe(
     .fetch_all(pool)
     .await?;
 
+    let tags: Vec<(String,)> = sqlx::query_as(
+        "SELECT tag FROM recipe_tags WHERE recipe_id = ? ORDER BY LOWER(tag)"
+    )
+    .bind(recipe_id)
+    .fetch_all(pool)
+    .await?;
+
+    let photos = list_recipe_photos(pool, recipe_id).await?;
+    let source_scans = list_recipe_source_scans(pool, recipe_id).await?;
+
     Ok(RecipeWithDetails {
         recipe,
         ingredients,
         steps,
+        tags: tags.into_iter().map(|(tag,)| tag).collect(),
+        photos,
+        source_scans,
     })
 }
 
 /// List all recipes (without ingredients/steps).
-/// When family_members is Some, only returns recipes created by family members.
-/// When family_members is None (god mode), returns all recipes.
+/// When family_id is Some, only returns the family's recipes.
+/// When family_id is None (god mode), returns all recipes.
 pub async fn list_recipes(
     pool: &SqlitePool,
-    family_members: Option<&[String]>,
+    family_id: Option<&str>,
 ) -> ApiResult<Vec<Recipe>> {
-    let recipes = match family_members {
-        Some(members) if !members.is_empty() => {
-            let filter = family_filter_clause(members);
-            let sql = format!("SELECT * FROM recipes WHERE {} ORDER BY LOWER(title)", filter);
-            let mut query = sqlx::query_as(&sql);
-            for member in members {
-                query = query.bind(member);
-            }
-            query.fetch_all(pool).await?
+    let recipes = match family_id {
+        Some(family_id) => {
+            sqlx::query_as("SELECT * FROM recipes WHERE family_id = ? ORDER BY LOWER(title)")
+                .bind(family_id)
+                .fetch_all(pool)
+                .await?
         }
-        _ => {
-            // God mode or empty members — return all
+        None => {
+            // God mode — return all
             sqlx::query_as("SELECT * FROM recipes ORDER BY LOWER(title)")
                 .fetch_all(pool)
                 .await?
@@ -183,29 +229,27 @@ pub async fn list_recipes(
 }
 
 /// Update a recipe.
-/// When family_members is Some, only updates if the recipe was created by a family member.
-/// When family_members is None (god mode), updates any recipe.
+/// When family_id is Some, only updates if the recipe belongs to that family.
+/// When family_id is None (god mode), updates any recipe.
 pub async fn update_recipe(
     pool: &SqlitePool,
     recipe_id: &str,
     input: UpdateRecipeInput,
     user_email: Option<String>,
-    family_members: Option<&[String]>,
+    family_id: Option<&str>,
 ) -> ApiResult<RecipeWithDetails> {
     let mut tx = pool.begin().await?;
 
     // Check if recipe exists (with family filtering)
-    let exists: Option<(i32,)> = match family_members {
-        Some(members) if !members.is_empty() => {
-            let filter = family_filter_clause(members);
-            let sql = format!("SELECT 1 FROM recipes WHERE id = ? AND {}", filter);
-            let mut query = sqlx::query_as(&sql).bind(recipe_id);
-            for member in members {
-                query = query.bind(member);
-            }
-            query.fetch_optional(&mut *tx).await?
+    let exists: Option<(i32,)> = match family_id {
+        Some(family_id) => {
+            sqlx::query_as("SELECT 1 FROM recipes WHERE id = ? AND family_id = ?")
+                .bind(recipe_id)
+                .bind(family_id)
+                .fetch_optional(&mut *tx)
+                .await?
         }
-        _ => {
+        None => {
             sqlx::query_as("SELECT 1 FROM recipes WHERE id = ?")
                 .bind(recipe_id)
                 .fetch_optional(&mut *tx)
@@ -315,22 +359,7 @@ pub async fn update_recipe(
             .execute(&mut *tx)
             .await?;
 
-        for (position, ingredient) in ingredients.iter().enumerate() {
-            let ingredient_id = Uuid::new_v4().to_string();
-            sqlx::query(
-                "INSERT INTO ingredients (id, recipe_id, position, name, quantity, unit, notes)
-                 VALUES (?, ?, ?, ?, ?, ?, ?)"
-            )
-            .bind(&ingredient_id)
-            .bind(recipe_id)
-            .bind(position as i32)
-            .bind(&ingredient.name)
-            .bind(ingredient.quantity)
-            .bind(&ingredient.unit)
-            .bind(&ingredient.notes)
-            .execute(&mut *tx)
-            .await?;
-        }
+        insert_ingredients(&mut tx, recipe_id, &ingredients).await?;
     }
 
     // Replace steps if provided
@@ -347,22 +376,25 @@ pub async fn update_recipe(
             .execute(&mut *tx)
             .await?;
 
-        for (position, step) in steps.iter().enumerate() {
-            let step_id = Uuid::new_v4().to_string();
-            sqlx::query(
-                "INSERT INTO steps (id, recipe_id, position, instruction, duration_minutes, temperature_value, temperature_unit)
-                 VALUES (?, ?, ?, ?, ?, ?, ?)"
-            )
-            .bind(&step_id)
+        insert_steps(&mut tx, recipe_id, &steps).await?;
+    }
+
+    // Replace tags if provided
+    if let Some(tags) = input.tags {
+        validate_tags(&tags)?;
+
+        sqlx::query("DELETE FROM recipe_tags WHERE recipe_id = ?")
             .bind(recipe_id)
-            .bind(position as i32)
-            .bind(&step.instruction)
-            .bind(step.duration_minutes)
-            .bind(step.temperature_value)
-            .bind(&step.temperature_unit)
             .execute(&mut *tx)
             .await?;
-        }
+
+        sqlx::query("UPDATE recipes SET updated_at = datetime('now'), updated_by = ? WHERE id = ?")
+            .bind(&user_email)
+            .bind(recipe_id)
+            .execute(&mut *tx)
+            .await?;
+
+        insert_tags(&mut tx, recipe_id, &tags).await?;
     }
 
     tx.commit().await?;
@@ -371,101 +403,1281 @@ pub async fn update_recipe(
     get_recipe(pool, recipe_id, None).await
 }
 
-/// Delete a recipe (cascade deletes ingredients and steps).
-/// When family_members is Some, only deletes if the recipe was created by a family member.
-/// When family_members is None (god mode), deletes any recipe.
-pub async fn delete_recipe(
+/// Find a recipe by title (case-insensitive). Titles are unique across all
+/// families, so this is not family filtered.
+pub async fn find_recipe_by_title(pool: &SqlitePool, title: &str) -> ApiResult<Option<Recipe>> {
+    let recipe: Option<Recipe> = sqlx::query_as("SELECT * FROM recipes WHERE LOWER(title) = LOWER(?)")
+        .bind(title)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(recipe)
+}
+
+/// Overwrite every field of an existing recipe, replacing its ingredients
+/// and steps. Unlike `update_recipe`, missing optional fields are cleared.
+/// The caller is responsible for access checks.
+pub async fn replace_recipe(
     pool: &SqlitePool,
     recipe_id: &str,
-    family_members: Option<&[String]>,
-) -> ApiResult<()> {
-    let result = match family_members {
-        Some(members) if !members.is_empty() => {
-            let filter = family_filter_clause(members);
-            let sql = format!("DELETE FROM recipes WHERE id = ? AND {}", filter);
-            let mut query = sqlx::query(&sql).bind(recipe_id);
-            for member in members {
-                query = query.bind(member);
-            }
-            query.execute(pool).await?
-        }
-        _ => {
-            sqlx::query("DELETE FROM recipes WHERE id = ?")
-                .bind(recipe_id)
-                .execute(pool)
-                .await?
-        }
-    };
+    input: CreateRecipeInput,
+    user_email: Option<String>,
+) -> ApiResult<RecipeWithDetails> {
+    input.validate()?;
+
+    let mut tx = pool.begin().await?;
+
+    let existing: Option<(i32,)> = sqlx::query_as(
+        "SELECT 1 FROM recipes WHERE LOWER(title) = LOWER(?) AND id != ?"
+    )
+    .bind(&input.title)
+    .bind(recipe_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    if existing.is_some() {
+        return Err(ApiError::Conflict(input.title));
+    }
+
+    let result = sqlx::query(
+        "UPDATE recipes SET title = ?, description = ?, prep_time_minutes = ?, cook_time_minutes = ?,
+            servings = ?, difficulty = ?, source_url = ?, shared_by = ?, updated_at = datetime('now'), updated_by = ?
+         WHERE id = ?"
+    )
+    .bind(&input.title)
+    .bind(&input.description)
+    .bind(input.prep_time_minutes)
+    .bind(input.cook_time_minutes)
+    .bind(input.servings)
+    .bind(input.difficulty)
+    .bind(&input.source_url)
+    .bind(&input.shared_by)
+    .bind(&user_email)
+    .bind(recipe_id)
+    .execute(&mut *tx)
+    .await?;
 
     if result.rows_affected() == 0 {
         return Err(ApiError::NotFound(recipe_id.to_string()));
     }
 
-    Ok(())
+    sqlx::query("DELETE FROM ingredients WHERE recipe_id = ?")
+        .bind(recipe_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM steps WHERE recipe_id = ?")
+        .bind(recipe_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM recipe_tags WHERE recipe_id = ?")
+        .bind(recipe_id)
+        .execute(&mut *tx)
+        .await?;
+    insert_ingredients(&mut tx, recipe_id, &input.ingredients).await?;
+    insert_steps(&mut tx, recipe_id, &input.steps).await?;
+    insert_tags(&mut tx, recipe_id, &input.tags).await?;
+
+    tx.commit().await?;
+
+    get_recipe(pool, recipe_id, None).await
 }
 
-/// Insert a new share link
-pub async fn create_share_link(
-    pool: &SqlitePool,
-    token: &str,
-    recipe_id: &str,
-    created_by: &str,
-    expires_at: &str,
-) -> ApiResult<ShareLink> {
-    sqlx::query(
-        "INSERT INTO share_links (token, recipe_id, created_by, expires_at) VALUES (?, ?, ?, ?)"
+const PHOTO_COLUMNS: &str = "p.id, p.recipe_id, p.filename, p.position, p.caption, p.step_position, \
+     COALESCE(p.filename = r.photo_filename, 0) AS is_cover, p.source_scan, p.created_at";
+
+/// A recipe's photos in gallery order, without source scans. Callers check
+/// family access on the recipe.
+pub async fn list_recipe_photos(pool: &SqlitePool, recipe_id: &str) -> ApiResult<Vec<RecipePhoto>> {
+    list_photos_of_kind(pool, recipe_id, false).await
+}
+
+/// A recipe's source scans in the order they were attached
+pub async fn list_recipe_source_scans(pool: &SqlitePool, recipe_id: &str) -> ApiResult<Vec<RecipePhoto>> {
+    list_photos_of_kind(pool, recipe_id, true).await
+}
+
+async fn list_photos_of_kind(pool: &SqlitePool, recipe_id: &str, source_scan: bool) -> ApiResult<Vec<RecipePhoto>> {
+    let sql = format!(
+        "SELECT {} FROM recipe_photos p JOIN recipes r ON r.id = p.recipe_id \
+         WHERE p.recipe_id = ? AND p.source_scan = ? ORDER BY p.position, p.created_at",
+        PHOTO_COLUMNS
+    );
+    Ok(sqlx::query_as(&sql).bind(recipe_id).bind(source_scan).fetch_all(pool).await?)
+}
+
+/// One photo of a recipe
+pub async fn get_recipe_photo(pool: &SqlitePool, recipe_id: &str, photo_id: &str) -> ApiResult<RecipePhoto> {
+    let sql = format!(
+        "SELECT {} FROM recipe_photos p JOIN recipes r ON r.id = p.recipe_id \
+         WHERE p.recipe_id = ? AND p.id = ?",
+        PHOTO_COLUMNS
+    );
+    sqlx::query_as(&sql)
+        .bind(recipe_id)
+        .bind(photo_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Photo {}", photo_id)))
+}
+
+/// A photo to add to a recipe's gallery; the file is already written
+#[derive(Debug, Clone)]
+pub struct NewRecipePhoto<'a> {
+    pub id: &'a str,
+    pub filename: &'a str,
+    pub caption: Option<&'a str>,
+    pub step_position: Option<i32>,
+    /// Make it the cover. The first photo always becomes the cover.
+    pub cover: bool,
+    /// Add it as a source scan rather than a gallery photo; never the cover
+    pub source_scan: bool,
+}
+
+/// Append a photo to the end of a recipe's gallery, or of its source scans.
+/// Source scans count towards the photo limit.
+pub async fn add_recipe_photo(pool: &SqlitePool, recipe_id: &str, photo: NewRecipePhoto<'_>) -> ApiResult<RecipePhoto> {
+    let mut tx = pool.begin().await?;
+
+    let (count, next_position): (i64, i32) = sqlx::query_as(
+        "SELECT COUNT(*), COALESCE(MAX(CASE WHEN source_scan = ? THEN position END) + 1, 0)
+         FROM recipe_photos WHERE recipe_id = ?"
     )
-    .bind(token)
+    .bind(photo.source_scan)
     .bind(recipe_id)
-    .bind(created_by)
-    .bind(expires_at)
-    .execute(pool)
+    .fetch_one(&mut *tx)
     .await?;
+    if count as usize >= MAX_PHOTOS_PER_RECIPE {
+        return Err(ApiError::Validation(format!(
+            "A recipe can have at most {} photos",
+            MAX_PHOTOS_PER_RECIPE
+        )));
+    }
 
-    let link: ShareLink = sqlx::query_as(
-        "SELECT * FROM share_links WHERE token = ?"
+    sqlx::query(
+        "INSERT INTO recipe_photos (id, recipe_id, filename, position, caption, step_position, source_scan)
+         VALUES (?, ?, ?, ?, ?, ?, ?)"
     )
-    .bind(token)
-    .fetch_one(pool)
+    .bind(photo.id)
+    .bind(recipe_id)
+    .bind(photo.filename)
+    .bind(next_position)
+    .bind(photo.caption)
+    .bind(photo.step_position)
+    .bind(photo.source_scan)
+    .execute(&mut *tx)
     .await?;
 
-    Ok(link)
+    if !photo.source_scan {
+        let cover_sql = if photo.cover {
+            "UPDATE recipes SET photo_filename = ? WHERE id = ?"
+        } else {
+            "UPDATE recipes SET photo_filename = ? WHERE id = ? AND photo_filename IS NULL"
+        };
+        sqlx::query(cover_sql)
+            .bind(photo.filename)
+            .bind(recipe_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+    get_recipe_photo(pool, recipe_id, photo.id).await
 }
 
-/// Look up a share link by token. Returns None if not found or expired.
-pub async fn get_share_link(
+/// Point a photo at a new file (a replacement upload), keeping it the cover if it was
+pub async fn replace_recipe_photo_file(
     pool: &SqlitePool,
-    token: &str,
-) -> ApiResult<Option<ShareLink>> {
-    let link: Option<ShareLink> = sqlx::query_as(
-        "SELECT * FROM share_links WHERE token = ?"
-    )
-    .bind(token)
-    .fetch_optional(pool)
-    .await?;
+    recipe_id: &str,
+    photo_id: &str,
+    filename: &str,
+) -> ApiResult<RecipePhoto> {
+    let existing = get_recipe_photo(pool, recipe_id, photo_id).await?;
+    let mut tx = pool.begin().await?;
+    sqlx::query("UPDATE recipe_photos SET filename = ? WHERE id = ?")
+        .bind(filename)
+        .bind(photo_id)
+        .execute(&mut *tx)
+        .await?;
+    if existing.is_cover {
+        sqlx::query("UPDATE recipes SET photo_filename = ? WHERE id = ?")
+            .bind(filename)
+            .bind(recipe_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+    get_recipe_photo(pool, recipe_id, photo_id).await
+}
 
-    Ok(link)
+/// Change a photo's caption and step link (already validated), or make it the
+/// cover. Source scans can only have their caption changed.
+pub async fn update_recipe_photo(
+    pool: &SqlitePool,
+    recipe_id: &str,
+    photo_id: &str,
+    caption: Option<Option<&str>>,
+    step_position: Option<Option<i32>>,
+    cover: bool,
+) -> ApiResult<RecipePhoto> {
+    let photo = get_recipe_photo(pool, recipe_id, photo_id).await?;
+    if photo.source_scan && (cover || matches!(step_position, Some(Some(_)))) {
+        return Err(ApiError::Validation(
+            "A source scan can't be the cover or be linked to a step".to_string(),
+        ));
+    }
+    let mut tx = pool.begin().await?;
+    if let Some(caption) = caption {
+        sqlx::query("UPDATE recipe_photos SET caption = ? WHERE id = ?")
+            .bind(caption)
+            .bind(photo_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    if let Some(step_position) = step_position {
+        sqlx::query("UPDATE recipe_photos SET step_position = ? WHERE id = ?")
+            .bind(step_position)
+            .bind(photo_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    if cover {
+        sqlx::query("UPDATE recipes SET photo_filename = ? WHERE id = ?")
+            .bind(&photo.filename)
+            .bind(recipe_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+    get_recipe_photo(pool, recipe_id, photo_id).await
 }
 
-/// Get a recipe with full details via a share token (no family filtering).
-/// Returns None if the token doesn't exist. Caller should check expiry.
-pub async fn get_recipe_by_share_token(
+/// Put a recipe's gallery photos in the given order, which must list each
+/// photo once. Source scans keep their own order.
+pub async fn reorder_recipe_photos(
     pool: &SqlitePool,
-    token: &str,
-) -> ApiResult<Option<RecipeWithDetails>> {
-    let link: Option<ShareLink> = sqlx::query_as(
-        "SELECT * FROM share_links WHERE token = ?"
-    )
-    .bind(token)
-    .fetch_optional(pool)
-    .await?;
+    recipe_id: &str,
+    photo_ids: &[String],
+) -> ApiResult<Vec<RecipePhoto>> {
+    let existing = list_recipe_photos(pool, recipe_id).await?;
+    let mut requested: Vec<&str> = photo_ids.iter().map(String::as_str).collect();
+    let mut current: Vec<&str> = existing.iter().map(|p| p.id.as_str()).collect();
+    requested.sort_unstable();
+    current.sort_unstable();
+    if requested != current {
+        return Err(ApiError::Validation(
+            "photo_ids must list every gallery photo of the recipe exactly once".to_string(),
+        ));
+    }
 
-    let link = match link {
-        Some(l) => l,
+    let mut tx = pool.begin().await?;
+    for (position, photo_id) in photo_ids.iter().enumerate() {
+        sqlx::query("UPDATE recipe_photos SET position = ? WHERE id = ?")
+            .bind(position as i32)
+            .bind(photo_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+    list_recipe_photos(pool, recipe_id).await
+}
+
+/// Remove a photo from a recipe's gallery. If it was the cover, the first
+/// remaining photo takes over. Returns the removed photo so its files can be
+/// deleted.
+pub async fn delete_recipe_photo(pool: &SqlitePool, recipe_id: &str, photo_id: &str) -> ApiResult<RecipePhoto> {
+    let photo = get_recipe_photo(pool, recipe_id, photo_id).await?;
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM recipe_photos WHERE id = ?")
+        .bind(photo_id)
+        .execute(&mut *tx)
+        .await?;
+    if photo.is_cover {
+        sqlx::query(
+            "UPDATE recipes SET photo_filename = (
+                SELECT filename FROM recipe_photos WHERE recipe_id = ? AND source_scan = 0
+                ORDER BY position, created_at LIMIT 1
+             ) WHERE id = ?"
+        )
+        .bind(recipe_id)
+        .bind(recipe_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(photo)
+}
+
+/// Remove every photo and source scan from a recipe. Returns them so their
+/// files can be deleted.
+pub async fn delete_recipe_photos(pool: &SqlitePool, recipe_id: &str) -> ApiResult<Vec<RecipePhoto>> {
+    let mut photos = list_recipe_photos(pool, recipe_id).await?;
+    photos.extend(list_recipe_source_scans(pool, recipe_id).await?);
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM recipe_photos WHERE recipe_id = ?")
+        .bind(recipe_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("UPDATE recipes SET photo_filename = NULL WHERE id = ?")
+        .bind(recipe_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(photos)
+}
+
+/// Every photo row across all recipes and families, for storage checks
+pub async fn list_all_recipe_photos(pool: &SqlitePool) -> ApiResult<Vec<RecipePhoto>> {
+    let sql = format!(
+        "SELECT {} FROM recipe_photos p JOIN recipes r ON r.id = p.recipe_id \
+         ORDER BY p.recipe_id, p.position, p.created_at",
+        PHOTO_COLUMNS
+    );
+    Ok(sqlx::query_as(&sql).fetch_all(pool).await?)
+}
+
+const DANGLING_COVER: &str = "photo_filename IS NOT NULL AND photo_filename NOT IN (
+     SELECT filename FROM recipe_photos WHERE recipe_id = recipes.id AND source_scan = 0
+ )";
+
+/// Recipes whose `photo_filename` names none of their own gallery photos
+pub async fn list_dangling_covers(pool: &SqlitePool) -> ApiResult<Vec<String>> {
+    let sql = format!("SELECT id FROM recipes WHERE {} ORDER BY id", DANGLING_COVER);
+    Ok(sqlx::query_scalar(&sql).fetch_all(pool).await?)
+}
+
+/// Point dangling covers at the recipe's first photo, or clear them.
+/// Returns the number of recipes changed.
+pub async fn repair_dangling_covers(pool: &SqlitePool) -> ApiResult<u64> {
+    let sql = format!(
+        "UPDATE recipes SET photo_filename = (
+            SELECT filename FROM recipe_photos WHERE recipe_id = recipes.id AND source_scan = 0
+            ORDER BY position, created_at LIMIT 1
+         ) WHERE {}",
+        DANGLING_COVER
+    );
+    Ok(sqlx::query(&sql).execute(pool).await?.rows_affected())
+}
+
+/// Delete a recipe (cascade deletes ingredients and steps).
+/// When family_id is Some, only deletes if the recipe belongs to that family.
+/// When family_id is None (god mode), deletes any recipe.
+pub async fn delete_recipe(
+    pool: &SqlitePool,
+    recipe_id: &str,
+    family_id: Option<&str>,
+) -> ApiResult<()> {
+    let result = match family_id {
+        Some(family_id) => {
+            sqlx::query("DELETE FROM recipes WHERE id = ? AND family_id = ?")
+                .bind(recipe_id)
+                .bind(family_id)
+                .execute(pool)
+                .await?
+        }
+        None => {
+            sqlx::query("DELETE FROM recipes WHERE id = ?")
+                .bind(recipe_id)
+                .execute(pool)
+                .await?
+        }
+    };
+
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound(recipe_id.to_string()));
+    }
+
+    Ok(())
+}
+
+/// Settings shared by recipe and collection share links
+pub struct NewShareLink<'a> {
+    pub token: &'a str,
+    pub created_by: &'a str,
+    /// None (god mode) takes the family of the shared recipe
+    pub family_id: Option<&'a str>,
+    /// None never expires
+    pub expires_at: Option<&'a str>,
+    pub max_views: Option<i64>,
+    pub passphrase_hash: Option<&'a str>,
+    pub single_use: bool,
+}
+
+/// Insert a new share link to one recipe
+pub async fn create_share_link(
+    pool: &SqlitePool,
+    recipe_id: &str,
+    link: NewShareLink<'_>,
+) -> ApiResult<ShareLink> {
+    let token = link.token;
+    sqlx::query(
+        "INSERT INTO share_links (token, recipe_id, created_by, family_id, expires_at, max_views, passphrase_hash, single_use)
+         VALUES (?, ?, ?, COALESCE(?, (SELECT family_id FROM recipes WHERE id = ?)), ?, ?, ?, ?)"
+    )
+    .bind(token)
+    .bind(recipe_id)
+    .bind(link.created_by)
+    .bind(link.family_id)
+    .bind(recipe_id)
+    .bind(link.expires_at)
+    .bind(link.max_views)
+    .bind(link.passphrase_hash)
+    .bind(link.single_use)
+    .execute(pool)
+    .await?;
+
+    let link: ShareLink = sqlx::query_as(
+        "SELECT * FROM share_links WHERE token = ?"
+    )
+    .bind(token)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(link)
+}
+
+/// Look up a share link by token. Returns None if not found or expired.
+pub async fn get_share_link(
+    pool: &SqlitePool,
+    token: &str,
+) -> ApiResult<Option<ShareLink>> {
+    let link: Option<ShareLink> = sqlx::query_as(
+        "SELECT * FROM share_links WHERE token = ?"
+    )
+    .bind(token)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(link)
+}
+
+/// Insert a share link to a set of recipes, kept in the given order
+pub async fn create_collection_share_link(
+    pool: &SqlitePool,
+    kind: ShareKind,
+    title: &str,
+    guest_count: Option<i32>,
+    recipes: &[(String, Option<String>)],
+    link: NewShareLink<'_>,
+) -> ApiResult<ShareLink> {
+    let token = link.token;
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        "INSERT INTO share_links (token, kind, title, guest_count, created_by, family_id, expires_at, max_views, passphrase_hash, single_use)
+         VALUES (?, ?, ?, ?, ?, COALESCE(?, (SELECT family_id FROM recipes WHERE id = ?)), ?, ?, ?, ?)"
+    )
+    .bind(token)
+    .bind(kind)
+    .bind(title)
+    .bind(guest_count)
+    .bind(link.created_by)
+    .bind(link.family_id)
+    .bind(recipes.first().map(|(recipe_id, _)| recipe_id))
+    .bind(link.expires_at)
+    .bind(link.max_views)
+    .bind(link.passphrase_hash)
+    .bind(link.single_use)
+    .execute(&mut *tx)
+    .await?;
+
+    for (position, (recipe_id, role)) in recipes.iter().enumerate() {
+        sqlx::query("INSERT INTO share_link_recipes (token, recipe_id, position, role) VALUES (?, ?, ?, ?)")
+            .bind(token)
+            .bind(recipe_id)
+            .bind(position as i64)
+            .bind(role)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let link: ShareLink = sqlx::query_as("SELECT * FROM share_links WHERE token = ?")
+        .bind(token)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(link)
+}
+
+/// The recipes of a collection share link, in page order
+pub async fn list_share_link_recipes(
+    pool: &SqlitePool,
+    token: &str,
+) -> ApiResult<Vec<SharedRecipe>> {
+    let recipes = sqlx::query_as(
+        "SELECT s.recipe_id, r.title, s.role, r.photo_filename IS NOT NULL AS has_photo FROM share_link_recipes s
+         JOIN recipes r ON r.id = s.recipe_id
+         WHERE s.token = ? ORDER BY s.position"
+    )
+    .bind(token)
+    .fetch_all(pool)
+    .await?;
+    Ok(recipes)
+}
+
+/// IDs of the recipes visible to the caller that carry `tag` (any case),
+/// ordered by title
+pub async fn list_recipe_ids_with_tag(
+    pool: &SqlitePool,
+    tag: &str,
+    family_id: Option<&str>,
+) -> ApiResult<Vec<String>> {
+    let ids: Vec<(String,)> = match family_id {
+        Some(family_id) => {
+            sqlx::query_as(
+                "SELECT r.id FROM recipes r JOIN recipe_tags t ON t.recipe_id = r.id
+                 WHERE LOWER(t.tag) = LOWER(?) AND r.family_id = ? ORDER BY LOWER(r.title)"
+            )
+            .bind(tag)
+            .bind(family_id)
+            .fetch_all(pool)
+            .await?
+        }
+        None => {
+            sqlx::query_as(
+                "SELECT r.id FROM recipes r JOIN recipe_tags t ON t.recipe_id = r.id
+                 WHERE LOWER(t.tag) = LOWER(?) ORDER BY LOWER(r.title)"
+            )
+            .bind(tag)
+            .fetch_all(pool)
+            .await?
+        }
+    };
+    Ok(ids.into_iter().map(|(id,)| id).collect())
+}
+
+/// List the family's share links.
+/// When family_id is None (god mode), lists every share link.
+pub async fn list_share_links(
+    pool: &SqlitePool,
+    family_id: Option<&str>,
+) -> ApiResult<Vec<ShareLink>> {
+    let links = match family_id {
+        Some(family_id) => {
+            sqlx::query_as("SELECT * FROM share_links WHERE family_id = ? ORDER BY created_at")
+                .bind(family_id)
+                .fetch_all(pool)
+                .await?
+        }
+        None => {
+            sqlx::query_as("SELECT * FROM share_links ORDER BY created_at")
+                .fetch_all(pool)
+                .await?
+        }
+    };
+
+    Ok(links)
+}
+
+/// Count a view of a share page. Returns false, without counting, once the
+/// link has reached its view limit. The view of a single-use link starts its
+/// grace period: it expires `grace_minutes` later, or sooner if it already would.
+pub async fn record_share_link_view(pool: &SqlitePool, token: &str, grace_minutes: i64) -> ApiResult<bool> {
+    let grace = format!("+{} minutes", grace_minutes);
+    let result = sqlx::query(
+        "UPDATE share_links SET view_count = view_count + 1, last_accessed_at = datetime('now'),
+            expires_at = CASE
+                WHEN single_use AND (expires_at IS NULL OR expires_at > datetime('now', ?)) THEN datetime('now', ?)
+                ELSE expires_at
+            END
+         WHERE token = ? AND (max_views IS NULL OR view_count < max_views) AND (NOT single_use OR view_count = 0)"
+    )
+    .bind(&grace)
+    .bind(&grace)
+    .bind(token)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Record a passphrase attempt for a share link, unless `max_attempts` were
+/// already made in the last `window_minutes`. Counting and recording are one
+/// statement, so parallel guesses can't all slip under the limit. Returns
+/// the attempt's id, or None when the limit is reached.
+pub async fn reserve_share_link_attempt(
+    pool: &SqlitePool,
+    token: &str,
+    window_minutes: i64,
+    max_attempts: i64,
+) -> ApiResult<Option<i64>> {
+    let window = format!("-{} minutes", window_minutes);
+    sqlx::query("DELETE FROM share_link_attempts WHERE token = ? AND attempted_at <= datetime('now', ?)")
+        .bind(token)
+        .bind(&window)
+        .execute(pool)
+        .await?;
+    let result = sqlx::query(
+        "INSERT INTO share_link_attempts (token)
+         SELECT ? WHERE (
+             SELECT COUNT(*) FROM share_link_attempts WHERE token = ? AND attempted_at > datetime('now', ?)
+         ) < ?"
+    )
+    .bind(token)
+    .bind(token)
+    .bind(&window)
+    .bind(max_attempts)
+    .execute(pool)
+    .await?;
+    Ok((result.rows_affected() > 0).then(|| result.last_insert_rowid()))
+}
+
+/// Forget a passphrase attempt that turned out to be right
+pub async fn delete_share_link_attempt(pool: &SqlitePool, attempt_id: i64) -> ApiResult<()> {
+    sqlx::query("DELETE FROM share_link_attempts WHERE rowid = ?")
+        .bind(attempt_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Revoke a share link.
+/// When family_id is Some, only the family's own links can be revoked.
+pub async fn delete_share_link(
+    pool: &SqlitePool,
+    token: &str,
+    family_id: Option<&str>,
+) -> ApiResult<()> {
+    let result = match family_id {
+        Some(family_id) => {
+            sqlx::query("DELETE FROM share_links WHERE token = ? AND family_id = ?")
+                .bind(token)
+                .bind(family_id)
+                .execute(pool)
+                .await?
+        }
+        None => {
+            sqlx::query("DELETE FROM share_links WHERE token = ?")
+                .bind(token)
+                .execute(pool)
+                .await?
+        }
+    };
+
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound(format!("Share link {}", token)));
+    }
+    Ok(())
+}
+
+/// Get a recipe with full details via a share token (no family filtering).
+/// Returns None if the token doesn't exist. Caller should check expiry.
+pub async fn get_recipe_by_share_token(
+    pool: &SqlitePool,
+    token: &str,
+) -> ApiResult<Option<RecipeWithDetails>> {
+    let link: Option<ShareLink> = sqlx::query_as(
+        "SELECT * FROM share_links WHERE token = ?"
+    )
+    .bind(token)
+    .fetch_optional(pool)
+    .await?;
+
+    let link = match link {
+        Some(l) => l,
         None => return Ok(None),
     };
 
+    let Some(recipe_id) = link.recipe_id else {
+        return Ok(None);
+    };
+
     // Fetch recipe without family filtering (share links bypass tenancy)
-    let recipe = get_recipe(pool, &link.recipe_id, None).await?;
+    let recipe = get_recipe(pool, &recipe_id, None).await?;
     Ok(Some(recipe))
 }
+
+/// Get a family's aisle order for shopping lists. Returns None if the family
+/// has not configured one.
+pub async fn get_aisle_config(
+    pool: &SqlitePool,
+    family_id: &str,
+) -> ApiResult<Option<AisleConfig>> {
+    let row: Option<(String, String)> = sqlx::query_as(
+        "SELECT aisles, ingredient_aisles FROM aisle_orders WHERE family_id = ?"
+    )
+    .bind(family_id)
+    .fetch_optional(pool)
+    .await?;
+
+    let Some((aisles, ingredient_aisles)) = row else {
+        return Ok(None);
+    };
+
+    let config = AisleConfig {
+        aisles: serde_json::from_str(&aisles)
+            .map_err(|e| ApiError::Internal(format!("Corrupt aisle order for {}: {}", family_id, e)))?,
+        ingredient_aisles: serde_json::from_str(&ingredient_aisles)
+            .map_err(|e| ApiError::Internal(format!("Corrupt aisle overrides for {}: {}", family_id, e)))?,
+    };
+    Ok(Some(config))
+}
+
+/// Insert or replace a family's aisle order
+pub async fn save_aisle_config(
+    pool: &SqlitePool,
+    family_id: &str,
+    config: &AisleConfig,
+    updated_by: Option<&str>,
+) -> ApiResult<()> {
+    let aisles = serde_json::to_string(&config.aisles)
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let ingredient_aisles = serde_json::to_string(&config.ingredient_aisles)
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    sqlx::query(
+        "INSERT INTO aisle_orders (family_id, aisles, ingredient_aisles, updated_by, updated_at)
+         VALUES (?, ?, ?, ?, datetime('now'))
+         ON CONFLICT(family_id) DO UPDATE SET
+            aisles = excluded.aisles,
+            ingredient_aisles = excluded.ingredient_aisles,
+            updated_by = excluded.updated_by,
+            updated_at = excluded.updated_at"
+    )
+    .bind(family_id)
+    .bind(aisles)
+    .bind(ingredient_aisles)
+    .bind(updated_by)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Insert a new public shopping list link
+pub async fn create_shopping_list_link(
+    pool: &SqlitePool,
+    token: &str,
+    recipe_ids: &[String],
+    family_id: Option<&str>,
+    created_by: &str,
+    expires_at: &str,
+) -> ApiResult<ShoppingListLink> {
+    let recipe_ids = serde_json::to_string(recipe_ids)
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    sqlx::query(
+        "INSERT INTO shopping_list_links (token, recipe_ids, family_id, created_by, expires_at)
+         VALUES (?, ?, ?, ?, ?)"
+    )
+    .bind(token)
+    .bind(&recipe_ids)
+    .bind(family_id)
+    .bind(created_by)
+    .bind(expires_at)
+    .execute(pool)
+    .await?;
+
+    let link: ShoppingListLink = sqlx::query_as(
+        "SELECT * FROM shopping_list_links WHERE token = ?"
+    )
+    .bind(token)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(link)
+}
+
+/// Look up a shopping list link by token. Caller should check expiry.
+pub async fn get_shopping_list_link(
+    pool: &SqlitePool,
+    token: &str,
+) -> ApiResult<Option<ShoppingListLink>> {
+    let link: Option<ShoppingListLink> = sqlx::query_as(
+        "SELECT * FROM shopping_list_links WHERE token = ?"
+    )
+    .bind(token)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(link)
+}
+
+/// List a family's shopping list links. When family_id is None (god mode),
+/// lists every link.
+pub async fn list_shopping_list_links(
+    pool: &SqlitePool,
+    family_id: Option<&str>,
+) -> ApiResult<Vec<ShoppingListLink>> {
+    let links = match family_id {
+        Some(family_id) => {
+            sqlx::query_as("SELECT * FROM shopping_list_links WHERE family_id = ? ORDER BY created_at")
+                .bind(family_id)
+                .fetch_all(pool)
+                .await?
+        }
+        None => {
+            sqlx::query_as("SELECT * FROM shopping_list_links ORDER BY created_at")
+                .fetch_all(pool)
+                .await?
+        }
+    };
+
+    Ok(links)
+}
+
+/// The family an email belongs to, the email's role in it and all of that
+/// family's members. Returns None if the email is not in any family.
+pub async fn get_family_membership(
+    pool: &SqlitePool,
+    email: &str,
+) -> ApiResult<Option<(String, FamilyRole, Vec<String>)>> {
+    let membership: Option<(String, FamilyRole)> =
+        sqlx::query_as("SELECT family_id, role FROM family_members WHERE email = ?")
+            .bind(email)
+            .fetch_optional(pool)
+            .await?;
+    let Some((family_id, role)) = membership else {
+        return Ok(None);
+    };
+
+    let members = list_family_members(pool, &family_id).await?.into_keys().collect();
+    Ok(Some((family_id, role, members)))
+}
+
+async fn list_family_members(pool: &SqlitePool, family_id: &str) -> ApiResult<BTreeMap<String, FamilyRole>> {
+    let members: Vec<(String, FamilyRole)> =
+        sqlx::query_as("SELECT email, role FROM family_members WHERE family_id = ? ORDER BY email")
+            .bind(family_id)
+            .fetch_all(pool)
+            .await?;
+    Ok(members.into_iter().collect())
+}
+
+fn with_members(family: Family, roles: BTreeMap<String, FamilyRole>) -> FamilyWithMembers {
+    FamilyWithMembers {
+        family,
+        members: roles.keys().cloned().collect(),
+        roles,
+    }
+}
+
+pub async fn list_families(pool: &SqlitePool) -> ApiResult<Vec<FamilyWithMembers>> {
+    let families: Vec<Family> = sqlx::query_as("SELECT * FROM families ORDER BY id")
+        .fetch_all(pool)
+        .await?;
+
+    let mut result = Vec::with_capacity(families.len());
+    for family in families {
+        let roles = list_family_members(pool, &family.id).await?;
+        result.push(with_members(family, roles));
+    }
+    Ok(result)
+}
+
+pub async fn get_family(pool: &SqlitePool, family_id: &str) -> ApiResult<FamilyWithMembers> {
+    let family: Family = sqlx::query_as("SELECT * FROM families WHERE id = ?")
+        .bind(family_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Family {}", family_id)))?;
+    let roles = list_family_members(pool, family_id).await?;
+    Ok(with_members(family, roles))
+}
+
+/// Fail with a conflict if the email already belongs to a family other than `family_id`
+async fn ensure_not_in_other_family(conn: &mut SqliteConnection, email: &str, family_id: &str) -> ApiResult<()> {
+    let current: Option<String> = sqlx::query_scalar("SELECT family_id FROM family_members WHERE email = ?")
+        .bind(email)
+        .fetch_optional(&mut *conn)
+        .await?;
+    match current {
+        Some(current) if current != family_id => Err(ApiError::Conflict(format!(
+            "{} already belongs to family {}",
+            email, current
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Create a family with its first members, who become its owners. Emails
+/// must already be normalized.
+pub async fn create_family(
+    pool: &SqlitePool,
+    family_id: &str,
+    name: &str,
+    members: &[String],
+    added_by: Option<&str>,
+) -> ApiResult<FamilyWithMembers> {
+    let mut tx = pool.begin().await?;
+
+    let exists: Option<i64> = sqlx::query_scalar("SELECT 1 FROM families WHERE id = ?")
+        .bind(family_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+    if exists.is_some() {
+        return Err(ApiError::Conflict(format!("Family {} already exists", family_id)));
+    }
+
+    sqlx::query("INSERT INTO families (id, name) VALUES (?, ?)")
+        .bind(family_id)
+        .bind(name)
+        .execute(&mut *tx)
+        .await?;
+
+    for email in members {
+        ensure_not_in_other_family(&mut tx, email, family_id).await?;
+        sqlx::query("INSERT OR IGNORE INTO family_members (email, family_id, added_by, role) VALUES (?, ?, ?, ?)")
+            .bind(email)
+            .bind(family_id)
+            .bind(added_by)
+            .bind(FamilyRole::Owner)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+    get_family(pool, family_id).await
+}
+
+/// Delete a family, its memberships and open invitations. Recipes are kept.
+pub async fn delete_family(pool: &SqlitePool, family_id: &str) -> ApiResult<()> {
+    let result = sqlx::query("DELETE FROM families WHERE id = ?")
+        .bind(family_id)
+        .execute(pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound(format!("Family {}", family_id)));
+    }
+    Ok(())
+}
+
+/// Add a member to a family with the given role. Adding an existing member is
+/// a no-op (use `set_family_member_role`); an email can't be in two families.
+pub async fn add_family_member(
+    pool: &SqlitePool,
+    family_id: &str,
+    email: &str,
+    role: FamilyRole,
+    added_by: Option<&str>,
+) -> ApiResult<()> {
+    let mut tx = pool.begin().await?;
+    add_family_member_in(&mut tx, family_id, email, role, added_by).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+async fn add_family_member_in(
+    conn: &mut SqliteConnection,
+    family_id: &str,
+    email: &str,
+    role: FamilyRole,
+    added_by: Option<&str>,
+) -> ApiResult<()> {
+    let exists: Option<i64> = sqlx::query_scalar("SELECT 1 FROM families WHERE id = ?")
+        .bind(family_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+    if exists.is_none() {
+        return Err(ApiError::NotFound(format!("Family {}", family_id)));
+    }
+
+    ensure_not_in_other_family(conn, email, family_id).await?;
+    sqlx::query("INSERT OR IGNORE INTO family_members (email, family_id, added_by, role) VALUES (?, ?, ?, ?)")
+        .bind(email)
+        .bind(family_id)
+        .bind(added_by)
+        .bind(role)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+pub async fn set_family_member_role(
+    pool: &SqlitePool,
+    family_id: &str,
+    email: &str,
+    role: FamilyRole,
+) -> ApiResult<()> {
+    let result = sqlx::query("UPDATE family_members SET role = ? WHERE family_id = ? AND email = ?")
+        .bind(role)
+        .bind(family_id)
+        .bind(email)
+        .execute(pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound(format!("{} in family {}", email, family_id)));
+    }
+    Ok(())
+}
+
+pub async fn remove_family_member(pool: &SqlitePool, family_id: &str, email: &str) -> ApiResult<()> {
+    let result = sqlx::query("DELETE FROM family_members WHERE family_id = ? AND email = ?")
+        .bind(family_id)
+        .bind(email)
+        .execute(pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound(format!("{} in family {}", email, family_id)));
+    }
+    Ok(())
+}
+
+/// Copy families from families.yaml into an empty database, with everyone as
+/// an owner. Returns how many families were imported; once the database has
+/// families the file is ignored.
+pub async fn import_families(pool: &SqlitePool, families: &[(String, Vec<String>)]) -> ApiResult<usize> {
+    let mut tx = pool.begin().await?;
+
+    let existing: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM families")
+        .fetch_one(&mut *tx)
+        .await?;
+    if existing > 0 {
+        return Ok(0);
+    }
+
+    for (family_id, members) in families {
+        sqlx::query("INSERT INTO families (id, name) VALUES (?, ?)")
+            .bind(family_id)
+            .bind(family_id)
+            .execute(&mut *tx)
+            .await?;
+        for email in members {
+            sqlx::query("INSERT OR IGNORE INTO family_members (email, family_id, role) VALUES (?, ?, ?)")
+                .bind(email)
+                .bind(family_id)
+                .bind(FamilyRole::Owner)
+                .execute(&mut *tx)
+                .await?;
+        }
+    }
+
+    tx.commit().await?;
+    Ok(families.len())
+}
+
+/// Give recipes and share links without a family the family of whoever
+/// created them. Share links follow their recipe where it has one. Returns
+/// how many rows were assigned.
+pub async fn assign_family_ids(pool: &SqlitePool) -> ApiResult<u64> {
+    let mut tx = pool.begin().await?;
+
+    let recipes = sqlx::query(
+        "UPDATE recipes SET family_id = (SELECT family_id FROM family_members WHERE email = LOWER(recipes.created_by))
+         WHERE family_id IS NULL AND LOWER(created_by) IN (SELECT email FROM family_members)"
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    let share_links = sqlx::query(
+        "WITH owners AS (
+            SELECT l.token, COALESCE(
+                (SELECT family_id FROM recipes WHERE id = l.recipe_id),
+                (SELECT r.family_id FROM share_link_recipes s JOIN recipes r ON r.id = s.recipe_id
+                 WHERE s.token = l.token AND r.family_id IS NOT NULL ORDER BY s.position LIMIT 1),
+                (SELECT family_id FROM family_members WHERE email = LOWER(l.created_by))
+            ) AS family_id
+            FROM share_links l WHERE l.family_id IS NULL
+         )
+         UPDATE share_links SET family_id = (SELECT family_id FROM owners WHERE owners.token = share_links.token)
+         WHERE token IN (SELECT token FROM owners WHERE family_id IS NOT NULL)"
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    tx.commit().await?;
+    Ok(recipes + share_links)
+}
+
+pub async fn create_family_invitation(
+    pool: &SqlitePool,
+    token: &str,
+    family_id: &str,
+    email: Option<&str>,
+    created_by: Option<&str>,
+    expires_at: &str,
+) -> ApiResult<FamilyInvitation> {
+    sqlx::query(
+        "INSERT INTO family_invitations (token, family_id, email, created_by, expires_at)
+         VALUES (?, ?, ?, ?, ?)"
+    )
+    .bind(token)
+    .bind(family_id)
+    .bind(email)
+    .bind(created_by)
+    .bind(expires_at)
+    .execute(pool)
+    .await?;
+
+    get_family_invitation(pool, token)
+        .await?
+        .ok_or_else(|| ApiError::Internal(format!("Invitation {} vanished", token)))
+}
+
+pub async fn get_family_invitation(pool: &SqlitePool, token: &str) -> ApiResult<Option<FamilyInvitation>> {
+    let invitation = sqlx::query_as("SELECT * FROM family_invitations WHERE token = ?")
+        .bind(token)
+        .fetch_optional(pool)
+        .await?;
+    Ok(invitation)
+}
+
+/// Use up an invitation and add `email` to its family, all or nothing.
+/// Fails with a conflict if the invitation was accepted in the meantime.
+pub async fn accept_family_invitation(pool: &SqlitePool, token: &str, email: &str) -> ApiResult<String> {
+    let mut tx = pool.begin().await?;
+
+    let family_id: Option<String> = sqlx::query_scalar(
+        "UPDATE family_invitations SET accepted_by = ?, accepted_at = datetime('now')
+         WHERE token = ? AND accepted_at IS NULL AND expires_at > datetime('now')
+         RETURNING family_id"
+    )
+    .bind(email)
+    .bind(token)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let family_id = family_id.ok_or_else(|| ApiError::Conflict("This invitation has already been used".to_string()))?;
+
+    add_family_member_in(&mut tx, &family_id, email, FamilyRole::Editor, Some(email)).await?;
+    tx.commit().await?;
+    Ok(family_id)
+}
+
+/// Settings for a new per-user API key
+pub struct NewApiKey<'a> {
+    pub key_hash: &'a str,
+    pub prefix: &'a str,
+    pub label: &'a str,
+    pub email: &'a str,
+    pub scope: ApiKeyScope,
+    pub created_by: Option<&'a str>,
+    /// None never expires
+    pub expires_at: Option<&'a str>,
+    /// Minted for a chat agent rather than by a person
+    pub chat_agent: bool,
+}
+
+pub async fn create_api_key(pool: &SqlitePool, key: NewApiKey<'_>) -> ApiResult<ApiKey> {
+    let id = Uuid::new_v4().to_string();
+    let api_key = sqlx::query_as(
+        "INSERT INTO api_keys (id, key_hash, prefix, label, email, scope, created_by, expires_at, chat_agent)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+         RETURNING *"
+    )
+    .bind(&id)
+    .bind(key.key_hash)
+    .bind(key.prefix)
+    .bind(key.label)
+    .bind(key.email)
+    .bind(key.scope)
+    .bind(key.created_by)
+    .bind(key.expires_at)
+    .bind(key.chat_agent)
+    .fetch_one(pool)
+    .await?;
+    Ok(api_key)
+}
+
+/// The key with this hash, if it hasn't been revoked or expired
+pub async fn find_active_api_key(pool: &SqlitePool, key_hash: &str) -> ApiResult<Option<ApiKey>> {
+    let api_key = sqlx::query_as(
+        "SELECT * FROM api_keys WHERE key_hash = ? AND revoked_at IS NULL
+         AND (expires_at IS NULL OR expires_at > datetime('now'))"
+    )
+    .bind(key_hash)
+    .fetch_optional(pool)
+    .await?;
+    Ok(api_key)
+}
+
+/// Note that a key was used, at most once a minute
+pub async fn touch_api_key(pool: &SqlitePool, id: &str) -> ApiResult<()> {
+    sqlx::query(
+        "UPDATE api_keys SET last_used_at = datetime('now')
+         WHERE id = ? AND (last_used_at IS NULL OR last_used_at < datetime('now', '-1 minutes'))"
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// List API keys, newest first, leaving out chat agents' keys.
+/// When email is Some, only that user's keys; None (god mode) lists every key.
+pub async fn list_api_keys(pool: &SqlitePool, email: Option<&str>) -> ApiResult<Vec<ApiKey>> {
+    let keys = match email {
+        Some(email) => {
+            sqlx::query_as("SELECT * FROM api_keys WHERE email = ? AND chat_agent = 0 ORDER BY created_at DESC, label")
+                .bind(email)
+                .fetch_all(pool)
+                .await?
+        }
+        None => {
+            sqlx::query_as("SELECT * FROM api_keys WHERE chat_agent = 0 ORDER BY created_at DESC, label")
+                .fetch_all(pool)
+                .await?
+        }
+    };
+    Ok(keys)
+}
+
+/// Revoke every chat agent key, left over from agents that were running
+/// when the server stopped
+pub async fn revoke_chat_agent_keys(pool: &SqlitePool) -> ApiResult<u64> {
+    let result = sqlx::query(
+        "UPDATE api_keys SET revoked_at = datetime('now') WHERE chat_agent = 1 AND revoked_at IS NULL"
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Revoke a key straight away.
+/// When email is Some, only that user's keys can be revoked.
+pub async fn revoke_api_key(pool: &SqlitePool, id: &str, email: Option<&str>) -> ApiResult<()> {
+    let result = match email {
+        Some(email) => {
+            sqlx::query("UPDATE api_keys SET revoked_at = datetime('now') WHERE id = ? AND email = ? AND revoked_at IS NULL")
+                .bind(id)
+                .bind(email)
+                .execute(pool)
+                .await?
+        }
+        None => {
+            sqlx::query("UPDATE api_keys SET revoked_at = datetime('now') WHERE id = ? AND revoked_at IS NULL")
+                .bind(id)
+                .execute(pool)
+                .await?
+        }
+    };
+
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound(format!("API key {}", id)));
+    }
+    Ok(())
+}
+
+/// Count one LLM call for a family today (UTC) if it has made fewer than
+/// `limit`, dropping days before yesterday. Returns whether the call was
+/// counted.
+pub async fn reserve_llm_call(pool: &SqlitePool, family_id: &str, limit: i64) -> ApiResult<bool> {
+    sqlx::query("DELETE FROM llm_usage WHERE day < date('now', '-1 day')")
+        .execute(pool)
+        .await?;
+    let result = sqlx::query(
+        "INSERT INTO llm_usage (family_id, day, calls) VALUES (?, date('now'), 1)
+         ON CONFLICT(family_id, day) DO UPDATE SET calls = calls + 1 WHERE calls < ?"
+    )
+    .bind(family_id)
+    .bind(limit)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Add to a family's LLM calls for today (UTC), dropping days before yesterday
+pub async fn record_llm_calls(pool: &SqlitePool, family_id: &str, calls: i64) -> ApiResult<()> {
+    sqlx::query("DELETE FROM llm_usage WHERE day < date('now', '-1 day')")
+        .execute(pool)
+        .await?;
+    sqlx::query(
+        "INSERT INTO llm_usage (family_id, day, calls) VALUES (?, date('now'), ?)
+         ON CONFLICT(family_id, day) DO UPDATE SET calls = calls + excluded.calls"
+    )
+    .bind(family_id)
+    .bind(calls)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
@@ -1,5 +1,7 @@
+use std::time::Duration;
+
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
@@ -31,16 +33,37 @@ pub enum ApiError {
 
     #[error("Filesystem error: {0}")]
     FileSystemError(String),
+
+    #[error("Upstream error: {0}")]
+    Upstream(String),
+
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Too many requests: {0}")]
+    TooManyRequests(String),
+
+    /// Over a rate limit or quota; the client may retry after `retry_after`
+    #[error("Rate limited: {message}")]
+    RateLimited { message: String, retry_after: Duration },
 }
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
+        let retry_after = match &self {
+            ApiError::RateLimited { retry_after, .. } => Some(*retry_after),
+            _ => None,
+        };
         let (status, error_message, error_code) = match self {
             ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, msg, "NOT_FOUND"),
             ApiError::Validation(msg) => (StatusCode::BAD_REQUEST, msg, "VALIDATION_ERROR"),
             ApiError::Conflict(msg) => (StatusCode::CONFLICT, msg, "CONFLICT"),
+            ApiError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg, "FORBIDDEN"),
+            ApiError::TooManyRequests(msg) => (StatusCode::TOO_MANY_REQUESTS, msg, "TOO_MANY_REQUESTS"),
+            ApiError::RateLimited { message, .. } => (StatusCode::TOO_MANY_REQUESTS, message, "TOO_MANY_REQUESTS"),
             ApiError::FileTooLarge(msg) => (StatusCode::PAYLOAD_TOO_LARGE, msg, "FILE_TOO_LARGE"),
             ApiError::UnsupportedFileType(msg) => (StatusCode::BAD_REQUEST, msg, "UNSUPPORTED_FILE_TYPE"),
+            ApiError::Upstream(msg) => (StatusCode::BAD_GATEWAY, msg, "UPSTREAM_ERROR"),
             ApiError::FileSystemError(msg) => {
                 tracing::error!("Filesystem error: {}", msg);
                 (
@@ -72,7 +95,15 @@ impl IntoResponse for ApiError {
             "code": error_code,
         }));
 
-        (status, body).into_response()
+        let mut response = (status, body).into_response();
+        if let Some(retry_after) = retry_after {
+            // Whole seconds, rounded up so a prompt retry isn't refused again
+            let seconds = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(seconds.max(1)));
+        }
+        response
     }
 }
 
@@ -0,0 +1,76 @@
+//! Family membership lookups for the auth middleware.
+//!
+//! Families live in the database (see the admin API). Every authenticated
+//! request needs the caller's family, so lookups are cached for a short while;
+//! changes made through this server clear the cache straight away.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use sqlx::SqlitePool;
+
+use crate::config::FamiliesConfig;
+use crate::db::queries;
+use crate::error::ApiResult;
+use crate::models::FamilyRole;
+
+/// How long a looked-up membership is trusted before asking the database again
+const CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// The family an email belongs to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Membership {
+    pub family_id: String,
+    pub role: FamilyRole,
+    /// All emails in the family (normalized), including the caller's
+    pub members: Vec<String>,
+}
+
+/// Lookups by email, with when they were made
+type MembershipCache = HashMap<String, (Instant, Option<Membership>)>;
+
+#[derive(Clone)]
+pub struct FamilyDirectory {
+    pool: SqlitePool,
+    cache: Arc<RwLock<MembershipCache>>,
+}
+
+impl FamilyDirectory {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self {
+            pool,
+            cache: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// The family of a normalized email, or None if it isn't in one
+    pub async fn membership(&self, email: &str) -> ApiResult<Option<Membership>> {
+        if let Some((fetched_at, membership)) = self.cache.read().unwrap().get(email)
+            && fetched_at.elapsed() < CACHE_TTL
+        {
+            return Ok(membership.clone());
+        }
+
+        let membership = queries::get_family_membership(&self.pool, email)
+            .await?
+            .map(|(family_id, role, members)| Membership { family_id, role, members });
+        self.cache
+            .write()
+            .unwrap()
+            .insert(email.to_string(), (Instant::now(), membership.clone()));
+        Ok(membership)
+    }
+
+    /// Forget cached memberships after families or members change
+    pub fn invalidate(&self) {
+        self.cache.write().unwrap().clear();
+    }
+
+    /// Copy families.yaml into the database if it has no families yet
+    pub async fn import_config(&self, config: &FamiliesConfig) -> ApiResult<usize> {
+        let imported = queries::import_families(&self.pool, &config.families()).await?;
+        self.invalidate();
+        Ok(imported)
+    }
+}
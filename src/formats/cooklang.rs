@@ -0,0 +1,839 @@
+//! Cooklang (<https://cooklang.org>) plain-text recipes. Each paragraph is a
+//! step, keeping its line breaks, with ingredients, cookware and timers
+//! marked up inline:
+//!
+//! ```text
+//! ---
+//! title: Pancakes
+//! servings: 4
+//! ---
+//!
+//! Whisk @flour{125%g} with @milk{300%ml} in a #bowl{}.
+//! Rest for ~{30%minutes}.
+//! ```
+//!
+//! Timers become the step's `duration_minutes` and a temperature written as
+//! "180°C" becomes its temperature. Ingredients are collected from the steps
+//! in order, adding up repeated mentions of the same ingredient and unit.
+
+use serde::Serialize;
+use serde_yaml::{Mapping, Value as YamlValue};
+
+use super::{
+    ingredient_line::parse_quantity,
+    schema_org::{parse_iso8601_duration, parse_yield},
+    FormatError, ParsedRecipe,
+};
+use crate::models::{CreateIngredientInput, CreateRecipeInput, CreateStepInput, RecipeWithDetails};
+
+/// Characters that start markup and are escaped with `\` in plain text
+const SPECIAL: [char; 3] = ['@', '#', '~'];
+
+/// Parse a Cooklang recipe. The title comes from `title` metadata and is
+/// left empty if there is none (Cooklang files are usually named after
+/// their recipe).
+pub fn parse_cooklang(text: &str) -> Result<ParsedRecipe, FormatError> {
+    let lines: Vec<&str> = text.lines().map(|l| l.trim_end_matches('\r')).collect();
+
+    let mut metadata = Mapping::new();
+    let mut body_start = 0;
+    if lines.first().map(|l| l.trim_end()) == Some("---") {
+        let Some(end) = lines.iter().skip(1).position(|l| l.trim_end() == "---").map(|p| p + 1) else {
+            return Err(line_error(1, "Front matter is not closed with '---'"));
+        };
+        let yaml = lines[1..end].join("\n");
+        if !yaml.trim().is_empty() {
+            metadata = serde_yaml::from_str(&yaml).map_err(|e| {
+                let line = e.location().map(|l| l.line() + 1).unwrap_or(1);
+                line_error(line, format!("Invalid front matter: {}", e))
+            })?;
+        }
+        body_start = end + 1;
+    }
+
+    let mut builder = RecipeBuilder::default();
+    let mut paragraph = Paragraph::default();
+    // Line a `[-` comment was opened on, while inside it
+    let mut block_comment: Option<usize> = None;
+
+    for (index, raw_line) in lines.iter().enumerate().skip(body_start) {
+        let line_number = index + 1;
+        let line = strip_comments(raw_line, line_number, &mut block_comment);
+        let trimmed = line.trim();
+        // A line holding only a comment doesn't end the step
+        if trimmed.is_empty() && !raw_line.trim().is_empty() {
+            continue;
+        }
+
+        if let Some(entry) = trimmed.strip_prefix(">>") {
+            let (key, value) = entry
+                .split_once(':')
+                .ok_or_else(|| line_error(line_number, "Metadata must be written as '>> key: value'"))?;
+            metadata.insert(
+                YamlValue::String(key.trim().to_string()),
+                YamlValue::String(value.trim().to_string()),
+            );
+            continue;
+        }
+        if let Some(note) = trimmed.strip_prefix('>') {
+            builder.notes.push(note.trim().to_string());
+            continue;
+        }
+        if trimmed.starts_with('=') {
+            builder.finish_paragraph(&mut paragraph);
+            let name = trimmed.trim_matches('=').trim();
+            if !name.is_empty() {
+                builder.warnings.push(format!("Section heading '{}' was dropped", name));
+            }
+            continue;
+        }
+        if trimmed.is_empty() {
+            builder.finish_paragraph(&mut paragraph);
+            continue;
+        }
+
+        if !paragraph.text.is_empty() {
+            paragraph.text.push('\n');
+        }
+        parse_line(trimmed, &mut paragraph, &mut builder.ingredients)
+            .map_err(|message| line_error(line_number, message))?;
+    }
+    if let Some(line) = block_comment {
+        return Err(line_error(line, "Unclosed '[-' comment"));
+    }
+    builder.finish_paragraph(&mut paragraph);
+
+    let mut recipe = CreateRecipeInput {
+        ingredients: builder.ingredients,
+        steps: builder.steps,
+        ..Default::default()
+    };
+    let mut warnings = builder.warnings;
+    let mut description_parts = Vec::new();
+    apply_metadata(&metadata, &mut recipe, &mut description_parts, &mut warnings);
+    description_parts.extend(builder.notes);
+    if !description_parts.is_empty() {
+        recipe.description = Some(description_parts.join("\n\n"));
+    }
+
+    Ok(ParsedRecipe {
+        recipe,
+        photo: None,
+        warnings,
+    })
+}
+
+fn line_error(line: usize, message: impl Into<String>) -> FormatError {
+    FormatError::Line {
+        line,
+        message: message.into(),
+    }
+}
+
+#[derive(Default)]
+struct RecipeBuilder {
+    ingredients: Vec<CreateIngredientInput>,
+    steps: Vec<CreateStepInput>,
+    notes: Vec<String>,
+    warnings: Vec<String>,
+}
+
+impl RecipeBuilder {
+    fn finish_paragraph(&mut self, paragraph: &mut Paragraph) {
+        let paragraph = std::mem::take(paragraph);
+        // A paragraph of nothing but ingredients lists them without a step
+        if !paragraph.has_text {
+            return;
+        }
+        let instruction = paragraph.text.trim().to_string();
+        let (temperature_value, temperature_unit) = match find_temperature(&instruction) {
+            Some((value, unit)) => (Some(value), Some(unit.to_string())),
+            None => (None, None),
+        };
+        self.steps.push(CreateStepInput {
+            instruction,
+            duration_minutes: paragraph.timer_minutes,
+            temperature_value,
+            temperature_unit,
+        });
+    }
+}
+
+/// A step being read
+#[derive(Default)]
+struct Paragraph {
+    text: String,
+    /// Whether there is any text besides ingredient markup
+    has_text: bool,
+    timer_minutes: Option<i32>,
+}
+
+/// Read one line of a step, replacing markup with plain text
+fn parse_line(line: &str, paragraph: &mut Paragraph, ingredients: &mut Vec<CreateIngredientInput>) -> Result<(), String> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\\' && chars.get(i + 1).is_some_and(|next| SPECIAL.contains(next)) {
+            paragraph.text.push(chars[i + 1]);
+            paragraph.has_text = true;
+            i += 2;
+            continue;
+        }
+        if !SPECIAL.contains(&c) {
+            paragraph.text.push(c);
+            if c.is_alphanumeric() {
+                paragraph.has_text = true;
+            }
+            i += 1;
+            continue;
+        }
+
+        let Some(component) = read_component(&chars, i + 1)? else {
+            // A lone '@', '#' or '~' is plain text
+            paragraph.text.push(c);
+            i += 1;
+            continue;
+        };
+        i = component.end;
+
+        match c {
+            '@' => {
+                if component.name.is_empty() {
+                    return Err("Ingredient has no name".to_string());
+                }
+                paragraph.text.push_str(&component.name);
+                let (quantity, unit) = parse_amount(component.amount.as_deref().unwrap_or(""));
+                add_ingredient(
+                    ingredients,
+                    CreateIngredientInput {
+                        name: component.name,
+                        quantity,
+                        unit,
+                        notes: component.note,
+                    },
+                );
+            }
+            '#' => {
+                paragraph.text.push_str(&component.name);
+                paragraph.has_text = true;
+            }
+            _ => {
+                let Some(amount) = component.amount else {
+                    return Err("Timers need a duration, like ~{10%minutes}".to_string());
+                };
+                let (quantity, unit) = parse_amount(&amount);
+                let minutes = quantity
+                    .zip(unit.as_deref())
+                    .and_then(|(quantity, unit)| timer_minutes(quantity, unit))
+                    .ok_or_else(|| format!("Invalid timer '{}'; use a number and a unit, like 10%minutes", amount))?;
+                paragraph.timer_minutes = Some(paragraph.timer_minutes.unwrap_or(0) + minutes);
+                if !component.name.is_empty() {
+                    paragraph.text.push_str(&component.name);
+                    paragraph.text.push(' ');
+                }
+                paragraph.text.push_str(&amount.replace('%', " "));
+                paragraph.has_text = true;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// An `@`, `#` or `~` component: `name`, `name{amount}` or `multi word{amount}`,
+/// optionally followed by `(note)`
+struct Component {
+    name: String,
+    amount: Option<String>,
+    note: Option<String>,
+    /// Index just past the component
+    end: usize,
+}
+
+fn read_component(chars: &[char], start: usize) -> Result<Option<Component>, String> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+
+    // Multi-word names run up to a '{' with no other markup in between
+    let brace = chars[start..]
+        .iter()
+        .position(|c| matches!(c, '{' | '}' | '@' | '#' | '~' | '(' | '.' | ','))
+        .map(|p| start + p)
+        .filter(|&p| chars[p] == '{');
+
+    let (name_end, amount, mut end) = match brace {
+        Some(open) => {
+            let close = chars[open..]
+                .iter()
+                .position(|c| *c == '}')
+                .map(|p| open + p)
+                .ok_or_else(|| "Unclosed '{'".to_string())?;
+            let amount: String = chars[open + 1..close].iter().collect();
+            (open, Some(amount.trim().to_string()), close + 1)
+        }
+        None => {
+            let end = chars[start..].iter().position(|c| !is_word(*c)).map_or(chars.len(), |p| start + p);
+            // Don't end a single-word name on a trailing hyphen ("@salt-" -> "salt")
+            let mut end = end;
+            while end > start && chars[end - 1] == '-' {
+                end -= 1;
+            }
+            (end, None, end)
+        }
+    };
+
+    let name: String = chars[start..name_end].iter().collect::<String>().trim().to_string();
+    if name.is_empty() && amount.is_none() {
+        return Ok(None);
+    }
+
+    let mut note = None;
+    if amount.is_some() && chars.get(end) == Some(&'(') {
+        let close = chars[end..]
+            .iter()
+            .position(|c| *c == ')')
+            .map(|p| end + p)
+            .ok_or_else(|| "Unclosed '(' after ingredient".to_string())?;
+        let text: String = chars[end + 1..close].iter().collect();
+        note = Some(text.trim().to_string()).filter(|n| !n.is_empty());
+        end = close + 1;
+    }
+
+    Ok(Some(Component {
+        name,
+        amount: amount.filter(|a| !a.is_empty()),
+        note,
+        end,
+    }))
+}
+
+/// "500%g" -> (500, "g"); "1/2%cup" -> (0.5, "cup"); "a pinch" -> (None, "a pinch")
+fn parse_amount(amount: &str) -> (Option<f64>, Option<String>) {
+    let (quantity, unit) = match amount.split_once('%') {
+        Some((quantity, unit)) => (quantity.trim(), Some(unit.trim())),
+        None => (amount.trim(), None),
+    };
+    let quantity = quantity.trim_start_matches('=').trim();
+    let unit = unit.filter(|u| !u.is_empty()).map(String::from);
+
+    if quantity.is_empty() {
+        return (None, unit);
+    }
+    match parse_quantity(quantity) {
+        (Some(value), rest) if rest.trim().is_empty() => (Some(value), unit),
+        // Not a number: keep the words as the unit so they are not lost
+        _ => (None, Some(match unit {
+            Some(unit) => format!("{} {}", quantity, unit),
+            None => quantity.to_string(),
+        })),
+    }
+}
+
+fn timer_minutes(quantity: f64, unit: &str) -> Option<i32> {
+    let unit = unit.to_lowercase();
+    let minutes = if unit.starts_with('h') {
+        quantity * 60.0
+    } else if unit.starts_with('m') {
+        quantity
+    } else if unit.starts_with('s') {
+        quantity / 60.0
+    } else if unit.starts_with('d') {
+        quantity * 24.0 * 60.0
+    } else {
+        return None;
+    };
+    Some(minutes.round().max(1.0) as i32)
+}
+
+/// Mentions of the same ingredient in the same unit are added up
+fn add_ingredient(ingredients: &mut Vec<CreateIngredientInput>, ingredient: CreateIngredientInput) {
+    let existing = ingredients.iter_mut().find(|i| {
+        i.name.eq_ignore_ascii_case(&ingredient.name)
+            && i.unit.as_deref().map(str::to_lowercase) == ingredient.unit.as_deref().map(str::to_lowercase)
+            && i.quantity.is_some() == ingredient.quantity.is_some()
+    });
+    match existing {
+        Some(existing) => {
+            if let (Some(total), Some(more)) = (existing.quantity, ingredient.quantity) {
+                existing.quantity = Some(total + more);
+            }
+            if existing.notes.is_none() {
+                existing.notes = ingredient.notes;
+            }
+        }
+        None => ingredients.push(ingredient),
+    }
+}
+
+/// First "180°C" or "350 °F" in a step
+fn find_temperature(text: &str) -> Option<(i32, &'static str)> {
+    for (pos, _) in text.match_indices('°') {
+        let unit = match text[pos + '°'.len_utf8()..].chars().next()? {
+            'C' | 'c' => "Celsius",
+            'F' | 'f' => "Fahrenheit",
+            _ => continue,
+        };
+        let before = text[..pos].trim_end();
+        let digits: String = before
+            .chars()
+            .rev()
+            .take_while(|c| c.is_ascii_digit())
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .collect();
+        if let Ok(value) = digits.parse() {
+            return Some((value, unit));
+        }
+    }
+    None
+}
+
+/// Remove `[- … -]` block comments and a trailing `--` comment from a line
+fn strip_comments(line: &str, line_number: usize, block_comment: &mut Option<usize>) -> String {
+    let mut out = String::new();
+    let mut rest = line;
+    loop {
+        if block_comment.is_some() {
+            match rest.find("-]") {
+                Some(end) => {
+                    *block_comment = None;
+                    rest = &rest[end + 2..];
+                }
+                None => return out,
+            }
+        }
+        match rest.find("[-") {
+            Some(start) => {
+                out.push_str(&rest[..start]);
+                *block_comment = Some(line_number);
+                rest = &rest[start + 2..];
+            }
+            None => {
+                out.push_str(rest);
+                break;
+            }
+        }
+    }
+    match out.find("--") {
+        Some(pos) if pos == 0 || out[..pos].ends_with(char::is_whitespace) => out[..pos].to_string(),
+        _ => out,
+    }
+}
+
+fn metadata_text(value: &YamlValue) -> Option<String> {
+    let text = match value {
+        YamlValue::String(s) => s.trim().to_string(),
+        YamlValue::Number(n) => n.to_string(),
+        _ => return None,
+    };
+    (!text.is_empty()).then_some(text)
+}
+
+fn apply_metadata(
+    metadata: &Mapping,
+    recipe: &mut CreateRecipeInput,
+    description_parts: &mut Vec<String>,
+    warnings: &mut Vec<String>,
+) {
+    let mut total_time = None;
+    for (key, value) in metadata {
+        let Some(key) = key.as_str() else { continue };
+        let normalized = key.trim().to_lowercase().replace(['_', '-'], " ");
+        match normalized.as_str() {
+            "title" => recipe.title = metadata_text(value).unwrap_or_default(),
+            "description" | "introduction" => description_parts.extend(metadata_text(value)),
+            "servings" | "serves" | "yield" => recipe.servings = metadata_text(value).as_deref().and_then(parse_yield),
+            "prep time" => recipe.prep_time_minutes = metadata_text(value).as_deref().and_then(parse_iso8601_duration),
+            "cook time" => recipe.cook_time_minutes = metadata_text(value).as_deref().and_then(parse_iso8601_duration),
+            "time" | "duration" | "total time" => {
+                total_time = metadata_text(value).as_deref().and_then(parse_iso8601_duration)
+            }
+            "difficulty" => {
+                recipe.difficulty = metadata_text(value)
+                    .and_then(|d| d.parse().ok())
+                    .filter(|d| (1..=5).contains(d));
+            }
+            "tags" | "tag" | "category" | "categories" => {
+                recipe.tags = match value {
+                    YamlValue::Sequence(items) => items.iter().filter_map(metadata_text).collect(),
+                    other => metadata_text(other)
+                        .map(|t| t.split(',').map(|s| s.trim().to_string()).collect())
+                        .unwrap_or_default(),
+                };
+            }
+            "source" | "source url" | "url" => match metadata_text(value) {
+                Some(url) if url.starts_with("http://") || url.starts_with("https://") => recipe.source_url = Some(url),
+                Some(source) => description_parts.push(format!("Source: {}", source)),
+                None => {}
+            },
+            _ => warnings.push(format!("Metadata '{}' was not imported", key)),
+        }
+    }
+    // Only a total is given: treat the remainder after prep as cooking
+    if recipe.cook_time_minutes.is_none()
+        && let Some(total) = total_time
+    {
+        recipe.cook_time_minutes = Some(total - recipe.prep_time_minutes.unwrap_or(0)).filter(|m| *m > 0);
+    }
+}
+
+#[derive(Serialize)]
+struct Metadata<'a> {
+    title: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    tags: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    servings: Option<i32>,
+    #[serde(rename = "prep time", skip_serializing_if = "Option::is_none")]
+    prep_time: Option<String>,
+    #[serde(rename = "cook time", skip_serializing_if = "Option::is_none")]
+    cook_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    difficulty: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<&'a str>,
+}
+
+/// Write a recipe as Cooklang
+pub fn to_cooklang(recipe: &RecipeWithDetails) -> String {
+    render_cooklang(&CreateRecipeInput::from(recipe))
+}
+
+/// Write recipe input as Cooklang. Each ingredient is marked up where a
+/// step first mentions it; ingredients no step mentions are listed in a
+/// paragraph of their own before the steps. Timers and temperatures add to
+/// a step's text, so a step's duration and temperature are only kept when
+/// its text gives them ("30 minutes", "180°C"). Blank lines within a step
+/// become single line breaks, since a blank line starts the next step.
+pub fn render_cooklang(recipe: &CreateRecipeInput) -> String {
+    let metadata = Metadata {
+        title: &recipe.title,
+        description: recipe.description.as_deref(),
+        tags: &recipe.tags,
+        servings: recipe.servings,
+        prep_time: recipe.prep_time_minutes.map(|m| format!("{} minutes", m)),
+        cook_time: recipe.cook_time_minutes.map(|m| format!("{} minutes", m)),
+        difficulty: recipe.difficulty,
+        source: recipe.source_url.as_deref(),
+    };
+    let mut out = format!("---\n{}---\n", serde_yaml::to_string(&metadata).unwrap_or_default());
+
+    let mut placed = vec![false; recipe.ingredients.len()];
+    let steps: Vec<String> = recipe
+        .steps
+        .iter()
+        .map(|step| render_step(step, &recipe.ingredients, &mut placed))
+        .collect();
+
+    let unplaced: Vec<String> = recipe
+        .ingredients
+        .iter()
+        .zip(&placed)
+        .filter(|(_, placed)| !**placed)
+        .map(|(ingredient, _)| ingredient_markup(ingredient))
+        .collect();
+    if !unplaced.is_empty() {
+        out.push('\n');
+        out.push_str(&unplaced.join("\n"));
+        out.push('\n');
+    }
+
+    for step in steps {
+        out.push('\n');
+        out.push_str(&step);
+        out.push('\n');
+    }
+    out
+}
+
+fn render_step(step: &CreateStepInput, ingredients: &[CreateIngredientInput], placed: &mut [bool]) -> String {
+    // Blank lines would start a new step
+    let mut text = escape(&step.instruction.split('\n').filter(|l| !l.trim().is_empty()).collect::<Vec<_>>().join("\n"));
+
+    for (index, ingredient) in ingredients.iter().enumerate() {
+        if placed[index] || ingredient.name.contains(['{', '}', '@', '#', '~']) {
+            continue;
+        }
+        if let Some(pos) = find_word(&text, &ingredient.name) {
+            text.replace_range(pos..pos + ingredient.name.len(), &ingredient_markup(ingredient));
+            placed[index] = true;
+        }
+    }
+
+    // "30 minutes" becomes "~{30%minutes}", which reads back as the same text
+    if let Some(minutes) = step.duration_minutes
+        && let Some((pos, len)) = duration_spellings(minutes)
+            .iter()
+            .find_map(|spelled| find_word(&text, spelled).map(|pos| (pos, spelled.len())))
+    {
+        let (quantity, unit) = text[pos..pos + len].split_once(' ').expect("spellings have a space");
+        let timer = format!("~{{{}%{}}}", quantity, unit);
+        text.replace_range(pos..pos + len, &timer);
+    }
+    text
+}
+
+/// Ways a step might give its duration, as `timer_minutes` reads them
+fn duration_spellings(minutes: i32) -> Vec<String> {
+    let mut spellings: Vec<String> =
+        ["minutes", "minute", "mins", "min"].iter().map(|unit| format!("{} {}", minutes, unit)).collect();
+    if minutes > 0 && minutes % 60 == 0 {
+        let hours = minutes / 60;
+        spellings.extend(["hours", "hour", "hrs", "hr"].iter().map(|unit| format!("{} {}", hours, unit)));
+    }
+    spellings
+}
+
+/// "@flour{2.5%cups}(sifted)"
+fn ingredient_markup(ingredient: &CreateIngredientInput) -> String {
+    let amount = match (ingredient.quantity, &ingredient.unit) {
+        (Some(quantity), Some(unit)) => format!("{}%{}", quantity, unit),
+        (Some(quantity), None) => quantity.to_string(),
+        // "{%2-3}": without the '%' the unit would be read as a quantity
+        (None, Some(unit)) if parse_amount(unit) != (None, Some(unit.clone())) => format!("%{}", unit),
+        (None, Some(unit)) => unit.clone(),
+        (None, None) => String::new(),
+    };
+    let mut markup = format!("@{}{{{}}}", ingredient.name, amount);
+    if let Some(notes) = &ingredient.notes {
+        markup.push_str(&format!("({})", notes));
+    }
+    markup
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if SPECIAL.contains(&c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Byte offset of `needle` as a whole word, ignoring case
+fn find_word(haystack: &str, needle: &str) -> Option<usize> {
+    if needle.is_empty() {
+        return None;
+    }
+    let lower = haystack.to_lowercase();
+    // Lowercasing can change byte lengths; only match when it doesn't
+    if lower.len() != haystack.len() {
+        return None;
+    }
+    let needle = needle.to_lowercase();
+    lower.match_indices(&needle).map(|(pos, _)| pos).find(|&pos| {
+        let before = lower[..pos].chars().next_back();
+        let after = lower[pos + needle.len()..].chars().next();
+        let boundary = |c: Option<char>| c.is_none_or(|c| !c.is_alphanumeric() && c != '\\' && c != '@');
+        boundary(before) && boundary(after)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> ParsedRecipe {
+        parse_cooklang(text).unwrap()
+    }
+
+    #[test]
+    fn test_ingredients_and_timers() {
+        let parsed = parse(
+            ">> servings: 4\n\nCrack @eggs{3} into a #mixing bowl{}, add @ground black pepper{} and @salt.\n\nFry in @butter{1%tbsp} for ~{3%minutes}, then rest ~resting{30%seconds}.\n",
+        );
+        let recipe = parsed.recipe;
+        assert_eq!(recipe.servings, Some(4));
+        assert_eq!(recipe.ingredients.len(), 4);
+        assert_eq!(recipe.ingredients[0].quantity, Some(3.0));
+        assert_eq!(recipe.ingredients[1].name, "ground black pepper");
+        assert_eq!(recipe.ingredients[2].name, "salt");
+        assert_eq!(recipe.ingredients[3].unit.as_deref(), Some("tbsp"));
+
+        assert_eq!(
+            recipe.steps[0].instruction,
+            "Crack eggs into a mixing bowl, add ground black pepper and salt."
+        );
+        assert_eq!(recipe.steps[0].duration_minutes, None);
+        assert_eq!(recipe.steps[1].instruction, "Fry in butter for 3 minutes, then rest resting 30 seconds.");
+        assert_eq!(recipe.steps[1].duration_minutes, Some(4));
+    }
+
+    #[test]
+    fn test_amounts() {
+        assert_eq!(parse_amount("500%g"), (Some(500.0), Some("g".to_string())));
+        assert_eq!(parse_amount("1/2%cup"), (Some(0.5), Some("cup".to_string())));
+        assert_eq!(parse_amount("=2"), (Some(2.0), None));
+        assert_eq!(parse_amount("a pinch"), (None, Some("a pinch".to_string())));
+        assert_eq!(parse_amount("%g"), (None, Some("g".to_string())));
+    }
+
+    #[test]
+    fn test_repeated_ingredients_are_added_up() {
+        let parsed = parse("Add @sugar{50%g}.\n\nAdd @sugar{25%g}(caster) and @sugar{1%tbsp}.\n");
+        let ingredients = parsed.recipe.ingredients;
+        assert_eq!(ingredients.len(), 2);
+        assert_eq!(ingredients[0].quantity, Some(75.0));
+        assert_eq!(ingredients[0].notes.as_deref(), Some("caster"));
+        assert_eq!(ingredients[1].unit.as_deref(), Some("tbsp"));
+    }
+
+    #[test]
+    fn test_metadata_comments_and_notes() {
+        let parsed = parse(
+            "---\ntitle: Lemonade\ntags: [Drinks, Summer]\nprep time: 10 minutes\nsource: https://example.com/lemonade\nlocale: en\n---\n\n> Best served cold.\n\n-- a comment\n= Syrup\nDissolve @sugar{100%g} in @water{100%ml}. -- more\n[- block\ncomment -]\nStir.\n",
+        );
+        let recipe = parsed.recipe;
+        assert_eq!(recipe.title, "Lemonade");
+        assert_eq!(recipe.tags, vec!["Drinks", "Summer"]);
+        assert_eq!(recipe.prep_time_minutes, Some(10));
+        assert_eq!(recipe.source_url.as_deref(), Some("https://example.com/lemonade"));
+        assert_eq!(recipe.description.as_deref(), Some("Best served cold."));
+        assert_eq!(recipe.steps.len(), 1);
+        assert_eq!(recipe.steps[0].instruction, "Dissolve sugar in water.\nStir.");
+        assert_eq!(
+            parsed.warnings,
+            vec!["Section heading 'Syrup' was dropped", "Metadata 'locale' was not imported"]
+        );
+    }
+
+    #[test]
+    fn test_temperature() {
+        let parsed = parse("Heat the oven to 180°C.\n\nBake at 350 °F.\n");
+        assert_eq!(parsed.recipe.steps[0].temperature_value, Some(180));
+        assert_eq!(parsed.recipe.steps[0].temperature_unit.as_deref(), Some("Celsius"));
+        assert_eq!(parsed.recipe.steps[1].temperature_value, Some(350));
+        assert_eq!(parsed.recipe.steps[1].temperature_unit.as_deref(), Some("Fahrenheit"));
+    }
+
+    #[test]
+    fn test_errors_report_lines() {
+        let error = |text: &str| match parse_cooklang(text) {
+            Err(FormatError::Line { line, message }) => (line, message),
+            other => panic!("expected a line error, got {:?}", other),
+        };
+        assert_eq!(error("Mix.\n\nAdd @flour{500%g.\n"), (3, "Unclosed '{'".to_string()));
+        assert_eq!(error("Wait ~{a while}.\n").0, 1);
+        assert_eq!(error("Wait ~rest.\n").0, 1);
+        assert_eq!(error("---\ntitle: x\n").0, 1);
+        assert_eq!(error("One.\n[- never closed\n").0, 2);
+    }
+
+    #[test]
+    fn test_literal_characters() {
+        let parsed = parse("Use pan #2 or \\@home, email me @ noon ~ later.\n");
+        assert!(parsed.recipe.ingredients.is_empty());
+        assert_eq!(parsed.recipe.steps[0].instruction, "Use pan 2 or @home, email me @ noon ~ later.");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let recipe = CreateRecipeInput {
+            title: "Pancakes".to_string(),
+            description: Some("Sunday breakfast.".to_string()),
+            prep_time_minutes: Some(10),
+            cook_time_minutes: Some(20),
+            servings: Some(4),
+            difficulty: Some(1),
+            ingredients: vec![
+                CreateIngredientInput {
+                    name: "plain flour".to_string(),
+                    quantity: Some(125.0),
+                    unit: Some("g".to_string()),
+                    notes: Some("sifted".to_string()),
+                },
+                CreateIngredientInput {
+                    name: "milk".to_string(),
+                    quantity: Some(300.0),
+                    unit: Some("ml".to_string()),
+                    notes: None,
+                },
+                CreateIngredientInput {
+                    name: "butter".to_string(),
+                    unit: Some("knob".to_string()),
+                    ..Default::default()
+                },
+                CreateIngredientInput {
+                    name: "eggs".to_string(),
+                    unit: Some("2-3".to_string()),
+                    ..Default::default()
+                },
+            ],
+            steps: vec![
+                CreateStepInput {
+                    instruction: "Whisk the plain flour and milk until smooth, then rest for 30 minutes.".to_string(),
+                    duration_minutes: Some(30),
+                    ..Default::default()
+                },
+                CreateStepInput {
+                    instruction: "Beat in the eggs:\none at a time,\nthen chill for 1 Hour.".to_string(),
+                    duration_minutes: Some(60),
+                    ..Default::default()
+                },
+                CreateStepInput {
+                    instruction: "Cook ladlefuls in a hot pan #1 at 200°C.".to_string(),
+                    temperature_value: Some(200),
+                    temperature_unit: Some("Celsius".to_string()),
+                    ..Default::default()
+                },
+            ],
+            tags: vec!["Breakfast".to_string()],
+            source_url: Some("https://example.com/pancakes".to_string()),
+            shared_by: None,
+        };
+
+        let text = render_cooklang(&recipe);
+        assert!(text.contains("\n@butter{knob}\n"), "{}", text);
+        assert!(
+            text.contains("Whisk the @plain flour{125%g}(sifted) and @milk{300%ml} until smooth, then rest for ~{30%minutes}."),
+            "{}",
+            text
+        );
+        assert!(text.contains("Beat in the @eggs{%2-3}:\none at a time,\nthen chill for ~{1%Hour}."), "{}", text);
+        assert!(text.contains("Cook ladlefuls in a hot pan \\#1 at 200°C."), "{}", text);
+
+        let parsed = parse(&text).recipe;
+        assert_eq!(parsed.title, recipe.title);
+        assert_eq!(parsed.description, recipe.description);
+        assert_eq!(parsed.prep_time_minutes, recipe.prep_time_minutes);
+        assert_eq!(parsed.cook_time_minutes, recipe.cook_time_minutes);
+        assert_eq!(parsed.servings, recipe.servings);
+        assert_eq!(parsed.difficulty, recipe.difficulty);
+        assert_eq!(parsed.tags, recipe.tags);
+        assert_eq!(parsed.source_url, recipe.source_url);
+        assert_eq!(
+            parsed.ingredients,
+            vec![
+                recipe.ingredients[2].clone(),
+                recipe.ingredients[0].clone(),
+                recipe.ingredients[1].clone(),
+                recipe.ingredients[3].clone()
+            ]
+        );
+        assert_eq!(parsed.steps, recipe.steps);
+        // Writing the parsed recipe again gives the same file
+        assert_eq!(render_cooklang(&parsed), text);
+    }
+
+    #[test]
+    fn test_durations_the_text_does_not_give_are_left_out() {
+        let recipe = CreateRecipeInput {
+            title: "Toast".to_string(),
+            steps: vec![CreateStepInput {
+                instruction: "Toast the bread.".to_string(),
+                duration_minutes: Some(3),
+                temperature_value: Some(200),
+                temperature_unit: Some("Celsius".to_string()),
+            }],
+            ..Default::default()
+        };
+        let text = render_cooklang(&recipe);
+        assert!(text.ends_with("\nToast the bread.\n"), "{}", text);
+        assert_eq!(parse(&text).recipe.steps[0].instruction, "Toast the bread.");
+    }
+}
@@ -0,0 +1,258 @@
+use crate::models::CreateIngredientInput;
+
+/// Units recognised at the start of an ingredient line (after the quantity).
+/// Multi-word units come first so "fl oz" wins over "fl".
+const UNITS: &[&str] = &[
+    "fl oz", "fluid ounces", "fluid ounce",
+    "tablespoons", "tablespoon", "tbsp", "tbs", "tbl",
+    "teaspoons", "teaspoon", "tsp",
+    "cups", "cup",
+    "grams", "gram", "g",
+    "kilograms", "kilogram", "kg",
+    "milligrams", "milligram", "mg",
+    "millilitres", "millilitre", "milliliters", "milliliter", "ml",
+    "litres", "litre", "liters", "liter", "l",
+    "ounces", "ounce", "oz",
+    "pounds", "pound", "lbs", "lb",
+    "pints", "pint", "quarts", "quart", "gallons", "gallon",
+    "cloves", "clove", "pinches", "pinch", "dashes", "dash",
+    "cans", "can", "tins", "tin", "packets", "packet", "bunches", "bunch",
+    "slices", "slice", "sprigs", "sprig", "handfuls", "handful",
+    "sticks", "stick", "heads", "head",
+];
+
+/// Parse a free-text ingredient line such as "2 ½ cups plain flour, sifted"
+/// into quantity, unit, name and notes.
+///
+/// Anything that can't be recognised stays in the name, so the worst case is
+/// an ingredient with no quantity rather than lost text.
+pub fn parse_ingredient_line(line: &str) -> CreateIngredientInput {
+    let line = normalize_fractions(line.trim());
+    let line = line.trim_start_matches(['-', '*', '•']).trim();
+
+    let (quantity, rest) = parse_quantity(line);
+    let (unit, rest) = match quantity {
+        Some(_) => parse_unit(rest),
+        None => (None, rest),
+    };
+    let rest = rest.trim();
+    let rest = rest.strip_prefix("of ").unwrap_or(rest);
+    let (name, notes) = split_notes(rest);
+
+    CreateIngredientInput {
+        name: if name.is_empty() { line.to_string() } else { name },
+        quantity,
+        unit,
+        notes,
+    }
+}
+
+/// Replace unicode vulgar fractions with ASCII ones ("1½" -> "1 1/2")
+pub(crate) fn normalize_fractions(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        let ascii = match c {
+            '½' => "1/2",
+            '⅓' => "1/3",
+            '⅔' => "2/3",
+            '¼' => "1/4",
+            '¾' => "3/4",
+            '⅕' => "1/5",
+            '⅛' => "1/8",
+            '⅜' => "3/8",
+            '⅝' => "5/8",
+            '⅞' => "7/8",
+            '⁄' => "/",
+            _ => {
+                out.push(c);
+                continue;
+            }
+        };
+        if out.ends_with(|p: char| p.is_ascii_digit()) {
+            out.push(' ');
+        }
+        out.push_str(ascii);
+    }
+    out
+}
+
+/// Parse a leading quantity: "2", "1.5", "1,5", "1/2", "1 1/2", "2-3" (first
+/// value of a range). Quantities glued to a unit ("500g") are split.
+pub(crate) fn parse_quantity(s: &str) -> (Option<f64>, &str) {
+    let (first, rest) = match parse_number(s) {
+        Some(parsed) => parsed,
+        None => return (None, s),
+    };
+
+    // Mixed number: "1 1/2"
+    let trimmed = rest.trim_start();
+    if let Some((fraction, after)) = parse_fraction(trimmed)
+        && first.fract() == 0.0
+    {
+        return (Some(first + fraction), after);
+    }
+
+    // Range: keep the lower bound, drop the upper
+    let range_rest = trimmed
+        .strip_prefix('-')
+        .or_else(|| trimmed.strip_prefix('–'))
+        .or_else(|| trimmed.strip_prefix("to "));
+    if let Some(range_rest) = range_rest
+        && let Some((_, after)) = parse_number(range_rest.trim_start())
+    {
+        return (Some(first), after);
+    }
+
+    (Some(first), rest)
+}
+
+/// A single number or simple fraction at the start of `s`
+fn parse_number(s: &str) -> Option<(f64, &str)> {
+    if let Some(parsed) = parse_fraction(s) {
+        return Some(parsed);
+    }
+
+    let end = s
+        .char_indices()
+        .find(|(_, c)| !(c.is_ascii_digit() || *c == '.' || *c == ','))
+        .map(|(i, _)| i)
+        .unwrap_or(s.len());
+    let digits = s[..end].trim_end_matches(['.', ',']);
+    if digits.is_empty() || !digits.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+
+    // "1,5" is a decimal comma; "1,000" is a thousands separator
+    let normalized = match digits.split_once(',') {
+        Some((_, frac)) if frac.len() == 3 && !frac.contains(',') => digits.replace(',', ""),
+        _ => digits.replacen(',', ".", 1),
+    };
+    let value = normalized.parse::<f64>().ok()?;
+    Some((value, &s[digits.len()..]))
+}
+
+/// "1/2" at the start of `s`
+fn parse_fraction(s: &str) -> Option<(f64, &str)> {
+    let slash = s.find('/')?;
+    let numerator: f64 = s[..slash].parse().ok()?;
+    let after = &s[slash + 1..];
+    let end = after
+        .char_indices()
+        .find(|(_, c)| !c.is_ascii_digit())
+        .map(|(i, _)| i)
+        .unwrap_or(after.len());
+    let denominator: f64 = after[..end].parse().ok()?;
+    if denominator == 0.0 {
+        return None;
+    }
+    Some((numerator / denominator, &after[end..]))
+}
+
+/// Match a known unit at the start of `s`, on a word boundary
+fn parse_unit(s: &str) -> (Option<String>, &str) {
+    let trimmed = s.trim_start();
+    for unit in UNITS {
+        if let Some(prefix) = trimmed.get(..unit.len())
+            && prefix.eq_ignore_ascii_case(unit)
+        {
+            let after = &trimmed[unit.len()..];
+            if after.is_empty() || after.starts_with(|c: char| !c.is_alphanumeric()) {
+                let after = after.strip_prefix('.').unwrap_or(after);
+                return (Some(prefix.to_string()), after.trim_start());
+            }
+        }
+    }
+    (None, trimmed)
+}
+
+/// "onion (large), finely chopped" -> ("onion", "large, finely chopped")
+pub(crate) fn split_notes(s: &str) -> (String, Option<String>) {
+    let mut name = s.to_string();
+    let mut notes = Vec::new();
+
+    if let Some(open) = name.find('(')
+        && let Some(close) = name[open..].find(')').map(|i| open + i)
+    {
+        notes.push(name[open + 1..close].trim().to_string());
+        name = format!("{} {}", &name[..open], &name[close + 1..]);
+    }
+
+    if let Some(comma) = name.find(',') {
+        let tail = name[comma + 1..].trim().to_string();
+        name.truncate(comma);
+        if !tail.is_empty() {
+            notes.push(tail);
+        }
+    }
+
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    let notes: Vec<String> = notes.into_iter().filter(|n| !n.is_empty()).collect();
+    let notes = if notes.is_empty() { None } else { Some(notes.join(", ")) };
+    (name, notes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ing(name: &str, quantity: Option<f64>, unit: Option<&str>, notes: Option<&str>) -> CreateIngredientInput {
+        CreateIngredientInput {
+            name: name.to_string(),
+            quantity,
+            unit: unit.map(|u| u.to_string()),
+            notes: notes.map(|n| n.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_simple_quantity_and_unit() {
+        assert_eq!(parse_ingredient_line("2 cups plain flour"), ing("plain flour", Some(2.0), Some("cups"), None));
+        assert_eq!(parse_ingredient_line("500g beef mince"), ing("beef mince", Some(500.0), Some("g"), None));
+        assert_eq!(parse_ingredient_line("1 tbsp. olive oil"), ing("olive oil", Some(1.0), Some("tbsp"), None));
+    }
+
+    #[test]
+    fn test_fractions() {
+        assert_eq!(parse_ingredient_line("½ tsp salt").quantity, Some(0.5));
+        assert_eq!(parse_ingredient_line("1½ cups milk").quantity, Some(1.5));
+        assert_eq!(parse_ingredient_line("1 1/2 cups milk").quantity, Some(1.5));
+        assert_eq!(parse_ingredient_line("3/4 cup sugar").quantity, Some(0.75));
+    }
+
+    #[test]
+    fn test_decimals_and_ranges() {
+        assert_eq!(parse_ingredient_line("1,5 kg potatoes").quantity, Some(1.5));
+        assert_eq!(parse_ingredient_line("1,000 g flour").quantity, Some(1000.0));
+        let range = parse_ingredient_line("2-3 cloves garlic");
+        assert_eq!(range.quantity, Some(2.0));
+        assert_eq!(range.unit.as_deref(), Some("cloves"));
+        assert_eq!(range.name, "garlic");
+    }
+
+    #[test]
+    fn test_notes() {
+        assert_eq!(
+            parse_ingredient_line("1 onion (large), finely chopped"),
+            ing("onion", Some(1.0), None, Some("large, finely chopped"))
+        );
+        assert_eq!(
+            parse_ingredient_line("200 g of dark chocolate"),
+            ing("dark chocolate", Some(200.0), Some("g"), None)
+        );
+    }
+
+    #[test]
+    fn test_unit_requires_word_boundary() {
+        // "l" must not be taken from "large"
+        assert_eq!(parse_ingredient_line("2 large eggs"), ing("large eggs", Some(2.0), None, None));
+    }
+
+    #[test]
+    fn test_no_quantity() {
+        assert_eq!(parse_ingredient_line("Salt and pepper"), ing("Salt and pepper", None, None, None));
+        assert_eq!(
+            parse_ingredient_line("Fresh basil, to serve"),
+            ing("Fresh basil", None, None, Some("to serve"))
+        );
+    }
+}
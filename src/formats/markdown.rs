@@ -0,0 +1,734 @@
+//! Markdown recipes with YAML front matter, for keeping recipes as text
+//! files. Metadata lives in the front matter, the title is the `#` heading,
+//! the description follows it, then `## Ingredients` and `## Steps`:
+//!
+//! ```text
+//! ---
+//! id: 0b6c…
+//! prep_time_minutes: 15
+//! servings: 24
+//! tags:
+//! - Cookies
+//! ---
+//!
+//! # Chocolate Chip Cookies
+//!
+//! Classic chewy cookies.
+//!
+//! ## Ingredients
+//!
+//! - **2.5 cups** flour _(all-purpose)_
+//! - salt
+//!
+//! ## Steps
+//!
+//! 1. Preheat the oven.
+//!    - Temperature: 180 Celsius
+//! 2. Bake.
+//!    - Duration: 12 minutes
+//! ```
+//!
+//! Text that would otherwise read as structure is escaped with a backslash:
+//! description and step lines starting with `#`, `-` or `\`, and `\`, `*`
+//! and `_` in ingredient fields. An amount that is only a unit but starts
+//! like a number is written `**\2-3**`. Rendering then parsing a recipe gives
+//! back the same recipe, as long as its title and ingredient fields are
+//! single trimmed lines; blank lines around a description or step are dropped.
+
+use serde::{Deserialize, Serialize};
+
+use super::FormatError;
+use crate::models::{CreateIngredientInput, CreateRecipeInput, CreateStepInput, RecipeWithDetails};
+
+const FRONT_MATTER_FENCE: &str = "---";
+const STEP_INDENT: &str = "   ";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FrontMatter {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    prep_time_minutes: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cook_time_minutes: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    servings: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    difficulty: Option<i32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    shared_by: Option<String>,
+}
+
+/// A recipe read from Markdown
+#[derive(Debug, Clone)]
+pub struct MarkdownRecipe {
+    /// Recipe ID from the front matter, if the file came from the vault
+    pub id: Option<String>,
+    pub recipe: CreateRecipeInput,
+}
+
+/// Render a recipe as Markdown
+pub fn to_markdown(recipe: &RecipeWithDetails) -> String {
+    render(Some(&recipe.recipe.id), &CreateRecipeInput::from(recipe))
+}
+
+/// Render recipe input as Markdown, with the recipe ID if it has one
+pub fn render(id: Option<&str>, recipe: &CreateRecipeInput) -> String {
+    let front_matter = FrontMatter {
+        id: id.map(String::from),
+        prep_time_minutes: recipe.prep_time_minutes,
+        cook_time_minutes: recipe.cook_time_minutes,
+        servings: recipe.servings,
+        difficulty: recipe.difficulty,
+        tags: recipe.tags.clone(),
+        source_url: recipe.source_url.clone(),
+        shared_by: recipe.shared_by.clone(),
+    };
+
+    let mut out = String::new();
+    let yaml = serde_yaml::to_string(&front_matter).unwrap_or_default();
+    if yaml.trim() != "{}" {
+        out.push_str(FRONT_MATTER_FENCE);
+        out.push('\n');
+        out.push_str(&yaml);
+        out.push_str(FRONT_MATTER_FENCE);
+        out.push_str("\n\n");
+    }
+
+    out.push_str(&format!("# {}\n", recipe.title));
+    if let Some(description) = &recipe.description {
+        out.push('\n');
+        for line in description.lines() {
+            out.push_str(&escape_line(line));
+            out.push('\n');
+        }
+    }
+
+    out.push_str("\n## Ingredients\n\n");
+    for ingredient in &recipe.ingredients {
+        out.push_str(&format!("- {}\n", render_ingredient(ingredient)));
+    }
+
+    out.push_str("\n## Steps\n\n");
+    for (index, step) in recipe.steps.iter().enumerate() {
+        let mut lines = step.instruction.lines();
+        out.push_str(&format!("{}. {}\n", index + 1, lines.next().unwrap_or("")));
+        for line in lines {
+            if line.is_empty() {
+                out.push('\n');
+            } else {
+                out.push_str(&format!("{}{}\n", STEP_INDENT, escape_line(line)));
+            }
+        }
+        if let Some(minutes) = step.duration_minutes {
+            out.push_str(&format!("{}- Duration: {} minutes\n", STEP_INDENT, minutes));
+        }
+        if let Some(value) = step.temperature_value {
+            match &step.temperature_unit {
+                Some(unit) => out.push_str(&format!("{}- Temperature: {} {}\n", STEP_INDENT, value, unit)),
+                None => out.push_str(&format!("{}- Temperature: {}\n", STEP_INDENT, value)),
+            }
+        }
+    }
+    out
+}
+
+/// Prefix a description or step line with a backslash when it would
+/// otherwise read as a heading, list item or step detail
+fn escape_line(line: &str) -> String {
+    let misread = line.starts_with(['#', '-', '\\']) || (!line.is_empty() && line.trim().is_empty());
+    if misread { format!("\\{}", line) } else { line.to_string() }
+}
+
+/// Undo `escape_line`
+fn unescape_line(line: &str) -> &str {
+    line.strip_prefix('\\').unwrap_or(line)
+}
+
+/// Escape the characters that delimit an ingredient's amount and notes
+fn escape_inline(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '_') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Undo `escape_inline`: a backslash keeps the character after it
+fn unescape_inline(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => out.push(chars.next().unwrap_or('\\')),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Byte offsets of `pattern` in `text`, skipping backslash-escaped characters
+fn unescaped_matches<'a>(text: &'a str, pattern: &'a str) -> impl Iterator<Item = usize> + 'a {
+    let mut escaped = false;
+    text.char_indices().filter_map(move |(pos, c)| {
+        let start = !escaped && text[pos..].starts_with(pattern);
+        escaped = !escaped && c == '\\';
+        start.then_some(pos)
+    })
+}
+
+/// "**2.5 cups** flour _(all-purpose)_"
+fn render_ingredient(ingredient: &CreateIngredientInput) -> String {
+    let amount = match (ingredient.quantity, &ingredient.unit) {
+        (Some(quantity), Some(unit)) => format!("{} {}", quantity, escape_inline(unit)),
+        (Some(quantity), None) => quantity.to_string(),
+        // A unit such as "2-3" would be read as a quantity
+        (None, Some(unit)) if looks_like_quantity(unit) => format!("\\{}", escape_inline(unit)),
+        (None, Some(unit)) => escape_inline(unit),
+        (None, None) => String::new(),
+    };
+    let name = escape_inline(&ingredient.name);
+    let mut line = if amount.is_empty() { name } else { format!("**{}** {}", amount, name) };
+    if let Some(notes) = &ingredient.notes {
+        line.push_str(&format!(" _({})_", escape_inline(notes)));
+    }
+    line
+}
+
+/// Whether an amount starting with this text is parsed as a quantity
+fn looks_like_quantity(amount: &str) -> bool {
+    let first = amount.split(' ').next().unwrap_or_default();
+    first.parse::<f64>().is_ok() || first.starts_with(|c: char| c.is_ascii_digit() || c == '.' || c == '-')
+}
+
+fn line_error(line: usize, message: impl Into<String>) -> FormatError {
+    FormatError::Line {
+        line,
+        message: message.into(),
+    }
+}
+
+#[derive(PartialEq)]
+enum Section {
+    Description,
+    Ingredients,
+    Steps,
+}
+
+/// Parse a Markdown recipe. Errors carry the 1-based line they were found on.
+pub fn parse_markdown(text: &str) -> Result<MarkdownRecipe, FormatError> {
+    let lines: Vec<&str> = text.lines().map(|l| l.trim_end_matches('\r')).collect();
+    let (front_matter, body_start) = parse_front_matter(&lines)?;
+
+    let mut title: Option<String> = None;
+    let mut description: Vec<&str> = Vec::new();
+    let mut ingredients = Vec::new();
+    let mut steps: Vec<CreateStepInput> = Vec::new();
+    let mut seen_ingredients = false;
+    let mut seen_steps = false;
+    let mut section = Section::Description;
+    // Blank lines inside a step, kept only if the step continues after them
+    let mut pending_blank_lines = 0;
+
+    for (index, line) in lines.iter().enumerate().skip(body_start) {
+        let line_number = index + 1;
+
+        if title.is_none() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let Some(heading) = line.strip_prefix("# ") else {
+                return Err(line_error(line_number, "Expected the recipe title as a '# ' heading"));
+            };
+            let heading = heading.trim();
+            if heading.is_empty() {
+                return Err(line_error(line_number, "The recipe title is empty"));
+            }
+            title = Some(heading.to_string());
+            continue;
+        }
+
+        if let Some(heading) = line.strip_prefix("## ") {
+            section = match heading.trim().to_lowercase().as_str() {
+                "ingredients" if !seen_ingredients => {
+                    seen_ingredients = true;
+                    Section::Ingredients
+                }
+                "steps" if !seen_steps => {
+                    seen_steps = true;
+                    Section::Steps
+                }
+                "ingredients" | "steps" => {
+                    return Err(line_error(line_number, format!("Duplicate section '{}'", heading.trim())));
+                }
+                _ => {
+                    return Err(line_error(
+                        line_number,
+                        format!("Unknown section '{}'. Expected 'Ingredients' or 'Steps'", heading.trim()),
+                    ));
+                }
+            };
+            pending_blank_lines = 0;
+            continue;
+        }
+        if line.starts_with('#') && section != Section::Description {
+            return Err(line_error(line_number, "Only '## Ingredients' and '## Steps' headings are allowed"));
+        }
+
+        match section {
+            Section::Description => description.push(unescape_line(line)),
+            Section::Ingredients => {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let Some(item) = line.strip_prefix("- ") else {
+                    return Err(line_error(line_number, "Ingredients must be list items starting with '- '"));
+                };
+                ingredients.push(parse_ingredient(item).map_err(|message| line_error(line_number, message))?);
+            }
+            Section::Steps => {
+                if line.trim().is_empty() {
+                    if !steps.is_empty() {
+                        pending_blank_lines += 1;
+                    }
+                    continue;
+                }
+                if let Some(instruction) = numbered_item(line) {
+                    steps.push(CreateStepInput {
+                        instruction: instruction.to_string(),
+                        ..Default::default()
+                    });
+                    pending_blank_lines = 0;
+                    continue;
+                }
+                let Some(step) = steps.last_mut() else {
+                    return Err(line_error(line_number, "Steps must be numbered list items like '1. '"));
+                };
+                let Some(content) = line.strip_prefix(STEP_INDENT) else {
+                    return Err(line_error(
+                        line_number,
+                        "Step continuation lines must be indented by three spaces",
+                    ));
+                };
+                if let Some(detail) = content.strip_prefix("- ") {
+                    parse_step_detail(step, detail).map_err(|message| line_error(line_number, message))?;
+                } else {
+                    if step.duration_minutes.is_some() || step.temperature_value.is_some() {
+                        return Err(line_error(line_number, "Step text must come before its duration and temperature"));
+                    }
+                    for _ in 0..pending_blank_lines {
+                        step.instruction.push('\n');
+                    }
+                    step.instruction.push('\n');
+                    step.instruction.push_str(unescape_line(content));
+                }
+                pending_blank_lines = 0;
+            }
+        }
+    }
+
+    let Some(title) = title else {
+        return Err(line_error(lines.len().max(1), "The recipe has no '# ' title"));
+    };
+
+    let description = description.join("\n").trim_matches('\n').to_string();
+    Ok(MarkdownRecipe {
+        id: front_matter.id,
+        recipe: CreateRecipeInput {
+            title,
+            description: if description.trim().is_empty() { None } else { Some(description) },
+            prep_time_minutes: front_matter.prep_time_minutes,
+            cook_time_minutes: front_matter.cook_time_minutes,
+            servings: front_matter.servings,
+            difficulty: front_matter.difficulty,
+            ingredients,
+            steps,
+            tags: front_matter.tags,
+            source_url: front_matter.source_url,
+            shared_by: front_matter.shared_by,
+        },
+    })
+}
+
+/// Read the front matter if the file starts with one. Returns it with the
+/// index of the first body line.
+fn parse_front_matter(lines: &[&str]) -> Result<(FrontMatter, usize), FormatError> {
+    if lines.first().map(|l| l.trim_end()) != Some(FRONT_MATTER_FENCE) {
+        return Ok((FrontMatter::default(), 0));
+    }
+    let Some(end) = lines.iter().skip(1).position(|l| l.trim_end() == FRONT_MATTER_FENCE).map(|p| p + 1) else {
+        return Err(line_error(1, "Front matter is not closed with '---'"));
+    };
+
+    let yaml = lines[1..end].join("\n");
+    if yaml.trim().is_empty() {
+        return Ok((FrontMatter::default(), end + 1));
+    }
+    let front_matter = serde_yaml::from_str(&yaml).map_err(|e| {
+        // Report the line in the file rather than within the front matter
+        let line = e.location().map(|l| l.line() + 1).unwrap_or(1);
+        let message = e.to_string();
+        let message = match message.rfind(" at line ") {
+            Some(pos) => message[..pos].to_string(),
+            None => message,
+        };
+        line_error(line, format!("Invalid front matter: {}", message))
+    })?;
+    Ok((front_matter, end + 1))
+}
+
+/// "12. Bake" -> "Bake"
+fn numbered_item(line: &str) -> Option<&str> {
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if digits == 0 {
+        return None;
+    }
+    let rest = &line[digits..];
+    rest.strip_prefix(". ").or_else(|| (rest == ".").then_some(""))
+}
+
+fn parse_ingredient(item: &str) -> Result<CreateIngredientInput, String> {
+    let mut rest = item.trim();
+
+    let mut notes = None;
+    if let Some(without_suffix) = rest.strip_suffix(")_")
+        && let Some(pos) = unescaped_matches(without_suffix, " _(").last()
+    {
+        notes = Some(unescape_inline(&without_suffix[pos + 3..]));
+        rest = without_suffix[..pos].trim_end();
+    }
+
+    let mut quantity = None;
+    let mut unit = None;
+    if let Some(after) = rest.strip_prefix("**") {
+        let Some(end) = unescaped_matches(after, "**").next() else {
+            return Err("Unclosed '**' around the ingredient amount".to_string());
+        };
+        let amount = after[..end].trim();
+        rest = after[end + 2..].trim_start();
+
+        // "**\2-3**" is a unit that only looks like a quantity
+        if amount.starts_with('\\') {
+            unit = Some(unescape_inline(amount));
+        } else {
+            let (first, remainder) = match amount.split_once(' ') {
+                Some((first, remainder)) => (first, Some(remainder.trim())),
+                None => (amount, None),
+            };
+            match first.parse::<f64>() {
+                Ok(value) if value.is_finite() => {
+                    quantity = Some(value);
+                    unit = remainder.map(unescape_inline);
+                }
+                _ if first.starts_with(|c: char| c.is_ascii_digit() || c == '.' || c == '-') => {
+                    return Err(format!("Invalid quantity '{}'; use a decimal number like 1.5", first));
+                }
+                _ => unit = Some(unescape_inline(amount)),
+            }
+        }
+        if amount.is_empty() {
+            return Err("Empty ingredient amount".to_string());
+        }
+    }
+
+    if rest.is_empty() {
+        return Err("Ingredient has no name".to_string());
+    }
+    Ok(CreateIngredientInput {
+        name: unescape_inline(rest),
+        quantity,
+        unit,
+        notes,
+    })
+}
+
+/// "Duration: 12 minutes" or "Temperature: 180 Celsius"
+fn parse_step_detail(step: &mut CreateStepInput, detail: &str) -> Result<(), String> {
+    let Some((field, value)) = detail.split_once(':') else {
+        return Err(format!("Unknown step detail '{}'. Expected 'Duration:' or 'Temperature:'", detail));
+    };
+    let value = value.trim();
+    match field.trim().to_lowercase().as_str() {
+        "duration" => {
+            let number = value
+                .strip_suffix("minutes")
+                .or_else(|| value.strip_suffix("minute"))
+                .or_else(|| value.strip_suffix("min"))
+                .unwrap_or(value)
+                .trim();
+            let minutes = number
+                .parse::<i32>()
+                .map_err(|_| format!("Invalid duration '{}'; use whole minutes like '12 minutes'", value))?;
+            step.duration_minutes = Some(minutes);
+        }
+        "temperature" => {
+            let (number, unit) = match value.split_once(' ') {
+                Some((number, unit)) => (number, Some(unit.trim().to_string())),
+                None => (value, None),
+            };
+            let temperature = number
+                .parse::<i32>()
+                .map_err(|_| format!("Invalid temperature '{}'; use a whole number like '180 Celsius'", value))?;
+            step.temperature_value = Some(temperature);
+            step.temperature_unit = unit;
+        }
+        other => {
+            return Err(format!("Unknown step detail '{}'. Expected 'Duration:' or 'Temperature:'", other));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> CreateRecipeInput {
+        CreateRecipeInput {
+            title: "Chocolate Chip Cookies".to_string(),
+            description: Some("Classic chewy cookies.\n\nBest warm.".to_string()),
+            prep_time_minutes: Some(15),
+            cook_time_minutes: Some(12),
+            servings: Some(24),
+            difficulty: Some(2),
+            ingredients: vec![
+                CreateIngredientInput {
+                    name: "flour".to_string(),
+                    quantity: Some(2.5),
+                    unit: Some("cups".to_string()),
+                    notes: Some("all-purpose, sifted".to_string()),
+                },
+                CreateIngredientInput {
+                    name: "eggs".to_string(),
+                    quantity: Some(2.0),
+                    ..Default::default()
+                },
+                CreateIngredientInput {
+                    name: "salt".to_string(),
+                    unit: Some("pinch".to_string()),
+                    ..Default::default()
+                },
+                CreateIngredientInput {
+                    name: "chocolate chips (dark)".to_string(),
+                    quantity: Some(1.0 / 3.0),
+                    unit: Some("14 oz bag".to_string()),
+                    ..Default::default()
+                },
+            ],
+            steps: vec![
+                CreateStepInput {
+                    instruction: "Preheat the oven.".to_string(),
+                    temperature_value: Some(180),
+                    temperature_unit: Some("Celsius".to_string()),
+                    ..Default::default()
+                },
+                CreateStepInput {
+                    instruction: "Mix everything.\nDon't overwork it.\n\nChill if the dough is soft.".to_string(),
+                    ..Default::default()
+                },
+                CreateStepInput {
+                    instruction: "Bake until golden.".to_string(),
+                    duration_minutes: Some(12),
+                    ..Default::default()
+                },
+            ],
+            tags: vec!["Cookies".to_string(), "Desserts: Baked".to_string()],
+            source_url: Some("https://example.com/cookies".to_string()),
+            shared_by: Some("alice@example.com".to_string()),
+        }
+    }
+
+    fn assert_same(a: &CreateRecipeInput, b: &CreateRecipeInput) {
+        assert_eq!(a.title, b.title);
+        assert_eq!(a.description, b.description);
+        assert_eq!(a.prep_time_minutes, b.prep_time_minutes);
+        assert_eq!(a.cook_time_minutes, b.cook_time_minutes);
+        assert_eq!(a.servings, b.servings);
+        assert_eq!(a.difficulty, b.difficulty);
+        assert_eq!(a.ingredients, b.ingredients);
+        assert_eq!(a.steps, b.steps);
+        assert_eq!(a.tags, b.tags);
+        assert_eq!(a.source_url, b.source_url);
+        assert_eq!(a.shared_by, b.shared_by);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let recipe = sample();
+        let markdown = render(Some("abc-123"), &recipe);
+        let parsed = parse_markdown(&markdown).unwrap();
+        assert_eq!(parsed.id.as_deref(), Some("abc-123"));
+        assert_same(&parsed.recipe, &recipe);
+        // Rendering again is stable
+        assert_eq!(render(Some("abc-123"), &parsed.recipe), markdown);
+    }
+
+    #[test]
+    fn test_round_trip_minimal() {
+        let recipe = CreateRecipeInput {
+            title: "Toast".to_string(),
+            ..Default::default()
+        };
+        let markdown = render(None, &recipe);
+        assert!(markdown.starts_with("# Toast\n"));
+        let parsed = parse_markdown(&markdown).unwrap();
+        assert_eq!(parsed.id, None);
+        assert_same(&parsed.recipe, &recipe);
+    }
+
+    #[test]
+    fn test_round_trip_markdown_lookalikes() {
+        let recipe = CreateRecipeInput {
+            title: "Pancakes".to_string(),
+            description: Some("Fluffy.\n## Ingredients\n- not an ingredient".to_string()),
+            ingredients: vec![
+                CreateIngredientInput {
+                    name: "eggs".to_string(),
+                    unit: Some("2-3".to_string()),
+                    ..Default::default()
+                },
+                CreateIngredientInput {
+                    name: "**bold** thing".to_string(),
+                    notes: Some("see _(below)_".to_string()),
+                    ..Default::default()
+                },
+            ],
+            steps: vec![CreateStepInput {
+                instruction: "Mix:\n- flour\n- sugar\n2. then rest".to_string(),
+                duration_minutes: Some(5),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let markdown = render(None, &recipe);
+        assert!(markdown.contains("\n\\## Ingredients\n\\- not an ingredient\n"));
+        assert!(markdown.contains("- **\\2-3** eggs\n"));
+        assert!(markdown.contains("- \\*\\*bold\\*\\* thing _(see \\_(below)\\_)_\n"));
+        assert!(markdown.contains("1. Mix:\n   \\- flour\n   \\- sugar\n   2. then rest\n   - Duration: 5 minutes\n"));
+        assert_same(&parse_markdown(&markdown).unwrap().recipe, &recipe);
+    }
+
+    /// Text that looks like Markdown structure, put in every field
+    const TRICKY: &[&str] = &[
+        "plain",
+        "## Ingredients",
+        "## Steps",
+        "# Heading",
+        "- flour",
+        "1. first",
+        "   - Duration: 5 minutes",
+        "**bold** thing",
+        "2-3",
+        "-1",
+        ".5 cup",
+        "inf",
+        "*",
+        "_(note)_",
+        "a _(b",
+        "x)_",
+        "back\\slash\\",
+        "\\",
+        "  indented",
+        "   ",
+    ];
+
+    #[test]
+    fn test_round_trip_tricky_text() {
+        for a in TRICKY {
+            for b in TRICKY {
+                let field = |text: &str| Some(text.trim().to_string()).filter(|t| !t.is_empty());
+                let ingredients = [
+                    field(a).map(|name| CreateIngredientInput {
+                        name,
+                        unit: field(b),
+                        notes: Some(b.to_string()),
+                        ..Default::default()
+                    }),
+                    field(b).map(|name| CreateIngredientInput {
+                        name,
+                        quantity: Some(1.5),
+                        unit: field(a),
+                        ..Default::default()
+                    }),
+                ];
+                let recipe = CreateRecipeInput {
+                    title: "Tricky".to_string(),
+                    description: Some(format!("Intro\n{}\n{}\nOutro", a, b)),
+                    ingredients: ingredients.into_iter().flatten().collect(),
+                    steps: vec![
+                        CreateStepInput {
+                            instruction: format!("{}\n{}\n\n{}", a, b, a),
+                            temperature_value: Some(180),
+                            ..Default::default()
+                        },
+                        CreateStepInput {
+                            instruction: format!("Mix:\n{}", b),
+                            ..Default::default()
+                        },
+                    ],
+                    ..Default::default()
+                };
+                let markdown = render(None, &recipe);
+                let parsed = parse_markdown(&markdown)
+                    .unwrap_or_else(|e| panic!("{:?} with {:?} / {:?}:\n{}", e, a, b, markdown))
+                    .recipe;
+                assert_same(&parsed, &recipe);
+                assert_eq!(render(None, &parsed), markdown);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rendered_layout() {
+        let markdown = render(None, &sample());
+        assert!(markdown.starts_with("---\nprep_time_minutes: 15\n"));
+        assert!(markdown.contains("- **2.5 cups** flour _(all-purpose, sifted)_\n"));
+        assert!(markdown.contains("- **pinch** salt\n"));
+        assert!(markdown.contains("1. Preheat the oven.\n   - Temperature: 180 Celsius\n"));
+        assert!(markdown.contains("2. Mix everything.\n   Don't overwork it.\n\n   Chill if the dough is soft.\n"));
+    }
+
+    #[test]
+    fn test_hand_written_file() {
+        let text = "# Omelette\n\n## Ingredients\n- **3** eggs\n- butter\n\n## Steps\n\n1. Whisk.\n1. Cook.\n   - Duration: 3 min\n";
+        let parsed = parse_markdown(text).unwrap().recipe;
+        assert_eq!(parsed.description, None);
+        assert_eq!(parsed.ingredients[0].quantity, Some(3.0));
+        assert_eq!(parsed.ingredients[1].name, "butter");
+        assert_eq!(parsed.steps.len(), 2);
+        assert_eq!(parsed.steps[1].duration_minutes, Some(3));
+    }
+
+    fn error_line(text: &str) -> (usize, String) {
+        match parse_markdown(text) {
+            Err(FormatError::Line { line, message }) => (line, message),
+            other => panic!("expected a line error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_errors_report_lines() {
+        assert_eq!(error_line("---\nservings: 4\n").0, 1);
+        assert_eq!(error_line("---\nservings: 4\ncolour: red\n---\n# T\n").0, 3);
+        assert_eq!(error_line("---\nservings: lots\n---\n# T\n").0, 2);
+        assert_eq!(error_line("Just text\n").0, 1);
+        assert_eq!(error_line("# T\n\n## Method\n").0, 3);
+        assert_eq!(error_line("# T\n## Ingredients\n- **2 cups flour\n").0, 3);
+        assert_eq!(error_line("# T\n## Ingredients\n- **1/2 cup** milk\n").0, 3);
+        assert_eq!(error_line("# T\n## Ingredients\n* flour\n").0, 3);
+        assert_eq!(error_line("# T\n## Steps\nMix.\n").0, 3);
+        assert_eq!(error_line("# T\n## Steps\n1. Bake.\n   - Duration: a while\n").0, 4);
+        assert_eq!(error_line("# T\n## Steps\n1. Bake.\nunindented\n").0, 4);
+
+        let (_, message) = error_line("---\ncolour: red\n---\n# T\n");
+        assert!(message.contains("colour"), "{}", message);
+    }
+}
@@ -0,0 +1,351 @@
+//! MealMaster text files. A file holds any number of recipes, each between a
+//! "MMMMM----- Recipe via Meal-Master" (or "----------") header and a line of
+//! just "MMMMM" (or "-----"). Ingredients use fixed columns, optionally two
+//! per line:
+//!
+//! ```text
+//!       1 c  Butter, softened                  3/4 c  Brown sugar
+//!  qty(7) unit(2) text                         second column at 41
+//! ```
+
+use super::{
+    ingredient_line::{parse_quantity, split_notes},
+    schema_org::parse_yield,
+    FormatError, ParsedRecipe,
+};
+use crate::models::{CreateIngredientInput, CreateRecipeInput, CreateStepInput};
+
+/// Start of the second ingredient column
+const SECOND_COLUMN: usize = 41;
+
+/// MealMaster unit codes and how they are stored
+const UNITS: &[(&str, Option<&str>)] = &[
+    ("x", None), // per serving
+    ("ea", None),
+    ("sm", Some("small")),
+    ("md", Some("medium")),
+    ("lg", Some("large")),
+    ("cn", Some("can")),
+    ("pk", Some("package")),
+    ("pn", Some("pinch")),
+    ("dr", Some("drop")),
+    ("ds", Some("dash")),
+    ("ct", Some("carton")),
+    ("bn", Some("bunch")),
+    ("sl", Some("slice")),
+    ("t", Some("tsp")),
+    ("ts", Some("tsp")),
+    ("T", Some("tbsp")),
+    ("tb", Some("tbsp")),
+    ("fl", Some("fl oz")),
+    ("c", Some("cup")),
+    ("pt", Some("pint")),
+    ("qt", Some("quart")),
+    ("ga", Some("gallon")),
+    ("oz", Some("oz")),
+    ("lb", Some("lb")),
+    ("ml", Some("ml")),
+    ("cb", Some("cc")),
+    ("cl", Some("cl")),
+    ("dl", Some("dl")),
+    ("l", Some("l")),
+    ("mg", Some("mg")),
+    ("cg", Some("cg")),
+    ("dg", Some("dg")),
+    ("g", Some("g")),
+    ("kg", Some("kg")),
+];
+
+/// Parse every recipe in a MealMaster file
+pub fn parse_mealmaster(text: &str) -> Result<Vec<Result<ParsedRecipe, FormatError>>, FormatError> {
+    let mut recipes = Vec::new();
+    let mut block: Option<Vec<&str>> = None;
+
+    for line in text.lines() {
+        let line = line.trim_end_matches('\r');
+        if is_recipe_header(line) {
+            if let Some(lines) = block.take() {
+                // Header without a matching end marker: keep what was read
+                recipes.push(parse_block(&lines));
+            }
+            block = Some(Vec::new());
+        } else if matches!(line.trim(), "MMMMM" | "-----") {
+            if let Some(lines) = block.take() {
+                recipes.push(parse_block(&lines));
+            }
+        } else if let Some(lines) = block.as_mut() {
+            lines.push(line);
+        }
+    }
+    if let Some(lines) = block {
+        recipes.push(parse_block(&lines));
+    }
+
+    if recipes.is_empty() {
+        return Err(FormatError::Invalid("No MealMaster recipes found".to_string()));
+    }
+    Ok(recipes)
+}
+
+/// Whether text looks like a MealMaster file
+pub fn looks_like_mealmaster(text: &str) -> bool {
+    text.lines().any(is_recipe_header)
+}
+
+fn is_recipe_header(line: &str) -> bool {
+    (line.starts_with("MMMMM") || line.starts_with("-----")) && line.to_lowercase().contains("meal-master")
+}
+
+#[derive(PartialEq)]
+enum Section {
+    Header,
+    Ingredients,
+    Directions,
+}
+
+fn parse_block(lines: &[&str]) -> Result<ParsedRecipe, FormatError> {
+    let mut title = None;
+    let mut tags = Vec::new();
+    let mut servings = None;
+    let mut ingredients: Vec<CreateIngredientInput> = Vec::new();
+    let mut paragraphs: Vec<String> = Vec::new();
+    let mut paragraph = String::new();
+    let mut warnings = Vec::new();
+    let mut section = Section::Header;
+
+    for line in lines {
+        if section == Section::Header {
+            if let Some((field, value)) = header_field(line) {
+                match field.as_str() {
+                    "title" => title = Some(value),
+                    "categories" => {
+                        tags = value
+                            .split(',')
+                            .map(|t| t.trim().to_string())
+                            .filter(|t| !t.is_empty() && !t.eq_ignore_ascii_case("none"))
+                            .collect();
+                    }
+                    "yield" | "servings" => servings = parse_yield(&value),
+                    _ => {}
+                }
+                continue;
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+            section = Section::Ingredients;
+        }
+
+        if let Some(heading) = section_heading(line) {
+            if section == Section::Ingredients {
+                warnings.push(format!("Ingredient heading '{}' was dropped", heading));
+            } else {
+                flush_paragraph(&mut paragraph, &mut paragraphs);
+                paragraph = format!("{}:", heading);
+            }
+            continue;
+        }
+
+        if section == Section::Ingredients {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if parse_ingredient_row(line, &mut ingredients) {
+                continue;
+            }
+            section = Section::Directions;
+        }
+
+        if line.trim().is_empty() {
+            flush_paragraph(&mut paragraph, &mut paragraphs);
+        } else {
+            if !paragraph.is_empty() {
+                paragraph.push(' ');
+            }
+            paragraph.push_str(line.trim());
+        }
+    }
+    flush_paragraph(&mut paragraph, &mut paragraphs);
+
+    let title = title
+        .filter(|t| !t.is_empty())
+        .ok_or_else(|| FormatError::Invalid("MealMaster recipe has no title".to_string()))?;
+
+    Ok(ParsedRecipe {
+        recipe: CreateRecipeInput {
+            title,
+            servings,
+            ingredients,
+            steps: paragraphs
+                .into_iter()
+                .map(|instruction| CreateStepInput {
+                    instruction,
+                    ..Default::default()
+                })
+                .collect(),
+            tags,
+            ..Default::default()
+        },
+        photo: None,
+        warnings,
+    })
+}
+
+fn flush_paragraph(paragraph: &mut String, paragraphs: &mut Vec<String>) {
+    let text = paragraph.trim();
+    // A heading with nothing under it is dropped
+    if !text.is_empty() && !text.ends_with(':') {
+        paragraphs.push(text.to_string());
+    }
+    paragraph.clear();
+}
+
+/// "      Title: Chocolate Chip Cookies" -> ("title", "Chocolate Chip Cookies")
+fn header_field(line: &str) -> Option<(String, String)> {
+    let (field, value) = line.split_once(':')?;
+    let field = field.trim().to_lowercase();
+    matches!(field.as_str(), "title" | "categories" | "yield" | "servings")
+        .then(|| (field, value.trim().to_string()))
+}
+
+/// "MMMMM-----FILLING-----" or "-----FILLING-----" -> "FILLING"
+fn section_heading(line: &str) -> Option<String> {
+    let trimmed = line.trim();
+    let inner = trimmed.strip_prefix("MMMMM").unwrap_or(trimmed);
+    if !inner.starts_with("---") {
+        return None;
+    }
+    let heading = inner.trim_matches('-').trim();
+    (!heading.is_empty()).then(|| heading.to_string())
+}
+
+/// Parse a fixed-column ingredient row (one or two ingredients) into
+/// `ingredients`. Returns false if the line is not an ingredient row.
+fn parse_ingredient_row(line: &str, ingredients: &mut Vec<CreateIngredientInput>) -> bool {
+    let chars: Vec<char> = line.chars().collect();
+    let left_end = if chars.len() > SECOND_COLUMN && chars[SECOND_COLUMN - 2..SECOND_COLUMN].iter().all(|c| *c == ' ') {
+        SECOND_COLUMN
+    } else {
+        chars.len()
+    };
+
+    let Some(left) = parse_column(&chars[..left_end]) else {
+        return false;
+    };
+    push_ingredient(left, ingredients);
+
+    if left_end < chars.len()
+        && let Some(right) = parse_column(&chars[left_end..])
+    {
+        push_ingredient(right, ingredients);
+    }
+    true
+}
+
+enum Column {
+    Ingredient(CreateIngredientInput),
+    /// "-finely chopped" continues the previous ingredient
+    Continuation(String),
+}
+
+fn parse_column(chars: &[char]) -> Option<Column> {
+    if chars.len() < 12 {
+        return None;
+    }
+    let field = |range: std::ops::Range<usize>| chars[range].iter().collect::<String>();
+    let quantity = field(0..7);
+    let unit_code = field(8..10);
+    let text = field(11..chars.len()).trim().to_string();
+
+    // Separator columns must be blank and the quantity column numeric
+    if chars[7] != ' ' || chars[10] != ' ' || text.is_empty() {
+        return None;
+    }
+    if !quantity.chars().all(|c| c.is_ascii_digit() || matches!(c, ' ' | '/' | '.' | '-')) {
+        return None;
+    }
+    let unit = match unit_code.trim() {
+        "" => None,
+        code => Some(UNITS.iter().find(|(c, _)| *c == code)?.1),
+    };
+
+    let quantity = quantity.trim();
+    if quantity.is_empty() && unit.is_none() && let Some(rest) = text.strip_prefix('-') {
+        return Some(Column::Continuation(rest.trim().to_string()));
+    }
+
+    let (name, notes) = split_notes(&text.replacen(';', ",", 1));
+    Some(Column::Ingredient(CreateIngredientInput {
+        name,
+        quantity: if quantity.is_empty() { None } else { parse_quantity(quantity).0 },
+        unit: unit.flatten().map(String::from),
+        notes,
+    }))
+}
+
+fn push_ingredient(column: Column, ingredients: &mut Vec<CreateIngredientInput>) {
+    match column {
+        Column::Ingredient(ingredient) => ingredients.push(ingredient),
+        Column::Continuation(text) => {
+            if let Some(last) = ingredients.last_mut() {
+                last.notes = Some(match last.notes.take() {
+                    Some(notes) => format!("{} {}", notes, text),
+                    None => text,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ingredients(line: &str) -> Vec<CreateIngredientInput> {
+        let mut out = Vec::new();
+        assert!(parse_ingredient_row(line, &mut out), "not an ingredient row: {:?}", line);
+        out
+    }
+
+    #[test]
+    fn test_single_column() {
+        let parsed = ingredients("  2 1/4 c  All-purpose flour");
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].name, "All-purpose flour");
+        assert_eq!(parsed[0].quantity, Some(2.25));
+        assert_eq!(parsed[0].unit.as_deref(), Some("cup"));
+    }
+
+    #[test]
+    fn test_notes_after_semicolon() {
+        let parsed = ingredients("      2 md Red onions; thinly sliced");
+        assert_eq!(parsed[0].name, "Red onions");
+        assert_eq!(parsed[0].unit.as_deref(), Some("medium"));
+        assert_eq!(parsed[0].notes.as_deref(), Some("thinly sliced"));
+    }
+
+    #[test]
+    fn test_directions_are_not_ingredients() {
+        let mut out = Vec::new();
+        assert!(!parse_ingredient_row("  Preheat oven to 375 F. Cream the butter and", &mut out));
+        assert!(!parse_ingredient_row("  1. Mix everything together in a large bowl", &mut out));
+    }
+
+    #[test]
+    fn test_section_heading() {
+        assert_eq!(
+            section_heading("MMMMM---------------FILLING---------------").as_deref(),
+            Some("FILLING")
+        );
+        assert_eq!(section_heading("-----SAUCE-----").as_deref(), Some("SAUCE"));
+        assert_eq!(section_heading("MMMMM"), None);
+        assert_eq!(section_heading("  Mix well"), None);
+    }
+
+    #[test]
+    fn test_missing_title() {
+        let text = "MMMMM----- Recipe via Meal-Master (tm) v8.05\n Categories: Soup\n\n      1 c  Water\nMMMMM\n";
+        let recipes = parse_mealmaster(text).unwrap();
+        assert!(recipes[0].is_err());
+    }
+}
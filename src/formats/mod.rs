@@ -0,0 +1,82 @@
+//! Conversions between recipes and external formats
+pub mod cooklang;
+pub mod ingredient_line;
+pub mod markdown;
+pub mod mealmaster;
+pub mod paprika;
+pub mod schema_org;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::models::CreateRecipeInput;
+
+pub use cooklang::{parse_cooklang, to_cooklang};
+pub use ingredient_line::parse_ingredient_line;
+pub use markdown::{parse_markdown, to_markdown, MarkdownRecipe};
+pub use mealmaster::parse_mealmaster;
+pub use paprika::{parse_paprika_archive, parse_paprika_recipe};
+pub use schema_org::{extract_recipe, to_json_ld, ExtractedRecipe, StructuredDataSource};
+
+#[derive(Debug, Error)]
+pub enum FormatError {
+    #[error("{0}")]
+    Invalid(String),
+    #[error("{entry}: {message}")]
+    Entry { entry: String, message: String },
+    #[error("line {line}: {message}")]
+    Line { line: usize, message: String },
+    #[error("The file contains no recipes")]
+    Empty,
+    #[error("Unrecognized file format. Supported: Paprika (.paprikarecipes, .paprikarecipe) and MealMaster")]
+    Unrecognized,
+}
+
+/// A recipe read from another recipe manager's file
+#[derive(Debug, Clone, Default)]
+pub struct ParsedRecipe {
+    pub recipe: CreateRecipeInput,
+    /// Embedded photo bytes
+    pub photo: Option<Vec<u8>>,
+    /// Data that could not be carried over
+    pub warnings: Vec<String>,
+}
+
+/// Recipe manager file formats that can be imported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecipeFileFormat {
+    /// `.paprikarecipes` export or a single `.paprikarecipe`
+    Paprika,
+    MealMaster,
+}
+
+impl RecipeFileFormat {
+    /// Identify a file from its contents
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        // Zip archive of recipes or a single gzip-compressed recipe
+        if bytes.starts_with(b"PK\x03\x04") || bytes.starts_with(&[0x1F, 0x8B]) {
+            return Some(RecipeFileFormat::Paprika);
+        }
+        let text = String::from_utf8_lossy(bytes);
+        mealmaster::looks_like_mealmaster(&text).then_some(RecipeFileFormat::MealMaster)
+    }
+}
+
+/// Parse a recipe manager file, detecting its format. Recipes that fail to
+/// parse are returned as errors so the rest of the file can still be imported.
+pub fn parse_recipe_file(
+    bytes: &[u8],
+) -> Result<(RecipeFileFormat, Vec<Result<ParsedRecipe, FormatError>>), FormatError> {
+    let format = RecipeFileFormat::detect(bytes).ok_or(FormatError::Unrecognized)?;
+    let recipes = match format {
+        RecipeFileFormat::Paprika if bytes.starts_with(&[0x1F, 0x8B]) => vec![parse_paprika_recipe(bytes)],
+        RecipeFileFormat::Paprika => parse_paprika_archive(bytes)?,
+        // MealMaster files predate UTF-8; fall back to Latin-1
+        RecipeFileFormat::MealMaster => match std::str::from_utf8(bytes) {
+            Ok(text) => parse_mealmaster(text)?,
+            Err(_) => parse_mealmaster(&bytes.iter().map(|&b| b as char).collect::<String>())?,
+        },
+    };
+    Ok((format, recipes))
+}
@@ -0,0 +1,273 @@
+//! Paprika exports: a `.paprikarecipes` file is a zip of `.paprikarecipe`
+//! entries, each a gzip-compressed JSON recipe. Photos are embedded as base64.
+
+use base64::Engine;
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Deserializer};
+use serde_json::Value as JsonValue;
+use std::io::{Cursor, Read};
+use zip::ZipArchive;
+
+use super::{
+    ingredient_line::parse_ingredient_line,
+    schema_org::{parse_iso8601_duration, parse_yield},
+    FormatError, ParsedRecipe,
+};
+use crate::models::{CreateRecipeInput, CreateStepInput};
+
+/// Largest decompressed recipe entry (photos make these a few MB at most)
+const MAX_ENTRY_BYTES: u64 = 20 * 1024 * 1024;
+
+const MAX_DESCRIPTION: usize = 2000;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct PaprikaRecipe {
+    name: String,
+    #[serde(deserialize_with = "lenient_string")]
+    description: Option<String>,
+    #[serde(deserialize_with = "lenient_string")]
+    ingredients: Option<String>,
+    #[serde(deserialize_with = "lenient_string")]
+    directions: Option<String>,
+    #[serde(deserialize_with = "lenient_string")]
+    notes: Option<String>,
+    #[serde(deserialize_with = "lenient_string")]
+    servings: Option<String>,
+    #[serde(deserialize_with = "lenient_string")]
+    prep_time: Option<String>,
+    #[serde(deserialize_with = "lenient_string")]
+    cook_time: Option<String>,
+    #[serde(deserialize_with = "lenient_string")]
+    total_time: Option<String>,
+    categories: Vec<String>,
+    #[serde(deserialize_with = "lenient_string")]
+    source: Option<String>,
+    #[serde(deserialize_with = "lenient_string")]
+    source_url: Option<String>,
+    #[serde(deserialize_with = "lenient_string")]
+    nutritional_info: Option<String>,
+    #[serde(deserialize_with = "lenient_string")]
+    photo_data: Option<String>,
+}
+
+/// Paprika writes some fields as numbers or null; keep any non-empty text
+fn lenient_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    let value = JsonValue::deserialize(deserializer)?;
+    let text = match value {
+        JsonValue::String(s) => s,
+        JsonValue::Number(n) => n.to_string(),
+        _ => return Ok(None),
+    };
+    let text = text.trim().to_string();
+    Ok(if text.is_empty() { None } else { Some(text) })
+}
+
+/// Parse a `.paprikarecipes` archive. Entries that cannot be read are
+/// returned as errors alongside the recipes that could.
+pub fn parse_paprika_archive(bytes: &[u8]) -> Result<Vec<Result<ParsedRecipe, FormatError>>, FormatError> {
+    let mut zip = ZipArchive::new(Cursor::new(bytes))
+        .map_err(|e| FormatError::Invalid(format!("Not a Paprika export: {}", e)))?;
+
+    let mut recipes = Vec::new();
+    for index in 0..zip.len() {
+        let mut entry = zip
+            .by_index(index)
+            .map_err(|e| FormatError::Invalid(format!("Corrupt Paprika export: {}", e)))?;
+        if entry.is_dir() {
+            continue;
+        }
+        let name = entry.name().to_string();
+
+        let mut compressed = Vec::new();
+        let parsed = entry
+            .by_ref()
+            .take(MAX_ENTRY_BYTES)
+            .read_to_end(&mut compressed)
+            .map_err(|e| e.to_string())
+            .and_then(|_| parse_paprika_recipe(&compressed).map_err(|e| e.to_string()));
+
+        recipes.push(parsed.map_err(|message| FormatError::Entry { entry: name, message }));
+    }
+
+    if recipes.is_empty() {
+        return Err(FormatError::Empty);
+    }
+    Ok(recipes)
+}
+
+/// Parse a single gzip-compressed `.paprikarecipe`
+pub fn parse_paprika_recipe(compressed: &[u8]) -> Result<ParsedRecipe, FormatError> {
+    let mut json = Vec::new();
+    GzDecoder::new(compressed)
+        .take(MAX_ENTRY_BYTES)
+        .read_to_end(&mut json)
+        .map_err(|e| FormatError::Invalid(format!("Not a gzip-compressed recipe: {}", e)))?;
+
+    let paprika: PaprikaRecipe =
+        serde_json::from_slice(&json).map_err(|e| FormatError::Invalid(format!("Invalid recipe JSON: {}", e)))?;
+    map_recipe(paprika)
+}
+
+fn map_recipe(paprika: PaprikaRecipe) -> Result<ParsedRecipe, FormatError> {
+    let title = paprika.name.trim().to_string();
+    if title.is_empty() {
+        return Err(FormatError::Invalid("Recipe has no name".to_string()));
+    }
+
+    let mut warnings = Vec::new();
+
+    let mut ingredients = Vec::new();
+    for line in paprika.ingredients.as_deref().unwrap_or("").lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        // Paprika has no ingredient groups; headings are plain lines ending in ':'
+        if let Some(heading) = line.strip_suffix(':') {
+            warnings.push(format!("Ingredient heading '{}' was dropped", heading.trim()));
+            continue;
+        }
+        ingredients.push(parse_ingredient_line(line));
+    }
+
+    let steps = paprika
+        .directions
+        .as_deref()
+        .unwrap_or("")
+        .lines()
+        .map(strip_step_number)
+        .filter(|line| !line.is_empty())
+        .map(|instruction| CreateStepInput {
+            instruction: instruction.to_string(),
+            ..Default::default()
+        })
+        .collect();
+
+    let mut description_parts: Vec<String> = paprika.description.into_iter().collect();
+    if let Some(notes) = paprika.notes {
+        description_parts.push(format!("Notes: {}", notes));
+    }
+    let source_url = paprika.source_url.filter(|url| url.starts_with("http://") || url.starts_with("https://"));
+    if source_url.is_none()
+        && let Some(source) = paprika.source
+    {
+        description_parts.push(format!("Source: {}", source));
+    }
+    let mut description = description_parts.join("\n\n");
+    if description.len() > MAX_DESCRIPTION {
+        let mut end = MAX_DESCRIPTION;
+        while !description.is_char_boundary(end) {
+            end -= 1;
+        }
+        description.truncate(end);
+        warnings.push(format!("Description was shortened to {} characters", MAX_DESCRIPTION));
+    }
+
+    if paprika.nutritional_info.is_some() {
+        warnings.push("Nutritional information was not imported".to_string());
+    }
+
+    let prep_time_minutes = paprika.prep_time.as_deref().and_then(parse_iso8601_duration);
+    let cook_time_minutes = paprika
+        .cook_time
+        .as_deref()
+        .and_then(parse_iso8601_duration)
+        .or_else(|| {
+            // Only a total is given: treat the remainder after prep as cooking
+            let total = paprika.total_time.as_deref().and_then(parse_iso8601_duration)?;
+            Some(total - prep_time_minutes.unwrap_or(0)).filter(|m| *m > 0)
+        });
+
+    let photo = match paprika.photo_data {
+        Some(data) => match base64::engine::general_purpose::STANDARD.decode(data.replace(['\n', '\r'], "")) {
+            Ok(bytes) => Some(bytes),
+            Err(e) => {
+                warnings.push(format!("Embedded photo could not be decoded: {}", e));
+                None
+            }
+        },
+        None => None,
+    };
+
+    Ok(ParsedRecipe {
+        recipe: CreateRecipeInput {
+            title,
+            description: if description.is_empty() { None } else { Some(description) },
+            prep_time_minutes,
+            cook_time_minutes,
+            servings: paprika.servings.as_deref().and_then(parse_yield),
+            difficulty: None,
+            ingredients,
+            steps,
+            tags: paprika.categories,
+            source_url,
+            shared_by: None,
+        },
+        photo,
+        warnings,
+    })
+}
+
+/// "1. Mix", "2) Bake", "Step 3: Serve" -> the instruction alone
+fn strip_step_number(line: &str) -> &str {
+    let line = line.trim();
+    let rest = line
+        .strip_prefix("Step ")
+        .or_else(|| line.strip_prefix("STEP "))
+        .unwrap_or(line);
+    let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if digits == 0 {
+        return line;
+    }
+    match rest[digits..].strip_prefix(['.', ')', ':']) {
+        Some(after) => after.trim_start(),
+        None => line,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gzip(json: &str) -> Vec<u8> {
+        use flate2::{write::GzEncoder, Compression};
+        use std::io::Write;
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(json.as_bytes()).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_strip_step_number() {
+        assert_eq!(strip_step_number("1. Mix well"), "Mix well");
+        assert_eq!(strip_step_number("12) Bake"), "Bake");
+        assert_eq!(strip_step_number("Step 3: Serve"), "Serve");
+        assert_eq!(strip_step_number("350 degrees is hot"), "350 degrees is hot");
+    }
+
+    #[test]
+    fn test_lenient_fields() {
+        let parsed = parse_paprika_recipe(&gzip(
+            r#"{"name": "Toast", "servings": 2, "photo_data": null, "categories": ["Breakfast"], "total_time": "10 mins"}"#,
+        ))
+        .unwrap();
+        assert_eq!(parsed.recipe.servings, Some(2));
+        assert_eq!(parsed.recipe.cook_time_minutes, Some(10));
+        assert_eq!(parsed.recipe.tags, vec!["Breakfast"]);
+        assert!(parsed.photo.is_none());
+    }
+
+    #[test]
+    fn test_rejects_unnamed_recipe() {
+        assert!(parse_paprika_recipe(&gzip(r#"{"name": " "}"#)).is_err());
+        assert!(parse_paprika_recipe(b"plain text").is_err());
+    }
+
+    #[test]
+    fn test_bad_photo_is_a_warning() {
+        let parsed = parse_paprika_recipe(&gzip(r#"{"name": "Toast", "photo_data": "***"}"#)).unwrap();
+        assert!(parsed.photo.is_none());
+        assert_eq!(parsed.warnings.len(), 1);
+    }
+}
@@ -0,0 +1,670 @@
+use scraper::{ElementRef, Html, Selector};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use super::ingredient_line::{normalize_fractions, parse_ingredient_line, parse_quantity};
+use crate::models::{CreateIngredientInput, CreateRecipeInput, CreateStepInput, RecipeIngredient, RecipeWithDetails};
+
+/// Where on the page the recipe data was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StructuredDataSource {
+    JsonLd,
+    Microdata,
+}
+
+/// A recipe extracted from schema.org markup
+#[derive(Debug, Clone)]
+pub struct ExtractedRecipe {
+    pub recipe: CreateRecipeInput,
+    /// Main recipe image as given on the page (may be relative)
+    pub image_url: Option<String>,
+    pub source: StructuredDataSource,
+}
+
+/// Extract a schema.org `Recipe` from an HTML page.
+///
+/// JSON-LD is preferred; microdata is used when the page has no usable
+/// JSON-LD recipe. Returns `None` when neither yields a recipe with a title.
+pub fn extract_recipe(html: &str) -> Option<ExtractedRecipe> {
+    let document = Html::parse_document(html);
+
+    if let Some(recipe) = extract_json_ld(&document) {
+        return Some(recipe);
+    }
+    extract_microdata(&document)
+}
+
+// ---------------------------------------------------------------------------
+// JSON-LD
+// ---------------------------------------------------------------------------
+
+fn extract_json_ld(document: &Html) -> Option<ExtractedRecipe> {
+    let selector = Selector::parse(r#"script[type="application/ld+json"]"#).unwrap();
+
+    for script in document.select(&selector) {
+        let raw: String = script.text().collect();
+        let raw = raw.trim().trim_start_matches("<!--").trim_end_matches("-->").trim();
+        let raw = raw.trim_end_matches(';');
+        let Ok(value) = serde_json::from_str::<JsonValue>(raw) else {
+            continue;
+        };
+
+        if let Some(node) = find_recipe_node(&value)
+            && let Some(recipe) = map_json_ld_recipe(node)
+        {
+            return Some(recipe);
+        }
+    }
+    None
+}
+
+/// Find the first object typed `Recipe`, looking through top-level arrays,
+/// `@graph` and `mainEntity`
+fn find_recipe_node(value: &JsonValue) -> Option<&JsonValue> {
+    match value {
+        JsonValue::Array(items) => items.iter().find_map(find_recipe_node),
+        JsonValue::Object(map) => {
+            if has_type(value, "Recipe") {
+                return Some(value);
+            }
+            ["@graph", "mainEntity", "mainEntityOfPage"]
+                .iter()
+                .filter_map(|key| map.get(*key))
+                .find_map(find_recipe_node)
+        }
+        _ => None,
+    }
+}
+
+/// `@type` may be a string or an array of strings, with or without the
+/// schema.org prefix
+fn has_type(value: &JsonValue, wanted: &str) -> bool {
+    let matches = |t: &JsonValue| {
+        t.as_str()
+            .map(|t| t.rsplit('/').next().unwrap_or(t).eq_ignore_ascii_case(wanted))
+            .unwrap_or(false)
+    };
+    match value.get("@type") {
+        Some(JsonValue::Array(types)) => types.iter().any(matches),
+        Some(t) => matches(t),
+        None => false,
+    }
+}
+
+fn map_json_ld_recipe(node: &JsonValue) -> Option<ExtractedRecipe> {
+    let title = node.get("name").and_then(json_text)?;
+
+    let ingredients: Vec<CreateIngredientInput> = node
+        .get("recipeIngredient")
+        .or_else(|| node.get("ingredients"))
+        .map(json_text_list)
+        .unwrap_or_default()
+        .iter()
+        .map(|line| parse_ingredient_line(line))
+        .collect();
+
+    let mut steps = Vec::new();
+    if let Some(instructions) = node.get("recipeInstructions") {
+        collect_json_ld_steps(instructions, None, &mut steps);
+    }
+
+    let prep = node.get("prepTime").and_then(json_text).and_then(|d| parse_iso8601_duration(&d));
+    let cook = node.get("cookTime").and_then(json_text).and_then(|d| parse_iso8601_duration(&d));
+    let total = node.get("totalTime").and_then(json_text).and_then(|d| parse_iso8601_duration(&d));
+
+    let recipe = build_recipe(
+        title,
+        node.get("description").and_then(json_text),
+        Durations { prep, cook, total },
+        node.get("recipeYield").and_then(parse_json_yield),
+        ingredients,
+        steps,
+    );
+
+    Some(ExtractedRecipe {
+        recipe,
+        image_url: node.get("image").and_then(json_image_url),
+        source: StructuredDataSource::JsonLd,
+    })
+}
+
+/// Flatten `recipeInstructions`, which may be a string, a list of strings,
+/// `HowToStep`s, or `HowToSection`s containing steps. The first step of a
+/// section is prefixed with the section name so the grouping isn't lost.
+fn collect_json_ld_steps(value: &JsonValue, section: Option<&str>, steps: &mut Vec<CreateStepInput>) {
+    let mut section = section.map(|s| s.to_string());
+
+    match value {
+        JsonValue::String(s) => {
+            for line in split_instruction_text(s) {
+                push_step(line, &mut section, steps);
+            }
+        }
+        JsonValue::Array(items) => {
+            for item in items {
+                if has_type(item, "HowToSection") {
+                    collect_section(item, steps);
+                } else if let Some(text) = item.get("text").or_else(|| item.get("name")).and_then(json_text) {
+                    push_step(text, &mut section, steps);
+                    if let Some(step) = steps.last_mut() {
+                        step.duration_minutes = item
+                            .get("timeRequired")
+                            .and_then(json_text)
+                            .and_then(|d| parse_iso8601_duration(&d));
+                    }
+                } else {
+                    let nested = item.get("itemListElement").unwrap_or(item);
+                    collect_json_ld_steps(nested, section.take().as_deref(), steps);
+                }
+            }
+        }
+        JsonValue::Object(_) if has_type(value, "HowToSection") => collect_section(value, steps),
+        JsonValue::Object(_) => {
+            if let Some(list) = value.get("itemListElement") {
+                collect_json_ld_steps(list, section.as_deref(), steps);
+            } else if let Some(text) = value.get("text").and_then(json_text) {
+                push_step(text, &mut section, steps);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn push_step(text: String, section: &mut Option<String>, steps: &mut Vec<CreateStepInput>) {
+    let instruction = match section.take() {
+        Some(name) => format!("{}: {}", name, text),
+        None => text,
+    };
+    steps.push(CreateStepInput {
+        instruction,
+        ..Default::default()
+    });
+}
+
+fn collect_section(section: &JsonValue, steps: &mut Vec<CreateStepInput>) {
+    let name = section.get("name").and_then(json_text);
+    if let Some(items) = section.get("itemListElement") {
+        collect_json_ld_steps(items, name.as_deref(), steps);
+    }
+}
+
+/// A JSON string or number as cleaned text
+fn json_text(value: &JsonValue) -> Option<String> {
+    let raw = match value {
+        JsonValue::String(s) => s.clone(),
+        JsonValue::Number(n) => n.to_string(),
+        JsonValue::Array(items) => return items.iter().find_map(json_text),
+        _ => return None,
+    };
+    let cleaned = clean_text(&raw);
+    if cleaned.is_empty() { None } else { Some(cleaned) }
+}
+
+fn json_text_list(value: &JsonValue) -> Vec<String> {
+    match value {
+        JsonValue::Array(items) => items.iter().filter_map(json_text).collect(),
+        JsonValue::String(s) => split_instruction_text(s),
+        _ => vec![],
+    }
+}
+
+fn parse_json_yield(value: &JsonValue) -> Option<i32> {
+    match value {
+        JsonValue::Number(n) => n.as_f64().map(|n| n as i32).filter(|n| *n > 0),
+        JsonValue::String(s) => parse_yield(s),
+        JsonValue::Array(items) => items.iter().find_map(parse_json_yield),
+        _ => None,
+    }
+}
+
+/// `image` may be a URL, a list of URLs, or an `ImageObject`
+fn json_image_url(value: &JsonValue) -> Option<String> {
+    match value {
+        JsonValue::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        JsonValue::Array(items) => items.iter().find_map(json_image_url),
+        JsonValue::Object(map) => map
+            .get("url")
+            .or_else(|| map.get("contentUrl"))
+            .and_then(json_image_url),
+        _ => None,
+    }
+}
+
+/// Build a schema.org `Recipe` JSON-LD object for a stored recipe.
+///
+/// Ingredients are written as "qty unit name, notes" lines so that
+/// `parse_ingredient_line` reads them back unchanged. Step temperatures have
+/// no schema.org property and stay only in the instruction text.
+pub fn to_json_ld(recipe: &RecipeWithDetails, image_url: Option<&str>, page_url: Option<&str>) -> JsonValue {
+    let r = &recipe.recipe;
+    let mut node = serde_json::json!({
+        "@context": "https://schema.org",
+        "@type": "Recipe",
+        "name": r.title,
+        "recipeIngredient": recipe.ingredients.iter().map(ingredient_line).collect::<Vec<_>>(),
+        "recipeInstructions": recipe
+            .steps
+            .iter()
+            .map(|step| {
+                let mut how_to = serde_json::json!({"@type": "HowToStep", "text": step.instruction});
+                if let Some(minutes) = step.duration_minutes {
+                    how_to["timeRequired"] = format_iso8601_duration(minutes).into();
+                }
+                how_to
+            })
+            .collect::<Vec<_>>(),
+    });
+
+    if let Some(description) = &r.description {
+        node["description"] = description.as_str().into();
+    }
+    if let Some(prep) = r.prep_time_minutes {
+        node["prepTime"] = format_iso8601_duration(prep).into();
+    }
+    if let Some(cook) = r.cook_time_minutes {
+        node["cookTime"] = format_iso8601_duration(cook).into();
+    }
+    if let Some(total) = r.total_time_minutes() {
+        node["totalTime"] = format_iso8601_duration(total).into();
+    }
+    if let Some(servings) = r.servings {
+        node["recipeYield"] = format!("{} servings", servings).into();
+    }
+    if let Some(image) = image_url {
+        node["image"] = image.into();
+    }
+    if let Some(url) = page_url {
+        node["url"] = url.into();
+    }
+    if let Some(date) = r.created_at.get(..10) {
+        node["datePublished"] = date.into();
+    }
+    node
+}
+
+/// "225 g unsalted butter, softened"
+fn ingredient_line(ingredient: &RecipeIngredient) -> String {
+    let mut line = String::new();
+    if let Some(quantity) = ingredient.quantity {
+        line.push_str(&format!("{} ", quantity));
+    }
+    if let Some(unit) = ingredient.unit.as_deref().filter(|u| !u.is_empty()) {
+        line.push_str(&format!("{} ", unit));
+    }
+    line.push_str(&ingredient.name);
+    if let Some(notes) = ingredient.notes.as_deref().filter(|n| !n.is_empty()) {
+        line.push_str(&format!(", {}", notes));
+    }
+    line
+}
+
+/// Whole minutes as an ISO-8601 duration ("PT1H30M")
+pub fn format_iso8601_duration(minutes: i32) -> String {
+    let (hours, minutes) = (minutes / 60, minutes % 60);
+    match (hours, minutes) {
+        (0, m) => format!("PT{}M", m),
+        (h, 0) => format!("PT{}H", h),
+        (h, m) => format!("PT{}H{}M", h, m),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Microdata
+// ---------------------------------------------------------------------------
+
+fn extract_microdata(document: &Html) -> Option<ExtractedRecipe> {
+    let scope_selector = Selector::parse("[itemscope][itemtype]").unwrap();
+    let prop_selector = Selector::parse("[itemprop]").unwrap();
+
+    let root = document.select(&scope_selector).find(|el| {
+        el.value()
+            .attr("itemtype")
+            .map(|t| t.split_whitespace().any(|t| t.trim_end_matches('/').ends_with("schema.org/Recipe")))
+            .unwrap_or(false)
+    })?;
+
+    let mut props: Vec<(String, String)> = Vec::new();
+    for el in root.select(&prop_selector) {
+        if nearest_scope(el).map(|scope| scope.id()) != Some(root.id()) {
+            continue;
+        }
+        let value = microdata_value(el);
+        if value.is_empty() {
+            continue;
+        }
+        for name in el.value().attr("itemprop").unwrap_or("").split_whitespace() {
+            props.push((name.to_string(), value.clone()));
+        }
+    }
+
+    let first = |name: &str| props.iter().find(|(n, _)| n == name).map(|(_, v)| v.clone());
+    let all = |names: &[&str]| -> Vec<String> {
+        props
+            .iter()
+            .filter(|(n, _)| names.contains(&n.as_str()))
+            .map(|(_, v)| v.clone())
+            .collect()
+    };
+
+    let title = first("name")?;
+    let ingredients = all(&["recipeIngredient", "ingredients"])
+        .iter()
+        .map(|line| parse_ingredient_line(line))
+        .collect();
+    let instructions = all(&["recipeInstructions"]);
+    let steps = if instructions.len() == 1 {
+        split_instruction_text(&instructions[0])
+    } else {
+        instructions
+    }
+    .into_iter()
+    .map(|instruction| CreateStepInput {
+        instruction,
+        ..Default::default()
+    })
+    .collect();
+
+    let recipe = build_recipe(
+        title,
+        first("description"),
+        Durations {
+            prep: first("prepTime").and_then(|d| parse_iso8601_duration(&d)),
+            cook: first("cookTime").and_then(|d| parse_iso8601_duration(&d)),
+            total: first("totalTime").and_then(|d| parse_iso8601_duration(&d)),
+        },
+        first("recipeYield").and_then(|y| parse_yield(&y)),
+        ingredients,
+        steps,
+    );
+
+    Some(ExtractedRecipe {
+        recipe,
+        image_url: first("image"),
+        source: StructuredDataSource::Microdata,
+    })
+}
+
+/// The closest `itemscope` element that a property belongs to. A property
+/// element that is itself an item belongs to its parent's scope.
+fn nearest_scope(el: ElementRef<'_>) -> Option<ElementRef<'_>> {
+    el.ancestors()
+        .filter_map(ElementRef::wrap)
+        .find(|a| a.value().attr("itemscope").is_some())
+}
+
+/// Property value per the microdata spec: attribute values for media and
+/// meta elements, text content otherwise
+fn microdata_value(el: ElementRef<'_>) -> String {
+    let element = el.value();
+    let attr = match element.name() {
+        "meta" => element.attr("content"),
+        "img" | "source" | "audio" | "video" => element.attr("src"),
+        "a" | "link" | "area" => element.attr("href"),
+        "time" => element.attr("datetime"),
+        "data" | "meter" => element.attr("value"),
+        _ => element.attr("content"),
+    };
+    match attr {
+        Some(value) => clean_text(value),
+        None => normalize_whitespace(&el.text().collect::<String>()),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Shared helpers
+// ---------------------------------------------------------------------------
+
+struct Durations {
+    prep: Option<i32>,
+    cook: Option<i32>,
+    total: Option<i32>,
+}
+
+fn build_recipe(
+    title: String,
+    description: Option<String>,
+    durations: Durations,
+    servings: Option<i32>,
+    ingredients: Vec<CreateIngredientInput>,
+    steps: Vec<CreateStepInput>,
+) -> CreateRecipeInput {
+    // Some sites only publish totalTime; keep it rather than drop it
+    let cook = match (durations.prep, durations.cook, durations.total) {
+        (None, None, Some(total)) => Some(total),
+        (_, cook, _) => cook,
+    };
+
+    CreateRecipeInput {
+        title: truncate(&title, 200),
+        description: description.map(|d| truncate(&d, 2000)),
+        prep_time_minutes: durations.prep,
+        cook_time_minutes: cook,
+        servings,
+        ingredients: ingredients.into_iter().filter(|i| !i.name.is_empty()).collect(),
+        steps,
+        ..Default::default()
+    }
+}
+
+/// Parse an ISO-8601 duration ("PT1H30M", "P0DT0H20M", "PT0.5H") into whole
+/// minutes. Free-text durations like "1 hour 20 mins" are also accepted since
+/// some sites publish those. Zero durations are treated as absent.
+pub fn parse_iso8601_duration(s: &str) -> Option<i32> {
+    let s = s.trim();
+    let upper = s.to_uppercase();
+
+    let minutes = if let Some(body) = upper.strip_prefix('P') {
+        let mut total = 0.0;
+        let mut in_time = false;
+        let mut number = String::new();
+        for c in body.chars() {
+            match c {
+                'T' => in_time = true,
+                '0'..='9' | '.' | ',' => number.push(if c == ',' { '.' } else { c }),
+                _ => {
+                    let value: f64 = number.parse().ok()?;
+                    number.clear();
+                    total += match (c, in_time) {
+                        ('W', false) => value * 7.0 * 24.0 * 60.0,
+                        ('D', false) => value * 24.0 * 60.0,
+                        ('H', true) => value * 60.0,
+                        ('M', true) => value,
+                        ('S', true) => value / 60.0,
+                        // Years and months are meaningless for recipes
+                        _ => return None,
+                    };
+                }
+            }
+        }
+        if !number.is_empty() {
+            return None;
+        }
+        total
+    } else {
+        parse_free_text_duration(s)?
+    };
+
+    let minutes = minutes.round() as i32;
+    if minutes > 0 { Some(minutes) } else { None }
+}
+
+/// "1 hour 20 mins", "45 minutes", "1½ hrs", "1 1/2 hours"
+fn parse_free_text_duration(s: &str) -> Option<f64> {
+    let text = normalize_fractions(&s.to_lowercase());
+    let mut rest = text.as_str();
+    let mut total = 0.0;
+    let mut found = false;
+    let mut pending: Option<f64> = None;
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            break;
+        }
+        if let (Some(value), after) = parse_quantity(rest) {
+            pending = Some(value);
+            rest = after;
+            continue;
+        }
+        let word_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let (unit, after) = rest.split_at(word_end);
+        rest = after;
+        if let Some(value) = pending {
+            if unit.starts_with('h') {
+                total += value * 60.0;
+                found = true;
+                pending = None;
+            } else if unit.starts_with("min") || unit == "m" {
+                total += value;
+                found = true;
+                pending = None;
+            }
+        }
+    }
+    if found { Some(total) } else { None }
+}
+
+/// First positive integer in a yield string ("Serves 4-6" -> 4)
+pub(crate) fn parse_yield(s: &str) -> Option<i32> {
+    let start = s.find(|c: char| c.is_ascii_digit())?;
+    let digits: String = s[start..].chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok().filter(|n| *n > 0)
+}
+
+/// Decode HTML entities, strip tags and collapse whitespace
+fn clean_text(raw: &str) -> String {
+    if !raw.contains(['<', '&']) {
+        return normalize_whitespace(raw);
+    }
+    let fragment = Html::parse_fragment(raw);
+    normalize_whitespace(&fragment.root_element().text().collect::<String>())
+}
+
+fn normalize_whitespace(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Instructions published as one block of text: one step per non-empty line
+fn split_instruction_text(s: &str) -> Vec<String> {
+    let with_breaks = s.replace("<br>", "\n").replace("<br/>", "\n").replace("<br />", "\n");
+    with_breaks
+        .lines()
+        .map(clean_text)
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+fn truncate(s: &str, max: usize) -> String {
+    if s.len() <= max {
+        return s.to_string();
+    }
+    let mut end = max;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    s[..end].trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_iso8601_duration() {
+        assert_eq!(parse_iso8601_duration("PT20M"), Some(20));
+        assert_eq!(parse_iso8601_duration("PT1H30M"), Some(90));
+        assert_eq!(parse_iso8601_duration("P0DT0H45M"), Some(45));
+        assert_eq!(parse_iso8601_duration("P1DT2H"), Some(1560));
+        assert_eq!(parse_iso8601_duration("PT0.5H"), Some(30));
+        assert_eq!(parse_iso8601_duration("PT90S"), Some(2));
+        assert_eq!(parse_iso8601_duration("PT0M"), None);
+        assert_eq!(parse_iso8601_duration("P1Y"), None);
+        assert_eq!(parse_iso8601_duration("PT"), None);
+    }
+
+    #[test]
+    fn test_format_iso8601_duration_round_trips() {
+        for minutes in [5, 60, 90, 1440] {
+            assert_eq!(parse_iso8601_duration(&format_iso8601_duration(minutes)), Some(minutes));
+        }
+        assert_eq!(format_iso8601_duration(90), "PT1H30M");
+    }
+
+    #[test]
+    fn test_parse_free_text_duration() {
+        assert_eq!(parse_iso8601_duration("1 hour 20 mins"), Some(80));
+        assert_eq!(parse_iso8601_duration("45 minutes"), Some(45));
+        assert_eq!(parse_iso8601_duration("1½ hrs"), Some(90));
+        assert_eq!(parse_iso8601_duration("1 1/2 hours"), Some(90));
+        assert_eq!(parse_iso8601_duration("¾ hour"), Some(45));
+        assert_eq!(parse_iso8601_duration("2h 15m"), Some(135));
+        assert_eq!(parse_iso8601_duration("soon"), None);
+    }
+
+    #[test]
+    fn test_parse_yield() {
+        assert_eq!(parse_json_yield(&serde_json::json!("Serves 4-6")), Some(4));
+        assert_eq!(parse_json_yield(&serde_json::json!(["8", "8 slices"])), Some(8));
+        assert_eq!(parse_json_yield(&serde_json::json!(2)), Some(2));
+        assert_eq!(parse_json_yield(&serde_json::json!("a loaf")), None);
+    }
+
+    #[test]
+    fn test_type_array_and_graph() {
+        let html = r#"<script type="application/ld+json">
+            {"@context":"https://schema.org","@graph":[
+                {"@type":"WebPage","name":"Page"},
+                {"@type":["Recipe","NewsArticle"],"name":"Soup &amp; Bread","recipeIngredient":["1 l stock"]}
+            ]}</script>"#;
+        let extracted = extract_recipe(html).unwrap();
+        assert_eq!(extracted.source, StructuredDataSource::JsonLd);
+        assert_eq!(extracted.recipe.title, "Soup & Bread");
+        assert_eq!(extracted.recipe.ingredients[0].unit.as_deref(), Some("l"));
+    }
+
+    #[test]
+    fn test_instruction_variants() {
+        let node = serde_json::json!([
+            "Preheat the oven.",
+            {"@type": "HowToStep", "text": "Mix <b>well</b>."},
+            {"@type": "HowToSection", "name": "For the glaze", "itemListElement": [
+                {"@type": "HowToStep", "text": "Whisk sugar and juice."},
+                {"@type": "HowToStep", "text": "Pour over the cake."}
+            ]}
+        ]);
+        let mut steps = Vec::new();
+        collect_json_ld_steps(&node, None, &mut steps);
+        let texts: Vec<&str> = steps.iter().map(|s| s.instruction.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "Preheat the oven.",
+                "Mix well.",
+                "For the glaze: Whisk sugar and juice.",
+                "Pour over the cake."
+            ]
+        );
+    }
+
+    #[test]
+    fn test_image_variants() {
+        assert_eq!(json_image_url(&serde_json::json!("/a.jpg")).as_deref(), Some("/a.jpg"));
+        assert_eq!(json_image_url(&serde_json::json!(["/a.jpg", "/b.jpg"])).as_deref(), Some("/a.jpg"));
+        assert_eq!(
+            json_image_url(&serde_json::json!({"@type": "ImageObject", "url": "/c.jpg"})).as_deref(),
+            Some("/c.jpg")
+        );
+    }
+
+    #[test]
+    fn test_invalid_json_ld_is_skipped() {
+        let html = r#"<script type="application/ld+json">{not json</script>
+            <script type="application/ld+json">{"@type":"Recipe","name":"Toast"}</script>"#;
+        assert_eq!(extract_recipe(html).unwrap().recipe.title, "Toast");
+    }
+
+    #[test]
+    fn test_no_structured_data() {
+        assert!(extract_recipe("<html><body><h1>Just a blog</h1></body></html>").is_none());
+    }
+}
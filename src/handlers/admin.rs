@@ -0,0 +1,70 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    auth::UserIdentity,
+    error::{ApiError, ApiResult},
+    photo_integrity::{self, IntegrityReport, DEFAULT_GRACE_PERIOD},
+    photo_store::PhotoStore,
+};
+
+/// Shared state for instance-wide maintenance handlers
+#[derive(Clone)]
+pub struct AdminState {
+    pub pool: SqlitePool,
+    pub photo_store: Arc<dyn PhotoStore>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PhotoCheckQuery {
+    /// Ignore files modified within this many minutes (default 60)
+    #[serde(default)]
+    pub grace_minutes: Option<u64>,
+}
+
+impl PhotoCheckQuery {
+    fn grace(&self) -> Duration {
+        self.grace_minutes
+            .map(|minutes| Duration::from_secs(minutes * 60))
+            .unwrap_or(DEFAULT_GRACE_PERIOD)
+    }
+}
+
+/// Admin endpoints cover every family's data, so they need an admin API key
+/// or the instance API key
+pub(crate) fn require_admin(extensions: &axum::http::Extensions) -> ApiResult<()> {
+    match extensions.get::<UserIdentity>() {
+        Some(identity) if identity.family_members.is_some() => Err(ApiError::Forbidden(
+            "Admin endpoints require an admin API key".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// GET /api/admin/photos/check — report orphan, missing and stale temp files
+pub async fn check_photos(
+    State(state): State<AdminState>,
+    Query(query): Query<PhotoCheckQuery>,
+    extensions: axum::http::Extensions,
+) -> ApiResult<Json<IntegrityReport>> {
+    require_admin(&extensions)?;
+    let report = photo_integrity::check(&state.pool, state.photo_store.as_ref(), query.grace()).await?;
+    Ok(Json(report))
+}
+
+/// POST /api/admin/photos/repair — fix what the check finds
+pub async fn repair_photos(
+    State(state): State<AdminState>,
+    Query(query): Query<PhotoCheckQuery>,
+    extensions: axum::http::Extensions,
+) -> ApiResult<Json<IntegrityReport>> {
+    require_admin(&extensions)?;
+    let report = photo_integrity::repair(&state.pool, state.photo_store.as_ref(), query.grace()).await?;
+    tracing::info!("Photo repair requested: {:?}", report.repaired);
+    Ok(Json(report))
+}
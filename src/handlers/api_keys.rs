@@ -0,0 +1,178 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::SqlitePool;
+
+use crate::{
+    auth::{LegacyApiKey, UserIdentity},
+    db::queries::{self, NewApiKey},
+    error::{ApiError, ApiResult},
+    handlers::admin::require_admin,
+    models::{
+        api_key::{api_key_prefix, generate_api_key, hash_api_key, CreateApiKeyInput},
+        ApiKey, ApiKeyScope,
+    },
+};
+
+pub const DEFAULT_ROTATION_GRACE_HOURS: u64 = 24;
+pub const MAX_ROTATION_GRACE_HOURS: u64 = 720;
+
+/// Shared state for API key management
+#[derive(Clone)]
+pub struct ApiKeysState {
+    pub pool: SqlitePool,
+    pub legacy_key: LegacyApiKey,
+}
+
+/// A newly created key, the only time the key itself is shown
+#[derive(Debug, Serialize)]
+pub struct CreatedApiKey {
+    pub key: String,
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RotateApiKeyInput {
+    /// Hours the previous key keeps working (default 24, 0 for none)
+    #[serde(default)]
+    pub grace_hours: Option<u64>,
+}
+
+/// The caller's email, and whether they may act for anyone (god mode)
+fn caller(extensions: &axum::http::Extensions) -> (Option<String>, bool) {
+    let email = extensions
+        .get::<UserIdentity>()
+        .and_then(|identity| identity.email.clone());
+    (email, require_admin(extensions).is_ok())
+}
+
+/// Whose keys the caller may see and revoke: their own, or everyone's in god mode
+fn key_owner_filter(extensions: &axum::http::Extensions) -> ApiResult<Option<String>> {
+    match caller(extensions) {
+        (_, true) => Ok(None),
+        (Some(email), false) => Ok(Some(email)),
+        (None, false) => Err(ApiError::Validation("Authentication required".to_string())),
+    }
+}
+
+/// POST /api/api-keys — create a key for the caller (admins: for anyone)
+pub async fn create_api_key(
+    State(state): State<ApiKeysState>,
+    extensions: axum::http::Extensions,
+    Json(mut input): Json<CreateApiKeyInput>,
+) -> ApiResult<(StatusCode, Json<CreatedApiKey>)> {
+    input.validate()?;
+    let (caller_email, is_admin) = caller(&extensions);
+
+    let email = match (input.email.take(), caller_email.as_deref()) {
+        (Some(email), Some(own)) if email == own => email,
+        (Some(email), _) if is_admin => email,
+        (Some(_), _) => {
+            return Err(ApiError::Forbidden("Only admins can create keys for other people".to_string()));
+        }
+        (None, Some(own)) => own.to_string(),
+        (None, None) => {
+            return Err(ApiError::Validation("Set \"email\" to the person the key is for".to_string()));
+        }
+    };
+    if input.scope == ApiKeyScope::Admin && !is_admin {
+        return Err(ApiError::Forbidden("Only admins can create admin keys".to_string()));
+    }
+
+    // A key can't hand out more than it has: no wider scope, no later expiry
+    let mut expires_at = input.expires_at();
+    if let Some(calling_key) = extensions.get::<ApiKey>() {
+        if calling_key.scope == ApiKeyScope::ReadOnly {
+            return Err(ApiError::Forbidden("Read-only keys can't create keys".to_string()));
+        }
+        if input.scope > calling_key.scope {
+            return Err(ApiError::Forbidden(
+                "A key can only create keys with the same scope or less".to_string(),
+            ));
+        }
+        if let Some(limit) = &calling_key.expires_at
+            && expires_at.as_ref().is_none_or(|expires_at| expires_at > limit)
+        {
+            expires_at = Some(limit.clone());
+        }
+    }
+
+    let key = generate_api_key();
+    let api_key = queries::create_api_key(
+        &state.pool,
+        NewApiKey {
+            key_hash: &hash_api_key(&key),
+            prefix: &api_key_prefix(&key),
+            label: &input.label,
+            email: &email,
+            scope: input.scope,
+            created_by: caller_email.as_deref(),
+            expires_at: expires_at.as_deref(),
+            chat_agent: false,
+        },
+    )
+    .await?;
+    tracing::info!("Created {:?} API key {} for {}", api_key.scope, api_key.id, email);
+
+    Ok((StatusCode::CREATED, Json(CreatedApiKey { key, api_key })))
+}
+
+/// GET /api/api-keys — the caller's keys (admins: every key)
+pub async fn list_api_keys(
+    State(state): State<ApiKeysState>,
+    extensions: axum::http::Extensions,
+) -> ApiResult<Json<Vec<ApiKey>>> {
+    let owner = key_owner_filter(&extensions)?;
+    Ok(Json(queries::list_api_keys(&state.pool, owner.as_deref()).await?))
+}
+
+/// DELETE /api/api-keys/:id — revoke a key immediately
+pub async fn revoke_api_key(
+    State(state): State<ApiKeysState>,
+    Path(id): Path<String>,
+    extensions: axum::http::Extensions,
+) -> ApiResult<StatusCode> {
+    let owner = key_owner_filter(&extensions)?;
+    queries::revoke_api_key(&state.pool, &id, owner.as_deref()).await?;
+    tracing::info!("Revoked API key {}", id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/admin/api-key/rotate — replace the instance API key. The old
+/// key keeps working for the grace period.
+pub async fn rotate_legacy_api_key(
+    State(state): State<ApiKeysState>,
+    extensions: axum::http::Extensions,
+    input: Option<Json<RotateApiKeyInput>>,
+) -> ApiResult<Json<serde_json::Value>> {
+    require_admin(&extensions)?;
+    let input = input.map(|Json(input)| input).unwrap_or_default();
+    let grace_hours = input.grace_hours.unwrap_or(DEFAULT_ROTATION_GRACE_HOURS);
+    if grace_hours > MAX_ROTATION_GRACE_HOURS {
+        return Err(ApiError::Validation(format!(
+            "grace_hours must be at most {}",
+            MAX_ROTATION_GRACE_HOURS
+        )));
+    }
+
+    let key = state
+        .legacy_key
+        .rotate(Duration::from_secs(grace_hours * 60 * 60))
+        .map_err(ApiError::Validation)?;
+    let previous_key_expires_at = (chrono::Utc::now() + chrono::Duration::hours(grace_hours as i64))
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string();
+    tracing::info!("Rotated the instance API key; the old key works for {} more hours", grace_hours);
+
+    Ok(Json(json!({
+        "key": key,
+        "previous_key_expires_at": previous_key_expires_at,
+    })))
+}
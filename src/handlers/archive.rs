@@ -0,0 +1,209 @@
+use axum::{
+    body::{Body, Bytes},
+    extract::{Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use std::{io::Cursor, sync::Arc};
+use tokio_util::io::ReaderStream;
+
+use crate::{
+    archive::{
+        read_archive, restore_archive, write_archive, ArchivedShareLink, ArchivedSharedRecipe,
+        ArchivedShoppingListLink, ConflictStrategy, ImportReport, Manifest, RestoreTarget, VaultArchive,
+    },
+    auth::UserIdentity,
+    config::Config,
+    db::queries,
+    error::{ApiError, ApiResult},
+    photo_store::PhotoStore,
+};
+
+/// Largest archive accepted by POST /api/import
+pub const MAX_ARCHIVE_BYTES: usize = 500 * 1024 * 1024;
+
+/// Shared state for export/import handlers
+#[derive(Clone)]
+pub struct ArchiveState {
+    pub pool: SqlitePool,
+    pub config: Arc<Config>,
+    pub photo_store: Arc<dyn PhotoStore>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportArchiveQuery {
+    #[serde(default)]
+    pub conflict: ConflictStrategy,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// GET /api/export — download the caller's family vault as a zip archive.
+///
+/// The archive is built in a temporary file and streamed from there, so
+/// photos are never all held in memory.
+pub async fn export_archive(
+    State(state): State<ArchiveState>,
+    extensions: axum::http::Extensions,
+) -> ApiResult<Response> {
+    let identity = extensions.get::<UserIdentity>();
+    let family_id = identity.and_then(|i| i.family_id.clone());
+    let user_email = identity.and_then(|i| i.email.clone());
+
+    let mut recipes = Vec::new();
+    for recipe in queries::list_recipes(&state.pool, family_id.as_deref()).await? {
+        recipes.push(queries::get_recipe(&state.pool, &recipe.id, None).await?);
+    }
+    let mut share_links = Vec::new();
+    for link in queries::list_share_links(&state.pool, family_id.as_deref()).await? {
+        let recipes = if link.is_collection() {
+            queries::list_share_link_recipes(&state.pool, &link.token).await?
+        } else {
+            Vec::new()
+        };
+        share_links.push(ArchivedShareLink {
+            link,
+            recipes: recipes.iter().map(ArchivedSharedRecipe::from).collect(),
+        });
+    }
+    // God mode sees every family's shopping list links
+    let shopping_list_links = queries::list_shopping_list_links(&state.pool, family_id.as_deref()).await?;
+    let aisle_order = match family_id.as_deref() {
+        Some(family_id) => queries::get_aisle_config(&state.pool, family_id).await?,
+        None => None,
+    };
+
+    let filename = archive_filename(family_id.as_deref());
+    let archive = VaultArchive {
+        manifest: Manifest::new(user_email, family_id),
+        recipes,
+        share_links,
+        shopping_list_links: shopping_list_links.iter().map(ArchivedShoppingListLink::from).collect(),
+        aisle_order,
+        photos: Default::default(),
+    };
+
+    let temp_path = std::env::temp_dir().join(format!("recipe-vault-export-{}.zip", uuid::Uuid::new_v4()));
+    let photo_store = state.photo_store.clone();
+    let runtime = tokio::runtime::Handle::current();
+    let write_path = temp_path.clone();
+    let written = tokio::task::spawn_blocking(move || {
+        let file = std::fs::File::create(&write_path)?;
+        // Photos are fetched one at a time as they are written
+        write_archive(file, archive, |filename| {
+            runtime.block_on(photo_store.get(filename)).map_err(|e| e.to_string())
+        })
+    })
+    .await
+    .map_err(|e| ApiError::Internal(format!("Export task failed: {}", e)))?;
+
+    let manifest = match written {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+            return Err(ApiError::Internal(format!("Failed to build export: {}", e)));
+        }
+    };
+
+    let file = tokio::fs::File::open(&temp_path)
+        .await
+        .map_err(|e| ApiError::FileSystemError(format!("Failed to open export: {}", e)))?;
+    // The open handle keeps the data readable until the response is streamed
+    if let Err(e) = tokio::fs::remove_file(&temp_path).await {
+        tracing::warn!("Failed to remove export file {}: {}", temp_path.display(), e);
+    }
+
+    tracing::info!(
+        "Exported {} recipes and {} photos for {}",
+        manifest.counts.recipes,
+        manifest.counts.photos,
+        manifest.family_id.as_deref().unwrap_or("all families")
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/zip".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        Body::from_stream(ReaderStream::new(file)),
+    )
+        .into_response())
+}
+
+/// POST /api/import?conflict=skip|rename|overwrite&dry_run=true — restore an
+/// archive from GET /api/export into the caller's family. The request body is
+/// the zip file.
+pub async fn import_archive(
+    State(state): State<ArchiveState>,
+    Query(query): Query<ImportArchiveQuery>,
+    extensions: axum::http::Extensions,
+    body: Bytes,
+) -> ApiResult<Json<ImportReport>> {
+    let identity = extensions.get::<UserIdentity>();
+    if let Some(identity) = identity {
+        identity.require_editor()?;
+    }
+
+    if body.is_empty() {
+        return Err(ApiError::Validation("Request body must be a vault archive (zip)".to_string()));
+    }
+
+    let archive = tokio::task::spawn_blocking(move || read_archive(Cursor::new(body)))
+        .await
+        .map_err(|e| ApiError::Internal(format!("Import task failed: {}", e)))??;
+
+    let target = RestoreTarget {
+        user_email: identity.and_then(|i| i.email.as_deref()),
+        family_members: identity.and_then(|i| i.family_members.as_deref()),
+        family_id: identity.and_then(|i| i.family_id.as_deref()),
+    };
+
+    let report = restore_archive(
+        &state.pool,
+        state.photo_store.as_ref(),
+        archive,
+        target,
+        query.conflict,
+        query.dry_run,
+    )
+    .await?;
+
+    tracing::info!(
+        "Archive import{}: {} created, {} renamed, {} overwritten, {} skipped",
+        if report.dry_run { " (dry run)" } else { "" },
+        report.created,
+        report.renamed,
+        report.overwritten,
+        report.skipped
+    );
+
+    Ok(Json(report))
+}
+
+fn archive_filename(family_id: Option<&str>) -> String {
+    let scope: String = family_id
+        .unwrap_or("all")
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+        .collect();
+    format!(
+        "recipe-vault-{}-{}.zip",
+        scope,
+        chrono::Utc::now().format("%Y%m%d")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_filename_is_header_safe() {
+        let name = archive_filename(Some("fam\"ily; a"));
+        assert!(name.starts_with("recipe-vault-familya-"));
+        assert!(archive_filename(None).starts_with("recipe-vault-all-"));
+    }
+}
@@ -83,9 +83,14 @@ pub async fn chat(
     let user_email = identity
         .and_then(|i| i.email.clone())
         .ok_or_else(|| ChatError::Session("User not authenticated".to_string()))?;
+    let family_id = identity.and_then(|i| i.family_id.clone());
+    let quota = state.llm_quota().cloned();
+    if let Some(quota) = &quota {
+        quota.reserve(family_id.as_deref()).await?;
+    }
 
-    // Ensure agent is running with the authenticated user's context
-    state.get_or_create_agent(&user_email).await?;
+    // This user's own agent, so tools run with their family scope and authorship
+    let agent = state.get_or_create_agent(&user_email).await?;
 
     // Get or create conversation
     let conversation_id = request
@@ -168,7 +173,21 @@ pub async fn chat(
             .data(serde_json::json!({"text": ""}).to_string()));
 
         // Get agent and process message
-        match state.chat(&conversation).await {
+        let result = agent.chat(&conversation).await;
+        if let Some(quota) = &quota {
+            // One LLM call per assistant turn, the first of them reserved
+            // above; a failed chat still made that one
+            let calls = match &result {
+                Ok((.., new_messages)) => new_messages
+                    .iter()
+                    .filter(|message| matches!(message, Message::Assistant { .. }))
+                    .count(),
+                Err(_) => 1,
+            };
+            quota.record(family_id.as_deref(), calls.saturating_sub(1) as u32).await;
+        }
+
+        match result {
             Ok((response_text, tools_used, recipe_ids, timer_data, meal_plans, new_messages)) => {
                     // Send tool use events
                     for tool in &tools_used {
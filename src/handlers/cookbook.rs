@@ -0,0 +1,139 @@
+use axum::{
+    extract::{Query, State},
+    response::Html,
+};
+use base64::Engine;
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{
+    auth::UserIdentity,
+    config::Config,
+    cookbook::{render_cookbook, CookbookOptions, TocGrouping},
+    db::queries,
+    error::{ApiError, ApiResult},
+    handlers::share::content_type_from_extension,
+    models::RecipeWithDetails,
+    photo_store::PhotoStore,
+};
+
+const DEFAULT_TITLE: &str = "Family Cookbook";
+
+/// Shared state for the cookbook handler
+#[derive(Clone)]
+pub struct CookbookState {
+    pub pool: SqlitePool,
+    pub config: Arc<Config>,
+    pub photo_store: Arc<dyn PhotoStore>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CookbookQuery {
+    /// Comma-separated recipe IDs in book order; all family recipes when absent
+    #[serde(default)]
+    pub recipe_ids: Option<String>,
+    #[serde(default)]
+    pub toc: Option<String>,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub subtitle: Option<String>,
+    /// Set to false to leave photos out (much smaller document)
+    #[serde(default = "default_photos")]
+    pub photos: bool,
+}
+
+fn default_photos() -> bool {
+    true
+}
+
+/// GET /api/cookbook?recipe_ids=a,b&toc=tag — printable HTML cookbook
+pub async fn get_cookbook(
+    State(state): State<CookbookState>,
+    Query(query): Query<CookbookQuery>,
+    extensions: axum::http::Extensions,
+) -> ApiResult<Html<String>> {
+    let identity = extensions.get::<UserIdentity>();
+    let family_id = identity.and_then(|i| i.family_id.as_deref());
+
+    let toc = match query.toc.as_deref().map(|t| t.trim().to_lowercase()) {
+        None => TocGrouping::Alphabetical,
+        Some(t) if t == "alphabetical" => TocGrouping::Alphabetical,
+        Some(t) if t == "tag" || t == "tags" => TocGrouping::Tag,
+        Some(other) => {
+            return Err(ApiError::Validation(format!(
+                "Unknown table of contents grouping '{}'; use 'alphabetical' or 'tag'",
+                other
+            )))
+        }
+    };
+
+    let mut recipes: Vec<RecipeWithDetails> = Vec::new();
+    match query.recipe_ids.as_deref() {
+        Some(ids) => {
+            for id in parse_recipe_ids(ids)? {
+                recipes.push(queries::get_recipe(&state.pool, &id, family_id).await?);
+            }
+        }
+        None => {
+            for recipe in queries::list_recipes(&state.pool, family_id).await? {
+                recipes.push(queries::get_recipe(&state.pool, &recipe.id, family_id).await?);
+            }
+        }
+    }
+
+    let photos = if query.photos {
+        load_photos(state.photo_store.as_ref(), &recipes).await
+    } else {
+        HashMap::new()
+    };
+
+    let options = CookbookOptions {
+        title: query
+            .title
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .unwrap_or_else(|| DEFAULT_TITLE.to_string()),
+        subtitle: query.subtitle.map(|s| s.trim().to_string()).filter(|s| !s.is_empty()),
+        toc,
+    };
+    Ok(Html(render_cookbook(&recipes, &photos, &options)))
+}
+
+/// Split the requested IDs, keeping their order and dropping duplicates
+fn parse_recipe_ids(ids: &str) -> ApiResult<Vec<String>> {
+    let mut recipe_ids: Vec<String> = Vec::new();
+    for id in ids.split(',').map(str::trim).filter(|id| !id.is_empty()) {
+        if !recipe_ids.iter().any(|existing| existing == id) {
+            recipe_ids.push(id.to_string());
+        }
+    }
+    if recipe_ids.is_empty() {
+        return Err(ApiError::Validation("At least one recipe ID is required".to_string()));
+    }
+    Ok(recipe_ids)
+}
+
+/// Inline each recipe photo as a data URI so the document is self-contained.
+/// A missing file just leaves the recipe without a photo.
+async fn load_photos(store: &dyn PhotoStore, recipes: &[RecipeWithDetails]) -> HashMap<String, String> {
+    let mut photos = HashMap::new();
+    for recipe in recipes {
+        let Some(filename) = &recipe.recipe.photo_filename else {
+            continue;
+        };
+        match store.get(filename).await {
+            Ok(Some(bytes)) => {
+                let data = base64::engine::general_purpose::STANDARD.encode(bytes);
+                photos.insert(
+                    recipe.recipe.id.clone(),
+                    format!("data:{};base64,{}", content_type_from_extension(filename), data),
+                );
+            }
+            Ok(None) => tracing::warn!("Skipping cookbook photo {}: file not found", filename),
+            Err(e) => tracing::warn!("Skipping cookbook photo {}: {}", filename, e),
+        }
+    }
+    photos
+}
@@ -0,0 +1,435 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Redirect, Response},
+    Json,
+};
+use serde_json::json;
+use sqlx::SqlitePool;
+
+use crate::{
+    auth::UserIdentity,
+    db::queries,
+    error::{ApiError, ApiResult},
+    families::FamilyDirectory,
+    handlers::{admin::require_admin, share::html_escape},
+    models::{
+        family::{
+            generate_invitation_token, validate_email, AddFamilyMemberInput, CreateFamilyInput,
+            CreateInvitationInput, SetFamilyRoleInput,
+        },
+        FamilyInvitation, FamilyRole, FamilyWithMembers,
+    },
+};
+
+/// Shared state for family management and invitations
+#[derive(Clone)]
+pub struct FamiliesState {
+    pub pool: SqlitePool,
+    pub families: FamilyDirectory,
+}
+
+/// GET /api/admin/families — every family with its members
+pub async fn list_families(
+    State(state): State<FamiliesState>,
+    extensions: axum::http::Extensions,
+) -> ApiResult<Json<Vec<FamilyWithMembers>>> {
+    require_admin(&extensions)?;
+    Ok(Json(queries::list_families(&state.pool).await?))
+}
+
+/// POST /api/admin/families — create a family, optionally with members
+pub async fn create_family(
+    State(state): State<FamiliesState>,
+    extensions: axum::http::Extensions,
+    Json(mut input): Json<CreateFamilyInput>,
+) -> ApiResult<(StatusCode, Json<FamilyWithMembers>)> {
+    require_admin(&extensions)?;
+    input.validate()?;
+
+    let family = queries::create_family(
+        &state.pool,
+        &input.id,
+        &input.display_name(),
+        &input.members,
+        caller_email(&extensions),
+    )
+    .await?;
+    state.families.invalidate();
+    tracing::info!("Created family {} with {} members", family.family.id, family.members.len());
+    Ok((StatusCode::CREATED, Json(family)))
+}
+
+/// GET /api/admin/families/:id
+pub async fn get_family(
+    State(state): State<FamiliesState>,
+    Path(family_id): Path<String>,
+    extensions: axum::http::Extensions,
+) -> ApiResult<Json<FamilyWithMembers>> {
+    require_admin(&extensions)?;
+    Ok(Json(queries::get_family(&state.pool, &family_id).await?))
+}
+
+/// DELETE /api/admin/families/:id — members lose access; recipes are kept
+pub async fn delete_family(
+    State(state): State<FamiliesState>,
+    Path(family_id): Path<String>,
+    extensions: axum::http::Extensions,
+) -> ApiResult<StatusCode> {
+    require_admin(&extensions)?;
+    queries::delete_family(&state.pool, &family_id).await?;
+    state.families.invalidate();
+    tracing::info!("Deleted family {}", family_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/admin/families/:id/members — add a member by email
+pub async fn add_family_member(
+    State(state): State<FamiliesState>,
+    Path(family_id): Path<String>,
+    extensions: axum::http::Extensions,
+    Json(input): Json<AddFamilyMemberInput>,
+) -> ApiResult<Json<FamilyWithMembers>> {
+    require_admin(&extensions)?;
+    let email = validate_email(&input.email)?;
+
+    queries::add_family_member(&state.pool, &family_id, &email, input.role, caller_email(&extensions)).await?;
+    state.families.invalidate();
+    tracing::info!("Added {} to family {} as {:?}", email, family_id, input.role);
+    Ok(Json(queries::get_family(&state.pool, &family_id).await?))
+}
+
+/// PUT /api/admin/families/:id/members/:email — change a member's role
+pub async fn set_family_member_role(
+    State(state): State<FamiliesState>,
+    Path((family_id, email)): Path<(String, String)>,
+    extensions: axum::http::Extensions,
+    Json(input): Json<SetFamilyRoleInput>,
+) -> ApiResult<Json<FamilyWithMembers>> {
+    require_admin(&extensions)?;
+    let email = validate_email(&email)?;
+    set_role(&state, &family_id, &email, input.role).await
+}
+
+async fn set_role(
+    state: &FamiliesState,
+    family_id: &str,
+    email: &str,
+    role: FamilyRole,
+) -> ApiResult<Json<FamilyWithMembers>> {
+    queries::set_family_member_role(&state.pool, family_id, email, role).await?;
+    state.families.invalidate();
+    tracing::info!("Made {} {:?} of family {}", email, role, family_id);
+    Ok(Json(queries::get_family(&state.pool, family_id).await?))
+}
+
+/// DELETE /api/admin/families/:id/members/:email
+pub async fn remove_family_member(
+    State(state): State<FamiliesState>,
+    Path((family_id, email)): Path<(String, String)>,
+    extensions: axum::http::Extensions,
+) -> ApiResult<StatusCode> {
+    require_admin(&extensions)?;
+    let email = validate_email(&email)?;
+
+    queries::remove_family_member(&state.pool, &family_id, &email).await?;
+    state.families.invalidate();
+    tracing::info!("Removed {} from family {}", email, family_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/admin/families/:id/invitations — invite someone to any family
+pub async fn create_admin_invitation(
+    State(state): State<FamiliesState>,
+    Path(family_id): Path<String>,
+    extensions: axum::http::Extensions,
+    input: Option<Json<CreateInvitationInput>>,
+) -> ApiResult<(StatusCode, Json<serde_json::Value>)> {
+    require_admin(&extensions)?;
+    queries::get_family(&state.pool, &family_id).await?;
+    create_invitation(&state, &family_id, caller_email(&extensions), input).await
+}
+
+/// GET /api/family — the caller's own family
+pub async fn get_own_family(
+    State(state): State<FamiliesState>,
+    extensions: axum::http::Extensions,
+) -> ApiResult<Json<FamilyWithMembers>> {
+    let family_id = own_family_id(&extensions)?;
+    Ok(Json(queries::get_family(&state.pool, family_id).await?))
+}
+
+/// POST /api/family/invitations — invite someone to the caller's family (owners)
+pub async fn create_family_invitation(
+    State(state): State<FamiliesState>,
+    extensions: axum::http::Extensions,
+    input: Option<Json<CreateInvitationInput>>,
+) -> ApiResult<(StatusCode, Json<serde_json::Value>)> {
+    let family_id = owned_family_id(&extensions)?;
+    create_invitation(&state, family_id, caller_email(&extensions), input).await
+}
+
+/// PUT /api/family/members/:email — change a member's role (owners)
+pub async fn set_own_family_member_role(
+    State(state): State<FamiliesState>,
+    Path(email): Path<String>,
+    extensions: axum::http::Extensions,
+    Json(input): Json<SetFamilyRoleInput>,
+) -> ApiResult<Json<FamilyWithMembers>> {
+    let family_id = owned_family_id(&extensions)?;
+    let email = validate_email(&email)?;
+    if input.role != FamilyRole::Owner {
+        ensure_other_owner(&state, family_id, &email).await?;
+    }
+    set_role(&state, family_id, &email, input.role).await
+}
+
+/// DELETE /api/family/members/:email — remove someone from the family (owners)
+pub async fn remove_own_family_member(
+    State(state): State<FamiliesState>,
+    Path(email): Path<String>,
+    extensions: axum::http::Extensions,
+) -> ApiResult<StatusCode> {
+    let family_id = owned_family_id(&extensions)?;
+    let email = validate_email(&email)?;
+    ensure_other_owner(&state, family_id, &email).await?;
+
+    queries::remove_family_member(&state.pool, family_id, &email).await?;
+    state.families.invalidate();
+    tracing::info!("Removed {} from family {}", email, family_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Owners can't leave their family without an owner; the admin API can
+async fn ensure_other_owner(state: &FamiliesState, family_id: &str, email: &str) -> ApiResult<()> {
+    let family = queries::get_family(&state.pool, family_id).await?;
+    if family.roles.get(email) == Some(&FamilyRole::Owner) && family.owner_count() == 1 {
+        return Err(ApiError::Conflict(format!(
+            "{} is the family's only owner; make someone else an owner first",
+            email
+        )));
+    }
+    Ok(())
+}
+
+async fn create_invitation(
+    state: &FamiliesState,
+    family_id: &str,
+    created_by: Option<&str>,
+    input: Option<Json<CreateInvitationInput>>,
+) -> ApiResult<(StatusCode, Json<serde_json::Value>)> {
+    let mut input = input.map(|Json(input)| input).unwrap_or_default();
+    input.validate()?;
+
+    let invitation = queries::create_family_invitation(
+        &state.pool,
+        &generate_invitation_token(),
+        family_id,
+        input.email.as_deref(),
+        created_by,
+        &input.expires_at(),
+    )
+    .await?;
+    tracing::info!("Created invitation to family {}", family_id);
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "token": invitation.token,
+            "url": format!("/invite/{}", invitation.token),
+            "family_id": invitation.family_id,
+            "email": invitation.email,
+            "expires_at": invitation.expires_at,
+        })),
+    ))
+}
+
+fn caller_email(extensions: &axum::http::Extensions) -> Option<&str> {
+    extensions
+        .get::<UserIdentity>()
+        .and_then(|identity| identity.email.as_deref())
+}
+
+fn own_family_id(extensions: &axum::http::Extensions) -> ApiResult<&str> {
+    extensions
+        .get::<UserIdentity>()
+        .and_then(|identity| identity.family_id.as_deref())
+        .ok_or_else(|| {
+            ApiError::Validation("No family for this request; use the admin families endpoints".to_string())
+        })
+}
+
+/// The caller's family, if they are one of its owners
+fn owned_family_id(extensions: &axum::http::Extensions) -> ApiResult<&str> {
+    let family_id = own_family_id(extensions)?;
+    if let Some(identity) = extensions.get::<UserIdentity>() {
+        identity.require_owner()?;
+    }
+    Ok(family_id)
+}
+
+/// Why an invitation can't be accepted by this visitor, as a page to show them
+async fn check_invitation(
+    state: &FamiliesState,
+    token: &str,
+    identity: Option<&UserIdentity>,
+) -> Result<(FamilyInvitation, String), Response> {
+    let invitation = match queries::get_family_invitation(&state.pool, token).await {
+        Ok(Some(invitation)) if invitation.is_open() => invitation,
+        Ok(_) => {
+            return Err(invite_message(
+                StatusCode::NOT_FOUND,
+                "Invitation not valid",
+                "This invitation has expired or has already been used. Ask for a new one.",
+            ))
+        }
+        Err(e) => return Err(e.into_response()),
+    };
+
+    let Some(email) = identity.and_then(|identity| identity.email.clone()) else {
+        return Err(invite_message(
+            StatusCode::UNAUTHORIZED,
+            "Sign in to join",
+            "Sign in to Recipe Vault, then open this invitation again.",
+        ));
+    };
+    if invitation.email.as_ref().is_some_and(|invited| *invited != email) {
+        return Err(invite_message(
+            StatusCode::FORBIDDEN,
+            "Invitation for someone else",
+            "This invitation was sent to a different email address.",
+        ));
+    }
+    if let Some(family_id) = identity.and_then(|identity| identity.family_id.as_deref()) {
+        let message = if family_id == invitation.family_id {
+            "You're already a member of this family."
+        } else {
+            "You already belong to another family. Ask the administrator to move you."
+        };
+        return Err(invite_message(StatusCode::CONFLICT, "Already in a family", message));
+    }
+
+    Ok((invitation, email))
+}
+
+/// GET /invite/:token — ask the signed-in visitor to confirm joining
+pub async fn invitation_page(
+    State(state): State<FamiliesState>,
+    Path(token): Path<String>,
+    extensions: axum::http::Extensions,
+) -> Response {
+    let (invitation, email) = match check_invitation(&state, &token, extensions.get::<UserIdentity>()).await {
+        Ok(checked) => checked,
+        Err(page) => return page,
+    };
+    let family_name = match queries::get_family(&state.pool, &invitation.family_id).await {
+        Ok(family) => family.family.name,
+        Err(e) => return e.into_response(),
+    };
+
+    Html(invite_page(
+        "Join a family",
+        &format!(
+            "You've been invited to join {} on Recipe Vault. You'll share recipes with everyone in it.",
+            family_name
+        ),
+        Some((&token, &email)),
+    ))
+    .into_response()
+}
+
+/// Whether the browser says this request came from another site. A page
+/// elsewhere could otherwise post the join form for a signed-in visitor.
+/// Clients that send neither header aren't browsers, so can't be tricked.
+fn is_cross_site(headers: &HeaderMap) -> bool {
+    if let Some(site) = headers.get("sec-fetch-site") {
+        return !matches!(site.to_str(), Ok("same-origin" | "none"));
+    }
+    let Some(origin) = headers.get(header::ORIGIN) else {
+        return false;
+    };
+    let origin_host = origin
+        .to_str()
+        .ok()
+        .and_then(|origin| origin.split_once("://"))
+        .map(|(_, host)| host);
+    let host = headers.get(header::HOST).and_then(|host| host.to_str().ok());
+    origin_host.is_none() || origin_host != host
+}
+
+/// POST /invite/:token — join the family and go to the app
+pub async fn accept_invitation(
+    State(state): State<FamiliesState>,
+    Path(token): Path<String>,
+    headers: HeaderMap,
+    extensions: axum::http::Extensions,
+) -> Response {
+    if is_cross_site(&headers) {
+        return invite_message(
+            StatusCode::FORBIDDEN,
+            "Couldn't join",
+            "Open the invitation link yourself and confirm there to join.",
+        );
+    }
+    let (_, email) = match check_invitation(&state, &token, extensions.get::<UserIdentity>()).await {
+        Ok(checked) => checked,
+        Err(page) => return page,
+    };
+
+    match queries::accept_family_invitation(&state.pool, &token, &email).await {
+        Ok(family_id) => {
+            state.families.invalidate();
+            tracing::info!("{} joined family {} by invitation", email, family_id);
+            Redirect::to("/").into_response()
+        }
+        Err(ApiError::Conflict(message)) => {
+            invite_message(StatusCode::CONFLICT, "Couldn't join", &message)
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+fn invite_message(status: StatusCode, title: &str, message: &str) -> Response {
+    (status, Html(invite_page(title, message, None))).into_response()
+}
+
+/// An invitation page; `join` adds the confirm button for (token, email)
+fn invite_page(title: &str, message: &str, join: Option<(&str, &str)>) -> String {
+    let form = join
+        .map(|(token, email)| {
+            format!(
+                r#"<form method="post" action="/invite/{token}">
+<button type="submit">Join as {email}</button>
+</form>"#,
+                token = html_escape(token),
+                email = html_escape(email),
+            )
+        })
+        .unwrap_or_default();
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<meta name="robots" content="noindex">
+<title>{title} - Recipe Vault</title>
+<style>
+body{{font-family:-apple-system,BlinkMacSystemFont,'Segoe UI',Roboto,sans-serif;max-width:480px;margin:80px auto;padding:24px 16px;color:#333;text-align:center;background:#faf9f6}}
+h1{{font-size:1.5em;color:#2c1810;margin-bottom:12px}}
+p{{color:#666}}
+form{{margin-top:20px}}
+button{{background:#2c1810;color:#fff;border:none;padding:8px 16px;border-radius:6px;cursor:pointer;font-size:0.95em}}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<p>{message}</p>
+{form}
+</body>
+</html>"#,
+        title = html_escape(title),
+        message = html_escape(message),
+        form = form,
+    )
+}
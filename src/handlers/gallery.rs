@@ -0,0 +1,182 @@
+use axum::{
+    extract::{Multipart, Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::Response,
+    Json,
+};
+
+use crate::{
+    auth::UserIdentity,
+    db::queries,
+    error::ApiResult,
+    handlers::recipes::{
+        photo_response, read_photo_upload, store_new_photo, validate_step_position, PhotoQuery, PhotoUpload,
+        RecipeState,
+    },
+    models::{
+        photo::{normalize_caption, ReorderPhotosInput, UpdatePhotoInput},
+        RecipePhoto,
+    },
+    photos,
+};
+
+/// GET /api/recipes/:id/photos — a recipe's photos in gallery order
+pub async fn list_photos(
+    State(state): State<RecipeState>,
+    Path(id): Path<String>,
+    extensions: axum::http::Extensions,
+) -> ApiResult<Json<Vec<RecipePhoto>>> {
+    let identity = extensions.get::<UserIdentity>();
+    let family_id = identity.and_then(|i| i.family_id.as_deref());
+
+    let recipe = queries::get_recipe(&state.pool, &id, family_id).await?;
+    Ok(Json(recipe.photos))
+}
+
+/// POST /api/recipes/:id/photos — add a photo to the end of the gallery
+pub async fn add_photo(
+    State(state): State<RecipeState>,
+    Path(id): Path<String>,
+    extensions: axum::http::Extensions,
+    multipart: Multipart,
+) -> ApiResult<(StatusCode, Json<RecipePhoto>)> {
+    let identity = extensions.get::<UserIdentity>();
+    let family_id = identity.and_then(|i| i.family_id.as_deref());
+    if let Some(identity) = identity {
+        identity.require_editor()?;
+    }
+
+    // Verify recipe exists and is accessible by user (family tenancy check)
+    let recipe = queries::get_recipe(&state.pool, &id, family_id).await?;
+
+    let upload = read_photo_upload(multipart).await?;
+    let photo = store_new_photo(&state, &recipe, upload).await?;
+    Ok((StatusCode::CREATED, Json(photo)))
+}
+
+/// GET /api/recipes/:id/source-scans — scans of the original the recipe was
+/// transcribed from
+pub async fn list_source_scans(
+    State(state): State<RecipeState>,
+    Path(id): Path<String>,
+    extensions: axum::http::Extensions,
+) -> ApiResult<Json<Vec<RecipePhoto>>> {
+    let identity = extensions.get::<UserIdentity>();
+    let family_id = identity.and_then(|i| i.family_id.as_deref());
+
+    let recipe = queries::get_recipe(&state.pool, &id, family_id).await?;
+    Ok(Json(recipe.source_scans))
+}
+
+/// POST /api/recipes/:id/source-scans — keep a scan of the handwritten card or
+/// cookbook page. Scans are served, captioned and deleted through the photo
+/// routes but are never the cover.
+pub async fn add_source_scan(
+    State(state): State<RecipeState>,
+    Path(id): Path<String>,
+    extensions: axum::http::Extensions,
+    multipart: Multipart,
+) -> ApiResult<(StatusCode, Json<RecipePhoto>)> {
+    let identity = extensions.get::<UserIdentity>();
+    let family_id = identity.and_then(|i| i.family_id.as_deref());
+    if let Some(identity) = identity {
+        identity.require_editor()?;
+    }
+
+    let recipe = queries::get_recipe(&state.pool, &id, family_id).await?;
+
+    let upload = read_photo_upload(multipart).await?;
+    let scan = store_new_photo(&state, &recipe, PhotoUpload { source_scan: true, ..upload }).await?;
+    Ok((StatusCode::CREATED, Json(scan)))
+}
+
+/// GET /api/recipes/:id/photos/:photo_id?size=thumb|medium|full
+pub async fn get_photo(
+    State(state): State<RecipeState>,
+    Path((id, photo_id)): Path<(String, String)>,
+    Query(query): Query<PhotoQuery>,
+    headers: HeaderMap,
+    extensions: axum::http::Extensions,
+) -> ApiResult<Response> {
+    let identity = extensions.get::<UserIdentity>();
+    let family_id = identity.and_then(|i| i.family_id.as_deref());
+    let size = query.size()?;
+
+    queries::get_recipe(&state.pool, &id, family_id).await?;
+    let photo = queries::get_recipe_photo(&state.pool, &id, &photo_id).await?;
+
+    let (bytes, served_filename) = photos::read_photo(state.photo_store.as_ref(), &photo.filename, size).await?;
+    Ok(photo_response(bytes, &served_filename, &headers, "private, no-cache"))
+}
+
+/// PUT /api/recipes/:id/photos/:photo_id — change caption, step link or cover
+/// (only the caption of a source scan)
+pub async fn update_photo(
+    State(state): State<RecipeState>,
+    Path((id, photo_id)): Path<(String, String)>,
+    extensions: axum::http::Extensions,
+    Json(input): Json<UpdatePhotoInput>,
+) -> ApiResult<Json<RecipePhoto>> {
+    let identity = extensions.get::<UserIdentity>();
+    let family_id = identity.and_then(|i| i.family_id.as_deref());
+    if let Some(identity) = identity {
+        identity.require_editor()?;
+    }
+
+    let recipe = queries::get_recipe(&state.pool, &id, family_id).await?;
+
+    let caption = match &input.caption {
+        Some(caption) => Some(normalize_caption(caption.as_deref())?),
+        None => None,
+    };
+    if let Some(step_position) = input.step_position {
+        validate_step_position(&recipe, step_position)?;
+    }
+
+    let photo = queries::update_recipe_photo(
+        &state.pool,
+        &id,
+        &photo_id,
+        caption.as_ref().map(|c| c.as_deref()),
+        input.step_position,
+        input.cover,
+    )
+    .await?;
+    Ok(Json(photo))
+}
+
+/// PUT /api/recipes/:id/photos/order — reorder the gallery
+pub async fn reorder_photos(
+    State(state): State<RecipeState>,
+    Path(id): Path<String>,
+    extensions: axum::http::Extensions,
+    Json(input): Json<ReorderPhotosInput>,
+) -> ApiResult<Json<Vec<RecipePhoto>>> {
+    let identity = extensions.get::<UserIdentity>();
+    let family_id = identity.and_then(|i| i.family_id.as_deref());
+    if let Some(identity) = identity {
+        identity.require_editor()?;
+    }
+
+    queries::get_recipe(&state.pool, &id, family_id).await?;
+    Ok(Json(queries::reorder_recipe_photos(&state.pool, &id, &input.photo_ids).await?))
+}
+
+/// DELETE /api/recipes/:id/photos/:photo_id — remove a photo; if it was the
+/// cover, the first remaining photo becomes the cover
+pub async fn delete_photo(
+    State(state): State<RecipeState>,
+    Path((id, photo_id)): Path<(String, String)>,
+    extensions: axum::http::Extensions,
+) -> ApiResult<StatusCode> {
+    let identity = extensions.get::<UserIdentity>();
+    let family_id = identity.and_then(|i| i.family_id.as_deref());
+    if let Some(identity) = identity {
+        identity.require_editor()?;
+    }
+
+    queries::get_recipe(&state.pool, &id, family_id).await?;
+    let removed = queries::delete_recipe_photo(&state.pool, &id, &photo_id).await?;
+    photos::remove_photo(state.photo_store.as_ref(), &removed.filename).await;
+    Ok(StatusCode::NO_CONTENT)
+}
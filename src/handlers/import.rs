@@ -0,0 +1,824 @@
+use axum::{
+    body::Bytes,
+    extract::{Multipart, Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use futures::StreamExt;
+use reqwest::Url;
+use scraper::{Html, Node};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use crate::{
+    ai::{extract_recipe_from_text, LlmProvider, LlmProviderType, RecipeExtractionError},
+    archive::{restore::free_title, ConflictStrategy},
+    auth::UserIdentity,
+    config::{Config, LlmProviderKind},
+    db::queries::{self, NewRecipePhoto},
+    error::{ApiError, ApiResult},
+    formats::{extract_recipe, parse_cooklang, parse_recipe_file, FormatError, ParsedRecipe, RecipeFileFormat, StructuredDataSource},
+    handlers::{
+        recipes::spawn_difficulty_assessment,
+        share::{check_passphrase, link_expired, public_base_url, share_unlocked, views_exhausted, PassphraseCheck},
+    },
+    llm_quota::LlmQuota,
+    models::{share_link::SINGLE_USE_GRACE_MINUTES, CreateRecipeInput, Recipe, RecipeWithDetails},
+    photo_store::PhotoStore,
+    photos::{self, PhotoSize},
+    safe_fetch,
+};
+
+/// Pages larger than this are rejected rather than parsed
+const MAX_PAGE_BYTES: usize = 5 * 1024 * 1024;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+
+const USER_AGENT: &str = concat!("RecipeVault/", env!("CARGO_PKG_VERSION"), " (recipe importer)");
+
+/// Largest recipe manager file accepted by POST /api/import/file
+pub const MAX_IMPORT_FILE_BYTES: usize = 100 * 1024 * 1024;
+
+/// Shared state for import handlers
+#[derive(Clone)]
+pub struct ImportState {
+    pub pool: SqlitePool,
+    pub config: Arc<Config>,
+    pub http_client: reqwest::Client,
+    /// Fetches pages and photos; refuses private addresses (see `safe_fetch`)
+    pub fetch_client: reqwest::Client,
+    pub photo_store: Arc<dyn PhotoStore>,
+    pub llm_quota: LlmQuota,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportUrlInput {
+    pub url: String,
+    /// Save the recipe (default) or only return the extracted data
+    #[serde(default = "default_save")]
+    pub save: bool,
+}
+
+fn default_save() -> bool {
+    true
+}
+
+/// How the recipe was obtained from the page
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportSource {
+    JsonLd,
+    Microdata,
+    Llm,
+}
+
+impl From<StructuredDataSource> for ImportSource {
+    fn from(source: StructuredDataSource) -> Self {
+        match source {
+            StructuredDataSource::JsonLd => ImportSource::JsonLd,
+            StructuredDataSource::Microdata => ImportSource::Microdata,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum ImportedRecipe {
+    Saved(Box<RecipeWithDetails>),
+    Preview(CreateRecipeInput),
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportUrlResponse {
+    pub source: ImportSource,
+    pub saved: bool,
+    pub recipe: ImportedRecipe,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_url: Option<String>,
+}
+
+/// POST /api/import/url — import a recipe from a web page.
+///
+/// schema.org JSON-LD and microdata are parsed directly; the LLM is only used
+/// when the page has no structured recipe data.
+pub async fn import_url(
+    State(state): State<ImportState>,
+    extensions: axum::http::Extensions,
+    Json(input): Json<ImportUrlInput>,
+) -> ApiResult<(StatusCode, Json<ImportUrlResponse>)> {
+    let identity = extensions.get::<UserIdentity>();
+    let user_email = identity.and_then(|i| i.email.clone());
+    let family_id = identity.and_then(|i| i.family_id.as_deref());
+    if let Some(identity) = identity {
+        identity.require_editor()?;
+    }
+
+    let url = parse_import_url(&input.url)?;
+    let html = fetch_page(&state, &url).await?;
+
+    let (mut recipe, image_url, source) = match extract_recipe(&html) {
+        Some(extracted) => (extracted.recipe, extracted.image_url, extracted.source.into()),
+        None => {
+            tracing::info!("No structured recipe data at {}, falling back to LLM", url);
+            let llm = build_llm(&state.config, state.http_client.clone())?;
+            state.llm_quota.reserve(family_id).await?;
+            let extracted = extract_recipe_from_text(&llm, &visible_text(&html)).await;
+            let recipe = extracted
+                .map_err(|e| match e {
+                    RecipeExtractionError::NoRecipe => {
+                        ApiError::Validation(format!("No recipe found at {}", url))
+                    }
+                    other => ApiError::Upstream(format!("Recipe extraction failed: {}", other)),
+                })?;
+            (recipe, None, ImportSource::Llm)
+        }
+    };
+
+    recipe.source_url = Some(url.to_string());
+    let image_url = image_url.and_then(|image| url.join(&image).ok()).map(|u| u.to_string());
+    recipe.validate()?;
+
+    if !input.save {
+        return Ok((
+            StatusCode::OK,
+            Json(ImportUrlResponse {
+                source,
+                saved: false,
+                recipe: ImportedRecipe::Preview(recipe),
+                image_url,
+            }),
+        ));
+    }
+
+    let saved = queries::create_recipe(&state.pool, recipe, user_email, family_id).await?;
+    if saved.recipe.difficulty.is_none() {
+        spawn_difficulty_assessment(&state.pool, &state.config, &state.http_client, &state.llm_quota, &saved.recipe.id);
+    }
+    tracing::info!("Imported recipe {} from {} via {:?}", saved.recipe.id, url, source);
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ImportUrlResponse {
+            source,
+            saved: true,
+            recipe: ImportedRecipe::Saved(Box::new(saved)),
+            image_url,
+        }),
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportCooklangQuery {
+    /// Title for files without `title` metadata (usually the file name)
+    pub title: Option<String>,
+    #[serde(default = "default_save")]
+    pub save: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportCooklangResponse {
+    pub saved: bool,
+    pub recipe: ImportedRecipe,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// POST /api/import/cooklang — import a recipe written in Cooklang.
+/// Export goes the other way with `GET /api/recipes/:id.cook`.
+pub async fn import_cooklang(
+    State(state): State<ImportState>,
+    Query(query): Query<ImportCooklangQuery>,
+    extensions: axum::http::Extensions,
+    body: String,
+) -> ApiResult<(StatusCode, Json<ImportCooklangResponse>)> {
+    let identity = extensions.get::<UserIdentity>();
+    let user_email = identity.and_then(|i| i.email.clone());
+    let family_id = identity.and_then(|i| i.family_id.as_deref());
+    if let Some(identity) = identity {
+        identity.require_editor()?;
+    }
+
+    let parsed = parse_cooklang(&body).map_err(|e| ApiError::Validation(e.to_string()))?;
+    let mut recipe = parsed.recipe;
+    if recipe.title.is_empty() {
+        recipe.title = query
+            .title
+            .map(|t| t.trim().trim_end_matches(".cook").to_string())
+            .filter(|t| !t.is_empty())
+            .ok_or_else(|| {
+                ApiError::Validation("The recipe has no title; add 'title' metadata or pass ?title=".to_string())
+            })?;
+    }
+    recipe.validate()?;
+
+    if !query.save {
+        return Ok((
+            StatusCode::OK,
+            Json(ImportCooklangResponse {
+                saved: false,
+                recipe: ImportedRecipe::Preview(recipe),
+                warnings: parsed.warnings,
+            }),
+        ));
+    }
+
+    let saved = queries::create_recipe(&state.pool, recipe, user_email, family_id).await?;
+    if saved.recipe.difficulty.is_none() {
+        spawn_difficulty_assessment(&state.pool, &state.config, &state.http_client, &state.llm_quota, &saved.recipe.id);
+    }
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ImportCooklangResponse {
+            saved: true,
+            recipe: ImportedRecipe::Saved(Box::new(saved)),
+            warnings: parsed.warnings,
+        }),
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportFileQuery {
+    /// skip (default) reports title conflicts; rename imports as "Title (2)"
+    #[serde(default)]
+    pub conflict: ConflictStrategy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileImportStatus {
+    Imported,
+    Conflict,
+    Failed,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileImportResult {
+    pub title: String,
+    pub status: FileImportStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipe_id: Option<String>,
+    /// New title when renamed to avoid a conflict
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub imported_title: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportFileResponse {
+    pub format: RecipeFileFormat,
+    pub imported: usize,
+    pub conflicts: usize,
+    pub failed: usize,
+    pub recipes: Vec<FileImportResult>,
+}
+
+/// POST /api/import/file — import recipes from a Paprika export or a
+/// MealMaster file (multipart field `file`). Each recipe is saved on its own,
+/// and the response reports what happened to every one.
+pub async fn import_file(
+    State(state): State<ImportState>,
+    Query(query): Query<ImportFileQuery>,
+    extensions: axum::http::Extensions,
+    mut multipart: Multipart,
+) -> ApiResult<Json<ImportFileResponse>> {
+    let identity = extensions.get::<UserIdentity>();
+    let user_email = identity.and_then(|i| i.email.clone());
+    let family_id = identity.and_then(|i| i.family_id.as_deref());
+    if let Some(identity) = identity {
+        identity.require_editor()?;
+    }
+
+    if query.conflict == ConflictStrategy::Overwrite {
+        return Err(ApiError::Validation(
+            "conflict=overwrite is only supported for vault archives".to_string(),
+        ));
+    }
+
+    let mut file_data = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| ApiError::Validation(format!("Failed to read multipart field: {}", e)))?
+    {
+        if field.name() == Some("file") {
+            let data = field
+                .bytes()
+                .await
+                .map_err(|e| ApiError::Validation(format!("Failed to read file data: {}", e)))?;
+            file_data = Some(data);
+            break;
+        }
+    }
+    let file_data = file_data.ok_or_else(|| ApiError::Validation("No file provided".to_string()))?;
+
+    let (format, entries) = tokio::task::spawn_blocking(move || parse_recipe_file(&file_data))
+        .await
+        .map_err(|e| ApiError::Internal(format!("Import task failed: {}", e)))?
+        .map_err(|e| ApiError::Validation(e.to_string()))?;
+
+    let mut response = ImportFileResponse {
+        format,
+        imported: 0,
+        conflicts: 0,
+        failed: 0,
+        recipes: Vec::with_capacity(entries.len()),
+    };
+    // Titles taken by renamed recipes earlier in this file
+    let mut claimed = HashSet::new();
+
+    for (index, entry) in entries.into_iter().enumerate() {
+        let result = match entry {
+            Ok(parsed) => save_parsed_recipe(&state, parsed, user_email.clone(), family_id, query.conflict, &mut claimed).await?,
+            Err(e) => FileImportResult {
+                title: match &e {
+                    FormatError::Entry { entry, .. } => entry.trim_end_matches(".paprikarecipe").to_string(),
+                    _ => format!("Recipe {}", index + 1),
+                },
+                status: FileImportStatus::Failed,
+                recipe_id: None,
+                imported_title: None,
+                warnings: Vec::new(),
+                error: Some(match e {
+                    FormatError::Entry { message, .. } => message,
+                    other => other.to_string(),
+                }),
+            },
+        };
+        match result.status {
+            FileImportStatus::Imported => response.imported += 1,
+            FileImportStatus::Conflict => response.conflicts += 1,
+            FileImportStatus::Failed => response.failed += 1,
+        }
+        response.recipes.push(result);
+    }
+
+    tracing::info!(
+        "Imported {:?} file: {} imported, {} conflicts, {} failed",
+        response.format,
+        response.imported,
+        response.conflicts,
+        response.failed
+    );
+    Ok(Json(response))
+}
+
+/// Save one parsed recipe and its photo. Per-recipe problems are reported in
+/// the result; only database and filesystem failures are errors.
+async fn save_parsed_recipe(
+    state: &ImportState,
+    parsed: ParsedRecipe,
+    user_email: Option<String>,
+    family_id: Option<&str>,
+    conflict: ConflictStrategy,
+    claimed: &mut HashSet<String>,
+) -> ApiResult<FileImportResult> {
+    let ParsedRecipe {
+        mut recipe,
+        photo,
+        warnings,
+    } = parsed;
+    let mut result = FileImportResult {
+        title: recipe.title.clone(),
+        status: FileImportStatus::Failed,
+        recipe_id: None,
+        imported_title: None,
+        warnings,
+        error: None,
+    };
+
+    if let Err(e) = recipe.validate() {
+        result.error = Some(e);
+        return Ok(result);
+    }
+
+    if queries::find_recipe_by_title(&state.pool, &recipe.title).await?.is_some() {
+        if conflict == ConflictStrategy::Rename {
+            let new_title = free_title(&state.pool, &recipe.title, claimed).await?;
+            claimed.insert(new_title.to_lowercase());
+            recipe.title = new_title.clone();
+            result.imported_title = Some(new_title);
+        } else {
+            result.status = FileImportStatus::Conflict;
+            result.error = Some("A recipe with this title already exists".to_string());
+            return Ok(result);
+        }
+    }
+
+    let saved = match queries::create_recipe(&state.pool, recipe, user_email, family_id).await {
+        Ok(saved) => saved,
+        Err(ApiError::Conflict(_)) => {
+            result.status = FileImportStatus::Conflict;
+            result.error = Some("A recipe with this title already exists".to_string());
+            return Ok(result);
+        }
+        Err(ApiError::Validation(message)) => {
+            result.error = Some(message);
+            return Ok(result);
+        }
+        Err(e) => return Err(e),
+    };
+    let recipe_id = saved.recipe.id;
+
+    if let Some(photo) = photo {
+        match photos::clean_upload(photo).await {
+            Ok(photo) => {
+                let photo_id = Recipe::new_id();
+                let filename = format!("{}.{}", photo_id, photo.extension);
+                photos::write_photo(state.photo_store.as_ref(), &filename, &photo.bytes).await?;
+                let cover = NewRecipePhoto {
+                    id: &photo_id,
+                    filename: &filename,
+                    caption: None,
+                    step_position: None,
+                    cover: true,
+                    source_scan: false,
+                };
+                queries::add_recipe_photo(&state.pool, &recipe_id, cover).await?;
+            }
+            Err(e) => {
+                tracing::warn!("Skipping embedded photo of '{}': {}", saved.recipe.title, e);
+                result
+                    .warnings
+                    .push("Embedded photo is not a supported image type".to_string())
+            }
+        }
+    }
+
+    if saved.recipe.difficulty.is_none() {
+        spawn_difficulty_assessment(&state.pool, &state.config, &state.http_client, &state.llm_quota, &recipe_id);
+    }
+
+    result.status = FileImportStatus::Imported;
+    result.recipe_id = Some(recipe_id);
+    Ok(result)
+}
+
+fn parse_import_url(raw: &str) -> ApiResult<Url> {
+    let url = Url::parse(raw.trim()).map_err(|e| ApiError::Validation(format!("Invalid URL: {}", e)))?;
+    match url.scheme() {
+        "http" | "https" => Ok(url),
+        other => Err(ApiError::Validation(format!(
+            "Unsupported URL scheme '{}'. Only http and https are supported",
+            other
+        ))),
+    }
+}
+
+/// Download a page, enforcing the timeout and size limit
+async fn fetch_page(state: &ImportState, url: &Url) -> ApiResult<String> {
+    let body = fetch_limited(state, url, "text/html,application/xhtml+xml").await?;
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+/// Download from a public address, enforcing the timeout and size limit
+async fn fetch_limited(state: &ImportState, url: &Url, accept: &str) -> ApiResult<Vec<u8>> {
+    safe_fetch::check_url(url, &state.config.import_private_hosts)
+        .map_err(|e| ApiError::Validation(format!("Can't fetch {}: {}", url, e)))?;
+    let response = state
+        .fetch_client
+        .get(url.clone())
+        .header(reqwest::header::USER_AGENT, USER_AGENT)
+        .header(reqwest::header::ACCEPT, accept)
+        .timeout(FETCH_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| ApiError::Upstream(format!("Failed to fetch {}: {}", url, with_causes(&e))))?;
+
+    if !response.status().is_success() {
+        return Err(ApiError::Upstream(format!(
+            "Fetching {} returned HTTP {}",
+            url,
+            response.status()
+        )));
+    }
+    if response.content_length().is_some_and(|len| len as usize > MAX_PAGE_BYTES) {
+        return Err(ApiError::FileTooLarge(format!(
+            "Page exceeds {} MB limit",
+            MAX_PAGE_BYTES / (1024 * 1024)
+        )));
+    }
+
+    let mut body = Vec::new();
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| ApiError::Upstream(format!("Failed to read {}: {}", url, e)))?;
+        if body.len() + chunk.len() > MAX_PAGE_BYTES {
+            return Err(ApiError::FileTooLarge(format!(
+                "Page exceeds {} MB limit",
+                MAX_PAGE_BYTES / (1024 * 1024)
+            )));
+        }
+        body.extend_from_slice(&chunk);
+    }
+
+    Ok(body)
+}
+
+/// An error's message followed by those of its causes, which say why a
+/// connection was refused
+fn with_causes(error: &dyn std::error::Error) -> String {
+    let mut message = error.to_string();
+    let mut cause = error.source();
+    while let Some(e) = cause {
+        message = format!("{}: {}", message, e);
+        cause = e.source();
+    }
+    message
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ImportShareInput {
+    /// Base URL of the Recipe Vault that issued the link, when it isn't this one
+    #[serde(default)]
+    pub origin: Option<String>,
+    /// Which recipe of a shared collection to save
+    #[serde(default)]
+    pub recipe_id: Option<String>,
+    /// Passphrase of a protected link from this instance, unless the share
+    /// page's unlock cookie is sent
+    #[serde(default)]
+    pub passphrase: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportShareResponse {
+    pub recipe: RecipeWithDetails,
+    /// The shared title, when a recipe already had it and the copy was renamed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub renamed_from: Option<String>,
+}
+
+/// A photo to copy with a shared recipe
+struct SharedPhoto {
+    bytes: Vec<u8>,
+    extension: String,
+    caption: Option<String>,
+    step_position: Option<i32>,
+    cover: bool,
+}
+
+/// POST /api/import/share/:token — save a shared recipe into the caller's
+/// vault.
+///
+/// Links from this instance are copied directly, gallery photos included.
+/// With `origin`, the share page of another Recipe Vault is fetched and its
+/// JSON-LD imported with the cover photo. The copy records the share page as
+/// its source and who shared it.
+pub async fn import_share(
+    State(state): State<ImportState>,
+    Path(token): Path<String>,
+    headers: HeaderMap,
+    extensions: axum::http::Extensions,
+    body: Bytes,
+) -> ApiResult<(StatusCode, Json<ImportShareResponse>)> {
+    let identity = extensions.get::<UserIdentity>();
+    let user_email = identity.and_then(|i| i.email.clone());
+    let family_id = identity.and_then(|i| i.family_id.as_deref());
+    if let Some(identity) = identity {
+        identity.require_editor()?;
+    }
+
+    let input: ImportShareInput = if body.iter().all(u8::is_ascii_whitespace) {
+        ImportShareInput::default()
+    } else {
+        serde_json::from_slice(&body).map_err(|e| ApiError::Validation(format!("Invalid request body: {}", e)))?
+    };
+
+    let (mut recipe, photos) = match &input.origin {
+        Some(origin) => fetch_shared_recipe(&state, origin, &token, input.recipe_id.as_deref()).await?,
+        None => local_shared_recipe(&state, &token, &input, &headers).await?,
+    };
+    recipe.validate()?;
+
+    let mut renamed_from = None;
+    if queries::find_recipe_by_title(&state.pool, &recipe.title).await?.is_some() {
+        let new_title = free_title(&state.pool, &recipe.title, &HashSet::new()).await?;
+        renamed_from = Some(std::mem::replace(&mut recipe.title, new_title));
+    }
+
+    let saved = queries::create_recipe(&state.pool, recipe, user_email, family_id).await?;
+    let recipe_id = saved.recipe.id.clone();
+    for photo in photos {
+        let photo_id = Recipe::new_id();
+        let filename = format!("{}.{}", photo_id, photo.extension);
+        photos::write_photo(state.photo_store.as_ref(), &filename, &photo.bytes).await?;
+        let new_photo = NewRecipePhoto {
+            id: &photo_id,
+            filename: &filename,
+            caption: photo.caption.as_deref(),
+            step_position: photo.step_position,
+            cover: photo.cover,
+            source_scan: false,
+        };
+        queries::add_recipe_photo(&state.pool, &recipe_id, new_photo).await?;
+    }
+
+    if saved.recipe.difficulty.is_none() {
+        spawn_difficulty_assessment(&state.pool, &state.config, &state.http_client, &state.llm_quota, &recipe_id);
+    }
+    tracing::info!("Saved shared recipe {} from share link {}", recipe_id, token);
+
+    let recipe = queries::get_recipe(&state.pool, &recipe_id, None).await?;
+    Ok((StatusCode::CREATED, Json(ImportShareResponse { recipe, renamed_from })))
+}
+
+/// A recipe shared from this instance, with its gallery photos. Expired and
+/// revoked links can't be saved from. Saving counts as a view; once a link's
+/// views are used up it can only be saved from in the grace period after the
+/// last one, so the visitor who saw the page can still keep it. Protected
+/// links need their passphrase or unlock cookie.
+async fn local_shared_recipe(
+    state: &ImportState,
+    token: &str,
+    input: &ImportShareInput,
+    headers: &HeaderMap,
+) -> ApiResult<(CreateRecipeInput, Vec<SharedPhoto>)> {
+    let link = queries::get_share_link(&state.pool, token)
+        .await?
+        .filter(|link| !link_expired(link))
+        .ok_or_else(|| ApiError::NotFound(format!("Share link {}", token)))?;
+
+    if !share_unlocked(&link, headers) {
+        let passphrase = input
+            .passphrase
+            .as_deref()
+            .ok_or_else(|| ApiError::Forbidden("This share link needs its passphrase".to_string()))?;
+        match check_passphrase(&state.pool, &link, passphrase).await? {
+            PassphraseCheck::Accepted => {}
+            PassphraseCheck::Rejected => return Err(ApiError::Forbidden("Wrong passphrase".to_string())),
+            PassphraseCheck::TooManyAttempts => {
+                return Err(ApiError::TooManyRequests(
+                    "Too many wrong passphrases for this link; try again later".to_string(),
+                ))
+            }
+        }
+    }
+
+    let recipe_id = input.recipe_id.as_deref();
+    let (recipe_id, page_path) = match (&link.recipe_id, recipe_id) {
+        (Some(id), _) => (id.clone(), format!("/share/{}", token)),
+        (None, Some(id)) => {
+            let shared = queries::list_share_link_recipes(&state.pool, token).await?;
+            if !shared.iter().any(|r| r.recipe_id == id) {
+                return Err(ApiError::NotFound(format!("Recipe {} is not in this collection", id)));
+            }
+            (id.to_string(), format!("/share/{}/recipes/{}", token, id))
+        }
+        (None, None) => {
+            return Err(ApiError::Validation(
+                "This link shares several recipes; give the recipe_id to save".to_string(),
+            ))
+        }
+    };
+
+    let used_up = if link.views_used_up() {
+        views_exhausted(&link)
+    } else {
+        !queries::record_share_link_view(&state.pool, token, SINGLE_USE_GRACE_MINUTES).await?
+    };
+    if used_up {
+        return Err(ApiError::NotFound(format!("Share link {}", token)));
+    }
+
+    let original = queries::get_recipe(&state.pool, &recipe_id, None).await?;
+    let mut recipe = CreateRecipeInput::from(&original);
+    recipe.source_url = Some(format!("{}{}", public_base_url(headers).unwrap_or_default(), page_path));
+    recipe.shared_by = original.recipe.created_by.clone();
+
+    let mut copies = Vec::new();
+    for photo in &original.photos {
+        let (bytes, _) = photos::read_photo(state.photo_store.as_ref(), &photo.filename, PhotoSize::Full).await?;
+        copies.push(SharedPhoto {
+            bytes,
+            extension: photos::extension_from_filename(&photo.filename).unwrap_or_else(|| "jpg".to_string()),
+            caption: photo.caption.clone(),
+            step_position: photo.step_position,
+            cover: photo.is_cover,
+        });
+    }
+    Ok((recipe, copies))
+}
+
+/// A recipe shared from another Recipe Vault, read from the JSON-LD of its
+/// share page, with the cover photo when it can be downloaded from the same
+/// origin. Both are fetched like URL imports, so never from private addresses.
+async fn fetch_shared_recipe(
+    state: &ImportState,
+    origin: &str,
+    token: &str,
+    recipe_id: Option<&str>,
+) -> ApiResult<(CreateRecipeInput, Vec<SharedPhoto>)> {
+    // Both go into the URL path
+    let path_safe = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !path_safe(token) || !recipe_id.is_none_or(path_safe) {
+        return Err(ApiError::Validation("Invalid share token or recipe ID".to_string()));
+    }
+    let origin = parse_import_url(origin)?;
+    let path = match recipe_id {
+        Some(id) => format!("/share/{}/recipes/{}", token, id),
+        None => format!("/share/{}", token),
+    };
+    let url = origin
+        .join(&path)
+        .map_err(|e| ApiError::Validation(format!("Invalid origin: {}", e)))?;
+
+    let html = fetch_page(state, &url).await?;
+    let extracted = extract_recipe(&html)
+        .filter(|extracted| extracted.source == StructuredDataSource::JsonLd)
+        .ok_or_else(|| ApiError::Validation(format!("No shared recipe found at {}", url)))?;
+
+    let mut recipe = extracted.recipe;
+    recipe.source_url = Some(url.to_string());
+    recipe.shared_by = url.host_str().map(String::from);
+
+    // Only the share page's own photo; the page could point anywhere
+    let image_url = extracted
+        .image_url
+        .and_then(|image| url.join(&image).ok())
+        .filter(|image_url| image_url.origin() == url.origin());
+    let mut copies = Vec::new();
+    if let Some(image_url) = image_url {
+        let cleaned = match fetch_limited(state, &image_url, "image/*").await {
+            Ok(bytes) => photos::clean_upload(bytes).await,
+            Err(e) => Err(e),
+        };
+        match cleaned {
+            Ok(photo) => copies.push(SharedPhoto {
+                bytes: photo.bytes,
+                extension: photo.extension.to_string(),
+                caption: None,
+                step_position: None,
+                cover: true,
+            }),
+            Err(e) => tracing::warn!("Skipping photo of shared recipe at {}: {}", url, e),
+        }
+    }
+    Ok((recipe, copies))
+}
+
+/// Readable text of a page for the LLM: script, style and similar elements
+/// are dropped and each text node goes on its own line
+fn visible_text(html: &str) -> String {
+    let document = Html::parse_document(html);
+    let mut out = String::new();
+    for node in document.tree.root().descendants() {
+        let Node::Text(text) = node.value() else {
+            continue;
+        };
+        let hidden = node.ancestors().any(|a| {
+            a.value()
+                .as_element()
+                .is_some_and(|e| matches!(e.name(), "script" | "style" | "noscript" | "template" | "svg" | "head"))
+        });
+        if !hidden && !text.trim().is_empty() {
+            out.push_str(text.trim());
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// LLM for extraction: the chat provider and model, since this is a
+/// harder task than difficulty rating
+fn build_llm(config: &Config, http_client: reqwest::Client) -> ApiResult<LlmProvider> {
+    if config.mock_llm {
+        return Ok(LlmProvider::mock(config.mock_recipe_id.clone()));
+    }
+
+    let (provider_type, api_key) = match config.ai_provider {
+        LlmProviderKind::Anthropic => (LlmProviderType::Anthropic, config.anthropic_api_key.clone()),
+        LlmProviderKind::Gemini => (LlmProviderType::Gemini, config.gemini_api_key.clone()),
+    };
+    let api_key = api_key.ok_or_else(|| {
+        ApiError::Validation(
+            "Page has no structured recipe data and no AI provider is configured".to_string(),
+        )
+    })?;
+
+    Ok(LlmProvider::new(provider_type, api_key, config.ai_model.clone(), Some(http_client)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_import_url_rejects_other_schemes() {
+        assert!(parse_import_url("https://example.com/recipe").is_ok());
+        assert!(parse_import_url("file:///etc/passwd").is_err());
+        assert!(parse_import_url("not a url").is_err());
+    }
+
+    #[test]
+    fn test_visible_text_skips_scripts() {
+        let text = visible_text(
+            "<html><head><title>T</title><style>p{}</style></head><body><p>Boil  water</p><script>var x;</script></body></html>",
+        );
+        assert_eq!(text, "Boil  water\n");
+    }
+}
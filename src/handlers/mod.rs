@@ -1,4 +1,12 @@
+pub mod admin;
+pub mod api_keys;
+pub mod archive;
 pub mod chat;
+pub mod cookbook;
+pub mod families;
+pub mod gallery;
+pub mod import;
 pub mod recipes;
 pub mod share;
+pub mod shopping;
 pub mod ui;
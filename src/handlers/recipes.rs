@@ -1,9 +1,11 @@
 use axum::{
-    extract::{Multipart, Path, State},
-    http::{header, StatusCode},
-    response::IntoResponse,
+    body::Bytes,
+    extract::{FromRequest, Multipart, Path, Query, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
+use serde::Deserialize;
 use sqlx::SqlitePool;
 use std::sync::Arc;
 
@@ -11,11 +13,16 @@ use crate::{
     ai::{assess_recipe_difficulty, LlmProvider, LlmProviderType},
     auth::UserIdentity,
     config::{Config, LlmProviderKind},
-    db::queries,
-    error::ApiResult,
+    db::queries::{self, NewRecipePhoto},
+    error::{ApiError, ApiResult},
+    formats,
+    llm_quota::LlmQuota,
+    photo_store::PhotoStore,
+    photos::{self, PhotoSize},
     models::{
+        photo::{self, MAX_PHOTOS_PER_RECIPE},
         recipe::{CreateRecipeInput, UpdateRecipeInput},
-        Recipe, RecipeWithDetails,
+        Recipe, RecipePhoto, RecipeWithDetails,
     },
 };
 
@@ -25,6 +32,21 @@ pub struct RecipeState {
     pub pool: SqlitePool,
     pub config: Arc<Config>,
     pub http_client: reqwest::Client,
+    pub photo_store: Arc<dyn PhotoStore>,
+    pub llm_quota: LlmQuota,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PhotoQuery {
+    /// thumb, medium or full (default)
+    #[serde(default)]
+    pub size: Option<String>,
+}
+
+impl PhotoQuery {
+    pub fn size(&self) -> ApiResult<PhotoSize> {
+        self.size.as_deref().map(PhotoSize::parse).unwrap_or(Ok(PhotoSize::Full))
+    }
 }
 
 /// Create a new recipe
@@ -35,33 +57,16 @@ pub async fn create_recipe(
 ) -> ApiResult<(StatusCode, Json<RecipeWithDetails>)> {
     let identity = extensions.get::<UserIdentity>();
     let user_email = identity.and_then(|i| i.email.clone());
+    let family_id = identity.and_then(|i| i.family_id.as_deref());
+    if let Some(identity) = identity {
+        identity.require_editor()?;
+    }
 
-    let recipe = queries::create_recipe(&state.pool, input, user_email).await?;
+    let recipe = queries::create_recipe(&state.pool, input, user_email, family_id).await?;
 
     // Check if difficulty was not specified - if so, auto-assign using AI
     if recipe.recipe.difficulty.is_none() {
-        let recipe_id = recipe.recipe.id.clone();
-        let pool = state.pool.clone();
-        let config = state.config.clone();
-        let http_client = state.http_client.clone();
-
-        tracing::info!("Recipe {} created without difficulty, spawning auto-assessment task", recipe_id);
-
-        // Spawn async task to assess and update difficulty (non-blocking)
-        tokio::spawn(async move {
-            // Small delay to allow the CREATE transaction to fully commit
-            // and avoid database lock contention (especially in tests with SQLite)
-            tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
-
-            match auto_assign_difficulty(&pool, &config, &recipe_id, http_client).await {
-                Ok(difficulty) => {
-                    tracing::info!("Auto-assigned difficulty {} to recipe {}", difficulty, recipe_id);
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to auto-assign difficulty to recipe {}: {}", recipe_id, e);
-                }
-            }
-        });
+        spawn_difficulty_assessment(&state.pool, &state.config, &state.http_client, &state.llm_quota, &recipe.recipe.id);
     }
 
     Ok((StatusCode::CREATED, Json(recipe)))
@@ -73,113 +78,271 @@ pub async fn list_recipes(
     extensions: axum::http::Extensions,
 ) -> ApiResult<Json<Vec<Recipe>>> {
     let identity = extensions.get::<UserIdentity>();
-    let family_members = identity.and_then(|i| i.family_members.as_ref());
+    let family_id = identity.and_then(|i| i.family_id.as_deref());
 
-    let recipes = queries::list_recipes(&state.pool, family_members.map(|v| v.as_slice())).await?;
+    let recipes = queries::list_recipes(&state.pool, family_id).await?;
     Ok(Json(recipes))
 }
 
-/// Get a single recipe by ID (filtered by family membership)
+/// Get a single recipe by ID (filtered by family membership).
+/// `GET /api/recipes/:id.md` returns the recipe as Markdown and
+/// `GET /api/recipes/:id.cook` as Cooklang.
 pub async fn get_recipe(
     State(state): State<RecipeState>,
     Path(id): Path<String>,
     extensions: axum::http::Extensions,
-) -> ApiResult<Json<RecipeWithDetails>> {
+) -> ApiResult<Response> {
     let identity = extensions.get::<UserIdentity>();
-    let family_members = identity.and_then(|i| i.family_members.as_ref());
+    let family_id = identity.and_then(|i| i.family_id.as_deref());
 
-    let recipe = queries::get_recipe(&state.pool, &id, family_members.map(|v| v.as_slice())).await?;
-    Ok(Json(recipe))
+    let (id, format) = TextFormat::from_path(&id);
+    let recipe = queries::get_recipe(&state.pool, id, family_id).await?;
+
+    match format {
+        Some(format) => Ok(format.response(&recipe)),
+        None => Ok(Json(recipe).into_response()),
+    }
 }
 
-/// Update a recipe (filtered by family membership)
+/// Update a recipe (filtered by family membership).
+///
+/// A JSON body is a partial update. A Markdown body (`PUT /api/recipes/:id.md`
+/// or `Content-Type: text/markdown`) or Cooklang body (`PUT
+/// /api/recipes/:id.cook`) replaces the whole recipe, so fields missing from
+/// the file are cleared.
 pub async fn update_recipe(
     State(state): State<RecipeState>,
     Path(id): Path<String>,
-    extensions: axum::http::Extensions,
-    Json(input): Json<UpdateRecipeInput>,
-) -> ApiResult<Json<RecipeWithDetails>> {
-    let identity = extensions.get::<UserIdentity>();
-    let user_email = identity.and_then(|i| i.email.clone());
-    let family_members = identity.and_then(|i| i.family_members.as_ref());
-
-    let recipe = queries::update_recipe(
-        &state.pool,
-        &id,
-        input,
-        user_email,
-        family_members.map(|v| v.as_slice()),
-    )
-    .await?;
-    Ok(Json(recipe))
+    request: Request,
+) -> ApiResult<Response> {
+    let identity = request.extensions().get::<UserIdentity>().cloned();
+    let user_email = identity.as_ref().and_then(|i| i.email.clone());
+    let family_id = identity.as_ref().and_then(|i| i.family_id.as_deref());
+    if let Some(identity) = &identity {
+        identity.require_editor()?;
+    }
+
+    let is_markdown = request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/markdown"));
+    let (id, path_format) = TextFormat::from_path(&id);
+    let Some(format) = path_format.or(is_markdown.then_some(TextFormat::Markdown)) else {
+        let input = match Json::<UpdateRecipeInput>::from_request(request, &state).await {
+            Ok(Json(input)) => input,
+            Err(rejection) => return Ok(rejection.into_response()),
+        };
+        let recipe = queries::update_recipe(
+            &state.pool,
+            id,
+            input,
+            user_email,
+            family_id,
+        )
+        .await?;
+        return Ok(Json(recipe).into_response());
+    };
+
+    let body = Bytes::from_request(request, &state)
+        .await
+        .map_err(|e| ApiError::Validation(format!("Failed to read request body: {}", e)))?;
+    let text = std::str::from_utf8(&body)
+        .map_err(|_| ApiError::Validation("The recipe must be UTF-8 text".to_string()))?;
+
+    // Only recipes the caller can see may be replaced
+    let existing = queries::get_recipe(&state.pool, id, family_id).await?;
+
+    let input = match format {
+        TextFormat::Markdown => {
+            let parsed = formats::parse_markdown(text).map_err(|e| ApiError::Validation(e.to_string()))?;
+            if let Some(file_id) = &parsed.id
+                && file_id != id
+            {
+                return Err(ApiError::Validation(format!(
+                    "The front matter id '{}' does not match recipe '{}'",
+                    file_id, id
+                )));
+            }
+            parsed.recipe
+        }
+        TextFormat::Cooklang => {
+            let mut recipe = formats::parse_cooklang(text)
+                .map_err(|e| ApiError::Validation(e.to_string()))?
+                .recipe;
+            // Cooklang files are often named after the recipe instead
+            if recipe.title.is_empty() {
+                recipe.title = existing.recipe.title.clone();
+            }
+            recipe
+        }
+    };
+    let recipe = queries::replace_recipe(&state.pool, id, input, user_email).await?;
+
+    if recipe.recipe.difficulty.is_none() {
+        spawn_difficulty_assessment(&state.pool, &state.config, &state.http_client, &state.llm_quota, id);
+    }
+
+    match path_format {
+        Some(format) => Ok(format.response(&recipe)),
+        None => Ok(Json(recipe).into_response()),
+    }
+}
+
+/// Plain-text representations of a recipe, selected by a path suffix
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TextFormat {
+    Markdown,
+    Cooklang,
 }
 
-/// Delete a recipe (filtered by family membership)
+impl TextFormat {
+    /// "abc.md" -> ("abc", Markdown); "abc" -> ("abc", None)
+    fn from_path(id: &str) -> (&str, Option<Self>) {
+        if let Some(id) = id.strip_suffix(".md") {
+            (id, Some(TextFormat::Markdown))
+        } else if let Some(id) = id.strip_suffix(".cook") {
+            (id, Some(TextFormat::Cooklang))
+        } else {
+            (id, None)
+        }
+    }
+
+    fn response(self, recipe: &RecipeWithDetails) -> Response {
+        let (content_type, body) = match self {
+            TextFormat::Markdown => ("text/markdown; charset=utf-8", formats::to_markdown(recipe)),
+            TextFormat::Cooklang => ("text/plain; charset=utf-8", formats::to_cooklang(recipe)),
+        };
+        ([(header::CONTENT_TYPE, content_type)], body).into_response()
+    }
+}
+
+/// Delete a recipe (filtered by family membership). Editors can only delete
+/// recipes they created; viewers none.
 pub async fn delete_recipe(
     State(state): State<RecipeState>,
     Path(id): Path<String>,
     extensions: axum::http::Extensions,
 ) -> ApiResult<StatusCode> {
     let identity = extensions.get::<UserIdentity>();
-    let family_members = identity.and_then(|i| i.family_members.as_ref());
-
-    // Before deleting, check if recipe has a photo and delete it
-    if let Ok(recipe) = queries::get_recipe(&state.pool, &id, family_members.map(|v| v.as_slice())).await
-        && let Some(photo_filename) = &recipe.recipe.photo_filename {
-            let photo_path = format!("{}/{}", state.config.photos_dir, photo_filename);
-            if let Err(e) = tokio::fs::remove_file(&photo_path).await {
-                tracing::warn!("Failed to delete photo file {}: {}", photo_path, e);
-                // Continue with recipe deletion even if photo deletion fails
-            } else {
-                tracing::info!("Deleted photo file: {}", photo_path);
-            }
-        }
+    let family_id = identity.and_then(|i| i.family_id.as_deref());
 
-    queries::delete_recipe(&state.pool, &id, family_members.map(|v| v.as_slice())).await?;
+    let recipe = queries::get_recipe(&state.pool, &id, family_id).await?;
+    if let Some(identity) = identity {
+        identity.require_can_delete("recipes", recipe.recipe.created_by.as_deref())?;
+    }
+    queries::delete_recipe(&state.pool, &id, family_id).await?;
+
+    // Photo rows went with the recipe; remove the files only once that has
+    // succeeded. Files that can't be removed are left for the photo check.
+    for photo in recipe.photos.iter().chain(&recipe.source_scans) {
+        photos::remove_photo(state.photo_store.as_ref(), &photo.filename).await;
+    }
     Ok(StatusCode::NO_CONTENT)
 }
 
-/// Upload a photo for a recipe
+/// Upload or replace a recipe's cover photo
 pub async fn upload_photo(
     State(state): State<RecipeState>,
     Path(id): Path<String>,
     extensions: axum::http::Extensions,
-    mut multipart: Multipart,
+    multipart: Multipart,
 ) -> ApiResult<Json<serde_json::Value>> {
     let identity = extensions.get::<UserIdentity>();
-    let family_members = identity.and_then(|i| i.family_members.as_ref());
+    let family_id = identity.and_then(|i| i.family_id.as_deref());
+    if let Some(identity) = identity {
+        identity.require_editor()?;
+    }
 
     // Verify recipe exists and is accessible by user (family tenancy check)
-    let recipe = queries::get_recipe(&state.pool, &id, family_members.map(|v| v.as_slice())).await?;
+    let recipe = queries::get_recipe(&state.pool, &id, family_id).await?;
+
+    let upload = read_photo_upload(multipart).await?;
+
+    let photo_filename = match recipe.photos.iter().find(|p| p.is_cover) {
+        // Replace the cover in place, keeping its gallery position and caption
+        Some(cover) => {
+            let photo_filename = format!("{}.{}", cover.id, upload.extension);
+
+            // Delete old file (and its derivatives) if format differs
+            if photo_filename != cover.filename {
+                photos::remove_photo(state.photo_store.as_ref(), &cover.filename).await;
+            }
+
+            save_photo_file(state.photo_store.as_ref(), &photo_filename, &upload.bytes).await?;
+            queries::replace_recipe_photo_file(&state.pool, &id, &cover.id, &photo_filename).await?;
+            photo_filename
+        }
+        None => {
+            let upload = PhotoUpload { cover: true, ..upload };
+            store_new_photo(&state, &recipe, upload).await?.filename
+        }
+    };
+
+    Ok(Json(serde_json::json!({
+        "photo_filename": photo_filename
+    })))
+}
 
-    // Extract file from multipart form data
+/// A validated photo upload with its optional form fields
+pub(crate) struct PhotoUpload {
+    pub bytes: Vec<u8>,
+    pub extension: String,
+    pub caption: Option<String>,
+    pub step_position: Option<i32>,
+    pub cover: bool,
+    pub source_scan: bool,
+}
+
+/// Read a multipart photo upload: the `photo` file plus optional `caption`,
+/// `step_position` and `cover` fields
+pub(crate) async fn read_photo_upload(mut multipart: Multipart) -> ApiResult<PhotoUpload> {
     let mut file_data: Option<Vec<u8>> = None;
     let mut filename: Option<String> = None;
     let mut content_type: Option<String> = None;
+    let mut caption = None;
+    let mut step_position = None;
+    let mut cover = false;
 
     while let Some(field) = multipart.next_field().await
-        .map_err(|e| crate::error::ApiError::Validation(format!("Failed to read multipart field: {}", e)))?
+        .map_err(|e| ApiError::Validation(format!("Failed to read multipart field: {}", e)))?
     {
-        if field.name() == Some("photo") {
-            filename = field.file_name().map(|s| s.to_string());
-            content_type = field.content_type().map(|s| s.to_string());
-
-            let data = field.bytes().await
-                .map_err(|e| crate::error::ApiError::Validation(format!("Failed to read file data: {}", e)))?;
-
-            file_data = Some(data.to_vec());
-            break;
+        match field.name() {
+            Some("photo") => {
+                filename = field.file_name().map(|s| s.to_string());
+                content_type = field.content_type().map(|s| s.to_string());
+
+                let data = field.bytes().await
+                    .map_err(|e| ApiError::Validation(format!("Failed to read file data: {}", e)))?;
+                file_data = Some(data.to_vec());
+            }
+            Some(name @ ("caption" | "step_position" | "cover")) => {
+                let name = name.to_string();
+                let value = field.text().await
+                    .map_err(|e| ApiError::Validation(format!("Failed to read field {}: {}", name, e)))?;
+                match name.as_str() {
+                    "caption" => caption = photo::normalize_caption(Some(&value))?,
+                    "step_position" if value.trim().is_empty() => {}
+                    "step_position" => {
+                        step_position = Some(value.trim().parse().map_err(|_| {
+                            ApiError::Validation(format!("step_position must be a number, got '{}'", value))
+                        })?)
+                    }
+                    _ => cover = matches!(value.trim(), "true" | "1" | "on"),
+                }
+            }
+            _ => {}
         }
     }
 
     let file_data = file_data.ok_or_else(||
-        crate::error::ApiError::Validation("No photo file provided".to_string())
+        ApiError::Validation("No photo file provided".to_string())
     )?;
 
     // Validate file size (5MB = 5,242,880 bytes)
     const MAX_FILE_SIZE: usize = 5 * 1024 * 1024;
     if file_data.len() > MAX_FILE_SIZE {
-        return Err(crate::error::ApiError::FileTooLarge(
+        return Err(ApiError::FileTooLarge(
             format!("File size {} bytes exceeds maximum of {} bytes (5MB)", file_data.len(), MAX_FILE_SIZE)
         ));
     }
@@ -190,124 +353,159 @@ pub async fn upload_photo(
     // Validate file extension
     validate_file_extension(&extension)?;
 
-    // Check for existing photo and delete old file if format differs
-    if let Some(old_photo_filename) = &recipe.recipe.photo_filename {
-        let old_extension = std::path::Path::new(old_photo_filename)
-            .extension()
-            .and_then(|s| s.to_str())
-            .unwrap_or("");
-
-        if old_extension.to_lowercase() != extension.to_lowercase() {
-            let old_photo_path = format!("{}/{}", state.config.photos_dir, old_photo_filename);
-            if let Err(e) = tokio::fs::remove_file(&old_photo_path).await {
-                tracing::warn!("Failed to delete old photo file {}: {}", old_photo_path, e);
-            } else {
-                tracing::info!("Deleted old photo file with different format: {}", old_photo_path);
-            }
-        }
-    }
-
-    // Save file to filesystem with atomic write
-    let photo_filename = format!("{}.{}", id, extension);
-    let photo_path = format!("{}/{}", state.config.photos_dir, photo_filename);
-
-    // Write to temporary file first, then rename (atomic operation)
-    let temp_path = format!("{}.tmp", photo_path);
-    tokio::fs::write(&temp_path, &file_data).await
-        .map_err(|e| crate::error::ApiError::FileSystemError(format!("Failed to write photo file: {}", e)))?;
+    // The declared type is only a first check: the bytes decide the format,
+    // and the image is re-encoded to drop EXIF/GPS metadata
+    let photo = photos::clean_upload(file_data).await?;
+
+    Ok(PhotoUpload {
+        bytes: photo.bytes,
+        extension: photo.extension.to_string(),
+        caption,
+        step_position,
+        cover,
+        source_scan: false,
+    })
+}
 
-    tokio::fs::rename(&temp_path, &photo_path).await
-        .map_err(|e| {
-            // Clean up temp file if rename fails
-            let _ = std::fs::remove_file(&temp_path);
-            crate::error::ApiError::FileSystemError(format!("Failed to save photo file: {}", e))
-        })?;
+/// Store a photo and pre-generate its thumbnail and medium sizes, replacing
+/// any derivatives of a previous upload
+pub(crate) async fn save_photo_file(store: &dyn PhotoStore, photo_filename: &str, bytes: &[u8]) -> ApiResult<()> {
+    photos::write_photo(store, photo_filename, bytes).await?;
+    tracing::info!("Saved photo file: {} in {}", photo_filename, store.location());
+    photos::write_derivatives(store, photo_filename, bytes).await;
+    Ok(())
+}
 
-    tracing::info!("Saved photo file: {}", photo_path);
+/// Add an uploaded photo to the end of a recipe's gallery, or of its source
+/// scans
+pub(crate) async fn store_new_photo(
+    state: &RecipeState,
+    recipe: &RecipeWithDetails,
+    upload: PhotoUpload,
+) -> ApiResult<RecipePhoto> {
+    validate_step_position(recipe, upload.step_position)?;
+    if upload.source_scan && (upload.cover || upload.step_position.is_some()) {
+        return Err(ApiError::Validation(
+            "A source scan can't be the cover or be linked to a step".to_string(),
+        ));
+    }
+    if recipe.photos.len() + recipe.source_scans.len() >= MAX_PHOTOS_PER_RECIPE {
+        return Err(ApiError::Validation(format!(
+            "A recipe can have at most {} photos",
+            MAX_PHOTOS_PER_RECIPE
+        )));
+    }
 
-    // Update recipe's photo_filename in database
-    sqlx::query("UPDATE recipes SET photo_filename = ? WHERE id = ?")
-        .bind(&photo_filename)
-        .bind(&id)
-        .execute(&state.pool)
-        .await?;
+    let photo_id = Recipe::new_id();
+    let photo_filename = format!("{}.{}", photo_id, upload.extension);
+    save_photo_file(state.photo_store.as_ref(), &photo_filename, &upload.bytes).await?;
+
+    let new_photo = NewRecipePhoto {
+        id: &photo_id,
+        filename: &photo_filename,
+        caption: upload.caption.as_deref(),
+        step_position: upload.step_position,
+        cover: upload.cover,
+        source_scan: upload.source_scan,
+    };
+    match queries::add_recipe_photo(&state.pool, &recipe.recipe.id, new_photo).await {
+        Ok(photo) => Ok(photo),
+        Err(e) => {
+            photos::remove_photo(state.photo_store.as_ref(), &photo_filename).await;
+            Err(e)
+        }
+    }
+}
 
-    Ok(Json(serde_json::json!({
-        "photo_filename": photo_filename
-    })))
+/// A photo may only be linked to one of the recipe's steps
+pub(crate) fn validate_step_position(recipe: &RecipeWithDetails, step_position: Option<i32>) -> ApiResult<()> {
+    match step_position {
+        Some(position) if !recipe.steps.iter().any(|step| step.position == position) => {
+            Err(ApiError::Validation(format!(
+                "step_position {} does not match any step of this recipe",
+                position
+            )))
+        }
+        _ => Ok(()),
+    }
 }
 
-/// Retrieve a photo for a recipe
+/// Retrieve a photo for a recipe: GET /api/recipes/:id/photo?size=thumb|medium|full
 pub async fn get_photo(
     State(state): State<RecipeState>,
     Path(id): Path<String>,
+    Query(query): Query<PhotoQuery>,
+    headers: HeaderMap,
     extensions: axum::http::Extensions,
-) -> ApiResult<impl IntoResponse> {
+) -> ApiResult<Response> {
     let identity = extensions.get::<UserIdentity>();
-    let family_members = identity.and_then(|i| i.family_members.as_ref());
+    let family_id = identity.and_then(|i| i.family_id.as_deref());
+    let size = query.size()?;
 
     // Query database for recipe and verify family tenancy
-    let recipe = queries::get_recipe(&state.pool, &id, family_members.map(|v| v.as_slice())).await?;
+    let recipe = queries::get_recipe(&state.pool, &id, family_id).await?;
 
     // Return 404 if recipe has no photo
     let photo_filename = recipe.recipe.photo_filename
         .ok_or_else(|| crate::error::ApiError::NotFound("Recipe has no photo".to_string()))?;
 
-    // Read photo file
-    let photo_path = format!("{}/{}", state.config.photos_dir, photo_filename);
-    let photo_bytes = tokio::fs::read(&photo_path).await
-        .map_err(|e| {
-            tracing::error!("Failed to read photo file {}: {}", photo_path, e);
-            crate::error::ApiError::NotFound("Photo file not found".to_string())
-        })?;
+    let (photo_bytes, served_filename) = photos::read_photo(state.photo_store.as_ref(), &photo_filename, size).await?;
 
-    // Detect content-type from file extension
-    let content_type = content_type_from_extension(&photo_filename);
+    // The URL stays the same when a photo is replaced, so browsers must revalidate
+    Ok(photo_response(photo_bytes, &served_filename, &headers, "private, no-cache"))
+}
 
-    // Return photo bytes with appropriate Content-Type header
-    Ok((
+/// Photo bytes with Content-Type, ETag and Cache-Control headers, or
+/// 304 Not Modified when the client's If-None-Match matches
+pub(crate) fn photo_response(bytes: Vec<u8>, filename: &str, headers: &HeaderMap, cache_control: &'static str) -> Response {
+    let etag = photos::etag(&bytes);
+    let not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.split(',').any(|tag| tag.trim().trim_start_matches("W/") == etag || tag.trim() == "*"));
+
+    let cache_headers = [(header::ETAG, etag), (header::CACHE_CONTROL, cache_control.to_string())];
+    if not_modified {
+        return (StatusCode::NOT_MODIFIED, cache_headers).into_response();
+    }
+    (
         StatusCode::OK,
-        [(header::CONTENT_TYPE, content_type)],
-        photo_bytes,
-    ))
+        [(header::CONTENT_TYPE, content_type_from_extension(filename))],
+        cache_headers,
+        bytes,
+    )
+        .into_response()
 }
 
-/// Delete a photo for a recipe
+/// Delete a recipe's cover photo; the next gallery photo becomes the cover
 pub async fn delete_photo(
     State(state): State<RecipeState>,
     Path(id): Path<String>,
     extensions: axum::http::Extensions,
 ) -> ApiResult<StatusCode> {
     let identity = extensions.get::<UserIdentity>();
-    let family_members = identity.and_then(|i| i.family_members.as_ref());
+    let family_id = identity.and_then(|i| i.family_id.as_deref());
+    if let Some(identity) = identity {
+        identity.require_editor()?;
+    }
 
     // Query database for recipe and verify family tenancy
-    let recipe = queries::get_recipe(&state.pool, &id, family_members.map(|v| v.as_slice())).await?;
+    let recipe = queries::get_recipe(&state.pool, &id, family_id).await?;
 
     // Return 404 if recipe has no photo
-    let photo_filename = recipe.recipe.photo_filename
-        .ok_or_else(|| crate::error::ApiError::NotFound("Recipe has no photo".to_string()))?;
+    let cover = recipe.photos.iter().find(|p| p.is_cover)
+        .ok_or_else(|| ApiError::NotFound("Recipe has no photo".to_string()))?;
 
-    // Delete photo file from filesystem
-    let photo_path = format!("{}/{}", state.config.photos_dir, photo_filename);
-    if let Err(e) = tokio::fs::remove_file(&photo_path).await {
-        tracing::warn!("Failed to delete photo file {}: {}", photo_path, e);
-        // Continue to set photo_filename to NULL even if file deletion fails
-    } else {
-        tracing::info!("Deleted photo file: {}", photo_path);
-    }
-
-    // Set recipe's photo_filename to NULL in database
-    sqlx::query("UPDATE recipes SET photo_filename = NULL WHERE id = ?")
-        .bind(&id)
-        .execute(&state.pool)
-        .await?;
+    // Remove it from the gallery, then delete the file and derivatives
+    // (the database is updated even if file deletion fails)
+    let removed = queries::delete_recipe_photo(&state.pool, &id, &cover.id).await?;
+    photos::remove_photo(state.photo_store.as_ref(), &removed.filename).await;
 
     Ok(StatusCode::OK)
 }
 
 /// Helper: Determine file extension from content-type or filename
-fn determine_file_extension(content_type: &Option<String>, filename: &Option<String>) -> ApiResult<String> {
+pub(crate) fn determine_file_extension(content_type: &Option<String>, filename: &Option<String>) -> ApiResult<String> {
     // Try content-type first
     if let Some(ct) = content_type {
         let extension = match ct.as_str() {
@@ -334,7 +532,7 @@ fn determine_file_extension(content_type: &Option<String>, filename: &Option<Str
 }
 
 /// Helper: Validate file extension is supported
-fn validate_file_extension(extension: &str) -> ApiResult<()> {
+pub(crate) fn validate_file_extension(extension: &str) -> ApiResult<()> {
     let ext_lower = extension.to_lowercase();
     match ext_lower.as_str() {
         "jpg" | "jpeg" | "png" | "webp" | "gif" => Ok(()),
@@ -344,10 +542,44 @@ fn validate_file_extension(extension: &str) -> ApiResult<()> {
     }
 }
 
+/// Assess a newly created recipe's difficulty in the background
+pub(crate) fn spawn_difficulty_assessment(
+    pool: &SqlitePool,
+    config: &Arc<Config>,
+    http_client: &reqwest::Client,
+    llm_quota: &LlmQuota,
+    recipe_id: &str,
+) {
+    let recipe_id = recipe_id.to_string();
+    let pool = pool.clone();
+    let config = config.clone();
+    let http_client = http_client.clone();
+    let llm_quota = llm_quota.clone();
+
+    tracing::info!("Recipe {} created without difficulty, spawning auto-assessment task", recipe_id);
+
+    // Spawn async task to assess and update difficulty (non-blocking)
+    tokio::spawn(async move {
+        // Small delay to allow the CREATE transaction to fully commit
+        // and avoid database lock contention (especially in tests with SQLite)
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+        match auto_assign_difficulty(&pool, &config, &llm_quota, &recipe_id, http_client).await {
+            Ok(difficulty) => {
+                tracing::info!("Auto-assigned difficulty {} to recipe {}", difficulty, recipe_id);
+            }
+            Err(e) => {
+                tracing::warn!("Failed to auto-assign difficulty to recipe {}: {}", recipe_id, e);
+            }
+        }
+    });
+}
+
 /// Auto-assign difficulty to a recipe using AI assessment
 async fn auto_assign_difficulty(
     pool: &SqlitePool,
     config: &Config,
+    llm_quota: &LlmQuota,
     recipe_id: &str,
     http_client: reqwest::Client,
 ) -> Result<u8, Box<dyn std::error::Error + Send + Sync>> {
@@ -382,7 +614,8 @@ async fn auto_assign_difficulty(
         )
     };
 
-    // Assess difficulty using AI
+    // Assess difficulty using AI, which counts towards the family's daily quota
+    llm_quota.reserve(recipe_details.recipe.family_id.as_deref()).await?;
     let difficulty = assess_recipe_difficulty(
         &llm,
         &recipe_details.recipe,
@@ -401,6 +634,7 @@ async fn auto_assign_difficulty(
         difficulty: Some(difficulty as i32),
         ingredients: None,
         steps: None,
+        tags: None,
     };
 
     queries::update_recipe(pool, recipe_id, update_input, None, None).await?;
@@ -1,17 +1,32 @@
 use axum::{
-    extract::{Path, State},
-    http::{header, StatusCode},
-    response::{Html, IntoResponse},
-    Json,
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Redirect, Response},
+    Form, Json,
 };
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
 use sqlx::SqlitePool;
 use std::sync::Arc;
 
 use crate::{
     auth::UserIdentity,
     config::Config,
-    db::queries,
-    models::share_link::generate_share_token,
+    db::queries::{self, NewShareLink},
+    error::{ApiError, ApiResult},
+    formats::to_json_ld,
+    handlers::recipes::{photo_response, PhotoQuery},
+    models::{
+        share_link::{
+            generate_share_token, hash_passphrase, verify_passphrase, CreateCollectionShareInput,
+            CreateShareLinkInput, MAX_COLLECTION_RECIPES, MAX_PASSPHRASE_ATTEMPTS,
+            PASSPHRASE_ATTEMPT_WINDOW_MINUTES, SINGLE_USE_GRACE_MINUTES,
+        },
+        RecipePhoto, RecipeWithDetails, ShareKind, ShareLink, SharedRecipe,
+    },
+    photo_store::PhotoStore,
+    photos,
 };
 
 /// Shared state for share handlers
@@ -19,16 +34,20 @@ use crate::{
 pub struct ShareState {
     pub pool: SqlitePool,
     pub config: Arc<Config>,
+    pub photo_store: Arc<dyn PhotoStore>,
 }
 
-/// POST /api/recipes/:id/share — create a share link (authenticated)
+/// POST /api/recipes/:id/share — create a share link (authenticated).
+/// The optional JSON body sets the expiry, a view limit or single use, and a
+/// passphrase.
 pub async fn create_share_link(
     State(state): State<ShareState>,
     Path(recipe_id): Path<String>,
     extensions: axum::http::Extensions,
+    body: Bytes,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
     let identity = extensions.get::<UserIdentity>();
-    let family_members = identity.and_then(|i| i.family_members.as_ref());
+    let family_id = identity.and_then(|i| i.family_id.as_deref());
     let user_email = identity.and_then(|i| i.email.as_ref());
 
     let user_email = match user_email {
@@ -40,12 +59,17 @@ pub async fn create_share_link(
             ));
         }
     };
+    if let Some(identity) = identity
+        && let Err(ApiError::Forbidden(message)) = identity.require_editor()
+    {
+        return Err((StatusCode::FORBIDDEN, Json(serde_json::json!({"error": message}))));
+    }
 
     // Verify recipe exists and is accessible
     let _recipe = queries::get_recipe(
         &state.pool,
         &recipe_id,
-        family_members.map(|v| v.as_slice()),
+        family_id,
     )
     .await
     .map_err(|_| {
@@ -55,20 +79,34 @@ pub async fn create_share_link(
         )
     })?;
 
+    let bad_request = |message: String| (StatusCode::BAD_REQUEST, Json(serde_json::json!({"error": message})));
+    let input: CreateShareLinkInput = if body.iter().all(u8::is_ascii_whitespace) {
+        CreateShareLinkInput::default()
+    } else {
+        serde_json::from_slice(&body).map_err(|e| bad_request(format!("Invalid share link options: {}", e)))?
+    };
+    input.validate().map_err(bad_request)?;
+
     let token = generate_share_token();
-    let expires_at = chrono::Utc::now()
-        .checked_add_signed(chrono::Duration::days(30))
-        .unwrap()
-        .format("%Y-%m-%d %H:%M:%S")
-        .to_string();
+    let expires_at = input.expires_at();
+    let passphrase_hash = input.passphrase.as_deref().map(hash_passphrase).transpose().map_err(|e| {
+        tracing::error!("{}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": "Failed to create share link"})),
+        )
+    })?;
 
-    let link = queries::create_share_link(
-        &state.pool,
-        &token,
-        &recipe_id,
-        &user_email,
-        &expires_at,
-    )
+    let new_link = NewShareLink {
+        token: &token,
+        created_by: &user_email,
+        family_id,
+        expires_at: expires_at.as_deref(),
+        max_views: input.max_views,
+        passphrase_hash: passphrase_hash.as_deref(),
+        single_use: input.single_use,
+    };
+    let link = queries::create_share_link(&state.pool, &recipe_id, new_link)
     .await
     .map_err(|e| {
         tracing::error!("Failed to create share link: {:?}", e);
@@ -83,31 +121,425 @@ pub async fn create_share_link(
         Json(serde_json::json!({
             "token": link.token,
             "url": format!("/share/{}", link.token),
-            "expires_at": link.expires_at
+            "expires_at": link.expires_at,
+            "max_views": link.max_views,
+            "single_use": link.single_use,
+            "protected": link.is_protected()
+        })),
+    ))
+}
+
+/// POST /api/share-links — share a tag, a hand-picked list of recipes or a
+/// meal plan under one public link (authenticated)
+pub async fn create_collection_share_link(
+    State(state): State<ShareState>,
+    extensions: axum::http::Extensions,
+    Json(input): Json<CreateCollectionShareInput>,
+) -> ApiResult<(StatusCode, Json<serde_json::Value>)> {
+    let identity = extensions.get::<UserIdentity>();
+    let family_id = identity.and_then(|i| i.family_id.as_deref());
+    let user_email = identity
+        .and_then(|i| i.email.clone())
+        .ok_or_else(|| ApiError::Validation("Authentication required".to_string()))?;
+    if let Some(identity) = identity {
+        identity.require_editor()?;
+    }
+
+    let (kind, title) = input.validate().map_err(ApiError::Validation)?;
+
+    // (recipe ID, meal plan role) in page order
+    let mut recipes: Vec<(String, Option<String>)> = match kind {
+        ShareKind::Tag => {
+            let tag = input.tag.as_deref().unwrap_or_default();
+            queries::list_recipe_ids_with_tag(&state.pool, tag.trim(), family_id)
+                .await?
+                .into_iter()
+                .map(|id| (id, None))
+                .collect()
+        }
+        ShareKind::List => input.recipe_ids.clone().unwrap_or_default().into_iter().map(|id| (id, None)).collect(),
+        ShareKind::MealPlan => input
+            .meal_plan
+            .iter()
+            .flat_map(|plan| &plan.recipes)
+            .map(|r| (r.recipe_id.clone(), r.role.clone().filter(|role| !role.trim().is_empty())))
+            .collect(),
+        ShareKind::Recipe => unreachable!("validate never returns a single-recipe kind"),
+    };
+    let mut seen = std::collections::HashSet::new();
+    recipes.retain(|(id, _)| seen.insert(id.clone()));
+
+    if recipes.is_empty() {
+        return Err(ApiError::Validation("There are no recipes to share".to_string()));
+    }
+    if recipes.len() > MAX_COLLECTION_RECIPES {
+        return Err(ApiError::Validation(format!(
+            "At most {} recipes can be shared under one link",
+            MAX_COLLECTION_RECIPES
+        )));
+    }
+
+    // Verify every recipe exists and is accessible before publishing
+    for (id, _) in &recipes {
+        queries::get_recipe(&state.pool, id, family_id).await?;
+    }
+
+    let token = generate_share_token();
+    let expires_at = input.options.expires_at();
+    let passphrase_hash = input
+        .options
+        .passphrase
+        .as_deref()
+        .map(hash_passphrase)
+        .transpose()
+        .map_err(ApiError::Internal)?;
+    let new_link = NewShareLink {
+        token: &token,
+        created_by: &user_email,
+        family_id,
+        expires_at: expires_at.as_deref(),
+        max_views: input.options.max_views,
+        passphrase_hash: passphrase_hash.as_deref(),
+        single_use: input.options.single_use,
+    };
+    let link = queries::create_collection_share_link(
+        &state.pool,
+        kind,
+        &title,
+        input.meal_plan.as_ref().and_then(|plan| plan.guest_count),
+        &recipes,
+        new_link,
+    )
+    .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(serde_json::json!({
+            "token": link.token,
+            "url": format!("/share/{}", link.token),
+            "kind": link.kind,
+            "title": link.title,
+            "recipe_count": recipes.len(),
+            "expires_at": link.expires_at,
+            "max_views": link.max_views,
+            "single_use": link.single_use,
+            "protected": link.is_protected()
         })),
     ))
 }
 
-/// GET /share/:token — public share page (no auth)
+/// A share link as listed for management, with its public URL and whether
+/// it still works
+#[derive(Debug, serde::Serialize)]
+pub struct ShareLinkSummary {
+    #[serde(flatten)]
+    pub link: ShareLink,
+    pub url: String,
+    /// The recipe's title, or the collection's
+    pub recipe_title: String,
+    pub active: bool,
+    /// Whether visitors need a passphrase; the hash itself is not listed
+    pub protected: bool,
+}
+
+/// GET /api/share-links — share links to the caller's family's recipes
+pub async fn list_share_links(
+    State(state): State<ShareState>,
+    extensions: axum::http::Extensions,
+) -> ApiResult<Json<Vec<ShareLinkSummary>>> {
+    let identity = extensions.get::<UserIdentity>();
+    let family_id = identity.and_then(|i| i.family_id.as_deref());
+
+    let links = queries::list_share_links(&state.pool, family_id).await?;
+    let titles: std::collections::HashMap<String, String> = queries::list_recipes(&state.pool, family_id)
+        .await?
+        .into_iter()
+        .map(|recipe| (recipe.id, recipe.title))
+        .collect();
+
+    let summaries = links
+        .into_iter()
+        .map(|mut link| ShareLinkSummary {
+            url: format!("/share/{}", link.token),
+            recipe_title: match &link.recipe_id {
+                Some(id) => titles.get(id).cloned().unwrap_or_default(),
+                None => link.title.clone().unwrap_or_default(),
+            },
+            active: !link_expired(&link) && !link.views_used_up(),
+            protected: link.passphrase_hash.take().is_some(),
+            link,
+        })
+        .collect();
+    Ok(Json(summaries))
+}
+
+/// DELETE /api/share-links/:token — revoke a share link immediately.
+/// Editors can only revoke links they created.
+pub async fn revoke_share_link(
+    State(state): State<ShareState>,
+    Path(token): Path<String>,
+    extensions: axum::http::Extensions,
+) -> ApiResult<StatusCode> {
+    let identity = extensions.get::<UserIdentity>();
+    let family_id = identity.and_then(|i| i.family_id.as_deref());
+
+    if let Some(identity) = identity
+        && let Some(link) = queries::get_share_link(&state.pool, &token).await?
+        && link.family_id.as_deref() == family_id
+    {
+        identity.require_can_delete("share links", Some(&link.created_by))?;
+    }
+    queries::delete_share_link(&state.pool, &token, family_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /share/:token — public share page (no auth). Links with a passphrase
+/// show a form until the visitor has unlocked them.
 pub async fn share_page(
     State(state): State<ShareState>,
     Path(token): Path<String>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
     let link = match queries::get_share_link(&state.pool, &token).await {
         Ok(Some(link)) => link,
         _ => return (StatusCode::NOT_FOUND, Html(not_found_page())),
     };
 
-    // Check expiry
-    if is_expired(&link.expires_at) {
+    // Check expiry and the passphrase, then count the view unless the link
+    // is used up
+    if link_expired(&link) {
         return (StatusCode::NOT_FOUND, Html(expired_page()));
     }
+    if !share_unlocked(&link, &headers) {
+        return (StatusCode::UNAUTHORIZED, Html(passphrase_page(&token, None)));
+    }
+    match queries::record_share_link_view(&state.pool, &token, SINGLE_USE_GRACE_MINUTES).await {
+        Ok(true) => {}
+        Ok(false) => return (StatusCode::NOT_FOUND, Html(expired_page())),
+        Err(e) => {
+            tracing::error!("Failed to record share link view: {:?}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Html(not_found_page()));
+        }
+    }
+
+    if link.is_collection() {
+        return match queries::list_share_link_recipes(&state.pool, &token).await {
+            Ok(recipes) => (StatusCode::OK, Html(render_collection_page(&link, &recipes, &headers))),
+            Err(_) => (StatusCode::NOT_FOUND, Html(not_found_page())),
+        };
+    }
 
     let recipe = match queries::get_recipe_by_share_token(&state.pool, &token).await {
         Ok(Some(r)) => r,
         _ => return (StatusCode::NOT_FOUND, Html(not_found_page())),
     };
 
+    let html = render_recipe_page(&recipe, &token, &format!("/share/{}", token), &headers, None);
+    (StatusCode::OK, Html(html))
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct UnlockForm {
+    pub passphrase: String,
+}
+
+/// Outcome of a passphrase given for a share link
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PassphraseCheck {
+    Accepted,
+    Rejected,
+    /// Too many wrong passphrases lately; this one wasn't checked
+    TooManyAttempts,
+}
+
+/// Check a passphrase for a protected link, counting wrong ones towards the
+/// per-link limit. The attempt is counted before the slow check and
+/// forgotten if the passphrase is right, so parallel guesses can't exceed
+/// the limit.
+pub(crate) async fn check_passphrase(pool: &SqlitePool, link: &ShareLink, passphrase: &str) -> ApiResult<PassphraseCheck> {
+    let Some(hash) = link.passphrase_hash.clone() else {
+        return Ok(PassphraseCheck::Accepted);
+    };
+    let attempt = queries::reserve_share_link_attempt(
+        pool,
+        &link.token,
+        PASSPHRASE_ATTEMPT_WINDOW_MINUTES,
+        MAX_PASSPHRASE_ATTEMPTS,
+    )
+    .await?;
+    let Some(attempt) = attempt else {
+        return Ok(PassphraseCheck::TooManyAttempts);
+    };
+
+    // Argon2 is deliberately slow; keep it off the async workers
+    let passphrase = passphrase.to_string();
+    let matches = tokio::task::spawn_blocking(move || verify_passphrase(&passphrase, &hash))
+        .await
+        .map_err(|e| ApiError::Internal(format!("Passphrase check failed: {}", e)))?;
+    if matches {
+        queries::delete_share_link_attempt(pool, attempt).await?;
+        return Ok(PassphraseCheck::Accepted);
+    }
+    Ok(PassphraseCheck::Rejected)
+}
+
+/// POST /share/:token — unlock a protected share link (no auth). The right
+/// passphrase sets a cookie for the link and redirects to its page.
+pub async fn unlock_share_link(
+    State(state): State<ShareState>,
+    Path(token): Path<String>,
+    headers: HeaderMap,
+    Form(form): Form<UnlockForm>,
+) -> Response {
+    let link = match queries::get_share_link(&state.pool, &token).await {
+        Ok(Some(link)) => link,
+        _ => return (StatusCode::NOT_FOUND, Html(not_found_page())).into_response(),
+    };
+    if link_expired(&link) || link.views_used_up() {
+        return (StatusCode::NOT_FOUND, Html(expired_page())).into_response();
+    }
+
+    let page = format!("/share/{}", token);
+    match check_passphrase(&state.pool, &link, &form.passphrase).await {
+        Ok(PassphraseCheck::Accepted) => {
+            let secure = if public_base_url(&headers).is_some_and(|url| url.starts_with("https:")) {
+                "; Secure"
+            } else {
+                ""
+            };
+            let cookie = unlock_cookie(&link).map(|(name, value)| {
+                format!("{}={}; Path=/; Max-Age=86400; HttpOnly; SameSite=Lax{}", name, value, secure)
+            });
+            let mut response = Redirect::to(&page).into_response();
+            if let Some(cookie) = cookie.and_then(|c| c.parse().ok()) {
+                response.headers_mut().insert(header::SET_COOKIE, cookie);
+            }
+            response
+        }
+        Ok(PassphraseCheck::Rejected) => (
+            StatusCode::UNAUTHORIZED,
+            Html(passphrase_page(&token, Some("That passphrase isn't right."))),
+        )
+            .into_response(),
+        Ok(PassphraseCheck::TooManyAttempts) => (
+            StatusCode::TOO_MANY_REQUESTS,
+            Html(passphrase_page(
+                &token,
+                Some(&format!(
+                    "Too many wrong passphrases. Try again in {} minutes.",
+                    PASSPHRASE_ATTEMPT_WINDOW_MINUTES
+                )),
+            )),
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to check share link passphrase: {:?}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Html(not_found_page())).into_response()
+        }
+    }
+}
+
+/// Name and value of the cookie that unlocks a protected link: an HMAC of
+/// the token keyed with the stored hash, so it can't be made without the
+/// database and stops working if the link is recreated
+fn unlock_cookie(link: &ShareLink) -> Option<(String, String)> {
+    let hash = link.passphrase_hash.as_deref()?;
+    let mut mac = Hmac::<Sha256>::new_from_slice(hash.as_bytes()).ok()?;
+    mac.update(link.token.as_bytes());
+    let value: String = mac.finalize().into_bytes().iter().map(|b| format!("{:02x}", b)).collect();
+    Some((format!("share_{}", link.token), value))
+}
+
+/// Whether the request may see the link's content: it has no passphrase, or
+/// the request carries its unlock cookie
+pub(crate) fn share_unlocked(link: &ShareLink, headers: &HeaderMap) -> bool {
+    let Some((name, value)) = unlock_cookie(link) else {
+        return !link.is_protected();
+    };
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .any(|(n, v)| n == name && v == value)
+}
+
+/// GET /share/:token/recipes/:recipe_id — a recipe of a shared collection
+/// (no auth). Only the collection page counts as a view.
+pub async fn shared_collection_recipe(
+    State(state): State<ShareState>,
+    Path((token, recipe_id)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let link = match shared_recipe_link(&state.pool, &token, Some(&recipe_id), &headers).await {
+        Ok(link) => link,
+        Err(StatusCode::GONE) => return (StatusCode::NOT_FOUND, Html(expired_page())),
+        Err(StatusCode::UNAUTHORIZED) => return (StatusCode::UNAUTHORIZED, Html(passphrase_page(&token, None))),
+        Err(_) => return (StatusCode::NOT_FOUND, Html(not_found_page())),
+    };
+
+    let recipe = match queries::get_recipe(&state.pool, &recipe_id, None).await {
+        Ok(r) => r,
+        Err(_) => return (StatusCode::NOT_FOUND, Html(not_found_page())),
+    };
+
+    let collection_path = format!("/share/{}", token);
+    let back_link = (collection_path.as_str(), link.title.as_deref().unwrap_or("All recipes"));
+    let html = render_recipe_page(
+        &recipe,
+        &token,
+        &format!("{}/recipes/{}", collection_path, recipe_id),
+        &headers,
+        Some(back_link),
+    );
+    (StatusCode::OK, Html(html))
+}
+
+/// Check that a share link works and covers the recipe asked for: its own
+/// recipe (`recipe_id` None) or one of its collection's. Expired links give
+/// GONE and locked ones UNAUTHORIZED so pages can say so.
+async fn shared_recipe_link(
+    pool: &SqlitePool,
+    token: &str,
+    recipe_id: Option<&str>,
+    headers: &HeaderMap,
+) -> Result<ShareLink, StatusCode> {
+    let link = match queries::get_share_link(pool, token).await {
+        Ok(Some(link)) => link,
+        _ => return Err(StatusCode::NOT_FOUND),
+    };
+
+    if link_expired(&link) || views_exhausted(&link) {
+        return Err(StatusCode::GONE);
+    }
+    if !share_unlocked(&link, headers) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    let covered = match (&link.recipe_id, recipe_id) {
+        (Some(_), None) => true,
+        (None, Some(id)) => queries::list_share_link_recipes(pool, token)
+            .await
+            .map_err(|_| StatusCode::NOT_FOUND)?
+            .iter()
+            .any(|r| r.recipe_id == id),
+        _ => false,
+    };
+    if covered { Ok(link) } else { Err(StatusCode::NOT_FOUND) }
+}
+
+/// A shared recipe as a standalone page. `page_path` is the page's own path
+/// under /share/; photos are served below it. Collection recipes get a link
+/// back to the collection. Signed-in visitors can save a copy through
+/// POST /api/import/share/:token.
+fn render_recipe_page(
+    recipe: &RecipeWithDetails,
+    token: &str,
+    page_path: &str,
+    headers: &HeaderMap,
+    back_link: Option<(&str, &str)>,
+) -> String {
     let r = &recipe.recipe;
 
     // Build ingredients HTML
@@ -142,11 +574,18 @@ pub async fn share_page(
                 .duration_minutes
                 .map(|d| format!(" <span style=\"color:#888\">({} min)</span>", d))
                 .unwrap_or_default();
+            let step_photos: String = recipe
+                .photos
+                .iter()
+                .filter(|p| p.step_position == Some(step.position))
+                .map(|p| photo_figure(page_path, p, "step-photo"))
+                .collect();
             format!(
-                "<li><strong>Step {}.</strong> {}{}</li>",
+                "<li><strong>Step {}.</strong> {}{}{}</li>",
                 i + 1,
                 html_escape(&step.instruction),
-                duration
+                duration,
+                step_photos
             )
         })
         .collect();
@@ -184,26 +623,60 @@ pub async fn share_page(
     // Photo
     let photo_html = if r.photo_filename.is_some() {
         format!(
-            "<img src=\"/share/{}/photo\" alt=\"{}\" class=\"recipe-photo\">",
-            html_escape(&token),
+            "<img src=\"{}/photo?size=medium\" alt=\"{}\" class=\"recipe-photo\">",
+            html_escape(page_path),
             html_escape(&r.title)
         )
     } else {
         String::new()
     };
 
-    // OG description
+    // Gallery: the other photos not shown with a step
+    let gallery: String = recipe
+        .photos
+        .iter()
+        .filter(|p| !p.is_cover && !recipe.steps.iter().any(|step| p.step_position == Some(step.position)))
+        .map(|p| photo_figure(page_path, p, "gallery-photo"))
+        .collect();
+    let gallery_html = if gallery.is_empty() {
+        String::new()
+    } else {
+        format!("<h2>Photos</h2>\n<div class=\"gallery\">{}</div>", gallery)
+    };
+
+    // Link previews need absolute URLs; fall back to relative ones when the
+    // host is unknown
+    let base_url = public_base_url(headers).unwrap_or_default();
+    let page_url = format!("{}{}", base_url, page_path);
+    let image_url = r.photo_filename.as_ref().map(|_| format!("{}/photo", page_url));
+
+    // Open Graph / Twitter card description
     let og_description = r
         .description
         .as_deref()
         .unwrap_or("A recipe shared from Recipe Vault");
 
-    // OG image
-    let og_image = if r.photo_filename.is_some() {
-        format!("<meta property=\"og:image\" content=\"/share/{}/photo\">", html_escape(&token))
-    } else {
-        String::new()
-    };
+    let mut social_meta = vec![
+        format!("<meta property=\"og:url\" content=\"{}\">", html_escape(&page_url)),
+        "<meta property=\"og:site_name\" content=\"Recipe Vault\">".to_string(),
+        format!("<meta name=\"twitter:title\" content=\"{}\">", html_escape(&r.title)),
+        format!("<meta name=\"twitter:description\" content=\"{}\">", html_escape(og_description)),
+    ];
+    match &image_url {
+        Some(image_url) => {
+            social_meta.push(format!("<meta property=\"og:image\" content=\"{}\">", html_escape(image_url)));
+            social_meta.push(format!("<meta property=\"og:image:alt\" content=\"{}\">", html_escape(&r.title)));
+            social_meta.push("<meta name=\"twitter:card\" content=\"summary_large_image\">".to_string());
+            social_meta.push(format!("<meta name=\"twitter:image\" content=\"{}\">", html_escape(image_url)));
+        }
+        None => social_meta.push("<meta name=\"twitter:card\" content=\"summary\">".to_string()),
+    }
+
+    // schema.org Recipe for other recipe apps; '<' is escaped so the JSON
+    // can't close the script element
+    let json_ld = to_json_ld(recipe, image_url.as_deref(), Some(&page_url))
+        .to_string()
+        .replace('<', "\\u003c");
 
     // Description
     let description_html = r
@@ -212,6 +685,12 @@ pub async fn share_page(
         .map(|d| format!("<p class=\"description\">{}</p>", html_escape(d)))
         .unwrap_or_default();
 
+    let back_html = back_link
+        .map(|(href, title)| {
+            format!("<a class=\"back\" href=\"{}\">&larr; {}</a>", html_escape(href), html_escape(title))
+        })
+        .unwrap_or_default();
+
     // Build plain text for clipboard (used by JS)
     let plain_ingredients: String = recipe
         .ingredients
@@ -237,7 +716,7 @@ pub async fn share_page(
         r.title, plain_ingredients, plain_steps
     );
 
-    let html = format!(
+    format!(
         r#"<!DOCTYPE html>
 <html lang="en">
 <head>
@@ -247,7 +726,8 @@ pub async fn share_page(
 <meta property="og:title" content="{title}">
 <meta property="og:description" content="{og_desc}">
 <meta property="og:type" content="article">
-{og_image}
+{social_meta}
+<script type="application/ld+json">{json_ld}</script>
 <style>
 *{{margin:0;padding:0;box-sizing:border-box}}
 body{{font-family:-apple-system,BlinkMacSystemFont,'Segoe UI',Roboto,sans-serif;max-width:680px;margin:0 auto;padding:24px 16px;color:#333;background:#faf9f6;line-height:1.6}}
@@ -258,14 +738,22 @@ h1{{font-size:1.8em;margin-bottom:8px;color:#2c1810}}
 h2{{font-size:1.2em;margin:24px 0 12px;color:#2c1810;border-bottom:1px solid #e0d6c8;padding-bottom:4px}}
 ul,ol{{padding-left:24px}}
 li{{margin:6px 0}}
+figure{{margin:10px 0}}
+figcaption{{color:#888;font-size:0.85em;font-style:italic;margin-top:4px}}
+.step-photo img{{width:100%;max-width:420px;border-radius:6px;display:block}}
+.gallery{{display:grid;grid-template-columns:repeat(auto-fill,minmax(150px,1fr));gap:12px}}
+.gallery-photo{{margin:0}}
+.gallery-photo img{{width:100%;aspect-ratio:1;object-fit:cover;border-radius:6px;display:block}}
 .footer{{margin-top:32px;padding-top:16px;border-top:1px solid #e0d6c8;color:#999;font-size:0.85em;display:flex;justify-content:space-between;align-items:center}}
 .copy-btn{{background:#2c1810;color:#fff;border:none;padding:8px 16px;border-radius:6px;cursor:pointer;font-size:0.9em}}
 .copy-btn:hover{{background:#4a2e20}}
 .toast{{position:fixed;bottom:24px;left:50%;transform:translateX(-50%);background:#333;color:#fff;padding:10px 20px;border-radius:8px;font-size:0.9em;opacity:0;transition:opacity 0.3s;pointer-events:none}}
 .toast.show{{opacity:1}}
+.back{{display:inline-block;margin-bottom:12px;color:#8b5e3c;text-decoration:none;font-size:0.9em}}
 </style>
 </head>
 <body>
+{back}
 <h1>{title}</h1>
 {meta}
 {description}
@@ -274,57 +762,195 @@ li{{margin:6px 0}}
 <ul>{ingredients}</ul>
 <h2>Preparation</h2>
 <ol>{steps}</ol>
+{gallery}
 <div class="footer">
 <span>Shared from Recipe Vault</span>
-<button class="copy-btn" id="copyBtn">Copy to clipboard</button>
+<span><button class="copy-btn" id="saveBtn">Save to my vault</button> <button class="copy-btn" id="copyBtn">Copy to clipboard</button></span>
 </div>
-<div class="toast" id="toast">Copied to clipboard!</div>
+<div class="toast" id="toast"></div>
 <script>
 (function(){{
 var btn=document.getElementById('copyBtn');
+var saveBtn=document.getElementById('saveBtn');
 var toast=document.getElementById('toast');
-if(!navigator.clipboard){{btn.style.display='none';return}}
-var text={plain_text_json};
-btn.addEventListener('click',function(){{
-navigator.clipboard.writeText(text).then(function(){{
+function show(message){{
+toast.textContent=message;
 toast.classList.add('show');
 setTimeout(function(){{toast.classList.remove('show')}},2000);
+}}
+saveBtn.addEventListener('click',function(){{
+saveBtn.disabled=true;
+fetch({save_url_json},{{method:'POST',credentials:'same-origin',headers:{{'Content-Type':'application/json'}},body:JSON.stringify({{recipe_id:{recipe_id_json}}})}})
+.then(function(r){{
+if(r.status===201){{show('Saved to your vault');return}}
+saveBtn.disabled=false;
+show(r.status===401||r.status===403||r.redirected?'Sign in to Recipe Vault to save this recipe':'Could not save the recipe');
+}})
+.catch(function(){{saveBtn.disabled=false;show('Sign in to Recipe Vault to save this recipe')}});
 }});
+if(!navigator.clipboard){{btn.style.display='none';return}}
+var text={plain_text_json};
+btn.addEventListener('click',function(){{
+navigator.clipboard.writeText(text).then(function(){{show('Copied to clipboard!')}});
 }});
 }})();
 </script>
 </body>
 </html>"#,
+        back = back_html,
         title = html_escape(&r.title),
         og_desc = html_escape(og_description),
-        og_image = og_image,
+        social_meta = social_meta.join("\n"),
+        json_ld = json_ld,
         meta = meta_html,
         description = description_html,
         photo = photo_html,
         ingredients = ingredients_html,
         steps = steps_html,
-        plain_text_json = serde_json::to_string(&plain_text).unwrap_or_else(|_| "\"\"".to_string()),
-    );
+        gallery = gallery_html,
+        save_url_json = serde_json::to_string(&format!("/api/import/share/{}", token))
+            .unwrap_or_else(|_| "\"\"".to_string())
+            .replace('<', "\\u003c"),
+        recipe_id_json = serde_json::to_string(&r.id).unwrap_or_else(|_| "\"\"".to_string()),
+        plain_text_json = serde_json::to_string(&plain_text)
+            .unwrap_or_else(|_| "\"\"".to_string())
+            .replace('<', "\\u003c"),
+    )
+}
 
-    (StatusCode::OK, Html(html))
+/// The index page of a shared collection or meal plan, linking to each
+/// recipe's page
+fn render_collection_page(link: &ShareLink, recipes: &[SharedRecipe], headers: &HeaderMap) -> String {
+    let page_path = format!("/share/{}", link.token);
+    let title = link.title.as_deref().unwrap_or("Shared recipes");
+
+    let cards: String = recipes
+        .iter()
+        .map(|recipe| {
+            let recipe_path = format!("{}/recipes/{}", page_path, recipe.recipe_id);
+            let thumb = if recipe.has_photo {
+                format!(
+                    "<img src=\"{}/photo?size=thumb\" alt=\"\" loading=\"lazy\">",
+                    html_escape(&recipe_path)
+                )
+            } else {
+                "<div class=\"no-photo\"></div>".to_string()
+            };
+            let role = recipe
+                .role
+                .as_deref()
+                .map(|role| format!("<span class=\"role\">{}</span>", html_escape(role)))
+                .unwrap_or_default();
+            format!(
+                "<li><a href=\"{}\">{}<span class=\"name\">{}</span>{}</a></li>",
+                html_escape(&recipe_path),
+                thumb,
+                html_escape(&recipe.title),
+                role
+            )
+        })
+        .collect();
+
+    let count = match recipes.len() {
+        1 => "1 recipe".to_string(),
+        n => format!("{} recipes", n),
+    };
+    let summary = match (link.kind, link.guest_count) {
+        (ShareKind::MealPlan, Some(guests)) => format!("A meal plan for {} guests &middot; {}", guests, count),
+        (ShareKind::MealPlan, None) => format!("A meal plan &middot; {}", count),
+        _ => count,
+    };
+
+    let base_url = public_base_url(headers).unwrap_or_default();
+    let page_url = format!("{}{}", base_url, page_path);
+    let mut social_meta = vec![
+        format!("<meta property=\"og:url\" content=\"{}\">", html_escape(&page_url)),
+        "<meta property=\"og:site_name\" content=\"Recipe Vault\">".to_string(),
+    ];
+    match recipes.iter().find(|recipe| recipe.has_photo) {
+        Some(recipe) => {
+            let image_url = format!("{}/recipes/{}/photo", page_url, recipe.recipe_id);
+            social_meta.push(format!("<meta property=\"og:image\" content=\"{}\">", html_escape(&image_url)));
+            social_meta.push("<meta name=\"twitter:card\" content=\"summary_large_image\">".to_string());
+            social_meta.push(format!("<meta name=\"twitter:image\" content=\"{}\">", html_escape(&image_url)));
+        }
+        None => social_meta.push("<meta name=\"twitter:card\" content=\"summary\">".to_string()),
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title} - Recipe Vault</title>
+<meta property="og:title" content="{title}">
+<meta property="og:description" content="{summary}">
+<meta property="og:type" content="website">
+{social_meta}
+<style>
+*{{margin:0;padding:0;box-sizing:border-box}}
+body{{font-family:-apple-system,BlinkMacSystemFont,'Segoe UI',Roboto,sans-serif;max-width:680px;margin:0 auto;padding:24px 16px;color:#333;background:#faf9f6;line-height:1.6}}
+h1{{font-size:1.8em;margin-bottom:8px;color:#2c1810}}
+.meta{{color:#666;margin:12px 0 20px;font-size:0.95em}}
+ul{{list-style:none;display:grid;grid-template-columns:repeat(auto-fill,minmax(180px,1fr));gap:16px}}
+li a{{display:block;color:inherit;text-decoration:none;background:#fff;border:1px solid #e0d6c8;border-radius:8px;overflow:hidden}}
+li a:hover{{border-color:#8b5e3c}}
+li img,.no-photo{{width:100%;aspect-ratio:4/3;object-fit:cover;display:block;background:#efe8dc}}
+.name{{display:block;padding:8px 10px 2px;font-weight:600;color:#2c1810}}
+.role{{display:block;padding:0 10px 8px;color:#888;font-size:0.85em}}
+.footer{{margin-top:32px;padding-top:16px;border-top:1px solid #e0d6c8;color:#999;font-size:0.85em}}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<div class="meta">{summary}</div>
+<ul>{cards}</ul>
+<div class="footer">Shared from Recipe Vault</div>
+</body>
+</html>"#,
+        title = html_escape(title),
+        summary = summary,
+        social_meta = social_meta.join("\n"),
+        cards = cards,
+    )
 }
 
-/// GET /share/:token/photo — public photo endpoint (no auth)
+/// GET /share/:token/photo?size=thumb|medium|full — public photo endpoint (no auth)
 pub async fn share_photo(
     State(state): State<ShareState>,
     Path(token): Path<String>,
-) -> Result<impl IntoResponse, StatusCode> {
-    let link = match queries::get_share_link(&state.pool, &token).await {
-        Ok(Some(link)) => link,
-        _ => return Err(StatusCode::NOT_FOUND),
-    };
+    Query(query): Query<PhotoQuery>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    serve_cover_photo(&state, &token, None, query, &headers).await
+}
 
-    if is_expired(&link.expires_at) {
-        return Err(StatusCode::NOT_FOUND);
-    }
+/// GET /share/:token/recipes/:recipe_id/photo — cover photo of a collection recipe (no auth)
+pub async fn shared_recipe_photo(
+    State(state): State<ShareState>,
+    Path((token, recipe_id)): Path<(String, String)>,
+    Query(query): Query<PhotoQuery>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    serve_cover_photo(&state, &token, Some(&recipe_id), query, &headers).await
+}
+
+async fn serve_cover_photo(
+    state: &ShareState,
+    token: &str,
+    recipe_id: Option<&str>,
+    query: PhotoQuery,
+    headers: &HeaderMap,
+) -> Result<Response, StatusCode> {
+    let size = query.size().map_err(|_| StatusCode::BAD_REQUEST)?;
+    let link = shared_recipe_link(&state.pool, token, recipe_id, headers)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+    let recipe_id = recipe_id.or(link.recipe_id.as_deref()).unwrap_or_default();
 
     // Get recipe to find photo filename
-    let recipe = match queries::get_recipe(&state.pool, &link.recipe_id, None).await {
+    let recipe = match queries::get_recipe(&state.pool, recipe_id, None).await {
         Ok(r) => r,
         Err(_) => return Err(StatusCode::NOT_FOUND),
     };
@@ -334,23 +960,123 @@ pub async fn share_photo(
         None => return Err(StatusCode::NOT_FOUND),
     };
 
-    let photo_path = format!("{}/{}", state.config.photos_dir, photo_filename);
-    let photo_bytes = tokio::fs::read(&photo_path).await.map_err(|e| {
-        tracing::error!("Failed to read shared photo {}: {}", photo_path, e);
-        StatusCode::NOT_FOUND
-    })?;
+    let (photo_bytes, served_filename) = photos::read_photo(state.photo_store.as_ref(), &photo_filename, size)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
 
-    let content_type = content_type_from_extension(&photo_filename);
+    // Short public lifetime so a replaced photo shows up soon on shared pages
+    Ok(photo_response(photo_bytes, &served_filename, headers, "public, max-age=300"))
+}
 
-    Ok((
-        StatusCode::OK,
-        [(header::CONTENT_TYPE, content_type)],
-        photo_bytes,
-    ))
+/// GET /share/:token/photos/:photo_id?size=thumb|medium|full — public gallery photo (no auth)
+pub async fn share_gallery_photo(
+    State(state): State<ShareState>,
+    Path((token, photo_id)): Path<(String, String)>,
+    Query(query): Query<PhotoQuery>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    serve_gallery_photo(&state, &token, None, &photo_id, query, &headers).await
+}
+
+/// GET /share/:token/recipes/:recipe_id/photos/:photo_id — gallery photo of a
+/// collection recipe (no auth)
+pub async fn shared_recipe_gallery_photo(
+    State(state): State<ShareState>,
+    Path((token, recipe_id, photo_id)): Path<(String, String, String)>,
+    Query(query): Query<PhotoQuery>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    serve_gallery_photo(&state, &token, Some(&recipe_id), &photo_id, query, &headers).await
+}
+
+async fn serve_gallery_photo(
+    state: &ShareState,
+    token: &str,
+    recipe_id: Option<&str>,
+    photo_id: &str,
+    query: PhotoQuery,
+    headers: &HeaderMap,
+) -> Result<Response, StatusCode> {
+    let size = query.size().map_err(|_| StatusCode::BAD_REQUEST)?;
+    let link = shared_recipe_link(&state.pool, token, recipe_id, headers)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+    let recipe_id = recipe_id.or(link.recipe_id.as_deref()).unwrap_or_default();
+
+    // Only gallery photos of the shared recipe; source scans stay private
+    let photo = queries::get_recipe_photo(&state.pool, recipe_id, photo_id)
+        .await
+        .ok()
+        .filter(|photo| !photo.source_scan)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let (photo_bytes, served_filename) = photos::read_photo(state.photo_store.as_ref(), &photo.filename, size)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+
+    Ok(photo_response(photo_bytes, &served_filename, headers, "public, max-age=300"))
+}
+
+/// A linked thumbnail (gallery) or medium image (step) with its caption
+fn photo_figure(page_path: &str, photo: &RecipePhoto, class: &str) -> String {
+    let base = format!("{}/photos/{}", html_escape(page_path), html_escape(&photo.id));
+    let src = if class == "gallery-photo" {
+        format!("{}?size=thumb", base)
+    } else {
+        format!("{}?size=medium", base)
+    };
+    let caption = photo.caption.as_deref().map(html_escape);
+    format!(
+        "<figure class=\"{class}\"><a href=\"{base}?size=full\"><img src=\"{src}\" alt=\"{alt}\" loading=\"lazy\"></a>{figcaption}</figure>",
+        class = class,
+        base = base,
+        src = src,
+        alt = caption.clone().unwrap_or_default(),
+        figcaption = caption.map(|c| format!("<figcaption>{}</figcaption>", c)).unwrap_or_default(),
+    )
+}
+
+/// Scheme and host the client used to reach us, for building absolute URLs.
+/// Honours `X-Forwarded-Proto` from the Cloudflare tunnel.
+pub(crate) fn public_base_url(headers: &HeaderMap) -> Option<String> {
+    let host = headers.get(header::HOST)?.to_str().ok()?;
+    if host.is_empty() || host.contains(['/', '"', '<', '>', ' ']) {
+        return None;
+    }
+
+    let scheme = match headers.get("x-forwarded-proto").and_then(|v| v.to_str().ok()) {
+        Some("http") => "http",
+        Some(_) => "https",
+        None if host.starts_with("localhost") || host.starts_with("127.0.0.1") => "http",
+        None => "https",
+    };
+    Some(format!("{}://{}", scheme, host))
 }
 
 /// Check if an expires_at datetime string is in the past
-fn is_expired(expires_at: &str) -> bool {
+/// Whether a share link is past its expiry; links without one never expire
+pub(crate) fn link_expired(link: &ShareLink) -> bool {
+    link.expires_at.as_deref().is_some_and(is_expired)
+}
+
+/// Whether a link's views are used up and the grace period after the last
+/// one, in which that page can still load photos and collection recipes,
+/// is over
+pub(crate) fn views_exhausted(link: &ShareLink) -> bool {
+    if !link.views_used_up() {
+        return false;
+    }
+    let last_view = link
+        .last_accessed_at
+        .as_deref()
+        .and_then(|last| chrono::NaiveDateTime::parse_from_str(last, "%Y-%m-%d %H:%M:%S").ok());
+    match last_view {
+        Some(last) => chrono::Utc::now().naive_utc() > last + chrono::Duration::minutes(SINGLE_USE_GRACE_MINUTES),
+        None => true,
+    }
+}
+
+pub(crate) fn is_expired(expires_at: &str) -> bool {
     match chrono::NaiveDateTime::parse_from_str(expires_at, "%Y-%m-%d %H:%M:%S") {
         Ok(expiry) => {
             let now = chrono::Utc::now().naive_utc();
@@ -360,7 +1086,7 @@ fn is_expired(expires_at: &str) -> bool {
     }
 }
 
-fn content_type_from_extension(filename: &str) -> &'static str {
+pub(crate) fn content_type_from_extension(filename: &str) -> &'static str {
     let extension = std::path::Path::new(filename)
         .extension()
         .and_then(|s| s.to_str())
@@ -375,7 +1101,7 @@ fn content_type_from_extension(filename: &str) -> &'static str {
     }
 }
 
-fn not_found_page() -> String {
+pub(crate) fn not_found_page() -> String {
     r#"<!DOCTYPE html>
 <html lang="en">
 <head>
@@ -396,7 +1122,45 @@ p{color:#666}
         .to_string()
 }
 
-fn expired_page() -> String {
+/// The form asking for a protected link's passphrase, with an optional error
+fn passphrase_page(token: &str, error: Option<&str>) -> String {
+    let error_html = error
+        .map(|e| format!("<p class=\"error\">{}</p>", html_escape(e)))
+        .unwrap_or_default();
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<meta name="robots" content="noindex">
+<title>Passphrase Required - Recipe Vault</title>
+<style>
+body{{font-family:-apple-system,BlinkMacSystemFont,'Segoe UI',Roboto,sans-serif;max-width:480px;margin:80px auto;padding:24px 16px;color:#333;text-align:center;background:#faf9f6}}
+h1{{font-size:1.5em;color:#2c1810;margin-bottom:12px}}
+p{{color:#666}}
+.error{{color:#b3261e}}
+form{{margin-top:20px;display:flex;gap:8px;justify-content:center}}
+input{{padding:8px 10px;border:1px solid #e0d6c8;border-radius:6px;font-size:1em;min-width:0;flex:1;max-width:260px}}
+button{{background:#2c1810;color:#fff;border:none;padding:8px 16px;border-radius:6px;cursor:pointer;font-size:0.95em}}
+</style>
+</head>
+<body>
+<h1>This recipe is protected</h1>
+<p>Enter the passphrase you were given to see it.</p>
+{error}
+<form method="post" action="/share/{token}">
+<input type="password" name="passphrase" autocomplete="off" autofocus required aria-label="Passphrase">
+<button type="submit">Open</button>
+</form>
+</body>
+</html>"#,
+        error = error_html,
+        token = html_escape(token),
+    )
+}
+
+pub(crate) fn expired_page() -> String {
     r#"<!DOCTYPE html>
 <html lang="en">
 <head>
@@ -411,17 +1175,48 @@ p{color:#666}
 </head>
 <body>
 <h1>This share link has expired</h1>
-<p>Share links are valid for 30 days. Ask the recipe owner to share it again.</p>
+<p>This link has expired or been used up. Ask the recipe owner to share it again.</p>
 </body>
 </html>"#
         .to_string()
 }
 
 /// Escape HTML special characters to prevent XSS
-fn html_escape(s: &str) -> String {
+pub(crate) fn html_escape(s: &str) -> String {
     s.replace('&', "&amp;")
         .replace('<', "&lt;")
         .replace('>', "&gt;")
         .replace('"', "&quot;")
         .replace('\'', "&#x27;")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(*name, value.parse().unwrap());
+        }
+        map
+    }
+
+    #[test]
+    fn test_public_base_url() {
+        assert_eq!(
+            public_base_url(&headers(&[("host", "recipes.example.com")])).as_deref(),
+            Some("https://recipes.example.com")
+        );
+        assert_eq!(
+            public_base_url(&headers(&[("host", "localhost:3000")])).as_deref(),
+            Some("http://localhost:3000")
+        );
+        assert_eq!(
+            public_base_url(&headers(&[("host", "example.com"), ("x-forwarded-proto", "http")])).as_deref(),
+            Some("http://example.com")
+        );
+        assert_eq!(public_base_url(&headers(&[("host", "evil\"><script>")])), None);
+        assert_eq!(public_base_url(&HeaderMap::new()), None);
+    }
+}
@@ -0,0 +1,270 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{Html, IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+
+use crate::{
+    auth::UserIdentity,
+    db::queries,
+    error::{ApiError, ApiResult},
+    handlers::share::{expired_page, is_expired, not_found_page},
+    models::{share_link::generate_share_token, RecipeWithDetails},
+    shopping::{build_shopping_list, export, AisleConfig, ShoppingListFormat},
+};
+
+/// Maximum number of recipes combined into one shopping list
+const MAX_RECIPES: usize = 50;
+
+/// Shared state for shopping list handlers
+#[derive(Clone)]
+pub struct ShoppingState {
+    pub pool: SqlitePool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ShoppingListQuery {
+    /// Comma-separated recipe IDs
+    pub recipe_ids: String,
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FormatQuery {
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateShoppingListLinkInput {
+    pub recipe_ids: Vec<String>,
+}
+
+/// GET /api/shopping-list?recipe_ids=a,b&format=markdown — combined ingredients
+pub async fn get_shopping_list(
+    State(state): State<ShoppingState>,
+    Query(query): Query<ShoppingListQuery>,
+    extensions: axum::http::Extensions,
+) -> ApiResult<Response> {
+    let identity = extensions.get::<UserIdentity>();
+    let family_id = identity.and_then(|i| i.family_id.as_deref());
+
+    let format = match query.format.as_deref() {
+        Some(f) => ShoppingListFormat::parse(f)?,
+        None => ShoppingListFormat::Json,
+    };
+
+    let recipe_ids = parse_recipe_ids(query.recipe_ids.split(','))?;
+    let mut recipes = Vec::with_capacity(recipe_ids.len());
+    for id in &recipe_ids {
+        recipes.push(queries::get_recipe(&state.pool, id, family_id).await?);
+    }
+
+    let aisles = load_aisle_config(&state.pool, family_id).await?;
+    Ok(render_response(&recipes, &aisles, format))
+}
+
+/// GET /api/shopping-list/aisles — the caller's family aisle order (or the default)
+pub async fn get_aisles(
+    State(state): State<ShoppingState>,
+    extensions: axum::http::Extensions,
+) -> ApiResult<Json<AisleConfig>> {
+    let identity = extensions.get::<UserIdentity>();
+    let family_id = identity.and_then(|i| i.family_id.as_deref());
+
+    Ok(Json(load_aisle_config(&state.pool, family_id).await?))
+}
+
+/// PUT /api/shopping-list/aisles — replace the caller's family aisle order
+pub async fn update_aisles(
+    State(state): State<ShoppingState>,
+    extensions: axum::http::Extensions,
+    Json(input): Json<AisleConfig>,
+) -> ApiResult<Json<AisleConfig>> {
+    let identity = extensions.get::<UserIdentity>();
+    let family_id = identity.and_then(|i| i.family_id.as_deref()).ok_or_else(|| {
+        ApiError::Validation("Aisle order can only be configured by a family member".to_string())
+    })?;
+    if let Some(identity) = identity {
+        identity.require_editor()?;
+    }
+    let user_email = identity.and_then(|i| i.email.as_deref());
+
+    let config = input.validate()?;
+    queries::save_aisle_config(&state.pool, family_id, &config, user_email).await?;
+    Ok(Json(config))
+}
+
+/// POST /api/shopping-list/share — create a public link to a shopping list
+pub async fn create_shopping_list_link(
+    State(state): State<ShoppingState>,
+    extensions: axum::http::Extensions,
+    Json(input): Json<CreateShoppingListLinkInput>,
+) -> ApiResult<(StatusCode, Json<serde_json::Value>)> {
+    let identity = extensions.get::<UserIdentity>();
+    let family_id = identity.and_then(|i| i.family_id.as_deref());
+    let user_email = identity
+        .and_then(|i| i.email.clone())
+        .ok_or_else(|| ApiError::Validation("Authentication required".to_string()))?;
+    if let Some(identity) = identity {
+        identity.require_editor()?;
+    }
+
+    let recipe_ids = parse_recipe_ids(input.recipe_ids.iter().map(|s| s.as_str()))?;
+
+    // Verify every recipe exists and is accessible before publishing
+    for id in &recipe_ids {
+        queries::get_recipe(&state.pool, id, family_id).await?;
+    }
+
+    let token = generate_share_token();
+    let expires_at = chrono::Utc::now()
+        .checked_add_signed(chrono::Duration::days(30))
+        .unwrap()
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string();
+
+    let link = queries::create_shopping_list_link(
+        &state.pool,
+        &token,
+        &recipe_ids,
+        family_id,
+        &user_email,
+        &expires_at,
+    )
+    .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(serde_json::json!({
+            "token": link.token,
+            "url": format!("/share/shopping/{}", link.token),
+            "expires_at": link.expires_at
+        })),
+    ))
+}
+
+/// GET /share/shopping/:token — public shopping list (no auth). Defaults to HTML.
+pub async fn shared_shopping_list(
+    State(state): State<ShoppingState>,
+    Path(token): Path<String>,
+    Query(query): Query<FormatQuery>,
+) -> Response {
+    let link = match queries::get_shopping_list_link(&state.pool, &token).await {
+        Ok(Some(link)) => link,
+        _ => return (StatusCode::NOT_FOUND, Html(not_found_page())).into_response(),
+    };
+
+    if is_expired(&link.expires_at) {
+        return (StatusCode::NOT_FOUND, Html(expired_page())).into_response();
+    }
+
+    let format = match query.format.as_deref().map(ShoppingListFormat::parse) {
+        None => ShoppingListFormat::Html,
+        Some(Ok(format)) => format,
+        Some(Err(e)) => return ApiError::Validation(e).into_response(),
+    };
+
+    // Share links bypass tenancy; recipes deleted since the link was made are skipped
+    let mut recipes = Vec::new();
+    for id in link.recipe_ids() {
+        match queries::get_recipe(&state.pool, &id, None).await {
+            Ok(recipe) => recipes.push(recipe),
+            Err(ApiError::NotFound(_)) => continue,
+            Err(e) => return e.into_response(),
+        }
+    }
+    if recipes.is_empty() {
+        return (StatusCode::NOT_FOUND, Html(not_found_page())).into_response();
+    }
+
+    let aisles = match load_aisle_config(&state.pool, link.family_id.as_deref()).await {
+        Ok(aisles) => aisles,
+        Err(e) => return e.into_response(),
+    };
+
+    render_response(&recipes, &aisles, format)
+}
+
+/// Split, trim and de-duplicate recipe IDs, enforcing the per-list limit
+fn parse_recipe_ids<'a>(ids: impl Iterator<Item = &'a str>) -> ApiResult<Vec<String>> {
+    let mut recipe_ids: Vec<String> = Vec::new();
+    for id in ids.map(str::trim).filter(|id| !id.is_empty()) {
+        if !recipe_ids.iter().any(|existing| existing == id) {
+            recipe_ids.push(id.to_string());
+        }
+    }
+
+    if recipe_ids.is_empty() {
+        return Err(ApiError::Validation("At least one recipe ID is required".to_string()));
+    }
+    if recipe_ids.len() > MAX_RECIPES {
+        return Err(ApiError::Validation(format!(
+            "A shopping list can combine at most {} recipes",
+            MAX_RECIPES
+        )));
+    }
+    Ok(recipe_ids)
+}
+
+/// The family's configured aisle order, or the default when unset / god mode
+async fn load_aisle_config(pool: &SqlitePool, family_id: Option<&str>) -> ApiResult<AisleConfig> {
+    match family_id {
+        Some(family_id) => Ok(queries::get_aisle_config(pool, family_id)
+            .await?
+            .unwrap_or_default()),
+        None => Ok(AisleConfig::default()),
+    }
+}
+
+fn render_response(
+    recipes: &[RecipeWithDetails],
+    aisles: &AisleConfig,
+    format: ShoppingListFormat,
+) -> Response {
+    let list = build_shopping_list(recipes, aisles);
+    if format == ShoppingListFormat::Json {
+        return Json(list).into_response();
+    }
+
+    let body = export::render(&list, format);
+    let disposition = format!(
+        "inline; filename=\"shopping-list.{}\"",
+        format.file_extension()
+    );
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_recipe_ids_dedupes_and_trims() {
+        let ids = parse_recipe_ids(" a, b,,a ,c".split(',')).unwrap();
+        assert_eq!(ids, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_parse_recipe_ids_requires_one() {
+        assert!(parse_recipe_ids(" , ".split(',')).is_err());
+    }
+
+    #[test]
+    fn test_parse_recipe_ids_limit() {
+        let many: Vec<String> = (0..=MAX_RECIPES).map(|i| i.to_string()).collect();
+        assert!(parse_recipe_ids(many.iter().map(|s| s.as_str())).is_err());
+    }
+}
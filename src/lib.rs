@@ -1,9 +1,22 @@
 pub mod ai;
+pub mod archive;
 pub mod auth;
 pub mod chat;
+pub mod cloudflare_access;
 pub mod config;
+pub mod cookbook;
 pub mod db;
 pub mod error;
+pub mod families;
+pub mod formats;
 pub mod handlers;
+pub mod llm_quota;
 pub mod mcp;
 pub mod models;
+pub mod photo_integrity;
+pub mod photo_store;
+pub mod photos;
+pub mod rate_limit;
+pub mod safe_fetch;
+pub mod shopping;
+pub mod sync;
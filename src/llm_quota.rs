@@ -0,0 +1,76 @@
+//! Daily quota on the LLM calls each family makes.
+//!
+//! Chat, AI-assisted imports and difficulty assessment all call a paid LLM.
+//! Calls are counted per family and UTC day in `llm_usage`; once a family
+//! reaches `LLM_DAILY_CALLS_PER_FAMILY` further requests are refused until
+//! midnight UTC. Callers without a family (admins) aren't limited.
+//!
+//! A call is reserved before it is made, in a single conditional upsert, so
+//! concurrent requests can't overshoot the limit. Chat, which may make
+//! several calls for one message, records the rest afterwards. The server
+//! shares one `LlmQuota` between every handler that calls an LLM.
+
+use std::time::Duration;
+
+use sqlx::SqlitePool;
+
+use crate::{
+    db::queries,
+    error::{ApiError, ApiResult},
+};
+
+#[derive(Clone)]
+pub struct LlmQuota {
+    pool: SqlitePool,
+    daily_calls: Option<u32>,
+}
+
+impl LlmQuota {
+    /// Allow each family `daily_calls` calls a day; None allows any number
+    pub fn new(pool: SqlitePool, daily_calls: Option<u32>) -> Self {
+        Self { pool, daily_calls }
+    }
+
+    /// Count one call for the family, or refuse it if today's are used up
+    pub async fn reserve(&self, family_id: Option<&str>) -> ApiResult<()> {
+        let Some(family_id) = family_id else {
+            return Ok(());
+        };
+        let Some(limit) = self.daily_calls else {
+            queries::record_llm_calls(&self.pool, family_id, 1).await?;
+            return Ok(());
+        };
+        if limit > 0 && queries::reserve_llm_call(&self.pool, family_id, i64::from(limit)).await? {
+            return Ok(());
+        }
+
+        tracing::warn!("Family {} has used its {} LLM calls for today", family_id, limit);
+        Err(ApiError::RateLimited {
+            message: format!(
+                "Your family has used its {} AI requests for today; the allowance resets at midnight UTC",
+                limit
+            ),
+            retry_after: until_midnight_utc(),
+        })
+    }
+
+    /// Count calls made beyond the reserved one. Failures are logged, not
+    /// returned: the calls have happened either way.
+    pub async fn record(&self, family_id: Option<&str>, calls: u32) {
+        let Some(family_id) = family_id.filter(|_| calls > 0) else {
+            return;
+        };
+        if let Err(e) = queries::record_llm_calls(&self.pool, family_id, i64::from(calls)).await {
+            tracing::warn!("Failed to record {} LLM calls for family {}: {}", calls, family_id, e);
+        }
+    }
+}
+
+fn until_midnight_utc() -> Duration {
+    let now = chrono::Utc::now();
+    let midnight = (now.date_naive() + chrono::Days::new(1))
+        .and_hms_opt(0, 0, 0)
+        .expect("midnight is a valid time")
+        .and_utc();
+    (midnight - now).to_std().unwrap_or_default()
+}
@@ -16,8 +16,11 @@ use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 use recipe_vault::{
     auth::{api_key_auth, cloudflare_auth, load_or_generate_api_key, ApiKeyState, CloudflareAuthState},
     config::Config,
-    db,
-    handlers::{chat, recipes, share::{self, ShareState}, ui::{self, UiState}},
+    config::PhotoStorage,
+    cloudflare_access::AccessVerifier, db, families::FamilyDirectory, photo_integrity, photo_store,
+    llm_quota::LlmQuota,
+    rate_limit::{rate_limit, RateLimiter},
+    handlers::{admin::{self, AdminState}, api_keys::{self, ApiKeysState}, archive::{self, ArchiveState}, chat, cookbook::{self, CookbookState}, families::{self, FamiliesState}, gallery, import::{self, ImportState}, recipes, share::{self, ShareState}, shopping::{self, ShoppingState}, ui::{self, UiState}},
 };
 
 #[tokio::main]
@@ -42,29 +45,19 @@ async fn main() {
     tracing::debug!("Bind address: {}", config.bind_address);
 
     // Create photos directory if it doesn't exist
-    tracing::info!("Ensuring photos directory exists: {}", config.photos_dir);
-    if let Err(e) = std::fs::create_dir_all(&config.photos_dir) {
-        tracing::error!("Failed to create photos directory at {}: {}", config.photos_dir, e);
-        panic!("Failed to create photos directory: {}", e);
+    if matches!(config.photo_storage, PhotoStorage::Local) {
+        tracing::info!("Ensuring photos directory exists: {}", config.photos_dir);
+        if let Err(e) = std::fs::create_dir_all(&config.photos_dir) {
+            tracing::error!("Failed to create photos directory at {}: {}", config.photos_dir, e);
+            panic!("Failed to create photos directory: {}", e);
+        }
+        tracing::info!("Photos directory ready");
     }
-    tracing::info!("Photos directory ready");
+    let photo_store = photo_store::from_config(&config).expect("Failed to configure photo storage");
+    tracing::info!("Photo storage: {}", photo_store.location());
 
     // Load or generate API key
     let api_key = load_or_generate_api_key();
-    let api_key_for_chat = api_key.clone();
-
-    let families_config = Arc::new(config.families_config.clone());
-
-    let api_key_state = ApiKeyState {
-        key: Arc::new(api_key),
-        families_config: families_config.clone(),
-        dev_user_email: config.dev_user_email.clone(),
-    };
-
-    let cloudflare_auth_state = CloudflareAuthState {
-        dev_user_email: config.dev_user_email.clone(),
-        families_config: families_config.clone(),
-    };
 
     let ui_state = UiState {};
 
@@ -105,25 +98,164 @@ async fn main() {
         }
     }
 
+    // Family membership comes from the database; families.yaml seeds it once
+    let families = FamilyDirectory::new(pool.clone());
+    match &config.families_config {
+        Some(families_config) => {
+            let imported = families
+                .import_config(families_config)
+                .await
+                .expect("Failed to import families.yaml");
+            if imported > 0 {
+                tracing::info!("Imported {} families from families.yaml", imported);
+            }
+        }
+        None => tracing::info!("No families.yaml found; families are managed through the admin API"),
+    }
+
+    // Recipes and share links from before family ownership go to their creator's family
+    let assigned = db::queries::assign_family_ids(&pool)
+        .await
+        .expect("Failed to assign recipes to families");
+    if assigned > 0 {
+        tracing::info!("Assigned {} recipes and share links to their creators' families", assigned);
+    }
+
+    let api_key_state = ApiKeyState {
+        pool: pool.clone(),
+        legacy_key: api_key.clone(),
+        families: families.clone(),
+        dev_user_email: config.dev_user_email.clone(),
+    };
+
     // Shared HTTP client — reuses TLS sessions and connection pool across all LLM calls
     let http_client = reqwest::Client::new();
 
-    // Create chat state with AI agent
-    let chat_state = recipe_vault::chat::ChatState::new(config.clone(), api_key_for_chat, http_client.clone());
+    // Without Access settings the email header is trusted, as behind a tunnel
+    let access = match &config.cloudflare_access {
+        Some(access_config) => {
+            let verifier = AccessVerifier::new(access_config.clone(), http_client.clone());
+            if let Err(e) = verifier.refresh().await {
+                tracing::warn!("Cloudflare Access keys unavailable, will retry: {}", e);
+            }
+            tracing::info!("Verifying Cloudflare Access tokens for {:?}", access_config.audiences);
+            if config.dev_user_email.is_some() {
+                tracing::warn!("DEV_USER_EMAIL is ignored for the web UI while Access tokens are verified");
+            }
+            Some(verifier)
+        }
+        None => {
+            tracing::warn!(
+                "CF_ACCESS_AUD not set: trusting Cf-Access-Authenticated-User-Email without verification"
+            );
+            None
+        }
+    };
+
+    let cloudflare_auth_state = CloudflareAuthState {
+        dev_user_email: config.dev_user_email.clone(),
+        families: families.clone(),
+        access,
+    };
+
+    // One daily LLM quota per family, shared by chat, imports and difficulty ratings
+    let llm_quota = LlmQuota::new(pool.clone(), config.llm_daily_calls_per_family);
+
+    // Create chat state; each user gets their own AI agent, stopped when idle
+    let chat_state = recipe_vault::chat::ChatState::new(config.clone(), pool.clone(), http_client.clone())
+        .with_llm_quota(llm_quota.clone());
+    match chat_state.revoke_stale_agent_keys().await {
+        Ok(revoked) if revoked > 0 => tracing::info!("Revoked {} keys of chat agents from the last run", revoked),
+        Ok(_) => {}
+        Err(e) => tracing::warn!("{}", e),
+    }
+    chat_state.spawn_idle_reaper();
 
     // Create recipe state with database and AI configuration
     let recipe_state = recipes::RecipeState {
+        pool: pool.clone(),
+        config: Arc::new(config.clone()),
+        http_client: http_client.clone(),
+        photo_store: photo_store.clone(),
+        llm_quota: llm_quota.clone(),
+    };
+
+    // Create import state (fetches pages, LLM fallback for unstructured ones)
+    let import_state = ImportState {
         pool: pool.clone(),
         config: Arc::new(config.clone()),
         http_client,
+        fetch_client: recipe_vault::safe_fetch::client(&config.import_private_hosts)
+            .expect("Failed to create import HTTP client"),
+        photo_store: photo_store.clone(),
+        llm_quota,
+    };
+
+    // Create archive state for vault export/import
+    let archive_state = ArchiveState {
+        pool: pool.clone(),
+        config: Arc::new(config.clone()),
+        photo_store: photo_store.clone(),
+    };
+
+    // Create cookbook state (reads photos to inline them)
+    let cookbook_state = CookbookState {
+        pool: pool.clone(),
+        config: Arc::new(config.clone()),
+        photo_store: photo_store.clone(),
     };
 
     // Create share state for share handlers
     let share_state = ShareState {
         pool: pool.clone(),
         config: Arc::new(config.clone()),
+        photo_store: photo_store.clone(),
+    };
+
+    // Create admin state for instance-wide maintenance
+    let admin_state = AdminState {
+        pool: pool.clone(),
+        photo_store: photo_store.clone(),
+    };
+
+    // Create API keys state for per-user keys and instance key rotation
+    let api_keys_state = ApiKeysState {
+        pool: pool.clone(),
+        legacy_key: api_key.clone(),
+    };
+
+    // Create families state for family management and invitations
+    let families_state = FamiliesState {
+        pool: pool.clone(),
+        families: families.clone(),
     };
 
+    // Repair photo storage drift on a schedule, if configured
+    if let Some(hours) = config.photo_check_interval_hours {
+        tracing::info!("Scheduling photo storage repair every {} hours", hours);
+        photo_integrity::spawn_scheduled_repair(
+            pool.clone(),
+            photo_store.clone(),
+            std::time::Duration::from_secs(hours * 60 * 60),
+        );
+    }
+
+    // Create shopping list state
+    let shopping_state = ShoppingState {
+        pool: pool.clone(),
+    };
+
+    // Per-user rate limits; recipe, import, archive and family changes share
+    // one allowance, share links and shopping list links another
+    let chat_limiter = RateLimiter::new("chat messages", config.rate_limits.chat_per_minute);
+    let mutation_limiter =
+        RateLimiter::new("recipe changes", config.rate_limits.mutations_per_minute).writes_only();
+    let share_limiter = RateLimiter::new("share link changes", config.rate_limits.shares_per_minute).writes_only();
+    tracing::info!("Rate limits per minute: {:?}", config.rate_limits);
+    if let Some(calls) = config.llm_daily_calls_per_family {
+        tracing::info!("Each family may make {} LLM calls a day", calls);
+    }
+
     // Build recipe routes with recipe state
     let recipe_routes = Router::new()
         .route("/recipes", post(recipes::create_recipe))
@@ -134,25 +266,119 @@ async fn main() {
         .route("/recipes/:id/photo", post(recipes::upload_photo))
         .route("/recipes/:id/photo", get(recipes::get_photo))
         .route("/recipes/:id/photo", delete(recipes::delete_photo))
+        .route("/recipes/:id/photos", get(gallery::list_photos))
+        .route("/recipes/:id/photos", post(gallery::add_photo))
+        .route("/recipes/:id/photos/order", put(gallery::reorder_photos))
+        .route("/recipes/:id/photos/:photo_id", get(gallery::get_photo))
+        .route("/recipes/:id/photos/:photo_id", put(gallery::update_photo))
+        .route("/recipes/:id/photos/:photo_id", delete(gallery::delete_photo))
+        .route("/recipes/:id/source-scans", get(gallery::list_source_scans))
+        .route("/recipes/:id/source-scans", post(gallery::add_source_scan))
+        .route_layer(middleware::from_fn_with_state(mutation_limiter.clone(), rate_limit))
         .with_state(recipe_state);
 
     // Build share link creation route (authenticated, under /api)
     let share_api_routes = Router::new()
         .route("/recipes/:id/share", post(share::create_share_link))
+        .route("/share-links", get(share::list_share_links).post(share::create_collection_share_link))
+        .route("/share-links/:token", delete(share::revoke_share_link))
+        .route_layer(middleware::from_fn_with_state(share_limiter.clone(), rate_limit))
         .with_state(share_state.clone());
 
+    // Build shopping list routes (authenticated, under /api)
+    let shopping_routes = Router::new()
+        .route("/shopping-list", get(shopping::get_shopping_list))
+        .route("/shopping-list/aisles", get(shopping::get_aisles))
+        .route("/shopping-list/aisles", put(shopping::update_aisles))
+        .route(
+            "/shopping-list/share",
+            post(shopping::create_shopping_list_link)
+                .route_layer(middleware::from_fn_with_state(share_limiter, rate_limit)),
+        )
+        .with_state(shopping_state.clone());
+
+    // Build import routes (authenticated, under /api)
+    let import_routes = Router::new()
+        .route("/import/url", post(import::import_url))
+        .route("/import/cooklang", post(import::import_cooklang))
+        .route("/import/share/:token", post(import::import_share))
+        .route(
+            "/import/file",
+            post(import::import_file).layer(DefaultBodyLimit::max(import::MAX_IMPORT_FILE_BYTES)),
+        )
+        .route_layer(middleware::from_fn_with_state(mutation_limiter.clone(), rate_limit))
+        .with_state(import_state);
+
+    // Build vault export/import routes (authenticated, under /api)
+    let archive_routes = Router::new()
+        .route("/export", get(archive::export_archive))
+        .route(
+            "/import",
+            post(archive::import_archive).layer(DefaultBodyLimit::max(archive::MAX_ARCHIVE_BYTES)),
+        )
+        .route_layer(middleware::from_fn_with_state(mutation_limiter.clone(), rate_limit))
+        .with_state(archive_state);
+
+    // Build printable cookbook route (authenticated, under /api)
+    let cookbook_routes = Router::new()
+        .route("/cookbook", get(cookbook::get_cookbook))
+        .with_state(cookbook_state);
+
+    // Build admin routes (authenticated, under /api; unscoped API key only)
+    let admin_routes = Router::new()
+        .route("/admin/photos/check", get(admin::check_photos))
+        .route("/admin/photos/repair", post(admin::repair_photos))
+        .with_state(admin_state);
+
+    // Build API key routes (each user's keys; rotation is admin only)
+    let api_key_routes = Router::new()
+        .route("/api-keys", get(api_keys::list_api_keys).post(api_keys::create_api_key))
+        .route("/api-keys/:id", delete(api_keys::revoke_api_key))
+        .route("/admin/api-key/rotate", post(api_keys::rotate_legacy_api_key))
+        .with_state(api_keys_state);
+
+    // Build family routes (admin management plus each member's own family)
+    let family_routes = Router::new()
+        .route("/admin/families", get(families::list_families).post(families::create_family))
+        .route("/admin/families/:id", get(families::get_family).delete(families::delete_family))
+        .route("/admin/families/:id/members", post(families::add_family_member))
+        .route(
+            "/admin/families/:id/members/:email",
+            put(families::set_family_member_role).delete(families::remove_family_member),
+        )
+        .route("/admin/families/:id/invitations", post(families::create_admin_invitation))
+        .route("/family", get(families::get_own_family))
+        .route("/family/invitations", post(families::create_family_invitation))
+        .route(
+            "/family/members/:email",
+            put(families::set_own_family_member_role).delete(families::remove_own_family_member),
+        )
+        .route_layer(middleware::from_fn_with_state(mutation_limiter, rate_limit))
+        .with_state(families_state.clone());
+
     // Build chat routes with chat state
     let chat_routes = Router::new()
-        .route("/chat", post(chat::chat))
+        .route(
+            "/chat",
+            post(chat::chat).route_layer(middleware::from_fn_with_state(chat_limiter, rate_limit)),
+        )
         .route("/chat/reset", post(chat::reset_conversation))
         .with_state(chat_state);
 
     // Combine API routes with authentication
     // Note: cloudflare_auth middleware runs on the entire app,
     // so UserIdentity is already available in extensions here.
+    // The rate limits above run after api_key_auth, so apply to both paths.
     let api_routes = Router::new()
         .merge(recipe_routes)
         .merge(share_api_routes)
+        .merge(shopping_routes)
+        .merge(import_routes)
+        .merge(archive_routes)
+        .merge(cookbook_routes)
+        .merge(admin_routes)
+        .merge(api_key_routes)
+        .merge(family_routes)
         .merge(chat_routes)
         .route_layer(middleware::from_fn_with_state(
             api_key_state.clone(),
@@ -165,15 +391,30 @@ async fn main() {
         .route("/chat", get(ui::chat_page))
         .with_state(ui_state);
 
+    // Invitation pages need the visitor's identity but not family membership
+    let invite_routes = Router::new()
+        .route("/invite/:token", get(families::invitation_page).post(families::accept_invitation))
+        .with_state(families_state);
+
     // Public share routes (no authentication required)
     let public_share_routes = Router::new()
-        .route("/share/:token", get(share::share_page))
+        .route("/share/:token", get(share::share_page).post(share::unlock_share_link))
         .route("/share/:token/photo", get(share::share_photo))
-        .with_state(share_state);
+        .route("/share/:token/photos/:photo_id", get(share::share_gallery_photo))
+        .route("/share/:token/recipes/:recipe_id", get(share::shared_collection_recipe))
+        .route("/share/:token/recipes/:recipe_id/photo", get(share::shared_recipe_photo))
+        .route("/share/:token/recipes/:recipe_id/photos/:photo_id", get(share::shared_recipe_gallery_photo))
+        .with_state(share_state)
+        .merge(
+            Router::new()
+                .route("/share/shopping/:token", get(shopping::shared_shopping_list))
+                .with_state(shopping_state),
+        );
 
     let app = Router::new()
         .nest_service("/static", ServeDir::new("./static"))
         .merge(ui_routes)
+        .merge(invite_routes)
         .nest("/api", api_routes)
         .layer(middleware::from_fn_with_state(
             cloudflare_auth_state,
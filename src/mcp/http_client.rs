@@ -1,5 +1,5 @@
 use crate::mcp::protocol::JsonRpcError;
-use crate::models::{CreateRecipeInput, Recipe, RecipeWithDetails, UpdateRecipeInput};
+use crate::models::{CreateRecipeInput, Recipe, RecipePhoto, RecipeWithDetails, UpdateRecipeInput};
 use reqwest::blocking::Client;
 use reqwest::StatusCode;
 use std::time::Duration;
@@ -119,6 +119,145 @@ impl ApiClient {
         }
     }
 
+    /// Import a recipe from a web page, optionally saving it
+    pub fn import_recipe_url(&self, url: &str, save: bool) -> Result<serde_json::Value, JsonRpcError> {
+        let endpoint = format!("{}/api/import/url", self.base_url);
+
+        let request = self
+            .client
+            .post(&endpoint)
+            .json(&serde_json::json!({ "url": url, "save": save }));
+        let response = self
+            .add_auth_headers(request)
+            .send()
+            .map_err(|e| self.map_request_error(e))?;
+
+        self.handle_response(response)
+    }
+
+    /// Get a recipe as Markdown
+    pub fn get_recipe_markdown(&self, recipe_id: &str) -> Result<String, JsonRpcError> {
+        let url = format!("{}/api/recipes/{}.md", self.base_url, recipe_id);
+
+        let request = self.client.get(&url);
+        let response = self
+            .add_auth_headers(request)
+            .send()
+            .map_err(|e| self.map_request_error(e))?;
+
+        self.handle_text_response(response)
+    }
+
+    /// Replace a recipe with the contents of a Markdown file, returning the
+    /// recipe as saved
+    pub fn put_recipe_markdown(&self, recipe_id: &str, markdown: &str) -> Result<String, JsonRpcError> {
+        let url = format!("{}/api/recipes/{}.md", self.base_url, recipe_id);
+
+        let request = self
+            .client
+            .put(&url)
+            .header(reqwest::header::CONTENT_TYPE, "text/markdown; charset=utf-8")
+            .body(markdown.to_string());
+        let response = self
+            .add_auth_headers(request)
+            .send()
+            .map_err(|e| self.map_request_error(e))?;
+
+        self.handle_text_response(response)
+    }
+
+    /// Keep an image as a source scan of a recipe
+    pub fn attach_source_scan(
+        &self,
+        recipe_id: &str,
+        image: Vec<u8>,
+        media_type: &str,
+        caption: Option<&str>,
+    ) -> Result<RecipePhoto, JsonRpcError> {
+        let url = format!("{}/api/recipes/{}/source-scans", self.base_url, recipe_id);
+
+        let photo = reqwest::blocking::multipart::Part::bytes(image)
+            .file_name("source-scan")
+            .mime_str(media_type)
+            .map_err(|_| JsonRpcError::invalid_params(format!("Invalid media type: {}", media_type)))?;
+        let mut form = reqwest::blocking::multipart::Form::new().part("photo", photo);
+        if let Some(caption) = caption {
+            form = form.text("caption", caption.to_string());
+        }
+
+        let request = self.client.post(&url).multipart(form);
+        let response = self
+            .add_auth_headers(request)
+            .send()
+            .map_err(|e| self.map_request_error(e))?;
+
+        self.handle_response(response)
+    }
+
+    /// Create a public share link for a recipe
+    pub fn create_share_link(
+        &self,
+        recipe_id: &str,
+        options: serde_json::Value,
+    ) -> Result<serde_json::Value, JsonRpcError> {
+        let url = format!("{}/api/recipes/{}/share", self.base_url, recipe_id);
+
+        let request = self.client.post(&url).json(&options);
+        let response = self
+            .add_auth_headers(request)
+            .send()
+            .map_err(|e| self.map_request_error(e))?;
+
+        self.handle_response(response)
+    }
+
+    /// Create a public share link for a tag, list of recipes or meal plan
+    pub fn create_collection_share_link(
+        &self,
+        input: serde_json::Value,
+    ) -> Result<serde_json::Value, JsonRpcError> {
+        let url = format!("{}/api/share-links", self.base_url);
+
+        let request = self.client.post(&url).json(&input);
+        let response = self
+            .add_auth_headers(request)
+            .send()
+            .map_err(|e| self.map_request_error(e))?;
+
+        self.handle_response(response)
+    }
+
+    /// List the share links of the user's family
+    pub fn list_share_links(&self) -> Result<Vec<serde_json::Value>, JsonRpcError> {
+        let url = format!("{}/api/share-links", self.base_url);
+
+        let request = self.client.get(&url);
+        let response = self
+            .add_auth_headers(request)
+            .send()
+            .map_err(|e| self.map_request_error(e))?;
+
+        self.handle_response(response)
+    }
+
+    /// Revoke a share link by token
+    pub fn revoke_share_link(&self, token: &str) -> Result<(), JsonRpcError> {
+        let url = format!("{}/api/share-links/{}", self.base_url, token);
+
+        let request = self.client.delete(&url);
+        let response = self
+            .add_auth_headers(request)
+            .send()
+            .map_err(|e| self.map_request_error(e))?;
+
+        let status = response.status();
+        if status.is_success() {
+            Ok(())
+        } else {
+            Err(self.map_status_error(status, response.text().ok()))
+        }
+    }
+
     /// Handle response and deserialize JSON
     fn handle_response<T: serde::de::DeserializeOwned>(
         &self,
@@ -136,6 +275,18 @@ impl ApiClient {
         }
     }
 
+    /// Handle response and return the body as text
+    fn handle_text_response(&self, response: reqwest::blocking::Response) -> Result<String, JsonRpcError> {
+        let status = response.status();
+        let body = response.text().ok();
+
+        if status.is_success() {
+            body.ok_or_else(|| JsonRpcError::internal_error("Failed to read response"))
+        } else {
+            Err(self.map_status_error(status, body))
+        }
+    }
+
     /// Map HTTP status codes to JSON-RPC errors
     fn map_status_error(&self, status: StatusCode, body: Option<String>) -> JsonRpcError {
         let message = body.unwrap_or_else(|| status.to_string());
@@ -144,6 +295,11 @@ impl ApiClient {
             StatusCode::UNAUTHORIZED => {
                 JsonRpcError::internal_error("API authentication failed. Check API_KEY configuration.")
             }
+            StatusCode::FORBIDDEN => JsonRpcError::forbidden(format!(
+                "Permission denied: {}. The user's family role doesn't allow this; explain that to them \
+                 rather than retrying.",
+                error_text(&message)
+            )),
             StatusCode::NOT_FOUND => JsonRpcError::not_found(message),
             StatusCode::CONFLICT => JsonRpcError::conflict(message),
             StatusCode::BAD_REQUEST => JsonRpcError::invalid_params(message),
@@ -170,6 +326,14 @@ impl ApiClient {
     }
 }
 
+/// The `error` field of an API error body, or the body itself
+fn error_text(body: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|value| value.get("error")?.as_str().map(str::to_string))
+        .unwrap_or_else(|| body.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -200,6 +364,17 @@ mod tests {
         assert_eq!(error.code, -32002);
     }
 
+    #[test]
+    fn test_map_status_error_forbidden() {
+        let client = test_client();
+        let body = r#"{"error": "Viewers can only read the family's recipes", "code": "FORBIDDEN"}"#;
+        let error = client.map_status_error(StatusCode::FORBIDDEN, Some(body.to_string()));
+        assert_eq!(error.code, -32003);
+        assert!(error
+            .message
+            .starts_with("Permission denied: Viewers can only read the family's recipes."));
+    }
+
     #[test]
     fn test_map_status_error_bad_request() {
         let client = test_client();
@@ -108,6 +108,14 @@ impl JsonRpcError {
             message: message.into(),
         }
     }
+
+    /// Permission denied (custom code)
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self {
+            code: -32003,
+            message: message.into(),
+        }
+    }
 }
 
 /// MCP tool definition with JSON Schema
@@ -108,6 +108,12 @@ fn handle_request(client: &ApiClient, request: JsonRpcRequest) -> Option<JsonRpc
                 "update_recipe" => tools::handle_update_recipe(client, arguments),
                 "delete_recipe" => tools::handle_delete_recipe(client, arguments),
                 "start_timer" => tools::handle_start_timer(client, arguments),
+                "import_recipe_url" => tools::handle_import_recipe_url(client, arguments),
+                "attach_source_scan" => tools::handle_attach_source_scan(client, arguments),
+                "create_share_link" => tools::handle_create_share_link(client, arguments),
+                "share_collection" => tools::handle_share_collection(client, arguments),
+                "list_share_links" => tools::handle_list_share_links(client, arguments),
+                "revoke_share_link" => tools::handle_revoke_share_link(client, arguments),
                 _ => {
                     return Some(JsonRpcResponse::error(
                         request_id,
@@ -1,6 +1,7 @@
 use crate::mcp::http_client::ApiClient;
 use crate::mcp::protocol::{JsonRpcError, ToolDefinition};
 use crate::models::recipe::{CreateIngredientInput, CreateRecipeInput, CreateStepInput, UpdateRecipeInput};
+use base64::Engine;
 use serde_json::{json, Value as JsonValue};
 
 /// Get all available MCP tool definitions
@@ -12,6 +13,12 @@ pub fn get_all_tools() -> Vec<ToolDefinition> {
         update_recipe_tool(),
         delete_recipe_tool(),
         start_timer_tool(),
+        import_recipe_url_tool(),
+        attach_source_scan_tool(),
+        create_share_link_tool(),
+        share_collection_tool(),
+        list_share_links_tool(),
+        revoke_share_link_tool(),
     ]
 }
 
@@ -79,6 +86,11 @@ pub fn create_recipe_tool() -> ToolDefinition {
                     "minimum": 1,
                     "maximum": 5
                 },
+                "tags": {
+                    "type": "array",
+                    "description": "Tags such as \"dessert\" or \"weeknight\" (optional)",
+                    "items": {"type": "string"}
+                },
                 "ingredients": {
                     "type": "array",
                     "description": "List of ingredients",
@@ -150,6 +162,11 @@ pub fn update_recipe_tool() -> ToolDefinition {
                     "minimum": 1,
                     "maximum": 5
                 },
+                "tags": {
+                    "type": "array",
+                    "description": "New list of tags (replaces all existing, optional)",
+                    "items": {"type": "string"}
+                },
                 "ingredients": {
                     "type": "array",
                     "description": "New list of ingredients (replaces all existing)",
@@ -223,6 +240,187 @@ pub fn start_timer_tool() -> ToolDefinition {
     )
 }
 
+/// Tool definition for importing a recipe from a web page
+pub fn import_recipe_url_tool() -> ToolDefinition {
+    ToolDefinition::new(
+        "import_recipe_url",
+        "Extract a recipe from a web page URL using the site's structured recipe data (schema.org), falling back to AI extraction. With save=false (default) returns the extracted recipe for preview; with save=true saves it and returns the new recipe_id.",
+        json!({
+            "type": "object",
+            "properties": {
+                "url": {
+                    "type": "string",
+                    "description": "The http(s) URL of the recipe page"
+                },
+                "save": {
+                    "type": "boolean",
+                    "description": "Save the recipe to the database (default false)"
+                }
+            },
+            "required": ["url"]
+        })
+    )
+}
+
+/// Tool definition for keeping the conversation's image as a source scan
+pub fn attach_source_scan_tool() -> ToolDefinition {
+    ToolDefinition::new(
+        "attach_source_scan",
+        "Attach the image the user shared in this conversation (e.g. a photo of a handwritten recipe card or cookbook page) to a saved recipe as its source scan. Call this after create_recipe when the recipe was transcribed from an image. The most recent image in the conversation is attached automatically; it is kept separately from the recipe's cover photo.",
+        json!({
+            "type": "object",
+            "properties": {
+                "recipe_id": {
+                    "type": "string",
+                    "description": "The UUID of the recipe the image was transcribed into"
+                },
+                "caption": {
+                    "type": "string",
+                    "description": "Short note about the original, e.g. 'Grandma's card, 1962' (optional)"
+                }
+            },
+            "required": ["recipe_id"]
+        })
+    )
+}
+
+/// Tool definition for creating a public share link
+pub fn create_share_link_tool() -> ToolDefinition {
+    ToolDefinition::new(
+        "create_share_link",
+        "Create a public link to a recipe that anyone can open without logging in. Links expire after 30 days unless expires_in_days or never_expires is given, can be limited to a number of views or a single view, and can require a passphrase.",
+        json!({
+            "type": "object",
+            "properties": {
+                "recipe_id": {
+                    "type": "string",
+                    "description": "The UUID of the recipe to share"
+                },
+                "expires_in_days": {
+                    "type": "integer",
+                    "description": "Days until the link stops working (optional, default 30)",
+                    "minimum": 1
+                },
+                "never_expires": {
+                    "type": "boolean",
+                    "description": "Keep the link working until it is revoked (optional)"
+                },
+                "max_views": {
+                    "type": "integer",
+                    "description": "Stop serving the page after this many views (optional)",
+                    "minimum": 1
+                },
+                "single_use": {
+                    "type": "boolean",
+                    "description": "Show the page once, then expire the link (optional)"
+                },
+                "passphrase": {
+                    "type": "string",
+                    "description": "Passphrase visitors must enter to see the recipe (optional, at least 4 characters)"
+                }
+            },
+            "required": ["recipe_id"]
+        })
+    )
+}
+
+/// Tool definition for sharing several recipes under one public link
+pub fn share_collection_tool() -> ToolDefinition {
+    ToolDefinition::new(
+        "share_collection",
+        "Create one public link to several recipes: everything with a tag, a hand-picked list, or a meal plan. The link opens an index page linking to each recipe. Give exactly one of tag, recipe_ids or meal_plan. Expiry, view limits, single use and passphrases work as for create_share_link.",
+        json!({
+            "type": "object",
+            "properties": {
+                "title": {
+                    "type": "string",
+                    "description": "Title of the shared page (optional for tags, which default to the tag)"
+                },
+                "tag": {
+                    "type": "string",
+                    "description": "Share the recipes that have this tag now"
+                },
+                "recipe_ids": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "UUIDs of the recipes to share, in page order"
+                },
+                "meal_plan": {
+                    "type": "object",
+                    "description": "A meal plan, as shown with display_meal_plan",
+                    "properties": {
+                        "guest_count": { "type": "integer", "minimum": 1 },
+                        "recipes": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "recipe_id": { "type": "string" },
+                                    "role": { "type": "string", "description": "e.g. centrepiece, side, dessert" }
+                                },
+                                "required": ["recipe_id"]
+                            }
+                        }
+                    },
+                    "required": ["recipes"]
+                },
+                "expires_in_days": {
+                    "type": "integer",
+                    "description": "Days until the link stops working (optional, default 30)",
+                    "minimum": 1
+                },
+                "never_expires": {
+                    "type": "boolean",
+                    "description": "Keep the link working until it is revoked (optional)"
+                },
+                "max_views": {
+                    "type": "integer",
+                    "description": "Stop serving the index page after this many views (optional)",
+                    "minimum": 1
+                },
+                "single_use": {
+                    "type": "boolean",
+                    "description": "Show the index page once, then expire the link (optional)"
+                },
+                "passphrase": {
+                    "type": "string",
+                    "description": "Passphrase visitors must enter (optional, at least 4 characters)"
+                }
+            }
+        })
+    )
+}
+
+/// Tool definition for listing share links
+pub fn list_share_links_tool() -> ToolDefinition {
+    ToolDefinition::new(
+        "list_share_links",
+        "List the public share links to the family's recipes, with their expiry, view counts and whether they still work.",
+        json!({
+            "type": "object",
+            "properties": {}
+        })
+    )
+}
+
+/// Tool definition for revoking a share link
+pub fn revoke_share_link_tool() -> ToolDefinition {
+    ToolDefinition::new(
+        "revoke_share_link",
+        "Revoke a public share link so it stops working immediately. Use the token from list_share_links.",
+        json!({
+            "type": "object",
+            "properties": {
+                "token": {
+                    "type": "string",
+                    "description": "The share link token"
+                }
+            },
+            "required": ["token"]
+        })
+    )
+}
+
 /// Handle list_recipes tool call
 pub fn handle_list_recipes(client: &ApiClient, _params: JsonValue) -> Result<JsonValue, JsonRpcError> {
     let recipes = client.list_recipes()?;
@@ -293,6 +491,7 @@ pub fn handle_update_recipe(client: &ApiClient, params: JsonValue) -> Result<Jso
         difficulty: params.get("difficulty").and_then(|v| v.as_i64()).map(|v| v as i32),
         ingredients,
         steps,
+        tags: parse_tags(&params),
     };
 
     let recipe = client.update_recipe(recipe_id, update_input)?;
@@ -340,6 +539,109 @@ pub fn handle_start_timer(_client: &ApiClient, params: JsonValue) -> Result<Json
     }))
 }
 
+/// Handle import_recipe_url tool call
+pub fn handle_import_recipe_url(client: &ApiClient, params: JsonValue) -> Result<JsonValue, JsonRpcError> {
+    let url = params
+        .get("url")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| JsonRpcError::invalid_params("Missing or invalid url parameter"))?;
+    let save = params.get("save").and_then(|v| v.as_bool()).unwrap_or(false);
+
+    let mut result = client.import_recipe_url(url, save)?;
+
+    // Surface the new ID under the same key list_recipes uses
+    if let Some(id) = result.pointer("/recipe/id").cloned() {
+        result["recipe_id"] = id;
+    }
+    Ok(result)
+}
+
+/// Handle attach_source_scan tool call. The chat agent adds the conversation's
+/// image as `image_data` (base64) and `media_type`.
+pub fn handle_attach_source_scan(client: &ApiClient, params: JsonValue) -> Result<JsonValue, JsonRpcError> {
+    let recipe_id = params
+        .get("recipe_id")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| JsonRpcError::invalid_params("Missing or invalid recipe_id parameter"))?;
+    let image_data = params
+        .get("image_data")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| JsonRpcError::invalid_params("There is no image in this conversation to attach"))?;
+    let media_type = params.get("media_type").and_then(|v| v.as_str()).unwrap_or("image/jpeg");
+    let caption = params.get("caption").and_then(|v| v.as_str());
+
+    let image = base64::engine::general_purpose::STANDARD
+        .decode(image_data)
+        .map_err(|e| JsonRpcError::invalid_params(format!("image_data is not valid base64: {}", e)))?;
+
+    let scan = client.attach_source_scan(recipe_id, image, media_type, caption)?;
+    Ok(json!({
+        "status": "success",
+        "recipe_id": recipe_id,
+        "source_scan_id": scan.id,
+        "message": "Source scan attached to the recipe"
+    }))
+}
+
+/// Handle create_share_link tool call
+pub fn handle_create_share_link(client: &ApiClient, params: JsonValue) -> Result<JsonValue, JsonRpcError> {
+    let recipe_id = params
+        .get("recipe_id")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| JsonRpcError::invalid_params("Missing or invalid recipe_id parameter"))?;
+
+    let mut options = serde_json::Map::new();
+    for key in ["expires_in_days", "never_expires", "max_views", "single_use", "passphrase"] {
+        if let Some(value) = params.get(key).filter(|v| !v.is_null()) {
+            options.insert(key.to_string(), value.clone());
+        }
+    }
+
+    client.create_share_link(recipe_id, JsonValue::Object(options))
+}
+
+/// Handle share_collection tool call
+pub fn handle_share_collection(client: &ApiClient, params: JsonValue) -> Result<JsonValue, JsonRpcError> {
+    let mut input = serde_json::Map::new();
+    for key in [
+        "title",
+        "tag",
+        "recipe_ids",
+        "meal_plan",
+        "expires_in_days",
+        "never_expires",
+        "max_views",
+        "single_use",
+        "passphrase",
+    ] {
+        if let Some(value) = params.get(key).filter(|v| !v.is_null()) {
+            input.insert(key.to_string(), value.clone());
+        }
+    }
+    if !["tag", "recipe_ids", "meal_plan"].iter().any(|key| input.contains_key(*key)) {
+        return Err(JsonRpcError::invalid_params("Give one of tag, recipe_ids or meal_plan"));
+    }
+
+    client.create_collection_share_link(JsonValue::Object(input))
+}
+
+/// Handle list_share_links tool call
+pub fn handle_list_share_links(client: &ApiClient, _params: JsonValue) -> Result<JsonValue, JsonRpcError> {
+    let links = client.list_share_links()?;
+    Ok(json!({ "share_links": links }))
+}
+
+/// Handle revoke_share_link tool call
+pub fn handle_revoke_share_link(client: &ApiClient, params: JsonValue) -> Result<JsonValue, JsonRpcError> {
+    let token = params
+        .get("token")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| JsonRpcError::invalid_params("Missing or invalid token parameter"))?;
+
+    client.revoke_share_link(token)?;
+    Ok(json!({ "status": "success", "message": format!("Share link {} revoked", token) }))
+}
+
 /// Handle create_recipe tool call
 pub fn handle_create_recipe(client: &ApiClient, params: JsonValue) -> Result<JsonValue, JsonRpcError> {
     let title = params
@@ -394,6 +696,9 @@ pub fn handle_create_recipe(client: &ApiClient, params: JsonValue) -> Result<Jso
         difficulty,
         ingredients,
         steps,
+        tags: parse_tags(&params).unwrap_or_default(),
+        source_url: None,
+        shared_by: None,
     };
 
     let recipe = client.create_recipe(create_recipe)?;
@@ -401,6 +706,16 @@ pub fn handle_create_recipe(client: &ApiClient, params: JsonValue) -> Result<Jso
         .map_err(|e| JsonRpcError::internal_error(format!("Serialization error: {}", e)))
 }
 
+/// Parse the optional tags array
+fn parse_tags(params: &JsonValue) -> Option<Vec<String>> {
+    params.get("tags").and_then(|v| v.as_array()).map(|tags| {
+        tags.iter()
+            .filter_map(|t| t.as_str())
+            .map(|t| t.to_string())
+            .collect()
+    })
+}
+
 /// Parse ingredients from JSON array
 fn parse_ingredients(ingredients_array: &[JsonValue]) -> Result<Vec<CreateIngredientInput>, JsonRpcError> {
     ingredients_array
@@ -517,13 +832,31 @@ mod tests {
     #[test]
     fn test_get_all_tools() {
         let tools = get_all_tools();
-        assert_eq!(tools.len(), 6);
+        assert_eq!(tools.len(), 12);
         assert_eq!(tools[0].name, "list_recipes");
         assert_eq!(tools[1].name, "get_recipe");
         assert_eq!(tools[2].name, "create_recipe");
         assert_eq!(tools[3].name, "update_recipe");
         assert_eq!(tools[4].name, "delete_recipe");
         assert_eq!(tools[5].name, "start_timer");
+        assert_eq!(tools[6].name, "import_recipe_url");
+        assert_eq!(tools[7].name, "attach_source_scan");
+        assert_eq!(tools[8].name, "create_share_link");
+        assert_eq!(tools[9].name, "share_collection");
+        assert_eq!(tools[10].name, "list_share_links");
+        assert_eq!(tools[11].name, "revoke_share_link");
+    }
+
+    #[test]
+    fn test_attach_source_scan_requires_conversation_image() {
+        let client = ApiClient::new("http://localhost:3000".to_string(), None, None).unwrap();
+        let error = handle_attach_source_scan(&client, json!({"recipe_id": "abc"})).unwrap_err();
+        assert_eq!(error.code, -32602);
+        assert!(error.message.contains("no image"));
+
+        let error = handle_attach_source_scan(&client, json!({"recipe_id": "abc", "image_data": "not base64!"}))
+            .unwrap_err();
+        assert_eq!(error.code, -32602);
     }
 
     #[test]
@@ -0,0 +1,181 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::models::family::validate_email;
+
+/// What requests made with an API key may do, from least to most
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum ApiKeyScope {
+    /// GET requests only, within the owner's family
+    ReadOnly,
+    /// Anything the owner could do, within their family
+    #[default]
+    ReadWrite,
+    /// Every family's data and the admin endpoints
+    Admin,
+}
+
+/// A per-user API key. The key itself is never stored, only its hash.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ApiKey {
+    pub id: String,
+    /// First characters of the key, to tell keys apart
+    pub prefix: String,
+    pub label: String,
+    /// The owner; requests made with the key act as them
+    pub email: String,
+    pub scope: ApiKeyScope,
+    pub created_by: Option<String>,
+    pub created_at: String,
+    /// None never expires
+    pub expires_at: Option<String>,
+    pub last_used_at: Option<String>,
+    pub revoked_at: Option<String>,
+}
+
+impl ApiKey {
+    /// Not revoked and not expired
+    pub fn is_active(&self) -> bool {
+        let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        self.revoked_at.is_none() && self.expires_at.as_ref().is_none_or(|expires_at| *expires_at > now)
+    }
+}
+
+pub const MAX_API_KEY_LABEL_LENGTH: usize = 100;
+pub const MAX_API_KEY_DAYS: i64 = 3650;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CreateApiKeyInput {
+    /// What the key is for, e.g. "Claude Desktop on my laptop"
+    pub label: String,
+    #[serde(default)]
+    pub scope: ApiKeyScope,
+    /// Days until the key expires (default never)
+    #[serde(default)]
+    pub expires_in_days: Option<i64>,
+    /// Owner of the key (admins only; defaults to the caller)
+    #[serde(default)]
+    pub email: Option<String>,
+}
+
+impl CreateApiKeyInput {
+    /// Check the input and normalize the email
+    pub fn validate(&mut self) -> Result<(), String> {
+        self.label = self.label.trim().to_string();
+        if self.label.is_empty() || self.label.chars().count() > MAX_API_KEY_LABEL_LENGTH {
+            return Err(format!("label must be 1 to {} characters", MAX_API_KEY_LABEL_LENGTH));
+        }
+        if let Some(days) = self.expires_in_days
+            && !(1..=MAX_API_KEY_DAYS).contains(&days)
+        {
+            return Err(format!("expires_in_days must be between 1 and {}", MAX_API_KEY_DAYS));
+        }
+        if let Some(email) = &self.email {
+            self.email = Some(validate_email(email)?);
+        }
+        Ok(())
+    }
+
+    pub fn expires_at(&self) -> Option<String> {
+        self.expires_in_days.map(|days| {
+            (chrono::Utc::now() + chrono::Duration::days(days))
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+    }
+}
+
+/// Marks per-user keys apart from the legacy hex key
+const API_KEY_PREFIX: &str = "rv_";
+const API_KEY_SECRET_LENGTH: usize = 40;
+const API_KEY_DISPLAY_LENGTH: usize = 10;
+const KEY_CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+/// Generate a new per-user key: `rv_` and 40 random alphanumerics
+pub fn generate_api_key() -> String {
+    let mut rng = rand::thread_rng();
+    let secret: String = (0..API_KEY_SECRET_LENGTH)
+        .map(|_| KEY_CHARS[rng.gen_range(0..KEY_CHARS.len())] as char)
+        .collect();
+    format!("{}{}", API_KEY_PREFIX, secret)
+}
+
+/// The stored form of a key. Keys are long and random, so a plain SHA-256
+/// is enough and lets a key be looked up by its hash.
+pub fn hash_api_key(key: &str) -> String {
+    Sha256::digest(key.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// The part of a key shown in listings
+pub fn api_key_prefix(key: &str) -> String {
+    key.chars().take(API_KEY_DISPLAY_LENGTH).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_and_hash_api_key() {
+        let key = generate_api_key();
+        assert!(key.starts_with(API_KEY_PREFIX));
+        assert_eq!(key.len(), API_KEY_PREFIX.len() + API_KEY_SECRET_LENGTH);
+        assert_ne!(key, generate_api_key());
+
+        let hash = hash_api_key(&key);
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, hash_api_key(&key));
+        assert_ne!(hash, hash_api_key(&generate_api_key()));
+        assert_eq!(api_key_prefix(&key), key[..API_KEY_DISPLAY_LENGTH]);
+    }
+
+    #[test]
+    fn test_validate_api_key_input() {
+        let mut input = CreateApiKeyInput {
+            label: "  Laptop ".to_string(),
+            email: Some("Alice@Example.com".to_string()),
+            ..Default::default()
+        };
+        assert!(input.validate().is_ok());
+        assert_eq!(input.label, "Laptop");
+        assert_eq!(input.email.as_deref(), Some("alice@example.com"));
+        assert_eq!(input.scope, ApiKeyScope::ReadWrite);
+        assert!(input.expires_at().is_none());
+
+        input.expires_in_days = Some(0);
+        assert!(input.validate().is_err());
+        input.expires_in_days = Some(30);
+        assert!(input.validate().is_ok());
+        assert!(input.expires_at().is_some());
+
+        input.label = " ".to_string();
+        assert!(input.validate().is_err());
+    }
+
+    #[test]
+    fn test_api_key_is_active() {
+        let mut key = ApiKey {
+            id: "k1".to_string(),
+            prefix: "rv_abcdefg".to_string(),
+            label: "Laptop".to_string(),
+            email: "alice@example.com".to_string(),
+            scope: ApiKeyScope::ReadOnly,
+            created_by: None,
+            created_at: "2026-01-01 00:00:00".to_string(),
+            expires_at: None,
+            last_used_at: None,
+            revoked_at: None,
+        };
+        assert!(key.is_active());
+
+        key.expires_at = Some("2026-01-02 00:00:00".to_string());
+        assert!(!key.is_active());
+
+        key.expires_at = None;
+        key.revoked_at = Some("2026-01-02 00:00:00".to_string());
+        assert!(!key.is_active());
+    }
+}
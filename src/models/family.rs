@@ -0,0 +1,279 @@
+use std::collections::BTreeMap;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::auth::normalize_email;
+
+/// A group of people who share one recipe collection
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Family {
+    /// Family key, e.g. `hewitt-family`
+    pub id: String,
+    pub name: String,
+    pub created_at: String,
+}
+
+/// What a family member may do with the family's recipes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum FamilyRole {
+    /// Everything, including managing members and invitations
+    Owner,
+    /// Add and change recipes; delete only the ones they created
+    #[default]
+    Editor,
+    /// Read only
+    Viewer,
+}
+
+impl FamilyRole {
+    pub fn can_edit(self) -> bool {
+        self != FamilyRole::Viewer
+    }
+
+    /// Whether this member may delete something created by `created_by`
+    pub fn can_delete(self, email: Option<&str>, created_by: Option<&str>) -> bool {
+        match self {
+            FamilyRole::Owner => true,
+            FamilyRole::Editor => email
+                .zip(created_by)
+                .is_some_and(|(email, created_by)| email.eq_ignore_ascii_case(created_by)),
+            FamilyRole::Viewer => false,
+        }
+    }
+}
+
+/// A family with its members, as listed by the admin API
+#[derive(Debug, Clone, Serialize)]
+pub struct FamilyWithMembers {
+    #[serde(flatten)]
+    pub family: Family,
+    pub members: Vec<String>,
+    /// Each member's role
+    pub roles: BTreeMap<String, FamilyRole>,
+}
+
+impl FamilyWithMembers {
+    pub fn owner_count(&self) -> usize {
+        self.roles.values().filter(|role| **role == FamilyRole::Owner).count()
+    }
+}
+
+/// A single-use link that adds whoever accepts it to a family
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct FamilyInvitation {
+    pub token: String,
+    pub family_id: String,
+    /// Only this address may accept; None for anyone with the link
+    pub email: Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: String,
+    pub expires_at: String,
+    pub accepted_by: Option<String>,
+    pub accepted_at: Option<String>,
+}
+
+impl FamilyInvitation {
+    /// Not yet accepted and not expired
+    pub fn is_open(&self) -> bool {
+        let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        self.accepted_at.is_none() && self.expires_at > now
+    }
+}
+
+pub const MAX_FAMILY_ID_LENGTH: usize = 64;
+pub const MAX_FAMILY_NAME_LENGTH: usize = 200;
+pub const MAX_EMAIL_LENGTH: usize = 254;
+pub const DEFAULT_INVITATION_DAYS: i64 = 7;
+pub const MAX_INVITATION_DAYS: i64 = 90;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateFamilyInput {
+    /// Family key: lowercase letters, digits, `-` and `_`
+    pub id: String,
+    /// Display name (defaults to the key)
+    #[serde(default)]
+    pub name: Option<String>,
+    /// First members; they become the family's owners
+    #[serde(default)]
+    pub members: Vec<String>,
+}
+
+impl CreateFamilyInput {
+    /// Check the input and normalize member emails
+    pub fn validate(&mut self) -> Result<(), String> {
+        validate_family_id(&self.id)?;
+        if let Some(name) = &self.name {
+            let name = name.trim();
+            if name.is_empty() || name.chars().count() > MAX_FAMILY_NAME_LENGTH {
+                return Err(format!("name must be 1 to {} characters", MAX_FAMILY_NAME_LENGTH));
+            }
+        }
+        self.members = self
+            .members
+            .iter()
+            .map(|email| validate_email(email))
+            .collect::<Result<_, _>>()?;
+        self.members.sort();
+        self.members.dedup();
+        Ok(())
+    }
+
+    pub fn display_name(&self) -> String {
+        self.name.as_deref().map(str::trim).unwrap_or(&self.id).to_string()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AddFamilyMemberInput {
+    pub email: String,
+    /// Role in the family (default editor)
+    #[serde(default)]
+    pub role: FamilyRole,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetFamilyRoleInput {
+    pub role: FamilyRole,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CreateInvitationInput {
+    /// Restrict the invitation to this address
+    #[serde(default)]
+    pub email: Option<String>,
+    /// Days until the invitation expires (default 7)
+    #[serde(default)]
+    pub expires_in_days: Option<i64>,
+}
+
+impl CreateInvitationInput {
+    /// Check the input and normalize the email
+    pub fn validate(&mut self) -> Result<(), String> {
+        if let Some(email) = &self.email {
+            self.email = Some(validate_email(email)?);
+        }
+        if let Some(days) = self.expires_in_days
+            && !(1..=MAX_INVITATION_DAYS).contains(&days)
+        {
+            return Err(format!("expires_in_days must be between 1 and {}", MAX_INVITATION_DAYS));
+        }
+        Ok(())
+    }
+
+    pub fn expires_at(&self) -> String {
+        let days = self.expires_in_days.unwrap_or(DEFAULT_INVITATION_DAYS);
+        (chrono::Utc::now() + chrono::Duration::days(days))
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string()
+    }
+}
+
+pub fn validate_family_id(id: &str) -> Result<(), String> {
+    let valid_chars = id
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if id.is_empty() || id.len() > MAX_FAMILY_ID_LENGTH || !valid_chars {
+        return Err(format!(
+            "Family id must be 1 to {} lowercase letters, digits, '-' or '_'",
+            MAX_FAMILY_ID_LENGTH
+        ));
+    }
+    Ok(())
+}
+
+/// Normalize an email address, rejecting anything that can't be one
+pub fn validate_email(email: &str) -> Result<String, String> {
+    let email = normalize_email(email);
+    let well_formed = email
+        .split_once('@')
+        .is_some_and(|(local, domain)| !local.is_empty() && !domain.is_empty());
+    if !well_formed || email.len() > MAX_EMAIL_LENGTH || email.chars().any(char::is_whitespace) {
+        return Err(format!("'{}' is not a valid email address", email));
+    }
+    Ok(email)
+}
+
+const INVITATION_TOKEN_LENGTH: usize = 24;
+const TOKEN_CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+/// Generate a random 24-character alphanumeric invitation token
+pub fn generate_invitation_token() -> String {
+    let mut rng = rand::thread_rng();
+    (0..INVITATION_TOKEN_LENGTH)
+        .map(|_| {
+            let idx = rng.gen_range(0..TOKEN_CHARS.len());
+            TOKEN_CHARS[idx] as char
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_family_input() {
+        let mut input = CreateFamilyInput {
+            id: "hewitt-family".to_string(),
+            name: None,
+            members: vec!["Alice@Example.com".to_string(), " alice@example.com".to_string()],
+        };
+        assert!(input.validate().is_ok());
+        assert_eq!(input.members, vec!["alice@example.com"]);
+        assert_eq!(input.display_name(), "hewitt-family");
+
+        for id in ["", "Hewitt", "hewitt family", "hewitt/family"] {
+            input.id = id.to_string();
+            assert!(input.validate().is_err(), "{}", id);
+        }
+    }
+
+    #[test]
+    fn test_family_role_permissions() {
+        let alice = Some("alice@example.com");
+        let bob = Some("bob@example.com");
+        assert!(FamilyRole::Owner.can_edit());
+        assert!(FamilyRole::Owner.can_delete(alice, bob));
+        assert!(FamilyRole::Editor.can_edit());
+        assert!(FamilyRole::Editor.can_delete(alice, alice));
+        assert!(FamilyRole::Editor.can_delete(alice, Some("Alice@Example.com")));
+        assert!(!FamilyRole::Editor.can_delete(alice, bob));
+        assert!(!FamilyRole::Editor.can_delete(None, None));
+        assert!(!FamilyRole::Viewer.can_edit());
+        assert!(!FamilyRole::Viewer.can_delete(alice, alice));
+    }
+
+    #[test]
+    fn test_validate_email() {
+        assert_eq!(validate_email(" Bob@Example.COM ").unwrap(), "bob@example.com");
+        assert!(validate_email("bob").is_err());
+        assert!(validate_email("@example.com").is_err());
+        assert!(validate_email("bob @example.com").is_err());
+    }
+
+    #[test]
+    fn test_invitation_is_open() {
+        let mut invitation = FamilyInvitation {
+            token: generate_invitation_token(),
+            family_id: "hewitt-family".to_string(),
+            email: None,
+            created_by: None,
+            created_at: "2026-01-01 00:00:00".to_string(),
+            expires_at: CreateInvitationInput::default().expires_at(),
+            accepted_by: None,
+            accepted_at: None,
+        };
+        assert_eq!(invitation.token.len(), INVITATION_TOKEN_LENGTH);
+        assert!(invitation.is_open());
+
+        invitation.accepted_at = Some("2026-01-02 00:00:00".to_string());
+        assert!(!invitation.is_open());
+
+        invitation.accepted_at = None;
+        invitation.expires_at = "2026-01-01 00:00:00".to_string();
+        assert!(!invitation.is_open());
+    }
+}
@@ -1,12 +1,20 @@
+pub mod api_key;
 pub mod recipe;
+pub mod family;
 pub mod ingredient;
+pub mod photo;
 pub mod share_link;
+pub mod shopping_list_link;
 pub mod step;
 
 pub use recipe::{
     Recipe, RecipeWithDetails, CreateRecipeInput, CreateIngredientInput,
     CreateStepInput, UpdateRecipeInput
 };
+pub use api_key::{ApiKey, ApiKeyScope};
+pub use family::{Family, FamilyInvitation, FamilyRole, FamilyWithMembers};
 pub use ingredient::RecipeIngredient;
-pub use share_link::ShareLink;
+pub use photo::RecipePhoto;
+pub use share_link::{ShareKind, ShareLink, SharedRecipe};
+pub use shopping_list_link::ShoppingListLink;
 pub use step::Step;
@@ -0,0 +1,89 @@
+use serde::{Deserialize, Deserializer, Serialize};
+
+pub const MAX_PHOTOS_PER_RECIPE: usize = 20;
+pub const MAX_CAPTION_LENGTH: usize = 500;
+
+/// One photo in a recipe's gallery
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct RecipePhoto {
+    pub id: String,
+    pub recipe_id: String,
+    pub filename: String,
+    pub position: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+    /// Position of the step this photo illustrates (matches `Step.position`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step_position: Option<i32>,
+    /// Whether this is the recipe's cover photo (`Recipe.photo_filename`)
+    #[serde(default)]
+    pub is_cover: bool,
+    /// The card or page the recipe was transcribed from, kept apart from the gallery
+    #[serde(default)]
+    pub source_scan: bool,
+    pub created_at: String,
+}
+
+/// Input for changing a photo's caption, step link or cover status.
+/// Fields left out are unchanged; `null` clears a caption or step link.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UpdatePhotoInput {
+    #[serde(default, deserialize_with = "present")]
+    pub caption: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub step_position: Option<Option<i32>>,
+    /// Make this photo the cover
+    #[serde(default)]
+    pub cover: bool,
+}
+
+/// Input for reordering a recipe's photos
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReorderPhotosInput {
+    /// Every photo ID of the recipe, in the new order
+    pub photo_ids: Vec<String>,
+}
+
+/// Distinguish a field set to `null` (Some(None)) from one left out (None)
+fn present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// Trim a caption, treating blank as none
+pub fn normalize_caption(caption: Option<&str>) -> Result<Option<String>, String> {
+    let Some(caption) = caption.map(str::trim).filter(|c| !c.is_empty()) else {
+        return Ok(None);
+    };
+    if caption.chars().count() > MAX_CAPTION_LENGTH {
+        return Err(format!("Captions can be at most {} characters", MAX_CAPTION_LENGTH));
+    }
+    Ok(Some(caption.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_input_distinguishes_null_from_missing() {
+        let input: UpdatePhotoInput = serde_json::from_str(r#"{"caption": null}"#).unwrap();
+        assert_eq!(input.caption, Some(None));
+        assert_eq!(input.step_position, None);
+        assert!(!input.cover);
+
+        let input: UpdatePhotoInput = serde_json::from_str(r#"{"step_position": 2, "cover": true}"#).unwrap();
+        assert_eq!(input.step_position, Some(Some(2)));
+        assert!(input.cover);
+    }
+
+    #[test]
+    fn test_normalize_caption() {
+        assert_eq!(normalize_caption(Some("  Golden crust ")).unwrap().as_deref(), Some("Golden crust"));
+        assert_eq!(normalize_caption(Some("   ")).unwrap(), None);
+        assert!(normalize_caption(Some(&"x".repeat(MAX_CAPTION_LENGTH + 1))).is_err());
+    }
+}
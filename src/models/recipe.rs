@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use super::{RecipeIngredient, Step};
+use super::{RecipeIngredient, RecipePhoto, Step};
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Recipe {
@@ -17,16 +17,29 @@ pub struct Recipe {
     pub servings: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub difficulty: Option<i32>,
+    /// The cover photo, one of the recipe's gallery photos
     #[serde(skip_serializing_if = "Option::is_none")]
     pub photo_filename: Option<String>,
+    /// Original location of an imported recipe
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<String>,
+    /// Who shared a recipe saved from a share link
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shared_by: Option<String>,
     pub created_at: String,
     pub updated_at: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub created_by: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub updated_by: Option<String>,
+    /// The family the recipe belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub family_id: Option<String>,
 }
 
+pub const MAX_TAGS: usize = 30;
+pub const MAX_TAG_LENGTH: usize = 50;
+
 /// Full recipe with ingredients and steps
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RecipeWithDetails {
@@ -34,10 +47,19 @@ pub struct RecipeWithDetails {
     pub recipe: Recipe,
     pub ingredients: Vec<RecipeIngredient>,
     pub steps: Vec<Step>,
+    /// Sorted alphabetically
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Gallery order; the cover is also named by `recipe.photo_filename`
+    #[serde(default)]
+    pub photos: Vec<RecipePhoto>,
+    /// Scans of the original the recipe was transcribed from, never the cover
+    #[serde(default)]
+    pub source_scans: Vec<RecipePhoto>,
 }
 
 /// Input for creating a recipe
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CreateRecipeInput {
     pub title: String,
     #[serde(default)]
@@ -54,9 +76,15 @@ pub struct CreateRecipeInput {
     pub ingredients: Vec<CreateIngredientInput>,
     #[serde(default)]
     pub steps: Vec<CreateStepInput>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shared_by: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct CreateIngredientInput {
     pub name: String,
     #[serde(default)]
@@ -67,7 +95,7 @@ pub struct CreateIngredientInput {
     pub notes: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct CreateStepInput {
     pub instruction: String,
     #[serde(default)]
@@ -79,7 +107,7 @@ pub struct CreateStepInput {
 }
 
 /// Input for updating a recipe
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct UpdateRecipeInput {
     #[serde(default)]
     pub title: Option<String>,
@@ -97,6 +125,9 @@ pub struct UpdateRecipeInput {
     pub ingredients: Option<Vec<CreateIngredientInput>>,
     #[serde(default)]
     pub steps: Option<Vec<CreateStepInput>>,
+    /// Replaces all tags when provided
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
 }
 
 impl Recipe {
@@ -116,6 +147,43 @@ impl Recipe {
     }
 }
 
+impl From<&RecipeWithDetails> for CreateRecipeInput {
+    /// The input that would recreate this recipe
+    fn from(recipe: &RecipeWithDetails) -> Self {
+        CreateRecipeInput {
+            title: recipe.recipe.title.clone(),
+            description: recipe.recipe.description.clone(),
+            prep_time_minutes: recipe.recipe.prep_time_minutes,
+            cook_time_minutes: recipe.recipe.cook_time_minutes,
+            servings: recipe.recipe.servings,
+            difficulty: recipe.recipe.difficulty,
+            ingredients: recipe
+                .ingredients
+                .iter()
+                .map(|i| CreateIngredientInput {
+                    name: i.name.clone(),
+                    quantity: i.quantity,
+                    unit: i.unit.clone(),
+                    notes: i.notes.clone(),
+                })
+                .collect(),
+            steps: recipe
+                .steps
+                .iter()
+                .map(|s| CreateStepInput {
+                    instruction: s.instruction.clone(),
+                    duration_minutes: s.duration_minutes,
+                    temperature_value: s.temperature_value,
+                    temperature_unit: s.temperature_unit.clone(),
+                })
+                .collect(),
+            tags: recipe.tags.clone(),
+            source_url: recipe.recipe.source_url.clone(),
+            shared_by: recipe.recipe.shared_by.clone(),
+        }
+    }
+}
+
 impl CreateRecipeInput {
     /// Validate the input
     pub fn validate(&self) -> Result<(), String> {
@@ -129,6 +197,61 @@ impl CreateRecipeInput {
             && desc.len() > 2000 {
                 return Err("Description exceeds maximum length of 2000 characters".to_string());
             }
-        Ok(())
+        if let Some(url) = &self.source_url
+            && url.len() > 2000 {
+                return Err("Source URL exceeds maximum length of 2000 characters".to_string());
+            }
+        if let Some(shared_by) = &self.shared_by
+            && shared_by.len() > 200 {
+                return Err("shared_by exceeds maximum length of 200 characters".to_string());
+            }
+        validate_tags(&self.tags)
+    }
+}
+
+/// Check tag count and length
+pub fn validate_tags(tags: &[String]) -> Result<(), String> {
+    if tags.len() > MAX_TAGS {
+        return Err(format!("A recipe can have at most {} tags", MAX_TAGS));
+    }
+    if let Some(tag) = tags.iter().find(|t| t.trim().len() > MAX_TAG_LENGTH) {
+        return Err(format!("Tag '{}' exceeds maximum length of {} characters", tag.trim(), MAX_TAG_LENGTH));
+    }
+    Ok(())
+}
+
+/// Trim tags and collapse inner whitespace, dropping empty ones and
+/// case-insensitive duplicates (the first spelling wins)
+pub fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !tag.is_empty() && !normalized.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+            normalized.push(tag);
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_tags() {
+        let tags = vec![
+            " Weeknight  dinner ".to_string(),
+            "weeknight dinner".to_string(),
+            "".to_string(),
+            "Vegan".to_string(),
+        ];
+        assert_eq!(normalize_tags(&tags), vec!["Weeknight dinner", "Vegan"]);
+    }
+
+    #[test]
+    fn test_validate_tags() {
+        assert!(validate_tags(&["ok".to_string()]).is_ok());
+        assert!(validate_tags(&["x".repeat(MAX_TAG_LENGTH + 1)]).is_err());
+        assert!(validate_tags(&vec!["t".to_string(); MAX_TAGS + 1]).is_err());
     }
 }
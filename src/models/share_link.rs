@@ -1,13 +1,151 @@
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 
+/// What a share link points at
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum ShareKind {
+    /// A single recipe (`recipe_id`)
+    #[default]
+    Recipe,
+    /// The recipes that had a tag when the link was created
+    Tag,
+    /// A hand-picked list of recipes
+    List,
+    /// A meal plan, with a role for each recipe
+    MealPlan,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct ShareLink {
     pub token: String,
-    pub recipe_id: String,
+    /// The shared recipe; None for collections, whose recipes are listed separately
+    pub recipe_id: Option<String>,
+    #[serde(default)]
+    pub kind: ShareKind,
+    /// Collection or meal plan title
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guest_count: Option<i32>,
     pub created_by: String,
+    /// The family whose recipes the link shares
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub family_id: Option<String>,
     pub created_at: String,
-    pub expires_at: String,
+    /// None for a link that never expires
+    pub expires_at: Option<String>,
+    /// Number of page views after which the link stops working
+    #[serde(default)]
+    pub max_views: Option<i64>,
+    #[serde(default)]
+    pub view_count: i64,
+    #[serde(default)]
+    pub last_accessed_at: Option<String>,
+    /// Argon2 hash of the passphrase visitors must give
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passphrase_hash: Option<String>,
+    /// The page is shown once; the link then expires after a grace period
+    #[serde(default)]
+    pub single_use: bool,
+}
+
+impl ShareLink {
+    pub fn is_collection(&self) -> bool {
+        self.kind != ShareKind::Recipe
+    }
+
+    /// Whether the link has been opened as often as it allows
+    pub fn views_used_up(&self) -> bool {
+        self.max_views.is_some_and(|max| self.view_count >= max) || (self.single_use && self.view_count > 0)
+    }
+
+    pub fn is_protected(&self) -> bool {
+        self.passphrase_hash.is_some()
+    }
+}
+
+pub const DEFAULT_EXPIRY_DAYS: i64 = 30;
+pub const MAX_EXPIRY_DAYS: i64 = 3650;
+pub const MAX_COLLECTION_RECIPES: usize = 100;
+pub const MAX_COLLECTION_TITLE_LENGTH: usize = 200;
+pub const MIN_PASSPHRASE_LENGTH: usize = 4;
+pub const MAX_PASSPHRASE_LENGTH: usize = 200;
+/// Minutes a single-use or used-up link keeps serving photos and collection
+/// recipes after its last page view
+pub const SINGLE_USE_GRACE_MINUTES: i64 = 10;
+/// Wrong passphrases allowed per link within the window before guesses are refused
+pub const MAX_PASSPHRASE_ATTEMPTS: i64 = 5;
+pub const PASSPHRASE_ATTEMPT_WINDOW_MINUTES: i64 = 15;
+
+/// Options for a new share link. Without a body a link expires after 30 days
+/// and has no view limit.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CreateShareLinkInput {
+    /// Days until the link expires (default 30)
+    #[serde(default)]
+    pub expires_in_days: Option<i64>,
+    /// Keep the link working until it is revoked
+    #[serde(default)]
+    pub never_expires: bool,
+    /// Stop serving the page after this many views
+    #[serde(default)]
+    pub max_views: Option<i64>,
+    /// Ask visitors for this passphrase before showing the page
+    #[serde(default)]
+    pub passphrase: Option<String>,
+    /// Show the page once, then expire the link
+    #[serde(default)]
+    pub single_use: bool,
+}
+
+impl CreateShareLinkInput {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.never_expires && self.expires_in_days.is_some() {
+            return Err("Use either expires_in_days or never_expires, not both".to_string());
+        }
+        if let Some(days) = self.expires_in_days
+            && !(1..=MAX_EXPIRY_DAYS).contains(&days)
+        {
+            return Err(format!("expires_in_days must be between 1 and {}", MAX_EXPIRY_DAYS));
+        }
+        if let Some(max_views) = self.max_views
+            && max_views < 1
+        {
+            return Err("max_views must be at least 1".to_string());
+        }
+        if self.single_use && self.max_views.is_some() {
+            return Err("Use either max_views or single_use, not both".to_string());
+        }
+        if let Some(passphrase) = &self.passphrase {
+            let length = passphrase.chars().count();
+            if !(MIN_PASSPHRASE_LENGTH..=MAX_PASSPHRASE_LENGTH).contains(&length) {
+                return Err(format!(
+                    "Passphrases must be {} to {} characters",
+                    MIN_PASSPHRASE_LENGTH, MAX_PASSPHRASE_LENGTH
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The expiry timestamp for a link created now, or None if it never expires
+    pub fn expires_at(&self) -> Option<String> {
+        if self.never_expires {
+            return None;
+        }
+        let days = self.expires_in_days.unwrap_or(DEFAULT_EXPIRY_DAYS);
+        Some(
+            (chrono::Utc::now() + chrono::Duration::days(days))
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string(),
+        )
+    }
 }
 
 const TOKEN_LENGTH: usize = 10;
@@ -24,6 +162,98 @@ pub fn generate_share_token() -> String {
         .collect()
 }
 
+/// Hash a share link passphrase for storage
+pub fn hash_passphrase(passphrase: &str) -> Result<String, String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(passphrase.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| format!("Failed to hash passphrase: {}", e))
+}
+
+/// Check a passphrase against a stored hash; unreadable hashes never match
+pub fn verify_passphrase(passphrase: &str, hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .is_ok_and(|parsed| Argon2::default().verify_password(passphrase.as_bytes(), &parsed).is_ok())
+}
+
+/// A recipe in a shared collection, in page order
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct SharedRecipe {
+    pub recipe_id: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    pub has_photo: bool,
+}
+
+/// One recipe of a meal plan to share
+#[derive(Debug, Clone, Deserialize)]
+pub struct MealPlanRecipeInput {
+    pub recipe_id: String,
+    #[serde(default)]
+    pub role: Option<String>,
+}
+
+/// A meal plan to share, as shown by the chat's meal plan panel
+#[derive(Debug, Clone, Deserialize)]
+pub struct MealPlanInput {
+    #[serde(default)]
+    pub guest_count: Option<i32>,
+    pub recipes: Vec<MealPlanRecipeInput>,
+}
+
+/// Input for sharing several recipes under one link. Exactly one of `tag`,
+/// `recipe_ids` or `meal_plan` picks the recipes; a tag is resolved to the
+/// recipes carrying it when the link is created.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CreateCollectionShareInput {
+    /// Page title; defaults to the tag for tag collections
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub recipe_ids: Option<Vec<String>>,
+    #[serde(default)]
+    pub meal_plan: Option<MealPlanInput>,
+    #[serde(flatten)]
+    pub options: CreateShareLinkInput,
+}
+
+impl CreateCollectionShareInput {
+    /// The kind of collection and its title. The recipes themselves are
+    /// checked by the caller.
+    pub fn validate(&self) -> Result<(ShareKind, String), String> {
+        self.options.validate()?;
+
+        let kind = match (&self.tag, &self.recipe_ids, &self.meal_plan) {
+            (Some(_), None, None) => ShareKind::Tag,
+            (None, Some(_), None) => ShareKind::List,
+            (None, None, Some(_)) => ShareKind::MealPlan,
+            _ => return Err("Give exactly one of tag, recipe_ids or meal_plan".to_string()),
+        };
+
+        let title = self
+            .title
+            .as_deref()
+            .or(self.tag.as_deref())
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .ok_or_else(|| "A title is required".to_string())?;
+        if title.chars().count() > MAX_COLLECTION_TITLE_LENGTH {
+            return Err(format!("Titles can be at most {} characters", MAX_COLLECTION_TITLE_LENGTH));
+        }
+
+        if let Some(meal_plan) = &self.meal_plan
+            && meal_plan.guest_count.is_some_and(|count| count < 1)
+        {
+            return Err("guest_count must be at least 1".to_string());
+        }
+        Ok((kind, title.to_string()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -40,6 +270,85 @@ mod tests {
         assert!(token.chars().all(|c| c.is_ascii_alphanumeric()));
     }
 
+    #[test]
+    fn test_create_input_expiry() {
+        let default = CreateShareLinkInput::default();
+        assert!(default.validate().is_ok());
+        assert!(default.expires_at().is_some());
+
+        let forever = CreateShareLinkInput { never_expires: true, ..Default::default() };
+        assert_eq!(forever.expires_at(), None);
+
+        let both = CreateShareLinkInput { never_expires: true, expires_in_days: Some(7), ..Default::default() };
+        assert!(both.validate().is_err());
+        let too_long = CreateShareLinkInput { expires_in_days: Some(MAX_EXPIRY_DAYS + 1), ..Default::default() };
+        assert!(too_long.validate().is_err());
+        let no_views = CreateShareLinkInput { max_views: Some(0), ..Default::default() };
+        assert!(no_views.validate().is_err());
+    }
+
+    #[test]
+    fn test_collection_input_picks_one_source() {
+        let input: CreateCollectionShareInput =
+            serde_json::from_str(r#"{"tag": " dessert ", "expires_in_days": 7}"#).unwrap();
+        assert_eq!(input.validate().unwrap(), (ShareKind::Tag, "dessert".to_string()));
+        assert_eq!(input.options.expires_in_days, Some(7));
+
+        let input: CreateCollectionShareInput = serde_json::from_str(
+            r#"{"title": "Sunday Roast", "meal_plan": {"guest_count": 6, "recipes": [{"recipe_id": "a", "role": "side"}]}}"#,
+        )
+        .unwrap();
+        assert_eq!(input.validate().unwrap().0, ShareKind::MealPlan);
+
+        let untitled: CreateCollectionShareInput = serde_json::from_str(r#"{"recipe_ids": ["a"]}"#).unwrap();
+        assert!(untitled.validate().is_err());
+        let both: CreateCollectionShareInput =
+            serde_json::from_str(r#"{"tag": "x", "recipe_ids": ["a"]}"#).unwrap();
+        assert!(both.validate().is_err());
+        assert!(CreateCollectionShareInput::default().validate().is_err());
+    }
+
+    #[test]
+    fn test_views_used_up() {
+        let mut link = ShareLink {
+            token: "t".to_string(),
+            recipe_id: Some("r".to_string()),
+            kind: ShareKind::Recipe,
+            title: None,
+            guest_count: None,
+            passphrase_hash: None,
+            single_use: false,
+            created_by: "cook@example.com".to_string(),
+            family_id: None,
+            created_at: String::new(),
+            expires_at: None,
+            max_views: Some(2),
+            view_count: 1,
+            last_accessed_at: None,
+        };
+        assert!(!link.views_used_up());
+        link.view_count = 2;
+        assert!(link.views_used_up());
+        link.max_views = None;
+        assert!(!link.views_used_up());
+        link.single_use = true;
+        assert!(link.views_used_up());
+    }
+
+    #[test]
+    fn test_passphrase_hashing() {
+        let hash = hash_passphrase("open sesame").unwrap();
+        assert!(!hash.contains("open sesame"));
+        assert!(verify_passphrase("open sesame", &hash));
+        assert!(!verify_passphrase("open sesame!", &hash));
+        assert!(!verify_passphrase("open sesame", "not a hash"));
+
+        let input: CreateShareLinkInput = serde_json::from_str(r#"{"passphrase": "abc"}"#).unwrap();
+        assert!(input.validate().is_err());
+        let input: CreateShareLinkInput = serde_json::from_str(r#"{"single_use": true, "max_views": 3}"#).unwrap();
+        assert!(input.validate().is_err());
+    }
+
     #[test]
     fn test_generate_share_token_unique() {
         let t1 = generate_share_token();
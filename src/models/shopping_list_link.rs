@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+/// A public link to a shopping list for a fixed set of recipes
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ShoppingListLink {
+    pub token: String,
+    /// JSON array of recipe IDs
+    pub recipe_ids: String,
+    /// Family whose aisle order is used when rendering. None for god mode links.
+    pub family_id: Option<String>,
+    pub created_by: String,
+    pub created_at: String,
+    pub expires_at: String,
+}
+
+impl ShoppingListLink {
+    /// Decode the stored recipe ID list
+    pub fn recipe_ids(&self) -> Vec<String> {
+        serde_json::from_str(&self.recipe_ids).unwrap_or_default()
+    }
+}
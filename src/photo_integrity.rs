@@ -0,0 +1,231 @@
+//! Reconciling photo storage with the `recipe_photos` table
+//!
+//! Files and rows drift apart: a photo file that can't be removed outlives
+//! its row, a crash mid-write leaves a `.tmp` file behind, and a database
+//! restored without its photos names files that don't exist. [`check`]
+//! reports the drift, [`repair`] fixes it, and [`spawn_scheduled_repair`]
+//! runs the repair periodically.
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use crate::{
+    db::queries,
+    error::{ApiError, ApiResult},
+    photo_store::PhotoStore,
+    photos::{self, PhotoSize},
+};
+
+/// Files younger than this are left alone, so an upload whose file has been
+/// written but whose row hasn't been inserted yet is not mistaken for an orphan
+pub const DEFAULT_GRACE_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+/// A stored file that no photo row accounts for
+#[derive(Debug, Clone, Serialize)]
+pub struct StrayFile {
+    pub filename: String,
+    pub bytes: u64,
+    pub modified_at: Option<String>,
+}
+
+/// A photo row whose file is not in photo storage
+#[derive(Debug, Clone, Serialize)]
+pub struct MissingFile {
+    pub recipe_id: String,
+    pub photo_id: String,
+    pub filename: String,
+    pub is_cover: bool,
+}
+
+/// What a repair changed
+#[derive(Debug, Default, Serialize)]
+pub struct RepairSummary {
+    pub orphan_files_removed: usize,
+    pub temp_files_removed: usize,
+    pub photo_rows_removed: usize,
+    pub covers_fixed: u64,
+    /// Problems that were found but could not be fixed
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct IntegrityReport {
+    pub files_scanned: usize,
+    pub photos_referenced: usize,
+    /// Originals and derivatives with no photo row
+    pub orphan_files: Vec<StrayFile>,
+    pub missing_files: Vec<MissingFile>,
+    /// `.tmp` files left by interrupted writes
+    pub stale_temp_files: Vec<StrayFile>,
+    /// Recipes whose cover names none of their photos
+    pub dangling_covers: Vec<String>,
+    /// Present when the report came from [`repair`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repaired: Option<RepairSummary>,
+}
+
+impl IntegrityReport {
+    pub fn is_clean(&self) -> bool {
+        self.orphan_files.is_empty()
+            && self.missing_files.is_empty()
+            && self.stale_temp_files.is_empty()
+            && self.dangling_covers.is_empty()
+    }
+}
+
+/// Compare photo storage with the database without changing either
+pub async fn check(pool: &SqlitePool, store: &dyn PhotoStore, grace: Duration) -> ApiResult<IntegrityReport> {
+    let rows = queries::list_all_recipe_photos(pool).await?;
+
+    // Every file a row accounts for: the original and its derivatives
+    let mut known = HashSet::new();
+    for row in &rows {
+        known.insert(row.filename.clone());
+        for size in PhotoSize::DERIVED {
+            known.insert(photos::derivative_filename(&row.filename, size));
+        }
+    }
+
+    let mut report = IntegrityReport {
+        photos_referenced: rows.len(),
+        ..Default::default()
+    };
+    let mut present = HashSet::new();
+    let now = SystemTime::now();
+
+    for object in store.list().await? {
+        report.files_scanned += 1;
+
+        let filename = object.key;
+        let settled = object
+            .modified
+            .and_then(|m| now.duration_since(m).ok())
+            .is_some_and(|age| age >= grace);
+        let stray = StrayFile {
+            filename: filename.clone(),
+            bytes: object.size,
+            modified_at: object.modified.map(|m| chrono::DateTime::<chrono::Utc>::from(m).format("%Y-%m-%d %H:%M:%S").to_string()),
+        };
+
+        if filename.ends_with(".tmp") {
+            if settled {
+                report.stale_temp_files.push(stray);
+            }
+        } else if !known.contains(&filename) && settled {
+            report.orphan_files.push(stray);
+        }
+        present.insert(filename);
+    }
+
+    report.missing_files = rows
+        .into_iter()
+        .filter(|row| !present.contains(&row.filename))
+        .map(|row| MissingFile {
+            recipe_id: row.recipe_id,
+            photo_id: row.id,
+            filename: row.filename,
+            is_cover: row.is_cover,
+        })
+        .collect();
+    report.dangling_covers = queries::list_dangling_covers(pool).await?;
+
+    report.orphan_files.sort_by(|a, b| a.filename.cmp(&b.filename));
+    report.stale_temp_files.sort_by(|a, b| a.filename.cmp(&b.filename));
+    Ok(report)
+}
+
+/// Check, then fix what was found: delete orphan and stale temp files, drop
+/// rows whose file is gone (promoting another cover where needed), and point
+/// dangling covers at a remaining photo
+pub async fn repair(pool: &SqlitePool, store: &dyn PhotoStore, grace: Duration) -> ApiResult<IntegrityReport> {
+    let mut report = check(pool, store, grace).await?;
+    let mut summary = RepairSummary::default();
+
+    for file in &report.orphan_files {
+        match remove_file(store, &file.filename).await {
+            Ok(()) => summary.orphan_files_removed += 1,
+            Err(e) => summary.errors.push(e),
+        }
+    }
+    for file in &report.stale_temp_files {
+        match remove_file(store, &file.filename).await {
+            Ok(()) => summary.temp_files_removed += 1,
+            Err(e) => summary.errors.push(e),
+        }
+    }
+
+    // Empty or unmounted storage would otherwise wipe every row
+    if report.files_scanned == 0 && !report.missing_files.is_empty() {
+        summary.errors.push(format!(
+            "Photo storage {} is empty; not removing {} photo rows. Check that it is mounted.",
+            store.location(),
+            report.missing_files.len()
+        ));
+    } else {
+        for missing in &report.missing_files {
+            match remove_missing_photo(pool, store, missing).await {
+                Ok(true) => summary.photo_rows_removed += 1,
+                Ok(false) => {}
+                Err(e) => summary.errors.push(format!("Failed to remove photo {}: {}", missing.photo_id, e)),
+            }
+        }
+    }
+
+    summary.covers_fixed = queries::repair_dangling_covers(pool).await?;
+    report.repaired = Some(summary);
+    Ok(report)
+}
+
+/// Drop the row of a photo whose file is gone. The row is re-read first: if
+/// it now names another file (the photo was just replaced) or the file has
+/// appeared, it is left alone.
+async fn remove_missing_photo(pool: &SqlitePool, store: &dyn PhotoStore, missing: &MissingFile) -> ApiResult<bool> {
+    let current = match queries::get_recipe_photo(pool, &missing.recipe_id, &missing.photo_id).await {
+        Ok(photo) => photo,
+        // Already gone, e.g. the recipe was deleted meanwhile
+        Err(ApiError::NotFound(_)) => return Ok(false),
+        Err(e) => return Err(e),
+    };
+    if current.filename != missing.filename || store.exists(&current.filename).await.unwrap_or(true) {
+        return Ok(false);
+    }
+
+    queries::delete_recipe_photo(pool, &missing.recipe_id, &missing.photo_id).await?;
+    photos::remove_derivatives(store, &missing.filename).await;
+    Ok(true)
+}
+
+async fn remove_file(store: &dyn PhotoStore, filename: &str) -> Result<(), String> {
+    store.delete(filename).await.map_err(|e| format!("Failed to delete {}: {}", filename, e))
+}
+
+/// Run [`repair`] every `every`, starting one interval after startup
+pub fn spawn_scheduled_repair(pool: SqlitePool, store: Arc<dyn PhotoStore>, every: Duration) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + every, every);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            match repair(&pool, store.as_ref(), DEFAULT_GRACE_PERIOD).await {
+                Ok(report) if report.is_clean() => tracing::debug!("Photo check: no problems found"),
+                Ok(report) => {
+                    let summary = report.repaired.unwrap_or_default();
+                    tracing::info!(
+                        "Photo check: removed {} orphan and {} temp files, {} photo rows without files; fixed {} covers",
+                        summary.orphan_files_removed,
+                        summary.temp_files_removed,
+                        summary.photo_rows_removed,
+                        summary.covers_fixed
+                    );
+                    for error in summary.errors {
+                        tracing::warn!("Photo check: {}", error);
+                    }
+                }
+                Err(e) => tracing::error!("Photo check failed: {}", e),
+            }
+        }
+    });
+}
@@ -0,0 +1,183 @@
+//! Where photo files live
+//!
+//! Every photo read, write and delete goes through [`PhotoStore`]. Keys are
+//! the photo filenames (`{photo_id}.{ext}` and their derivatives) in a single
+//! flat namespace. [`LocalPhotoStore`] keeps them in the photos directory;
+//! [`S3PhotoStore`] keeps them in an S3-compatible bucket (AWS, MinIO, R2...).
+
+mod s3;
+
+pub use s3::S3PhotoStore;
+
+use async_trait::async_trait;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use crate::{
+    config::{Config, PhotoStorage},
+    error::{ApiError, ApiResult},
+};
+
+/// An object in a photo store
+#[derive(Debug, Clone)]
+pub struct StoredObject {
+    pub key: String,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+#[async_trait]
+pub trait PhotoStore: Send + Sync {
+    /// The object's bytes, or None if there is no such object
+    async fn get(&self, key: &str) -> ApiResult<Option<Vec<u8>>>;
+
+    /// Create or replace an object. Readers see the old or the new bytes,
+    /// never a partial write.
+    async fn put(&self, key: &str, bytes: &[u8]) -> ApiResult<()>;
+
+    /// Delete an object; deleting one that doesn't exist is not an error
+    async fn delete(&self, key: &str) -> ApiResult<()>;
+
+    async fn exists(&self, key: &str) -> ApiResult<bool>;
+
+    /// Every object in the store
+    async fn list(&self) -> ApiResult<Vec<StoredObject>>;
+
+    /// Where the photos are kept, for logs and error messages
+    fn location(&self) -> String;
+}
+
+/// Build the store selected by `PHOTO_STORAGE`
+pub fn from_config(config: &Config) -> ApiResult<Arc<dyn PhotoStore>> {
+    match &config.photo_storage {
+        PhotoStorage::Local => Ok(Arc::new(LocalPhotoStore::new(&config.photos_dir))),
+        PhotoStorage::S3(s3) => Ok(Arc::new(S3PhotoStore::new(s3.clone())?)),
+    }
+}
+
+/// Photos as files in a directory
+#[derive(Debug, Clone)]
+pub struct LocalPhotoStore {
+    dir: PathBuf,
+}
+
+impl LocalPhotoStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(key)
+    }
+}
+
+#[async_trait]
+impl PhotoStore for LocalPhotoStore {
+    async fn get(&self, key: &str) -> ApiResult<Option<Vec<u8>>> {
+        let path = self.path(key);
+        match tokio::fs::read(&path).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(ApiError::FileSystemError(format!(
+                "Failed to read photo file {}: {}",
+                path.display(),
+                e
+            ))),
+        }
+    }
+
+    /// Written to a temp file, then renamed into place
+    async fn put(&self, key: &str, bytes: &[u8]) -> ApiResult<()> {
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .map_err(|e| ApiError::FileSystemError(format!("Failed to create photos directory: {}", e)))?;
+
+        let photo_path = self.path(key);
+        let temp_path = self.path(&format!("{}.tmp", key));
+        tokio::fs::write(&temp_path, bytes)
+            .await
+            .map_err(|e| ApiError::FileSystemError(format!("Failed to write photo file: {}", e)))?;
+        tokio::fs::rename(&temp_path, &photo_path).await.map_err(|e| {
+            let _ = std::fs::remove_file(&temp_path);
+            ApiError::FileSystemError(format!("Failed to save photo file: {}", e))
+        })?;
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> ApiResult<()> {
+        let path = self.path(key);
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(ApiError::FileSystemError(format!(
+                "Failed to delete {}: {}",
+                path.display(),
+                e
+            ))),
+        }
+    }
+
+    async fn exists(&self, key: &str) -> ApiResult<bool> {
+        tokio::fs::try_exists(self.path(key))
+            .await
+            .map_err(|e| ApiError::FileSystemError(format!("Failed to check photo file {}: {}", key, e)))
+    }
+
+    /// Regular, non-hidden files; none if the directory doesn't exist
+    async fn list(&self) -> ApiResult<Vec<StoredObject>> {
+        let read_error = |e: std::io::Error| {
+            ApiError::FileSystemError(format!("Failed to read photos directory {}: {}", self.dir.display(), e))
+        };
+        let mut entries = match tokio::fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(read_error(e)),
+        };
+
+        let mut objects = Vec::new();
+        while let Some(entry) = entries.next_entry().await.map_err(read_error)? {
+            let Ok(metadata) = entry.metadata().await else { continue };
+            let Some(key) = entry.file_name().to_str().map(String::from) else { continue };
+            if metadata.is_file() && !key.starts_with('.') {
+                objects.push(StoredObject {
+                    key,
+                    size: metadata.len(),
+                    modified: metadata.modified().ok(),
+                });
+            }
+        }
+        Ok(objects)
+    }
+
+    fn location(&self) -> String {
+        self.dir.display().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_local_store_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = LocalPhotoStore::new(dir.path().join("photos"));
+
+        assert_eq!(store.get("a.jpg").await.unwrap(), None);
+        assert!(store.list().await.unwrap().is_empty());
+
+        store.put("a.jpg", b"one").await.unwrap();
+        store.put("a.jpg", b"two").await.unwrap();
+        assert_eq!(store.get("a.jpg").await.unwrap().as_deref(), Some(&b"two"[..]));
+        assert!(store.exists("a.jpg").await.unwrap());
+
+        let listed = store.list().await.unwrap();
+        assert_eq!(listed.len(), 1, "temp file left behind");
+        assert_eq!((listed[0].key.as_str(), listed[0].size), ("a.jpg", 3));
+
+        store.delete("a.jpg").await.unwrap();
+        store.delete("a.jpg").await.unwrap();
+        assert!(!store.exists("a.jpg").await.unwrap());
+    }
+}
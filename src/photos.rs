@@ -0,0 +1,413 @@
+//! Recipe photo files
+//!
+//! Files are kept in a [`PhotoStore`]. Alongside each original upload we keep smaller JPEG derivatives
+//! (`{stem}.thumb.jpg`, `{stem}.medium.jpg`) for list views and phones.
+//! Uploads are identified by their content and re-encoded before they are
+//! stored, so EXIF, GPS and other metadata never reach photo storage.
+
+use image::{
+    codecs::{
+        gif::{GifDecoder, GifEncoder, Repeat},
+        jpeg::JpegEncoder,
+        png::PngEncoder,
+        webp::WebPEncoder,
+    },
+    imageops::FilterType,
+    metadata::Orientation,
+    AnimationDecoder, DynamicImage, GenericImageView, ImageDecoder, ImageFormat, ImageReader, Limits, Rgb,
+    RgbImage,
+};
+use std::io::Cursor;
+use std::path::Path;
+
+use crate::{
+    error::{ApiError, ApiResult},
+    photo_store::PhotoStore,
+};
+
+/// Photo file extensions the app serves
+pub const PHOTO_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp", "gif"];
+
+/// JPEG quality for derivatives
+const DERIVATIVE_QUALITY: u8 = 82;
+
+/// JPEG quality when re-encoding an uploaded JPEG
+const UPLOAD_QUALITY: u8 = 90;
+
+/// Largest width or height accepted for an upload
+const MAX_UPLOAD_DIMENSION: u32 = 12_000;
+
+/// A rendition of a recipe photo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhotoSize {
+    /// Fits in 320px, for lists and the recipe index
+    Thumb,
+    /// Fits in 1280px, for the recipe view
+    Medium,
+    /// The original upload
+    Full,
+}
+
+impl PhotoSize {
+    pub const DERIVED: [PhotoSize; 2] = [PhotoSize::Thumb, PhotoSize::Medium];
+
+    pub fn parse(value: &str) -> ApiResult<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "thumb" | "thumbnail" => Ok(Self::Thumb),
+            "medium" => Ok(Self::Medium),
+            "full" | "original" => Ok(Self::Full),
+            other => Err(ApiError::Validation(format!(
+                "Unknown photo size '{}'. Supported sizes: thumb, medium, full",
+                other
+            ))),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Thumb => "thumb",
+            Self::Medium => "medium",
+            Self::Full => "full",
+        }
+    }
+
+    /// Longest edge in pixels, or None for the original
+    fn max_dimension(self) -> Option<u32> {
+        match self {
+            Self::Thumb => Some(320),
+            Self::Medium => Some(1280),
+            Self::Full => None,
+        }
+    }
+}
+
+/// Lowercased extension of a photo filename, if it is a supported type
+pub fn extension_from_filename(filename: &str) -> Option<String> {
+    let extension = Path::new(filename).extension()?.to_str()?.to_lowercase();
+    PHOTO_EXTENSIONS.contains(&extension.as_str()).then_some(extension)
+}
+
+/// Identify an image from its leading bytes
+pub fn sniff_extension(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("jpg")
+    } else if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("png")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("gif")
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("webp")
+    } else {
+        None
+    }
+}
+
+/// An uploaded photo after validation, re-encoded without metadata
+#[derive(Debug)]
+pub struct CleanPhoto {
+    pub bytes: Vec<u8>,
+    /// Extension matching the actual image format
+    pub extension: &'static str,
+}
+
+/// Confirm that uploaded bytes are a JPG, PNG, WebP or GIF image and
+/// re-encode them in the same format. The EXIF orientation is applied to the
+/// pixels, and EXIF, GPS, XMP, ICC profiles and comments are dropped.
+pub fn clean_photo(bytes: &[u8]) -> Result<CleanPhoto, String> {
+    let extension = sniff_extension(bytes).ok_or("File content is not a JPG, PNG, WebP or GIF image")?;
+    let decode_error = |e: image::ImageError| format!("Photo could not be decoded as {}: {}", extension, e);
+
+    if extension == "gif" {
+        let bytes = clean_gif(bytes).map_err(decode_error)?;
+        return Ok(CleanPhoto { bytes, extension });
+    }
+
+    let format = match extension {
+        "jpg" => ImageFormat::Jpeg,
+        "png" => ImageFormat::Png,
+        _ => ImageFormat::WebP,
+    };
+    let mut reader = ImageReader::with_format(Cursor::new(bytes), format);
+    reader.limits(upload_limits());
+    let mut decoder = reader.into_decoder().map_err(decode_error)?;
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let mut image = DynamicImage::from_decoder(decoder).map_err(decode_error)?;
+    image.apply_orientation(orientation);
+
+    let mut encoded = Vec::new();
+    let result = match format {
+        ImageFormat::Jpeg => {
+            flatten(&image).write_with_encoder(JpegEncoder::new_with_quality(&mut encoded, UPLOAD_QUALITY))
+        }
+        ImageFormat::Png => image.write_with_encoder(PngEncoder::new(&mut encoded)),
+        _ if image.color().has_alpha() => image.to_rgba8().write_with_encoder(WebPEncoder::new_lossless(&mut encoded)),
+        _ => image.to_rgb8().write_with_encoder(WebPEncoder::new_lossless(&mut encoded)),
+    };
+    result.map_err(|e| format!("Failed to encode photo: {}", e))?;
+    Ok(CleanPhoto { bytes: encoded, extension })
+}
+
+/// Re-encode every frame of a GIF, keeping animation timing but dropping
+/// comment and application extensions
+fn clean_gif(bytes: &[u8]) -> image::ImageResult<Vec<u8>> {
+    let mut decoder = GifDecoder::new(Cursor::new(bytes))?;
+    decoder.set_limits(upload_limits())?;
+    let frames = decoder.into_frames().collect_frames()?;
+
+    let mut encoded = Vec::new();
+    {
+        let mut encoder = GifEncoder::new(&mut encoded);
+        if frames.len() > 1 {
+            encoder.set_repeat(Repeat::Infinite)?;
+        }
+        encoder.encode_frames(frames)?;
+    }
+    Ok(encoded)
+}
+
+fn upload_limits() -> Limits {
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_UPLOAD_DIMENSION);
+    limits.max_image_height = Some(MAX_UPLOAD_DIMENSION);
+    limits
+}
+
+/// Validate and clean an upload off the async runtime; anything that isn't a
+/// decodable image is rejected as an unsupported file type
+pub async fn clean_upload(bytes: Vec<u8>) -> ApiResult<CleanPhoto> {
+    tokio::task::spawn_blocking(move || clean_photo(&bytes))
+        .await
+        .map_err(|e| ApiError::Internal(format!("Photo validation task failed: {}", e)))?
+        .map_err(ApiError::UnsupportedFileType)
+}
+
+/// Filename of a photo rendition: the original for `Full`, otherwise
+/// `{stem}.{size}.jpg`
+pub fn derivative_filename(filename: &str, size: PhotoSize) -> String {
+    if size == PhotoSize::Full {
+        return filename.to_string();
+    }
+    let stem = Path::new(filename)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(filename);
+    format!("{}.{}.jpg", stem, size.name())
+}
+
+/// Scale an image down to fit `size` and encode it as JPEG. Transparent
+/// areas are flattened onto white; images already small enough keep their
+/// dimensions.
+pub fn render_derivative(bytes: &[u8], size: PhotoSize) -> Result<Vec<u8>, String> {
+    let max = size.max_dimension().ok_or("The full size photo is not a derivative")?;
+    let image = image::load_from_memory(bytes).map_err(|e| format!("Failed to decode photo: {}", e))?;
+
+    let (width, height) = image.dimensions();
+    let image = if width > max || height > max {
+        image.resize(max, max, FilterType::Lanczos3)
+    } else {
+        image
+    };
+
+    let mut encoded = Vec::new();
+    let encoder = JpegEncoder::new_with_quality(&mut encoded, DERIVATIVE_QUALITY);
+    flatten(&image)
+        .write_with_encoder(encoder)
+        .map_err(|e| format!("Failed to encode photo: {}", e))?;
+    Ok(encoded)
+}
+
+/// Composite any alpha channel onto a white background
+fn flatten(image: &DynamicImage) -> RgbImage {
+    if !image.color().has_alpha() {
+        return image.to_rgb8();
+    }
+    let rgba = image.to_rgba8();
+    RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let [r, g, b, a] = rgba.get_pixel(x, y).0;
+        let blend = |c: u8| ((c as u32 * a as u32 + 255 * (255 - a as u32)) / 255) as u8;
+        Rgb([blend(r), blend(g), blend(b)])
+    })
+}
+
+/// Generate and store every derivative of a photo. Failures are logged; the
+/// derivative will be retried when first requested.
+pub async fn write_derivatives(store: &dyn PhotoStore, filename: &str, bytes: &[u8]) {
+    for size in PhotoSize::DERIVED {
+        if let Err(e) = write_derivative(store, filename, bytes, size).await {
+            tracing::warn!("Failed to create {} photo for {}: {}", size.name(), filename, e);
+        }
+    }
+}
+
+async fn write_derivative(store: &dyn PhotoStore, filename: &str, bytes: &[u8], size: PhotoSize) -> ApiResult<Vec<u8>> {
+    let original = bytes.to_vec();
+    let derivative = tokio::task::spawn_blocking(move || render_derivative(&original, size))
+        .await
+        .map_err(|e| ApiError::Internal(format!("Photo resize task failed: {}", e)))?
+        .map_err(ApiError::Internal)?;
+    store.put(&derivative_filename(filename, size), &derivative).await?;
+    Ok(derivative)
+}
+
+/// Read a photo rendition, generating a missing derivative from the original.
+/// Falls back to the original if it can't be decoded. Returns the bytes and
+/// the filename they were read from.
+pub async fn read_photo(store: &dyn PhotoStore, filename: &str, size: PhotoSize) -> ApiResult<(Vec<u8>, String)> {
+    let derived = derivative_filename(filename, size);
+    if size != PhotoSize::Full
+        && let Ok(Some(bytes)) = store.get(&derived).await
+    {
+        return Ok((bytes, derived));
+    }
+
+    let original = store.get(filename).await?.ok_or_else(|| {
+        tracing::error!("Photo file {} is not in {}", filename, store.location());
+        ApiError::NotFound("Photo file not found".to_string())
+    })?;
+    if size == PhotoSize::Full {
+        return Ok((original, filename.to_string()));
+    }
+
+    match write_derivative(store, filename, &original, size).await {
+        Ok(bytes) => Ok((bytes, derived)),
+        Err(e) => {
+            tracing::warn!("Serving original photo {} instead of {}: {}", filename, size.name(), e);
+            Ok((original, filename.to_string()))
+        }
+    }
+}
+
+/// Store a photo, discarding derivatives made from any previous file of the
+/// same name
+pub async fn write_photo(store: &dyn PhotoStore, filename: &str, bytes: &[u8]) -> ApiResult<()> {
+    remove_derivatives(store, filename).await;
+    store.put(filename, bytes).await
+}
+
+/// Delete a photo file and its derivatives, logging rather than failing if it
+/// cannot be removed
+pub async fn remove_photo(store: &dyn PhotoStore, filename: &str) {
+    if let Err(e) = store.delete(filename).await {
+        tracing::warn!("Failed to delete photo file {}: {}", filename, e);
+    } else {
+        tracing::info!("Deleted photo file: {}", filename);
+    }
+    remove_derivatives(store, filename).await;
+}
+
+/// Delete the derivatives of a photo; they may not exist yet
+pub async fn remove_derivatives(store: &dyn PhotoStore, filename: &str) {
+    for size in PhotoSize::DERIVED {
+        let derived = derivative_filename(filename, size);
+        if let Err(e) = store.delete(&derived).await {
+            tracing::warn!("Failed to delete photo derivative {}: {}", derived, e);
+        }
+    }
+}
+
+/// Content-based entity tag for a photo response
+pub fn etag(bytes: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    let digest = Sha256::digest(bytes);
+    let hex: String = digest[..12].iter().map(|b| format!("{:02x}", b)).collect();
+    format!("\"{}\"", hex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extension_from_filename() {
+        assert_eq!(extension_from_filename("abc.JPG").as_deref(), Some("jpg"));
+        assert_eq!(extension_from_filename("abc.webp").as_deref(), Some("webp"));
+        assert_eq!(extension_from_filename("abc.svg"), None);
+        assert_eq!(extension_from_filename("abc"), None);
+    }
+
+    #[test]
+    fn test_sniff_extension() {
+        assert_eq!(sniff_extension(&[0xFF, 0xD8, 0xFF, 0xE0]), Some("jpg"));
+        assert_eq!(sniff_extension(b"\x89PNG\r\n\x1a\n...."), Some("png"));
+        assert_eq!(sniff_extension(b"GIF89a..."), Some("gif"));
+        assert_eq!(sniff_extension(b"RIFF\0\0\0\0WEBPVP8 "), Some("webp"));
+        assert_eq!(sniff_extension(b"<svg>"), None);
+    }
+
+    fn encode(image: DynamicImage, format: ImageFormat) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        image.write_to(&mut Cursor::new(&mut bytes), format).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_derivative_filename() {
+        assert_eq!(derivative_filename("abc.png", PhotoSize::Thumb), "abc.thumb.jpg");
+        assert_eq!(derivative_filename("abc.jpg", PhotoSize::Medium), "abc.medium.jpg");
+        assert_eq!(derivative_filename("abc.png", PhotoSize::Full), "abc.png");
+    }
+
+    #[test]
+    fn test_parse_photo_size() {
+        assert_eq!(PhotoSize::parse("Thumb").unwrap(), PhotoSize::Thumb);
+        assert_eq!(PhotoSize::parse("full").unwrap(), PhotoSize::Full);
+        assert!(PhotoSize::parse("huge").is_err());
+    }
+
+    #[test]
+    fn test_render_derivative_scales_down() {
+        let original = encode(DynamicImage::new_rgb8(2000, 1000), ImageFormat::Png);
+        let thumb = render_derivative(&original, PhotoSize::Thumb).unwrap();
+        assert_eq!(sniff_extension(&thumb), Some("jpg"));
+        assert_eq!(image::load_from_memory(&thumb).unwrap().dimensions(), (320, 160));
+
+        let medium = render_derivative(&original, PhotoSize::Medium).unwrap();
+        assert_eq!(image::load_from_memory(&medium).unwrap().dimensions(), (1280, 640));
+    }
+
+    #[test]
+    fn test_render_derivative_keeps_small_images_and_flattens_alpha() {
+        let original = encode(DynamicImage::new_rgba8(40, 30), ImageFormat::Png);
+        let thumb = image::load_from_memory(&render_derivative(&original, PhotoSize::Thumb).unwrap()).unwrap();
+        assert_eq!(thumb.dimensions(), (40, 30));
+        // Fully transparent pixels become white rather than black
+        assert!(thumb.to_rgb8().get_pixel(0, 0).0.iter().all(|&c| c > 250));
+    }
+
+    #[test]
+    fn test_clean_photo_rejects_non_images() {
+        assert!(clean_photo(b"not an image").is_err());
+        assert!(clean_photo(b"\x89PNG\r\n\x1a\n truncated").is_err());
+    }
+
+    #[test]
+    fn test_clean_photo_keeps_format_and_alpha() {
+        let webp = encode(DynamicImage::new_rgba8(6, 4), ImageFormat::WebP);
+        let cleaned = clean_photo(&webp).unwrap();
+        assert_eq!(cleaned.extension, "webp");
+        let image = image::load_from_memory(&cleaned.bytes).unwrap();
+        assert_eq!(image.dimensions(), (6, 4));
+        assert!(image.color().has_alpha());
+    }
+
+    #[test]
+    fn test_clean_photo_keeps_gif_animation() {
+        let frames = (0..3u8).map(|i| {
+            image::Frame::new(image::RgbaImage::from_pixel(4, 4, image::Rgba([i * 80, 0, 0, 255])))
+        });
+        let mut gif = Vec::new();
+        GifEncoder::new(&mut gif).encode_frames(frames).unwrap();
+
+        let cleaned = clean_photo(&gif).unwrap();
+        assert_eq!(cleaned.extension, "gif");
+        let decoder = GifDecoder::new(Cursor::new(cleaned.bytes)).unwrap();
+        assert_eq!(decoder.into_frames().collect_frames().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_render_derivative_rejects_garbage() {
+        assert!(render_derivative(b"not an image", PhotoSize::Thumb).is_err());
+        assert_eq!(etag(b"a"), etag(b"a"));
+        assert_ne!(etag(b"a"), etag(b"b"));
+    }
+}
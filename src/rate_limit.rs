@@ -0,0 +1,147 @@
+//! Per-user token-bucket rate limits for costly endpoints.
+//!
+//! Each limiter gives every caller a bucket holding a minute's worth of
+//! requests, refilled continuously, so short bursts are fine but a steady
+//! stream above the limit is turned away with 429 and `Retry-After`.
+//!
+//! Requests made with an API key (personal or the instance key) count
+//! against that key, so a busy script doesn't use up its owner's allowance
+//! in the browser. Other requests count against the signed-in user's email.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{Request, State},
+    http::Method,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::{auth::UserIdentity, error::ApiError, models::api_key::hash_api_key};
+
+/// Buckets kept before idle ones are dropped
+const PRUNE_THRESHOLD: usize = 1024;
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+#[derive(Clone)]
+pub struct RateLimiter {
+    /// What is being limited, for the error message
+    name: &'static str,
+    per_minute: Option<u32>,
+    writes_only: bool,
+    buckets: Arc<Mutex<HashMap<String, Bucket>>>,
+}
+
+impl RateLimiter {
+    /// Allow `per_minute` requests a minute per caller; None allows any number
+    pub fn new(name: &'static str, per_minute: Option<u32>) -> Self {
+        Self {
+            name,
+            per_minute,
+            writes_only: false,
+            buckets: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Let GET and HEAD requests through without counting them
+    pub fn writes_only(mut self) -> Self {
+        self.writes_only = true;
+        self
+    }
+
+    /// Take a token from the caller's bucket, or say how long until one is free
+    pub fn check(&self, caller: &str) -> Result<(), Duration> {
+        let Some(per_minute) = self.per_minute else {
+            return Ok(());
+        };
+        let capacity = f64::from(per_minute);
+        let per_second = capacity / 60.0;
+        let now = Instant::now();
+
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= PRUNE_THRESHOLD {
+            // A bucket untouched for a minute is full again, the same as a new one
+            buckets.retain(|_, bucket| now.duration_since(bucket.updated_at) < Duration::from_secs(60));
+        }
+        let bucket = buckets.entry(caller.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            updated_at: now,
+        });
+
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * per_second).min(capacity);
+        bucket.updated_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / per_second))
+        }
+    }
+}
+
+/// Who a request counts against: the API key it was made with, or the user
+fn caller(request: &Request) -> Option<String> {
+    let key = request.headers().get("X-API-Key").and_then(|key| key.to_str().ok());
+    if let Some(key) = key {
+        return Some(format!("key:{}", hash_api_key(key)));
+    }
+    request
+        .extensions()
+        .get::<UserIdentity>()
+        .and_then(|identity| identity.email.as_deref())
+        .map(|email| format!("user:{}", email.to_lowercase()))
+}
+
+/// Middleware applying a `RateLimiter`; must run after authentication
+pub async fn rate_limit(State(limiter): State<RateLimiter>, request: Request, next: Next) -> Response {
+    if limiter.writes_only && matches!(*request.method(), Method::GET | Method::HEAD) {
+        return next.run(request).await;
+    }
+
+    if let Some(caller) = caller(&request)
+        && let Err(retry_after) = limiter.check(&caller)
+    {
+        tracing::warn!("Rate limited {} for {}", limiter.name, caller);
+        return ApiError::RateLimited {
+            message: format!("Too many {}; try again in a moment", limiter.name),
+            retry_after,
+        }
+        .into_response();
+    }
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_allows_a_burst_then_refuses() {
+        let limiter = RateLimiter::new("requests", Some(3));
+        for _ in 0..3 {
+            assert!(limiter.check("user:alice@example.com").is_ok());
+        }
+        let retry_after = limiter.check("user:alice@example.com").unwrap_err();
+        assert!(retry_after > Duration::ZERO && retry_after <= Duration::from_secs(20));
+
+        // Other callers have their own bucket
+        assert!(limiter.check("user:bob@example.com").is_ok());
+    }
+
+    #[test]
+    fn test_no_limit() {
+        let limiter = RateLimiter::new("requests", None);
+        for _ in 0..1000 {
+            assert!(limiter.check("user:alice@example.com").is_ok());
+        }
+    }
+}
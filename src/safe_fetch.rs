@@ -0,0 +1,154 @@
+//! HTTP client for fetching the URLs users give us.
+//!
+//! Imports fetch whatever URL they're given, from inside the network the
+//! server runs in. This client refuses to connect to loopback, private,
+//! link-local and similar addresses, so an import can't reach the router's
+//! admin page or a cloud metadata service. Host names are checked when they
+//! are resolved and literal addresses before each request and redirect, so a
+//! redirect or a DNS answer can't get around the check.
+//!
+//! Hosts listed in `IMPORT_ALLOW_PRIVATE_HOSTS` may be private, for recipe
+//! sites or other Recipe Vaults on the home network.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::{redirect, Url};
+
+/// Most redirects followed for one request
+const MAX_REDIRECTS: usize = 10;
+
+/// A client that only connects to public addresses, except for the hosts in
+/// `private_hosts`. Proxies aren't used, as they would resolve names for us.
+pub fn client(private_hosts: &[String]) -> Result<reqwest::Client, String> {
+    let allowed: Arc<[String]> = private_hosts.iter().map(|host| host.to_lowercase()).collect();
+    let redirect_allowed = allowed.clone();
+    let policy = redirect::Policy::custom(move |attempt| {
+        if attempt.previous().len() >= MAX_REDIRECTS {
+            attempt.error("too many redirects")
+        } else if let Err(e) = check_url(attempt.url(), &redirect_allowed) {
+            attempt.error(e)
+        } else {
+            attempt.follow()
+        }
+    });
+    reqwest::Client::builder()
+        .redirect(policy)
+        .no_proxy()
+        .dns_resolver(Arc::new(PublicResolver { allowed }))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+/// Refuse a URL whose host is a literal non-public address. Names are checked
+/// by the client's resolver when it connects.
+pub fn check_url(url: &Url, private_hosts: &[String]) -> Result<(), String> {
+    let host = url.host_str().ok_or_else(|| format!("{} has no host", url))?;
+    if private_hosts.iter().any(|allowed| allowed.eq_ignore_ascii_case(host)) {
+        return Ok(());
+    }
+    // The URL parser has already turned "0x7f.1" and the like into dotted quads
+    let Ok(ip) = host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() else {
+        return Ok(());
+    };
+    if is_public_address(ip) {
+        Ok(())
+    } else {
+        Err(format!("{} is not a public address", host))
+    }
+}
+
+/// Whether an address is on the public internet: not loopback, private,
+/// link-local, unspecified, unique-local, multicast or otherwise reserved
+pub fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(v4) => is_public_v4(v4),
+            None => is_public_v6(ip),
+        },
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    !(ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        // 0.0.0.0/8 "this network"
+        || a == 0
+        // 100.64.0.0/10 carrier-grade NAT, also used by Tailscale
+        || (a == 100 && (64..128).contains(&b))
+        // 198.18.0.0/15 benchmarking
+        || (a == 198 && (b == 18 || b == 19))
+        // 240.0.0.0/4 reserved
+        || a >= 240)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !(ip.is_loopback()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        // fc00::/7 unique local
+        || (first & 0xfe00) == 0xfc00
+        // fe80::/10 link-local
+        || (first & 0xffc0) == 0xfe80
+        // 2001:db8::/32 documentation
+        || (first == 0x2001 && ip.segments()[1] == 0x0db8))
+}
+
+/// Resolves names as usual, dropping addresses that aren't public
+struct PublicResolver {
+    allowed: Arc<[String]>,
+}
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_lowercase();
+        let allow_private = self.allowed.contains(&host);
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| allow_private || is_public_address(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} does not resolve to a public address", host).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_private_addresses() {
+        for ip in [
+            "127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "0.0.0.0", "100.100.1.1",
+            "::1", "::", "fd00::1", "fe80::1", "::ffff:127.0.0.1", "::ffff:192.168.0.1",
+        ] {
+            assert!(!is_public_address(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in ["93.184.216.34", "1.1.1.1", "2606:4700:4700::1111"] {
+            assert!(is_public_address(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn test_check_url() {
+        let url = |s: &str| Url::parse(s).unwrap();
+        assert!(check_url(&url("http://127.0.0.1:8080/"), &[]).is_err());
+        assert!(check_url(&url("http://[::1]/"), &[]).is_err());
+        assert!(check_url(&url("http://0x7f.1/"), &[]).is_err());
+        assert!(check_url(&url("https://example.com/"), &[]).is_ok());
+        assert!(check_url(&url("http://127.0.0.1:8080/"), &["127.0.0.1".to_string()]).is_ok());
+    }
+}
@@ -0,0 +1,268 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Aisle used for ingredients that match no keyword and have no override
+pub const OTHER_AISLE: &str = "Other";
+
+/// Default supermarket walking order, used until a family configures its own
+const DEFAULT_AISLES: &[&str] = &[
+    "Produce",
+    "Bakery",
+    "Meat & Fish",
+    "Dairy & Eggs",
+    "Pantry",
+    "Spices",
+    "Frozen",
+    OTHER_AISLE,
+];
+
+/// Keyword table for the default aisles. Matched against whole words of the
+/// normalized ingredient name. When several keywords match, the one ending
+/// last wins (the head noun: "chicken stock" is stock, not chicken), and
+/// longer keywords break ties ("coconut milk" over "milk").
+const AISLE_KEYWORDS: &[(&str, &[&str])] = &[
+    ("Frozen", &["frozen", "ice cream", "peas", "sorbet"]),
+    (
+        "Spices",
+        &[
+            "salt", "pepper", "peppercorns", "cumin", "coriander", "paprika", "turmeric",
+            "cinnamon", "nutmeg", "cardamom", "cloves", "chilli", "chili", "oregano", "thyme",
+            "rosemary", "bay", "garam masala", "curry powder", "saffron", "vanilla", "spice",
+            "allspice", "fenugreek",
+        ],
+    ),
+    (
+        "Meat & Fish",
+        &[
+            "chicken", "beef", "pork", "lamb", "mince", "bacon", "sausage", "sausages", "ham",
+            "turkey", "duck", "fish", "salmon", "cod", "tuna", "prawns", "shrimp", "mussels",
+            "anchovies", "chorizo",
+        ],
+    ),
+    (
+        "Dairy & Eggs",
+        &[
+            "milk", "butter", "cream", "cheese", "yoghurt", "yogurt", "egg", "eggs", "parmesan",
+            "mozzarella", "feta", "ricotta", "mascarpone", "creme fraiche", "ghee", "paneer",
+        ],
+    ),
+    (
+        "Bakery",
+        &["bread", "baguette", "rolls", "buns", "pitta", "pita", "naan", "tortillas", "croissants"],
+    ),
+    (
+        "Produce",
+        &[
+            "onion", "onions", "garlic", "ginger", "tomato", "tomatoes", "potato", "potatoes",
+            "carrot", "carrots", "celery", "lettuce", "spinach", "kale", "cabbage", "broccoli",
+            "cauliflower", "courgette", "zucchini", "aubergine", "eggplant", "mushroom",
+            "mushrooms", "lemon", "lemons", "lime", "limes", "apple", "apples", "banana",
+            "bananas", "avocado", "cucumber", "leek", "leeks", "shallot", "shallots", "parsley",
+            "basil", "mint", "dill", "chives", "coriander leaves", "cilantro", "spring onions",
+            "scallions", "herbs", "berries", "orange", "oranges", "squash", "pumpkin",
+            "bell pepper", "red pepper", "green pepper", "peppers", "chillies",
+        ],
+    ),
+    (
+        "Pantry",
+        &[
+            "flour", "sugar", "rice", "pasta", "spaghetti", "noodles", "oil", "vinegar", "stock",
+            "broth", "honey", "syrup", "beans", "lentils", "chickpeas", "oats", "yeast",
+            "baking powder", "baking soda", "bicarbonate", "cocoa", "chocolate", "nuts",
+            "almonds", "walnuts", "sauce", "ketchup", "mustard", "mayonnaise", "passata",
+            "coconut milk", "tinned", "canned", "paste", "puree", "stock cube",
+        ],
+    ),
+];
+
+/// A family's supermarket layout: aisle walking order plus ingredient overrides
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AisleConfig {
+    /// Aisle names in the order they are walked
+    pub aisles: Vec<String>,
+    /// Normalized ingredient name -> aisle, for ingredients the keyword table gets wrong
+    #[serde(default)]
+    pub ingredient_aisles: HashMap<String, String>,
+}
+
+impl Default for AisleConfig {
+    fn default() -> Self {
+        Self {
+            aisles: DEFAULT_AISLES.iter().map(|a| a.to_string()).collect(),
+            ingredient_aisles: HashMap::new(),
+        }
+    }
+}
+
+impl AisleConfig {
+    /// Validate and normalize a config submitted by a user
+    pub fn validate(mut self) -> Result<Self, String> {
+        if self.aisles.is_empty() {
+            return Err("At least one aisle is required".to_string());
+        }
+        if self.aisles.len() > 50 {
+            return Err("No more than 50 aisles are supported".to_string());
+        }
+
+        let mut seen = Vec::new();
+        for aisle in &mut self.aisles {
+            *aisle = aisle.trim().to_string();
+            if aisle.is_empty() {
+                return Err("Aisle names cannot be empty".to_string());
+            }
+            if aisle.len() > 100 {
+                return Err("Aisle names cannot exceed 100 characters".to_string());
+            }
+            let lower = aisle.to_lowercase();
+            if seen.contains(&lower) {
+                return Err(format!("Duplicate aisle '{}'", aisle));
+            }
+            seen.push(lower);
+        }
+
+        let mut overrides = HashMap::new();
+        for (ingredient, aisle) in self.ingredient_aisles {
+            let aisle = aisle.trim().to_string();
+            if !seen.contains(&aisle.to_lowercase()) && aisle != OTHER_AISLE {
+                return Err(format!(
+                    "Ingredient '{}' is assigned to unknown aisle '{}'",
+                    ingredient, aisle
+                ));
+            }
+            overrides.insert(normalize_ingredient_name(&ingredient), aisle);
+        }
+        self.ingredient_aisles = overrides;
+
+        Ok(self)
+    }
+
+    /// Determine which aisle an ingredient belongs in
+    pub fn aisle_for(&self, ingredient_name: &str) -> String {
+        let normalized = normalize_ingredient_name(ingredient_name);
+
+        if let Some(aisle) = self.ingredient_aisles.get(&normalized) {
+            return self.canonical_aisle_name(aisle);
+        }
+
+        let padded = format!(" {} ", normalized);
+        let mut best: Option<(usize, usize, &str)> = None;
+        for (aisle, keywords) in AISLE_KEYWORDS {
+            for kw in keywords.iter() {
+                if let Some(start) = padded.rfind(&format!(" {} ", kw)) {
+                    let candidate = (start + kw.len(), kw.len(), *aisle);
+                    if best.is_none_or(|(end, len, _)| (candidate.0, candidate.1) > (end, len)) {
+                        best = Some(candidate);
+                    }
+                }
+            }
+        }
+
+        match best {
+            Some((_, _, aisle)) => self.canonical_aisle_name(aisle),
+            None => OTHER_AISLE.to_string(),
+        }
+    }
+
+    /// Position of an aisle in the walking order. Unknown aisles sort after
+    /// every configured aisle.
+    pub fn position(&self, aisle: &str) -> usize {
+        self.aisles
+            .iter()
+            .position(|a| a.eq_ignore_ascii_case(aisle))
+            .unwrap_or(self.aisles.len())
+    }
+
+    /// Prefer the family's spelling of an aisle over the built-in one
+    fn canonical_aisle_name(&self, aisle: &str) -> String {
+        self.aisles
+            .iter()
+            .find(|a| a.eq_ignore_ascii_case(aisle))
+            .cloned()
+            .unwrap_or_else(|| aisle.to_string())
+    }
+}
+
+/// Normalize an ingredient name for matching: lowercase, single-spaced,
+/// punctuation stripped
+pub fn normalize_ingredient_name(name: &str) -> String {
+    name.to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == ' ' || c == '&' { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_classification() {
+        let config = AisleConfig::default();
+        assert_eq!(config.aisle_for("Red onions"), "Produce");
+        assert_eq!(config.aisle_for("chicken thighs"), "Meat & Fish");
+        assert_eq!(config.aisle_for("Plain flour"), "Pantry");
+        assert_eq!(config.aisle_for("Ground cumin"), "Spices");
+        assert_eq!(config.aisle_for("unsalted butter"), "Dairy & Eggs");
+        assert_eq!(config.aisle_for("dragonfruit"), OTHER_AISLE);
+    }
+
+    #[test]
+    fn test_head_noun_wins() {
+        let config = AisleConfig::default();
+        assert_eq!(config.aisle_for("chicken stock"), "Pantry");
+        assert_eq!(config.aisle_for("coconut milk"), "Pantry");
+        assert_eq!(config.aisle_for("fresh coriander leaves"), "Produce");
+        assert_eq!(config.aisle_for("ground coriander"), "Spices");
+        assert_eq!(config.aisle_for("frozen peas"), "Frozen");
+    }
+
+    #[test]
+    fn test_keywords_match_whole_words_only() {
+        let config = AisleConfig::default();
+        // "ham" must not match "champagne"
+        assert_eq!(config.aisle_for("champagne"), OTHER_AISLE);
+    }
+
+    #[test]
+    fn test_override_takes_precedence() {
+        let config = AisleConfig {
+            aisles: vec!["World Foods".to_string(), "Produce".to_string()],
+            ingredient_aisles: HashMap::from([("Coconut Milk".to_string(), "world foods".to_string())]),
+        }
+        .validate()
+        .unwrap();
+
+        assert_eq!(config.aisle_for("coconut milk"), "World Foods");
+        assert_eq!(config.position("World Foods"), 0);
+        assert_eq!(config.position("Pantry"), 2);
+    }
+
+    #[test]
+    fn test_validate_rejects_duplicates_and_unknown_aisles() {
+        let dup = AisleConfig {
+            aisles: vec!["Produce".to_string(), "produce".to_string()],
+            ingredient_aisles: HashMap::new(),
+        };
+        assert!(dup.validate().is_err());
+
+        let unknown = AisleConfig {
+            aisles: vec!["Produce".to_string()],
+            ingredient_aisles: HashMap::from([("tofu".to_string(), "Chilled".to_string())]),
+        };
+        assert!(unknown.validate().unwrap_err().contains("unknown aisle"));
+
+        let empty = AisleConfig {
+            aisles: vec![],
+            ingredient_aisles: HashMap::new(),
+        };
+        assert!(empty.validate().is_err());
+    }
+
+    #[test]
+    fn test_normalize_ingredient_name() {
+        assert_eq!(normalize_ingredient_name("  Extra-Virgin  Olive Oil "), "extra virgin olive oil");
+    }
+}
@@ -0,0 +1,277 @@
+use crate::handlers::share::html_escape;
+
+use super::{format_quantity, ShoppingItem, ShoppingList};
+
+/// Output formats supported by the shopping list endpoints
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShoppingListFormat {
+    Json,
+    Text,
+    Markdown,
+    Csv,
+    Html,
+}
+
+impl ShoppingListFormat {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "text" | "txt" => Ok(Self::Text),
+            "markdown" | "md" => Ok(Self::Markdown),
+            "csv" => Ok(Self::Csv),
+            "html" => Ok(Self::Html),
+            other => Err(format!(
+                "Unsupported format '{}'. Expected json, text, markdown, csv or html",
+                other
+            )),
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Text => "text/plain; charset=utf-8",
+            Self::Markdown => "text/markdown; charset=utf-8",
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Html => "text/html; charset=utf-8",
+        }
+    }
+
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Text => "txt",
+            Self::Markdown => "md",
+            Self::Csv => "csv",
+            Self::Html => "html",
+        }
+    }
+}
+
+/// Render a shopping list in the requested format
+pub fn render(list: &ShoppingList, format: ShoppingListFormat) -> String {
+    match format {
+        ShoppingListFormat::Json => serde_json::to_string_pretty(list).unwrap_or_else(|_| "{}".to_string()),
+        ShoppingListFormat::Text => to_text(list),
+        ShoppingListFormat::Markdown => to_markdown(list),
+        ShoppingListFormat::Csv => to_csv(list),
+        ShoppingListFormat::Html => to_html(list),
+    }
+}
+
+/// "500 g flour (sifted)" — quantity, unit, name and notes on one line
+fn item_line(item: &ShoppingItem) -> String {
+    let qty = item.quantity.map(|q| format!("{} ", format_quantity(q))).unwrap_or_default();
+    let unit = item.unit.as_deref().map(|u| format!("{} ", u)).unwrap_or_default();
+    let notes = if item.notes.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", item.notes.join("; "))
+    };
+    format!("{}{}{}{}", qty, unit, item.name, notes)
+}
+
+fn recipe_titles(list: &ShoppingList) -> String {
+    list.recipes
+        .iter()
+        .map(|r| r.title.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Plain text, one item per line under aisle headings
+pub fn to_text(list: &ShoppingList) -> String {
+    let mut out = format!("Shopping list for: {}\n", recipe_titles(list));
+    for (aisle, items) in list.by_aisle() {
+        out.push_str(&format!("\n{}\n", aisle.to_uppercase()));
+        for item in items {
+            out.push_str(&format!("- {}\n", item_line(item)));
+        }
+    }
+    out
+}
+
+/// Markdown task-list checklist, one section per aisle
+pub fn to_markdown(list: &ShoppingList) -> String {
+    let mut out = format!("# Shopping list\n\n_For: {}_\n", markdown_escape(&recipe_titles(list)));
+    for (aisle, items) in list.by_aisle() {
+        out.push_str(&format!("\n## {}\n\n", markdown_escape(aisle)));
+        for item in items {
+            out.push_str(&format!("- [ ] {}\n", markdown_escape(&item_line(item))));
+        }
+    }
+    out
+}
+
+/// CSV with a header row; one row per item
+pub fn to_csv(list: &ShoppingList) -> String {
+    let mut out = String::from("aisle,item,quantity,unit,notes,recipes\r\n");
+    for item in &list.items {
+        let row = [
+            item.aisle.clone(),
+            item.name.clone(),
+            item.quantity.map(format_quantity).unwrap_or_default(),
+            item.unit.clone().unwrap_or_default(),
+            item.notes.join("; "),
+            item.recipes.join("; "),
+        ];
+        let escaped: Vec<String> = row.iter().map(|f| csv_escape(f)).collect();
+        out.push_str(&escaped.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Printable HTML page with checkboxes, styled to match the share page
+pub fn to_html(list: &ShoppingList) -> String {
+    let sections: String = list
+        .by_aisle()
+        .into_iter()
+        .map(|(aisle, items)| {
+            let lis: String = items
+                .iter()
+                .map(|item| {
+                    format!(
+                        "<li><label><input type=\"checkbox\"> {}</label></li>",
+                        html_escape(&item_line(item))
+                    )
+                })
+                .collect();
+            format!("<h2>{}</h2>\n<ul>{}</ul>\n", html_escape(aisle), lis)
+        })
+        .collect();
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>Shopping list - Recipe Vault</title>
+<style>
+*{{margin:0;padding:0;box-sizing:border-box}}
+body{{font-family:-apple-system,BlinkMacSystemFont,'Segoe UI',Roboto,sans-serif;max-width:680px;margin:0 auto;padding:24px 16px;color:#333;background:#faf9f6;line-height:1.6}}
+h1{{font-size:1.8em;margin-bottom:8px;color:#2c1810}}
+.for{{color:#666;margin:8px 0 16px;font-style:italic}}
+h2{{font-size:1.1em;margin:20px 0 8px;color:#2c1810;border-bottom:1px solid #e0d6c8;padding-bottom:4px}}
+ul{{list-style:none}}
+li{{margin:4px 0}}
+input{{margin-right:8px}}
+.footer{{margin-top:32px;padding-top:16px;border-top:1px solid #e0d6c8;color:#999;font-size:0.85em}}
+@media print{{body{{background:#fff;max-width:none;padding:0}}h2{{break-after:avoid}}li{{break-inside:avoid}}.footer{{display:none}}}}
+</style>
+</head>
+<body>
+<h1>Shopping list</h1>
+<p class="for">For: {recipes}</p>
+{sections}<div class="footer">From Recipe Vault</div>
+</body>
+</html>"#,
+        recipes = html_escape(&recipe_titles(list)),
+        sections = sections,
+    )
+}
+
+/// Escape a CSV field: quote when needed, and neutralize leading characters
+/// that spreadsheet apps would treat as a formula
+fn csv_escape(field: &str) -> String {
+    let field = if field.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", field)
+    } else {
+        field.to_string()
+    };
+
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+/// Escape characters that Markdown would interpret as formatting
+fn markdown_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shopping::ShoppingListRecipe;
+
+    fn sample_list() -> ShoppingList {
+        ShoppingList {
+            recipes: vec![ShoppingListRecipe {
+                id: "r1".to_string(),
+                title: "Mac & <Cheese>".to_string(),
+            }],
+            items: vec![
+                ShoppingItem {
+                    name: "Onion".to_string(),
+                    quantity: Some(2.0),
+                    unit: None,
+                    notes: vec![],
+                    aisle: "Produce".to_string(),
+                    recipes: vec!["Mac & <Cheese>".to_string()],
+                },
+                ShoppingItem {
+                    name: "Cheddar, mature".to_string(),
+                    quantity: Some(200.0),
+                    unit: Some("g".to_string()),
+                    notes: vec!["\"grated\"".to_string()],
+                    aisle: "Dairy & Eggs".to_string(),
+                    recipes: vec!["Mac & <Cheese>".to_string()],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(ShoppingListFormat::parse("MD").unwrap(), ShoppingListFormat::Markdown);
+        assert_eq!(ShoppingListFormat::parse("csv").unwrap(), ShoppingListFormat::Csv);
+        assert!(ShoppingListFormat::parse("pdf").is_err());
+    }
+
+    #[test]
+    fn test_text_groups_by_aisle() {
+        let text = to_text(&sample_list());
+        assert!(text.contains("PRODUCE\n- 2 Onion\n"));
+        assert!(text.contains("DAIRY & EGGS\n- 200 g Cheddar, mature (\"grated\")\n"));
+    }
+
+    #[test]
+    fn test_markdown_checklist() {
+        let md = to_markdown(&sample_list());
+        assert!(md.contains("## Produce\n\n- [ ] 2 Onion\n"));
+        assert!(md.contains("Mac & \\<Cheese\\>"));
+    }
+
+    #[test]
+    fn test_csv_quotes_fields() {
+        let csv = to_csv(&sample_list());
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+        assert_eq!(lines[0], "aisle,item,quantity,unit,notes,recipes");
+        assert_eq!(lines[2], "Dairy & Eggs,\"Cheddar, mature\",200,g,\"\"\"grated\"\"\",Mac & <Cheese>");
+    }
+
+    #[test]
+    fn test_csv_neutralizes_formulas() {
+        assert_eq!(csv_escape("=HYPERLINK(\"x\")"), "\"'=HYPERLINK(\"\"x\"\")\"");
+        assert_eq!(csv_escape("@cmd"), "'@cmd");
+    }
+
+    #[test]
+    fn test_html_escapes_content() {
+        let html = to_html(&sample_list());
+        assert!(html.contains("Mac &amp; &lt;Cheese&gt;"));
+        assert!(!html.contains("<Cheese>"));
+        assert!(html.contains("&quot;grated&quot;"));
+    }
+}
@@ -0,0 +1,235 @@
+pub mod aisles;
+pub mod export;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::RecipeWithDetails;
+
+pub use aisles::{normalize_ingredient_name, AisleConfig};
+pub use export::ShoppingListFormat;
+
+/// A recipe that contributed to a shopping list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShoppingListRecipe {
+    pub id: String,
+    pub title: String,
+}
+
+/// One line on a shopping list: an ingredient combined across recipes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShoppingItem {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantity: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    /// Distinct ingredient notes from the contributing recipes
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+    pub aisle: String,
+    /// Titles of the recipes that need this ingredient
+    pub recipes: Vec<String>,
+}
+
+/// Combined ingredients for a set of recipes, sorted into walking order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShoppingList {
+    pub recipes: Vec<ShoppingListRecipe>,
+    pub items: Vec<ShoppingItem>,
+}
+
+impl ShoppingList {
+    /// Group items by aisle, preserving walking order
+    pub fn by_aisle(&self) -> Vec<(&str, Vec<&ShoppingItem>)> {
+        let mut groups: Vec<(&str, Vec<&ShoppingItem>)> = Vec::new();
+        for item in &self.items {
+            match groups.last_mut() {
+                Some((aisle, items)) if *aisle == item.aisle => items.push(item),
+                _ => groups.push((item.aisle.as_str(), vec![item])),
+            }
+        }
+        groups
+    }
+}
+
+/// Combine the ingredients of the given recipes into a shopping list.
+///
+/// Ingredients are merged when their normalized name and unit match; quantities
+/// are summed where known. Items are sorted by the family's aisle order, then
+/// alphabetically within an aisle.
+pub fn build_shopping_list(recipes: &[RecipeWithDetails], aisles: &AisleConfig) -> ShoppingList {
+    let mut items: Vec<ShoppingItem> = Vec::new();
+    let mut keys: Vec<(String, String)> = Vec::new();
+
+    for recipe in recipes {
+        let title = &recipe.recipe.title;
+        for ing in &recipe.ingredients {
+            let unit = ing
+                .unit
+                .as_deref()
+                .map(str::trim)
+                .filter(|u| !u.is_empty());
+            let key = (
+                normalize_ingredient_name(&ing.name),
+                unit.map(|u| u.to_lowercase()).unwrap_or_default(),
+            );
+
+            let index = match keys.iter().position(|k| *k == key) {
+                Some(index) => index,
+                None => {
+                    keys.push(key);
+                    items.push(ShoppingItem {
+                        name: ing.name.trim().to_string(),
+                        quantity: None,
+                        unit: unit.map(|u| u.to_string()),
+                        notes: Vec::new(),
+                        aisle: aisles.aisle_for(&ing.name),
+                        recipes: Vec::new(),
+                    });
+                    items.len() - 1
+                }
+            };
+
+            let item = &mut items[index];
+            if let Some(q) = ing.quantity {
+                item.quantity = Some(item.quantity.unwrap_or(0.0) + q);
+            }
+            if let Some(notes) = ing.notes.as_deref().map(str::trim)
+                && !notes.is_empty()
+                && !item.notes.iter().any(|n| n == notes)
+            {
+                item.notes.push(notes.to_string());
+            }
+            if !item.recipes.contains(title) {
+                item.recipes.push(title.clone());
+            }
+        }
+    }
+
+    items.sort_by(|a, b| {
+        aisles
+            .position(&a.aisle)
+            .cmp(&aisles.position(&b.aisle))
+            .then_with(|| a.aisle.to_lowercase().cmp(&b.aisle.to_lowercase()))
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+
+    ShoppingList {
+        recipes: recipes
+            .iter()
+            .map(|r| ShoppingListRecipe {
+                id: r.recipe.id.clone(),
+                title: r.recipe.title.clone(),
+            })
+            .collect(),
+        items,
+    }
+}
+
+/// Format a quantity for display, rounding away floating point noise
+/// (0.1 + 0.2 shows as "0.3", 2.0 as "2")
+pub fn format_quantity(quantity: f64) -> String {
+    let rounded = (quantity * 100.0).round() / 100.0;
+    format!("{}", rounded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Recipe, RecipeIngredient};
+
+    fn recipe(title: &str, ingredients: &[(&str, Option<f64>, Option<&str>)]) -> RecipeWithDetails {
+        let id = Recipe::new_id();
+        RecipeWithDetails {
+            recipe: Recipe {
+                id: id.clone(),
+                title: title.to_string(),
+                description: None,
+                prep_time_minutes: None,
+                cook_time_minutes: None,
+                servings: None,
+                difficulty: None,
+                photo_filename: None,
+                source_url: None,
+                shared_by: None,
+                created_at: String::new(),
+                updated_at: String::new(),
+                created_by: None,
+                updated_by: None,
+                family_id: None,
+            },
+            ingredients: ingredients
+                .iter()
+                .enumerate()
+                .map(|(i, (name, quantity, unit))| RecipeIngredient {
+                    id: format!("ing-{}", i),
+                    recipe_id: id.clone(),
+                    position: i as i32,
+                    name: name.to_string(),
+                    quantity: *quantity,
+                    unit: unit.map(|u| u.to_string()),
+                    notes: None,
+                })
+                .collect(),
+            steps: vec![],
+            tags: vec![],
+            photos: vec![],
+            source_scans: vec![],
+        }
+    }
+
+    #[test]
+    fn test_merges_matching_name_and_unit() {
+        let recipes = vec![
+            recipe("Curry", &[("Onion", Some(1.0), None), ("Rice", Some(200.0), Some("g"))]),
+            recipe("Pilaf", &[("onion", Some(2.0), None), ("rice", Some(150.0), Some("G"))]),
+        ];
+        let list = build_shopping_list(&recipes, &AisleConfig::default());
+
+        assert_eq!(list.items.len(), 2);
+        let onion = list.items.iter().find(|i| i.name == "Onion").unwrap();
+        assert_eq!(onion.quantity, Some(3.0));
+        assert_eq!(onion.recipes, vec!["Curry", "Pilaf"]);
+        let rice = list.items.iter().find(|i| i.name == "Rice").unwrap();
+        assert_eq!(rice.quantity, Some(350.0));
+    }
+
+    #[test]
+    fn test_keeps_different_units_separate() {
+        let recipes = vec![recipe(
+            "Cake",
+            &[("Sugar", Some(100.0), Some("g")), ("Sugar", Some(1.0), Some("tbsp")), ("Salt", None, None)],
+        )];
+        let list = build_shopping_list(&recipes, &AisleConfig::default());
+
+        assert_eq!(list.items.len(), 3);
+        let salt = list.items.iter().find(|i| i.name == "Salt").unwrap();
+        assert_eq!(salt.quantity, None);
+    }
+
+    #[test]
+    fn test_sorted_by_aisle_order() {
+        let recipes = vec![recipe(
+            "Dinner",
+            &[("flour", None, None), ("chicken", None, None), ("onion", None, None)],
+        )];
+        let default_list = build_shopping_list(&recipes, &AisleConfig::default());
+        let names: Vec<&str> = default_list.items.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, vec!["onion", "chicken", "flour"]);
+
+        let custom = AisleConfig {
+            aisles: vec!["Pantry".to_string(), "Meat & Fish".to_string(), "Produce".to_string()],
+            ingredient_aisles: Default::default(),
+        };
+        let custom_list = build_shopping_list(&recipes, &custom);
+        let names: Vec<&str> = custom_list.items.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, vec!["flour", "chicken", "onion"]);
+    }
+
+    #[test]
+    fn test_format_quantity() {
+        assert_eq!(format_quantity(2.0), "2");
+        assert_eq!(format_quantity(0.1 + 0.2), "0.3");
+        assert_eq!(format_quantity(1.25), "1.25");
+    }
+}
@@ -0,0 +1,423 @@
+//! Mirror the vault to a folder of Markdown recipes and back.
+//!
+//! `pull` writes every recipe to `<dir>/<title-slug>.md` and removes files
+//! for recipes that no longer exist. `push` sends edited files back, creates
+//! recipes for files without an `id` in their front matter, and with `prune`
+//! deletes recipes that have no file. Every file is parsed before anything
+//! is changed, so one broken file stops the whole sync with its line number.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::formats::{parse_markdown, MarkdownRecipe};
+use crate::mcp::http_client::ApiClient;
+use crate::models::{CreateRecipeInput, Recipe};
+
+/// The vault operations a sync needs
+pub trait SyncClient {
+    fn list_recipes(&self) -> Result<Vec<Recipe>, String>;
+    fn get_markdown(&self, id: &str) -> Result<String, String>;
+    /// Replace a recipe, returning its Markdown as saved
+    fn put_markdown(&self, id: &str, markdown: &str) -> Result<String, String>;
+    /// Create a recipe, returning its ID
+    fn create_recipe(&self, input: CreateRecipeInput) -> Result<String, String>;
+    fn delete_recipe(&self, id: &str) -> Result<(), String>;
+}
+
+impl SyncClient for ApiClient {
+    fn list_recipes(&self) -> Result<Vec<Recipe>, String> {
+        ApiClient::list_recipes(self).map_err(|e| e.message)
+    }
+
+    fn get_markdown(&self, id: &str) -> Result<String, String> {
+        self.get_recipe_markdown(id).map_err(|e| e.message)
+    }
+
+    fn put_markdown(&self, id: &str, markdown: &str) -> Result<String, String> {
+        self.put_recipe_markdown(id, markdown).map_err(|e| e.message)
+    }
+
+    fn create_recipe(&self, input: CreateRecipeInput) -> Result<String, String> {
+        ApiClient::create_recipe(self, input)
+            .map(|recipe| recipe.recipe.id)
+            .map_err(|e| e.message)
+    }
+
+    fn delete_recipe(&self, id: &str) -> Result<(), String> {
+        ApiClient::delete_recipe(self, id).map_err(|e| e.message)
+    }
+}
+
+/// What a sync changed
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub deleted: usize,
+}
+
+impl fmt::Display for SyncReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} created, {} updated, {} unchanged, {} deleted",
+            self.created, self.updated, self.unchanged, self.deleted
+        )
+    }
+}
+
+struct LocalFile {
+    path: PathBuf,
+    text: String,
+    parsed: MarkdownRecipe,
+}
+
+/// Write the vault to `dir`. Files are matched to recipes by the `id` in
+/// their front matter, so renamed files keep their names.
+pub fn pull(client: &impl SyncClient, dir: &Path) -> Result<SyncReport, String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    let files = read_dir(dir)?;
+
+    let mut by_id: HashMap<String, &LocalFile> = HashMap::new();
+    let mut taken: HashSet<String> = HashSet::new();
+    for file in &files {
+        if let Some(id) = &file.parsed.id {
+            by_id.insert(id.clone(), file);
+        }
+        taken.insert(file_name(&file.path).to_lowercase());
+    }
+
+    let mut report = SyncReport::default();
+    let recipes = client.list_recipes()?;
+    let vault_ids: HashSet<&str> = recipes.iter().map(|r| r.id.as_str()).collect();
+
+    for recipe in &recipes {
+        let markdown = client.get_markdown(&recipe.id)?;
+        match by_id.get(&recipe.id) {
+            Some(file) if file.text == markdown => report.unchanged += 1,
+            Some(file) => {
+                write_file(&file.path, &markdown)?;
+                report.updated += 1;
+            }
+            None => {
+                let name = free_file_name(&recipe.title, &mut taken);
+                write_file(&dir.join(name), &markdown)?;
+                report.created += 1;
+            }
+        }
+    }
+
+    for file in &files {
+        if let Some(id) = &file.parsed.id
+            && !vault_ids.contains(id.as_str())
+        {
+            std::fs::remove_file(&file.path).map_err(|e| format!("{}: {}", file.path.display(), e))?;
+            report.deleted += 1;
+        }
+    }
+    Ok(report)
+}
+
+/// Send the files in `dir` to the vault. New recipes get their `id` written
+/// back into their file. With `prune`, recipes without a file are deleted.
+pub fn push(client: &impl SyncClient, dir: &Path, prune: bool) -> Result<SyncReport, String> {
+    let files = read_dir(dir)?;
+
+    let mut seen: HashMap<&str, &Path> = HashMap::new();
+    for file in &files {
+        if let Some(id) = &file.parsed.id
+            && let Some(other) = seen.insert(id, &file.path)
+        {
+            return Err(format!(
+                "{} and {} both have id '{}'",
+                other.display(),
+                file.path.display(),
+                id
+            ));
+        }
+    }
+
+    let mut report = SyncReport::default();
+    let vault_ids: HashSet<String> = client.list_recipes()?.into_iter().map(|r| r.id).collect();
+    let mut pushed_ids = HashSet::new();
+
+    for file in &files {
+        let id = file.parsed.id.as_deref().filter(|id| vault_ids.contains(*id));
+        let saved = match id {
+            Some(id) => {
+                pushed_ids.insert(id.to_string());
+                let current = client.get_markdown(id)?;
+                if current == file.text {
+                    report.unchanged += 1;
+                    continue;
+                }
+                report.updated += 1;
+                client
+                    .put_markdown(id, &file.text)
+                    .map_err(|e| format!("{}: {}", file.path.display(), e))?
+            }
+            // New file, or a recipe that was deleted from the vault
+            None => {
+                let id = client
+                    .create_recipe(file.parsed.recipe.clone())
+                    .map_err(|e| format!("{}: {}", file.path.display(), e))?;
+                pushed_ids.insert(id.clone());
+                report.created += 1;
+                client.get_markdown(&id)?
+            }
+        };
+        if saved != file.text {
+            write_file(&file.path, &saved)?;
+        }
+    }
+
+    if prune {
+        for id in vault_ids.difference(&pushed_ids) {
+            client.delete_recipe(id)?;
+            report.deleted += 1;
+        }
+    }
+    Ok(report)
+}
+
+/// Read and parse every `.md` file in `dir`, reporting all parse errors at once
+fn read_dir(dir: &Path) -> Result<Vec<LocalFile>, String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "md"))
+        .collect();
+    paths.sort();
+
+    let mut files = Vec::new();
+    let mut errors = Vec::new();
+    for path in paths {
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) => {
+                errors.push(format!("{}: {}", path.display(), e));
+                continue;
+            }
+        };
+        match parse_markdown(&text) {
+            Ok(parsed) => files.push(LocalFile { path, text, parsed }),
+            Err(e) => errors.push(format!("{}:{}", path.display(), e.to_string().trim_start_matches("line "))),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors.join("\n"));
+    }
+    Ok(files)
+}
+
+fn write_file(path: &Path, text: &str) -> Result<(), String> {
+    std::fs::write(path, text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+/// "Grandma's Apple Pie" -> "grandmas-apple-pie.md", numbered if taken
+fn free_file_name(title: &str, taken: &mut HashSet<String>) -> String {
+    let mut slug = String::new();
+    for c in title.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if matches!(c, ' ' | '-' | '_' | '/' | '.' | ',') && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_matches('-');
+    let slug = if slug.is_empty() { "recipe" } else { slug };
+
+    let mut name = format!("{}.md", slug);
+    let mut n = 2;
+    while taken.contains(&name) {
+        name = format!("{}-{}.md", slug, n);
+        n += 1;
+    }
+    taken.insert(name.clone());
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::markdown::render;
+    use std::cell::RefCell;
+
+    /// In-memory vault holding each recipe's Markdown
+    #[derive(Default)]
+    struct FakeVault {
+        recipes: RefCell<Vec<(String, String, CreateRecipeInput)>>,
+        next_id: RefCell<u32>,
+    }
+
+    impl FakeVault {
+        fn add(&self, title: &str) -> String {
+            self.create_recipe(CreateRecipeInput {
+                title: title.to_string(),
+                ..Default::default()
+            })
+            .unwrap()
+        }
+
+        fn titles(&self) -> Vec<String> {
+            self.recipes.borrow().iter().map(|(_, title, _)| title.clone()).collect()
+        }
+    }
+
+    impl SyncClient for FakeVault {
+        fn list_recipes(&self) -> Result<Vec<Recipe>, String> {
+            Ok(self
+                .recipes
+                .borrow()
+                .iter()
+                .map(|(id, title, _)| Recipe {
+                    id: id.clone(),
+                    title: title.clone(),
+                    description: None,
+                    prep_time_minutes: None,
+                    cook_time_minutes: None,
+                    servings: None,
+                    difficulty: None,
+                    photo_filename: None,
+                    source_url: None,
+                    shared_by: None,
+                    created_at: String::new(),
+                    updated_at: String::new(),
+                    created_by: None,
+                    updated_by: None,
+                    family_id: None,
+                })
+                .collect())
+        }
+
+        fn get_markdown(&self, id: &str) -> Result<String, String> {
+            let recipes = self.recipes.borrow();
+            let (_, _, input) = recipes.iter().find(|(i, _, _)| i == id).ok_or("not found")?;
+            Ok(render(Some(id), input))
+        }
+
+        fn put_markdown(&self, id: &str, markdown: &str) -> Result<String, String> {
+            let parsed = parse_markdown(markdown).map_err(|e| e.to_string())?;
+            {
+                let mut recipes = self.recipes.borrow_mut();
+                let entry = recipes.iter_mut().find(|(i, _, _)| i == id).ok_or("not found")?;
+                entry.1 = parsed.recipe.title.clone();
+                entry.2 = parsed.recipe;
+            }
+            self.get_markdown(id)
+        }
+
+        fn create_recipe(&self, input: CreateRecipeInput) -> Result<String, String> {
+            *self.next_id.borrow_mut() += 1;
+            let id = format!("id-{}", self.next_id.borrow());
+            self.recipes.borrow_mut().push((id.clone(), input.title.clone(), input));
+            Ok(id)
+        }
+
+        fn delete_recipe(&self, id: &str) -> Result<(), String> {
+            self.recipes.borrow_mut().retain(|(i, _, _)| i != id);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_free_file_name() {
+        let mut taken = HashSet::new();
+        assert_eq!(free_file_name("Grandma's Apple Pie", &mut taken), "grandmas-apple-pie.md");
+        assert_eq!(free_file_name("Grandma's  apple pie!", &mut taken), "grandmas-apple-pie-2.md");
+        assert_eq!(free_file_name("???", &mut taken), "recipe.md");
+    }
+
+    #[test]
+    fn test_pull_then_push_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = FakeVault::default();
+        let soup = vault.add("Tomato Soup");
+        vault.add("Pancakes");
+
+        let report = pull(&vault, dir.path()).unwrap();
+        assert_eq!(report.created, 2);
+        assert!(dir.path().join("tomato-soup.md").exists());
+
+        // Nothing changed, so nothing is sent
+        let report = push(&vault, dir.path(), false).unwrap();
+        assert_eq!(report.unchanged, 2);
+
+        // An edited file updates its recipe, even after being renamed
+        let text = std::fs::read_to_string(dir.path().join("tomato-soup.md")).unwrap();
+        std::fs::remove_file(dir.path().join("tomato-soup.md")).unwrap();
+        std::fs::write(dir.path().join("soup.md"), text.replace("# Tomato Soup", "# Roast Tomato Soup")).unwrap();
+        let report = push(&vault, dir.path(), false).unwrap();
+        assert_eq!(report.updated, 1);
+        assert!(vault.titles().contains(&"Roast Tomato Soup".to_string()));
+
+        let report = pull(&vault, dir.path()).unwrap();
+        assert_eq!(report, SyncReport { unchanged: 2, ..Default::default() });
+        assert!(std::fs::read_to_string(dir.path().join("soup.md")).unwrap().contains(&soup));
+    }
+
+    #[test]
+    fn test_push_creates_and_prunes() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = FakeVault::default();
+        vault.add("Old Recipe");
+
+        std::fs::write(dir.path().join("toast.md"), "# Toast\n\n## Steps\n\n1. Toast the bread.\n").unwrap();
+        let report = push(&vault, dir.path(), true).unwrap();
+        assert_eq!(report, SyncReport { created: 1, deleted: 1, ..Default::default() });
+        assert_eq!(vault.titles(), vec!["Toast"]);
+
+        // The new id is written back so the next push updates instead of creating
+        let text = std::fs::read_to_string(dir.path().join("toast.md")).unwrap();
+        assert!(text.starts_with("---\nid: id-2\n"));
+        assert_eq!(push(&vault, dir.path(), true).unwrap().unchanged, 1);
+    }
+
+    #[test]
+    fn test_pull_removes_deleted_recipes() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = FakeVault::default();
+        let id = vault.add("Gone Soon");
+        pull(&vault, dir.path()).unwrap();
+        std::fs::write(dir.path().join("draft.md"), "# Draft\n").unwrap();
+
+        vault.delete_recipe(&id).unwrap();
+        let report = pull(&vault, dir.path()).unwrap();
+        assert_eq!(report.deleted, 1);
+        assert!(!dir.path().join("gone-soon.md").exists());
+        // Files that were never pushed are left alone
+        assert!(dir.path().join("draft.md").exists());
+    }
+
+    #[test]
+    fn test_parse_errors_stop_the_sync() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = FakeVault::default();
+        std::fs::write(dir.path().join("good.md"), "# Good\n").unwrap();
+        std::fs::write(dir.path().join("bad.md"), "# Bad\n\n## Method\n").unwrap();
+
+        let error = push(&vault, dir.path(), false).unwrap_err();
+        assert!(error.ends_with("bad.md:3: Unknown section 'Method'. Expected 'Ingredients' or 'Steps'"), "{}", error);
+        assert!(vault.titles().is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_ids_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = FakeVault::default();
+        let id = vault.add("Soup");
+        pull(&vault, dir.path()).unwrap();
+        let text = std::fs::read_to_string(dir.path().join("soup.md")).unwrap();
+        std::fs::write(dir.path().join("copy.md"), text).unwrap();
+
+        let error = push(&vault, dir.path(), false).unwrap_err();
+        assert!(error.contains(&format!("both have id '{}'", id)), "{}", error);
+    }
+}
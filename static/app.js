@@ -294,6 +294,7 @@ function setupRecipePhotoUpload() {
                 return;
             }
             // Re-render the recipe to show the new photo
+            RecipeDisplay.markPhotoChanged(targetRecipeId);
             await RecipeDisplay.fetchAndDisplayRecipe(targetRecipeId);
         } catch (err) {
             console.error('Photo upload error:', err);
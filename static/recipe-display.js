@@ -13,6 +13,14 @@ export function initializeState(appState) {
     state = appState;
 }
 
+// Recipes whose photo changed this session; the version busts the browser's
+// in-page image cache, which ignores the server's revalidation headers
+const photoVersions = new Map();
+
+export function markPhotoChanged(recipeId) {
+    photoVersions.set(recipeId, Date.now());
+}
+
 export function isMobile() {
     return window.matchMedia('(max-width: 600px)').matches;
 }
@@ -412,7 +420,7 @@ export function renderRecipe(recipe) {
         // SANITIZED: recipe.title used in alt attribute
         ? `<div class="recipe-photo-container">
             <div class="recipe-photo-wrapper">
-                <img src="/api/recipes/${recipe.id}/photo?t=${Date.now()}"
+                <img src="/api/recipes/${recipe.id}/photo?size=medium${photoVersions.has(recipe.id) ? `&v=${photoVersions.get(recipe.id)}` : ''}"
                      alt="${escapeHtml(recipe.title)} photo"
                      class="recipe-photo"
                      id="recipe-photo-img"
@@ -422,6 +430,23 @@ export function renderRecipe(recipe) {
            </div>`
         : '';
 
+    // Source scans: the original card or page, kept apart from the cover
+    const sourceScans = recipe.source_scans || [];
+    // SANITIZED: scan captions are user input
+    const sourceScansHtml = sourceScans.length
+        ? `<div class="source-scans">
+            <div class="section-header">the original</div>
+            ${sourceScans.map(scan => `
+            <figure class="source-scan">
+                <img src="/api/recipes/${recipe.id}/photos/${scan.id}?size=thumb"
+                     alt="${escapeHtml(scan.caption || `Original of ${recipe.title}`)}"
+                     class="recipe-photo"
+                     data-preview-url="/api/recipes/${recipe.id}/photos/${scan.id}">
+                ${scan.caption ? `<figcaption>${escapeHtml(scan.caption)}</figcaption>` : ''}
+            </figure>`).join('')}
+           </div>`
+        : '';
+
     // SANITIZED: recipe.title is user input
     const ingredientsHtml = `
         <div class="recipe-title-row">
@@ -506,6 +531,7 @@ export function renderRecipe(recipe) {
 
         ${recipe.notes ? `<div class="recipe-note">Note: ${escapeHtml(recipe.notes)}</div>` : ''}
         ${recipe.description ? `<div class="recipe-note">${escapeHtml(recipe.description)}</div>` : ''}
+        ${sourceScansHtml}
         ${authorship}
     `;
 
@@ -0,0 +1,167 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+use common::{create_test_app_with_config, create_test_db, create_two_family_config, send_request_with_headers};
+
+const ADMIN: &[(&str, &str)] = &[("X-API-Key", "test-api-key")];
+const ALICE: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "alice@example.com")];
+const BOB: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "bob@example.com")];
+
+async fn create_key(app: &axum::Router, headers: &[(&str, &str)], input: Value) -> (StatusCode, Value) {
+    let (status, body) = send_request_with_headers(app, "POST", "/api/api-keys", Some(input), headers).await;
+    (status, body.unwrap_or_default())
+}
+
+#[tokio::test]
+async fn test_user_key_acts_as_its_owner() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+
+    let (status, created) = create_key(&app, ALICE, json!({"label": "Laptop"})).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(created["email"], "alice@example.com");
+    assert_eq!(created["scope"], "read_write");
+    let key = created["key"].as_str().unwrap().to_string();
+    assert!(key.starts_with("rv_"));
+    assert!(key.starts_with(created["prefix"].as_str().unwrap()));
+    let with_key = [("X-API-Key", key.as_str())];
+
+    // No X-User-Email needed: the key says who the caller is
+    let recipe = json!({"title": "Soda Bread", "ingredients": [{"name": "flour"}]});
+    let (status, body) = send_request_with_headers(&app, "POST", "/api/recipes", Some(recipe), &with_key).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let body = body.unwrap();
+    assert_eq!(body["created_by"], "alice@example.com");
+    assert_eq!(body["family_id"], "family-a");
+
+    // ...and can't be talked into being someone else
+    let as_bob = [("X-API-Key", key.as_str()), ("X-User-Email", "bob@example.com")];
+    let (status, _) = send_request_with_headers(&app, "GET", "/api/recipes", None, &as_bob).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let as_alice = [("X-API-Key", key.as_str()), ("X-User-Email", "Alice@Example.com")];
+    let (status, _) = send_request_with_headers(&app, "GET", "/api/recipes", None, &as_alice).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Listings never include the key
+    let (status, keys) = send_request_with_headers(&app, "GET", "/api/api-keys", None, ALICE).await;
+    assert_eq!(status, StatusCode::OK);
+    let keys = keys.unwrap();
+    assert_eq!(keys.as_array().unwrap().len(), 1);
+    assert!(keys[0].get("key").is_none());
+    assert!(keys[0].get("key_hash").is_none());
+    assert!(keys[0]["last_used_at"].is_string());
+    let (_, keys) = send_request_with_headers(&app, "GET", "/api/api-keys", None, BOB).await;
+    assert!(keys.unwrap().as_array().unwrap().is_empty());
+    let (_, keys) = send_request_with_headers(&app, "GET", "/api/api-keys", None, ADMIN).await;
+    assert_eq!(keys.unwrap().as_array().unwrap().len(), 1);
+
+    // Only the owner (or an admin) can revoke it; it stops working at once
+    let revoke = format!("/api/api-keys/{}", created["id"].as_str().unwrap());
+    let (status, _) = send_request_with_headers(&app, "DELETE", &revoke, None, BOB).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send_request_with_headers(&app, "DELETE", &revoke, None, ALICE).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send_request_with_headers(&app, "GET", "/api/recipes", None, &with_key).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_key_scopes() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+
+    let (status, created) = create_key(&app, ALICE, json!({"label": "Dashboard", "scope": "read_only"})).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let read_only = [("X-API-Key", created["key"].as_str().unwrap())];
+    let (status, _) = send_request_with_headers(&app, "GET", "/api/recipes", None, &read_only).await;
+    assert_eq!(status, StatusCode::OK);
+    let recipe = json!({"title": "Soda Bread", "ingredients": [{"name": "flour"}]});
+    let (status, _) = send_request_with_headers(&app, "POST", "/api/recipes", Some(recipe), &read_only).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Family members can't hand out admin keys or keys for other people
+    let (status, _) = create_key(&app, ALICE, json!({"label": "Root", "scope": "admin"})).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = create_key(&app, ALICE, json!({"label": "For Bob", "email": "bob@example.com"})).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send_request_with_headers(&app, "GET", "/api/admin/families", None, ALICE).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Admins can; an admin key reaches the admin endpoints without a family
+    let (status, _) = create_key(&app, ADMIN, json!({"label": "No owner"})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let input = json!({"label": "Ops", "scope": "admin", "email": "ops@example.com"});
+    let (status, created) = create_key(&app, ADMIN, input).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let admin_key = [("X-API-Key", created["key"].as_str().unwrap())];
+    let (status, _) = send_request_with_headers(&app, "GET", "/api/admin/families", None, &admin_key).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // A non-admin key for someone outside every family gets nowhere
+    let (_, created) = create_key(&app, ADMIN, json!({"label": "Guest", "email": "carol@example.com"})).await;
+    let carol = [("X-API-Key", created["key"].as_str().unwrap())];
+    let (status, _) = send_request_with_headers(&app, "GET", "/api/recipes", None, &carol).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_expired_key_is_rejected() {
+    let pool = create_test_db().await;
+    let app = create_test_app_with_config(pool.clone(), None, create_two_family_config()).await;
+
+    let (status, created) = create_key(&app, ALICE, json!({"label": "Trip", "expires_in_days": 7})).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert!(created["expires_at"].is_string());
+    let with_key = [("X-API-Key", created["key"].as_str().unwrap())];
+    let (status, _) = send_request_with_headers(&app, "GET", "/api/recipes", None, &with_key).await;
+    assert_eq!(status, StatusCode::OK);
+
+    sqlx::query("UPDATE api_keys SET expires_at = datetime('now', '-1 minutes')")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (status, _) = send_request_with_headers(&app, "GET", "/api/recipes", None, &with_key).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = create_key(&app, ALICE, json!({"label": "Forever", "expires_in_days": 0})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_keys_cannot_mint_wider_keys() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+
+    let (_, trip) = create_key(&app, ALICE, json!({"label": "Trip", "expires_in_days": 7})).await;
+    let trip_expiry = trip["expires_at"].as_str().unwrap();
+    let with_trip = [("X-API-Key", trip["key"].as_str().unwrap())];
+
+    // Keys made with an expiring key expire with it at the latest
+    let (status, forever) = create_key(&app, &with_trip, json!({"label": "Forever"})).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(forever["expires_at"], trip_expiry);
+    let (_, later) = create_key(&app, &with_trip, json!({"label": "Later", "expires_in_days": 30})).await;
+    assert_eq!(later["expires_at"], trip_expiry);
+    let (_, sooner) = create_key(&app, &with_trip, json!({"label": "Sooner", "expires_in_days": 1})).await;
+    assert!(sooner["expires_at"].as_str().unwrap() < trip_expiry);
+
+    // Read-only keys can't make keys at all, and keys can't widen their scope
+    let (_, read_only) = create_key(&app, ALICE, json!({"label": "Dashboard", "scope": "read_only"})).await;
+    let with_read_only = [("X-API-Key", read_only["key"].as_str().unwrap())];
+    let (status, _) = create_key(&app, &with_read_only, json!({"label": "Escape"})).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = create_key(&app, &with_trip, json!({"label": "Root", "scope": "admin"})).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_rotating_instance_key_is_admin_only() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+
+    let (status, _) = send_request_with_headers(&app, "POST", "/api/admin/api-key/rotate", None, ALICE).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // The test key comes from configuration, like API_KEY, so can't be rotated here
+    let (status, body) = send_request_with_headers(&app, "POST", "/api/admin/api-key/rotate", None, ADMIN).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.unwrap()["error"].as_str().unwrap().contains("API_KEY"));
+}
@@ -0,0 +1,469 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+use std::io::{Cursor, Read, Write};
+
+use common::{
+    create_test_app_with_config, create_test_db, create_two_family_config, send_bytes_request,
+    send_request_with_headers, send_text_request,
+};
+
+const ALICE: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "alice@example.com")];
+const BOB: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "bob@example.com")];
+
+/// Minimal 1x1 PNG
+const PNG: &[u8] = &[
+    0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D, 0x49, 0x48, 0x44, 0x52,
+    0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1F, 0x15, 0xC4,
+    0x89, 0x00, 0x00, 0x00, 0x0A, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9C, 0x63, 0x00, 0x01, 0x00, 0x00,
+    0x05, 0x00, 0x01, 0x0D, 0x0A, 0x2D, 0xB4, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4E, 0x44, 0xAE,
+    0x42, 0x60, 0x82,
+];
+
+async fn new_app() -> axum::Router {
+    create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await
+}
+
+async fn create_recipe(app: &axum::Router, headers: &[(&str, &str)], body: Value) -> String {
+    let (status, response) = send_request_with_headers(app, "POST", "/api/recipes", Some(body), headers).await;
+    assert_eq!(status, StatusCode::CREATED);
+    response.unwrap()["id"].as_str().unwrap().to_string()
+}
+
+async fn upload_photo(app: &axum::Router, headers: &[(&str, &str)], recipe_id: &str) {
+    let boundary = "archive-test-boundary";
+    let mut body = format!(
+        "--{}\r\nContent-Disposition: form-data; name=\"photo\"; filename=\"dish.png\"\r\nContent-Type: image/png\r\n\r\n",
+        boundary
+    )
+    .into_bytes();
+    body.extend_from_slice(PNG);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+
+    let content_type = format!("multipart/form-data; boundary={}", boundary);
+    let mut headers = headers.to_vec();
+    headers.push(("Content-Type", &content_type));
+    let (status, _, _) =
+        send_bytes_request(app, "POST", &format!("/api/recipes/{}/photo", recipe_id), &headers, body).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+/// Alice's family: two recipes (one with a photo), a share link, a shopping
+/// list link and an aisle order. Bob has one recipe of his own.
+/// Returns (curry id, share url).
+async fn seed_vault(app: &axum::Router) -> (String, String) {
+    let curry = create_recipe(
+        app,
+        ALICE,
+        json!({
+            "title": "Curry",
+            "description": "Weeknight curry",
+            "servings": 4,
+            "ingredients": [{"name": "Onion", "quantity": 1.0}, {"name": "Rice", "quantity": 200.0, "unit": "g"}],
+            "steps": [{"instruction": "Fry the onion.", "duration_minutes": 5}]
+        }),
+    )
+    .await;
+    let pilaf = create_recipe(app, ALICE, json!({"title": "Pilaf", "ingredients": [{"name": "Rice"}]})).await;
+    create_recipe(app, BOB, json!({"title": "Bob's Stew"})).await;
+    upload_photo(app, ALICE, &curry).await;
+
+    let (status, link) =
+        send_request_with_headers(app, "POST", &format!("/api/recipes/{}/share", curry), None, ALICE).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let share_url = link.unwrap()["url"].as_str().unwrap().to_string();
+
+    let (status, _) = send_request_with_headers(
+        app,
+        "POST",
+        "/api/shopping-list/share",
+        Some(json!({"recipe_ids": [curry, pilaf]})),
+        ALICE,
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, _) = send_request_with_headers(
+        app,
+        "PUT",
+        "/api/shopping-list/aisles",
+        Some(json!({"aisles": ["Pantry", "Produce"]})),
+        ALICE,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    (curry, share_url)
+}
+
+async fn export(app: &axum::Router, headers: &[(&str, &str)]) -> Vec<u8> {
+    let (status, body, response_headers) = send_bytes_request(app, "GET", "/api/export", headers, vec![]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response_headers["content-type"], "application/zip");
+    body
+}
+
+async fn import(app: &axum::Router, headers: &[(&str, &str)], query: &str, archive: Vec<u8>) -> (StatusCode, Value) {
+    let (status, body, _) =
+        send_bytes_request(app, "POST", &format!("/api/import{}", query), headers, archive).await;
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+async fn titles(app: &axum::Router, headers: &[(&str, &str)]) -> Vec<String> {
+    let (_, list) = send_request_with_headers(app, "GET", "/api/recipes", None, headers).await;
+    list.unwrap()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["title"].as_str().unwrap().to_string())
+        .collect()
+}
+
+fn read_entry(archive: &[u8], name: &str) -> Vec<u8> {
+    let mut zip = zip::ZipArchive::new(Cursor::new(archive)).unwrap();
+    let mut entry = zip.by_name(name).unwrap();
+    let mut bytes = Vec::new();
+    entry.read_to_end(&mut bytes).unwrap();
+    bytes
+}
+
+/// Copy an archive, swapping the contents of the named entries
+fn replace_entries(archive: &[u8], replacements: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut zip = zip::ZipArchive::new(Cursor::new(archive)).unwrap();
+    let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    for i in 0..zip.len() {
+        let mut entry = zip.by_index(i).unwrap();
+        let name = entry.name().to_string();
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes).unwrap();
+        let bytes = replacements
+            .iter()
+            .find(|(replaced, _)| *replaced == name)
+            .map_or(bytes, |(_, replacement)| replacement.to_vec());
+        writer.start_file(name, zip::write::SimpleFileOptions::default()).unwrap();
+        writer.write_all(&bytes).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
+
+/// A small JPEG carrying an EXIF segment with a GPS latitude, as a phone
+/// camera would write it
+fn jpeg_with_gps() -> Vec<u8> {
+    let mut jpeg = Vec::new();
+    image::DynamicImage::ImageRgb8(image::RgbImage::from_pixel(8, 8, image::Rgb([200, 120, 40])))
+        .write_to(&mut Cursor::new(&mut jpeg), image::ImageFormat::Jpeg)
+        .unwrap();
+
+    // IFD0 holds only a pointer to the GPS IFD at offset 26
+    let mut tiff = b"II\x2A\x00\x08\x00\x00\x00".to_vec();
+    tiff.extend_from_slice(&1u16.to_le_bytes());
+    tiff.extend_from_slice(&[0x25, 0x88, 0x04, 0x00, 0x01, 0x00, 0x00, 0x00, 26, 0x00, 0x00, 0x00]);
+    tiff.extend_from_slice(&0u32.to_le_bytes());
+    // GPS IFD: GPSLatitudeRef = "N"
+    tiff.extend_from_slice(&1u16.to_le_bytes());
+    tiff.extend_from_slice(&[0x01, 0x00, 0x02, 0x00, 0x02, 0x00, 0x00, 0x00, b'N', 0x00, 0x00, 0x00]);
+    tiff.extend_from_slice(&0u32.to_le_bytes());
+
+    let mut bytes = jpeg[..2].to_vec();
+    bytes.extend_from_slice(&[0xFF, 0xE1]);
+    bytes.extend_from_slice(&((2 + 6 + tiff.len()) as u16).to_be_bytes());
+    bytes.extend_from_slice(b"Exif\0\0");
+    bytes.extend_from_slice(&tiff);
+    bytes.extend_from_slice(&jpeg[2..]);
+    bytes
+}
+
+#[tokio::test]
+async fn test_export_contains_only_family_data() {
+    let app = new_app().await;
+    let (curry, _) = seed_vault(&app).await;
+
+    let (_, _, headers) = send_bytes_request(&app, "GET", "/api/export", ALICE, vec![]).await;
+    let disposition = headers["content-disposition"].to_str().unwrap();
+    assert!(disposition.starts_with("attachment; filename=\"recipe-vault-family-a-"));
+
+    let archive = export(&app, ALICE).await;
+    let manifest: Value = serde_json::from_slice(&read_entry(&archive, "manifest.json")).unwrap();
+    assert_eq!(manifest["format"], "recipe-vault-archive");
+    assert_eq!(manifest["version"], 1);
+    assert_eq!(manifest["family_id"], "family-a");
+    assert_eq!(
+        manifest["counts"],
+        json!({"recipes": 2, "photos": 1, "share_links": 1, "shopping_list_links": 1})
+    );
+
+    let recipe: Value = serde_json::from_slice(&read_entry(&archive, &format!("recipes/{}.json", curry))).unwrap();
+    assert_eq!(recipe["title"], "Curry");
+    assert_eq!(recipe["ingredients"].as_array().unwrap().len(), 2);
+    let photo = recipe["photo_filename"].as_str().unwrap();
+    assert_eq!(recipe["photos"][0]["filename"], photo);
+    // The archive holds the stored (re-encoded) photo
+    let (_, stored, _) =
+        send_bytes_request(&app, "GET", &format!("/api/recipes/{}/photo", curry), ALICE, vec![]).await;
+    assert_eq!(read_entry(&archive, &format!("photos/{}", photo)), stored);
+
+    let aisles: Value = serde_json::from_slice(&read_entry(&archive, "aisle_order.json")).unwrap();
+    assert_eq!(aisles["aisles"], json!(["Pantry", "Produce"]));
+
+    let names: Vec<String> = zip::ZipArchive::new(Cursor::new(&archive[..]))
+        .unwrap()
+        .file_names()
+        .map(String::from)
+        .collect();
+    assert_eq!(names.iter().filter(|n| n.starts_with("recipes/")).count(), 2);
+}
+
+#[tokio::test]
+async fn test_import_into_new_instance() {
+    let source = new_app().await;
+    let (_, share_url) = seed_vault(&source).await;
+    let archive = export(&source, ALICE).await;
+
+    let target = new_app().await;
+    let (status, report) = import(&target, ALICE, "", archive).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report["dry_run"], false);
+    assert_eq!(report["created"], 2);
+    assert_eq!(report["photos"], 1);
+    assert_eq!(report["share_links"], 1);
+    assert_eq!(report["shopping_list_links"], 1);
+    assert_eq!(report["aisle_order"], true);
+
+    assert_eq!(titles(&target, ALICE).await, vec!["Curry", "Pilaf"]);
+    assert!(titles(&target, BOB).await.is_empty());
+
+    let curry = report["recipes"].as_array().unwrap().iter().find(|r| r["title"] == "Curry").unwrap();
+    let curry_id = curry["recipe_id"].as_str().unwrap();
+    let (_, recipe) =
+        send_request_with_headers(&target, "GET", &format!("/api/recipes/{}", curry_id), None, ALICE).await;
+    let recipe = recipe.unwrap();
+    assert_eq!(recipe["created_by"], "alice@example.com");
+    assert_eq!(recipe["steps"][0]["duration_minutes"], 5);
+    assert_eq!(recipe["photos"].as_array().unwrap().len(), 1);
+    assert_eq!(recipe["photo_filename"], recipe["photos"][0]["filename"]);
+    assert!(recipe["photo_filename"].as_str().unwrap().ends_with(".png"));
+
+    // The share link keeps its token and points at the restored recipe
+    let (status, html, _) = send_text_request(&target, "GET", &share_url, &[]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(html.contains("Curry"));
+
+    let (_, aisles) = send_request_with_headers(&target, "GET", "/api/shopping-list/aisles", None, ALICE).await;
+    assert_eq!(aisles.unwrap()["aisles"], json!(["Pantry", "Produce"]));
+}
+
+#[tokio::test]
+async fn test_import_conflict_strategies() {
+    let app = new_app().await;
+    let (curry, _) = seed_vault(&app).await;
+    let archive = export(&app, ALICE).await;
+
+    // Edit the recipe after the export
+    let (status, _) = send_request_with_headers(
+        &app,
+        "PUT",
+        &format!("/api/recipes/{}", curry),
+        Some(json!({"description": "Changed", "servings": 2})),
+        ALICE,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Dry run: nothing written
+    let (_, report) = import(&app, ALICE, "?conflict=rename&dry_run=true", archive.clone()).await;
+    assert_eq!(report["dry_run"], true);
+    assert_eq!(report["renamed"], 2);
+    assert_eq!(report["recipes"][0]["imported_title"], "Curry (2)");
+    assert!(report["recipes"][0].get("recipe_id").is_none());
+    assert_eq!(titles(&app, ALICE).await, vec!["Curry", "Pilaf"]);
+
+    // Skip is the default
+    let (_, report) = import(&app, ALICE, "", archive.clone()).await;
+    assert_eq!(report["skipped"], 2);
+    assert_eq!(report["recipes"][0]["action"], "skipped");
+    assert_eq!(titles(&app, ALICE).await, vec!["Curry", "Pilaf"]);
+
+    // Overwrite restores the exported content in place
+    let (_, report) = import(&app, ALICE, "?conflict=overwrite", archive.clone()).await;
+    assert_eq!(report["overwritten"], 2);
+    assert_eq!(report["recipes"][0]["recipe_id"], curry);
+    let (_, recipe) =
+        send_request_with_headers(&app, "GET", &format!("/api/recipes/{}", curry), None, ALICE).await;
+    let recipe = recipe.unwrap();
+    assert_eq!(recipe["description"], "Weeknight curry");
+    assert_eq!(recipe["servings"], 4);
+    assert_eq!(recipe["photos"].as_array().unwrap().len(), 1);
+    assert_eq!(recipe["photo_filename"], recipe["photos"][0]["filename"]);
+
+    // Rename keeps both copies
+    let (_, report) = import(&app, ALICE, "?conflict=rename", archive).await;
+    assert_eq!(report["renamed"], 2);
+    assert_eq!(titles(&app, ALICE).await, vec!["Curry", "Curry (2)", "Pilaf", "Pilaf (2)"]);
+}
+
+#[tokio::test]
+async fn test_import_cannot_overwrite_other_family() {
+    let app = new_app().await;
+    seed_vault(&app).await;
+    let archive = export(&app, ALICE).await;
+
+    let (_, report) = import(&app, BOB, "?conflict=overwrite", archive).await;
+    assert_eq!(report["overwritten"], 0);
+    assert_eq!(report["skipped"], 2);
+    assert_eq!(report["recipes"][0]["reason"], "The title is used by a recipe outside your family");
+    assert_eq!(titles(&app, BOB).await, vec!["Bob's Stew"]);
+}
+
+#[tokio::test]
+async fn test_import_rejects_invalid_archives() {
+    let app = new_app().await;
+
+    let (status, body) = import(&app, ALICE, "", b"not a zip".to_vec()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "VALIDATION_ERROR");
+
+    let (status, _) = import(&app, ALICE, "", vec![]).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _, _) = send_bytes_request(&app, "POST", "/api/import?conflict=merge", ALICE, b"x".to_vec()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_import_cleans_archived_photos() {
+    let source = new_app().await;
+    let curry = create_recipe(&source, ALICE, json!({"title": "Curry"})).await;
+    let pilaf = create_recipe(&source, ALICE, json!({"title": "Pilaf"})).await;
+    upload_photo(&source, ALICE, &curry).await;
+    upload_photo(&source, ALICE, &pilaf).await;
+    let archive = export(&source, ALICE).await;
+
+    let photo_entry = |id: &str| {
+        let recipe: Value = serde_json::from_slice(&read_entry(&archive, &format!("recipes/{}.json", id))).unwrap();
+        format!("photos/{}", recipe["photo_filename"].as_str().unwrap())
+    };
+    let (curry_photo, pilaf_photo) = (photo_entry(&curry), photo_entry(&pilaf));
+    let gps = jpeg_with_gps();
+    assert!(gps.windows(6).any(|w| w == b"Exif\0\0"));
+    let archive = replace_entries(
+        &archive,
+        &[(&curry_photo, &gps), (&pilaf_photo, b"<script>alert(1)</script>")],
+    );
+
+    let target = new_app().await;
+    let (status, report) = import(&target, ALICE, "", archive).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report["created"], 2);
+    assert_eq!(report["photos"], 1);
+    let warnings = report["warnings"].as_array().unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].as_str().unwrap().contains("for 'Pilaf' was skipped"));
+
+    let restored_id = |title: &str| {
+        report["recipes"].as_array().unwrap().iter().find(|r| r["title"] == title).unwrap()["recipe_id"]
+            .as_str()
+            .unwrap()
+            .to_string()
+    };
+
+    // The JPEG is kept, without its metadata
+    let (_, recipe) =
+        send_request_with_headers(&target, "GET", &format!("/api/recipes/{}", restored_id("Curry")), None, ALICE).await;
+    let recipe = recipe.unwrap();
+    assert!(recipe["photo_filename"].as_str().unwrap().ends_with(".jpg"));
+    let (status, stored, _) = send_bytes_request(
+        &target,
+        "GET",
+        &format!("/api/recipes/{}/photo", restored_id("Curry")),
+        ALICE,
+        vec![],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(image::load_from_memory(&stored).is_ok());
+    assert!(!stored.windows(4).any(|w| w == b"Exif"));
+
+    // The disguised file never reaches the photo store
+    let (_, recipe) =
+        send_request_with_headers(&target, "GET", &format!("/api/recipes/{}", restored_id("Pilaf")), None, ALICE).await;
+    let recipe = recipe.unwrap();
+    assert!(recipe["photos"].as_array().unwrap().is_empty());
+    assert!(recipe["photo_filename"].is_null());
+}
+
+#[tokio::test]
+async fn test_import_restores_collection_share_links() {
+    let source = new_app().await;
+    let (curry, _) = seed_vault(&source).await;
+    let stew = create_recipe(&source, ALICE, json!({"title": "Stew"})).await;
+    let plan = json!({"title": "Sunday lunch", "meal_plan": {"guest_count": 6, "recipes": [
+        {"recipe_id": stew, "role": "centrepiece"},
+        {"recipe_id": curry, "role": "side"}
+    ]}});
+    let (status, link) = send_request_with_headers(&source, "POST", "/api/share-links", Some(plan), ALICE).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let plan_url = link.unwrap()["url"].as_str().unwrap().to_string();
+    let archive = export(&source, ALICE).await;
+
+    let links: Value = serde_json::from_slice(&read_entry(&archive, "share_links.json")).unwrap();
+    let archived = links.as_array().unwrap().iter().find(|l| l["kind"] == "meal_plan").unwrap();
+    assert_eq!(archived["recipes"], json!([{"recipe_id": stew, "role": "centrepiece"}, {"recipe_id": curry, "role": "side"}]));
+
+    let target = new_app().await;
+    let (status, report) = import(&target, ALICE, "", archive).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report["share_links"], 2);
+    assert!(report["warnings"].as_array().unwrap().is_empty());
+
+    // The plan points at the restored recipes, in the same order and roles
+    let (status, page, _) = send_text_request(&target, "GET", &plan_url, &[]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(page.contains("for 6 guests"));
+    assert!(page.find("Stew").unwrap() < page.find("Curry").unwrap());
+    let restored_stew = report["recipes"].as_array().unwrap().iter().find(|r| r["title"] == "Stew").unwrap()
+        ["recipe_id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert_ne!(restored_stew, stew);
+    assert!(page.contains(&format!("{}/recipes/{}", plan_url, restored_stew)));
+    let (status, _, _) =
+        send_text_request(&target, "GET", &format!("{}/recipes/{}", plan_url, restored_stew), &[]).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_import_does_not_trust_archived_share_link_owners_or_passphrases() {
+    const ALICE2: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "alice2@example.com")];
+    let source = new_app().await;
+    let (curry, share_url) = seed_vault(&source).await;
+    let (status, protected) = send_request_with_headers(
+        &source,
+        "POST",
+        &format!("/api/recipes/{}/share", curry),
+        Some(json!({"passphrase": "garam masala"})),
+        ALICE,
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let protected_token = protected.unwrap()["token"].as_str().unwrap().to_string();
+    let archive = export(&source, ALICE).await;
+
+    // Alice2 restores Alice's archive
+    let target = new_app().await;
+    let (status, report) = import(&target, ALICE2, "", archive).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report["share_links"], 1);
+    let warnings = report["warnings"].to_string();
+    assert!(warnings.contains(&protected_token) && warnings.contains("passphrase"), "{}", warnings);
+
+    let (_, links) = send_request_with_headers(&target, "GET", "/api/share-links", None, ALICE2).await;
+    let links = links.unwrap();
+    let links = links.as_array().unwrap();
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0]["created_by"], "alice2@example.com");
+    assert!(share_url.ends_with(links[0]["token"].as_str().unwrap()));
+    let (status, _, _) = send_text_request(&target, "GET", &format!("/share/{}", protected_token), &[]).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
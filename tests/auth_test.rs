@@ -1,3 +1,5 @@
+mod common;
+
 use axum::{
     body::Body,
     http::{Request, StatusCode},
@@ -5,16 +7,27 @@ use axum::{
     Router,
     middleware,
 };
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{EncodingKey, Header};
+use rsa::{pkcs1::EncodeRsaPrivateKey, traits::PublicKeyParts, RsaPrivateKey};
+use serde_json::{json, Value};
 use std::io::Write;
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, OnceLock,
+};
 use tower::ServiceExt;
 
 use recipe_vault::{
     auth::{cloudflare_auth, CloudflareAuthState},
-    config::FamiliesConfig,
+    cloudflare_access::AccessVerifier,
+    config::{CloudflareAccessConfig, FamiliesConfig, JwksSource},
     handlers::ui::{self, UiState},
 };
 
+const AUDIENCE: &str = "test-aud-tag";
+const ISSUER: &str = "https://test.cloudflareaccess.com";
+
 fn test_families_config() -> FamiliesConfig {
     let yaml = r#"
 families:
@@ -29,12 +42,17 @@ families:
 }
 
 // Helper to create the UI app with cloudflare auth middleware
-fn create_ui_app(dev_email: Option<String>) -> Router {
+async fn create_ui_app(dev_email: Option<String>) -> Router {
+    create_ui_app_with_access(dev_email, None).await
+}
+
+async fn create_ui_app_with_access(dev_email: Option<String>, access: Option<AccessVerifier>) -> Router {
     let state = UiState {};
 
     let cloudflare_auth_state = CloudflareAuthState {
         dev_user_email: dev_email,
-        families_config: Arc::new(test_families_config()),
+        families: common::create_test_family_directory(test_families_config()).await,
+        access,
     };
 
     Router::new()
@@ -49,7 +67,7 @@ fn create_ui_app(dev_email: Option<String>) -> Router {
 #[tokio::test]
 async fn test_cloudflare_identity_extraction() {
     // No dev email, but provide header
-    let app = create_ui_app(None);
+    let app = create_ui_app(None).await;
 
     let request = Request::builder()
         .method("GET")
@@ -71,7 +89,7 @@ async fn test_cloudflare_identity_extraction() {
 #[tokio::test]
 async fn test_dev_user_email_fallback() {
     // Dev email provided, no header
-    let app = create_ui_app(Some("dev@example.com".to_string()));
+    let app = create_ui_app(Some("dev@example.com".to_string())).await;
 
     let request = Request::builder()
         .method("GET")
@@ -91,7 +109,7 @@ async fn test_dev_user_email_fallback() {
 #[tokio::test]
 async fn test_unauthenticated_access() {
     // No dev email, no header
-    let app = create_ui_app(None);
+    let app = create_ui_app(None).await;
 
     let request = Request::builder()
         .method("GET")
@@ -107,3 +125,229 @@ async fn test_unauthenticated_access() {
     let body_str = String::from_utf8(body_bytes.to_vec()).unwrap();
     assert!(body_str.contains("Authentication required via Cloudflare Access"));
 }
+
+/// An RSA signing key made for the test run, with its public half as a JWK
+struct TestKey {
+    kid: String,
+    encoding_key: EncodingKey,
+    jwk: Value,
+}
+
+impl TestKey {
+    fn generate(kid: &str) -> Self {
+        let private_key = RsaPrivateKey::new(&mut rand::thread_rng(), 2048).unwrap();
+        let der = private_key.to_pkcs1_der().unwrap();
+        Self {
+            kid: kid.to_string(),
+            encoding_key: EncodingKey::from_rsa_der(der.as_bytes()),
+            jwk: json!({
+                "kty": "RSA",
+                "kid": kid,
+                "alg": "RS256",
+                "use": "sig",
+                "n": URL_SAFE_NO_PAD.encode(private_key.n().to_bytes_be()),
+                "e": URL_SAFE_NO_PAD.encode(private_key.e().to_bytes_be()),
+            }),
+        }
+    }
+
+    /// A token as Cloudflare Access would issue it, signed with this key
+    fn sign(&self, email: &str, audience: &str, expires_in_secs: i64) -> String {
+        self.sign_as(&self.kid, email, audience, expires_in_secs)
+    }
+
+    /// Signed with this key, but claiming to be signed with key `kid`
+    fn sign_as(&self, kid: &str, email: &str, audience: &str, expires_in_secs: i64) -> String {
+        self.sign_claims(kid, email, audience, ISSUER, expires_in_secs)
+    }
+
+    /// Signed with this key, but issued by another team
+    fn sign_issued_by(&self, issuer: &str, email: &str, audience: &str) -> String {
+        self.sign_claims(&self.kid, email, audience, issuer, 300)
+    }
+
+    fn sign_claims(&self, kid: &str, email: &str, audience: &str, issuer: &str, expires_in_secs: i64) -> String {
+        let now = chrono::Utc::now().timestamp();
+        let claims = json!({
+            "aud": [audience],
+            "email": email,
+            "exp": now + expires_in_secs,
+            "iat": now,
+            "iss": issuer,
+            "sub": "user-id",
+        });
+        let mut header = Header::new(jsonwebtoken::Algorithm::RS256);
+        header.kid = Some(kid.to_string());
+        jsonwebtoken::encode(&header, &claims, &self.encoding_key).unwrap()
+    }
+}
+
+/// Keys are slow to generate, so every test shares the same two
+fn test_keys() -> &'static (TestKey, TestKey) {
+    static KEYS: OnceLock<(TestKey, TestKey)> = OnceLock::new();
+    KEYS.get_or_init(|| (TestKey::generate("key-1"), TestKey::generate("key-2")))
+}
+
+fn access_verifier(jwks: JwksSource) -> AccessVerifier {
+    let config = CloudflareAccessConfig {
+        audiences: vec![AUDIENCE.to_string()],
+        issuer: ISSUER.to_string(),
+        jwks,
+    };
+    AccessVerifier::new(config, reqwest::Client::new())
+}
+
+/// GET /chat with the given headers; the status and page
+async fn get_chat(app: &Router, headers: &[(&str, &str)]) -> (StatusCode, String) {
+    let mut request = Request::builder().method("GET").uri("/chat");
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    let response = app.clone().oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn test_access_token_identifies_user() {
+    let (key, _) = test_keys();
+    let mut jwks_file = tempfile::NamedTempFile::new().unwrap();
+    write!(jwks_file, "{}", json!({"keys": [key.jwk]})).unwrap();
+    let verifier = access_verifier(JwksSource::File(jwks_file.path().to_path_buf()));
+    let app = create_ui_app_with_access(None, Some(verifier)).await;
+
+    // The email comes from the token; the plain header is ignored
+    let token = key.sign("User@Example.com", AUDIENCE, 300);
+    let (status, page) = get_chat(
+        &app,
+        &[("Cf-Access-Jwt-Assertion", &token), ("Cf-Access-Authenticated-User-Email", "dev@example.com")],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(page.contains("user@example.com"));
+    assert!(!page.contains("dev@example.com"));
+}
+
+#[tokio::test]
+async fn test_access_rejects_unverified_identity() {
+    let (key, other_key) = test_keys();
+    let mut jwks_file = tempfile::NamedTempFile::new().unwrap();
+    write!(jwks_file, "{}", json!({"keys": [key.jwk]})).unwrap();
+    let verifier = access_verifier(JwksSource::File(jwks_file.path().to_path_buf()));
+    let app = create_ui_app_with_access(None, Some(verifier)).await;
+
+    // Someone reaching the origin directly can only set the header
+    let (status, _) = get_chat(&app, &[("Cf-Access-Authenticated-User-Email", "user@example.com")]).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let rejected = [
+        key.sign("user@example.com", "another-app", 300),
+        key.sign("user@example.com", AUDIENCE, -300),
+        other_key.sign("user@example.com", AUDIENCE, 300),
+        other_key.sign_as(&key.kid, "user@example.com", AUDIENCE, 300),
+        "not-a-jwt".to_string(),
+    ];
+    for token in rejected {
+        let (status, _) = get_chat(&app, &[("Cf-Access-Jwt-Assertion", &token)]).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{}", token);
+    }
+}
+
+#[tokio::test]
+async fn test_access_rejects_token_from_another_team() {
+    let (key, _) = test_keys();
+    let mut jwks_file = tempfile::NamedTempFile::new().unwrap();
+    write!(jwks_file, "{}", json!({"keys": [key.jwk]})).unwrap();
+    let verifier = access_verifier(JwksSource::File(jwks_file.path().to_path_buf()));
+    let app = create_ui_app_with_access(None, Some(verifier)).await;
+
+    // Right key and audience, but issued for a different team domain
+    let token = key.sign_issued_by("https://other-team.cloudflareaccess.com", "user@example.com", AUDIENCE);
+    let (status, _) = get_chat(&app, &[("Cf-Access-Jwt-Assertion", &token)]).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let token = key.sign_issued_by(ISSUER, "user@example.com", AUDIENCE);
+    let (status, _) = get_chat(&app, &[("Cf-Access-Jwt-Assertion", &token)]).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_access_keys_fetched_from_url_and_cached() {
+    let (key, _) = test_keys();
+    let fetches = Arc::new(AtomicUsize::new(0));
+    let jwks = json!({"keys": [key.jwk]});
+    let counter = fetches.clone();
+    let jwks_app = Router::new().route(
+        "/cdn-cgi/access/certs",
+        get(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let jwks = jwks.clone();
+            async move { axum::Json(jwks) }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/cdn-cgi/access/certs", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, jwks_app).await.unwrap() });
+
+    let app = create_ui_app_with_access(None, Some(access_verifier(JwksSource::Url(url)))).await;
+    let token = key.sign("user@example.com", AUDIENCE, 300);
+    for _ in 0..3 {
+        let (status, _) = get_chat(&app, &[("Cf-Access-Jwt-Assertion", &token)]).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    assert_eq!(fetches.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_access_ignores_dev_user_email() {
+    let (key, other_key) = test_keys();
+    let mut jwks_file = tempfile::NamedTempFile::new().unwrap();
+    write!(jwks_file, "{}", json!({"keys": [key.jwk]})).unwrap();
+    let verifier = access_verifier(JwksSource::File(jwks_file.path().to_path_buf()));
+    let app = create_ui_app_with_access(Some("dev@example.com".to_string()), Some(verifier)).await;
+
+    // Without a valid token nobody is signed in, not even the dev user
+    let (status, page) = get_chat(&app, &[]).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert!(!page.contains("dev@example.com"));
+    let forged = other_key.sign("user@example.com", AUDIENCE, 300);
+    let (status, _) = get_chat(&app, &[("Cf-Access-Jwt-Assertion", &forged)]).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = get_chat(&app, &[("Cf-Access-Authenticated-User-Email", "user@example.com")]).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_access_requests_wait_for_keys_being_loaded() {
+    let (key, _) = test_keys();
+    let fetches = Arc::new(AtomicUsize::new(0));
+    let jwks = json!({"keys": [key.jwk]});
+    let counter = fetches.clone();
+    // A slow key endpoint, so every request arrives while the keys load
+    let jwks_app = Router::new().route(
+        "/cdn-cgi/access/certs",
+        get(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let jwks = jwks.clone();
+            async move {
+                tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+                axum::Json(jwks)
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/cdn-cgi/access/certs", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, jwks_app).await.unwrap() });
+
+    let app = create_ui_app_with_access(None, Some(access_verifier(JwksSource::Url(url)))).await;
+    let token = key.sign("user@example.com", AUDIENCE, 300);
+    let requests = (0..5).map(|_| {
+        let app = app.clone();
+        let token = token.clone();
+        async move { get_chat(&app, &[("Cf-Access-Jwt-Assertion", &token)]).await.0 }
+    });
+    let statuses = futures::future::join_all(requests).await;
+    assert!(statuses.iter().all(|status| *status == StatusCode::OK), "{:?}", statuses);
+    assert_eq!(fetches.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,143 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use recipe_vault::ai::Message;
+use recipe_vault::chat::{ChatError, ChatState};
+use recipe_vault::config::PhotoStorage;
+
+use common::{
+    create_test_app_with_config, create_test_config, create_test_db, create_two_family_config,
+    send_request_with_headers, test_photos_dir,
+};
+
+const MCP_BINARY: &str = env!("CARGO_BIN_EXE_recipe-vault-mcp");
+
+fn chat_state(pool: sqlx::SqlitePool, port: u16, max_agents: usize, idle_minutes: u64) -> ChatState {
+    let mut config = create_test_config(
+        Some(create_two_family_config()),
+        test_photos_dir().to_str().unwrap(),
+        PhotoStorage::Local,
+    );
+    config.bind_address = format!("127.0.0.1:{}", port);
+    config.chat_max_agents = max_agents;
+    config.chat_agent_idle_minutes = idle_minutes;
+    ChatState::new(config, pool, reqwest::Client::new()).with_mcp_binary(MCP_BINARY)
+}
+
+fn ask(text: &str) -> Vec<Message> {
+    vec![Message::User {
+        content: vec![recipe_vault::ai::ContentBlock::Text { text: text.to_string() }],
+    }]
+}
+
+/// The raw list_recipes output the agent saw while answering
+fn tool_output(messages: &[Message]) -> String {
+    messages
+        .iter()
+        .filter_map(|message| match message {
+            Message::Tool { tool_results } => Some(tool_results),
+            _ => None,
+        })
+        .flatten()
+        .map(|result| result.content.clone())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_each_family_chats_with_its_own_recipes() {
+    let pool = create_test_db().await;
+    let app = create_test_app_with_config(pool.clone(), None, create_two_family_config()).await;
+    for (email, title) in [("alice@example.com", "Alice Stew"), ("bob@example.com", "Bob Pie")] {
+        let headers = [("Cf-Access-Authenticated-User-Email", email)];
+        let recipe = json!({"title": title, "ingredients": [], "steps": []});
+        let (status, _) = send_request_with_headers(&app, "POST", "/api/recipes", Some(recipe), &headers).await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    // The MCP children call back into the API over HTTP
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    let state = chat_state(pool.clone(), port, 10, 30);
+
+    // Alice chats first; Bob must not inherit her agent
+    let alice = state.get_or_create_agent("alice@example.com").await.unwrap();
+    let (_, tools_used, .., messages) = alice.chat(&ask("list my recipes")).await.unwrap();
+    assert_eq!(tools_used, vec!["list_recipes"]);
+    let seen = tool_output(&messages);
+    assert!(seen.contains("Alice Stew"), "{}", seen);
+    assert!(!seen.contains("Bob Pie"), "{}", seen);
+
+    let bob = state.get_or_create_agent("Bob@Example.com").await.unwrap();
+    let (.., messages) = bob.chat(&ask("list my recipes")).await.unwrap();
+    let seen = tool_output(&messages);
+    assert!(seen.contains("Bob Pie"), "{}", seen);
+    assert!(!seen.contains("Alice Stew"), "{}", seen);
+
+    // Later chats reuse the same agent
+    let again = state.get_or_create_agent("bob@example.com").await.unwrap();
+    assert!(std::sync::Arc::ptr_eq(&bob, &again));
+    assert_eq!(state.agent_count().await, 2);
+}
+
+#[tokio::test]
+async fn test_agent_limit_and_idle_eviction() {
+    // No API server is needed to start agents, only to run recipe tools
+    let pool = create_test_db().await;
+    let state = chat_state(pool.clone(), 9, 1, 30);
+
+    let alice = state.get_or_create_agent("alice@example.com").await.unwrap();
+    // Alice's chat is still running, so there's no room for Bob
+    let err = state.get_or_create_agent("bob@example.com").await.err().unwrap();
+    assert!(matches!(err, ChatError::Busy(_)));
+    assert_eq!(state.agent_count().await, 1);
+
+    // Once her chat finishes, her idle agent makes way
+    drop(alice);
+    let bob = state.get_or_create_agent("bob@example.com").await.unwrap();
+    assert_eq!(state.agent_count().await, 1);
+
+    // Idle agents are only stopped after the timeout
+    drop(bob);
+    assert_eq!(state.evict_idle().await, 0);
+    let state = chat_state(pool.clone(), 9, 1, 0);
+    state.get_or_create_agent("bob@example.com").await.unwrap();
+    assert_eq!(state.evict_idle().await, 1);
+    assert_eq!(state.agent_count().await, 0);
+}
+
+/// Active keys minted for chat agents, by owner
+async fn active_agent_keys(pool: &sqlx::SqlitePool) -> Vec<String> {
+    sqlx::query_scalar("SELECT email FROM api_keys WHERE chat_agent = 1 AND revoked_at IS NULL ORDER BY email")
+        .fetch_all(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_agents_get_their_own_keys() {
+    let pool = create_test_db().await;
+    let state = chat_state(pool.clone(), 9, 10, 0);
+
+    // Each agent gets a key of its own, which people don't see among theirs
+    let alice = state.get_or_create_agent("Alice@Example.com").await.unwrap();
+    assert_eq!(active_agent_keys(&pool).await, vec!["alice@example.com"]);
+    let listed = recipe_vault::db::queries::list_api_keys(&pool, Some("alice@example.com")).await.unwrap();
+    assert!(listed.is_empty());
+
+    // Stopping the agent revokes its key
+    drop(alice);
+    assert_eq!(state.evict_idle().await, 1);
+    assert!(active_agent_keys(&pool).await.is_empty());
+
+    // Keys left over from a previous run are revoked at startup
+    let _bob = state.get_or_create_agent("bob@example.com").await.unwrap();
+    let restarted = chat_state(pool.clone(), 9, 10, 0);
+    assert_eq!(restarted.revoke_stale_agent_keys().await.unwrap(), 1);
+    assert!(active_agent_keys(&pool).await.is_empty());
+}
@@ -9,10 +9,9 @@ use axum::{
 };
 use rstest::*;
 use serde_json::json;
-use std::sync::Arc;
 use tower::ServiceExt;
 
-use recipe_vault::auth::{api_key_auth, ApiKeyState};
+use recipe_vault::auth::{api_key_auth, ApiKeyState, LegacyApiKey};
 use recipe_vault::config::FamiliesConfig;
 
 fn test_families_config() -> FamiliesConfig {
@@ -24,10 +23,11 @@ fn test_families_config() -> FamiliesConfig {
 }
 
 // ==== Helper to create a test app with auth middleware ====
-fn create_auth_test_app(api_key: &str) -> Router {
+async fn create_auth_test_app(api_key: &str) -> Router {
     let api_key_state = ApiKeyState {
-        key: Arc::new(api_key.to_string()),
-        families_config: Arc::new(test_families_config()),
+        pool: common::create_test_db().await,
+        legacy_key: LegacyApiKey::new(api_key.to_string(), None),
+        families: common::create_test_family_directory(test_families_config()).await,
         dev_user_email: None,
     };
 
@@ -70,7 +70,7 @@ async fn send_auth_request(
 #[rstest]
 #[tokio::test]
 async fn test_chat_missing_auth_returns_401() {
-    let app = create_auth_test_app("test-api-key-12345");
+    let app = create_auth_test_app("test-api-key-12345").await;
 
     let (status, body) = send_auth_request(&app, None).await;
 
@@ -82,7 +82,7 @@ async fn test_chat_missing_auth_returns_401() {
 #[rstest]
 #[tokio::test]
 async fn test_chat_invalid_api_key_returns_401() {
-    let app = create_auth_test_app("correct-api-key");
+    let app = create_auth_test_app("correct-api-key").await;
 
     let (status, body) = send_auth_request(&app, Some("wrong-api-key")).await;
 
@@ -95,7 +95,7 @@ async fn test_chat_invalid_api_key_returns_401() {
 #[tokio::test]
 async fn test_chat_valid_api_key_accepted() {
     let api_key = "my-valid-api-key-12345";
-    let app = create_auth_test_app(api_key);
+    let app = create_auth_test_app(api_key).await;
 
     let (status, body) = send_auth_request(&app, Some(api_key)).await;
 
@@ -107,7 +107,7 @@ async fn test_chat_valid_api_key_accepted() {
 #[rstest]
 #[tokio::test]
 async fn test_chat_api_key_case_sensitive() {
-    let app = create_auth_test_app("CaseSensitiveKey123");
+    let app = create_auth_test_app("CaseSensitiveKey123").await;
 
     // Wrong case should fail
     let (status, _) = send_auth_request(&app, Some("casesensitivekey123")).await;
@@ -9,6 +9,8 @@ use std::io::Write;
 use std::sync::Arc;
 use tower::ServiceExt;
 
+use recipe_vault::config::PhotoStorage;
+
 /// Create an in-memory test database with migrations
 #[allow(dead_code)]
 pub async fn create_test_db() -> SqlitePool {
@@ -59,48 +61,100 @@ families:
 
 /// Create test router with database pool (single-family, backward compatible)
 #[allow(dead_code)]
-pub fn create_test_app(pool: SqlitePool) -> Router {
-    create_test_app_with_email(pool, "test@example.com")
+pub async fn create_test_app(pool: SqlitePool) -> Router {
+    create_test_app_with_email(pool, "test@example.com").await
 }
 
 /// Create test router with a specific dev user email and auto-generated families config
 #[allow(dead_code)]
-pub fn create_test_app_with_email(pool: SqlitePool, email: &str) -> Router {
+pub async fn create_test_app_with_email(pool: SqlitePool, email: &str) -> Router {
     let families_config = create_test_families_config();
-    create_test_app_with_config(pool, Some(email.to_string()), families_config)
+    create_test_app_with_config(pool, Some(email.to_string()), families_config).await
+}
+
+/// A family directory backed by a fresh test database holding `families_config`
+#[allow(dead_code)]
+pub async fn create_test_family_directory(
+    families_config: recipe_vault::config::FamiliesConfig,
+) -> recipe_vault::families::FamilyDirectory {
+    let families = recipe_vault::families::FamilyDirectory::new(create_test_db().await);
+    families.import_config(&families_config).await.unwrap();
+    families
+}
+
+/// Directory the test apps store photos in (shared; files are named by recipe ID)
+#[allow(dead_code)]
+pub fn test_photos_dir() -> std::path::PathBuf {
+    std::env::temp_dir().join("recipe-vault-test-photos")
 }
 
 /// Create test router with custom families config (for multi-family tests)
 #[allow(dead_code)]
-pub fn create_test_app_with_config(
+pub async fn create_test_app_with_config(
     pool: SqlitePool,
     dev_email: Option<String>,
     families_config: recipe_vault::config::FamiliesConfig,
 ) -> Router {
-    use recipe_vault::auth::{api_key_auth, cloudflare_auth, ApiKeyState, CloudflareAuthState};
-    use recipe_vault::handlers::recipes;
-    use recipe_vault::config::{Config, LlmProviderKind};
-    use axum::middleware;
+    build_test_app(pool, dev_email, families_config, test_photos_dir(), PhotoStorage::Local, |_| {}).await
+}
 
-    let families_config = Arc::new(families_config.clone());
+/// Create multi-family test router with rate limits, LLM quotas and other
+/// settings set by `configure`
+#[allow(dead_code)]
+pub async fn create_test_app_with_limits(
+    pool: SqlitePool,
+    configure: impl FnOnce(&mut recipe_vault::config::Config),
+) -> Router {
+    build_test_app(
+        pool,
+        None,
+        create_two_family_config(),
+        test_photos_dir(),
+        PhotoStorage::Local,
+        configure,
+    )
+    .await
+}
 
-    let api_key_state = ApiKeyState {
-        key: Arc::new("test-api-key".to_string()),
-        families_config: families_config.clone(),
-        dev_user_email: dev_email.clone(),
-    };
+/// Create test router that stores photos in its own directory, for tests
+/// that inspect or clean up the whole photos directory
+#[allow(dead_code)]
+pub async fn create_test_app_with_photos_dir(pool: SqlitePool, photos_dir: &std::path::Path) -> Router {
+    build_test_app(
+        pool,
+        Some("test@example.com".to_string()),
+        create_test_families_config(),
+        photos_dir.to_path_buf(),
+        PhotoStorage::Local,
+        |_| {},
+    )
+    .await
+}
 
-    let cloudflare_auth_state = CloudflareAuthState {
-        dev_user_email: dev_email,
-        families_config: families_config.clone(),
-    };
+/// Create test router with a non-default photo storage backend
+#[allow(dead_code)]
+pub async fn create_test_app_with_photo_storage(pool: SqlitePool, photo_storage: PhotoStorage) -> Router {
+    build_test_app(
+        pool,
+        Some("test@example.com".to_string()),
+        create_test_families_config(),
+        test_photos_dir(),
+        photo_storage,
+        |_| {},
+    )
+    .await
+}
 
-    // Create RecipeState with Config for handlers
-    // Use a temporary directory for photo storage in tests
-    let photos_dir = std::env::temp_dir().join("recipe-vault-test-photos");
-    std::fs::create_dir_all(&photos_dir).ok();
+/// Server configuration for tests, using the mock LLM
+#[allow(dead_code)]
+pub fn create_test_config(
+    families_config: Option<recipe_vault::config::FamiliesConfig>,
+    photos_dir: &str,
+    photo_storage: PhotoStorage,
+) -> recipe_vault::config::Config {
+    use recipe_vault::config::{Config, LlmProviderKind};
 
-    let config = Config {
+    Config {
         database_url: ":memory:".to_string(),
         bind_address: "127.0.0.1:3000".to_string(),
         anthropic_api_key: Some("test-key".to_string()),
@@ -111,17 +165,148 @@ pub fn create_test_app_with_config(
         difficulty_model: "test-model".to_string(),
         mock_llm: true,
         mock_recipe_id: None,
-        families_config: (*families_config).clone(),
+        families_config,
         dev_user_email: None,
-        photos_dir: photos_dir.to_str().unwrap().to_string(),
+        photos_dir: photos_dir.to_string(),
+        photo_storage,
+        photo_check_interval_hours: None,
+        chat_max_agents: 10,
+        chat_agent_idle_minutes: 30,
+        cloudflare_access: None,
+        rate_limits: Default::default(),
+        llm_daily_calls_per_family: None,
+        // Tests serve pages from local listeners
+        import_private_hosts: vec!["127.0.0.1".to_string()],
+    }
+}
+
+async fn build_test_app(
+    pool: SqlitePool,
+    dev_email: Option<String>,
+    families_config: recipe_vault::config::FamiliesConfig,
+    photos_dir: std::path::PathBuf,
+    photo_storage: PhotoStorage,
+    configure: impl FnOnce(&mut recipe_vault::config::Config),
+) -> Router {
+    use recipe_vault::auth::{api_key_auth, cloudflare_auth, ApiKeyState, CloudflareAuthState, LegacyApiKey};
+    use recipe_vault::families::FamilyDirectory;
+    use recipe_vault::handlers::{admin, api_keys, archive, cookbook, families, gallery, import, recipes, share, shopping};
+    use recipe_vault::rate_limit::{rate_limit, RateLimiter};
+    use axum::middleware;
+
+    let families = FamilyDirectory::new(pool.clone());
+    families.import_config(&families_config).await.unwrap();
+
+    let legacy_key = LegacyApiKey::new("test-api-key".to_string(), None);
+    let api_key_state = ApiKeyState {
+        pool: pool.clone(),
+        legacy_key: legacy_key.clone(),
+        families: families.clone(),
+        dev_user_email: dev_email.clone(),
+    };
+
+    let cloudflare_auth_state = CloudflareAuthState {
+        dev_user_email: dev_email,
+        families: families.clone(),
+        access: None,
     };
 
+    // Create RecipeState with Config for handlers
+    std::fs::create_dir_all(&photos_dir).ok();
+
+    let mut config = create_test_config(
+        Some(families_config),
+        photos_dir.to_str().unwrap(),
+        photo_storage,
+    );
+    configure(&mut config);
+
+    let mutation_limiter =
+        RateLimiter::new("recipe changes", config.rate_limits.mutations_per_minute).writes_only();
+    let share_limiter = RateLimiter::new("share link changes", config.rate_limits.shares_per_minute).writes_only();
+
+    let photo_store = recipe_vault::photo_store::from_config(&config).unwrap();
+    let llm_quota = recipe_vault::llm_quota::LlmQuota::new(pool.clone(), config.llm_daily_calls_per_family);
+    let config = Arc::new(config);
+
     let recipe_state = recipes::RecipeState {
         pool: pool.clone(),
-        config: Arc::new(config),
+        config: config.clone(),
         http_client: reqwest::Client::new(),
+        photo_store: photo_store.clone(),
+        llm_quota: llm_quota.clone(),
+    };
+
+    let share_state = share::ShareState {
+        pool: pool.clone(),
+        config: config.clone(),
+        photo_store: photo_store.clone(),
+    };
+
+    let shopping_state = shopping::ShoppingState {
+        pool: pool.clone(),
+    };
+
+    let import_state = import::ImportState {
+        pool: pool.clone(),
+        config: config.clone(),
+        http_client: reqwest::Client::new(),
+        fetch_client: recipe_vault::safe_fetch::client(&config.import_private_hosts).unwrap(),
+        photo_store: photo_store.clone(),
+        llm_quota,
+    };
+
+    let archive_state = archive::ArchiveState {
+        pool: pool.clone(),
+        config: config.clone(),
+        photo_store: photo_store.clone(),
+    };
+
+    let cookbook_state = cookbook::CookbookState {
+        pool: pool.clone(),
+        config: config.clone(),
+        photo_store: photo_store.clone(),
+    };
+
+    let admin_state = admin::AdminState {
+        pool: pool.clone(),
+        photo_store,
     };
 
+    let api_keys_state = api_keys::ApiKeysState {
+        pool: pool.clone(),
+        legacy_key,
+    };
+
+    let families_state = families::FamiliesState {
+        pool: pool.clone(),
+        families,
+    };
+
+    // Public routes (no authentication), mirroring main.rs
+    let public_routes = Router::new()
+        .route(
+            "/share/:token",
+            axum::routing::get(share::share_page).post(share::unlock_share_link),
+        )
+        .route("/share/:token/photo", axum::routing::get(share::share_photo))
+        .route("/share/:token/photos/:photo_id", axum::routing::get(share::share_gallery_photo))
+        .route("/share/:token/recipes/:recipe_id", axum::routing::get(share::shared_collection_recipe))
+        .route("/share/:token/recipes/:recipe_id/photo", axum::routing::get(share::shared_recipe_photo))
+        .route(
+            "/share/:token/recipes/:recipe_id/photos/:photo_id",
+            axum::routing::get(share::shared_recipe_gallery_photo),
+        )
+        .with_state(share_state.clone())
+        .merge(
+            Router::new()
+                .route(
+                    "/share/shopping/:token",
+                    axum::routing::get(shopping::shared_shopping_list),
+                )
+                .with_state(shopping_state.clone()),
+        );
+
     Router::new()
         .route("/api/recipes", axum::routing::post(recipes::create_recipe))
         .route("/api/recipes", axum::routing::get(recipes::list_recipes))
@@ -149,15 +334,151 @@ pub fn create_test_app_with_config(
             "/api/recipes/:id/photo",
             axum::routing::delete(recipes::delete_photo),
         )
+        .route(
+            "/api/recipes/:id/photos",
+            axum::routing::get(gallery::list_photos).post(gallery::add_photo),
+        )
+        .route(
+            "/api/recipes/:id/photos/order",
+            axum::routing::put(gallery::reorder_photos),
+        )
+        .route(
+            "/api/recipes/:id/photos/:photo_id",
+            axum::routing::get(gallery::get_photo)
+                .put(gallery::update_photo)
+                .delete(gallery::delete_photo),
+        )
+        .route(
+            "/api/recipes/:id/source-scans",
+            axum::routing::get(gallery::list_source_scans).post(gallery::add_source_scan),
+        )
+        .route_layer(middleware::from_fn_with_state(mutation_limiter.clone(), rate_limit))
         .with_state(recipe_state)
+        .merge(
+            Router::new()
+                .route(
+                    "/api/recipes/:id/share",
+                    axum::routing::post(share::create_share_link),
+                )
+                .route(
+                    "/api/share-links",
+                    axum::routing::get(share::list_share_links).post(share::create_collection_share_link),
+                )
+                .route(
+                    "/api/share-links/:token",
+                    axum::routing::delete(share::revoke_share_link),
+                )
+                .route_layer(middleware::from_fn_with_state(share_limiter.clone(), rate_limit))
+                .with_state(share_state),
+        )
+        .merge(
+            Router::new()
+                .route(
+                    "/api/shopping-list",
+                    axum::routing::get(shopping::get_shopping_list),
+                )
+                .route(
+                    "/api/shopping-list/aisles",
+                    axum::routing::get(shopping::get_aisles).put(shopping::update_aisles),
+                )
+                .route(
+                    "/api/shopping-list/share",
+                    axum::routing::post(shopping::create_shopping_list_link)
+                        .route_layer(middleware::from_fn_with_state(share_limiter, rate_limit)),
+                )
+                .with_state(shopping_state),
+        )
+        .merge(
+            Router::new()
+                .route("/api/import/url", axum::routing::post(import::import_url))
+                .route("/api/import/cooklang", axum::routing::post(import::import_cooklang))
+                .route("/api/import/share/:token", axum::routing::post(import::import_share))
+                .route(
+                    "/api/import/file",
+                    axum::routing::post(import::import_file)
+                        .layer(axum::extract::DefaultBodyLimit::max(import::MAX_IMPORT_FILE_BYTES)),
+                )
+                .route_layer(middleware::from_fn_with_state(mutation_limiter.clone(), rate_limit))
+                .with_state(import_state),
+        )
+        .merge(
+            Router::new()
+                .route("/api/export", axum::routing::get(archive::export_archive))
+                .route(
+                    "/api/import",
+                    axum::routing::post(archive::import_archive)
+                        .layer(axum::extract::DefaultBodyLimit::max(archive::MAX_ARCHIVE_BYTES)),
+                )
+                .route_layer(middleware::from_fn_with_state(mutation_limiter.clone(), rate_limit))
+                .with_state(archive_state),
+        )
+        .merge(
+            Router::new()
+                .route("/api/cookbook", axum::routing::get(cookbook::get_cookbook))
+                .with_state(cookbook_state),
+        )
+        .merge(
+            Router::new()
+                .route("/api/admin/photos/check", axum::routing::get(admin::check_photos))
+                .route("/api/admin/photos/repair", axum::routing::post(admin::repair_photos))
+                .with_state(admin_state),
+        )
+        .merge(
+            Router::new()
+                .route(
+                    "/api/api-keys",
+                    axum::routing::get(api_keys::list_api_keys).post(api_keys::create_api_key),
+                )
+                .route("/api/api-keys/:id", axum::routing::delete(api_keys::revoke_api_key))
+                .route("/api/admin/api-key/rotate", axum::routing::post(api_keys::rotate_legacy_api_key))
+                .with_state(api_keys_state),
+        )
+        .merge(
+            Router::new()
+                .route(
+                    "/api/admin/families",
+                    axum::routing::get(families::list_families).post(families::create_family),
+                )
+                .route(
+                    "/api/admin/families/:id",
+                    axum::routing::get(families::get_family).delete(families::delete_family),
+                )
+                .route("/api/admin/families/:id/members", axum::routing::post(families::add_family_member))
+                .route(
+                    "/api/admin/families/:id/members/:email",
+                    axum::routing::put(families::set_family_member_role).delete(families::remove_family_member),
+                )
+                .route(
+                    "/api/admin/families/:id/invitations",
+                    axum::routing::post(families::create_admin_invitation),
+                )
+                .route("/api/family", axum::routing::get(families::get_own_family))
+                .route("/api/family/invitations", axum::routing::post(families::create_family_invitation))
+                .route(
+                    "/api/family/members/:email",
+                    axum::routing::put(families::set_own_family_member_role)
+                        .delete(families::remove_own_family_member),
+                )
+                .route_layer(middleware::from_fn_with_state(mutation_limiter, rate_limit))
+                .with_state(families_state.clone()),
+        )
         .route_layer(middleware::from_fn_with_state(
             api_key_state,
             api_key_auth,
         ))
+        .merge(
+            Router::new()
+                .route(
+                    "/invite/:token",
+                    axum::routing::get(families::invitation_page).post(families::accept_invitation),
+                )
+                .with_state(families_state),
+        )
         .layer(middleware::from_fn_with_state(
             cloudflare_auth_state,
             cloudflare_auth,
         ))
+        .merge(public_routes)
 }
 
 /// Helper to send JSON request and get response
@@ -297,3 +618,60 @@ pub async fn send_binary_request(
 
     (status, body_bytes, content_type)
 }
+
+/// Helper to send a request and return the raw body as text (for non-JSON responses)
+#[allow(dead_code)]
+pub async fn send_text_request(
+    app: &Router,
+    method: &str,
+    uri: &str,
+    headers: &[(&str, &str)],
+) -> (StatusCode, String, Option<String>) {
+    let mut request = Request::builder().method(method).uri(uri);
+    for (key, value) in headers {
+        request = request.header(*key, *value);
+    }
+    let request = request.body(Body::empty()).unwrap();
+
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let content_type = response
+        .headers()
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
+
+    let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+
+    (status, String::from_utf8_lossy(&body_bytes).to_string(), content_type)
+}
+
+/// Helper to send a raw body and return the raw response with its headers
+/// (for zip archives)
+#[allow(dead_code)]
+pub async fn send_bytes_request(
+    app: &Router,
+    method: &str,
+    uri: &str,
+    headers: &[(&str, &str)],
+    body: Vec<u8>,
+) -> (StatusCode, Vec<u8>, axum::http::HeaderMap) {
+    let mut request = Request::builder().method(method).uri(uri);
+    for (key, value) in headers {
+        request = request.header(*key, *value);
+    }
+    let request = request.body(Body::from(body)).unwrap();
+
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let headers = response.headers().clone();
+
+    let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap()
+        .to_vec();
+
+    (status, body_bytes, headers)
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+use common::{
+    create_test_app, create_test_app_with_config, create_test_db, create_two_family_config, send_multipart_request,
+    send_request, send_request_with_headers, send_text_request,
+};
+
+const ALICE: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "alice@example.com")];
+const BOB: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "bob@example.com")];
+
+/// Minimal 1x1 PNG
+const PNG: &[u8] = &[
+    0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D, 0x49, 0x48, 0x44, 0x52,
+    0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1F, 0x15, 0xC4,
+    0x89, 0x00, 0x00, 0x00, 0x0A, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9C, 0x63, 0x00, 0x01, 0x00, 0x00,
+    0x05, 0x00, 0x01, 0x0D, 0x0A, 0x2D, 0xB4, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4E, 0x44, 0xAE,
+    0x42, 0x60, 0x82,
+];
+
+fn sample_recipe(title: &str, tags: &[&str], ingredients: &[&str]) -> Value {
+    json!({
+        "title": title,
+        "servings": 4,
+        "tags": tags,
+        "ingredients": ingredients.iter().map(|name| json!({"name": name, "quantity": 1})).collect::<Vec<_>>(),
+        "steps": [{"instruction": format!("Make the {}.", title.to_lowercase())}]
+    })
+}
+
+async fn create_recipe(app: &axum::Router, headers: &[(&str, &str)], recipe: Value) -> String {
+    let (status, body) = send_request_with_headers(app, "POST", "/api/recipes", Some(recipe), headers).await;
+    assert_eq!(status, StatusCode::CREATED);
+    body.unwrap()["id"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_cookbook_contains_family_recipes() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+    let scones = create_recipe(&app, ALICE, sample_recipe("Scones", &["Baking"], &["flour", "Butter"])).await;
+    let bread = create_recipe(&app, ALICE, sample_recipe("Bread", &["Baking"], &["Flour", "yeast"])).await;
+    create_recipe(&app, BOB, sample_recipe("Bob's Chili", &[], &["beans"])).await;
+
+    let (status, html, content_type) = send_text_request(&app, "GET", "/api/cookbook", ALICE).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("text/html; charset=utf-8"));
+    assert!(html.contains("<h1>Family Cookbook</h1>"));
+    assert!(html.contains("<p class=\"count\">2 recipes</p>"));
+    assert!(!html.contains("Chili"));
+
+    // Without an explicit order the book is alphabetical
+    assert!(html.find("<h2>Bread</h2>").unwrap() < html.find("<h2>Scones</h2>").unwrap());
+    assert!(html.contains("id=\"recipe-1\">\n<span class=\"number\">No. 1</span>\n<h2>Bread</h2>"));
+
+    // The ingredient index merges differently-cased names
+    assert!(html.contains(
+        "<li><span>flour</span><span class=\"refs\"><a href=\"#recipe-1\">1</a>, <a href=\"#recipe-2\">2</a></span></li>"
+    ));
+
+    // A chosen subset keeps the caller's order
+    let uri = format!("/api/cookbook?recipe_ids={},{}&title=Baking%20Day&toc=tag", scones, bread);
+    let (status, html, _) = send_text_request(&app, "GET", &uri, ALICE).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(html.contains("<h1>Baking Day</h1>"));
+    assert!(html.contains("<h3>Baking</h3>"));
+    assert!(html.find("<h2>Scones</h2>").unwrap() < html.find("<h2>Bread</h2>").unwrap());
+
+    // Another family's recipe cannot be included
+    let uri = format!("/api/cookbook?recipe_ids={}", scones);
+    let (status, _, _) = send_text_request(&app, "GET", &uri, BOB).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_cookbook_embeds_photos_and_validates() {
+    let app = create_test_app(create_test_db().await).await;
+    let (_, recipe) = send_request(&app, "POST", "/api/recipes", Some(sample_recipe("Pie", &[], &["apples"]))).await;
+    let id = recipe.unwrap()["id"].as_str().unwrap().to_string();
+    let (status, _) = send_multipart_request(
+        &app,
+        "POST",
+        &format!("/api/recipes/{}/photo", id),
+        "photo",
+        PNG.to_vec(),
+        "pie.png",
+        "image/png",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, html, _) = send_text_request(&app, "GET", "/api/cookbook", &[]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(html.contains("src=\"data:image/png;base64,iVBORw0KGgo"));
+    assert!(!html.contains("/photo\""));
+
+    let (_, html, _) = send_text_request(&app, "GET", "/api/cookbook?photos=false", &[]).await;
+    assert!(!html.contains("data:image/png"));
+
+    let (status, body) = send_request(&app, "GET", "/api/cookbook?toc=chapters", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.unwrap()["error"].as_str().unwrap().contains("alphabetical"));
+
+    let (status, _) = send_request(&app, "GET", "/api/cookbook?recipe_ids=,", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+use common::{create_test_app, create_test_db, send_bytes_request, send_request, send_text_request};
+
+const PANCAKES: &str = "\
+>> servings: 4
+>> tags: Breakfast, Quick
+
+Whisk @plain flour{125%g}, @eggs{2} and @milk{300%ml} in a #bowl{}.
+Rest the batter for ~{30%minutes}.
+
+Melt @butter{1%tbsp} in a #frying pan{} over medium heat.
+
+Cook ladlefuls for ~{2%minutes} per side.
+";
+
+async fn import(app: &axum::Router, uri: &str, text: &str) -> (StatusCode, Value) {
+    let (status, body, _) = send_bytes_request(app, "POST", uri, &[("Content-Type", "text/plain")], text.as_bytes().to_vec()).await;
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_import_cooklang() {
+    let app = create_test_app(create_test_db().await).await;
+
+    let (status, body) = import(&app, "/api/import/cooklang?title=Pancakes.cook", PANCAKES).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(body["saved"], true);
+
+    let recipe = &body["recipe"];
+    assert_eq!(recipe["title"], "Pancakes");
+    assert_eq!(recipe["servings"], 4);
+    assert_eq!(recipe["tags"], json!(["Breakfast", "Quick"]));
+
+    let ingredients = recipe["ingredients"].as_array().unwrap();
+    assert_eq!(ingredients.len(), 4);
+    assert_eq!(ingredients[0]["name"], "plain flour");
+    assert_eq!(ingredients[0]["quantity"], 125.0);
+    assert_eq!(ingredients[0]["unit"], "g");
+    assert_eq!(ingredients[1]["quantity"], 2.0);
+
+    let steps = recipe["steps"].as_array().unwrap();
+    assert_eq!(steps.len(), 3);
+    assert_eq!(
+        steps[0]["instruction"],
+        "Whisk plain flour, eggs and milk in a bowl.\nRest the batter for 30 minutes."
+    );
+    assert_eq!(steps[0]["duration_minutes"], 30);
+    assert!(steps[1].get("duration_minutes").is_none());
+    assert_eq!(steps[2]["duration_minutes"], 2);
+}
+
+#[tokio::test]
+async fn test_import_cooklang_preview_and_errors() {
+    let app = create_test_app(create_test_db().await).await;
+
+    let (status, body) = import(&app, "/api/import/cooklang?title=Pancakes&save=false", PANCAKES).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["saved"], false);
+    assert!(body["recipe"].get("id").is_none());
+    let (_, list) = send_request(&app, "GET", "/api/recipes", None).await;
+    assert!(list.unwrap().as_array().unwrap().is_empty());
+
+    let (status, body) = import(&app, "/api/import/cooklang", PANCAKES).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].as_str().unwrap().contains("no title"));
+
+    let (status, body) = import(&app, "/api/import/cooklang?title=Broken", "Mix.\n\nAdd @flour{500%g.\n").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "line 3: Unclosed '{'");
+}
+
+#[tokio::test]
+async fn test_export_and_reimport_cooklang() {
+    let app = create_test_app(create_test_db().await).await;
+    let (_, created) = send_request(
+        &app,
+        "POST",
+        "/api/recipes",
+        Some(json!({
+            "title": "Roast Potatoes",
+            "servings": 4,
+            "difficulty": 2,
+            "cook_time_minutes": 50,
+            "ingredients": [
+                {"name": "potatoes", "quantity": 1, "unit": "kg", "notes": "peeled"},
+                {"name": "olive oil", "quantity": 3, "unit": "tbsp"},
+                {"name": "rosemary"}
+            ],
+            "steps": [
+                {"instruction": "Boil the potatoes for 10 minutes.", "duration_minutes": 10},
+                {"instruction": "Toss with the olive oil and roast at 200°C for 40 minutes.", "duration_minutes": 40, "temperature_value": 200, "temperature_unit": "Celsius"}
+            ]
+        })),
+    )
+    .await;
+    let created = created.unwrap();
+    let id = created["id"].as_str().unwrap();
+
+    let (status, text, content_type) = send_text_request(&app, "GET", &format!("/api/recipes/{}.cook", id), &[]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("text/plain; charset=utf-8"));
+    assert!(text.starts_with("---\ntitle: Roast Potatoes\n"), "{}", text);
+    assert!(text.contains("\n@rosemary{}\n"), "{}", text);
+    assert!(text.contains("Boil the @potatoes{1%kg}(peeled) for ~{10%minutes}."), "{}", text);
+    assert!(text.contains("Toss with the @olive oil{3%tbsp} and roast at 200°C for ~{40%minutes}."), "{}", text);
+
+    // Editing the Cooklang file and sending it back replaces the recipe
+    let edited = text.replace("@olive oil{3%tbsp}", "@olive oil{4%tbsp}");
+    let (status, body, _) = send_bytes_request(
+        &app,
+        "PUT",
+        &format!("/api/recipes/{}.cook", id),
+        &[("Content-Type", "text/plain")],
+        edited.into_bytes(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(String::from_utf8(body).unwrap().contains("@olive oil{4%tbsp}"));
+
+    let (_, after) = send_request(&app, "GET", &format!("/api/recipes/{}", id), None).await;
+    let after = after.unwrap();
+    assert_eq!(after["title"], "Roast Potatoes");
+    assert_eq!(after["difficulty"], 2);
+    assert_eq!(after["cook_time_minutes"], 50);
+    let names: Vec<&str> = after["ingredients"].as_array().unwrap().iter().map(|i| i["name"].as_str().unwrap()).collect();
+    assert_eq!(names, vec!["rosemary", "potatoes", "olive oil"]);
+    assert_eq!(after["ingredients"][2]["quantity"], 4.0);
+    assert_eq!(after["steps"][1]["duration_minutes"], 40);
+    assert_eq!(after["steps"][1]["temperature_value"], 200);
+    assert_eq!(after["steps"][1]["temperature_unit"], "Celsius");
+}
@@ -24,9 +24,12 @@ async fn test_invalid_difficulty_validation() {
         difficulty: Some(0), // Invalid: too low
         ingredients: vec![],
         steps: vec![],
+        tags: vec![],
+        source_url: None,
+        shared_by: None,
     };
 
-    let result = queries::create_recipe(&pool, invalid_input, None).await;
+    let result = queries::create_recipe(&pool, invalid_input, None, None).await;
     // SQLite CHECK constraint should prevent this
     assert!(result.is_err(), "Expected error for difficulty < 1");
 
@@ -40,9 +43,12 @@ async fn test_invalid_difficulty_validation() {
         difficulty: Some(6), // Invalid: too high
         ingredients: vec![],
         steps: vec![],
+        tags: vec![],
+        source_url: None,
+        shared_by: None,
     };
 
-    let result2 = queries::create_recipe(&pool, invalid_input2, None).await;
+    let result2 = queries::create_recipe(&pool, invalid_input2, None, None).await;
     assert!(result2.is_err(), "Expected error for difficulty > 5");
 
     // Test: valid difficulty values (1-5) should succeed
@@ -56,9 +62,12 @@ async fn test_invalid_difficulty_validation() {
             difficulty: Some(difficulty),
             ingredients: vec![],
             steps: vec![],
+            tags: vec![],
+            source_url: None,
+            shared_by: None,
         };
 
-        let result = queries::create_recipe(&pool, valid_input, None).await;
+        let result = queries::create_recipe(&pool, valid_input, None, None).await;
         assert!(
             result.is_ok(),
             "Expected success for difficulty {}, got error: {:?}",
@@ -85,10 +94,13 @@ async fn test_ai_assessment_with_mock() {
         servings: Some(4),
         difficulty: None,
         photo_filename: None,
+        source_url: None,
+        shared_by: None,
         created_at: "2024-01-01T00:00:00Z".to_string(),
         updated_at: "2024-01-01T00:00:00Z".to_string(),
         created_by: None,
         updated_by: None,
+        family_id: None,
     };
 
     let ingredients = vec![RecipeIngredient {
@@ -149,10 +161,13 @@ async fn test_existing_recipe_crud_with_difficulty() {
             temperature_value: Some(180),
             temperature_unit: Some("Celsius".to_string()),
         }],
+        tags: vec![],
+        source_url: None,
+        shared_by: None,
     };
 
     // Test CREATE
-    let created = queries::create_recipe(&pool, input, None)
+    let created = queries::create_recipe(&pool, input, None, None)
         .await
         .expect("Failed to create recipe");
     assert_eq!(created.recipe.title, "Test Recipe");
@@ -179,6 +194,7 @@ async fn test_existing_recipe_crud_with_difficulty() {
         difficulty: Some(5), // Change difficulty
         ingredients: None,
         steps: None,
+        tags: None,
     };
 
     let updated = queries::update_recipe(&pool, &recipe_id, update_input, None, None)
@@ -212,9 +228,12 @@ async fn test_recipe_without_difficulty() {
         difficulty: None, // No difficulty
         ingredients: vec![],
         steps: vec![],
+        tags: vec![],
+        source_url: None,
+        shared_by: None,
     };
 
-    let created = queries::create_recipe(&pool, input, None)
+    let created = queries::create_recipe(&pool, input, None, None)
         .await
         .expect("Should create recipe without difficulty");
 
@@ -252,9 +271,12 @@ async fn test_user_specified_difficulty_preserved() {
             temperature_value: None,
             temperature_unit: None,
         }],
+        tags: vec![],
+        source_url: None,
+        shared_by: None,
     };
 
-    let created = queries::create_recipe(&pool, input, None)
+    let created = queries::create_recipe(&pool, input, None, None)
         .await
         .expect("Should create recipe");
 
@@ -0,0 +1,155 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::{
+    create_test_app_with_config, create_test_db, create_two_family_config, send_request_with_headers,
+    send_text_request,
+};
+
+const ADMIN: &[(&str, &str)] = &[("X-API-Key", "test-api-key")];
+const ALICE: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "alice@example.com")];
+const BOB: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "bob@example.com")];
+const CAROL: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "carol@example.com")];
+
+fn signed_in(email: &str) -> [(&'static str, &str); 1] {
+    [("Cf-Access-Authenticated-User-Email", email)]
+}
+
+#[tokio::test]
+async fn test_families_yaml_is_imported() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+
+    let (status, body) = send_request_with_headers(&app, "GET", "/api/admin/families", None, ADMIN).await;
+    assert_eq!(status, StatusCode::OK);
+    let families = body.unwrap();
+    assert_eq!(families.as_array().unwrap().len(), 2);
+    assert_eq!(families[0]["id"], "family-a");
+    assert_eq!(families[0]["members"], json!(["alice2@example.com", "alice@example.com"]));
+
+    let (status, body) = send_request_with_headers(&app, "GET", "/api/family", None, BOB).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.unwrap()["id"], "family-b");
+
+    // Only the unscoped API key may manage families
+    let (status, _) = send_request_with_headers(&app, "GET", "/api/admin/families", None, ALICE).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_admin_manages_members_without_restart() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+
+    // Carol isn't in a family yet
+    let (status, _) = send_request_with_headers(&app, "GET", "/api/recipes", None, CAROL).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let family = json!({"id": "family-c", "name": "The Cs", "members": ["Carol@Example.com"]});
+    let (status, body) = send_request_with_headers(&app, "POST", "/api/admin/families", Some(family.clone()), ADMIN).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let body = body.unwrap();
+    assert_eq!(body["name"], "The Cs");
+    assert_eq!(body["members"], json!(["carol@example.com"]));
+    let (status, _) = send_request_with_headers(&app, "POST", "/api/admin/families", Some(family), ADMIN).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    // Access follows straight away
+    let (status, _) = send_request_with_headers(&app, "GET", "/api/recipes", None, CAROL).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // An email can only be in one family
+    let bob = json!({"email": "bob@example.com"});
+    let (status, _) =
+        send_request_with_headers(&app, "POST", "/api/admin/families/family-c/members", Some(bob), ADMIN).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let bad = json!({"email": "not an email"});
+    let (status, _) =
+        send_request_with_headers(&app, "POST", "/api/admin/families/family-c/members", Some(bad), ADMIN).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Moving Bob: remove him from his family, then add him to Carol's
+    let (status, _) =
+        send_request_with_headers(&app, "DELETE", "/api/admin/families/family-b/members/bob@example.com", None, ADMIN)
+            .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send_request_with_headers(&app, "GET", "/api/recipes", None, BOB).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let bob = json!({"email": "bob@example.com"});
+    let (status, body) =
+        send_request_with_headers(&app, "POST", "/api/admin/families/family-c/members", Some(bob), ADMIN).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.unwrap()["members"], json!(["bob@example.com", "carol@example.com"]));
+    let (_, body) = send_request_with_headers(&app, "GET", "/api/family", None, BOB).await;
+    assert_eq!(body.unwrap()["id"], "family-c");
+
+    let (status, _) = send_request_with_headers(&app, "DELETE", "/api/admin/families/family-c", None, ADMIN).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send_request_with_headers(&app, "GET", "/api/recipes", None, CAROL).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send_request_with_headers(&app, "GET", "/api/admin/families/family-c", None, ADMIN).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_invitation_adds_a_relative() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+    let recipe = json!({"title": "Granny's Scones", "ingredients": [], "steps": []});
+    send_request_with_headers(&app, "POST", "/api/recipes", Some(recipe), ALICE).await;
+
+    let invite = json!({"email": "carol@example.com"});
+    let (status, body) = send_request_with_headers(&app, "POST", "/api/family/invitations", Some(invite), ALICE).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let body = body.unwrap();
+    assert_eq!(body["family_id"], "family-a");
+    let url = body["url"].as_str().unwrap().to_string();
+
+    // Must be signed in, as the invited address
+    let (status, _, _) = send_text_request(&app, "GET", &url, &[]).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _, _) = send_text_request(&app, "GET", &url, &signed_in("dave@example.com")).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, page, _) = send_text_request(&app, "GET", &url, &signed_in("carol@example.com")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(page.contains("Join as carol@example.com"));
+
+    // Another site can't submit the form for a signed-in visitor
+    let carol = "carol@example.com";
+    let forged: [&[(&str, &str)]; 3] = [
+        &[("Cf-Access-Authenticated-User-Email", carol), ("Sec-Fetch-Site", "cross-site")],
+        &[("Cf-Access-Authenticated-User-Email", carol), ("Host", "vault.example.com"), ("Origin", "https://evil.example")],
+        &[("Cf-Access-Authenticated-User-Email", carol), ("Origin", "null")],
+    ];
+    for headers in forged {
+        let (status, _, _) = send_text_request(&app, "POST", &url, headers).await;
+        assert_eq!(status, StatusCode::FORBIDDEN, "{:?}", headers);
+    }
+
+    let same_origin = [
+        ("Cf-Access-Authenticated-User-Email", carol),
+        ("Host", "vault.example.com"),
+        ("Origin", "https://vault.example.com"),
+        ("Sec-Fetch-Site", "same-origin"),
+    ];
+    let (status, _, _) = send_text_request(&app, "POST", &url, &same_origin).await;
+    assert_eq!(status, StatusCode::SEE_OTHER);
+    let (status, recipes) =
+        send_request_with_headers(&app, "GET", "/api/recipes", None, &signed_in("carol@example.com")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(recipes.unwrap().to_string().contains("Granny's Scones"));
+
+    // Invitations are single use
+    let (status, _, _) = send_text_request(&app, "POST", &url, &signed_in("carol@example.com")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // People already in a family can't join another by invitation
+    let (_, body) = send_request_with_headers(&app, "POST", "/api/admin/families/family-a/invitations", None, ADMIN).await;
+    let url = body.unwrap()["url"].as_str().unwrap().to_string();
+    let (status, _, _) = send_text_request(&app, "POST", &url, &signed_in("bob@example.com")).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    // God mode has no family to invite to
+    let (status, _) = send_request_with_headers(&app, "POST", "/api/family/invitations", None, ADMIN).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
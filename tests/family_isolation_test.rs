@@ -10,12 +10,12 @@ use common::{
 };
 
 /// Helper: create an app configured for Family A user (alice@example.com via Cloudflare header)
-fn create_family_a_app(pool: SqlitePool) -> axum::Router {
-    // No dev_email — we'll use Cloudflare header or API key + X-User-Email
-    create_test_app_with_config(pool, None, create_two_family_config())
+async fn create_family_a_app(pool: SqlitePool) -> axum::Router {
+    // No dev_email — we'll use the Cloudflare header
+    create_test_app_with_config(pool, None, create_two_family_config()).await
 }
 
-/// Helper: seed a recipe for a specific family member using API key + X-User-Email
+/// Helper: seed a recipe for a specific family member using the Cloudflare header
 async fn seed_recipe(
     app: &axum::Router,
     title: &str,
@@ -26,10 +26,7 @@ async fn seed_recipe(
         "POST",
         "/api/recipes",
         Some(json!({"title": title})),
-        &[
-            ("X-API-Key", "test-api-key"),
-            ("X-User-Email", creator_email),
-        ],
+        &[("Cf-Access-Authenticated-User-Email", creator_email)],
     )
     .await;
 
@@ -38,7 +35,7 @@ async fn seed_recipe(
     recipe["id"].as_str().unwrap().to_string()
 }
 
-/// Helper: make a request as a specific family user (via API key + X-User-Email)
+/// Helper: make a request as a specific family user (via the Cloudflare header)
 async fn request_as_user(
     app: &axum::Router,
     method: &str,
@@ -51,15 +48,12 @@ async fn request_as_user(
         method,
         uri,
         body,
-        &[
-            ("X-API-Key", "test-api-key"),
-            ("X-User-Email", email),
-        ],
+        &[("Cf-Access-Authenticated-User-Email", email)],
     )
     .await
 }
 
-/// Helper: make a request in god mode (API key without X-User-Email)
+/// Helper: make a request in god mode (the instance API key)
 async fn request_as_god(
     app: &axum::Router,
     method: &str,
@@ -80,7 +74,7 @@ async fn request_as_god(
 #[tokio::test]
 async fn test_family_a_lists_only_family_a_recipes() {
     let pool = create_test_db().await;
-    let app = create_family_a_app(pool);
+    let app = create_family_a_app(pool).await;
 
     // Seed recipes for both families
     seed_recipe(&app, "Alice's Cookies", "alice@example.com").await;
@@ -100,7 +94,7 @@ async fn test_family_a_lists_only_family_a_recipes() {
 #[tokio::test]
 async fn test_family_a_gets_own_recipe() {
     let pool = create_test_db().await;
-    let app = create_family_a_app(pool);
+    let app = create_family_a_app(pool).await;
 
     let recipe_id = seed_recipe(&app, "Alice's Cake", "alice@example.com").await;
 
@@ -116,7 +110,7 @@ async fn test_family_a_gets_own_recipe() {
 #[tokio::test]
 async fn test_family_a_cannot_get_family_b_recipe() {
     let pool = create_test_db().await;
-    let app = create_family_a_app(pool);
+    let app = create_family_a_app(pool).await;
 
     let bob_recipe_id = seed_recipe(&app, "Bob's Secret", "bob@example.com").await;
 
@@ -131,7 +125,7 @@ async fn test_family_a_cannot_get_family_b_recipe() {
 #[tokio::test]
 async fn test_family_a_updates_own_recipe() {
     let pool = create_test_db().await;
-    let app = create_family_a_app(pool);
+    let app = create_family_a_app(pool).await;
 
     let recipe_id = seed_recipe(&app, "Alice's Original", "alice@example.com").await;
 
@@ -151,7 +145,7 @@ async fn test_family_a_updates_own_recipe() {
 #[tokio::test]
 async fn test_family_a_cannot_update_family_b_recipe() {
     let pool = create_test_db().await;
-    let app = create_family_a_app(pool);
+    let app = create_family_a_app(pool).await;
 
     let bob_recipe_id = seed_recipe(&app, "Bob's Recipe", "bob@example.com").await;
 
@@ -170,7 +164,7 @@ async fn test_family_a_cannot_update_family_b_recipe() {
 #[tokio::test]
 async fn test_family_a_deletes_own_recipe() {
     let pool = create_test_db().await;
-    let app = create_family_a_app(pool);
+    let app = create_family_a_app(pool).await;
 
     let recipe_id = seed_recipe(&app, "Alice's Temp", "alice@example.com").await;
 
@@ -191,7 +185,7 @@ async fn test_family_a_deletes_own_recipe() {
 #[tokio::test]
 async fn test_family_a_cannot_delete_family_b_recipe() {
     let pool = create_test_db().await;
-    let app = create_family_a_app(pool);
+    let app = create_family_a_app(pool).await;
 
     let bob_recipe_id = seed_recipe(&app, "Bob's Keep", "bob@example.com").await;
 
@@ -212,7 +206,7 @@ async fn test_family_a_cannot_delete_family_b_recipe() {
 #[tokio::test]
 async fn test_god_mode_lists_all_recipes() {
     let pool = create_test_db().await;
-    let app = create_family_a_app(pool);
+    let app = create_family_a_app(pool).await;
 
     seed_recipe(&app, "Alice's Recipe", "alice@example.com").await;
     seed_recipe(&app, "Bob's Recipe", "bob@example.com").await;
@@ -229,7 +223,7 @@ async fn test_god_mode_lists_all_recipes() {
 #[tokio::test]
 async fn test_god_mode_gets_any_recipe() {
     let pool = create_test_db().await;
-    let app = create_family_a_app(pool);
+    let app = create_family_a_app(pool).await;
 
     let bob_recipe_id = seed_recipe(&app, "Bob's Private", "bob@example.com").await;
 
@@ -250,7 +244,7 @@ async fn test_god_mode_creates_recipe_with_dev_email() {
         pool,
         Some("dev@example.com".to_string()),
         create_two_family_config(),
-    );
+    ).await;
 
     let (status, response) = request_as_god(
         &app,
@@ -264,11 +258,11 @@ async fn test_god_mode_creates_recipe_with_dev_email() {
     assert_eq!(recipe["created_by"], "dev@example.com");
 }
 
-// ==== 8.13: Scoped mode (API key + X-User-Email) sees only that family ====
+// ==== 8.13: Scoped mode sees only that family ====
 #[tokio::test]
 async fn test_scoped_mode_sees_only_family() {
     let pool = create_test_db().await;
-    let app = create_family_a_app(pool);
+    let app = create_family_a_app(pool).await;
 
     seed_recipe(&app, "Alice's Scoped", "alice@example.com").await;
     seed_recipe(&app, "Bob's Scoped", "bob@example.com").await;
@@ -283,11 +277,34 @@ async fn test_scoped_mode_sees_only_family() {
     assert_eq!(list[0]["title"], "Bob's Scoped");
 }
 
+// ==== The instance API key can't be used to act as someone else ====
+#[tokio::test]
+async fn test_instance_key_cannot_act_as_user() {
+    let pool = create_test_db().await;
+    let app = create_family_a_app(pool).await;
+
+    seed_recipe(&app, "Alice's Secret", "alice@example.com").await;
+
+    let (status, response) = send_request_with_headers(
+        &app,
+        "GET",
+        "/api/recipes",
+        None,
+        &[("X-API-Key", "test-api-key"), ("X-User-Email", "alice@example.com")],
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let error = response.unwrap();
+    assert!(error["error"].as_str().unwrap().contains("personal API key"));
+    assert!(!error.to_string().contains("Alice's Secret"));
+}
+
 // ==== 8.14: User not in config receives 403 error ====
 #[tokio::test]
 async fn test_user_not_in_config_gets_403() {
     let pool = create_test_db().await;
-    let app = create_family_a_app(pool);
+    let app = create_family_a_app(pool).await;
 
     let (status, response) = request_as_user(
         &app, "GET", "/api/recipes", None, "stranger@example.com",
@@ -302,7 +319,7 @@ async fn test_user_not_in_config_gets_403() {
 #[tokio::test]
 async fn test_case_insensitive_email_matching() {
     let pool = create_test_db().await;
-    let app = create_family_a_app(pool);
+    let app = create_family_a_app(pool).await;
 
     // Create recipe as lowercase alice
     let recipe_id = seed_recipe(&app, "Alice's Case Test", "alice@example.com").await;
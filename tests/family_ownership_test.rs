@@ -0,0 +1,124 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+use common::{create_test_app_with_config, create_test_db, create_two_family_config, send_request_with_headers};
+use recipe_vault::db::queries;
+
+const ADMIN: &[(&str, &str)] = &[("X-API-Key", "test-api-key")];
+const ALICE: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "alice@example.com")];
+const ALICE2: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "alice2@example.com")];
+const BOB: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "bob@example.com")];
+
+async fn titles(app: &axum::Router, headers: &[(&str, &str)]) -> Vec<String> {
+    let (status, body) = send_request_with_headers(app, "GET", "/api/recipes", None, headers).await;
+    assert_eq!(status, StatusCode::OK);
+    body.unwrap()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["title"].as_str().unwrap().to_string())
+        .collect()
+}
+
+async fn share_link_count(app: &axum::Router, headers: &[(&str, &str)]) -> usize {
+    let (status, body) = send_request_with_headers(app, "GET", "/api/share-links", None, headers).await;
+    assert_eq!(status, StatusCode::OK);
+    body.unwrap().as_array().unwrap().len()
+}
+
+#[tokio::test]
+async fn test_recipes_stay_when_author_moves_family() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+
+    let recipe = json!({"title": "Soda Bread", "ingredients": [{"name": "flour"}]});
+    let (status, body) = send_request_with_headers(&app, "POST", "/api/recipes", Some(recipe), ALICE).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let body = body.unwrap();
+    assert_eq!(body["family_id"], "family-a");
+    let id = body["id"].as_str().unwrap().to_string();
+    let (status, _) =
+        send_request_with_headers(&app, "POST", &format!("/api/recipes/{}/share", id), None, ALICE).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    // Alice moves to Bob's family
+    let (status, _) = send_request_with_headers(
+        &app,
+        "DELETE",
+        "/api/admin/families/family-a/members/alice@example.com",
+        None,
+        ADMIN,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send_request_with_headers(
+        &app,
+        "POST",
+        "/api/admin/families/family-b/members",
+        Some(json!({"email": "alice@example.com"})),
+        ADMIN,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // The recipe and its link stay with family-a, still credited to Alice
+    assert_eq!(titles(&app, ALICE2).await, vec!["Soda Bread"]);
+    assert_eq!(share_link_count(&app, ALICE2).await, 1);
+    let (status, recipe) =
+        send_request_with_headers(&app, "GET", &format!("/api/recipes/{}", id), None, ALICE2).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(recipe.unwrap()["created_by"], "alice@example.com");
+
+    // ...and don't follow her
+    assert!(titles(&app, ALICE).await.is_empty());
+    assert!(titles(&app, BOB).await.is_empty());
+    assert_eq!(share_link_count(&app, BOB).await, 0);
+    let (status, _) = send_request_with_headers(&app, "GET", &format!("/api/recipes/{}", id), None, ALICE).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_god_mode_recipes_go_to_creators_family() {
+    let dev = Some("alice2@example.com".to_string());
+    let app = create_test_app_with_config(create_test_db().await, dev, create_two_family_config()).await;
+
+    let recipe = json!({"title": "Pancakes", "ingredients": [{"name": "flour"}]});
+    let (status, body) = send_request_with_headers(&app, "POST", "/api/recipes", Some(recipe), ADMIN).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(body.unwrap()["family_id"], "family-a");
+    assert_eq!(titles(&app, ALICE).await, vec!["Pancakes"]);
+}
+
+#[tokio::test]
+async fn test_existing_recipes_are_assigned_to_families() {
+    let pool = create_test_db().await;
+    let app = create_test_app_with_config(pool.clone(), None, create_two_family_config()).await;
+
+    // Rows from before recipes had a family
+    sqlx::query(
+        "INSERT INTO recipes (id, title, created_by, created_at, updated_at)
+         VALUES ('r1', 'Old Stew', 'Alice@Example.com', datetime('now'), datetime('now')),
+                ('r2', 'Stranger Stew', 'carol@example.com', datetime('now'), datetime('now'))",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query("INSERT INTO share_links (token, recipe_id, created_by) VALUES ('oldlink', 'r1', 'bob@example.com')")
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert!(titles(&app, ALICE2).await.is_empty());
+
+    assert_eq!(queries::assign_family_ids(&pool).await.unwrap(), 2);
+    assert_eq!(queries::assign_family_ids(&pool).await.unwrap(), 0);
+
+    // The link follows its recipe rather than its creator
+    assert_eq!(titles(&app, ALICE2).await, vec!["Old Stew"]);
+    assert_eq!(share_link_count(&app, ALICE2).await, 1);
+    assert_eq!(share_link_count(&app, BOB).await, 0);
+
+    let (_, all) = send_request_with_headers(&app, "GET", "/api/recipes", None, ADMIN).await;
+    let stranger: Vec<Value> = all.unwrap().as_array().unwrap().iter().filter(|r| r["id"] == "r2").cloned().collect();
+    assert!(stranger[0].get("family_id").is_none());
+}
@@ -0,0 +1,182 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+use common::{
+    create_test_app_with_config, create_test_db, create_two_family_config, send_bytes_request,
+    send_request_with_headers,
+};
+
+const ADMIN: &[(&str, &str)] = &[("X-API-Key", "test-api-key")];
+const ALICE: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "alice@example.com")];
+const ALICE2: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "alice2@example.com")];
+const CAROL: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "carol@example.com")];
+const DAVE: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "dave@example.com")];
+
+async fn create_recipe(app: &axum::Router, headers: &[(&str, &str)], title: &str) -> (StatusCode, Value) {
+    let recipe = json!({"title": title, "ingredients": [{"name": "flour"}]});
+    let (status, body) = send_request_with_headers(app, "POST", "/api/recipes", Some(recipe), headers).await;
+    (status, body.unwrap_or_default())
+}
+
+/// Carol joins family-a as a viewer and Dave as an editor
+async fn add_carol_and_dave(app: &axum::Router) {
+    for (email, role) in [("carol@example.com", "viewer"), ("dave@example.com", "editor")] {
+        let member = json!({"email": email, "role": role});
+        let (status, _) =
+            send_request_with_headers(app, "POST", "/api/admin/families/family-a/members", Some(member), ADMIN).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+}
+
+#[tokio::test]
+async fn test_viewers_only_read() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+    add_carol_and_dave(&app).await;
+    let (_, recipe) = create_recipe(&app, ALICE, "Soda Bread").await;
+    let id = recipe["id"].as_str().unwrap();
+
+    let (status, _) = send_request_with_headers(&app, "GET", &format!("/api/recipes/{}", id), None, CAROL).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = create_recipe(&app, CAROL, "Carol's Cake").await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert!(body["error"].as_str().unwrap().contains("Viewers"));
+    let update = json!({"title": "Carol's Bread"});
+    let (status, _) =
+        send_request_with_headers(&app, "PUT", &format!("/api/recipes/{}", id), Some(update), CAROL).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send_request_with_headers(&app, "DELETE", &format!("/api/recipes/{}", id), None, CAROL).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) =
+        send_request_with_headers(&app, "POST", &format!("/api/recipes/{}/share", id), None, CAROL).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_viewers_cannot_import_or_change_shopping_settings() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+    add_carol_and_dave(&app).await;
+    let (_, recipe) = create_recipe(&app, ALICE, "Soda Bread").await;
+
+    // Refused before the archive is even read
+    let (status, _, _) = send_bytes_request(&app, "POST", "/api/import", CAROL, b"PK\x03\x04".to_vec()).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _, _) = send_bytes_request(&app, "POST", "/api/import", DAVE, b"not a zip".to_vec()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let aisles = json!({"aisles": ["Bakery", "Dairy"]});
+    let (status, _) =
+        send_request_with_headers(&app, "PUT", "/api/shopping-list/aisles", Some(aisles.clone()), CAROL).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send_request_with_headers(&app, "PUT", "/api/shopping-list/aisles", Some(aisles), DAVE).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let link = json!({"recipe_ids": [recipe["id"]]});
+    let (status, _) = send_request_with_headers(&app, "POST", "/api/shopping-list/share", Some(link), CAROL).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_editors_delete_only_their_own() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+    add_carol_and_dave(&app).await;
+    let (_, alices) = create_recipe(&app, ALICE, "Soda Bread").await;
+    let alices = format!("/api/recipes/{}", alices["id"].as_str().unwrap());
+    let (status, daves) = create_recipe(&app, DAVE, "Dave's Stew").await;
+    assert_eq!(status, StatusCode::CREATED);
+    let daves = format!("/api/recipes/{}", daves["id"].as_str().unwrap());
+
+    // Editors may change anyone's recipe, but only delete their own
+    let update = json!({"description": "Dense and good"});
+    let (status, _) = send_request_with_headers(&app, "PUT", &alices, Some(update), DAVE).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = send_request_with_headers(&app, "DELETE", &alices, None, DAVE).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body.unwrap()["code"], "FORBIDDEN");
+    let (status, _) = send_request_with_headers(&app, "DELETE", &daves, None, DAVE).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    // The same goes for share links
+    let (_, link) = send_request_with_headers(&app, "POST", &format!("{}/share", alices), None, ALICE).await;
+    let revoke = format!("/api/share-links/{}", link.unwrap()["token"].as_str().unwrap());
+    let (status, _) = send_request_with_headers(&app, "DELETE", &revoke, None, DAVE).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = send_request_with_headers(&app, "DELETE", &revoke, None, ALICE).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    // Owners can delete anything in the family
+    let (_, daves) = create_recipe(&app, DAVE, "Dave's Pie").await;
+    let daves = format!("/api/recipes/{}", daves["id"].as_str().unwrap());
+    let (status, _) = send_request_with_headers(&app, "DELETE", &daves, None, ALICE).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn test_owners_manage_members() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+    add_carol_and_dave(&app).await;
+
+    // Members from families.yaml are owners; people added later get their role
+    let (status, body) = send_request_with_headers(&app, "GET", "/api/family", None, DAVE).await;
+    assert_eq!(status, StatusCode::OK);
+    let roles = &body.unwrap()["roles"];
+    assert_eq!(roles["alice@example.com"], "owner");
+    assert_eq!(roles["carol@example.com"], "viewer");
+    assert_eq!(roles["dave@example.com"], "editor");
+
+    // Only owners invite and change roles
+    let (status, _) = send_request_with_headers(&app, "POST", "/api/family/invitations", None, DAVE).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let promote = json!({"role": "editor"});
+    let (status, _) =
+        send_request_with_headers(&app, "PUT", "/api/family/members/carol@example.com", Some(promote.clone()), DAVE)
+            .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, body) =
+        send_request_with_headers(&app, "PUT", "/api/family/members/carol@example.com", Some(promote), ALICE).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.unwrap()["roles"]["carol@example.com"], "editor");
+    let (status, _) = create_recipe(&app, CAROL, "Carol's Cake").await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    // Members of other families can't be touched
+    let (status, _) =
+        send_request_with_headers(&app, "DELETE", "/api/family/members/bob@example.com", None, ALICE).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // The family keeps at least one owner
+    let demote = json!({"role": "viewer"});
+    let (status, _) =
+        send_request_with_headers(&app, "PUT", "/api/family/members/alice2@example.com", Some(demote.clone()), ALICE)
+            .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = create_recipe(&app, ALICE2, "Alice2's Soup").await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) =
+        send_request_with_headers(&app, "PUT", "/api/family/members/alice@example.com", Some(demote), ALICE).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, _) =
+        send_request_with_headers(&app, "DELETE", "/api/family/members/alice@example.com", None, ALICE).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, _) =
+        send_request_with_headers(&app, "DELETE", "/api/family/members/dave@example.com", None, ALICE).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send_request_with_headers(&app, "GET", "/api/recipes", None, DAVE).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // The admin API sets any role
+    let owner = json!({"role": "owner"});
+    let (status, body) = send_request_with_headers(
+        &app,
+        "PUT",
+        "/api/admin/families/family-a/members/carol@example.com",
+        Some(owner),
+        ADMIN,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.unwrap()["roles"]["carol@example.com"], "owner");
+}
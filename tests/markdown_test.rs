@@ -0,0 +1,185 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+use common::{
+    create_test_app_with_config, create_test_db, create_two_family_config, send_bytes_request,
+    send_request_with_headers, send_text_request,
+};
+
+const ALICE: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "alice@example.com")];
+const BOB: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "bob@example.com")];
+
+async fn new_app() -> axum::Router {
+    create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await
+}
+
+async fn create_recipe(app: &axum::Router) -> Value {
+    let body = json!({
+        "title": "Chocolate Chip Cookies",
+        "description": "Classic chewy cookies.\n\nBest warm.",
+        "prep_time_minutes": 15,
+        "cook_time_minutes": 12,
+        "servings": 24,
+        "difficulty": 2,
+        "tags": ["Desserts", "Cookies"],
+        "source_url": "https://example.com/cookies",
+        "ingredients": [
+            {"name": "flour", "quantity": 2.25, "unit": "cups", "notes": "all-purpose"},
+            {"name": "eggs", "quantity": 2},
+            {"name": "salt", "unit": "pinch"}
+        ],
+        "steps": [
+            {"instruction": "Preheat the oven.", "temperature_value": 190, "temperature_unit": "Celsius"},
+            {"instruction": "Mix everything.\nChill if soft.", "duration_minutes": 30}
+        ]
+    });
+    let (status, recipe) = send_request_with_headers(app, "POST", "/api/recipes", Some(body), ALICE).await;
+    assert_eq!(status, StatusCode::CREATED);
+    recipe.unwrap()
+}
+
+async fn get_markdown(app: &axum::Router, id: &str, headers: &[(&str, &str)]) -> (StatusCode, String) {
+    let (status, body, content_type) = send_text_request(app, "GET", &format!("/api/recipes/{}.md", id), headers).await;
+    if status == StatusCode::OK {
+        assert_eq!(content_type.as_deref(), Some("text/markdown; charset=utf-8"));
+    }
+    (status, body)
+}
+
+async fn put_markdown(app: &axum::Router, uri: &str, headers: &[(&str, &str)], markdown: &str) -> (StatusCode, String) {
+    let mut headers = headers.to_vec();
+    headers.push(("Content-Type", "text/markdown"));
+    let (status, body, _) = send_bytes_request(app, "PUT", uri, &headers, markdown.as_bytes().to_vec()).await;
+    (status, String::from_utf8(body).unwrap())
+}
+
+#[tokio::test]
+async fn test_get_recipe_as_markdown() {
+    let app = new_app().await;
+    let recipe = create_recipe(&app).await;
+    let id = recipe["id"].as_str().unwrap();
+
+    let (status, markdown) = get_markdown(&app, id, ALICE).await;
+    assert_eq!(status, StatusCode::OK);
+    let expected = format!(
+        "---
+id: {id}
+prep_time_minutes: 15
+cook_time_minutes: 12
+servings: 24
+difficulty: 2
+tags:
+- Cookies
+- Desserts
+source_url: https://example.com/cookies
+---
+
+# Chocolate Chip Cookies
+
+Classic chewy cookies.
+
+Best warm.
+
+## Ingredients
+
+- **2.25 cups** flour _(all-purpose)_
+- **2** eggs
+- **pinch** salt
+
+## Steps
+
+1. Preheat the oven.
+   - Temperature: 190 Celsius
+2. Mix everything.
+   Chill if soft.
+   - Duration: 30 minutes
+"
+    );
+    assert_eq!(markdown, expected);
+
+    // The JSON representation is unchanged
+    let (status, json) = send_request_with_headers(&app, "GET", &format!("/api/recipes/{}", id), None, ALICE).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json.unwrap()["title"], "Chocolate Chip Cookies");
+}
+
+#[tokio::test]
+async fn test_markdown_round_trip_is_lossless() {
+    let app = new_app().await;
+    let recipe = create_recipe(&app).await;
+    let id = recipe["id"].as_str().unwrap();
+
+    let (_, markdown) = get_markdown(&app, id, ALICE).await;
+    let uri = format!("/api/recipes/{}.md", id);
+    let (status, saved) = put_markdown(&app, &uri, ALICE, &markdown).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(saved, markdown);
+
+    let (_, after) = send_request_with_headers(&app, "GET", &format!("/api/recipes/{}", id), None, ALICE).await;
+    let after = after.unwrap();
+    for field in ["title", "description", "prep_time_minutes", "cook_time_minutes", "servings", "difficulty", "tags", "source_url"] {
+        assert_eq!(after[field], recipe[field], "{} changed", field);
+    }
+    for (a, b) in after["ingredients"].as_array().unwrap().iter().zip(recipe["ingredients"].as_array().unwrap()) {
+        for field in ["name", "quantity", "unit", "notes"] {
+            assert_eq!(a[field], b[field]);
+        }
+    }
+    for (a, b) in after["steps"].as_array().unwrap().iter().zip(recipe["steps"].as_array().unwrap()) {
+        for field in ["instruction", "duration_minutes", "temperature_value", "temperature_unit"] {
+            assert_eq!(a[field], b[field]);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_put_markdown_replaces_recipe() {
+    let app = new_app().await;
+    let recipe = create_recipe(&app).await;
+    let id = recipe["id"].as_str().unwrap();
+
+    // Fields left out of the file are cleared; the id may be omitted
+    let markdown = "---\nservings: 12\ndifficulty: 1\ntags: [Quick]\n---\n\n# Half Batch Cookies\n\n## Ingredients\n\n- **1 cup** flour\n\n## Steps\n\n1. Mix and bake.\n";
+    let (status, _) = put_markdown(&app, &format!("/api/recipes/{}", id), ALICE, markdown).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, after) = send_request_with_headers(&app, "GET", &format!("/api/recipes/{}", id), None, ALICE).await;
+    let after = after.unwrap();
+    assert_eq!(after["title"], "Half Batch Cookies");
+    assert_eq!(after["servings"], 12);
+    assert_eq!(after["tags"], json!(["Quick"]));
+    assert!(after.get("description").is_none());
+    assert!(after.get("prep_time_minutes").is_none());
+    assert!(after.get("source_url").is_none());
+    assert_eq!(after["ingredients"].as_array().unwrap().len(), 1);
+    assert_eq!(after["steps"][0]["instruction"], "Mix and bake.");
+}
+
+#[tokio::test]
+async fn test_put_markdown_errors() {
+    let app = new_app().await;
+    let recipe = create_recipe(&app).await;
+    let id = recipe["id"].as_str().unwrap();
+    let uri = format!("/api/recipes/{}.md", id);
+
+    let (status, body) = put_markdown(&app, &uri, ALICE, "# Cookies\n\n## Ingredients\n\n- **2 cups flour\n").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let error: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(error["code"], "VALIDATION_ERROR");
+    assert_eq!(error["error"], "line 5: Unclosed '**' around the ingredient amount");
+
+    let (status, body) = put_markdown(&app, &uri, ALICE, "---\nid: someone-else\n---\n# Cookies\n").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("does not match"));
+
+    // Another family cannot read or replace the recipe
+    let (status, _) = get_markdown(&app, id, BOB).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = put_markdown(&app, &uri, BOB, "# Mine Now\n").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = get_markdown(&app, "missing", ALICE).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,199 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+use common::{
+    create_test_app_with_photos_dir, create_test_db, send_multipart_request, send_request,
+    send_request_with_headers,
+};
+
+/// The unscoped API key, which admin endpoints require
+const ADMIN: &[(&str, &str)] = &[("X-API-Key", "test-api-key")];
+
+fn png(seed: u8) -> Vec<u8> {
+    let image = image::RgbImage::from_pixel(8, 8, image::Rgb([seed, 10, 10]));
+    let mut bytes = Vec::new();
+    image::DynamicImage::ImageRgb8(image)
+        .write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageFormat::Png)
+        .unwrap();
+    bytes
+}
+
+async fn create_recipe(app: &axum::Router, title: &str) -> String {
+    let (status, body) = send_request(app, "POST", "/api/recipes", Some(json!({"title": title}))).await;
+    assert_eq!(status, StatusCode::CREATED);
+    body.unwrap()["id"].as_str().unwrap().to_string()
+}
+
+/// Add a gallery photo and return its row
+async fn add_photo(app: &axum::Router, recipe_id: &str, seed: u8) -> Value {
+    let uri = format!("/api/recipes/{}/photos", recipe_id);
+    let (status, body) = send_multipart_request(app, "POST", &uri, "photo", png(seed), "dish.png", "image/png").await;
+    assert_eq!(status, StatusCode::CREATED);
+    body.unwrap()
+}
+
+fn filename(photo: &Value) -> &str {
+    photo["filename"].as_str().unwrap()
+}
+
+fn filenames(list: &Value) -> Vec<&str> {
+    list.as_array().unwrap().iter().map(|f| f["filename"].as_str().unwrap()).collect()
+}
+
+fn thumb_of(filename: &str) -> String {
+    format!("{}.thumb.jpg", filename.rsplit_once('.').unwrap().0)
+}
+
+#[tokio::test]
+async fn test_check_and_repair_photo_storage() {
+    let dir = tempfile::tempdir().unwrap();
+    let photos = dir.path();
+    let pool = create_test_db().await;
+    let app = create_test_app_with_photos_dir(pool.clone(), photos).await;
+
+    // A recipe whose second photo file is lost, and one whose only (cover) photo is lost
+    let bread = create_recipe(&app, "Bread").await;
+    let kept = add_photo(&app, &bread, 1).await;
+    let lost = add_photo(&app, &bread, 2).await;
+    std::fs::remove_file(photos.join(filename(&lost))).unwrap();
+    let soup = create_recipe(&app, "Soup").await;
+    let lost_cover = add_photo(&app, &soup, 3).await;
+    std::fs::remove_file(photos.join(filename(&lost_cover))).unwrap();
+
+    // A cover pointing at a file that was never a photo row
+    let salad = create_recipe(&app, "Salad").await;
+    sqlx::query("UPDATE recipes SET photo_filename = 'gone.jpg' WHERE id = ?")
+        .bind(&salad)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    // Leftovers with no row: an original, a derivative and an interrupted write
+    std::fs::write(photos.join("stray.jpg"), b"x").unwrap();
+    std::fs::write(photos.join("stray.medium.jpg"), b"x").unwrap();
+    std::fs::write(photos.join("upload.png.tmp"), b"x").unwrap();
+
+    // With the default grace period, just-written files are not flagged
+    let (status, report) = send_request_with_headers(&app, "GET", "/api/admin/photos/check", None, ADMIN).await;
+    assert_eq!(status, StatusCode::OK);
+    let report = report.unwrap();
+    assert!(report["orphan_files"].as_array().unwrap().is_empty());
+    assert!(report["stale_temp_files"].as_array().unwrap().is_empty());
+    assert_eq!(report["missing_files"].as_array().unwrap().len(), 2);
+
+    let (_, report) =
+        send_request_with_headers(&app, "GET", "/api/admin/photos/check?grace_minutes=0", None, ADMIN).await;
+    let report = report.unwrap();
+    assert_eq!(report["photos_referenced"], 3);
+    assert_eq!(filenames(&report["orphan_files"]), vec!["stray.jpg", "stray.medium.jpg"]);
+    assert_eq!(filenames(&report["stale_temp_files"]), vec!["upload.png.tmp"]);
+    let missing = report["missing_files"].as_array().unwrap();
+    assert!(missing.iter().any(|m| m["photo_id"] == lost["id"] && m["is_cover"] == false));
+    assert!(missing.iter().any(|m| m["photo_id"] == lost_cover["id"] && m["is_cover"] == true));
+    assert_eq!(report["dangling_covers"], json!([salad]));
+    assert!(report.get("repaired").is_none());
+    // Checking changes nothing
+    assert!(photos.join("stray.jpg").exists());
+
+    let (status, report) =
+        send_request_with_headers(&app, "POST", "/api/admin/photos/repair?grace_minutes=0", None, ADMIN).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        report.unwrap()["repaired"],
+        json!({
+            "orphan_files_removed": 2,
+            "temp_files_removed": 1,
+            "photo_rows_removed": 2,
+            "covers_fixed": 1,
+            "errors": []
+        })
+    );
+
+    assert!(!photos.join("stray.jpg").exists());
+    assert!(!photos.join("upload.png.tmp").exists());
+    assert!(photos.join(filename(&kept)).exists());
+    assert!(photos.join(thumb_of(filename(&kept))).exists());
+
+    let (_, recipe) = send_request(&app, "GET", &format!("/api/recipes/{}", bread), None).await;
+    let recipe = recipe.unwrap();
+    assert_eq!(recipe["photos"].as_array().unwrap().len(), 1);
+    assert_eq!(recipe["photo_filename"], kept["filename"]);
+    let (_, recipe) = send_request(&app, "GET", &format!("/api/recipes/{}", soup), None).await;
+    assert!(recipe.unwrap().get("photo_filename").is_none_or(Value::is_null));
+    let (_, recipe) = send_request(&app, "GET", &format!("/api/recipes/{}", salad), None).await;
+    assert!(recipe.unwrap().get("photo_filename").is_none_or(Value::is_null));
+
+    let (_, report) =
+        send_request_with_headers(&app, "GET", "/api/admin/photos/check?grace_minutes=0", None, ADMIN).await;
+    let report = report.unwrap();
+    for problem in ["orphan_files", "missing_files", "stale_temp_files", "dangling_covers"] {
+        assert!(report[problem].as_array().unwrap().is_empty(), "{} not repaired", problem);
+    }
+}
+
+#[tokio::test]
+async fn test_admin_endpoints_require_unscoped_key() {
+    let dir = tempfile::tempdir().unwrap();
+    let app = create_test_app_with_photos_dir(create_test_db().await, dir.path()).await;
+
+    // The dev user belongs to a family
+    let (status, body) = send_request(&app, "GET", "/api/admin/photos/check", None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body.unwrap()["code"], "FORBIDDEN");
+
+    let scoped = &[("Cf-Access-Authenticated-User-Email", "test@example.com")];
+    let (status, _) = send_request_with_headers(&app, "POST", "/api/admin/photos/repair", None, scoped).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_repair_keeps_rows_when_photos_directory_is_empty() {
+    let dir = tempfile::tempdir().unwrap();
+    let app = create_test_app_with_photos_dir(create_test_db().await, dir.path()).await;
+    let recipe = create_recipe(&app, "Bread").await;
+    add_photo(&app, &recipe, 1).await;
+
+    // Simulate an unmounted volume
+    for entry in std::fs::read_dir(dir.path()).unwrap() {
+        std::fs::remove_file(entry.unwrap().path()).unwrap();
+    }
+
+    let (_, report) =
+        send_request_with_headers(&app, "POST", "/api/admin/photos/repair?grace_minutes=0", None, ADMIN).await;
+    let repaired = &report.unwrap()["repaired"];
+    assert_eq!(repaired["photo_rows_removed"], 0);
+    assert!(repaired["errors"][0].as_str().unwrap().contains("is empty"));
+
+    let (_, recipe) = send_request(&app, "GET", &format!("/api/recipes/{}", recipe), None).await;
+    assert_eq!(recipe.unwrap()["photos"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_deleting_recipe_removes_all_photo_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let photos = dir.path();
+    let app = create_test_app_with_photos_dir(create_test_db().await, photos).await;
+    let recipe = create_recipe(&app, "Bread").await;
+    let first = add_photo(&app, &recipe, 1).await;
+    let second = add_photo(&app, &recipe, 2).await;
+    assert!(photos.join(thumb_of(filename(&second))).exists());
+
+    let (status, _) = send_request(&app, "DELETE", &format!("/api/recipes/{}", recipe), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(std::fs::read_dir(photos).unwrap().count(), 0, "photo files left behind");
+    assert!(!photos.join(filename(&first)).exists());
+}
+
+#[tokio::test]
+async fn test_check_tolerates_missing_photos_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let photos = dir.path().join("photos");
+    let app = create_test_app_with_photos_dir(create_test_db().await, &photos).await;
+    std::fs::remove_dir(&photos).unwrap();
+
+    let (status, report) = send_request_with_headers(&app, "GET", "/api/admin/photos/check", None, ADMIN).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report.unwrap()["files_scanned"], 0);
+}
@@ -6,8 +6,10 @@ use serde_json::json;
 use sqlx::SqlitePool;
 
 use common::{
-    create_test_app, create_test_db, send_binary_request, send_multipart_request, send_request,
+    create_test_app, create_test_db, send_binary_request, send_bytes_request, send_multipart_request, send_request,
+    test_photos_dir,
 };
+use image::GenericImageView;
 
 #[fixture]
 async fn test_db() -> SqlitePool {
@@ -62,7 +64,7 @@ fn create_test_image_jpeg() -> Vec<u8> {
 #[tokio::test]
 async fn test_successful_photo_upload(#[future] test_db: SqlitePool) {
     let db = test_db.await;
-    let app = create_test_app(db);
+    let app = create_test_app(db).await;
 
     // Create a recipe
     let recipe_id = create_test_recipe(&app, "Test Recipe").await;
@@ -94,7 +96,7 @@ async fn test_successful_photo_upload(#[future] test_db: SqlitePool) {
 #[tokio::test]
 async fn test_upload_photo_too_large(#[future] test_db: SqlitePool) {
     let db = test_db.await;
-    let app = create_test_app(db);
+    let app = create_test_app(db).await;
 
     let recipe_id = create_test_recipe(&app, "Test Recipe").await;
 
@@ -122,7 +124,7 @@ async fn test_upload_photo_too_large(#[future] test_db: SqlitePool) {
 #[tokio::test]
 async fn test_upload_invalid_extension(#[future] test_db: SqlitePool) {
     let db = test_db.await;
-    let app = create_test_app(db);
+    let app = create_test_app(db).await;
 
     let recipe_id = create_test_recipe(&app, "Test Recipe").await;
 
@@ -147,7 +149,7 @@ async fn test_upload_invalid_extension(#[future] test_db: SqlitePool) {
 #[tokio::test]
 async fn test_upload_to_nonexistent_recipe(#[future] test_db: SqlitePool) {
     let db = test_db.await;
-    let app = create_test_app(db);
+    let app = create_test_app(db).await;
 
     let fake_id = "00000000-0000-0000-0000-000000000000";
     let image_data = create_test_image_png();
@@ -171,7 +173,7 @@ async fn test_upload_to_nonexistent_recipe(#[future] test_db: SqlitePool) {
 #[tokio::test]
 async fn test_replace_photo_different_format(#[future] test_db: SqlitePool) {
     let db = test_db.await;
-    let app = create_test_app(db);
+    let app = create_test_app(db).await;
 
     let recipe_id = create_test_recipe(&app, "Test Recipe").await;
 
@@ -215,7 +217,7 @@ async fn test_replace_photo_different_format(#[future] test_db: SqlitePool) {
 #[tokio::test]
 async fn test_retrieve_photo(#[future] test_db: SqlitePool) {
     let db = test_db.await;
-    let app = create_test_app(db);
+    let app = create_test_app(db).await;
 
     let recipe_id = create_test_recipe(&app, "Test Recipe").await;
 
@@ -238,7 +240,11 @@ async fn test_retrieve_photo(#[future] test_db: SqlitePool) {
 
     assert_eq!(status, StatusCode::OK);
     assert_eq!(content_type.unwrap(), "image/png");
-    assert_eq!(body, original_image);
+    // Uploads are re-encoded, so compare pixels rather than bytes
+    assert_eq!(
+        image::load_from_memory(&body).unwrap().to_rgba8(),
+        image::load_from_memory(&original_image).unwrap().to_rgba8()
+    );
 }
 
 // ==== Scenario: Retrieve photo for recipe without photo ====
@@ -246,7 +252,7 @@ async fn test_retrieve_photo(#[future] test_db: SqlitePool) {
 #[tokio::test]
 async fn test_retrieve_photo_not_found(#[future] test_db: SqlitePool) {
     let db = test_db.await;
-    let app = create_test_app(db);
+    let app = create_test_app(db).await;
 
     let recipe_id = create_test_recipe(&app, "Test Recipe").await;
 
@@ -262,7 +268,7 @@ async fn test_retrieve_photo_not_found(#[future] test_db: SqlitePool) {
 #[tokio::test]
 async fn test_delete_photo(#[future] test_db: SqlitePool) {
     let db = test_db.await;
-    let app = create_test_app(db);
+    let app = create_test_app(db).await;
 
     let recipe_id = create_test_recipe(&app, "Test Recipe").await;
 
@@ -302,7 +308,7 @@ async fn test_delete_photo(#[future] test_db: SqlitePool) {
 #[tokio::test]
 async fn test_recipe_deletion_removes_photo(#[future] test_db: SqlitePool) {
     let db = test_db.await;
-    let app = create_test_app(db);
+    let app = create_test_app(db).await;
 
     let recipe_id = create_test_recipe(&app, "Test Recipe").await;
 
@@ -338,7 +344,7 @@ async fn test_recipe_deletion_removes_photo(#[future] test_db: SqlitePool) {
 #[tokio::test]
 async fn test_content_type_detection(#[future] test_db: SqlitePool) {
     let db = test_db.await;
-    let app = create_test_app(db);
+    let app = create_test_app(db).await;
 
     // Test PNG
     let recipe_id_png = create_test_recipe(&app, "PNG Recipe").await;
@@ -374,3 +380,263 @@ async fn test_content_type_detection(#[future] test_db: SqlitePool) {
         send_binary_request(&app, "GET", &format!("/api/recipes/{}/photo", recipe_id_jpeg)).await;
     assert_eq!(content_type.unwrap(), "image/jpeg");
 }
+
+/// Encode a solid-colour PNG of the given size
+fn create_large_png(width: u32, height: u32) -> Vec<u8> {
+    let image = image::RgbImage::from_pixel(width, height, image::Rgb([200, 120, 40]));
+    let mut bytes = Vec::new();
+    image::DynamicImage::ImageRgb8(image)
+        .write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageFormat::Png)
+        .unwrap();
+    bytes
+}
+
+/// Upload a PNG as the cover photo and return the stored file's stem
+async fn upload_png(app: &axum::Router, recipe_id: &str, image_data: Vec<u8>) -> String {
+    let (status, body) = send_multipart_request(
+        app,
+        "POST",
+        &format!("/api/recipes/{}/photo", recipe_id),
+        "photo",
+        image_data,
+        "dish.png",
+        "image/png",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let filename = body.unwrap()["photo_filename"].as_str().unwrap().to_string();
+    filename.trim_end_matches(".png").to_string()
+}
+
+// ==== Scenario: Thumbnail and medium sizes ====
+#[rstest]
+#[tokio::test]
+async fn test_photo_sizes(#[future] test_db: SqlitePool) {
+    let db = test_db.await;
+    let app = create_test_app(db).await;
+
+    let recipe_id = create_test_recipe(&app, "Test Recipe").await;
+    let original = create_large_png(1600, 1200);
+    let stem = upload_png(&app, &recipe_id, original.clone()).await;
+
+    // Derivatives are generated on upload
+    let photos_dir = test_photos_dir();
+    assert!(photos_dir.join(format!("{}.thumb.jpg", stem)).exists());
+    assert!(photos_dir.join(format!("{}.medium.jpg", stem)).exists());
+
+    let uri = format!("/api/recipes/{}/photo", recipe_id);
+    let (status, thumb, headers) = send_bytes_request(&app, "GET", &format!("{}?size=thumb", uri), &[], vec![]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers["content-type"], "image/jpeg");
+    assert_eq!(image::load_from_memory(&thumb).unwrap().dimensions(), (320, 240));
+
+    let (_, medium, _) = send_bytes_request(&app, "GET", &format!("{}?size=medium", uri), &[], vec![]).await;
+    assert_eq!(image::load_from_memory(&medium).unwrap().dimensions(), (1280, 960));
+
+    let (_, full, headers) = send_bytes_request(&app, "GET", &format!("{}?size=full", uri), &[], vec![]).await;
+    assert_eq!(full, original);
+    assert_eq!(headers["content-type"], "image/png");
+
+    let (status, _) = send_request(&app, "GET", &format!("{}?size=huge", uri), None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+// ==== Scenario: Derivatives are created lazily for existing photos ====
+#[rstest]
+#[tokio::test]
+async fn test_photo_derivative_generated_on_demand(#[future] test_db: SqlitePool) {
+    let db = test_db.await;
+    let app = create_test_app(db).await;
+
+    let recipe_id = create_test_recipe(&app, "Test Recipe").await;
+    let stem = upload_png(&app, &recipe_id, create_large_png(600, 400)).await;
+
+    // Simulate a photo uploaded before derivatives existed
+    let thumb_path = test_photos_dir().join(format!("{}.thumb.jpg", stem));
+    std::fs::remove_file(&thumb_path).unwrap();
+
+    let (status, thumb, _) =
+        send_bytes_request(&app, "GET", &format!("/api/recipes/{}/photo?size=thumb", recipe_id), &[], vec![]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(image::load_from_memory(&thumb).unwrap().dimensions(), (320, 213));
+    assert!(thumb_path.exists());
+}
+
+// ==== Scenario: Caching headers ====
+#[rstest]
+#[tokio::test]
+async fn test_photo_etag_and_cache_control(#[future] test_db: SqlitePool) {
+    let db = test_db.await;
+    let app = create_test_app(db).await;
+
+    let recipe_id = create_test_recipe(&app, "Test Recipe").await;
+    upload_png(&app, &recipe_id, create_large_png(400, 400)).await;
+    let uri = format!("/api/recipes/{}/photo?size=thumb", recipe_id);
+
+    let (_, _, headers) = send_bytes_request(&app, "GET", &uri, &[], vec![]).await;
+    assert_eq!(headers["cache-control"], "private, no-cache");
+    let etag = headers["etag"].to_str().unwrap().to_string();
+    assert!(etag.starts_with('"'));
+
+    let (status, body, headers) = send_bytes_request(&app, "GET", &uri, &[("If-None-Match", &etag)], vec![]).await;
+    assert_eq!(status, StatusCode::NOT_MODIFIED);
+    assert!(body.is_empty());
+    assert_eq!(headers["etag"], etag.as_str());
+
+    // Replacing the photo changes the tag
+    upload_png(&app, &recipe_id, create_large_png(500, 300)).await;
+    let (status, _, headers) = send_bytes_request(&app, "GET", &uri, &[("If-None-Match", &etag)], vec![]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_ne!(headers["etag"], etag.as_str());
+
+    // Shared photos are publicly cacheable and support sizes too
+    let (_, link) = send_request(&app, "POST", &format!("/api/recipes/{}/share", recipe_id), None).await;
+    let share_url = link.unwrap()["url"].as_str().unwrap().to_string();
+    let (status, thumb, headers) =
+        send_bytes_request(&app, "GET", &format!("{}/photo?size=thumb", share_url), &[], vec![]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers["cache-control"], "public, max-age=300");
+    assert_eq!(image::load_from_memory(&thumb).unwrap().dimensions(), (320, 192));
+}
+
+// ==== Scenario: Deleting a photo removes its derivatives ====
+#[rstest]
+#[tokio::test]
+async fn test_delete_photo_removes_derivatives(#[future] test_db: SqlitePool) {
+    let db = test_db.await;
+    let app = create_test_app(db).await;
+
+    let recipe_id = create_test_recipe(&app, "Test Recipe").await;
+    let stem = upload_png(&app, &recipe_id, create_large_png(400, 400)).await;
+    let photos_dir = test_photos_dir();
+    let thumb_path = photos_dir.join(format!("{}.thumb.jpg", stem));
+    assert!(thumb_path.exists());
+
+    let (status, _) = send_request(&app, "DELETE", &format!("/api/recipes/{}/photo", recipe_id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(!thumb_path.exists());
+    assert!(!photos_dir.join(format!("{}.medium.jpg", stem)).exists());
+    assert!(!photos_dir.join(format!("{}.png", stem)).exists());
+}
+
+/// Encode a JPEG whose left half is red and right half blue
+fn create_two_tone_jpeg(width: u32, height: u32) -> Vec<u8> {
+    let image = image::RgbImage::from_fn(width, height, |x, _| {
+        if x < width / 2 { image::Rgb([220, 20, 20]) } else { image::Rgb([20, 20, 220]) }
+    });
+    let mut bytes = Vec::new();
+    image::DynamicImage::ImageRgb8(image)
+        .write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageFormat::Jpeg)
+        .unwrap();
+    bytes
+}
+
+/// Insert an EXIF segment, as written by a phone camera, holding an
+/// orientation tag and a GPS latitude
+fn with_exif(jpeg: &[u8], orientation: u16) -> Vec<u8> {
+    let mut tiff = b"II\x2A\x00\x08\x00\x00\x00".to_vec();
+    // IFD0: Orientation, then a pointer to the GPS IFD at offset 38
+    tiff.extend_from_slice(&2u16.to_le_bytes());
+    tiff.extend_from_slice(&[0x12, 0x01, 0x03, 0x00, 0x01, 0x00, 0x00, 0x00]);
+    tiff.extend_from_slice(&orientation.to_le_bytes());
+    tiff.extend_from_slice(&[0x00, 0x00]);
+    tiff.extend_from_slice(&[0x25, 0x88, 0x04, 0x00, 0x01, 0x00, 0x00, 0x00, 38, 0x00, 0x00, 0x00]);
+    tiff.extend_from_slice(&0u32.to_le_bytes());
+    // GPS IFD: GPSLatitudeRef = "N"
+    tiff.extend_from_slice(&1u16.to_le_bytes());
+    tiff.extend_from_slice(&[0x01, 0x00, 0x02, 0x00, 0x02, 0x00, 0x00, 0x00, b'N', 0x00, 0x00, 0x00]);
+    tiff.extend_from_slice(&0u32.to_le_bytes());
+
+    let mut segment = vec![0xFF, 0xE1];
+    segment.extend_from_slice(&((2 + 6 + tiff.len()) as u16).to_be_bytes());
+    segment.extend_from_slice(b"Exif\0\0");
+    segment.extend_from_slice(&tiff);
+
+    let mut bytes = jpeg[..2].to_vec();
+    bytes.extend_from_slice(&segment);
+    bytes.extend_from_slice(&jpeg[2..]);
+    bytes
+}
+
+// ==== Scenario: Files that only claim to be images are rejected ====
+#[rstest]
+#[tokio::test]
+async fn test_upload_rejects_disguised_files(#[future] test_db: SqlitePool) {
+    let db = test_db.await;
+    let app = create_test_app(db).await;
+
+    let recipe_id = create_test_recipe(&app, "Test Recipe").await;
+    let uri = format!("/api/recipes/{}/photo", recipe_id);
+
+    // Arbitrary bytes renamed to .jpg
+    let (status, body) =
+        send_multipart_request(&app, "POST", &uri, "photo", b"<script>alert(1)</script>".to_vec(), "dish.jpg", "image/jpeg")
+            .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body.unwrap()["code"], "UNSUPPORTED_FILE_TYPE");
+
+    // Right magic bytes, but not a decodable image
+    let mut truncated = create_large_png(64, 64);
+    truncated.truncate(40);
+    let (status, body) = send_multipart_request(&app, "POST", &uri, "photo", truncated, "dish.png", "image/png").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.unwrap()["error"].as_str().unwrap().contains("could not be decoded"));
+
+    let (status, _, _) = send_binary_request(&app, "GET", &uri).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+// ==== Scenario: The stored format follows the content, not the label ====
+#[rstest]
+#[tokio::test]
+async fn test_upload_uses_detected_format(#[future] test_db: SqlitePool) {
+    let db = test_db.await;
+    let app = create_test_app(db).await;
+
+    let recipe_id = create_test_recipe(&app, "Test Recipe").await;
+    let uri = format!("/api/recipes/{}/photo", recipe_id);
+
+    let (status, body) =
+        send_multipart_request(&app, "POST", &uri, "photo", create_large_png(10, 10), "dish.jpg", "image/jpeg").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.unwrap()["photo_filename"].as_str().unwrap().ends_with(".png"));
+
+    let (_, photo, content_type) = send_binary_request(&app, "GET", &uri).await;
+    assert_eq!(content_type.as_deref(), Some("image/png"));
+    assert!(photo.starts_with(b"\x89PNG"));
+}
+
+// ==== Scenario: EXIF and GPS metadata are stripped, orientation applied ====
+#[rstest]
+#[tokio::test]
+async fn test_upload_strips_metadata_and_applies_orientation(#[future] test_db: SqlitePool) {
+    let db = test_db.await;
+    let app = create_test_app(db).await;
+
+    let recipe_id = create_test_recipe(&app, "Test Recipe").await;
+    let uri = format!("/api/recipes/{}/photo", recipe_id);
+
+    // Orientation 6: the camera was held upright, so rotate 90° clockwise
+    let original = with_exif(&create_two_tone_jpeg(40, 20), 6);
+    assert!(original.windows(6).any(|w| w == b"Exif\0\0"));
+    let (status, _) = send_multipart_request(&app, "POST", &uri, "photo", original, "dish.jpg", "image/jpeg").await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, stored, _) = send_binary_request(&app, "GET", &uri).await;
+    assert!(!stored.windows(4).any(|w| w == b"Exif"));
+    assert!(!stored.windows(2).any(|w| w == [0xFF, 0xE1]));
+
+    let image = image::load_from_memory(&stored).unwrap();
+    assert_eq!(image.dimensions(), (20, 40));
+    // The left (red) half is now on top
+    let top = image.get_pixel(10, 5).0;
+    let bottom = image.get_pixel(10, 35).0;
+    assert!(top[0] > 150 && top[2] < 100);
+    assert!(bottom[2] > 150 && bottom[0] < 100);
+
+    // The shared page serves the cleaned file too
+    let (_, link) = send_request(&app, "POST", &format!("/api/recipes/{}/share", recipe_id), None).await;
+    let share_url = link.unwrap()["url"].as_str().unwrap().to_string();
+    let (_, shared, _) = send_binary_request(&app, "GET", &format!("{}/photo", share_url)).await;
+    assert_eq!(shared, stored);
+}
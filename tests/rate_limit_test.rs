@@ -0,0 +1,243 @@
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+use recipe_vault::llm_quota::LlmQuota;
+
+use common::{create_test_app_with_limits, create_test_db, send_bytes_request, send_request_with_headers};
+
+const ALICE: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "alice@example.com")];
+const ALICE2: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "alice2@example.com")];
+const BOB: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "bob@example.com")];
+
+/// Send a JSON request, returning the Retry-After header along with the response
+async fn send(
+    app: &axum::Router,
+    method: &str,
+    uri: &str,
+    body: Value,
+    headers: &[(&str, &str)],
+) -> (StatusCode, Option<u64>, Value) {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json");
+    for (key, value) in headers {
+        request = request.header(*key, *value);
+    }
+    let response = app
+        .clone()
+        .oneshot(request.body(Body::from(body.to_string())).unwrap())
+        .await
+        .unwrap();
+
+    let status = response.status();
+    let retry_after = response
+        .headers()
+        .get(header::RETRY_AFTER)
+        .map(|value| value.to_str().unwrap().parse().unwrap());
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, retry_after, serde_json::from_slice(&bytes).unwrap_or_default())
+}
+
+fn recipe(title: &str) -> Value {
+    json!({"title": title, "ingredients": [{"name": "flour"}]})
+}
+
+/// Serve the HTML fixtures on a random local port, returning the base URL
+async fn serve_fixtures() -> String {
+    let app = axum::Router::new().nest_service("/", tower_http::services::ServeDir::new("test_fixtures/html"));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    format!("http://{}", addr)
+}
+
+#[tokio::test]
+async fn test_recipe_changes_are_limited_per_user() {
+    let app = create_test_app_with_limits(create_test_db().await, |config| {
+        config.rate_limits.mutations_per_minute = Some(2);
+    })
+    .await;
+
+    let (status, _, _) = send(&app, "POST", "/api/recipes", recipe("Soda Bread"), ALICE).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, _, _) = send(&app, "POST", "/api/recipes", recipe("Barmbrack"), ALICE).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, retry_after, body) = send(&app, "POST", "/api/recipes", recipe("Farls"), ALICE).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(body["code"], "TOO_MANY_REQUESTS");
+    assert!(retry_after.is_some_and(|seconds| (1..=30).contains(&seconds)));
+    let cooklang = b"Mix @flour{500%g} with @buttermilk{400%ml}.".to_vec();
+    let (status, _, _) = send_bytes_request(&app, "POST", "/api/import/cooklang?title=Farls", ALICE, cooklang).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+
+    // Reading isn't limited, and other people have their own allowance
+    let (status, _) = send_request_with_headers(&app, "GET", "/api/recipes", None, ALICE).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _, _) = send(&app, "POST", "/api/recipes", recipe("Bob's Stew"), BOB).await;
+    assert_eq!(status, StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn test_api_keys_have_their_own_allowance() {
+    let app = create_test_app_with_limits(create_test_db().await, |config| {
+        config.rate_limits.mutations_per_minute = Some(2);
+    })
+    .await;
+    let (status, _, created) = send(&app, "POST", "/api/api-keys", json!({"label": "Laptop"}), ALICE).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let laptop = [("X-API-Key", created["key"].as_str().unwrap())];
+    let (_, _, created) = send(&app, "POST", "/api/api-keys", json!({"label": "Script"}), ALICE).await;
+    let script = [("X-API-Key", created["key"].as_str().unwrap())];
+
+    // A script using up its key's allowance leaves Alice's own and her
+    // other keys' alone
+    for title in ["Scones", "Farls"] {
+        let (status, _, _) = send(&app, "POST", "/api/recipes", recipe(title), &script).await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+    let (status, _, _) = send(&app, "POST", "/api/recipes", recipe("Barmbrack"), &script).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+
+    let (status, _, _) = send(&app, "POST", "/api/recipes", recipe("Soda Bread"), ALICE).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, _, _) = send(&app, "POST", "/api/recipes", recipe("Boxty"), &laptop).await;
+    assert_eq!(status, StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn test_share_links_are_limited() {
+    let app = create_test_app_with_limits(create_test_db().await, |config| {
+        config.rate_limits.shares_per_minute = Some(1);
+    })
+    .await;
+    let (_, _, created) = send(&app, "POST", "/api/recipes", recipe("Soda Bread"), ALICE).await;
+    let share = format!("/api/recipes/{}/share", created["id"].as_str().unwrap());
+
+    let (status, _, _) = send(&app, "POST", &share, json!({}), ALICE).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, retry_after, _) = send(&app, "POST", &share, json!({}), ALICE).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert!(retry_after.is_some_and(|seconds| (1..=60).contains(&seconds)));
+
+    let (status, _) = send_request_with_headers(&app, "GET", "/api/share-links", None, ALICE).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_shopping_list_links_are_limited() {
+    let app = create_test_app_with_limits(create_test_db().await, |config| {
+        config.rate_limits.shares_per_minute = Some(1);
+    })
+    .await;
+    let (_, _, created) = send(&app, "POST", "/api/recipes", recipe("Irish Stew"), ALICE).await;
+    let link = json!({"recipe_ids": [created["id"]]});
+
+    let (status, _, _) = send(&app, "POST", "/api/shopping-list/share", link.clone(), ALICE).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, retry_after, _) = send(&app, "POST", "/api/shopping-list/share", link, ALICE).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert!(retry_after.is_some());
+
+    let (status, _) = send_request_with_headers(&app, "GET", "/api/shopping-list/aisles", None, ALICE).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_vault_restores_are_limited() {
+    let app = create_test_app_with_limits(create_test_db().await, |config| {
+        config.rate_limits.mutations_per_minute = Some(1);
+    })
+    .await;
+
+    let (status, _, _) = send_bytes_request(&app, "POST", "/api/import", ALICE, b"not a zip".to_vec()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _, body) = send_bytes_request(&app, "POST", "/api/import", ALICE, b"not a zip".to_vec()).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS, "{:?}", body);
+
+    let (status, _) = send_request_with_headers(&app, "GET", "/api/export", None, ALICE).await;
+    assert_ne!(status, StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn test_family_changes_are_limited() {
+    let app = create_test_app_with_limits(create_test_db().await, |config| {
+        config.rate_limits.mutations_per_minute = Some(1);
+    })
+    .await;
+    let invite = json!({"email": "carol@example.com"});
+
+    let (status, _, _) = send(&app, "POST", "/api/family/invitations", invite.clone(), ALICE).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, _, _) = send(&app, "POST", "/api/family/invitations", invite, ALICE).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+
+    let (status, _) = send_request_with_headers(&app, "GET", "/api/family", None, ALICE).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_llm_calls_have_a_daily_family_quota() {
+    let pool = create_test_db().await;
+    let app = create_test_app_with_limits(pool.clone(), |config| {
+        config.llm_daily_calls_per_family = Some(1);
+    })
+    .await;
+    let base = serve_fixtures().await;
+    let import = json!({"url": format!("{}/no_structured_data.html", base), "save": false});
+
+    let (status, _, body) = send(&app, "POST", "/api/import/url", import.clone(), ALICE).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["source"], "llm");
+
+    // The quota is the family's, so Alice2 has used it too
+    let (status, retry_after, body) = send(&app, "POST", "/api/import/url", import.clone(), ALICE2).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert!(body["error"].as_str().unwrap().contains("midnight UTC"));
+    assert!(retry_after.is_some_and(|seconds| (1..=24 * 60 * 60).contains(&seconds)));
+
+    let (status, _, _) = send(&app, "POST", "/api/import/url", import, BOB).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (calls,): (i64,) = sqlx::query_as("SELECT calls FROM llm_usage WHERE family_id = 'family-a'")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(calls, 1);
+}
+
+#[tokio::test]
+async fn test_llm_quota_reservations_do_not_overshoot() {
+    let pool = create_test_db().await;
+    let quota = LlmQuota::new(pool.clone(), Some(3));
+
+    let reservations: Vec<_> = (0..10)
+        .map(|_| {
+            let quota = quota.clone();
+            tokio::spawn(async move { quota.reserve(Some("family-a")).await })
+        })
+        .collect();
+    let mut granted = 0;
+    for reservation in reservations {
+        if reservation.await.unwrap().is_ok() {
+            granted += 1;
+        }
+    }
+    assert_eq!(granted, 3);
+
+    let (calls,): (i64,) = sqlx::query_as("SELECT calls FROM llm_usage WHERE family_id = 'family-a'")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(calls, 3);
+    assert!(quota.reserve(None).await.is_ok());
+}
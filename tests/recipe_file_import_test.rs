@@ -0,0 +1,197 @@
+mod common;
+
+use axum::http::StatusCode;
+
+use common::{create_test_app, create_test_db, send_binary_request, send_multipart_request, send_request};
+use recipe_vault::formats::{parse_mealmaster, parse_paprika_archive, parse_recipe_file, RecipeFileFormat};
+
+fn fixture(path: &str) -> Vec<u8> {
+    std::fs::read(format!("test_fixtures/{}", path)).expect("fixture exists")
+}
+
+#[test]
+fn test_paprika_archive() {
+    let entries = parse_paprika_archive(&fixture("paprika/family.paprikarecipes")).unwrap();
+    assert_eq!(entries.len(), 3);
+
+    let pie = entries[0].as_ref().unwrap();
+    let recipe = &pie.recipe;
+    assert_eq!(recipe.title, "Grandma's Apple Pie");
+    assert_eq!(recipe.description.as_deref(), Some("The Sunday pie.\n\nNotes: Use a deep dish."));
+    assert_eq!(recipe.servings, Some(8));
+    assert_eq!(recipe.prep_time_minutes, Some(30));
+    assert_eq!(recipe.cook_time_minutes, Some(60));
+    assert_eq!(recipe.tags, vec!["Desserts", "Baking"]);
+    assert_eq!(recipe.source_url.as_deref(), Some("https://example.com/grandmas-pie"));
+
+    assert_eq!(recipe.ingredients.len(), 6);
+    assert_eq!(recipe.ingredients[0].name, "plain flour");
+    assert_eq!(recipe.ingredients[0].quantity, Some(300.0));
+    assert_eq!(recipe.ingredients[1].notes.as_deref(), Some("cubed"));
+    assert_eq!(recipe.ingredients[3].name, "Bramley apples");
+    assert_eq!(recipe.ingredients[3].notes.as_deref(), Some("peeled and sliced"));
+
+    assert_eq!(recipe.steps.len(), 3);
+    assert_eq!(recipe.steps[1].instruction, "Toss the apples with the sugar and cinnamon.");
+
+    assert!(pie.photo.as_ref().unwrap().starts_with(b"\x89PNG"));
+    assert_eq!(
+        pie.warnings,
+        vec!["Ingredient heading 'For the crust' was dropped", "Ingredient heading 'For the filling' was dropped"]
+    );
+
+    let soup = entries[1].as_ref().unwrap();
+    assert_eq!(soup.recipe.title, "Tomato Soup");
+    assert_eq!(soup.recipe.description, None);
+    assert_eq!(soup.recipe.cook_time_minutes, Some(25));
+    assert_eq!(soup.recipe.steps.len(), 3);
+    assert!(soup.photo.is_none());
+
+    // A corrupt entry doesn't stop the rest of the file
+    assert!(entries[2].is_err());
+}
+
+#[test]
+fn test_single_paprika_recipe() {
+    let (format, entries) = parse_recipe_file(&fixture("paprika/single.paprikarecipe")).unwrap();
+    assert_eq!(format, RecipeFileFormat::Paprika);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].as_ref().unwrap().recipe.title, "Tomato Soup");
+}
+
+#[test]
+fn test_mealmaster_file() {
+    let text = String::from_utf8(fixture("mealmaster/family.mmf")).unwrap();
+    let entries = parse_mealmaster(&text).unwrap();
+    assert_eq!(entries.len(), 2);
+
+    let cookies = entries[0].as_ref().unwrap();
+    let recipe = &cookies.recipe;
+    assert_eq!(recipe.title, "Chocolate Chip Cookies");
+    assert_eq!(recipe.tags, vec!["Cookies", "Desserts"]);
+    assert_eq!(recipe.servings, Some(36));
+
+    let names: Vec<&str> = recipe.ingredients.iter().map(|i| i.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "Butter",
+            "Brown sugar",
+            "Sugar",
+            "Eggs",
+            "All-purpose flour",
+            "Baking soda",
+            "Chocolate chips",
+            "Vanilla extract"
+        ]
+    );
+    assert_eq!(recipe.ingredients[0].unit.as_deref(), Some("cup"));
+    assert_eq!(recipe.ingredients[0].notes.as_deref(), Some("softened"));
+    assert_eq!(recipe.ingredients[1].quantity, Some(0.75));
+    assert_eq!(recipe.ingredients[3].quantity, Some(2.0));
+    assert_eq!(recipe.ingredients[3].unit, None);
+    assert_eq!(recipe.ingredients[4].quantity, Some(2.25));
+    assert_eq!(recipe.ingredients[5].unit.as_deref(), Some("tsp"));
+    // Continuation line
+    assert_eq!(recipe.ingredients[6].notes.as_deref(), Some("semi-sweet"));
+
+    assert_eq!(recipe.steps.len(), 3);
+    assert_eq!(
+        recipe.steps[0].instruction,
+        "Preheat oven to 375 F. Cream the butter and sugars until fluffy."
+    );
+    assert_eq!(cookies.warnings, vec!["Ingredient heading 'FILLING' was dropped"]);
+
+    let onions = entries[1].as_ref().unwrap();
+    assert_eq!(onions.recipe.title, "Quick Pickled Onions");
+    assert_eq!(onions.recipe.servings, Some(6));
+    assert_eq!(onions.recipe.ingredients.len(), 4);
+    assert_eq!(onions.recipe.ingredients[0].unit.as_deref(), Some("medium"));
+    assert_eq!(onions.recipe.ingredients[2].unit.as_deref(), Some("tbsp"));
+    assert_eq!(onions.recipe.ingredients[3].name, "Salt to taste");
+    assert_eq!(onions.recipe.steps.len(), 1);
+}
+
+#[test]
+fn test_unrecognized_file() {
+    assert!(parse_recipe_file(b"just some text").is_err());
+}
+
+async fn upload(app: &axum::Router, uri: &str, path: &str) -> (StatusCode, serde_json::Value) {
+    let (status, body) = send_multipart_request(
+        app,
+        "POST",
+        uri,
+        "file",
+        fixture(path),
+        path.rsplit('/').next().unwrap(),
+        "application/octet-stream",
+    )
+    .await;
+    (status, body.unwrap())
+}
+
+#[tokio::test]
+async fn test_upload_paprika_export() {
+    let app = create_test_app(create_test_db().await).await;
+
+    let (status, report) = upload(&app, "/api/import/file", "paprika/family.paprikarecipes").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report["format"], "paprika");
+    assert_eq!(report["imported"], 2);
+    assert_eq!(report["failed"], 1);
+
+    let recipes = report["recipes"].as_array().unwrap();
+    assert_eq!(recipes[0]["status"], "imported");
+    assert_eq!(recipes[0]["warnings"].as_array().unwrap().len(), 2);
+    assert_eq!(recipes[2]["title"], "Broken");
+    assert_eq!(recipes[2]["status"], "failed");
+
+    let pie_id = recipes[0]["recipe_id"].as_str().unwrap();
+    let (_, pie) = send_request(&app, "GET", &format!("/api/recipes/{}", pie_id), None).await;
+    let pie = pie.unwrap();
+    assert_eq!(pie["tags"], serde_json::json!(["Baking", "Desserts"]));
+    assert_eq!(pie["photos"].as_array().unwrap().len(), 1);
+    assert_eq!(pie["photo_filename"], pie["photos"][0]["filename"]);
+
+    let (status, photo, content_type) =
+        send_binary_request(&app, "GET", &format!("/api/recipes/{}/photo", pie_id)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("image/png"));
+    assert!(photo.starts_with(b"\x89PNG"));
+}
+
+#[tokio::test]
+async fn test_upload_reports_conflicts() {
+    let app = create_test_app(create_test_db().await).await;
+    upload(&app, "/api/import/file", "mealmaster/family.mmf").await;
+
+    let (status, report) = upload(&app, "/api/import/file", "mealmaster/family.mmf").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report["format"], "meal_master");
+    assert_eq!(report["imported"], 0);
+    assert_eq!(report["conflicts"], 2);
+    assert_eq!(report["recipes"][0]["status"], "conflict");
+
+    let (_, report) = upload(&app, "/api/import/file?conflict=rename", "mealmaster/family.mmf").await;
+    assert_eq!(report["imported"], 2);
+    assert_eq!(report["recipes"][0]["imported_title"], "Chocolate Chip Cookies (2)");
+}
+
+#[tokio::test]
+async fn test_upload_rejects_unknown_files() {
+    let app = create_test_app(create_test_db().await).await;
+
+    let (status, body) = send_multipart_request(
+        &app,
+        "POST",
+        "/api/import/file",
+        "file",
+        b"hello".to_vec(),
+        "notes.txt",
+        "text/plain",
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body.unwrap()["code"], "VALIDATION_ERROR");
+}
@@ -7,7 +7,7 @@ use common::{create_test_db, create_test_app, send_request};
 #[tokio::test]
 async fn recipe_list_and_ordering() {
     let pool = create_test_db().await;
-    let app = create_test_app(pool.clone());
+    let app = create_test_app(pool.clone()).await;
 
     let r1 = json!({ "title": "Banana Bread" });
     let r2 = json!({ "title": "Apple Pie" });
@@ -31,7 +31,7 @@ async fn recipe_list_and_ordering() {
 #[tokio::test]
 async fn deletion_fallback_and_creation_visibility() {
     let pool = create_test_db().await;
-    let app = create_test_app(pool.clone());
+    let app = create_test_app(pool.clone()).await;
 
     // Create two recipes
     let (s, body) = send_request(&app, "POST", "/api/recipes", Some(json!({ "title": "One" }))).await;
@@ -0,0 +1,319 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+use sqlx::SqlitePool;
+
+use common::{
+    create_test_app, create_test_app_with_config, create_test_db, create_two_family_config, send_binary_request,
+    send_bytes_request, send_request, send_request_with_headers, send_text_request, test_photos_dir,
+};
+
+const ALICE: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "alice@example.com")];
+const BOB: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "bob@example.com")];
+const BOUNDARY: &str = "----RecipePhotosBoundary";
+
+/// A small PNG, distinct per seed so uploads can be told apart
+fn png(seed: u8) -> Vec<u8> {
+    let image = image::RgbImage::from_pixel(8, 8, image::Rgb([seed, 100, 50]));
+    let mut bytes = Vec::new();
+    image::DynamicImage::ImageRgb8(image)
+        .write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageFormat::Png)
+        .unwrap();
+    bytes
+}
+
+/// POST a photo with optional text fields; returns (status, JSON body)
+async fn upload(
+    app: &axum::Router,
+    uri: &str,
+    headers: &[(&str, &str)],
+    photo: Vec<u8>,
+    fields: &[(&str, &str)],
+) -> (StatusCode, Value) {
+    let mut body = Vec::new();
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!("--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n", BOUNDARY, name, value)
+                .as_bytes(),
+        );
+    }
+    body.extend_from_slice(
+        format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"photo\"; filename=\"dish.png\"\r\nContent-Type: image/png\r\n\r\n",
+            BOUNDARY
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(&photo);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", BOUNDARY).as_bytes());
+
+    let content_type = format!("multipart/form-data; boundary={}", BOUNDARY);
+    let mut headers = headers.to_vec();
+    headers.push(("Content-Type", &content_type));
+    let (status, body, _) = send_bytes_request(app, "POST", uri, &headers, body).await;
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+async fn create_recipe(app: &axum::Router, headers: &[(&str, &str)], title: &str) -> String {
+    let recipe = json!({
+        "title": title,
+        "steps": [
+            {"instruction": "Make the dough."},
+            {"instruction": "Dimple and bake."}
+        ]
+    });
+    let (status, body) = send_request_with_headers(app, "POST", "/api/recipes", Some(recipe), headers).await;
+    assert_eq!(status, StatusCode::CREATED);
+    body.unwrap()["id"].as_str().unwrap().to_string()
+}
+
+async fn get_recipe(app: &axum::Router, id: &str) -> Value {
+    let (status, body) = send_request(app, "GET", &format!("/api/recipes/{}", id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    body.unwrap()
+}
+
+#[tokio::test]
+async fn test_gallery_upload_order_and_cover() {
+    let app = create_test_app(create_test_db().await).await;
+    let id = create_recipe(&app, &[], "Focaccia").await;
+    let uri = format!("/api/recipes/{}/photos", id);
+
+    // The first photo becomes the cover
+    let (status, first) = upload(&app, &uri, &[], png(1), &[]).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(first["is_cover"], true);
+    assert_eq!(first["position"], 0);
+
+    let (status, second) =
+        upload(&app, &uri, &[], png(2), &[("caption", " Dimpled "), ("step_position", "1")]).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(second["is_cover"], false);
+    assert_eq!(second["caption"], "Dimpled");
+    assert_eq!(second["step_position"], 1);
+
+    let (_, third) = upload(&app, &uri, &[], png(3), &[("cover", "true")]).await;
+    assert_eq!(third["is_cover"], true);
+
+    let recipe = get_recipe(&app, &id).await;
+    assert_eq!(recipe["photo_filename"], third["filename"]);
+    let photos = recipe["photos"].as_array().unwrap();
+    assert_eq!(photos.len(), 3);
+    assert_eq!(photos.iter().filter(|p| p["is_cover"] == true).count(), 1);
+
+    // The cover is what the single-photo endpoint serves
+    let (_, cover, _) = send_binary_request(&app, "GET", &format!("/api/recipes/{}/photo", id)).await;
+    assert_eq!(cover, png(3));
+    let (status, bytes, _) =
+        send_binary_request(&app, "GET", &format!("{}/{}", uri, second["id"].as_str().unwrap())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(bytes, png(2));
+
+    // Reorder
+    let order = json!({"photo_ids": [third["id"], first["id"], second["id"]]});
+    let (status, reordered) = send_request(&app, "PUT", &format!("{}/order", uri), Some(order)).await;
+    assert_eq!(status, StatusCode::OK);
+    let ids: Vec<Value> = reordered.unwrap().as_array().unwrap().iter().map(|p| p["id"].clone()).collect();
+    assert_eq!(ids, vec![third["id"].clone(), first["id"].clone(), second["id"].clone()]);
+
+    let (status, _) = send_request(&app, "PUT", &format!("{}/order", uri), Some(json!({"photo_ids": [first["id"]]}))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Update caption, step link and cover
+    let photo_uri = format!("{}/{}", uri, second["id"].as_str().unwrap());
+    let (status, updated) =
+        send_request(&app, "PUT", &photo_uri, Some(json!({"caption": null, "cover": true}))).await;
+    assert_eq!(status, StatusCode::OK);
+    let updated = updated.unwrap();
+    assert!(updated.get("caption").is_none());
+    assert_eq!(updated["step_position"], 1);
+    assert_eq!(updated["is_cover"], true);
+
+    let (status, _) = send_request(&app, "PUT", &photo_uri, Some(json!({"step_position": 7}))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Deleting the cover promotes the first remaining photo
+    let (status, _) = send_request(&app, "DELETE", &photo_uri, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert!(!test_photos_dir().join(second["filename"].as_str().unwrap()).exists());
+    let recipe = get_recipe(&app, &id).await;
+    assert_eq!(recipe["photo_filename"], third["filename"]);
+    assert_eq!(recipe["photos"].as_array().unwrap().len(), 2);
+
+    let (status, _) = send_request(&app, "DELETE", &photo_uri, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_gallery_family_tenancy() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+    let id = create_recipe(&app, ALICE, "Focaccia").await;
+    let uri = format!("/api/recipes/{}/photos", id);
+    let (status, photo) = upload(&app, &uri, ALICE, png(1), &[]).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let photo_uri = format!("{}/{}", uri, photo["id"].as_str().unwrap());
+
+    let (status, _) = upload(&app, &uri, BOB, png(2), &[]).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send_request_with_headers(&app, "GET", &uri, None, BOB).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _, _) = send_bytes_request(&app, "GET", &photo_uri, BOB, vec![]).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send_request_with_headers(&app, "PUT", &photo_uri, Some(json!({"cover": true})), BOB).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send_request_with_headers(&app, "DELETE", &photo_uri, None, BOB).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // A photo ID only works under its own recipe
+    let other = create_recipe(&app, ALICE, "Ciabatta").await;
+    let (status, _, _) = send_bytes_request(
+        &app,
+        "GET",
+        &format!("/api/recipes/{}/photos/{}", other, photo["id"].as_str().unwrap()),
+        ALICE,
+        vec![],
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, list) = send_request_with_headers(&app, "GET", &uri, None, ALICE).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(list.unwrap().as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_share_page_gallery_and_step_photos() {
+    let app = create_test_app(create_test_db().await).await;
+    let id = create_recipe(&app, &[], "Focaccia").await;
+    let uri = format!("/api/recipes/{}/photos", id);
+    upload(&app, &uri, &[], png(1), &[]).await;
+    let (_, step_photo) = upload(&app, &uri, &[], png(2), &[("caption", "Dimples"), ("step_position", "1")]).await;
+    let (_, extra) = upload(&app, &uri, &[], png(3), &[("caption", "Crumb shot")]).await;
+
+    let (_, link) = send_request(&app, "POST", &format!("/api/recipes/{}/share", id), None).await;
+    let share_url = link.unwrap()["url"].as_str().unwrap().to_string();
+    let (status, html, _) = send_text_request(&app, "GET", &share_url, &[]).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let step_src = format!("{}/photos/{}?size=medium", share_url, step_photo["id"].as_str().unwrap());
+    let step_at = html.find(&step_src).unwrap();
+    assert!(step_at > html.find("Dimple and bake.").unwrap());
+    assert!(html.contains("<figcaption>Dimples</figcaption>"));
+
+    let gallery_at = html.find("<div class=\"gallery\">").unwrap();
+    let extra_src = format!("{}/photos/{}?size=thumb", share_url, extra["id"].as_str().unwrap());
+    assert!(html.find(&extra_src).unwrap() > gallery_at);
+    // The cover is the hero image, not repeated in the gallery
+    assert_eq!(html.matches("class=\"gallery-photo\"").count(), 1);
+
+    let (status, bytes, _) = send_binary_request(
+        &app,
+        "GET",
+        &format!("{}/photos/{}?size=full", share_url, extra["id"].as_str().unwrap()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(bytes, png(3));
+
+    // Photos of other recipes are not reachable through the link
+    let other = create_recipe(&app, &[], "Ciabatta").await;
+    let (_, other_photo) = upload(&app, &format!("/api/recipes/{}/photos", other), &[], png(4), &[]).await;
+    let (status, _, _) = send_binary_request(
+        &app,
+        "GET",
+        &format!("{}/photos/{}", share_url, other_photo["id"].as_str().unwrap()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_source_scans_kept_apart_from_gallery() {
+    let app = create_test_app(create_test_db().await).await;
+    let id = create_recipe(&app, &[], "Grandma's Scones").await;
+    let scans_uri = format!("/api/recipes/{}/source-scans", id);
+
+    // A scan is never the cover, even as the recipe's first image
+    let (status, scan) = upload(&app, &scans_uri, &[], png(1), &[("caption", "Grandma's card")]).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(scan["source_scan"], true);
+    assert_eq!(scan["is_cover"], false);
+    let recipe = get_recipe(&app, &id).await;
+    assert!(recipe.get("photo_filename").is_none());
+    assert!(recipe["photos"].as_array().unwrap().is_empty());
+
+    let (_, photo) = upload(&app, &format!("/api/recipes/{}/photos", id), &[], png(2), &[]).await;
+    assert_eq!(photo["is_cover"], true);
+    assert_eq!(photo["position"], 0);
+
+    let (status, _) = upload(&app, &scans_uri, &[], png(3), &[("cover", "true")]).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let scan_uri = format!("/api/recipes/{}/photos/{}", id, scan["id"].as_str().unwrap());
+    let (status, _) = send_request(&app, "PUT", &scan_uri, Some(json!({"cover": true}))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, body) = send_request(&app, "PUT", &scan_uri, Some(json!({"caption": "Card, 1962"}))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.unwrap()["caption"], "Card, 1962");
+
+    // Reordering covers the gallery only
+    let order = json!({"photo_ids": [photo["id"]]});
+    let (status, _) = send_request(&app, "PUT", &format!("/api/recipes/{}/photos/order", id), Some(order)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Edits keep the scan
+    let update = json!({"title": "Grandma's Cheese Scones", "steps": [{"instruction": "Rub in the butter."}]});
+    let (status, _) = send_request(&app, "PUT", &format!("/api/recipes/{}", id), Some(update)).await;
+    assert_eq!(status, StatusCode::OK);
+    let recipe = get_recipe(&app, &id).await;
+    assert_eq!(recipe["photo_filename"], photo["filename"]);
+    let scans = recipe["source_scans"].as_array().unwrap();
+    assert_eq!(scans.len(), 1);
+    assert_eq!(scans[0]["id"], scan["id"]);
+
+    let (status, bytes, _) = send_binary_request(&app, "GET", &scan_uri).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(bytes, png(1));
+
+    // Deleting the cover doesn't promote the scan
+    let (status, _) = send_request(&app, "DELETE", &format!("/api/recipes/{}/photo", id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let recipe = get_recipe(&app, &id).await;
+    assert!(recipe.get("photo_filename").is_none());
+    assert_eq!(recipe["source_scans"].as_array().unwrap().len(), 1);
+
+    // Share pages don't expose scans
+    let (_, link) = send_request(&app, "POST", &format!("/api/recipes/{}/share", id), None).await;
+    let share_url = link.unwrap()["url"].as_str().unwrap().to_string();
+    let (status, _, _) =
+        send_binary_request(&app, "GET", &format!("{}/photos/{}", share_url, scan["id"].as_str().unwrap())).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_migration_moves_existing_photos() {
+    let pool = SqlitePool::connect(":memory:").await.unwrap();
+    let gallery_version = 20260304000000;
+
+    // Apply migrations by hand so a pre-gallery photo can be added first
+    for migration in sqlx::migrate!().iter() {
+        if migration.version == gallery_version {
+            sqlx::query(
+                "INSERT INTO recipes (id, title, photo_filename, created_at, updated_at)
+                 VALUES ('r1', 'Old Recipe', 'r1.jpg', datetime('now'), datetime('now')),
+                        ('r2', 'No Photo', NULL, datetime('now'), datetime('now'))",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        sqlx::raw_sql(&migration.sql).execute(&pool).await.unwrap();
+    }
+
+    let photos = recipe_vault::db::queries::list_recipe_photos(&pool, "r1").await.unwrap();
+    assert_eq!(photos.len(), 1);
+    assert_eq!(photos[0].filename, "r1.jpg");
+    assert!(photos[0].is_cover);
+    assert_eq!(photos[0].position, 0);
+    assert!(recipe_vault::db::queries::list_recipe_photos(&pool, "r2").await.unwrap().is_empty());
+}
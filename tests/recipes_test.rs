@@ -17,7 +17,7 @@ async fn test_db() -> SqlitePool {
 #[tokio::test]
 async fn test_create_recipe_with_all_fields(#[future] test_db: SqlitePool) {
     let db = test_db.await;
-    let app = create_test_app(db);
+    let app = create_test_app(db).await;
 
     let recipe_json = json!({
         "title": "Chocolate Chip Cookies",
@@ -71,7 +71,7 @@ async fn test_create_recipe_with_all_fields(#[future] test_db: SqlitePool) {
 #[tokio::test]
 async fn test_create_recipe_minimal(#[future] test_db: SqlitePool) {
     let db = test_db.await;
-    let app = create_test_app(db);
+    let app = create_test_app(db).await;
 
     let recipe_json = json!({
         "title": "Quick Pasta"
@@ -93,7 +93,7 @@ async fn test_create_recipe_minimal(#[future] test_db: SqlitePool) {
 #[tokio::test]
 async fn test_list_recipes_ordered_by_title(#[future] test_db: SqlitePool) {
     let db = test_db.await;
-    let app = create_test_app(db);
+    let app = create_test_app(db).await;
 
     // Create multiple recipes
     send_request(&app, "POST", "/api/recipes", Some(json!({"title": "Zebra Cake"})))
@@ -118,7 +118,7 @@ async fn test_list_recipes_ordered_by_title(#[future] test_db: SqlitePool) {
 #[tokio::test]
 async fn test_get_recipe_with_ingredients_and_steps(#[future] test_db: SqlitePool) {
     let db = test_db.await;
-    let app = create_test_app(db);
+    let app = create_test_app(db).await;
 
     // Create recipe
     let (_, create_response) = send_request(
@@ -169,7 +169,7 @@ async fn test_get_recipe_with_ingredients_and_steps(#[future] test_db: SqlitePoo
 #[tokio::test]
 async fn test_get_nonexistent_recipe_returns_404(#[future] test_db: SqlitePool) {
     let db = test_db.await;
-    let app = create_test_app(db);
+    let app = create_test_app(db).await;
 
     let (status, response) = send_request(&app, "GET", "/api/recipes/invalid-uuid", None).await;
 
@@ -183,7 +183,7 @@ async fn test_get_nonexistent_recipe_returns_404(#[future] test_db: SqlitePool)
 #[tokio::test]
 async fn test_update_recipe_preserves_ingredients(#[future] test_db: SqlitePool) {
     let db = test_db.await;
-    let app = create_test_app(db);
+    let app = create_test_app(db).await;
 
     // Create recipe with ingredients
     let (_, create_response) = send_request(
@@ -221,7 +221,7 @@ async fn test_update_recipe_preserves_ingredients(#[future] test_db: SqlitePool)
 #[tokio::test]
 async fn test_update_recipe_replaces_ingredients(#[future] test_db: SqlitePool) {
     let db = test_db.await;
-    let app = create_test_app(db);
+    let app = create_test_app(db).await;
 
     // Create recipe
     let (_, create_response) = send_request(
@@ -265,7 +265,7 @@ async fn test_update_recipe_replaces_ingredients(#[future] test_db: SqlitePool)
 #[tokio::test]
 async fn test_delete_recipe_cascades(#[future] test_db: SqlitePool) {
     let db = test_db.await;
-    let app = create_test_app(db);
+    let app = create_test_app(db).await;
 
     // Create recipe with ingredients and steps
     let (_, create_response) = send_request(
@@ -298,7 +298,7 @@ async fn test_delete_recipe_cascades(#[future] test_db: SqlitePool) {
 #[tokio::test]
 async fn test_create_duplicate_title_returns_conflict(#[future] test_db: SqlitePool) {
     let db = test_db.await;
-    let app = create_test_app(db);
+    let app = create_test_app(db).await;
 
     // Create first recipe
     send_request(&app, "POST", "/api/recipes", Some(json!({"title": "Duplicate Recipe"})))
@@ -323,7 +323,7 @@ async fn test_create_duplicate_title_returns_conflict(#[future] test_db: SqliteP
 #[tokio::test]
 async fn test_create_recipe_empty_title_returns_validation_error(#[future] test_db: SqlitePool) {
     let db = test_db.await;
-    let app = create_test_app(db);
+    let app = create_test_app(db).await;
 
     let (status, response) = send_request(&app, "POST", "/api/recipes", Some(json!({"title": ""})))
         .await;
@@ -339,7 +339,7 @@ async fn test_create_recipe_empty_title_returns_validation_error(#[future] test_
 #[tokio::test]
 async fn test_ingredient_without_measurement(#[future] test_db: SqlitePool) {
     let db = test_db.await;
-    let app = create_test_app(db);
+    let app = create_test_app(db).await;
 
     let (status, response) = send_request(
         &app,
@@ -371,7 +371,7 @@ async fn test_ingredient_without_measurement(#[future] test_db: SqlitePool) {
 #[tokio::test]
 async fn test_step_with_timing(#[future] test_db: SqlitePool) {
     let db = test_db.await;
-    let app = create_test_app(db);
+    let app = create_test_app(db).await;
 
     let (status, response) = send_request(
         &app,
@@ -401,7 +401,7 @@ async fn test_step_with_timing(#[future] test_db: SqlitePool) {
 #[tokio::test]
 async fn test_step_with_temperature(#[future] test_db: SqlitePool) {
     let db = test_db.await;
-    let app = create_test_app(db);
+    let app = create_test_app(db).await;
 
     let (status, response) = send_request(
         &app,
@@ -433,7 +433,7 @@ async fn test_step_with_temperature(#[future] test_db: SqlitePool) {
 #[tokio::test]
 async fn test_create_recipe_tracks_author(#[future] test_db: SqlitePool) {
     let db = test_db.await;
-    let app = create_test_app(db);
+    let app = create_test_app(db).await;
 
     let (status, response) = send_request(
         &app,
@@ -453,7 +453,7 @@ async fn test_create_recipe_tracks_author(#[future] test_db: SqlitePool) {
 #[tokio::test]
 async fn test_update_recipe_tracks_author(#[future] test_db: SqlitePool) {
     let db = test_db.await;
-    let app = create_test_app(db);
+    let app = create_test_app(db).await;
 
     // Create recipe
     let (_, create_response) = send_request(
@@ -0,0 +1,277 @@
+mod common;
+
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, Method, StatusCode, Uri},
+    response::{IntoResponse, Response},
+};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::io::{Cursor, Read};
+use std::sync::{Arc, Mutex};
+
+use common::{
+    create_test_app_with_photo_storage, create_test_db, send_bytes_request, send_multipart_request, send_request,
+    send_request_with_headers,
+};
+use recipe_vault::config::{PhotoStorage, S3Config};
+
+const BUCKET: &str = "vault";
+const ACCESS_KEY: &str = "test-access";
+/// Objects returned per ListObjectsV2 page, small enough to exercise paging
+const PAGE_SIZE: usize = 2;
+
+type Objects = Arc<Mutex<BTreeMap<String, Vec<u8>>>>;
+
+/// A minimal stand-in for MinIO: path-style GET/PUT/DELETE/HEAD on objects and
+/// ListObjectsV2, with every request required to carry a SigV4 authorization
+/// and a matching payload hash
+async fn fake_s3(objects: State<Objects>, method: Method, uri: Uri, headers: HeaderMap, body: Bytes) -> Response {
+    let authorized = headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with(&format!("AWS4-HMAC-SHA256 Credential={}/", ACCESS_KEY)));
+    if !authorized {
+        return (StatusCode::FORBIDDEN, "<Error><Code>InvalidAccessKeyId</Code></Error>").into_response();
+    }
+    let payload_hash: String = Sha256::digest(&body).iter().map(|b| format!("{:02x}", b)).collect();
+    if headers.get("x-amz-content-sha256").and_then(|v| v.to_str().ok()) != Some(payload_hash.as_str()) {
+        return (StatusCode::BAD_REQUEST, "<Error><Code>XAmzContentSHA256Mismatch</Code></Error>").into_response();
+    }
+
+    let Some(key) = uri.path().strip_prefix(&format!("/{}/", BUCKET)) else {
+        return (StatusCode::NOT_FOUND, "<Error><Code>NoSuchBucket</Code></Error>").into_response();
+    };
+    let key = key.replace("%20", " ");
+    let mut objects = objects.lock().unwrap();
+
+    match method {
+        Method::GET if key.is_empty() => list_objects(&objects, uri.query().unwrap_or("")).into_response(),
+        Method::GET => match objects.get(&key) {
+            Some(bytes) => bytes.clone().into_response(),
+            None => (StatusCode::NOT_FOUND, "<Error><Code>NoSuchKey</Code></Error>").into_response(),
+        },
+        Method::HEAD if objects.contains_key(&key) => StatusCode::OK.into_response(),
+        Method::HEAD => StatusCode::NOT_FOUND.into_response(),
+        Method::PUT => {
+            objects.insert(key, body.to_vec());
+            StatusCode::OK.into_response()
+        }
+        Method::DELETE => {
+            objects.remove(&key);
+            StatusCode::NO_CONTENT.into_response()
+        }
+        _ => StatusCode::METHOD_NOT_ALLOWED.into_response(),
+    }
+}
+
+fn list_objects(objects: &BTreeMap<String, Vec<u8>>, query: &str) -> String {
+    let param = |name: &str| {
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix(&format!("{}=", name)))
+            .map(|value| value.replace("%2F", "/"))
+    };
+    let prefix = param("prefix").unwrap_or_default();
+    let start: usize = param("continuation-token").map(|t| t.parse().unwrap()).unwrap_or(0);
+
+    let matching: Vec<_> = objects.iter().filter(|(key, _)| key.starts_with(&prefix)).collect();
+    let page = matching.iter().skip(start).take(PAGE_SIZE);
+    let truncated = start + PAGE_SIZE < matching.len();
+
+    let mut xml = format!("<ListBucketResult><Prefix>{}</Prefix><IsTruncated>{}</IsTruncated>", prefix, truncated);
+    for (key, bytes) in page {
+        xml.push_str(&format!(
+            "<Contents><Key>{}</Key><LastModified>2020-01-01T00:00:00.000Z</LastModified><Size>{}</Size></Contents>",
+            key,
+            bytes.len()
+        ));
+    }
+    if truncated {
+        xml.push_str(&format!("<NextContinuationToken>{}</NextContinuationToken>", start + PAGE_SIZE));
+    }
+    xml.push_str("</ListBucketResult>");
+    xml
+}
+
+/// Start the fake on a random local port, returning its endpoint and objects
+async fn start_fake_s3() -> (String, Objects) {
+    let objects = Objects::default();
+    let app = axum::Router::new().fallback(fake_s3).with_state(objects.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    (format!("http://{}", addr), objects)
+}
+
+fn s3_storage(endpoint: &str) -> PhotoStorage {
+    PhotoStorage::S3(S3Config {
+        endpoint: endpoint.to_string(),
+        bucket: BUCKET.to_string(),
+        region: "us-east-1".to_string(),
+        access_key_id: ACCESS_KEY.to_string(),
+        secret_access_key: "test-secret".to_string(),
+        prefix: "photos/".to_string(),
+        path_style: true,
+    })
+}
+
+fn png(seed: u8) -> Vec<u8> {
+    let image = image::RgbImage::from_pixel(8, 8, image::Rgb([seed, 10, 10]));
+    let mut bytes = Vec::new();
+    image::DynamicImage::ImageRgb8(image)
+        .write_to(&mut Cursor::new(&mut bytes), image::ImageFormat::Png)
+        .unwrap();
+    bytes
+}
+
+async fn create_recipe(app: &axum::Router, title: &str) -> String {
+    let (status, body) = send_request(app, "POST", "/api/recipes", Some(json!({"title": title}))).await;
+    assert_eq!(status, StatusCode::CREATED);
+    body.unwrap()["id"].as_str().unwrap().to_string()
+}
+
+/// Upload a cover photo and return its filename
+async fn upload_cover(app: &axum::Router, recipe_id: &str, seed: u8) -> String {
+    let uri = format!("/api/recipes/{}/photo", recipe_id);
+    let (status, _) = send_multipart_request(app, "POST", &uri, "photo", png(seed), "dish.png", "image/png").await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, recipe) = send_request(app, "GET", &format!("/api/recipes/{}", recipe_id), None).await;
+    recipe.unwrap()["photo_filename"].as_str().unwrap().to_string()
+}
+
+fn keys(objects: &Objects) -> Vec<String> {
+    objects.lock().unwrap().keys().cloned().collect()
+}
+
+fn stem(filename: &str) -> &str {
+    filename.rsplit_once('.').unwrap().0
+}
+
+#[tokio::test]
+async fn test_photos_are_stored_in_the_bucket() {
+    let (endpoint, objects) = start_fake_s3().await;
+    let app = create_test_app_with_photo_storage(create_test_db().await, s3_storage(&endpoint)).await;
+    let recipe = create_recipe(&app, "Focaccia").await;
+
+    let filename = upload_cover(&app, &recipe, 1).await;
+    let stem = stem(&filename);
+    assert_eq!(
+        keys(&objects),
+        vec![
+            format!("photos/{}.medium.jpg", stem),
+            format!("photos/{}", filename),
+            format!("photos/{}.thumb.jpg", stem),
+        ]
+    );
+
+    let uri = format!("/api/recipes/{}/photo", recipe);
+    let (status, served, _) = send_bytes_request(&app, "GET", &uri, &[], vec![]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(served, objects.lock().unwrap()[&format!("photos/{}", filename)]);
+
+    // A missing derivative is regenerated into the bucket
+    objects.lock().unwrap().remove(&format!("photos/{}.thumb.jpg", stem));
+    let (status, thumb, headers) = send_bytes_request(&app, "GET", &format!("{}?size=thumb", uri), &[], vec![]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers["content-type"], "image/jpeg");
+    assert_eq!(thumb, objects.lock().unwrap()[&format!("photos/{}.thumb.jpg", stem)]);
+
+    // Public share pages read from the bucket too
+    let (_, link) = send_request(&app, "POST", &format!("/api/recipes/{}/share", recipe), None).await;
+    let share_url = link.unwrap()["url"].as_str().unwrap().to_string();
+    let (status, shared, _) = send_bytes_request(&app, "GET", &format!("{}/photo", share_url), &[], vec![]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(shared, served);
+
+    // So do exports
+    let (status, archive, _) = send_bytes_request(&app, "GET", "/api/export", &[], vec![]).await;
+    assert_eq!(status, StatusCode::OK);
+    let mut zip = zip::ZipArchive::new(Cursor::new(archive)).unwrap();
+    let mut exported = Vec::new();
+    zip.by_name(&format!("photos/{}", filename)).unwrap().read_to_end(&mut exported).unwrap();
+    assert_eq!(exported, served);
+
+    // Replacing the cover in another format swaps the objects
+    let uri = format!("/api/recipes/{}/photo", recipe);
+    let jpeg = {
+        let mut bytes = Vec::new();
+        image::DynamicImage::new_rgb8(8, 8)
+            .write_to(&mut Cursor::new(&mut bytes), image::ImageFormat::Jpeg)
+            .unwrap();
+        bytes
+    };
+    let (status, _) = send_multipart_request(&app, "POST", &uri, "photo", jpeg, "dish.jpg", "image/jpeg").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(keys(&objects).contains(&format!("photos/{}.jpg", stem)));
+    assert!(!keys(&objects).contains(&format!("photos/{}", filename)));
+
+    let (status, _) = send_request(&app, "DELETE", &format!("/api/recipes/{}", recipe), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert!(keys(&objects).is_empty(), "objects left behind: {:?}", keys(&objects));
+}
+
+#[tokio::test]
+async fn test_photo_check_scans_the_bucket() {
+    let (endpoint, objects) = start_fake_s3().await;
+    let app = create_test_app_with_photo_storage(create_test_db().await, s3_storage(&endpoint)).await;
+    let admin = &[("X-API-Key", "test-api-key")];
+
+    let kept = upload_cover(&app, &create_recipe(&app, "Bread").await, 1).await;
+    let lost_recipe = create_recipe(&app, "Soup").await;
+    let lost = upload_cover(&app, &lost_recipe, 2).await;
+    {
+        let mut objects = objects.lock().unwrap();
+        objects.remove(&format!("photos/{}", lost));
+        objects.insert("photos/stray.jpg".to_string(), b"x".to_vec());
+        // Outside the prefix or in a deeper folder: not ours
+        objects.insert("backups/old.jpg".to_string(), b"x".to_vec());
+        objects.insert("photos/archive/old.jpg".to_string(), b"x".to_vec());
+    }
+
+    let (status, report) =
+        send_request_with_headers(&app, "POST", "/api/admin/photos/repair?grace_minutes=0", None, admin).await;
+    assert_eq!(status, StatusCode::OK);
+    let report = report.unwrap();
+    // Two derivatives of the lost photo, plus the kept photo and its
+    // derivatives, plus the stray, across several listing pages
+    assert_eq!(report["files_scanned"], 6);
+    assert_eq!(report["orphan_files"][0]["filename"], "stray.jpg");
+    assert_eq!(report["missing_files"][0]["filename"], lost.as_str());
+    assert_eq!(report["repaired"]["orphan_files_removed"], 1);
+    assert_eq!(report["repaired"]["photo_rows_removed"], 1);
+
+    let remaining = keys(&objects);
+    assert!(remaining.contains(&format!("photos/{}", kept)));
+    assert!(remaining.contains(&"backups/old.jpg".to_string()));
+    assert!(!remaining.contains(&"photos/stray.jpg".to_string()));
+    assert!(!remaining.iter().any(|key| key.contains(stem(&lost))), "derivatives of the lost photo remain");
+
+    let (_, recipe) = send_request(&app, "GET", &format!("/api/recipes/{}", lost_recipe), None).await;
+    assert!(recipe.unwrap()["photos"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_storage_errors_fail_the_upload() {
+    let (endpoint, objects) = start_fake_s3().await;
+    let PhotoStorage::S3(config) = s3_storage(&endpoint) else { unreachable!() };
+    let storage = PhotoStorage::S3(S3Config {
+        access_key_id: "wrong-key".to_string(),
+        ..config
+    });
+    let app = create_test_app_with_photo_storage(create_test_db().await, storage).await;
+    let recipe = create_recipe(&app, "Bread").await;
+
+    let uri = format!("/api/recipes/{}/photo", recipe);
+    let (status, body) = send_multipart_request(&app, "POST", &uri, "photo", png(1), "dish.png", "image/png").await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(body.unwrap()["error"].as_str().unwrap().contains("InvalidAccessKeyId"));
+    assert!(keys(&objects).is_empty());
+
+    let (_, recipe) = send_request(&app, "GET", &format!("/api/recipes/{}", recipe), None).await;
+    assert!(recipe.unwrap()["photos"].as_array().unwrap().is_empty());
+}
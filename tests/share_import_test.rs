@@ -0,0 +1,262 @@
+mod common;
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use axum::{http::StatusCode, response::Html, routing::get, Router};
+use serde_json::{json, Value};
+
+use common::{
+    create_test_app_with_config, create_test_app_with_limits, create_test_db, create_two_family_config,
+    send_bytes_request, send_request_with_headers, send_text_request,
+};
+
+const ALICE: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "alice@example.com")];
+const BOB: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "bob@example.com")];
+const BOUNDARY: &str = "----ShareImportBoundary";
+
+fn png() -> Vec<u8> {
+    let image = image::RgbImage::from_pixel(8, 8, image::Rgb([200, 120, 40]));
+    let mut bytes = Vec::new();
+    image::DynamicImage::ImageRgb8(image)
+        .write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageFormat::Png)
+        .unwrap();
+    bytes
+}
+
+async fn add_photo(app: &axum::Router, id: &str) {
+    let mut body = format!(
+        "--{}\r\nContent-Disposition: form-data; name=\"caption\"\r\n\r\nCrusty\r\n--{}\r\nContent-Disposition: form-data; name=\"photo\"; filename=\"bread.png\"\r\nContent-Type: image/png\r\n\r\n",
+        BOUNDARY, BOUNDARY
+    )
+    .into_bytes();
+    body.extend_from_slice(&png());
+    body.extend_from_slice(format!("\r\n--{}--\r\n", BOUNDARY).as_bytes());
+
+    let content_type = format!("multipart/form-data; boundary={}", BOUNDARY);
+    let mut headers = ALICE.to_vec();
+    headers.push(("Content-Type", &content_type));
+    let (status, _, _) =
+        send_bytes_request(app, "POST", &format!("/api/recipes/{}/photos", id), &headers, body).await;
+    assert_eq!(status, StatusCode::CREATED);
+}
+
+async fn create_recipe(app: &axum::Router, title: &str) -> String {
+    let recipe = json!({
+        "title": title,
+        "tags": ["bread"],
+        "ingredients": [{"name": "flour", "quantity": 500, "unit": "g"}],
+        "steps": [{"instruction": "Mix and bake.", "duration_minutes": 40}]
+    });
+    let (status, body) = send_request_with_headers(app, "POST", "/api/recipes", Some(recipe), ALICE).await;
+    assert_eq!(status, StatusCode::CREATED);
+    body.unwrap()["id"].as_str().unwrap().to_string()
+}
+
+async fn share(app: &axum::Router, id: &str) -> String {
+    let (status, body) =
+        send_request_with_headers(app, "POST", &format!("/api/recipes/{}/share", id), None, ALICE).await;
+    assert_eq!(status, StatusCode::CREATED);
+    body.unwrap()["token"].as_str().unwrap().to_string()
+}
+
+async fn import(app: &axum::Router, token: &str, body: Option<Value>) -> (StatusCode, Value) {
+    let (status, body) =
+        send_request_with_headers(app, "POST", &format!("/api/import/share/{}", token), body, BOB).await;
+    (status, body.unwrap_or_default())
+}
+
+#[tokio::test]
+async fn test_save_shared_recipe_into_another_family() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+    let id = create_recipe(&app, "Soda Bread").await;
+    add_photo(&app, &id).await;
+    let token = share(&app, &id).await;
+
+    let (status, body) = import(&app, &token, None).await;
+    assert_eq!(status, StatusCode::CREATED);
+    // Titles are unique across the vault, so the copy is renamed
+    assert_eq!(body["renamed_from"], "Soda Bread");
+    let copy = &body["recipe"];
+    assert_eq!(copy["title"], "Soda Bread (2)");
+    assert_eq!(copy["created_by"], "bob@example.com");
+    assert_eq!(copy["shared_by"], "alice@example.com");
+    assert!(copy["source_url"].as_str().unwrap().ends_with(&format!("/share/{}", token)));
+    assert_eq!(copy["ingredients"][0]["name"], "flour");
+    assert_eq!(copy["steps"][0]["duration_minutes"], 40);
+    assert_eq!(copy["photos"][0]["caption"], "Crusty");
+    assert!(copy["photo_filename"].is_string());
+
+    // The copy belongs to Bob's family and is independent of the original
+    let copy_uri = format!("/api/recipes/{}", copy["id"].as_str().unwrap());
+    let (status, _) = send_request_with_headers(&app, "GET", &copy_uri, None, BOB).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send_request_with_headers(&app, "GET", &copy_uri, None, ALICE).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, original) =
+        send_request_with_headers(&app, "GET", &format!("/api/recipes/{}", id), None, ALICE).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_ne!(original.unwrap()["photo_filename"], copy["photo_filename"]);
+}
+
+#[tokio::test]
+async fn test_save_from_collection_and_revoked_links() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+    let id = create_recipe(&app, "Bagels").await;
+    let (_, link) = send_request_with_headers(&app, "POST", "/api/share-links", Some(json!({"tag": "bread"})), ALICE)
+        .await;
+    let token = link.unwrap()["token"].as_str().unwrap().to_string();
+
+    let (status, _) = import(&app, &token, None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = import(&app, &token, Some(json!({"recipe_id": "not-in-it"}))).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, body) = import(&app, &token, Some(json!({"recipe_id": id}))).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert!(body["recipe"]["source_url"]
+        .as_str()
+        .unwrap()
+        .ends_with(&format!("/share/{}/recipes/{}", token, id)));
+
+    let revoke_uri = format!("/api/share-links/{}", token);
+    send_request_with_headers(&app, "DELETE", &revoke_uri, None, ALICE).await;
+    let (status, _) = import(&app, &token, Some(json!({"recipe_id": id}))).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_saving_counts_as_a_view() {
+    let pool = create_test_db().await;
+    let app = create_test_app_with_config(pool.clone(), None, create_two_family_config()).await;
+    let id = create_recipe(&app, "Soda Bread").await;
+    let share_uri = format!("/api/recipes/{}/share", id);
+
+    // Saving uses up a one-view link, so its page is gone
+    let (_, link) = send_request_with_headers(&app, "POST", &share_uri, Some(json!({"max_views": 1})), ALICE).await;
+    let link = link.unwrap();
+    let (status, _) = import(&app, link["token"].as_str().unwrap(), None).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, _, _) = send_text_request(&app, "GET", link["url"].as_str().unwrap(), &[]).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // A single-use link can be saved by whoever just viewed it, but only
+    // in the grace period
+    let (_, link) = send_request_with_headers(&app, "POST", &share_uri, Some(json!({"single_use": true})), ALICE).await;
+    let link = link.unwrap();
+    let token = link["token"].as_str().unwrap();
+    let (status, _, _) = send_text_request(&app, "GET", link["url"].as_str().unwrap(), &[]).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = import(&app, token, None).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    sqlx::query("UPDATE share_links SET last_accessed_at = datetime('now', '-1 hour'), expires_at = NULL")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (status, _) = import(&app, token, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_save_from_another_instance() {
+    // The sister's vault, reachable over HTTP
+    let remote = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+    let id = create_recipe(&remote, "Barmbrack").await;
+    add_photo(&remote, &id).await;
+    let token = share(&remote, &id).await;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let origin = format!("http://{}", listener.local_addr().unwrap());
+    let server = remote.clone();
+    tokio::spawn(async move {
+        axum::serve(listener, server).await.unwrap();
+    });
+
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+    let (status, body) = import(&app, &token, Some(json!({"origin": origin}))).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", body);
+    let copy = &body["recipe"];
+    assert_eq!(copy["title"], "Barmbrack");
+    assert!(body.get("renamed_from").is_none());
+    assert_eq!(copy["source_url"], format!("{}/share/{}", origin, token));
+    assert_eq!(copy["shared_by"], "127.0.0.1");
+    assert_eq!(copy["ingredients"][0]["quantity"], 500.0);
+    assert_eq!(copy["steps"][0]["instruction"], "Mix and bake.");
+    assert!(copy["photo_filename"].is_string());
+
+    let (status, _) = import(&app, "no-such-token", Some(json!({"origin": origin}))).await;
+    assert_eq!(status, StatusCode::BAD_GATEWAY);
+    let (status, _) = import(&app, &token, Some(json!({"origin": "ftp://example.com"}))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_save_from_protected_link() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+    let id = create_recipe(&app, "Potato Farls").await;
+    let (_, link) = send_request_with_headers(
+        &app,
+        "POST",
+        &format!("/api/recipes/{}/share", id),
+        Some(json!({"passphrase": "griddle"})),
+        ALICE,
+    )
+    .await;
+    let token = link.unwrap()["token"].as_str().unwrap().to_string();
+
+    let (status, _) = import(&app, &token, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _) = import(&app, &token, Some(json!({"passphrase": "skillet"}))).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, body) = import(&app, &token, Some(json!({"passphrase": "griddle"}))).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(body["recipe"]["shared_by"], "alice@example.com");
+}
+
+#[tokio::test]
+async fn test_save_from_another_instance_stays_on_public_addresses() {
+    // A "Recipe Vault" whose share page points its photo at another host
+    let photo_fetches = Arc::new(AtomicUsize::new(0));
+    let counter = photo_fetches.clone();
+    let photo_listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let photo_url = format!("http://localhost:{}/photo.png", photo_listener.local_addr().unwrap().port());
+    let photo_app = Router::new().route(
+        "/photo.png",
+        get(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async { png() }
+        }),
+    );
+    tokio::spawn(async move { axum::serve(photo_listener, photo_app).await.unwrap() });
+
+    let page = format!(
+        r#"<html><head><script type="application/ld+json">
+        {{"@context":"https://schema.org","@type":"Recipe","name":"Brown Bread","image":"{}",
+          "recipeIngredient":["500 g wholemeal flour"],"recipeInstructions":"Mix and bake."}}
+        </script></head></html>"#,
+        photo_url
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let remote = Router::new().route("/share/:token", get(move || async move { Html(page) }));
+    tokio::spawn(async move { axum::serve(listener, remote).await.unwrap() });
+
+    let app = create_test_app_with_limits(create_test_db().await, |config| {
+        config.import_private_hosts = vec!["localhost".to_string()];
+    })
+    .await;
+
+    // The recipe is saved without the photo from elsewhere
+    let (status, body) = import(&app, "abc123", Some(json!({"origin": format!("http://localhost:{}", port)}))).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", body);
+    assert_eq!(body["recipe"]["title"], "Brown Bread");
+    assert!(body["recipe"]["photo_filename"].is_null());
+    assert_eq!(photo_fetches.load(Ordering::SeqCst), 0);
+
+    // An origin on a private address isn't fetched at all
+    let (status, body) = import(&app, "abc123", Some(json!({"origin": format!("http://127.0.0.1:{}", port)}))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].as_str().unwrap().contains("not a public address"));
+}
@@ -0,0 +1,351 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::{
+    create_test_app_with_config, create_test_db, create_two_family_config, send_bytes_request,
+    send_request_with_headers, send_text_request,
+};
+
+const ALICE: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "alice@example.com")];
+const BOB: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "bob@example.com")];
+
+async fn create_recipe(app: &axum::Router, headers: &[(&str, &str)], title: &str) -> String {
+    let recipe = json!({"title": title, "ingredients": [{"name": "flour"}]});
+    let (status, body) = send_request_with_headers(app, "POST", "/api/recipes", Some(recipe), headers).await;
+    assert_eq!(status, StatusCode::CREATED);
+    body.unwrap()["id"].as_str().unwrap().to_string()
+}
+
+async fn share(
+    app: &axum::Router,
+    id: &str,
+    options: Option<serde_json::Value>,
+) -> (StatusCode, serde_json::Value) {
+    let (status, body) =
+        send_request_with_headers(app, "POST", &format!("/api/recipes/{}/share", id), options, ALICE).await;
+    (status, body.unwrap_or_default())
+}
+
+#[tokio::test]
+async fn test_share_link_expiry_options() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+    let id = create_recipe(&app, ALICE, "Soda Bread").await;
+
+    let (status, default) = share(&app, &id, None).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert!(default["expires_at"].is_string());
+    assert!(default["max_views"].is_null());
+
+    let (status, forever) = share(&app, &id, Some(json!({"never_expires": true}))).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert!(forever["expires_at"].is_null());
+    let (status, _, _) = send_text_request(&app, "GET", forever["url"].as_str().unwrap(), &[]).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, week) = share(&app, &id, Some(json!({"expires_in_days": 7}))).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert!(week["expires_at"].as_str().unwrap() < default["expires_at"].as_str().unwrap());
+
+    for invalid in [json!({"expires_in_days": 0}), json!({"max_views": 0}), json!({"never_expires": true, "expires_in_days": 3})] {
+        let (status, _) = share(&app, &id, Some(invalid)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+async fn test_share_link_view_limit_and_stats() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+    let id = create_recipe(&app, ALICE, "Soda Bread").await;
+    let (_, link) = share(&app, &id, Some(json!({"max_views": 2}))).await;
+    let url = link["url"].as_str().unwrap();
+
+    for _ in 0..2 {
+        let (status, _, _) = send_text_request(&app, "GET", url, &[]).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    let (status, _, _) = send_text_request(&app, "GET", url, &[]).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, links) = send_request_with_headers(&app, "GET", "/api/share-links", None, ALICE).await;
+    assert_eq!(status, StatusCode::OK);
+    let links = links.unwrap();
+    let listed = &links.as_array().unwrap()[0];
+    assert_eq!(listed["token"], link["token"]);
+    assert_eq!(listed["recipe_title"], "Soda Bread");
+    assert_eq!(listed["view_count"], 2);
+    assert_eq!(listed["max_views"], 2);
+    assert!(listed["last_accessed_at"].is_string());
+    assert_eq!(listed["active"], false);
+}
+
+#[tokio::test]
+async fn test_revoke_share_link_within_family() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+    let id = create_recipe(&app, ALICE, "Soda Bread").await;
+    let (_, link) = share(&app, &id, None).await;
+    let token = link["token"].as_str().unwrap();
+    let revoke_uri = format!("/api/share-links/{}", token);
+
+    // Other families neither see nor revoke the link
+    let (_, links) = send_request_with_headers(&app, "GET", "/api/share-links", None, BOB).await;
+    assert!(links.unwrap().as_array().unwrap().is_empty());
+    let (status, _) = send_request_with_headers(&app, "DELETE", &revoke_uri, None, BOB).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send_request_with_headers(&app, "DELETE", &revoke_uri, None, ALICE).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _, _) = send_text_request(&app, "GET", link["url"].as_str().unwrap(), &[]).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send_request_with_headers(&app, "DELETE", &revoke_uri, None, ALICE).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_share_tag_collection() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+    let mut ids = Vec::new();
+    for title in ["Mince Pies", "Christmas Pudding"] {
+        let recipe = json!({"title": title, "tags": ["Christmas"], "ingredients": [{"name": "suet"}]});
+        let (_, body) = send_request_with_headers(&app, "POST", "/api/recipes", Some(recipe), ALICE).await;
+        ids.push(body.unwrap()["id"].as_str().unwrap().to_string());
+    }
+    let other = create_recipe(&app, ALICE, "Soda Bread").await;
+    // Another family's tagged recipe stays out
+    let bobs = json!({"title": "Bob's Trifle", "tags": ["christmas"], "ingredients": [{"name": "custard"}]});
+    send_request_with_headers(&app, "POST", "/api/recipes", Some(bobs), BOB).await;
+
+    let (status, link) =
+        send_request_with_headers(&app, "POST", "/api/share-links", Some(json!({"tag": "christmas"})), ALICE).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let link = link.unwrap();
+    assert_eq!(link["kind"], "tag");
+    assert_eq!(link["title"], "christmas");
+    assert_eq!(link["recipe_count"], 2);
+    let url = link["url"].as_str().unwrap();
+
+    let (status, index, _) = send_text_request(&app, "GET", url, &[]).await;
+    assert_eq!(status, StatusCode::OK);
+    // Ordered by title, linking to each recipe's page
+    let pudding = index.find(&format!("{}/recipes/{}", url, ids[1])).unwrap();
+    let pies = index.find(&format!("{}/recipes/{}", url, ids[0])).unwrap();
+    assert!(pudding < pies);
+    assert!(!index.contains("Trifle"));
+
+    let (status, page, _) = send_text_request(&app, "GET", &format!("{}/recipes/{}", url, ids[0]), &[]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(page.contains("Mince Pies"));
+    assert!(page.contains(&format!("href=\"{}\"", url)));
+
+    // Recipes outside the collection aren't reachable through it
+    let (status, _, _) = send_text_request(&app, "GET", &format!("{}/recipes/{}", url, other), &[]).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Listed for the family and revocable like recipe links
+    let (_, links) = send_request_with_headers(&app, "GET", "/api/share-links", None, ALICE).await;
+    let links = links.unwrap();
+    assert_eq!(links[0]["recipe_title"], "christmas");
+    assert!(links[0]["recipe_id"].is_null());
+    let (_, links) = send_request_with_headers(&app, "GET", "/api/share-links", None, BOB).await;
+    assert!(links.unwrap().as_array().unwrap().is_empty());
+
+    let revoke_uri = format!("/api/share-links/{}", link["token"].as_str().unwrap());
+    let (status, _) = send_request_with_headers(&app, "DELETE", &revoke_uri, None, ALICE).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _, _) = send_text_request(&app, "GET", &format!("{}/recipes/{}", url, ids[0]), &[]).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_share_meal_plan() {
+    let pool = create_test_db().await;
+    let app = create_test_app_with_config(pool.clone(), None, create_two_family_config()).await;
+    let roast = create_recipe(&app, ALICE, "Roast Lamb").await;
+    let greens = create_recipe(&app, ALICE, "Spring Greens").await;
+    let plan = json!({
+        "title": "Easter Lunch",
+        "max_views": 1,
+        "meal_plan": {"guest_count": 8, "recipes": [
+            {"recipe_id": roast, "role": "centrepiece"},
+            {"recipe_id": greens, "role": "side"}
+        ]}
+    });
+    let (status, link) = send_request_with_headers(&app, "POST", "/api/share-links", Some(plan), ALICE).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let link = link.unwrap();
+    assert_eq!(link["kind"], "meal_plan");
+    let url = link["url"].as_str().unwrap();
+
+    let (status, index, _) = send_text_request(&app, "GET", url, &[]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(index.contains("Easter Lunch"));
+    assert!(index.contains("for 8 guests"));
+    assert!(index.find("centrepiece").unwrap() < index.find("side</span>").unwrap());
+
+    // Only the index counts towards the view limit
+    let greens_url = format!("{}/recipes/{}", url, greens);
+    let (status, _, _) = send_text_request(&app, "GET", &greens_url, &[]).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _, _) = send_text_request(&app, "GET", url, &[]).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Once the views are used up, the recipes stay open only briefly
+    let (status, _, _) = send_text_request(&app, "GET", &greens_url, &[]).await;
+    assert_eq!(status, StatusCode::OK);
+    sqlx::query("UPDATE share_links SET last_accessed_at = datetime('now', '-1 hour')")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (status, page, _) = send_text_request(&app, "GET", &greens_url, &[]).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(!page.contains("Spring Greens"));
+}
+
+#[tokio::test]
+async fn test_share_collection_validation() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+    let alices = create_recipe(&app, ALICE, "Soda Bread").await;
+    let bobs = create_recipe(&app, BOB, "Bob's Bread").await;
+
+    let (status, _) = send_request_with_headers(
+        &app,
+        "POST",
+        "/api/share-links",
+        Some(json!({"title": "Breads", "recipe_ids": [alices, bobs]})),
+        ALICE,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    for invalid in [
+        json!({"recipe_ids": [alices]}),
+        json!({"title": "Both", "tag": "bread", "recipe_ids": [alices]}),
+        json!({"tag": "no-such-tag"}),
+        json!({"title": "Empty", "recipe_ids": []}),
+    ] {
+        let (status, _) = send_request_with_headers(&app, "POST", "/api/share-links", Some(invalid), ALICE).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
+
+/// Post a passphrase to a share page; returns the status and any unlock cookie
+async fn unlock(app: &axum::Router, url: &str, passphrase: &str) -> (StatusCode, Option<String>) {
+    let body = format!("passphrase={}", passphrase).into_bytes();
+    let headers = [("Content-Type", "application/x-www-form-urlencoded")];
+    let (status, _, response_headers) = send_bytes_request(app, "POST", url, &headers, body).await;
+    let cookie = response_headers
+        .get("set-cookie")
+        .map(|c| c.to_str().unwrap().split(';').next().unwrap().to_string());
+    (status, cookie)
+}
+
+#[tokio::test]
+async fn test_passphrase_protected_link() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+    let id = create_recipe(&app, ALICE, "Soda Bread").await;
+    let (status, link) = share(&app, &id, Some(json!({"passphrase": "buttermilk"}))).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(link["protected"], true);
+    let url = link["url"].as_str().unwrap();
+
+    // Locked: a form instead of the recipe, and no photo
+    let (status, page, _) = send_text_request(&app, "GET", url, &[]).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert!(page.contains("name=\"passphrase\""));
+    assert!(!page.contains("Soda Bread"));
+
+    let (status, cookie) = unlock(&app, url, "sourdough").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert!(cookie.is_none());
+
+    let (status, cookie) = unlock(&app, url, "buttermilk").await;
+    assert_eq!(status, StatusCode::SEE_OTHER);
+    let cookie = cookie.unwrap();
+    let (status, page, _) = send_text_request(&app, "GET", url, &[("Cookie", &cookie)]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(page.contains("Soda Bread"));
+
+    // A made-up cookie doesn't unlock it
+    let forged = format!("share_{}=00", link["token"].as_str().unwrap());
+    let (status, _, _) = send_text_request(&app, "GET", url, &[("Cookie", &forged)]).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // The hash is never listed
+    let (_, links) = send_request_with_headers(&app, "GET", "/api/share-links", None, ALICE).await;
+    let listed = &links.unwrap()[0];
+    assert_eq!(listed["protected"], true);
+    assert!(listed.get("passphrase_hash").is_none());
+}
+
+#[tokio::test]
+async fn test_passphrase_guesses_are_rate_limited() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+    let id = create_recipe(&app, ALICE, "Soda Bread").await;
+    let (_, link) = share(&app, &id, Some(json!({"passphrase": "buttermilk"}))).await;
+    let url = link["url"].as_str().unwrap();
+
+    for _ in 0..5 {
+        let (status, _) = unlock(&app, url, "wrong").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+    // Even the right passphrase is refused while locked out
+    let (status, cookie) = unlock(&app, url, "buttermilk").await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert!(cookie.is_none());
+
+    // Other links are unaffected
+    let (_, other) = share(&app, &id, Some(json!({"passphrase": "buttermilk"}))).await;
+    let (status, _) = unlock(&app, other["url"].as_str().unwrap(), "buttermilk").await;
+    assert_eq!(status, StatusCode::SEE_OTHER);
+}
+
+#[tokio::test]
+async fn test_parallel_passphrase_guesses_are_rate_limited() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+    let id = create_recipe(&app, ALICE, "Soda Bread").await;
+    let (_, link) = share(&app, &id, Some(json!({"passphrase": "buttermilk"}))).await;
+    let url = link["url"].as_str().unwrap();
+
+    // Guesses in flight at once still only get five tries
+    let guesses = (0..20).map(|i| {
+        let app = app.clone();
+        let url = url.to_string();
+        async move { unlock(&app, &url, &format!("wrong {}", i)).await.0 }
+    });
+    let statuses = futures::future::join_all(guesses).await;
+    let checked = statuses.iter().filter(|status| **status == StatusCode::UNAUTHORIZED).count();
+    let refused = statuses.iter().filter(|status| **status == StatusCode::TOO_MANY_REQUESTS).count();
+    assert_eq!((checked, refused), (5, 15));
+
+    // Right passphrases don't use up tries
+    let (_, other) = share(&app, &id, Some(json!({"passphrase": "buttermilk"}))).await;
+    for _ in 0..8 {
+        let (status, _) = unlock(&app, other["url"].as_str().unwrap(), "buttermilk").await;
+        assert_eq!(status, StatusCode::SEE_OTHER);
+    }
+}
+
+#[tokio::test]
+async fn test_single_use_link() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+    let id = create_recipe(&app, ALICE, "Soda Bread").await;
+    let (status, link) = share(&app, &id, Some(json!({"single_use": true, "never_expires": true}))).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(link["single_use"], true);
+    let url = link["url"].as_str().unwrap();
+
+    let (status, _, _) = send_text_request(&app, "GET", url, &[]).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _, _) = send_text_request(&app, "GET", url, &[]).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Viewing starts the grace period for photos
+    let (_, links) = send_request_with_headers(&app, "GET", "/api/share-links", None, ALICE).await;
+    let listed = &links.unwrap()[0];
+    assert!(listed["expires_at"].is_string());
+    assert_eq!(listed["active"], false);
+
+    let (status, _) = share(&app, &id, Some(json!({"single_use": true, "max_views": 2}))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::{create_test_app, create_test_db, send_multipart_request, send_request, send_text_request};
+use recipe_vault::formats::{extract_recipe, StructuredDataSource};
+
+/// Minimal 1x1 PNG
+const PNG: &[u8] = &[
+    0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D, 0x49, 0x48, 0x44, 0x52,
+    0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1F, 0x15, 0xC4,
+    0x89, 0x00, 0x00, 0x00, 0x0A, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9C, 0x63, 0x00, 0x01, 0x00, 0x00,
+    0x05, 0x00, 0x01, 0x0D, 0x0A, 0x2D, 0xB4, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4E, 0x44, 0xAE,
+    0x42, 0x60, 0x82,
+];
+
+fn sample_recipe(title: &str) -> serde_json::Value {
+    json!({
+        "title": title,
+        "description": "Eggs poached in a spiced tomato & pepper sauce.",
+        "prep_time_minutes": 10,
+        "cook_time_minutes": 95,
+        "servings": 4,
+        "difficulty": 2,
+        "ingredients": [
+            {"name": "olive oil", "quantity": 2.0, "unit": "tbsp"},
+            {"name": "onion", "quantity": 1.0, "notes": "finely sliced"},
+            {"name": "chopped tomatoes", "quantity": 800.0, "unit": "g"},
+            {"name": "ground cumin", "quantity": 0.5, "unit": "tsp"},
+            {"name": "eggs", "quantity": 6.0},
+            {"name": "Fresh coriander", "notes": "to serve"}
+        ],
+        "steps": [
+            {"instruction": "Soften the onion in the oil.", "duration_minutes": 10},
+            {"instruction": "Add the tomatoes and cumin and simmer until thick."},
+            {"instruction": "Make wells, crack in the eggs, cover and cook until just set.", "duration_minutes": 8}
+        ]
+    })
+}
+
+/// Create the recipe and a share link, returning (recipe, share url)
+async fn create_shared_recipe(app: &axum::Router, title: &str, with_photo: bool) -> (serde_json::Value, String) {
+    let (status, recipe) = send_request(app, "POST", "/api/recipes", Some(sample_recipe(title))).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let recipe = recipe.unwrap();
+    let id = recipe["id"].as_str().unwrap();
+
+    if with_photo {
+        let (status, _) = send_multipart_request(
+            app,
+            "POST",
+            &format!("/api/recipes/{}/photo", id),
+            "photo",
+            PNG.to_vec(),
+            "dish.png",
+            "image/png",
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (status, link) = send_request(app, "POST", &format!("/api/recipes/{}/share", id), None).await;
+    assert_eq!(status, StatusCode::CREATED);
+    (recipe, link.unwrap()["url"].as_str().unwrap().to_string())
+}
+
+#[tokio::test]
+async fn test_share_page_json_ld_round_trips() {
+    let app = create_test_app(create_test_db().await).await;
+    let (original, url) = create_shared_recipe(&app, "Shakshuka", true).await;
+
+    let (status, html, _) = send_text_request(&app, "GET", &url, &[("Host", "recipes.example.com")]).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let extracted = extract_recipe(&html).expect("share page should carry a recipe");
+    assert_eq!(extracted.source, StructuredDataSource::JsonLd);
+    let imported = extracted.recipe;
+
+    assert_eq!(imported.title, original["title"]);
+    assert_eq!(imported.description.as_deref(), original["description"].as_str());
+    assert_eq!(imported.prep_time_minutes, Some(10));
+    assert_eq!(imported.cook_time_minutes, Some(95));
+    assert_eq!(imported.servings, Some(4));
+    assert_eq!(
+        extracted.image_url,
+        Some(format!("https://recipes.example.com{}/photo", url))
+    );
+
+    let ingredients = original["ingredients"].as_array().unwrap();
+    assert_eq!(imported.ingredients.len(), ingredients.len());
+    for (got, want) in imported.ingredients.iter().zip(ingredients) {
+        assert_eq!(got.name, want["name"]);
+        assert_eq!(got.quantity, want["quantity"].as_f64());
+        assert_eq!(got.unit.as_deref(), want["unit"].as_str());
+        assert_eq!(got.notes.as_deref(), want["notes"].as_str());
+    }
+
+    let steps = original["steps"].as_array().unwrap();
+    assert_eq!(imported.steps.len(), steps.len());
+    for (got, want) in imported.steps.iter().zip(steps) {
+        assert_eq!(got.instruction, want["instruction"]);
+        assert_eq!(got.duration_minutes.map(i64::from), want["duration_minutes"].as_i64());
+    }
+}
+
+#[tokio::test]
+async fn test_share_page_social_meta_tags() {
+    let app = create_test_app(create_test_db().await).await;
+    let (_, url) = create_shared_recipe(&app, "Shakshuka </script><b>", true).await;
+
+    let (_, html, _) = send_text_request(
+        &app,
+        "GET",
+        &url,
+        &[("Host", "recipes.example.com"), ("X-Forwarded-Proto", "https")],
+    )
+    .await;
+
+    let page_url = format!("https://recipes.example.com{}", url);
+    assert!(html.contains(&format!("<meta property=\"og:url\" content=\"{}\">", page_url)));
+    assert!(html.contains(&format!("<meta property=\"og:image\" content=\"{}/photo\">", page_url)));
+    assert!(html.contains("<meta name=\"twitter:card\" content=\"summary_large_image\">"));
+    assert!(html.contains(&format!("<meta name=\"twitter:image\" content=\"{}/photo\">", page_url)));
+    assert!(html.contains(
+        "<meta property=\"og:description\" content=\"Eggs poached in a spiced tomato &amp; pepper sauce.\">"
+    ));
+
+    // The title can't break out of the JSON-LD or clipboard script elements
+    assert_eq!(html.matches("</script>").count(), 2);
+    assert!(!html.contains("<b>"));
+}
+
+#[tokio::test]
+async fn test_share_page_without_photo() {
+    let app = create_test_app(create_test_db().await).await;
+    let (_, url) = create_shared_recipe(&app, "Shakshuka", false).await;
+
+    let (_, html, _) = send_text_request(&app, "GET", &url, &[("Host", "localhost:3000")]).await;
+
+    assert!(html.contains(&format!("<meta property=\"og:url\" content=\"http://localhost:3000{}\">", url)));
+    assert!(html.contains("<meta name=\"twitter:card\" content=\"summary\">"));
+    assert!(!html.contains("og:image"));
+    assert!(extract_recipe(&html).unwrap().image_url.is_none());
+}
@@ -0,0 +1,224 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::{
+    create_test_app_with_config, create_test_db, create_two_family_config,
+    send_request_with_headers, send_text_request,
+};
+
+const ALICE: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "alice@example.com")];
+const BOB: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "bob@example.com")];
+
+async fn create_recipe(app: &axum::Router, headers: &[(&str, &str)], body: serde_json::Value) -> String {
+    let (status, response) =
+        send_request_with_headers(app, "POST", "/api/recipes", Some(body), headers).await;
+    assert_eq!(status, StatusCode::CREATED);
+    response.unwrap()["id"].as_str().unwrap().to_string()
+}
+
+async fn seed_two_recipes(app: &axum::Router) -> (String, String) {
+    let curry = create_recipe(
+        app,
+        ALICE,
+        json!({
+            "title": "Curry",
+            "difficulty": 2,
+            "ingredients": [
+                {"name": "Onion", "quantity": 1.0},
+                {"name": "Chicken thighs", "quantity": 500.0, "unit": "g"},
+                {"name": "Rice", "quantity": 200.0, "unit": "g"}
+            ]
+        }),
+    )
+    .await;
+    let pilaf = create_recipe(
+        app,
+        ALICE,
+        json!({
+            "title": "Pilaf",
+            "difficulty": 1,
+            "ingredients": [
+                {"name": "onion", "quantity": 2.0},
+                {"name": "rice", "quantity": 150.0, "unit": "g"}
+            ]
+        }),
+    )
+    .await;
+    (curry, pilaf)
+}
+
+#[tokio::test]
+async fn test_shopping_list_combines_ingredients() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+    let (curry, pilaf) = seed_two_recipes(&app).await;
+
+    let (status, response) = send_request_with_headers(
+        &app,
+        "GET",
+        &format!("/api/shopping-list?recipe_ids={},{}", curry, pilaf),
+        None,
+        ALICE,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    let list = response.unwrap();
+    let items = list["items"].as_array().unwrap();
+    assert_eq!(items.len(), 3);
+    // Default walking order: produce, meat, pantry
+    assert_eq!(items[0]["name"], "Onion");
+    assert_eq!(items[0]["quantity"], 3.0);
+    assert_eq!(items[1]["aisle"], "Meat & Fish");
+    assert_eq!(items[2]["name"], "Rice");
+    assert_eq!(items[2]["quantity"], 350.0);
+}
+
+#[tokio::test]
+async fn test_shopping_list_export_formats() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+    let (curry, pilaf) = seed_two_recipes(&app).await;
+    let base = format!("/api/shopping-list?recipe_ids={},{}", curry, pilaf);
+
+    let (status, body, content_type) =
+        send_text_request(&app, "GET", &format!("{}&format=markdown", base), ALICE).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(content_type.unwrap().starts_with("text/markdown"));
+    assert!(body.contains("- [ ] 3 Onion"));
+
+    let (status, body, content_type) =
+        send_text_request(&app, "GET", &format!("{}&format=csv", base), ALICE).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(content_type.unwrap().starts_with("text/csv"));
+    assert!(body.starts_with("aisle,item,quantity,unit,notes,recipes\r\n"));
+    assert!(body.contains("Pantry,Rice,350,g,,Curry; Pilaf"));
+
+    let (status, body, _) = send_text_request(&app, "GET", &format!("{}&format=text", base), ALICE).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("PRODUCE\n- 3 Onion"));
+
+    let (status, body, content_type) =
+        send_text_request(&app, "GET", &format!("{}&format=html", base), ALICE).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(content_type.unwrap().starts_with("text/html"));
+    assert!(body.contains("<input type=\"checkbox\"> 500 g Chicken thighs"));
+
+    let (status, _, _) = send_text_request(&app, "GET", &format!("{}&format=pdf", base), ALICE).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_shopping_list_respects_family_tenancy() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+    let (curry, _) = seed_two_recipes(&app).await;
+
+    let (status, _) = send_request_with_headers(
+        &app,
+        "GET",
+        &format!("/api/shopping-list?recipe_ids={}", curry),
+        None,
+        BOB,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_family_aisle_order() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+    let (curry, _) = seed_two_recipes(&app).await;
+
+    let (status, response) = send_request_with_headers(
+        &app,
+        "PUT",
+        "/api/shopping-list/aisles",
+        Some(json!({
+            "aisles": ["Pantry", "Meat & Fish", "Produce"],
+            "ingredient_aisles": {"Onion": "Pantry"}
+        })),
+        ALICE,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response.unwrap()["aisles"][0], "Pantry");
+
+    let (_, response) = send_request_with_headers(
+        &app,
+        "GET",
+        &format!("/api/shopping-list?recipe_ids={}", curry),
+        None,
+        ALICE,
+    )
+    .await;
+    let names: Vec<String> = response.unwrap()["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|i| i["name"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(names, vec!["Onion", "Rice", "Chicken thighs"]);
+
+    // Another family still sees the default order
+    let (_, response) =
+        send_request_with_headers(&app, "GET", "/api/shopping-list/aisles", None, BOB).await;
+    assert_eq!(response.unwrap()["aisles"][0], "Produce");
+
+    // Unknown aisle in an override is rejected
+    let (status, _) = send_request_with_headers(
+        &app,
+        "PUT",
+        "/api/shopping-list/aisles",
+        Some(json!({"aisles": ["Produce"], "ingredient_aisles": {"tofu": "Chilled"}})),
+        ALICE,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_public_shopping_list_link() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+    let (curry, pilaf) = seed_two_recipes(&app).await;
+
+    let (status, response) = send_request_with_headers(
+        &app,
+        "POST",
+        "/api/shopping-list/share",
+        Some(json!({"recipe_ids": [curry, pilaf]})),
+        ALICE,
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let url = response.unwrap()["url"].as_str().unwrap().to_string();
+    assert!(url.starts_with("/share/shopping/"));
+
+    // No auth headers: a non-member shopper can view the list
+    let (status, body, content_type) = send_text_request(&app, "GET", &url, &[]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(content_type.unwrap().starts_with("text/html"));
+    assert!(body.contains("3 Onion"));
+
+    let (status, body, _) = send_text_request(&app, "GET", &format!("{}?format=text", url), &[]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("Shopping list for: Curry, Pilaf"));
+
+    let (status, _, _) = send_text_request(&app, "GET", "/share/shopping/doesnotexist", &[]).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_cannot_share_other_family_recipes() {
+    let app = create_test_app_with_config(create_test_db().await, None, create_two_family_config()).await;
+    let (curry, _) = seed_two_recipes(&app).await;
+
+    let (status, _) = send_request_with_headers(
+        &app,
+        "POST",
+        "/api/shopping-list/share",
+        Some(json!({"recipe_ids": [curry]})),
+        BOB,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
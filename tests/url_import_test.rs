@@ -0,0 +1,262 @@
+mod common;
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use axum::{
+    http::StatusCode,
+    response::{Html, Redirect},
+    routing::get,
+};
+use serde_json::json;
+
+use common::{create_test_app, create_test_app_with_limits, create_test_db, send_request, send_request_with_headers};
+
+const ALICE: &[(&str, &str)] = &[("Cf-Access-Authenticated-User-Email", "alice@example.com")];
+use recipe_vault::formats::{extract_recipe, StructuredDataSource};
+
+fn fixture(name: &str) -> String {
+    std::fs::read_to_string(format!("test_fixtures/html/{}", name)).expect("fixture exists")
+}
+
+/// Serve the HTML fixtures on a random local port, returning the base URL
+async fn serve_fixtures() -> String {
+    let app = axum::Router::new().nest_service("/", tower_http::services::ServeDir::new("test_fixtures/html"));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    format!("http://{}", addr)
+}
+
+#[test]
+fn test_json_ld_graph_with_sections() {
+    let extracted = extract_recipe(&fixture("jsonld_graph.html")).unwrap();
+    let recipe = &extracted.recipe;
+
+    assert_eq!(extracted.source, StructuredDataSource::JsonLd);
+    assert_eq!(recipe.title, "Lemon Drizzle Cake");
+    assert_eq!(
+        recipe.description.as_deref(),
+        Some("A light sponge soaked in a tangy lemon & sugar syrup.")
+    );
+    assert_eq!(recipe.prep_time_minutes, Some(20));
+    assert_eq!(recipe.cook_time_minutes, Some(45));
+    assert_eq!(recipe.servings, Some(8));
+    assert_eq!(extracted.image_url.as_deref(), Some("/images/lemon-drizzle-1x1.jpg"));
+
+    assert_eq!(recipe.ingredients.len(), 6);
+    let butter = &recipe.ingredients[0];
+    assert_eq!(butter.name, "unsalted butter");
+    assert_eq!(butter.quantity, Some(225.0));
+    assert_eq!(butter.unit.as_deref(), Some("g"));
+    assert_eq!(butter.notes.as_deref(), Some("softened"));
+    assert_eq!(recipe.ingredients[3].quantity, Some(1.5));
+    assert_eq!(recipe.ingredients[4].notes.as_deref(), Some("zested"));
+
+    assert_eq!(recipe.steps.len(), 6);
+    assert_eq!(recipe.steps[0].instruction, "For the cake: Heat the oven to 180C. Line a loaf tin.");
+    assert_eq!(recipe.steps[1].instruction, "Beat the butter and sugar until pale and fluffy.");
+    assert_eq!(recipe.steps[4].instruction, "For the drizzle: Mix the lemon juice with the icing sugar.");
+}
+
+#[test]
+fn test_json_ld_text_instructions_and_total_time() {
+    let extracted = extract_recipe(&fixture("jsonld_simple.html")).unwrap();
+    let recipe = &extracted.recipe;
+
+    assert_eq!(recipe.title, "Weeknight Chilli");
+    // Only totalTime is published
+    assert_eq!(recipe.prep_time_minutes, None);
+    assert_eq!(recipe.cook_time_minutes, Some(60));
+    assert_eq!(recipe.servings, Some(4));
+    assert_eq!(recipe.steps.len(), 4);
+    assert_eq!(recipe.ingredients[3].name, "garlic");
+    assert_eq!(recipe.ingredients[3].quantity, Some(2.0));
+    assert_eq!(recipe.ingredients[5].quantity, None);
+}
+
+#[test]
+fn test_microdata() {
+    let extracted = extract_recipe(&fixture("microdata.html")).unwrap();
+    let recipe = &extracted.recipe;
+
+    assert_eq!(extracted.source, StructuredDataSource::Microdata);
+    // The author's itemprop="name" belongs to the nested Person, not the recipe
+    assert_eq!(recipe.title, "Buttermilk Pancakes");
+    assert_eq!(recipe.prep_time_minutes, Some(10));
+    assert_eq!(recipe.cook_time_minutes, Some(15));
+    assert_eq!(recipe.servings, Some(12));
+    assert_eq!(extracted.image_url.as_deref(), Some("pancakes.jpg"));
+    assert_eq!(recipe.ingredients.len(), 4);
+    assert_eq!(recipe.ingredients[1].unit.as_deref(), Some("tbsp"));
+    assert_eq!(recipe.steps.len(), 3);
+}
+
+#[test]
+fn test_no_structured_data() {
+    assert!(extract_recipe(&fixture("no_structured_data.html")).is_none());
+}
+
+#[tokio::test]
+async fn test_import_url_saves_recipe() {
+    let base = serve_fixtures().await;
+    let app = create_test_app(create_test_db().await).await;
+    let url = format!("{}/jsonld_graph.html", base);
+
+    let (status, body) = send_request(&app, "POST", "/api/import/url", Some(json!({"url": url}))).await;
+
+    assert_eq!(status, StatusCode::CREATED);
+    let body = body.unwrap();
+    assert_eq!(body["source"], "json_ld");
+    assert_eq!(body["saved"], true);
+    assert_eq!(body["image_url"], format!("{}/images/lemon-drizzle-1x1.jpg", base));
+    assert_eq!(body["recipe"]["source_url"], url);
+
+    let id = body["recipe"]["id"].as_str().unwrap();
+    let (status, recipe) = send_request(&app, "GET", &format!("/api/recipes/{}", id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let recipe = recipe.unwrap();
+    assert_eq!(recipe["title"], "Lemon Drizzle Cake");
+    assert_eq!(recipe["steps"].as_array().unwrap().len(), 6);
+    assert_eq!(recipe["source_url"], url);
+}
+
+#[tokio::test]
+async fn test_import_url_preview_does_not_save() {
+    let base = serve_fixtures().await;
+    let app = create_test_app(create_test_db().await).await;
+
+    let (status, body) = send_request(
+        &app,
+        "POST",
+        "/api/import/url",
+        Some(json!({"url": format!("{}/microdata.html", base), "save": false})),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    let body = body.unwrap();
+    assert_eq!(body["source"], "microdata");
+    assert_eq!(body["saved"], false);
+    assert_eq!(body["recipe"]["title"], "Buttermilk Pancakes");
+    assert!(body["recipe"].get("id").is_none());
+
+    let (_, list) = send_request(&app, "GET", "/api/recipes", None).await;
+    assert!(list.unwrap().as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_import_url_falls_back_to_llm() {
+    let base = serve_fixtures().await;
+    let app = create_test_app(create_test_db().await).await;
+
+    let (status, body) = send_request(
+        &app,
+        "POST",
+        "/api/import/url",
+        Some(json!({"url": format!("{}/no_structured_data.html", base)})),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::CREATED);
+    let body = body.unwrap();
+    assert_eq!(body["source"], "llm");
+    // Mock LLM response
+    assert_eq!(body["recipe"]["title"], "Mock Extracted Recipe");
+}
+
+#[tokio::test]
+async fn test_import_url_errors() {
+    let base = serve_fixtures().await;
+    let app = create_test_app(create_test_db().await).await;
+
+    let (status, _) =
+        send_request(&app, "POST", "/api/import/url", Some(json!({"url": "ftp://example.com/x"}))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, body) = send_request(
+        &app,
+        "POST",
+        "/api/import/url",
+        Some(json!({"url": format!("{}/missing.html", base)})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_GATEWAY);
+    assert_eq!(body.unwrap()["code"], "UPSTREAM_ERROR");
+}
+
+/// A page at /page that counts its visits, and /go redirecting to it by
+/// address; returns the port and the visit counter
+async fn serve_redirect() -> (u16, Arc<AtomicUsize>) {
+    let visits = Arc::new(AtomicUsize::new(0));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let counter = visits.clone();
+    let app = axum::Router::new()
+        .route(
+            "/page",
+            get(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async { Html(fixture("jsonld_simple.html")) }
+            }),
+        )
+        .route("/go", get(move || async move { Redirect::to(&format!("http://127.0.0.1:{}/page", port)) }));
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    (port, visits)
+}
+
+#[tokio::test]
+async fn test_import_url_refuses_private_addresses() {
+    let (port, visits) = serve_redirect().await;
+    // Only the name "localhost" may be fetched from; its address may not
+    let app = create_test_app_with_limits(create_test_db().await, |config| {
+        config.import_private_hosts = vec!["localhost".to_string()];
+    })
+    .await;
+    let import = |url: String| {
+        let app = app.clone();
+        async move { send_request_with_headers(&app, "POST", "/api/import/url", Some(json!({"url": url})), ALICE).await }
+    };
+
+    for url in [
+        format!("http://127.0.0.1:{}/page", port),
+        format!("http://[::ffff:127.0.0.1]:{}/page", port),
+        "http://169.254.169.254/latest/meta-data/".to_string(),
+    ] {
+        let (status, body) = import(url.clone()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", url);
+        assert!(body.unwrap()["error"].as_str().unwrap().contains("not a public address"));
+    }
+
+    // A redirect to a private address is refused too
+    let (status, body) = import(format!("http://localhost:{}/go", port)).await;
+    assert_eq!(status, StatusCode::BAD_GATEWAY);
+    assert!(body.unwrap()["error"].as_str().unwrap().contains("not a public address"));
+    assert_eq!(visits.load(Ordering::SeqCst), 0);
+
+    // Allowed hosts are fetched
+    let (status, _) = import(format!("http://localhost:{}/page", port)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(visits.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_import_url_refuses_names_of_private_addresses() {
+    let (port, visits) = serve_redirect().await;
+    let app = create_test_app_with_limits(create_test_db().await, |config| {
+        config.import_private_hosts = Vec::new();
+    })
+    .await;
+
+    let url = format!("http://localhost:{}/page", port);
+    let (status, body) = send_request_with_headers(&app, "POST", "/api/import/url", Some(json!({"url": url})), ALICE).await;
+    assert_eq!(status, StatusCode::BAD_GATEWAY);
+    assert!(body.unwrap()["error"].as_str().unwrap().contains("public address"));
+    assert_eq!(visits.load(Ordering::SeqCst), 0);
+}